@@ -103,7 +103,7 @@ async fn main() -> Result<()> {
         command: "up{job=\"kubernetes-pods\"}".to_string(),
     };
     
-    match promql.call(promql_args).await {
+    match promql.call(promql_args.into()).await {
         Ok(result) => {
             println!("\nPromQL execution:");
             println!("  Success: {}", result.success);
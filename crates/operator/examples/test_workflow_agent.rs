@@ -94,6 +94,7 @@ fn create_test_workflow() -> Workflow {
                     api_key_secret: None,
                 },
                 environment: HashMap::new(),
+                agent_config_map: None,
             },
             steps: vec![
                 Step {
@@ -110,10 +111,33 @@ fn create_test_workflow() -> Workflow {
                     approval_required: false,
                     condition: None,
                     agent: None,
+                    then_steps: vec![],
+                    else_steps: vec![],
+                    url_template: None,
+                    method: None,
+                    headers: HashMap::new(),
+                    body_template: None,
+                    expected_status: None,
+                    depends_on: vec![],
+                    continue_on_error: false,
+                    retries: None,
+                    retry_delay_seconds: None,
+                    env_from_context: vec![],
+                    env: vec![],
+                    env_from: vec![],
+                    resources: None,
+                    service_account_name: None,
+                    output_transform: None,
                 },
             ],
+            input_schema: None,
             outputs: vec![],
             sinks: vec![],
+            notify_on_start: false,
+            token_budget: None,
+            max_cost_usd: None,
+            config_vars: Vec::new(),
+            max_parallel_agents: None,
         },
         status: None,
     }
@@ -0,0 +1,16 @@
+-- At most one unresolved alert per fingerprint, so concurrent deliveries of
+-- the same alert can be deduplicated with an upsert
+
+-- Resolve older duplicates left behind before this constraint existed
+UPDATE alerts SET status = 'resolved'
+WHERE status != 'resolved'
+  AND EXISTS (
+    SELECT 1 FROM alerts newer
+    WHERE newer.fingerprint = alerts.fingerprint
+      AND newer.status != 'resolved'
+      AND (newer.created_at > alerts.created_at
+           OR (newer.created_at = alerts.created_at AND newer.id > alerts.id))
+  );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_alerts_active_fingerprint
+    ON alerts(fingerprint) WHERE status != 'resolved';
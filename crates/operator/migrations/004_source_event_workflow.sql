@@ -0,0 +1,3 @@
+-- The workflow execution a source event queued, so event lists can show
+-- how it turned out
+ALTER TABLE source_events ADD COLUMN workflow_id UUID;
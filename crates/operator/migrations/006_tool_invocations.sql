@@ -0,0 +1,14 @@
+-- Every tool call an agent made while investigating, for reviewing what
+-- the agent touched. Rows are only ever inserted.
+CREATE TABLE IF NOT EXISTS tool_invocations (
+    id UUID PRIMARY KEY,
+    workflow_id UUID NOT NULL,
+    step_name TEXT,
+    tool_name TEXT NOT NULL,
+    arguments TEXT NOT NULL, -- JSON stored as text
+    success BOOLEAN NOT NULL,
+    output TEXT NOT NULL, -- Truncated
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tool_invocations_workflow_id ON tool_invocations(workflow_id, created_at);
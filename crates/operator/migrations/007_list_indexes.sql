@@ -0,0 +1,5 @@
+-- Listing pages by status sorts on created_at, and counting them runs on
+-- every page, so both are served from one index. Fingerprint and status
+-- each have their own index since 001.
+CREATE INDEX IF NOT EXISTS idx_alerts_status_created_at ON alerts(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_workflows_status_created_at ON workflows(status, created_at);
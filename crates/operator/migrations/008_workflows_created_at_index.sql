@@ -0,0 +1,3 @@
+-- Time-range listings bound created_at without a status; alerts have had
+-- this index since 001.
+CREATE INDEX IF NOT EXISTS idx_workflows_created_at ON workflows(created_at);
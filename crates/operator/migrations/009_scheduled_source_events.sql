@@ -0,0 +1,5 @@
+-- The run a scheduled source's event was created for. Each run gets at most
+-- one event, so a run claimed by one replica isn't fired again by another;
+-- webhook events leave it NULL, which the unique index doesn't compare.
+ALTER TABLE source_events ADD COLUMN scheduled_at TIMESTAMP;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_source_events_scheduled_at ON source_events(source_name, scheduled_at);
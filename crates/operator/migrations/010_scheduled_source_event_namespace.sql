@@ -0,0 +1,6 @@
+-- Same-named sources in different namespaces have separate schedules, so
+-- their runs are claimed per namespace. Webhook events leave the namespace
+-- NULL along with `scheduled_at`.
+ALTER TABLE source_events ADD COLUMN source_namespace TEXT;
+DROP INDEX IF EXISTS idx_source_events_scheduled_at;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_source_events_scheduled_run ON source_events(source_namespace, source_name, scheduled_at);
@@ -3,7 +3,7 @@
 //! Core trait and types for pluggable agent behaviors
 
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use serde::{Serialize, Deserialize};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -14,9 +14,12 @@ use super::{
     provider::{LLMProvider, LLMProviderType},
     safety::SafetyValidator,
     result::AgentResult,
+    templates::TemplateLibrary,
+    tools::ToolRecorder,
 };
 use crate::agent::runtime::ToolType;
-use kube::Client as K8sClient;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{Api, Client as K8sClient};
 
 /// Shared context for all agent behaviors
 #[derive(Clone)]
@@ -29,6 +32,8 @@ pub struct AgentContext {
     pub k8s_client: Option<K8sClient>,
     pub prometheus_endpoint: String,
     pub safety_validator: Arc<SafetyValidator>,
+    /// Saves the investigator's tool calls, when it runs for a workflow
+    pub tool_recorder: Option<ToolRecorder>,
     // Additional resources like runbook access, config, etc.
 }
 
@@ -147,6 +152,9 @@ pub struct AgentBehaviorConfig {
     pub temperature: Option<f32>,
     pub system_prompt: Option<String>,
     pub require_approval_for: Vec<String>, // Tool names that require approval
+    /// Prompts given to the agent
+    #[serde(skip)]
+    pub templates: TemplateLibrary,
 }
 
 impl Default for AgentBehaviorConfig {
@@ -157,6 +165,88 @@ impl Default for AgentBehaviorConfig {
             temperature: Some(0.7),
             system_prompt: None,
             require_approval_for: vec!["kubectl delete".to_string(), "kubectl patch".to_string()],
+            templates: TemplateLibrary::default(),
         }
     }
+}
+
+impl AgentBehaviorConfig {
+    /// Load the configuration from a ConfigMap's `data`. Recognized keys are
+    /// `system_prompt`, `max_iterations`, `timeout_seconds`, `temperature` and
+    /// `require_approval_for` (comma-separated); missing keys keep their defaults.
+    /// `system.tera`, `investigation.tera` and `summary.tera` replace the
+    /// embedded prompt templates.
+    pub async fn from_config_map(client: &K8sClient, namespace: &str, name: &str) -> Result<Self> {
+        let api: Api<ConfigMap> = Api::namespaced(client.clone(), namespace);
+        let config_map = api.get(name).await
+            .map_err(|e| anyhow::anyhow!("Failed to get ConfigMap '{}' in namespace '{}': {}", name, namespace, e))?;
+        
+        Self::from_config_map_data(&config_map.data.unwrap_or_default())
+            .map_err(|e| anyhow::anyhow!("Invalid agent configuration in ConfigMap '{}': {}", name, e))
+    }
+    
+    fn from_config_map_data(data: &BTreeMap<String, String>) -> Result<Self> {
+        fn parse<T: std::str::FromStr>(data: &BTreeMap<String, String>, key: &str) -> Result<Option<T>>
+        where
+            T::Err: std::fmt::Display,
+        {
+            data.get(key)
+                .map(|value| value.trim().parse()
+                    .map_err(|e| anyhow::anyhow!("invalid value '{}' for {}: {}", value, key, e)))
+                .transpose()
+        }
+        
+        let mut config = Self::default();
+        if let Some(system_prompt) = data.get("system_prompt") {
+            config.system_prompt = Some(system_prompt.clone());
+        }
+        if let Some(max_iterations) = parse(data, "max_iterations")? {
+            config.max_iterations = Some(max_iterations);
+        }
+        if let Some(timeout_seconds) = parse(data, "timeout_seconds")? {
+            config.timeout_seconds = Some(timeout_seconds);
+        }
+        if let Some(temperature) = parse(data, "temperature")? {
+            config.temperature = Some(temperature);
+        }
+        if let Some(require_approval_for) = data.get("require_approval_for") {
+            config.require_approval_for = require_approval_for.split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        config.templates = TemplateLibrary::with_overrides(data)?;
+        
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config_map_data() {
+        let data: BTreeMap<String, String> = [
+            ("system_prompt".to_string(), "You are the on-call SRE.".to_string()),
+            ("max_iterations".to_string(), " 25 ".to_string()),
+            ("require_approval_for".to_string(), "kubectl delete, kubectl scale,,".to_string()),
+        ].into();
+        
+        let config = AgentBehaviorConfig::from_config_map_data(&data).unwrap();
+        assert_eq!(config.system_prompt.as_deref(), Some("You are the on-call SRE."));
+        assert_eq!(config.max_iterations, Some(25));
+        assert_eq!(config.require_approval_for, vec!["kubectl delete", "kubectl scale"]);
+        // Keys that aren't set keep their defaults
+        assert_eq!(config.timeout_seconds, AgentBehaviorConfig::default().timeout_seconds);
+        
+        let invalid: BTreeMap<String, String> = [("temperature".to_string(), "warm".to_string())].into();
+        let err = AgentBehaviorConfig::from_config_map_data(&invalid).unwrap_err();
+        assert!(err.to_string().contains("invalid value 'warm' for temperature"));
+        
+        let templated: BTreeMap<String, String> = [("system.tera".to_string(), "You are the on-call SRE.".to_string())].into();
+        let config = AgentBehaviorConfig::from_config_map_data(&templated).unwrap();
+        assert_eq!(config.templates.system_prompt(), "You are the on-call SRE.");
+    }
 } 
\ No newline at end of file
@@ -10,7 +10,7 @@ use rig::completion::Prompt;
 
 use super::{
     behavior::{AgentBehavior, AgentInput, AgentOutput, AgentContext, ToolCall, AgentBehaviorConfig},
-    provider::{LLMProviderType, map_anthropic_model},
+    provider::{LLMProviderType, map_anthropic_model, map_vertex_model},
 };
 use crate::agent::runtime::ToolType;
 
@@ -84,6 +84,9 @@ impl ChatbotAgent {
                         ToolType::Script(script_tool) => {
                             builder = builder.tool(script_tool.clone());
                         }
+                        ToolType::Ingress(ingress_tool) => {
+                            builder = builder.tool(ingress_tool.clone());
+                        }
                     }
                 }
                 
@@ -122,6 +125,9 @@ impl ChatbotAgent {
                         ToolType::Script(script_tool) => {
                             builder = builder.tool(script_tool.clone());
                         }
+                        ToolType::Ingress(ingress_tool) => {
+                            builder = builder.tool(ingress_tool.clone());
+                        }
                     }
                 }
                 
@@ -137,6 +143,43 @@ impl ChatbotAgent {
                 
                 Ok((response, None))
             }
+            LLMProviderType::VertexAI(client) => {
+                let mut builder = rig::agent::AgentBuilder::new(client.completion_model(map_vertex_model(&context.model)))
+                    .preamble(&self.build_system_prompt());
+                
+                // Add tools from context
+                for (name, tool) in context.tools.iter() {
+                    debug!("Adding tool to chatbot: {}", name);
+                    match tool {
+                        ToolType::Kubectl(kubectl_tool) => {
+                            builder = builder.tool(kubectl_tool.clone());
+                        }
+                        ToolType::PromQL(promql_tool) => {
+                            builder = builder.tool(promql_tool.clone());
+                        }
+                        ToolType::Curl(curl_tool) => {
+                            builder = builder.tool(curl_tool.clone());
+                        }
+                        ToolType::Script(script_tool) => {
+                            builder = builder.tool(script_tool.clone());
+                        }
+                        ToolType::Ingress(ingress_tool) => {
+                            builder = builder.tool(ingress_tool.clone());
+                        }
+                    }
+                }
+                
+                let agent = builder.build();
+                
+                let mut history_clone = history.clone();
+                let response = agent.prompt(content)
+                    .with_history(&mut history_clone)
+                    .multi_turn(10)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Chat failed: {:?}", e))?;
+                
+                Ok((response, None))
+            }
             LLMProviderType::Mock => {
                 // For mock or unsupported providers, return a simple response
                 Ok((
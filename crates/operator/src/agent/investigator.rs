@@ -10,17 +10,17 @@ use tracing::{info, debug, warn, error};
 use serde_json;
 use regex::Regex;
 use chrono::Utc;
-use rig::{completion::Prompt, providers::{anthropic, openai}};
+use rig::{agent::AgentBuilder, completion::{CompletionModel, Prompt}};
 
 use super::{
     behavior::{
         AgentBehavior, AgentInput, AgentOutput, AgentContext, ToolCall, 
         AgentBehaviorConfig, RiskLevel, HumanApprovalResponse
     },
-    provider::{LLMProvider, LLMProviderType, map_anthropic_model},
-    result::{AgentResult, Finding, FindingSeverity, Recommendation, RiskLevel as ResultRiskLevel, ActionTaken},
-    templates,
+    provider::{LLMProviderType, MeteredModel, TokenCounter, map_anthropic_model, map_vertex_model},
+    result::{AgentResult, Finding, FindingSeverity, InhibitionRule, Recommendation, RiskLevel as ResultRiskLevel, ActionTaken},
     safety::SafetyValidator,
+    tools::{Recorded, ToolRecorder},
 };
 use crate::agent::runtime::ToolType;
 
@@ -38,7 +38,7 @@ impl InvestigatorAgent {
     /// Build system prompt for investigation
     fn build_investigation_prompt(&self, goal: &str, context: &serde_json::Value) -> String {
         let system_prompt = self.config.system_prompt.clone().unwrap_or_else(|| {
-            templates::INVESTIGATION_SYSTEM_PROMPT.to_string()
+            self.config.templates.system_prompt().to_string()
         });
         
         format!(
@@ -50,12 +50,15 @@ impl InvestigatorAgent {
             1. Root cause analysis\n\
             2. Key findings\n\
             3. Recommendations\n\
-            4. Whether this can be auto-fixed\n\n\
+            4. Whether this can be auto-fixed\n\
+            5. Whether another firing alert causes this one (or this one causes others)\n\n\
             Structure your final response with clear sections:\n\
             ROOT CAUSE: <explanation>\n\
             FINDINGS:\n- finding 1\n- finding 2\n\
             RECOMMENDATIONS:\n- recommendation 1\n- recommendation 2\n\
-            AUTO-FIX: <yes/no and command if applicable>",
+            AUTO-FIX: <yes/no and command if applicable>\n\
+            INHIBITION: <none, or if alert A causes alert B, a JSON object \
+            {{\"source_labels\": {{labels of A}}, \"target_labels\": {{labels of B}}, \"equal\": [labels both share]}}>",
             system_prompt,
             goal,
             serde_json::to_string_pretty(context).unwrap_or_default()
@@ -82,12 +85,14 @@ impl InvestigatorAgent {
         }
     }
     
-    /// Run investigation using Rig's agent
+    /// Run investigation using Rig's agent, adding the tokens reported by
+    /// the provider to `tokens_used`
     async fn run_investigation(
         &self,
         goal: &str,
         context: &serde_json::Value,
         agent_context: Arc<AgentContext>,
+        tokens_used: &Arc<TokenCounter>,
     ) -> Result<String> {
         let prompt = self.build_investigation_prompt(goal, context);
         
@@ -101,221 +106,18 @@ impl InvestigatorAgent {
             LLMProviderType::Anthropic(client) => {
                 // Map the model name to correct Anthropic API identifier
                 let anthropic_model = map_anthropic_model(&agent_context.model);
-                
-                let mut builder = client
-                    .agent(anthropic_model)
-                    .preamble(&prompt);
-                
-                // Add tools
-                for (name, tool) in agent_context.tools.iter() {
-                    debug!("Adding tool to investigator: {}", name);
-                    match tool {
-                        ToolType::Kubectl(kubectl_tool) => {
-                            builder = builder.tool(kubectl_tool.clone());
-                        }
-                        ToolType::PromQL(promql_tool) => {
-                            builder = builder.tool(promql_tool.clone());
-                        }
-                        ToolType::Curl(curl_tool) => {
-                            builder = builder.tool(curl_tool.clone());
-                        }
-                        ToolType::Script(script_tool) => {
-                            builder = builder.tool(script_tool.clone());
-                        }
-                    }
-                }
-                
-                let agent = builder
-                    .build();
-                
-                // Try investigation with error recovery
-                match agent.prompt(&investigation_message)
-                    .multi_turn(10)
-                    .await
-                {
-                    Ok(response) => Ok(response),
-                    Err(e) => {
-                        // Check if this is a tool validation error that we can recover from
-                        let error_msg = format!("{:?}", e);
-                        if error_msg.contains("ToolCallError") && (
-                            error_msg.contains("not allowed") || 
-                            error_msg.contains("ValidationError") ||
-                            error_msg.contains("Allowed verbs are")
-                        ) {
-                            warn!("Tool validation error encountered, attempting recovery: {}", error_msg);
-                            
-                            // Create a recovery prompt that informs the model about the tool constraints
-                            let recovery_prompt = format!(
-                                "{}\n\nIMPORTANT: Some tools have constraints. For kubectl, only these verbs are allowed: get, describe, logs, events, top. \
-                                Do NOT attempt to use delete, patch, or other modification commands.\n\n\
-                                Please complete your investigation using only the available tools and provide your analysis.",
-                                prompt
-                            );
-                            
-                            // Try again with the constraint-aware prompt
-                            let mut recovery_builder = client
-                                .agent(anthropic_model)
-                                .preamble(&recovery_prompt);
-                                
-                            // Add all tools to recovery agent
-                            for (name, tool) in agent_context.tools.iter() {
-                                debug!("Adding tool to recovery investigator: {}", name);
-                                match tool {
-                                    ToolType::Kubectl(kubectl_tool) => {
-                                        recovery_builder = recovery_builder.tool(kubectl_tool.clone());
-                                    }
-                                    ToolType::PromQL(promql_tool) => {
-                                        recovery_builder = recovery_builder.tool(promql_tool.clone());
-                                    }
-                                    ToolType::Curl(curl_tool) => {
-                                        recovery_builder = recovery_builder.tool(curl_tool.clone());
-                                    }
-                                    ToolType::Script(script_tool) => {
-                                        recovery_builder = recovery_builder.tool(script_tool.clone());
-                                    }
-                                }
-                            }
-                            
-                            let recovery_agent = recovery_builder.build();
-                            
-                            match recovery_agent.prompt(&investigation_message)
-                                .multi_turn(5)  // Fewer turns for recovery attempt
-                                .await
-                            {
-                                Ok(response) => {
-                                    info!("Investigation recovered successfully after tool validation error");
-                                    Ok(response)
-                                }
-                                Err(recovery_err) => {
-                                    error!("Investigation failed even after recovery attempt: {:?}", recovery_err);
-                                    // Return a partial result based on what we know
-                                    Ok(format!(
-                                        "Investigation encountered tool constraints but provided partial analysis:\n\n\
-                                        ROOT CAUSE: Unable to complete full investigation due to tool limitations\n\n\
-                                        FINDINGS:\n\
-                                        - Investigation was limited by available tool permissions\n\
-                                        - Only read-only operations are available (get, describe, logs, events, top)\n\
-                                        - Original error: {}\n\n\
-                                        RECOMMENDATIONS:\n\
-                                        - Manual investigation required for actions requiring elevated permissions\n\
-                                        - Review kubectl tool configuration to allow necessary operations\n\
-                                        - Use available tools to gather more diagnostic information\n\n\
-                                        AUTO-FIX: no",
-                                        error_msg
-                                    ))
-                                }
-                            }
-                        } else {
-                            Err(anyhow::anyhow!("Investigation failed: {:?}", e))
-                        }
-                    }
-                }
+                let model = MeteredModel::new(client.completion_model(anthropic_model), "anthropic", tokens_used.clone());
+                self.prompt_with_recovery(model, &prompt, &investigation_message, &agent_context.tools, agent_context.tool_recorder.as_ref()).await
             }
             LLMProviderType::OpenAI(client) => {
                 // For OpenAI, use the model name directly (no mapping needed)
-                let mut builder = client
-                    .agent(&agent_context.model)
-                    .preamble(&prompt);
-                
-                // Add tools
-                for (name, tool) in agent_context.tools.iter() {
-                    debug!("Adding tool to investigator: {}", name);
-                    match tool {
-                        ToolType::Kubectl(kubectl_tool) => {
-                            builder = builder.tool(kubectl_tool.clone());
-                        }
-                        ToolType::PromQL(promql_tool) => {
-                            builder = builder.tool(promql_tool.clone());
-                        }
-                        ToolType::Curl(curl_tool) => {
-                            builder = builder.tool(curl_tool.clone());
-                        }
-                        ToolType::Script(script_tool) => {
-                            builder = builder.tool(script_tool.clone());
-                        }
-                    }
-                }
-                
-                let agent = builder
-                    .build();
-                
-                // Try investigation with error recovery (similar logic for OpenAI)
-                match agent.prompt(&investigation_message)
-                    .multi_turn(10)
-                    .await
-                {
-                    Ok(response) => Ok(response),
-                    Err(e) => {
-                        let error_msg = format!("{:?}", e);
-                        if error_msg.contains("ToolCallError") && (
-                            error_msg.contains("not allowed") || 
-                            error_msg.contains("ValidationError") ||
-                            error_msg.contains("Allowed verbs are")
-                        ) {
-                            warn!("Tool validation error encountered, attempting recovery: {}", error_msg);
-                            
-                            let recovery_prompt = format!(
-                                "{}\n\nIMPORTANT: Some tools have constraints. For kubectl, only these verbs are allowed: get, describe, logs, events, top. \
-                                Do NOT attempt to use delete, patch, or other modification commands.\n\n\
-                                Please complete your investigation using only the available tools and provide your analysis.",
-                                prompt
-                            );
-                            
-                            let mut recovery_builder = client
-                                .agent(&agent_context.model)
-                                .preamble(&recovery_prompt);
-                                
-                            // Add all tools to recovery agent
-                            for (name, tool) in agent_context.tools.iter() {
-                                debug!("Adding tool to recovery investigator: {}", name);
-                                match tool {
-                                    ToolType::Kubectl(kubectl_tool) => {
-                                        recovery_builder = recovery_builder.tool(kubectl_tool.clone());
-                                    }
-                                    ToolType::PromQL(promql_tool) => {
-                                        recovery_builder = recovery_builder.tool(promql_tool.clone());
-                                    }
-                                    ToolType::Curl(curl_tool) => {
-                                        recovery_builder = recovery_builder.tool(curl_tool.clone());
-                                    }
-                                    ToolType::Script(script_tool) => {
-                                        recovery_builder = recovery_builder.tool(script_tool.clone());
-                                    }
-                                }
-                            }
-                            
-                            let recovery_agent = recovery_builder.build();
-                            
-                            match recovery_agent.prompt(&investigation_message)
-                                .multi_turn(5)
-                                .await
-                            {
-                                Ok(response) => {
-                                    info!("Investigation recovered successfully after tool validation error");
-                                    Ok(response)
-                                }
-                                Err(_) => {
-                                    Ok(format!(
-                                        "Investigation encountered tool constraints but provided partial analysis:\n\n\
-                                        ROOT CAUSE: Unable to complete full investigation due to tool limitations\n\n\
-                                        FINDINGS:\n\
-                                        - Investigation was limited by available tool permissions\n\
-                                        - Only read-only operations are available (get, describe, logs, events, top)\n\
-                                        - Original error: {}\n\n\
-                                        RECOMMENDATIONS:\n\
-                                        - Manual investigation required for actions requiring elevated permissions\n\
-                                        - Review kubectl tool configuration to allow necessary operations\n\
-                                        - Use available tools to gather more diagnostic information\n\n\
-                                        AUTO-FIX: no",
-                                        error_msg
-                                    ))
-                                }
-                            }
-                        } else {
-                            Err(anyhow::anyhow!("Investigation failed: {:?}", e))
-                        }
-                    }
-                }
+                let model = MeteredModel::new(client.completion_model(&agent_context.model), "openai", tokens_used.clone());
+                self.prompt_with_recovery(model, &prompt, &investigation_message, &agent_context.tools, agent_context.tool_recorder.as_ref()).await
+            }
+            LLMProviderType::VertexAI(client) => {
+                let vertex_model = map_vertex_model(&agent_context.model);
+                let model = MeteredModel::new(client.completion_model(vertex_model), "vertexai", tokens_used.clone());
+                self.prompt_with_recovery(model, &prompt, &investigation_message, &agent_context.tools, agent_context.tool_recorder.as_ref()).await
             }
             LLMProviderType::Mock => {
                 // Mock response for testing
@@ -324,6 +126,83 @@ impl InvestigatorAgent {
         }
     }
     
+    /// Prompt `model` with the investigation tools. If a tool call is
+    /// rejected by validation, retry once with a preamble spelling out the
+    /// tool constraints.
+    async fn prompt_with_recovery<M: CompletionModel + Clone>(
+        &self,
+        model: M,
+        prompt: &str,
+        investigation_message: &str,
+        tools: &HashMap<String, ToolType>,
+        recorder: Option<&ToolRecorder>,
+    ) -> Result<String> {
+        let agent = with_tools(AgentBuilder::new(model.clone()).preamble(prompt), tools, recorder, "investigator")
+            .build();
+        
+        // Try investigation with error recovery
+        match agent.prompt(investigation_message)
+            .multi_turn(10)
+            .await
+        {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                // Check if this is a tool validation error that we can recover from
+                let error_msg = format!("{:?}", e);
+                if error_msg.contains("ToolCallError") && (
+                    error_msg.contains("not allowed") || 
+                    error_msg.contains("ValidationError") ||
+                    error_msg.contains("Allowed verbs are")
+                ) {
+                    warn!("Tool validation error encountered, attempting recovery: {}", error_msg);
+                    crate::metrics::AGENT_TOOL_RECOVERY_PROMPTS_TOTAL.inc();
+                    
+                    // Create a recovery prompt that informs the model about the tool constraints
+                    let recovery_prompt = format!(
+                        "{}\n\nIMPORTANT: Some tools have constraints. For kubectl, only these verbs are allowed: get, describe, logs, events, top. \
+                        Do NOT attempt to use delete, patch, or other modification commands.\n\n\
+                        Please complete your investigation using only the available tools and provide your analysis.",
+                        prompt
+                    );
+                    
+                    // Try again with the constraint-aware prompt
+                    let recovery_agent = with_tools(AgentBuilder::new(model).preamble(&recovery_prompt), tools, recorder, "recovery investigator")
+                        .build();
+                    
+                    match recovery_agent.prompt(investigation_message)
+                        .multi_turn(5)  // Fewer turns for recovery attempt
+                        .await
+                    {
+                        Ok(response) => {
+                            info!("Investigation recovered successfully after tool validation error");
+                            Ok(response)
+                        }
+                        Err(recovery_err) => {
+                            error!("Investigation failed even after recovery attempt: {:?}", recovery_err);
+                            // Return a partial result based on what we know
+                            Ok(format!(
+                                "Investigation encountered tool constraints but provided partial analysis:\n\n\
+                                ROOT CAUSE: Unable to complete full investigation due to tool limitations\n\n\
+                                FINDINGS:\n\
+                                - Investigation was limited by available tool permissions\n\
+                                - Only read-only operations are available (get, describe, logs, events, top)\n\
+                                - Original error: {}\n\n\
+                                RECOMMENDATIONS:\n\
+                                - Manual investigation required for actions requiring elevated permissions\n\
+                                - Review kubectl tool configuration to allow necessary operations\n\
+                                - Use available tools to gather more diagnostic information\n\n\
+                                AUTO-FIX: no",
+                                error_msg
+                            ))
+                        }
+                    }
+                } else {
+                    Err(anyhow::anyhow!("Investigation failed: {:?}", e))
+                }
+            }
+        }
+    }
+    
     /// Mock investigation response for testing
     fn mock_investigation_response(&self, goal: &str) -> String {
         // Check for PodCrashLooping in the goal or initial data
@@ -425,6 +304,11 @@ impl InvestigatorAgent {
             }
         }
         
+        // Extract the inhibition rule for a causal chain between alerts
+        if let Some(inhibition_text) = self.extract_section(response, &["INHIBITION:", "inhibition:", "Inhibition:"]) {
+            result.inhibition_rule = InhibitionRule::parse(&inhibition_text).map(Box::new);
+        }
+        
         // Set summary
         if result.root_cause.is_some() {
             result.summary = format!(
@@ -446,7 +330,7 @@ impl InvestigatorAgent {
                 // Find the end of this section
                 let end_markers = vec![
                     "\nROOT CAUSE:", "\nFINDINGS:", "\nRECOMMENDATIONS:", 
-                    "\nAUTO-FIX:", "\nSUMMARY:", "\n\n\n"
+                    "\nAUTO-FIX:", "\nINHIBITION:", "\nSUMMARY:", "\n\n\n"
                 ];
                 let mut end = section_text.len();
                 
@@ -464,6 +348,32 @@ impl InvestigatorAgent {
     }
 }
 
+/// Register the context's tools with an agent builder, saving their calls
+/// with `recorder` if there is one
+fn with_tools<M: CompletionModel>(mut builder: AgentBuilder<M>, tools: &HashMap<String, ToolType>, recorder: Option<&ToolRecorder>, agent: &str) -> AgentBuilder<M> {
+    for (name, tool) in tools.iter() {
+        debug!("Adding tool to {}: {}", agent, name);
+        match tool {
+            ToolType::Kubectl(kubectl_tool) => {
+                builder = builder.tool(Recorded::new(kubectl_tool.clone(), recorder.cloned()));
+            }
+            ToolType::PromQL(promql_tool) => {
+                builder = builder.tool(Recorded::new(promql_tool.clone(), recorder.cloned()));
+            }
+            ToolType::Curl(curl_tool) => {
+                builder = builder.tool(Recorded::new(curl_tool.clone(), recorder.cloned()));
+            }
+            ToolType::Script(script_tool) => {
+                builder = builder.tool(Recorded::new(script_tool.clone(), recorder.cloned()));
+            }
+            ToolType::Ingress(ingress_tool) => {
+                builder = builder.tool(Recorded::new(ingress_tool.clone(), recorder.cloned()));
+            }
+        }
+    }
+    builder
+}
+
 #[async_trait]
 impl AgentBehavior for InvestigatorAgent {
     async fn handle(
@@ -489,8 +399,16 @@ impl AgentBehavior for InvestigatorAgent {
                 }
                 
                 // Run the investigation
-                let response = self.run_investigation(&goal, &investigation_context, context.clone()).await?;
+                let counter = Arc::new(TokenCounter::default());
+                let response = self.run_investigation(&goal, &investigation_context, context.clone(), &counter).await?;
                 debug!("Investigation response: {}", response);
+                let as_reported = |tokens: u64| match tokens {
+                    0 => None,
+                    tokens => Some(u32::try_from(tokens).unwrap_or(u32::MAX)),
+                };
+                let tokens_used = as_reported(counter.total());
+                let input_tokens = as_reported(counter.input());
+                let output_tokens = as_reported(counter.output());
                 
                 // Check if the response contains actions that require approval
                 if response.contains("kubectl delete") || response.contains("kubectl patch") {
@@ -514,6 +432,9 @@ impl AgentBehavior for InvestigatorAgent {
                                 "response": response,
                                 "goal": goal,
                                 "proposed_action": proposed_action,
+                                "tokens_used": tokens_used,
+                                "input_tokens": input_tokens,
+                                "output_tokens": output_tokens,
                             }),
                             workflow_id,
                             risk_level,
@@ -523,7 +444,10 @@ impl AgentBehavior for InvestigatorAgent {
                 }
                 
                 // Parse and return the final result
-                let result = self.parse_investigation_response(&response);
+                let mut result = self.parse_investigation_response(&response);
+                result.tokens_used = tokens_used;
+                result.input_tokens = input_tokens;
+                result.output_tokens = output_tokens;
                 Ok(AgentOutput::FinalInvestigationResult(result))
             }
             AgentInput::ResumeInvestigation {
@@ -542,6 +466,12 @@ impl AgentBehavior for InvestigatorAgent {
                     .unwrap_or("");
                 
                 let mut result = self.parse_investigation_response(response);
+                let saved_tokens = |key: &str| saved_state.get(key)
+                    .and_then(|v| v.as_u64())
+                    .and_then(|v| u32::try_from(v).ok());
+                result.tokens_used = saved_tokens("tokens_used");
+                result.input_tokens = saved_tokens("input_tokens");
+                result.output_tokens = saved_tokens("output_tokens");
                 
                 if approval_response.approved {
                     result.add_action(ActionTaken {
@@ -12,6 +12,7 @@ pub mod tools;
 pub mod safety;
 pub mod templates;
 pub mod result;
+pub mod vertex;
 
 pub use behavior::{AgentBehavior, AgentInput, AgentOutput, AgentContext, AgentBehaviorConfig};
 pub use chatbot::ChatbotAgent;
@@ -4,11 +4,14 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 // Import from rig
-use rig::completion::Prompt;
-use rig::providers::{anthropic, openai};
+use rig::completion::{CompletionError, CompletionModel, CompletionRequest, CompletionResponse, Prompt};
+use rig::providers::{anthropic, gemini, openai};
+
+use super::vertex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMConfig {
@@ -67,6 +70,131 @@ pub fn map_anthropic_model(model: &str) -> &'static str {
     }
 }
 
+/// Map Gemini model names to the versioned model IDs Vertex AI serves.
+/// Names that are already versioned, or unknown, are passed through.
+pub fn map_vertex_model(model: &str) -> &str {
+    match model {
+        "gemini-1.5-pro" | "gemini-1.5-pro-latest" => "gemini-1.5-pro-002",
+        "gemini-1.5-flash" | "gemini-1.5-flash-latest" => "gemini-1.5-flash-002",
+        "gemini-2.0-flash" | "gemini-2.0-flash-latest" => "gemini-2.0-flash-001",
+        "gemini-2.0-flash-lite" => "gemini-2.0-flash-lite-001",
+        _ => model,
+    }
+}
+
+/// Token usage reported in a provider's raw completion response
+pub trait TokenUsage {
+    /// Input (prompt) and output (completion) tokens consumed by the
+    /// request, if the provider reported them
+    fn input_output_tokens(&self) -> Option<(u64, u64)>;
+
+    /// Total tokens consumed by the request, if the provider reported it
+    fn total_tokens(&self) -> Option<u64> {
+        self.input_output_tokens().map(|(input, output)| input + output)
+    }
+}
+
+impl TokenUsage for anthropic::completion::CompletionResponse {
+    fn input_output_tokens(&self) -> Option<(u64, u64)> {
+        Some((self.usage.input_tokens, self.usage.output_tokens))
+    }
+}
+
+impl TokenUsage for openai::CompletionResponse {
+    fn input_output_tokens(&self) -> Option<(u64, u64)> {
+        self.usage.as_ref().map(|usage| {
+            let input = usage.prompt_tokens as u64;
+            (input, (usage.total_tokens as u64).saturating_sub(input))
+        })
+    }
+}
+
+impl TokenUsage for gemini::completion::gemini_api_types::GenerateContentResponse {
+    fn input_output_tokens(&self) -> Option<(u64, u64)> {
+        self.usage_metadata.as_ref().map(|usage| {
+            let input = usage.prompt_token_count as u64;
+            (input, (usage.total_token_count as u64).saturating_sub(input))
+        })
+    }
+}
+
+/// Input and output tokens metered across the requests of a prompt
+#[derive(Debug, Default)]
+pub struct TokenCounter {
+    input: AtomicU64,
+    output: AtomicU64,
+}
+
+impl TokenCounter {
+    pub fn add(&self, input: u64, output: u64) {
+        self.input.fetch_add(input, Ordering::Relaxed);
+        self.output.fetch_add(output, Ordering::Relaxed);
+    }
+
+    pub fn input(&self) -> u64 {
+        self.input.load(Ordering::Relaxed)
+    }
+
+    pub fn output(&self) -> u64 {
+        self.output.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input() + self.output()
+    }
+}
+
+/// Completion model wrapper that adds the token usage of every response to a
+/// shared counter, so multi-turn prompts can be metered as a whole. Failed
+/// requests are counted in `llm_api_errors_total`.
+#[derive(Clone)]
+pub struct MeteredModel<M> {
+    inner: M,
+    provider: &'static str,
+    tokens_used: Arc<TokenCounter>,
+}
+
+impl<M> MeteredModel<M> {
+    pub fn new(inner: M, provider: &'static str, tokens_used: Arc<TokenCounter>) -> Self {
+        Self { inner, provider, tokens_used }
+    }
+}
+
+impl<M> CompletionModel for MeteredModel<M>
+where
+    M: CompletionModel,
+    M::Response: TokenUsage,
+{
+    type Response = M::Response;
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> std::result::Result<CompletionResponse<Self::Response>, CompletionError> {
+        let response = self.inner.completion(request).await
+            .inspect_err(|e| {
+                crate::metrics::LLM_API_ERRORS_TOTAL
+                    .with_label_values(&[self.provider, completion_error_type(e)])
+                    .inc();
+            })?;
+        if let Some((input, output)) = response.raw_response.input_output_tokens() {
+            self.tokens_used.add(input, output);
+        }
+        Ok(response)
+    }
+}
+
+/// `error_type` label for a failed completion request
+fn completion_error_type(error: &CompletionError) -> &'static str {
+    match error {
+        CompletionError::HttpError(_) => "http",
+        CompletionError::JsonError(_) => "json",
+        CompletionError::RequestError(_) => "request",
+        CompletionError::ResponseError(_) => "response",
+        CompletionError::ProviderError(_) => "provider",
+    }
+}
+
 /// Anthropic Claude provider using Rig
 pub struct AnthropicProvider {
     client: anthropic::Client,
@@ -160,6 +288,36 @@ impl LLMProvider for OpenAIProvider {
     }
 }
 
+/// Google Cloud Vertex AI provider, authenticated with Application Default Credentials
+pub struct VertexAIProvider {
+    client: vertex::Client,
+    model: String,
+}
+
+impl VertexAIProvider {
+    pub fn new(endpoint: Option<&str>, model: &str) -> Result<Self> {
+        Ok(Self {
+            client: vertex::Client::from_env(endpoint)?,
+            model: model.to_string(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMProvider for VertexAIProvider {
+    async fn prompt(&self, prompt: &str) -> Result<String> {
+        let agent = rig::agent::AgentBuilder::new(self.client.completion_model(map_vertex_model(&self.model)))
+            .build();
+        
+        let response = agent
+            .prompt(prompt)
+            .await
+            .map_err(|e| anyhow::anyhow!("Vertex AI error: {:?}", e))?;
+        
+        Ok(response)
+    }
+}
+
 /// Mock provider for testing
 pub struct MockProvider;
 
@@ -197,6 +355,7 @@ impl LLMProvider for MockProvider {
 pub enum LLMProviderType {
     Anthropic(anthropic::Client),
     OpenAI(openai::Client),
+    VertexAI(vertex::Client),
     Mock,
 }
 
@@ -225,6 +384,10 @@ impl LLMProviderType {
                 };
                 Ok(LLMProviderType::OpenAI(client))
             }
+            "vertexai" | "vertex" => {
+                let client = vertex::Client::from_env(config.endpoint.as_deref())?;
+                Ok(LLMProviderType::VertexAI(client))
+            }
             _ => Ok(LLMProviderType::Mock),
         }
     }
@@ -241,10 +404,61 @@ pub fn create_provider(config: &LLMConfig) -> Result<Arc<dyn LLMProvider>> {
             let provider = OpenAIProvider::new(config.api_key.clone(), &config.model)?;
             Ok(Arc::new(provider))
         }
+        "vertexai" | "vertex" => {
+            let provider = VertexAIProvider::new(config.endpoint.as_deref(), &config.model)?;
+            Ok(Arc::new(provider))
+        }
         "mock" => Ok(Arc::new(MockProvider)),
         _ => {
             // Default to mock for now
             Ok(Arc::new(MockProvider))
         }
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_token_usage() {
+        let anthropic: anthropic::completion::CompletionResponse = serde_json::from_value(json!({
+            "content": [{ "type": "text", "text": "done" }],
+            "id": "msg_1",
+            "model": "claude-3-5-sonnet-latest",
+            "role": "assistant",
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": { "input_tokens": 120, "output_tokens": 30 }
+        })).unwrap();
+        assert_eq!(anthropic.total_tokens(), Some(150));
+        assert_eq!(anthropic.input_output_tokens(), Some((120, 30)));
+
+        let openai: openai::CompletionResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "gpt-4o",
+            "choices": [],
+            "usage": { "prompt_tokens": 90, "total_tokens": 110 }
+        })).unwrap();
+        assert_eq!(openai.total_tokens(), Some(110));
+        assert_eq!(openai.input_output_tokens(), Some((90, 20)));
+
+        let vertex: gemini::completion::gemini_api_types::GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [],
+            "usageMetadata": { "promptTokenCount": 70, "candidatesTokenCount": 20, "totalTokenCount": 90 }
+        })).unwrap();
+        assert_eq!(vertex.total_tokens(), Some(90));
+        assert_eq!(vertex.input_output_tokens(), Some((70, 20)));
+    }
+
+    #[test]
+    fn test_map_vertex_model() {
+        assert_eq!(map_vertex_model("gemini-1.5-pro"), "gemini-1.5-pro-002");
+        assert_eq!(map_vertex_model("gemini-2.0-flash"), "gemini-2.0-flash-001");
+        assert_eq!(map_vertex_model("gemini-1.5-pro-001"), "gemini-1.5-pro-001");
+        assert_eq!(map_vertex_model("gemini-2.5-pro"), "gemini-2.5-pro");
+    }
+}
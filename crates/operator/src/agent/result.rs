@@ -37,6 +37,72 @@ pub struct AgentResult {
     
     /// Raw conversation history (for debugging)
     pub conversation: Vec<ConversationTurn>,
+    
+    /// Tokens consumed by the LLM, if the provider reported usage
+    #[serde(default)]
+    pub tokens_used: Option<u32>,
+    
+    /// Prompt tokens within `tokens_used`, if the provider reported them
+    #[serde(default)]
+    pub input_tokens: Option<u32>,
+    
+    /// Completion tokens within `tokens_used`, if the provider reported them
+    #[serde(default)]
+    pub output_tokens: Option<u32>,
+    
+    /// Inhibition rule to suppress a symptom alert while the alert causing
+    /// it fires, if the investigation found such a causal chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inhibition_rule: Option<Box<InhibitionRule>>,
+}
+
+fn add_tokens(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.saturating_add(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// AlertManager inhibition rule: alerts matching `target_labels` are muted
+/// while an alert matching `source_labels` fires with the same values for
+/// the `equal` labels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InhibitionRule {
+    pub source_labels: HashMap<String, String>,
+    pub target_labels: HashMap<String, String>,
+    #[serde(default)]
+    pub equal: Vec<String>,
+}
+
+impl InhibitionRule {
+    /// Parse the JSON object in an investigation's INHIBITION section.
+    /// Returns `None` when there is no causal chain or the rule is unusable.
+    pub fn parse(text: &str) -> Option<Self> {
+        let json = &text[text.find('{')?..=text.rfind('}')?];
+        let rule: InhibitionRule = serde_json::from_str(json).ok()?;
+        if rule.source_labels.is_empty() || rule.target_labels.is_empty() || rule.source_labels == rule.target_labels {
+            return None;
+        }
+        Some(rule)
+    }
+
+    /// The rule in AlertManager's `inhibit_rules` format
+    pub fn to_alertmanager(&self) -> serde_json::Value {
+        serde_json::json!({
+            "source_matchers": matchers(&self.source_labels),
+            "target_matchers": matchers(&self.target_labels),
+            "equal": self.equal,
+        })
+    }
+}
+
+/// `name="value"` equality matchers, sorted by label name
+fn matchers(labels: &HashMap<String, String>) -> Vec<String> {
+    let mut matchers: Vec<String> = labels.iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    matchers.sort();
+    matchers
 }
 
 /// A specific finding from the investigation
@@ -107,6 +173,10 @@ impl Default for AgentResult {
             fix_command: None,
             escalation_notes: None,
             conversation: Vec::new(),
+            tokens_used: None,
+            input_tokens: None,
+            output_tokens: None,
+            inhibition_rule: None,
         }
     }
 }
@@ -137,6 +207,63 @@ impl AgentResult {
         self.recommendations.sort_by_key(|r| r.priority);
     }
     
+    /// Merge the result of another agent step into this one.
+    ///
+    /// Findings are deduplicated by category and description, recommendations
+    /// are concatenated and renumbered, the higher confidence wins and
+    /// `can_auto_fix` only holds if both results can auto-fix. Actions and
+    /// conversation turns are combined in chronological order.
+    pub fn merge(self, other: AgentResult) -> AgentResult {
+        let mut findings: Vec<Finding> = Vec::new();
+        for finding in self.findings.into_iter().chain(other.findings) {
+            let duplicate = findings.iter().any(|f| {
+                f.category == finding.category && f.description == finding.description
+            });
+            if !duplicate {
+                findings.push(finding);
+            }
+        }
+        
+        let mut recommendations = Vec::new();
+        for mut part in [self.recommendations, other.recommendations] {
+            part.sort_by_key(|r| r.priority);
+            recommendations.extend(part);
+        }
+        for (idx, rec) in recommendations.iter_mut().enumerate() {
+            rec.priority = u8::try_from(idx + 1).unwrap_or(u8::MAX);
+        }
+        
+        let mut actions_taken = self.actions_taken;
+        actions_taken.extend(other.actions_taken);
+        actions_taken.sort_by_key(|a| a.timestamp);
+        
+        let mut conversation = self.conversation;
+        conversation.extend(other.conversation);
+        conversation.sort_by_key(|t| t.timestamp);
+        
+        let can_auto_fix = self.can_auto_fix && other.can_auto_fix;
+        
+        Self {
+            summary: format!("{}\nAdditionally: {}", self.summary, other.summary),
+            findings,
+            root_cause: self.root_cause.or(other.root_cause),
+            confidence: self.confidence.max(other.confidence),
+            actions_taken,
+            recommendations,
+            can_auto_fix,
+            fix_command: if can_auto_fix { self.fix_command.or(other.fix_command) } else { None },
+            escalation_notes: match (self.escalation_notes, other.escalation_notes) {
+                (Some(a), Some(b)) => Some(format!("{}\n{}", a, b)),
+                (a, b) => a.or(b),
+            },
+            conversation,
+            tokens_used: add_tokens(self.tokens_used, other.tokens_used),
+            input_tokens: add_tokens(self.input_tokens, other.input_tokens),
+            output_tokens: add_tokens(self.output_tokens, other.output_tokens),
+            inhibition_rule: self.inhibition_rule.or(other.inhibition_rule),
+        }
+    }
+    
     /// Format as a human-readable report
     pub fn format_report(&self) -> String {
         let mut report = String::new();
@@ -215,4 +342,143 @@ impl AgentResult {
         
         report
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+    
+    fn finding() -> impl Strategy<Value = Finding> {
+        ("[a-c]", "[x-z]").prop_map(|(category, description)| Finding {
+            category,
+            description,
+            severity: FindingSeverity::Medium,
+            evidence: HashMap::new(),
+        })
+    }
+    
+    fn action() -> impl Strategy<Value = ActionTaken> {
+        (0i64..1_000_000, any::<bool>()).prop_map(|(secs, success)| ActionTaken {
+            tool: "kubectl".to_string(),
+            command: format!("get pods # {}", secs),
+            timestamp: Utc.timestamp_opt(secs, 0).unwrap(),
+            success,
+            output_summary: String::new(),
+        })
+    }
+    
+    fn recommendation() -> impl Strategy<Value = Recommendation> {
+        (1u8..10, "[a-z]{1,8}").prop_map(|(priority, action)| Recommendation {
+            priority,
+            action,
+            rationale: String::new(),
+            risk_level: RiskLevel::Low,
+            requires_approval: false,
+        })
+    }
+    
+    prop_compose! {
+        fn agent_result()(
+            summary in "[a-z ]{0,20}",
+            findings in prop::collection::vec(finding(), 0..6),
+            confidence in 0.0f32..=1.0,
+            actions_taken in prop::collection::vec(action(), 0..6),
+            recommendations in prop::collection::vec(recommendation(), 0..6),
+            can_auto_fix in any::<bool>(),
+        ) -> AgentResult {
+            AgentResult {
+                summary,
+                findings,
+                confidence,
+                actions_taken,
+                recommendations,
+                can_auto_fix,
+                ..Default::default()
+            }
+        }
+    }
+    
+    fn finding_keys(result: &AgentResult) -> BTreeSet<(String, String)> {
+        result.findings.iter()
+            .map(|f| (f.category.clone(), f.description.clone()))
+            .collect()
+    }
+    
+    proptest! {
+        #[test]
+        fn merge_is_commutative(a in agent_result(), b in agent_result()) {
+            let ab = a.clone().merge(b.clone());
+            let ba = b.merge(a);
+            
+            prop_assert_eq!(ab.confidence, ba.confidence);
+            prop_assert_eq!(ab.can_auto_fix, ba.can_auto_fix);
+            prop_assert_eq!(finding_keys(&ab), finding_keys(&ba));
+            prop_assert_eq!(ab.findings.len(), finding_keys(&ab).len());
+            prop_assert_eq!(
+                ab.actions_taken.iter().map(|a| a.timestamp).collect::<Vec<_>>(),
+                ba.actions_taken.iter().map(|a| a.timestamp).collect::<Vec<_>>()
+            );
+            prop_assert_eq!(
+                ab.recommendations.iter().map(|r| r.priority).collect::<Vec<_>>(),
+                ba.recommendations.iter().map(|r| r.priority).collect::<Vec<_>>()
+            );
+        }
+    }
+    
+    #[test]
+    fn test_merge() {
+        let mut triage = AgentResult::new("Pod is OOMKilled".to_string());
+        triage.confidence = 0.6;
+        triage.can_auto_fix = true;
+        triage.add_recommendation(Recommendation {
+            priority: 2,
+            action: "Raise memory limit".to_string(),
+            rationale: String::new(),
+            risk_level: RiskLevel::Low,
+            requires_approval: true,
+        });
+        
+        let mut remediation = AgentResult::new("Memory limit raised".to_string());
+        remediation.confidence = 0.9;
+        remediation.add_recommendation(Recommendation {
+            priority: 1,
+            action: "Monitor restarts".to_string(),
+            rationale: String::new(),
+            risk_level: RiskLevel::Low,
+            requires_approval: false,
+        });
+        
+        let merged = triage.merge(remediation);
+        assert_eq!(merged.summary, "Pod is OOMKilled\nAdditionally: Memory limit raised");
+        assert_eq!(merged.confidence, 0.9);
+        assert!(!merged.can_auto_fix);
+        assert_eq!(
+            merged.recommendations.iter().map(|r| (r.priority, r.action.as_str())).collect::<Vec<_>>(),
+            vec![(1, "Raise memory limit"), (2, "Monitor restarts")]
+        );
+    }
+    
+    #[test]
+    fn test_inhibition_rule() {
+        let rule = InhibitionRule::parse(
+            r#"NodeDown causes the pod alerts
+{"source_labels": {"alertname": "NodeDown"}, "target_labels": {"alertname": "PodCrashLooping", "team": "a\"b"}, "equal": ["node"]}"#,
+        ).unwrap();
+        assert_eq!(rule.equal, vec!["node"]);
+        assert_eq!(rule.to_alertmanager(), serde_json::json!({
+            "source_matchers": ["alertname=\"NodeDown\""],
+            "target_matchers": ["alertname=\"PodCrashLooping\"", "team=\"a\\\"b\""],
+            "equal": ["node"],
+        }));
+        
+        assert!(InhibitionRule::parse("none").is_none());
+        assert!(InhibitionRule::parse(r#"{"source_labels": {}, "target_labels": {"alertname": "A"}}"#).is_none());
+        // A rule inhibiting its own source would mute the cause too
+        assert!(InhibitionRule::parse(
+            r#"{"source_labels": {"alertname": "A"}, "target_labels": {"alertname": "A"}}"#
+        ).is_none());
+    }
+}
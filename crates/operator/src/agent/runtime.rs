@@ -12,8 +12,10 @@ use super::{
     provider::{self, LLMProvider, LLMConfig},
     result::{AgentResult, Finding, FindingSeverity, Recommendation, RiskLevel},
     safety::{SafetyValidator, SafetyConfig},
+    vertex,
     tools::{
-        kubectl::KubectlTool, promql::PromQLTool, curl::CurlTool, script::ScriptTool
+        kubectl::KubectlTool, promql::PromQLTool, curl::CurlTool, script::ScriptTool,
+        ingress::IngressTool, ToolRecorder,
     },
 };
 use anyhow::Result;
@@ -35,6 +37,7 @@ pub enum ToolType {
     PromQL(PromQLTool),
     Curl(CurlTool),
     Script(ScriptTool),
+    Ingress(IngressTool),
 }
 
 // Implement From traits for each tool type
@@ -62,6 +65,12 @@ impl From<ScriptTool> for ToolType {
     }
 }
 
+impl From<IngressTool> for ToolType {
+    fn from(tool: IngressTool) -> Self {
+        ToolType::Ingress(tool)
+    }
+}
+
 /// Agent runtime for executing investigations
 pub struct AgentRuntime {
     llm_config: LLMConfig,
@@ -71,6 +80,8 @@ pub struct AgentRuntime {
     k8s_client: Option<K8sClient>,
     prometheus_endpoint: String,
     tools: HashMap<String, ToolType>,
+    behavior_config: Option<AgentBehaviorConfig>,
+    tool_recorder: Option<ToolRecorder>,
 }
 
 impl AgentRuntime {
@@ -90,9 +101,23 @@ impl AgentRuntime {
             k8s_client: None,
             prometheus_endpoint: "http://prometheus:9090".to_string(),
             tools: HashMap::new(),
+            behavior_config: None,
+            tool_recorder: None,
         })
     }
     
+    /// Use this behavior configuration for investigations instead of the defaults
+    pub fn with_behavior_config(mut self, config: AgentBehaviorConfig) -> Self {
+        self.behavior_config = Some(config);
+        self
+    }
+    
+    /// Save every tool call investigations make with this recorder
+    pub fn with_tool_recorder(mut self, recorder: ToolRecorder) -> Self {
+        self.tool_recorder = Some(recorder);
+        self
+    }
+    
     /// Set Kubernetes client
     pub fn with_k8s_client(mut self, client: K8sClient) -> Self {
         self.k8s_client = Some(client);
@@ -186,6 +211,7 @@ impl AgentRuntime {
             k8s_client: self.k8s_client.clone(),
             prometheus_endpoint: self.prometheus_endpoint.clone(),
             safety_validator: Arc::new(self.safety_validator.clone()),
+            tool_recorder: self.tool_recorder.clone(),
         })
     }
     
@@ -201,6 +227,10 @@ impl AgentRuntime {
     
     /// Get an investigator agent for autonomous investigations
     pub fn get_investigator_agent(&self) -> InvestigatorAgent {
+        if let Some(config) = &self.behavior_config {
+            return InvestigatorAgent::new(config.clone());
+        }
+        
         let mut config = AgentBehaviorConfig::default();
         config.max_iterations = Some(self.max_iterations);
         config.timeout_seconds = Some(self.timeout.as_secs());
@@ -250,6 +280,9 @@ impl AgentRuntime {
                         ToolType::Script(script_tool) => {
                             builder = builder.tool(script_tool.clone());
                         }
+                        ToolType::Ingress(ingress_tool) => {
+                            builder = builder.tool(ingress_tool.clone());
+                        }
                     }
                     debug!("Added tool: {}", name);
                 }
@@ -295,6 +328,9 @@ impl AgentRuntime {
                         ToolType::Script(script_tool) => {
                             builder = builder.tool(script_tool.clone());
                         }
+                        ToolType::Ingress(ingress_tool) => {
+                            builder = builder.tool(ingress_tool.clone());
+                        }
                     }
                     debug!("Added tool: {}", name);
                 }
@@ -316,6 +352,50 @@ impl AgentRuntime {
                     .await
                     .map_err(|e| anyhow::anyhow!("OpenAI chat failed: {:?}", e))
             }
+            "vertexai" | "vertex" => {
+                let client = vertex::Client::from_env(self.llm_config.endpoint.as_deref())?;
+                let model = client.completion_model(provider::map_vertex_model(&self.llm_config.model));
+                let mut builder = rig::agent::AgentBuilder::new(model);
+                
+                // Add stored tools to the builder
+                for (name, tool) in &self.tools {
+                    match tool {
+                        ToolType::Kubectl(kubectl_tool) => {
+                            builder = builder.tool(kubectl_tool.clone());
+                        }
+                        ToolType::PromQL(promql_tool) => {
+                            builder = builder.tool(promql_tool.clone());
+                        }
+                        ToolType::Curl(curl_tool) => {
+                            builder = builder.tool(curl_tool.clone());
+                        }
+                        ToolType::Script(script_tool) => {
+                            builder = builder.tool(script_tool.clone());
+                        }
+                        ToolType::Ingress(ingress_tool) => {
+                            builder = builder.tool(ingress_tool.clone());
+                        }
+                    }
+                    debug!("Added tool: {}", name);
+                }
+                
+                // If no tools were explicitly added but k8s client is available, add default tools
+                if self.tools.is_empty() && self.k8s_client.is_some() {
+                    if let Some(k8s_client) = &self.k8s_client {
+                        builder = builder
+                            .tool(KubectlTool::new(k8s_client.clone()))
+                            .tool(PromQLTool::new(self.prometheus_endpoint.clone()))
+                            .tool(CurlTool::new())
+                            .tool(ScriptTool::new());
+                    }
+                }
+                
+                let agent = builder.build();
+                agent.prompt(prompt)
+                    .multi_turn(5)  // Allow tool usage
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Vertex AI chat failed: {:?}", e))
+            }
             _ => {
                 // For mock provider, return a mock response
                 Ok(self.mock_investigation_response(prompt))
@@ -437,7 +517,7 @@ impl AgentRuntime {
         );
         
         let response = match self.llm_config.provider.as_str() {
-            "anthropic" | "claude" | "openai" => {
+            "anthropic" | "claude" | "openai" | "vertexai" | "vertex" => {
                 self.build_and_chat(&confidence_prompt).await?
             }
             _ => "75".to_string(), // Default for mock provider
@@ -1,8 +1,17 @@
 //! Investigation Templates
 //! 
-//! Pre-defined investigation templates for common alert types.
+//! Pre-defined investigation templates for common alert types, and the
+//! `TemplateLibrary` of Tera prompts the agents are given. The prompts live in
+//! `templates/agent/` and are embedded at compile time; each file documents
+//! the variables it is rendered with.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use super::result::AgentResult;
+use crate::{store::Alert, workflow::WorkflowContext, Error, Result};
 
 /// Get investigation template for a specific alert type
 pub fn get_investigation_template(alert_name: &str) -> Option<InvestigationTemplate> {
@@ -11,7 +20,7 @@ pub fn get_investigation_template(alert_name: &str) -> Option<InvestigationTempl
 }
 
 /// Investigation template for a specific alert type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InvestigationTemplate {
     /// Name of the alert type
     pub alert_name: String,
@@ -23,7 +32,7 @@ pub struct InvestigationTemplate {
     pub success_criteria: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InvestigationStep {
     pub command_template: String,
     pub description: String,
@@ -124,58 +133,182 @@ fn create_templates() -> HashMap<String, InvestigationTemplate> {
 }
 
 /// System prompt for investigation agents
-pub const INVESTIGATION_SYSTEM_PROMPT: &str = r#"You are an expert Kubernetes SRE tasked with investigating alerts and issues.
-
-Your approach should be:
-1. Systematic - follow a logical investigation path
-2. Evidence-based - support conclusions with data
-3. Action-oriented - provide clear next steps
-4. Risk-aware - consider the impact of any recommended actions
-
-When investigating:
-- Start with understanding the current state
-- Gather relevant metrics and logs
-- Look for recent changes
-- Consider the broader system context
-- Identify root causes, not just symptoms
-
-For each investigation, provide:
-- A clear summary of findings
-- Root cause analysis
-- Specific recommendations
-- Risk assessment for any actions
-"#;
-
-/// Build investigation prompt based on alert
-pub fn build_investigation_prompt(alert_name: &str, context: &serde_json::Value) -> String {
-    let mut prompt = String::from(INVESTIGATION_SYSTEM_PROMPT);
-    prompt.push_str("\n\n");
-    
-    // Add alert-specific context
-    prompt.push_str(&format!("Alert: {}\n", alert_name));
-    
-    if let Some(severity) = context.get("severity").and_then(|v| v.as_str()) {
-        prompt.push_str(&format!("Severity: {}\n", severity));
+const SYSTEM_TEMPLATE: &str = include_str!("../../templates/agent/system.tera");
+
+/// Prompt for investigating an alert
+const INVESTIGATION_TEMPLATE: &str = include_str!("../../templates/agent/investigation.tera");
+
+/// Prompt for summarizing a finished investigation
+const SUMMARY_TEMPLATE: &str = include_str!("../../templates/agent/summary.tera");
+
+/// Prompts for the agents, rendered with Tera
+#[derive(Debug, Clone)]
+pub struct TemplateLibrary {
+    tera: Tera,
+    system_prompt: String,
+}
+
+impl Default for TemplateLibrary {
+    fn default() -> Self {
+        Self::with_overrides(&BTreeMap::new()).expect("embedded agent templates are valid")
     }
-    
-    if let Some(namespace) = context.get("namespace").and_then(|v| v.as_str()) {
-        prompt.push_str(&format!("Namespace: {}\n", namespace));
+}
+
+impl TemplateLibrary {
+    /// The embedded templates, with any of `system.tera`,
+    /// `investigation.tera` and `summary.tera` replaced by the entry of the
+    /// same name in `overrides`, e.g. the `data` of a ConfigMap
+    pub fn with_overrides(overrides: &BTreeMap<String, String>) -> Result<Self> {
+        let template = |name: &str, embedded: &'static str| {
+            overrides.get(&format!("{}.tera", name)).map(String::as_str).unwrap_or(embedded)
+        };
+
+        let mut tera = Tera::default();
+        for (name, embedded) in [("investigation", INVESTIGATION_TEMPLATE), ("summary", SUMMARY_TEMPLATE)] {
+            tera.add_raw_template(name, template(name, embedded))
+                .map_err(|e| Error::Config(format!("Invalid agent template {}.tera: {}", name, e)))?;
+        }
+
+        Ok(Self {
+            tera,
+            system_prompt: template("system", SYSTEM_TEMPLATE).trim().to_string(),
+        })
     }
-    
-    // Add template guidance if available
-    if let Some(template) = get_investigation_template(alert_name) {
-        prompt.push_str(&format!("\nAlert Description: {}\n", template.description));
-        prompt.push_str(&template.description);
-        
-        prompt.push_str("\n\nSuggested investigation steps:\n");
-        for (i, step) in template.initial_steps.iter().enumerate() {
-            prompt.push_str(&format!("{}. {}\n", i + 1, step.description));
+
+    /// System prompt for investigation agents
+    pub fn system_prompt(&self) -> &str {
+        &self.system_prompt
+    }
+
+    /// Prompt for investigating `alert`, with built-in guidance for known
+    /// alert types and the outputs of the workflow steps run so far
+    pub fn get_investigation_prompt(&self, alert: &Alert, context: &WorkflowContext) -> Result<String> {
+        let mut tera_context = Context::new();
+        tera_context.insert("system_prompt", &self.system_prompt);
+        tera_context.insert("alert", alert);
+        tera_context.insert("guidance", &get_investigation_template(&alert.alert_name));
+        tera_context.insert("context", &context.to_json());
+        self.render("investigation", &tera_context)
+    }
+
+    /// Prompt asking for a short summary of a finished investigation
+    pub fn get_summary_prompt(&self, result: &AgentResult) -> Result<String> {
+        let mut tera_context = Context::new();
+        tera_context.insert("result", result);
+        self.render("summary", &tera_context)
+    }
+
+    /// Render a template, without the blank lines its doc comment leaves
+    fn render(&self, name: &str, context: &Context) -> Result<String> {
+        self.tera.render(name, context)
+            .map(|prompt| prompt.trim().to_string())
+            .map_err(|e| Error::Internal(format!("Failed to render {} template: {}", name, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::result::{Finding, FindingSeverity, Recommendation};
+
+    fn alert() -> Alert {
+        let now = chrono::Utc::now();
+        Alert {
+            id: uuid::Uuid::new_v4(),
+            external_id: None,
+            fingerprint: "abc".to_string(),
+            status: crate::store::AlertStatus::Received,
+            severity: crate::store::AlertSeverity::Critical,
+            alert_name: "PodCrashLooping".to_string(),
+            summary: Some("Pod api-0 is crash looping".to_string()),
+            description: None,
+            labels: [("namespace".to_string(), "shop".to_string())].into(),
+            annotations: Default::default(),
+            source_id: None,
+            workflow_id: None,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at: now,
+            ends_at: None,
+            received_at: now,
+            triage_started_at: None,
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
         }
-        
-        prompt.push_str(&format!("\nSuccess criteria: {}\n", template.success_criteria));
     }
-    
-    prompt.push_str("\n\nPlease investigate this issue thoroughly and provide actionable recommendations.");
-    
-    prompt
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_investigation_prompt() {
+        let library = TemplateLibrary::default();
+        let mut context = WorkflowContext::new();
+        context.add_step_output("collect", serde_json::json!({ "restarts": 12 }));
+
+        let prompt = library.get_investigation_prompt(&alert(), &context).unwrap();
+        assert!(prompt.starts_with("You are an expert Kubernetes SRE"));
+        assert!(prompt.contains("Alert: PodCrashLooping\nSeverity: critical\nNamespace: shop"));
+        assert!(prompt.contains("Summary: Pod api-0 is crash looping"));
+        assert!(prompt.contains("1. Get pod details and recent events"));
+        assert!(prompt.contains("\"restarts\": 12"));
+
+        // Alerts without built-in guidance get none
+        let mut unknown = alert();
+        unknown.alert_name = "DiskFull".to_string();
+        let prompt = library.get_investigation_prompt(&unknown, &WorkflowContext::new()).unwrap();
+        assert!(!prompt.contains("Suggested investigation steps"));
+        assert!(!prompt.contains("Output of earlier workflow steps"));
+    }
+
+    #[test]
+    fn test_summary_prompt() {
+        let result = AgentResult {
+            summary: "api-0 runs out of memory".to_string(),
+            root_cause: Some("Memory limit too low".to_string()),
+            confidence: 0.85,
+            findings: vec![Finding {
+                category: "memory".to_string(),
+                description: "OOMKilled 12 times".to_string(),
+                severity: FindingSeverity::High,
+                evidence: Default::default(),
+            }],
+            recommendations: vec![Recommendation {
+                priority: 1,
+                action: "Raise the memory limit to 1Gi".to_string(),
+                rationale: "Usage peaks at 900Mi".to_string(),
+                risk_level: crate::agent::result::RiskLevel::Low,
+                requires_approval: false,
+            }],
+            ..Default::default()
+        };
+
+        let prompt = TemplateLibrary::default().get_summary_prompt(&result).unwrap();
+        assert!(prompt.contains("Root cause: Memory limit too low"));
+        assert!(prompt.contains("Confidence: 85%"));
+        assert!(prompt.contains("- [high] memory: OOMKilled 12 times"));
+        assert!(prompt.contains("1. Raise the memory limit to 1Gi (Usage peaks at 900Mi)"));
+
+        let prompt = TemplateLibrary::default().get_summary_prompt(&AgentResult::default()).unwrap();
+        assert!(prompt.contains("Root cause: not determined"));
+    }
+
+    #[test]
+    fn test_template_overrides() {
+        let overrides: BTreeMap<String, String> = [
+            ("system.tera".to_string(), "You are the on-call SRE.".to_string()),
+            ("summary.tera".to_string(), "Summarize: {{ result.summary }}".to_string()),
+        ].into();
+        let library = TemplateLibrary::with_overrides(&overrides).unwrap();
+        assert_eq!(library.system_prompt(), "You are the on-call SRE.");
+        let prompt = library.get_investigation_prompt(&alert(), &WorkflowContext::new()).unwrap();
+        assert!(prompt.starts_with("You are the on-call SRE."));
+
+        let result = AgentResult { summary: "done".to_string(), ..Default::default() };
+        assert_eq!(library.get_summary_prompt(&result).unwrap(), "Summarize: done");
+
+        let broken: BTreeMap<String, String> = [("summary.tera".to_string(), "{{ result.".to_string())].into();
+        let err = TemplateLibrary::with_overrides(&broken).unwrap_err();
+        assert!(err.to_string().contains("Invalid agent template summary.tera"));
+    }
+}
@@ -1,121 +1,289 @@
 //! Curl Tool for HTTP Requests
 //! 
 //! Allows agents to make HTTP requests for health checks and API calls.
+//!
+//! Requests only go to allowlisted hosts. The host is resolved before the
+//! request and the connection is pinned to the checked address, so a DNS
+//! answer can't swap in another one. Loopback and link-local addresses, such
+//! as the cloud metadata endpoint 169.254.169.254, are rejected unless an
+//! allowlisted IP or CIDR covers them.
+//!
+//! Only `GET` and `HEAD` requests are allowed unless more methods are
+//! enabled with `with_allowed_methods`.
 
 use super::{ToolResult, ToolArgs, ToolError};
 use anyhow::Result;
+use ipnet::IpNet;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool as RigTool;
 use reqwest;
-use url::Url;
+use serde::{Deserialize, Serialize};
+use url::{Host, Url};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
+/// Bytes of the response body included in the output
+const MAX_BODY_BYTES: usize = 1000;
+
+/// Arguments for CurlTool execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurlToolArgs {
+    /// HTTP method; defaults to `GET`
+    #[serde(default)]
+    pub method: Option<String>,
+    /// URL to request
+    #[serde(alias = "command")]
+    pub url: String,
+    /// Request headers
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Request body
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl From<ToolArgs> for CurlToolArgs {
+    fn from(args: ToolArgs) -> Self {
+        Self { method: None, url: args.command, headers: HashMap::new(), body: None }
+    }
+}
+
 /// Curl tool for HTTP requests
 #[derive(Clone)]
 pub struct CurlTool {
-    allowed_domains: Vec<String>,
+    /// Domains (including their subdomains), IP addresses and CIDR ranges
+    allowed_hosts: Vec<String>,
+    allowed_methods: HashSet<String>,
 }
 
 impl CurlTool {
     pub fn new() -> Self {
+        // Safe read-only methods
+        let allowed_methods = ["GET", "HEAD"].iter().map(|m| m.to_string()).collect();
+        
         Self {
-            // Allow common domains by default, including httpbin for testing
-            allowed_domains: vec![
-                "localhost".to_string(),
-                "127.0.0.1".to_string(),
-                "httpbin.org".to_string(),
-                "connerswann.me".to_string(),
-            ],
+            allowed_hosts: default_allowed_domains(),
+            allowed_methods,
         }
     }
     
-    pub fn with_allowed_domains(mut self, domains: Vec<String>) -> Self {
-        self.allowed_domains = domains;
+    /// Allow additional methods such as `POST` (for remediation workflows)
+    pub fn with_allowed_methods(mut self, methods: Vec<String>) -> Self {
+        self.allowed_methods.extend(methods.into_iter().map(|m| m.to_uppercase()));
+        self
+    }
+    
+    /// Restrict requests to these domains (including their subdomains), IP
+    /// addresses and CIDR ranges such as `10.0.0.0/8`
+    pub fn with_allowed_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.allowed_hosts = hosts;
         self
     }
     
-    fn validate(&self, input: &str) -> Result<()> {
+    /// Check the URL against the allowlist and resolve its host, returning
+    /// the address the request must connect to
+    async fn validate(&self, input: &str) -> Result<(Url, SocketAddr)> {
         // Parse URL
         let url = Url::parse(input)
             .map_err(|e| anyhow::anyhow!("Invalid URL: {}", e))?;
         
-        // Check if host is allowed
-        if let Some(host) = url.host_str() {
-            let is_allowed = self.allowed_domains.iter().any(|domain| {
-                host == domain || host.ends_with(&format!(".{}", domain))
-            });
-            
-            if !is_allowed {
+        // Only allow HTTP and HTTPS
+        if !["http", "https"].contains(&url.scheme()) {
+            return Err(anyhow::anyhow!("Only HTTP and HTTPS protocols are allowed"));
+        }
+        
+        let port = url.port_or_known_default().unwrap_or(80);
+        let (domain, addresses): (Option<&str>, Vec<IpAddr>) = match url.host() {
+            Some(Host::Domain(domain)) => {
+                let addresses = tokio::net::lookup_host((domain, port)).await
+                    .map_err(|e| anyhow::anyhow!("Failed to resolve host '{}': {}", domain, e))?
+                    .map(|address| address.ip())
+                    .collect();
+                (Some(domain), addresses)
+            }
+            Some(Host::Ipv4(ip)) => (None, vec![IpAddr::V4(ip)]),
+            Some(Host::Ipv6(ip)) => (None, vec![IpAddr::V6(ip)]),
+            None => return Err(anyhow::anyhow!("URL has no host")),
+        };
+        let Some(&first) = addresses.first() else {
+            return Err(anyhow::anyhow!("Host '{}' did not resolve to any address", url.host_str().unwrap_or_default()));
+        };
+        
+        // Every address must be reachable, not just the one connected to
+        for &ip in &addresses {
+            if is_internal_address(ip) && !address_allowed(ip, &self.allowed_hosts) {
                 return Err(anyhow::anyhow!(
-                    "Domain '{}' is not in the allowed list: {:?}",
-                    host,
-                    self.allowed_domains
+                    "Host '{}' resolves to {}, a loopback or link-local address",
+                    url.host_str().unwrap_or_default(),
+                    ip
                 ));
             }
-        } else {
-            return Err(anyhow::anyhow!("URL has no host"));
         }
         
-        // Only allow HTTP and HTTPS
-        if !["http", "https"].contains(&url.scheme()) {
-            return Err(anyhow::anyhow!("Only HTTP and HTTPS protocols are allowed"));
+        let domain_allowed = domain.is_some_and(|domain| check_allowed_domain(domain, &self.allowed_hosts).is_ok());
+        if !domain_allowed && !addresses.iter().all(|&ip| address_allowed(ip, &self.allowed_hosts)) {
+            return Err(anyhow::anyhow!(
+                "Host '{}' is not in the allowed list: {:?}",
+                url.host_str().unwrap_or_default(),
+                self.allowed_hosts
+            ));
         }
         
-        Ok(())
+        Ok((url, SocketAddr::new(first, port)))
+    }
+}
+
+/// Domains HTTP tools may reach unless configured otherwise
+pub(crate) fn default_allowed_domains() -> Vec<String> {
+    // Allow common domains by default, including httpbin for testing
+    vec![
+        "httpbin.org".to_string(),
+        "connerswann.me".to_string(),
+    ]
+}
+
+/// Cut a response body down to `MAX_BODY_BYTES`, noting its full size
+fn truncate_body(text: String) -> String {
+    if text.len() <= MAX_BODY_BYTES {
+        return text;
+    }
+    let mut end = MAX_BODY_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... (truncated, {} total bytes)", &text[..end], text.len())
+}
+
+/// Whether `ip` is covered by an IP address or CIDR range in `allowed_hosts`
+fn address_allowed(ip: IpAddr, allowed_hosts: &[String]) -> bool {
+    allowed_hosts.iter().any(|entry| {
+        if let Ok(network) = entry.parse::<IpNet>() {
+            network.contains(&ip)
+        } else if let Ok(allowed) = entry.parse::<IpAddr>() {
+            allowed == ip
+        } else {
+            false
+        }
+    })
+}
+
+/// Loopback, link-local and unspecified addresses, which reach the node
+/// itself or cloud metadata services rather than another service
+fn is_internal_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_internal_address(IpAddr::V4(mapped)),
+            // fe80::/10
+            None => ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xffc0) == 0xfe80,
+        },
+    }
+}
+
+/// Check that `host` is one of `allowed_domains` or a subdomain of one
+pub(crate) fn check_allowed_domain(host: &str, allowed_domains: &[String]) -> Result<()> {
+    let is_allowed = allowed_domains.iter().any(|domain| {
+        host == domain || host.ends_with(&format!(".{}", domain))
+    });
+    
+    if !is_allowed {
+        return Err(anyhow::anyhow!(
+            "Domain '{}' is not in the allowed list: {:?}",
+            host,
+            allowed_domains
+        ));
     }
+    
+    Ok(())
 }
 
 impl RigTool for CurlTool {
     const NAME: &'static str = "curl";
     
     type Error = ToolError;
-    type Args = ToolArgs;
+    type Args = CurlToolArgs;
     type Output = ToolResult;
     
     async fn definition(&self, _prompt: String) -> ToolDefinition {
+        let mut methods: Vec<&str> = self.allowed_methods.iter().map(String::as_str).collect();
+        methods.sort_unstable();
+        
         ToolDefinition {
             name: Self::NAME.to_string(),
-            description: "Make HTTP requests for health checks and API calls. \
-                         Example: 'curl http://service:8080/health'".to_string(),
+            description: "Make HTTP requests for health checks and API calls, \
+                         e.g. a GET of 'http://service:8080/health'. Returns the status code \
+                         and the start of the response body.".to_string(),
             parameters: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "command": {
+                    "method": {
+                        "type": "string",
+                        "enum": methods,
+                        "description": "The HTTP method; defaults to 'GET'"
+                    },
+                    "url": {
                         "type": "string",
                         "description": "The URL to request (e.g., 'http://service:8080/health')"
+                    },
+                    "headers": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                        "description": "Request headers (e.g., {\"Accept\": \"application/json\"}). Optional."
+                    },
+                    "body": {
+                        "type": "string",
+                        "description": "Request body. Optional."
                     }
                 },
-                "required": ["command"]
+                "required": ["url"]
             }),
         }
     }
     
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
-        self.validate(&args.command)
+        let method = args.method.as_deref().unwrap_or("GET").to_uppercase();
+        if !self.allowed_methods.contains(&method) {
+            let mut allowed: Vec<&String> = self.allowed_methods.iter().collect();
+            allowed.sort_unstable();
+            return Err(ToolError::ValidationError(format!(
+                "Method '{}' is not allowed. Allowed methods are: {:?}.", method, allowed
+            )));
+        }
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|e| ToolError::ValidationError(format!("Invalid method '{}': {}", method, e)))?;
+        
+        let (url, address) = self.validate(&args.url).await
             .map_err(|e| ToolError::ValidationError(e.to_string()))?;
         
-        // Create HTTP client with timeout
-        let client = reqwest::Client::builder()
+        // Create HTTP client with timeout. The connection goes to the
+        // address that was checked, and redirects aren't followed since
+        // they could lead anywhere.
+        let mut builder = reqwest::Client::builder()
             .timeout(Duration::from_secs(10))
-            .build()
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some(Host::Domain(domain)) = url.host() {
+            builder = builder.resolve(domain, address);
+        }
+        let client = builder.build()
             .map_err(|e| ToolError::ExecutionError(format!("Failed to create HTTP client: {}", e)))?;
         
         // Make the request
-        match client.get(&args.command).send().await {
+        let mut request = client.request(method.clone(), url);
+        for (name, value) in &args.headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = args.body {
+            request = request.body(body);
+        }
+        match request.send().await {
             Ok(response) => {
                 let status = response.status();
                 let headers = response.headers().clone();
                 
                 // Try to get response body
                 let body = match response.text().await {
-                    Ok(text) => {
-                        // Truncate very long responses
-                        if text.len() > 1000 {
-                            format!("{}... (truncated, {} total bytes)", &text[..1000], text.len())
-                        } else {
-                            text
-                        }
-                    }
+                    Ok(text) => truncate_body(text),
                     Err(e) => format!("<Error reading response body: {}>", e),
                 };
                 
@@ -146,7 +314,8 @@ impl RigTool for CurlTool {
                     },
                     metadata: Some(serde_json::json!({
                         "status_code": status.as_u16(),
-                        "url": args.command,
+                        "method": method.as_str(),
+                        "url": args.url,
                     })),
                 })
             }
@@ -164,7 +333,8 @@ impl RigTool for CurlTool {
                     output: error_msg.clone(),
                     error: Some(error_msg),
                     metadata: Some(serde_json::json!({
-                        "url": args.command,
+                        "method": method.as_str(),
+                        "url": args.url,
                         "error_type": if e.is_timeout() { "timeout" } 
                                      else if e.is_connect() { "connection" }
                                      else { "other" },
@@ -173,4 +343,122 @@ impl RigTool for CurlTool {
             }
         }
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Start an HTTP server on the loopback interface. `/echo` answers with
+    /// the method, the `x-probe` header and the body it received.
+    async fn start_local_server() -> String {
+        let app = axum::Router::new()
+            .route("/health", axum::routing::get(|| async { "ok" }))
+            .route("/echo", axum::routing::any(
+                |method: axum::http::Method, headers: axum::http::HeaderMap, body: String| async move {
+                    let probe = headers.get("x-probe").and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+                    format!("{} {} {}", method, probe, body)
+                },
+            ))
+            .route("/large", axum::routing::get(|| async { "é".repeat(MAX_BODY_BYTES) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+    
+    fn curl(url: &str) -> CurlToolArgs {
+        ToolArgs { command: url.to_string() }.into()
+    }
+    
+    #[tokio::test]
+    async fn test_blocked_hosts() {
+        let tool = CurlTool::new();
+        
+        // Cloud metadata and loopback are off limits by default
+        for url in ["http://169.254.169.254/latest/meta-data/", "http://127.0.0.1:8080/", "http://[::1]/", "http://[::ffff:169.254.169.254]/"] {
+            let result = tool.call(curl(url)).await;
+            assert!(matches!(result, Err(ToolError::ValidationError(_))), "{} was not blocked", url);
+        }
+        
+        // Allowlisting a name doesn't allow what it resolves to
+        let tool = CurlTool::new().with_allowed_hosts(vec!["localhost".to_string()]);
+        match tool.call(curl("http://localhost:8080/")).await {
+            Err(ToolError::ValidationError(e)) => assert!(e.contains("loopback or link-local"), "{}", e),
+            other => panic!("localhost was not blocked: {:?}", other.map(|r| r.output)),
+        }
+        
+        // Hosts outside the allowlist are rejected before any request
+        let tool = CurlTool::new().with_allowed_hosts(vec!["10.0.0.0/8".to_string()]);
+        match tool.call(curl("http://192.168.1.10/")).await {
+            Err(ToolError::ValidationError(e)) => assert!(e.contains("not in the allowed list"), "{}", e),
+            other => panic!("192.168.1.10 was not blocked: {:?}", other.map(|r| r.output)),
+        }
+        assert!(matches!(tool.call(curl("file:///etc/passwd")).await, Err(ToolError::ValidationError(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_allowed_cidr() {
+        let base_url = start_local_server().await;
+        
+        // An explicit range opens up loopback, by address or by name
+        let tool = CurlTool::new().with_allowed_hosts(vec!["127.0.0.0/8".to_string()]);
+        let result = tool.call(curl(&format!("{}/health", base_url))).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.ends_with("\nok"));
+        
+        let port = base_url.rsplit(':').next().unwrap();
+        let tool = CurlTool::new().with_allowed_hosts(vec!["localhost".to_string(), "127.0.0.1".to_string(), "::1".to_string()]);
+        let result = tool.call(curl(&format!("http://localhost:{}/health", port))).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+    }
+    
+    #[test]
+    fn test_address_rules() {
+        let allowed = vec!["10.0.0.0/8".to_string(), "fd00::/8".to_string(), "192.168.1.5".to_string(), "example.com".to_string()];
+        assert!(address_allowed("10.1.2.3".parse().unwrap(), &allowed));
+        assert!(address_allowed("fd12::1".parse().unwrap(), &allowed));
+        assert!(address_allowed("192.168.1.5".parse().unwrap(), &allowed));
+        assert!(!address_allowed("192.168.1.6".parse().unwrap(), &allowed));
+        
+        assert!(is_internal_address("169.254.169.254".parse().unwrap()));
+        assert!(is_internal_address("127.0.0.53".parse().unwrap()));
+        assert!(is_internal_address("0.0.0.0".parse().unwrap()));
+        assert!(is_internal_address("fe80::1".parse().unwrap()));
+        assert!(!is_internal_address("10.0.0.1".parse().unwrap()));
+        assert!(!is_internal_address("2001:db8::1".parse().unwrap()));
+    }
+    
+    #[tokio::test]
+    async fn test_methods_headers_and_body() {
+        let base_url = start_local_server().await;
+        let tool = CurlTool::new().with_allowed_hosts(vec!["127.0.0.0/8".to_string()]);
+        let request = |method: &str| CurlToolArgs {
+            method: Some(method.to_string()),
+            url: format!("{}/echo", base_url),
+            headers: HashMap::from([("X-Probe".to_string(), "synthetic".to_string())]),
+            body: Some("{\"check\": true}".to_string()),
+        };
+        
+        let result = tool.call(request("get")).await.unwrap();
+        assert!(result.output.ends_with("\nGET synthetic {\"check\": true}"), "{}", result.output);
+        assert_eq!(result.metadata.unwrap()["status_code"], 200);
+        
+        // Mutating methods need to be enabled first
+        match tool.call(request("POST")).await {
+            Err(ToolError::ValidationError(e)) => assert!(e.contains("Method 'POST' is not allowed"), "{}", e),
+            other => panic!("POST was not rejected: {:?}", other.map(|r| r.output)),
+        }
+        let tool = tool.with_allowed_methods(vec!["post".to_string()]);
+        let result = tool.call(request("POST")).await.unwrap();
+        assert!(result.output.ends_with("\nPOST synthetic {\"check\": true}"), "{}", result.output);
+        assert!(matches!(tool.call(request("DELETE")).await, Err(ToolError::ValidationError(_))));
+        
+        // Long bodies are cut short on a character boundary
+        let result = tool.call(curl(&format!("{}/large", base_url))).await.unwrap();
+        assert!(result.output.ends_with(&format!("... (truncated, {} total bytes)", 2 * MAX_BODY_BYTES)));
+        
+        let definition = tool.definition(String::new()).await;
+        assert_eq!(definition.parameters["properties"]["method"]["enum"], serde_json::json!(["GET", "HEAD", "POST"]));
+    }
+}
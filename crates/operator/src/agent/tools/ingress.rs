@@ -0,0 +1,295 @@
+//! Ingress Tool for External Health Checks
+//!
+//! Resolves the hosts and paths exposed by an Ingress and sends an HTTP HEAD
+//! request to each, reporting the status code and latency per endpoint.
+//! Hosts are checked against the same domain allowlist as `CurlTool`.
+
+use super::{ToolResult, ToolError};
+use super::curl::{check_allowed_domain, default_allowed_domains};
+use anyhow::Result;
+use k8s_openapi::api::networking::v1::Ingress;
+use kube::{Api, Client, Config};
+use rig::completion::ToolDefinition;
+use rig::tool::Tool as RigTool;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Arguments for IngressTool execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngressToolArgs {
+    pub name: String,
+    pub namespace: Option<String>,
+}
+
+/// A single host/path exposed by an Ingress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngressTarget {
+    pub scheme: &'static str,
+    pub host: String,
+    pub path: String,
+}
+
+impl IngressTarget {
+    pub fn url(&self) -> String {
+        format!("{}://{}{}", self.scheme, self.host, self.path)
+    }
+}
+
+/// Health check result for one Ingress endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct IngressCheck {
+    pub host: String,
+    pub path: String,
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Ingress tool for checking externally exposed endpoints
+#[derive(Clone)]
+pub struct IngressTool {
+    client: Client,
+    allowed_domains: Vec<String>,
+}
+
+impl IngressTool {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            allowed_domains: default_allowed_domains(),
+        }
+    }
+
+    /// Create a new IngressTool using the inferred Kubernetes configuration
+    pub async fn infer() -> Result<Self> {
+        let config = Config::infer().await
+            .map_err(|e| anyhow::anyhow!("Failed to infer Kubernetes config: {}", e))?;
+
+        let client = Client::try_from(config)
+            .map_err(|e| anyhow::anyhow!("Failed to create Kubernetes client: {}", e))?;
+
+        Ok(Self::new(client))
+    }
+
+    pub fn with_allowed_domains(mut self, domains: Vec<String>) -> Self {
+        self.allowed_domains = domains;
+        self
+    }
+
+    async fn check(&self, http: &reqwest::Client, target: &IngressTarget) -> IngressCheck {
+        let mut check = IngressCheck {
+            host: target.host.clone(),
+            path: target.path.clone(),
+            status_code: None,
+            latency_ms: 0,
+            error: None,
+        };
+
+        if let Err(e) = check_allowed_domain(&target.host, &self.allowed_domains) {
+            check.error = Some(e.to_string());
+            return check;
+        }
+
+        let start = Instant::now();
+        let response = http.head(target.url()).send().await;
+        check.latency_ms = start.elapsed().as_millis() as u64;
+
+        match response {
+            Ok(response) => check.status_code = Some(response.status().as_u16()),
+            Err(e) => check.error = Some(e.to_string()),
+        }
+
+        check
+    }
+
+    async fn execute(&self, args: IngressToolArgs) -> Result<ToolResult, ToolError> {
+        let namespace = args.namespace.as_deref().unwrap_or("default");
+        let api: Api<Ingress> = Api::namespaced(self.client.clone(), namespace);
+
+        let ingress = api.get(&args.name).await
+            .map_err(|e| ToolError::ExecutionError(format!(
+                "Failed to get ingress {}/{}: {}", namespace, args.name, e
+            )))?;
+
+        let targets = ingress_targets(&ingress);
+        if targets.is_empty() {
+            return Ok(ToolResult {
+                success: false,
+                output: format!("Ingress {}/{} has no host rules to check", namespace, args.name),
+                error: Some("No hosts found".to_string()),
+                metadata: None,
+            });
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to create HTTP client: {}", e)))?;
+
+        let mut checks = Vec::with_capacity(targets.len());
+        for target in &targets {
+            checks.push(self.check(&http, target).await);
+        }
+
+        let mut output = format!("Ingress {}/{}:\n", namespace, args.name);
+        for check in &checks {
+            match (check.status_code, &check.error) {
+                (Some(code), _) => output.push_str(&format!(
+                    "  {}{} -> {} ({}ms)\n", check.host, check.path, code, check.latency_ms
+                )),
+                (None, Some(error)) => output.push_str(&format!(
+                    "  {}{} -> error: {}\n", check.host, check.path, error
+                )),
+                (None, None) => {}
+            }
+        }
+
+        let failed = checks.iter()
+            .filter(|c| c.status_code.is_none_or(|code| code >= 400))
+            .count();
+
+        Ok(ToolResult {
+            success: failed == 0,
+            output,
+            error: if failed > 0 {
+                Some(format!("{} of {} endpoints are unhealthy", failed, checks.len()))
+            } else {
+                None
+            },
+            metadata: Some(serde_json::json!({
+                "ingress": args.name,
+                "namespace": namespace,
+                "endpoints": checks,
+            })),
+        })
+    }
+}
+
+/// Resolve the host/path pairs exposed by an Ingress. Hosts listed under
+/// `spec.tls` are checked over https, rules without a host are skipped and
+/// rules without paths are checked at "/".
+pub fn ingress_targets(ingress: &Ingress) -> Vec<IngressTarget> {
+    let Some(spec) = &ingress.spec else {
+        return Vec::new();
+    };
+
+    let tls_hosts: HashSet<&str> = spec.tls.iter()
+        .flatten()
+        .flat_map(|tls| tls.hosts.iter().flatten())
+        .map(String::as_str)
+        .collect();
+
+    let mut targets = Vec::new();
+    for rule in spec.rules.iter().flatten() {
+        let Some(host) = &rule.host else {
+            continue;
+        };
+        let scheme = if tls_hosts.contains(host.as_str()) { "https" } else { "http" };
+
+        let paths: Vec<String> = rule.http.as_ref()
+            .map(|http| http.paths.iter()
+                .map(|p| p.path.clone().unwrap_or_else(|| "/".to_string()))
+                .collect())
+            .unwrap_or_default();
+        let paths = if paths.is_empty() { vec!["/".to_string()] } else { paths };
+
+        for path in paths {
+            let target = IngressTarget { scheme, host: host.clone(), path };
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+
+    targets
+}
+
+impl RigTool for IngressTool {
+    const NAME: &'static str = "ingress";
+
+    type Error = ToolError;
+    type Args = IngressToolArgs;
+    type Output = ToolResult;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Check the health of the external endpoints exposed by a Kubernetes Ingress. \
+                         Sends an HTTP HEAD request to every host and path and reports the status \
+                         code and latency of each.".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Name of the Ingress"
+                    },
+                    "namespace": {
+                        "type": "string",
+                        "description": "Namespace of the Ingress (defaults to 'default')"
+                    }
+                },
+                "required": ["name"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let tool = self.clone();
+        
+        // Spawn the execution to avoid Sync issues with kube client
+        tokio::spawn(async move { tool.execute(args).await })
+            .await
+            .map_err(|e| ToolError::InternalError(anyhow::anyhow!("Task join error: {}", e)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::networking::v1::{
+        HTTPIngressPath, HTTPIngressRuleValue, IngressBackend, IngressRule, IngressSpec, IngressTLS,
+    };
+
+    fn rule(host: Option<&str>, paths: &[Option<&str>]) -> IngressRule {
+        IngressRule {
+            host: host.map(str::to_string),
+            http: Some(HTTPIngressRuleValue {
+                paths: paths.iter().map(|p| HTTPIngressPath {
+                    path: p.map(str::to_string),
+                    path_type: "Prefix".to_string(),
+                    backend: IngressBackend::default(),
+                }).collect(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_ingress_targets() {
+        let ingress = Ingress {
+            spec: Some(IngressSpec {
+                rules: Some(vec![
+                    rule(Some("app.example.com"), &[Some("/"), Some("/api"), Some("/api")]),
+                    rule(Some("plain.example.com"), &[None]),
+                    rule(None, &[Some("/ignored")]),
+                ]),
+                tls: Some(vec![IngressTLS {
+                    hosts: Some(vec!["app.example.com".to_string()]),
+                    secret_name: Some("app-tls".to_string()),
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let urls: Vec<String> = ingress_targets(&ingress).iter().map(IngressTarget::url).collect();
+        assert_eq!(urls, vec![
+            "https://app.example.com/",
+            "https://app.example.com/api",
+            "http://plain.example.com/",
+        ]);
+
+        assert!(ingress_targets(&Ingress::default()).is_empty());
+    }
+}
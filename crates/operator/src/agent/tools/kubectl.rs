@@ -15,29 +15,62 @@
 //! 
 //! - **pods**: List or get specific pods
 //! - **namespaces**: List or get specific namespaces  
+//! - **nodes**: List nodes with their readiness, roles and kubelet version;
+//!   `describe` flags unhealthy conditions such as DiskPressure or NotReady
 //! - **services**: List or get specific services
 //! - **deployments**: List or get specific deployments
+//! - **ingresses**: List or get specific ingresses; `describe` also reports
+//!   the expiry of referenced TLS certificates
+//! - **ingressclasses**: List or get specific ingress classes
+//! - **clusterroles**, **roles**: List or get RBAC roles; `describe` summarizes
+//!   the verbs and resources each rule grants
+//! - **clusterrolebindings**, **rolebindings**: List or get RBAC bindings;
+//!   `describe` lists the bound role and its subjects
 //! - **all**: Special resource type that returns pods, services, and deployments
+//! 
+//! ## Resource Usage
+//! 
+//! The `top` verb reports CPU and memory usage of `pods` (the default) or
+//! `nodes` from the metrics.k8s.io API, so it needs metrics-server.
+//! 
+//! ## Network Topology
+//! 
+//! The `port-forward-info` verb reports which pod ports a service forwards to,
+//! without binding any local ports like `kubectl port-forward` would.
+//! 
+//! ## Namespace Whitelist
+//! 
+//! The namespaces the tool may read can be fixed with
+//! `with_namespace_whitelist`, or kept in sync with the comma-separated
+//! `allowed_namespaces` key of a ConfigMap through `watch_whitelist_configmap`.
 
 use super::{ToolResult, ToolError};
 use anyhow::Result;
-use k8s_openapi::api::core::v1::{Pod, Namespace, Service, ConfigMap, Secret, Event};
+use chrono::{DateTime, Duration, Utc};
+use k8s_openapi::api::core::v1::{Pod, Namespace, Node, Service, ConfigMap, Secret, Event};
 use k8s_openapi::api::apps::v1::{Deployment, StatefulSet, DaemonSet, ReplicaSet};
 use k8s_openapi::api::batch::v1::{Job, CronJob};
-use k8s_openapi::api::networking::v1::Ingress;
-use kube::{api::{Api, ListParams, DynamicObject}, Client, discovery};
+use k8s_openapi::api::networking::v1::{Ingress, IngressClass};
+use k8s_openapi::api::rbac::v1::{ClusterRole, ClusterRoleBinding, PolicyRule, Role, RoleBinding, RoleRef, Subject};
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use kube::{api::{Api, ApiResource, DynamicObject, GetParams, ListParams}, Client, Resource};
 use kube::core::GroupVersionKind;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool as RigTool;
 use regex::Regex;
-use std::collections::{HashSet, HashMap};
+use std::collections::{BTreeMap, HashSet, HashMap};
+use std::sync::{Arc, RwLock};
+use futures::StreamExt;
+use kube::runtime::watcher::{self, Event as WatchEvent};
 use tokio;
+use tracing::{error, info};
 use kube::Config;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_yaml;
 
 /// Arguments for KubectlTool execution
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KubectlToolArgs {
     pub verb: String,
     pub resource: Option<String>,
@@ -46,6 +79,9 @@ pub struct KubectlToolArgs {
     pub tail_lines: Option<i64>, // Number of lines to return from the end of the logs
     pub field_selector: Option<String>, // Field selector for filtering resources (e.g., "status.phase=Running")
     pub label_selector: Option<String>, // Label selector for filtering resources (e.g., "app=nginx")
+    pub container: Option<String>, // Container to read logs from; required for pods with several containers
+    pub previous: Option<bool>, // Read logs of the previous, terminated instance of the container
+    pub as_of_resource_version: Option<String>, // Read resources no older than this resourceVersion
     // We might want to add a field for 'raw_options' or similar in the future
     // for flags that don't fit neatly into the above.
     // For now, keeping it simple.
@@ -56,7 +92,8 @@ pub struct KubectlToolArgs {
 pub struct KubectlTool {
     client: Client,
     allowed_verbs: HashSet<String>,
-    namespace_whitelist: Option<Vec<String>>,
+    /// Shared with clones, so a watched ConfigMap updates every copy
+    namespace_whitelist: Arc<RwLock<Option<Vec<String>>>>,
 }
 
 impl KubectlTool {
@@ -68,11 +105,12 @@ impl KubectlTool {
         allowed_verbs.insert("logs".to_string());
         allowed_verbs.insert("top".to_string());
         allowed_verbs.insert("events".to_string());
+        allowed_verbs.insert("port-forward-info".to_string());
         
         Self {
             client,
             allowed_verbs,
-            namespace_whitelist: None,
+            namespace_whitelist: Arc::new(RwLock::new(None)),
         }
     }
     
@@ -105,10 +143,42 @@ impl KubectlTool {
     
     /// Restrict to specific namespaces
     pub fn with_namespace_whitelist(mut self, namespaces: Vec<String>) -> Self {
-        self.namespace_whitelist = Some(namespaces);
+        self.namespace_whitelist = Arc::new(RwLock::new(Some(namespaces)));
         self
     }
     
+    /// Keep the namespace whitelist in sync with the `allowed_namespaces`
+    /// key of a ConfigMap, reloading it on every change. Without the key
+    /// every namespace is allowed; deleting the ConfigMap keeps the last
+    /// whitelist.
+    pub fn watch_whitelist_configmap(&self, client: Client, namespace: &str, name: &str) -> tokio::task::JoinHandle<()> {
+        let config_maps: Api<ConfigMap> = Api::namespaced(client, namespace);
+        let watcher_config = watcher::Config::default().fields(&format!("metadata.name={}", name));
+        let whitelist = self.namespace_whitelist.clone();
+        let name = name.to_string();
+        
+        tokio::spawn(async move {
+            info!("Watching ConfigMap {} for the kubectl namespace whitelist", name);
+            let reload = |config_map: &ConfigMap| {
+                let namespaces = allowed_namespaces(config_map);
+                info!("Reloaded kubectl namespace whitelist from ConfigMap {}: {:?}", name, namespaces);
+                *whitelist.write().unwrap_or_else(|e| e.into_inner()) = namespaces;
+            };
+            
+            let mut events = watcher::watcher(config_maps, watcher_config).boxed();
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(WatchEvent::Applied(config_map)) => reload(&config_map),
+                    Ok(WatchEvent::Restarted(config_maps)) => config_maps.iter().for_each(reload),
+                    Ok(WatchEvent::Deleted(_)) => {
+                        info!("ConfigMap {} deleted; keeping the current namespace whitelist", name);
+                    }
+                    Err(e) => error!("Namespace whitelist watch error: {}", e),
+                }
+            }
+        })
+    }
+    
     /// Get cluster context information for agent initialization
     pub async fn get_cluster_context(&self) -> Result<String> {
         let mut context = Vec::new();
@@ -130,24 +200,56 @@ impl KubectlTool {
         
         // List supported resource types
         let supported_resources = vec![
-            "pods", "namespaces", "services", "deployments", "statefulsets", 
+            "pods", "namespaces", "nodes", "services", "deployments", "statefulsets", 
             "daemonsets", "replicasets", "jobs", "cronjobs", "configmaps", 
-            "secrets", "ingresses", "all"
+            "secrets", "ingresses", "ingressclasses", "clusterroles", "roles",
+            "clusterrolebindings", "rolebindings", "all"
         ];
         context.push(format!("Supported resources: {}", supported_resources.join(", ")));
         
         Ok(context.join("\n"))
     }
     
-    /// Execute kubectl command via Kubernetes API
-    async fn execute_command(&self, args: &KubectlToolArgs) -> Result<String> {
-        match args.verb.as_str() {
-            "get" => self.execute_get(args).await,
+    /// Execute kubectl command via Kubernetes API, returning its output and,
+    /// for `get`, the observed `resourceVersion`
+    async fn execute_command(&self, args: &KubectlToolArgs) -> Result<(String, Option<String>)> {
+        let output = match args.verb.as_str() {
+            "get" => return self.execute_get(args).await,
             "describe" => self.execute_describe(args).await,
             "logs" => self.execute_logs(args).await,
-            "top" => Ok("Top command not yet implemented".to_string()),
+            "top" => self.execute_top(args).await,
             "events" => self.execute_events(args).await,
+            "port-forward-info" => self.execute_port_forward_info(args).await,
             _ => Err(anyhow::anyhow!("Unsupported verb: {}", args.verb)),
+        }?;
+        Ok((output, None))
+    }
+    
+    /// Fetch only the metadata of a resource to learn its current
+    /// `resourceVersion`, a cheap way to check whether it changed
+    pub async fn fetch_resource_version(&self, resource_type: &str, name: &str, namespace: Option<&str>) -> Result<String> {
+        let client = self.client.clone();
+        let ns = namespace.unwrap_or("default");
+        match resource_type {
+            "pods" | "pod" => metadata_resource_version(Api::<Pod>::namespaced(client, ns), name).await,
+            "namespaces" | "namespace" | "ns" => metadata_resource_version(Api::<Namespace>::all(client), name).await,
+            "nodes" | "node" | "no" => metadata_resource_version(Api::<Node>::all(client), name).await,
+            "deployments" | "deployment" | "deploy" => metadata_resource_version(Api::<Deployment>::namespaced(client, ns), name).await,
+            "services" | "service" | "svc" => metadata_resource_version(Api::<Service>::namespaced(client, ns), name).await,
+            "statefulsets" | "statefulset" | "sts" => metadata_resource_version(Api::<StatefulSet>::namespaced(client, ns), name).await,
+            "daemonsets" | "daemonset" | "ds" => metadata_resource_version(Api::<DaemonSet>::namespaced(client, ns), name).await,
+            "replicasets" | "replicaset" | "rs" => metadata_resource_version(Api::<ReplicaSet>::namespaced(client, ns), name).await,
+            "jobs" | "job" => metadata_resource_version(Api::<Job>::namespaced(client, ns), name).await,
+            "cronjobs" | "cronjob" | "cj" => metadata_resource_version(Api::<CronJob>::namespaced(client, ns), name).await,
+            "configmaps" | "configmap" | "cm" => metadata_resource_version(Api::<ConfigMap>::namespaced(client, ns), name).await,
+            "secrets" | "secret" => metadata_resource_version(Api::<Secret>::namespaced(client, ns), name).await,
+            "ingresses" | "ingress" | "ing" => metadata_resource_version(Api::<Ingress>::namespaced(client, ns), name).await,
+            "ingressclasses" | "ingressclass" => metadata_resource_version(Api::<IngressClass>::all(client), name).await,
+            "clusterroles" | "clusterrole" => metadata_resource_version(Api::<ClusterRole>::all(client), name).await,
+            "roles" | "role" => metadata_resource_version(Api::<Role>::namespaced(client, ns), name).await,
+            "clusterrolebindings" | "clusterrolebinding" => metadata_resource_version(Api::<ClusterRoleBinding>::all(client), name).await,
+            "rolebindings" | "rolebinding" => metadata_resource_version(Api::<RoleBinding>::namespaced(client, ns), name).await,
+            _ => Err(anyhow::anyhow!("Resource type '{}' not supported for version checks", resource_type)),
         }
     }
     
@@ -181,7 +283,8 @@ impl KubectlTool {
         format!("{}\n{}", headers, rows.join("\n"))
     }
     
-    /// Build ListParams with optional field and label selectors
+    /// Build ListParams with optional field and label selectors and
+    /// resource version
     fn build_list_params(&self, args: &KubectlToolArgs) -> ListParams {
         let mut lp = ListParams::default();
         
@@ -193,10 +296,24 @@ impl KubectlTool {
             lp = lp.labels(label_selector);
         }
         
+        if let Some(resource_version) = &args.as_of_resource_version {
+            lp = lp.at(resource_version);
+        }
+        
         lp
     }
     
-    async fn execute_get(&self, args: &KubectlToolArgs) -> Result<String> {
+    /// Build GetParams, reading no older than `as_of_resource_version` if set
+    fn build_get_params(&self, args: &KubectlToolArgs) -> GetParams {
+        match &args.as_of_resource_version {
+            Some(resource_version) => GetParams::at(resource_version),
+            None => GetParams::default(),
+        }
+    }
+    
+    /// Get or list resources, returning the output along with the
+    /// `resourceVersion` the API server answered with
+    async fn execute_get(&self, args: &KubectlToolArgs) -> Result<(String, Option<String>)> {
         let resource = args.resource.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Missing resource type for 'get' verb"))?;
         
@@ -205,7 +322,8 @@ impl KubectlTool {
             return self.execute_get_all(args).await;
         }
         
-        match resource.as_str() {
+        let mut resource_version = None;
+        let output = match resource.as_str() {
             "pods" | "pod" => {
                 if let Some(name) = &args.name {
                     let namespace_to_use = args.namespace.as_deref();
@@ -215,6 +333,7 @@ impl KubectlTool {
                         let lp = self.build_list_params(args);
                         match all_pods_api.list(&lp).await {
                             Ok(pod_list) => {
+                                resource_version = pod_list.metadata.resource_version.clone();
                                 let mut found_pods = Vec::new();
                                 for pod in pod_list.items {
                                     if pod.metadata.name.as_deref() == Some(name) {
@@ -236,8 +355,11 @@ impl KubectlTool {
                         // Get specific pod in a specific namespace (or default)
                         let ns = namespace_to_use.unwrap_or("default");
                         let specific_pods_api: Api<Pod> = Api::namespaced(self.client.clone(), ns);
-                        match specific_pods_api.get(name).await {
-                            Ok(pod) => Ok(serde_json::to_string_pretty(&pod)?),
+                        match specific_pods_api.get_with(name, &self.build_get_params(args)).await {
+                            Ok(pod) => {
+                                resource_version = pod.metadata.resource_version.clone();
+                                Ok(serde_json::to_string_pretty(&pod)?)
+                            }
                             Err(e) => Err(anyhow::anyhow!("Failed to get pod '{}' in namespace '{}': {}", name, ns, e)),
                         }
                     }
@@ -251,6 +373,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match pods_api.list(&lp).await {
                         Ok(pod_list) => {
+                            resource_version = pod_list.metadata.resource_version.clone();
                             let summary: Vec<String> = pod_list.items.iter().map(|pod| {
                                 format!("{}\t{}\t{}\t{}",
                                     pod.metadata.namespace.as_ref().unwrap_or(&"<unknown>".to_string()),
@@ -274,8 +397,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     // Get specific namespace
-                    match namespaces.get(name).await {
-                        Ok(ns) => Ok(serde_json::to_string_pretty(&ns)?),
+                    match namespaces.get_with(name, &self.build_get_params(args)).await {
+                        Ok(ns) => {
+                            resource_version = ns.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&ns)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get namespace: {}", e)),
                     }
                 } else {
@@ -283,6 +409,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match namespaces.list(&lp).await {
                         Ok(ns_list) => {
+                            resource_version = ns_list.metadata.resource_version.clone();
                             let summary: Vec<String> = ns_list.items.iter().map(|ns| {
                                 format!("{}\t{}\t{}", 
                                     ns.metadata.name.as_ref().unwrap_or(&"<unknown>".to_string()),
@@ -300,14 +427,53 @@ impl KubectlTool {
                     }
                 }
             }
+            "nodes" | "node" | "no" => {
+                let api: Api<Node> = Api::all(self.client.clone());
+                
+                if let Some(name) = &args.name {
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(node) => {
+                            resource_version = node.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&node)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get node '{}': {}", name, e)),
+                    }
+                } else {
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(node_list) => {
+                            resource_version = node_list.metadata.resource_version.clone();
+                            let now = Utc::now();
+                            let summary: Vec<String> = node_list.items.iter().map(|node| {
+                                format!("{}\t{}\t{}\t{}\t{}",
+                                    node.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    node_status(node),
+                                    node_roles(node),
+                                    node.metadata.creation_timestamp.as_ref()
+                                        .map(|t| format_age(t.0, now))
+                                        .unwrap_or_else(|| "<unknown>".to_string()),
+                                    node.status.as_ref()
+                                        .and_then(|s| s.node_info.as_ref())
+                                        .map_or("<unknown>", |info| info.kubelet_version.as_str())
+                                )
+                            }).collect();
+                            Ok(format!("NAME\tSTATUS\tROLES\tAGE\tVERSION\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list nodes: {}", e)),
+                    }
+                }
+            }
             "deployments" | "deployment" | "deploy" => {
                 let namespace = args.namespace.as_deref().unwrap_or("default");
                 
                 if let Some(name) = &args.name {
                     // Get specific deployment
                     let api: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(deploy) => Ok(serde_json::to_string_pretty(&deploy)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(deploy) => {
+                            resource_version = deploy.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&deploy)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get deployment '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -321,6 +487,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(deploy_list) => {
+                            resource_version = deploy_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 deploy_list.items,
                                 "deployment",
@@ -343,8 +510,11 @@ impl KubectlTool {
                 if let Some(name) = &args.name {
                     // Get specific service
                     let api: Api<Service> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(svc) => Ok(serde_json::to_string_pretty(&svc)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(svc) => {
+                            resource_version = svc.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&svc)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get service '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -358,6 +528,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(svc_list) => {
+                            resource_version = svc_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 svc_list.items,
                                 "service",
@@ -379,8 +550,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     let api: Api<StatefulSet> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(sts) => Ok(serde_json::to_string_pretty(&sts)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(sts) => {
+                            resource_version = sts.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&sts)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get statefulset '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -393,6 +567,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(sts_list) => {
+                            resource_version = sts_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 sts_list.items,
                                 "statefulset",
@@ -414,8 +589,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     let api: Api<DaemonSet> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(ds) => Ok(serde_json::to_string_pretty(&ds)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(ds) => {
+                            resource_version = ds.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&ds)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get daemonset '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -428,6 +606,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(ds_list) => {
+                            resource_version = ds_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 ds_list.items,
                                 "daemonset",
@@ -449,8 +628,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     let api: Api<Job> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(job) => Ok(serde_json::to_string_pretty(&job)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(job) => {
+                            resource_version = job.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&job)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get job '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -463,6 +645,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(job_list) => {
+                            resource_version = job_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 job_list.items,
                                 "job",
@@ -484,8 +667,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     let api: Api<CronJob> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(cj) => Ok(serde_json::to_string_pretty(&cj)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(cj) => {
+                            resource_version = cj.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&cj)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get cronjob '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -498,6 +684,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(cj_list) => {
+                            resource_version = cj_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 cj_list.items,
                                 "cronjob",
@@ -519,8 +706,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     let api: Api<ConfigMap> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(cm) => Ok(serde_json::to_string_pretty(&cm)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(cm) => {
+                            resource_version = cm.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&cm)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get configmap '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -533,6 +723,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(cm_list) => {
+                            resource_version = cm_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 cm_list.items,
                                 "configmap",
@@ -554,8 +745,11 @@ impl KubectlTool {
                 
                 if let Some(name) = &args.name {
                     let api: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
-                    match api.get(name).await {
-                        Ok(secret) => Ok(serde_json::to_string_pretty(&secret)?),
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(secret) => {
+                            resource_version = secret.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&secret)?)
+                        }
                         Err(e) => Err(anyhow::anyhow!("Failed to get secret '{}' in namespace '{}': {}", name, namespace, e)),
                     }
                 } else {
@@ -568,6 +762,7 @@ impl KubectlTool {
                     let lp = self.build_list_params(args);
                     match api.list(&lp).await {
                         Ok(secret_list) => {
+                            resource_version = secret_list.metadata.resource_version.clone();
                             let formatted = self.format_resource_list(
                                 secret_list.items,
                                 "secret",
@@ -584,14 +779,222 @@ impl KubectlTool {
                     }
                 }
             }
+            "ingresses" | "ingress" | "ing" => {
+                let namespace = args.namespace.as_deref().unwrap_or("default");
+                
+                if let Some(name) = &args.name {
+                    let api: Api<Ingress> = Api::namespaced(self.client.clone(), namespace);
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(ingress) => {
+                            resource_version = ingress.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&ingress)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get ingress '{}' in namespace '{}': {}", name, namespace, e)),
+                    }
+                } else {
+                    let api: Api<Ingress> = match args.namespace.as_deref() {
+                        Some("all") => Api::all(self.client.clone()),
+                        Some(ns) => Api::namespaced(self.client.clone(), ns),
+                        None => Api::namespaced(self.client.clone(), "default"),
+                    };
+                    
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(ingress_list) => {
+                            resource_version = ingress_list.metadata.resource_version.clone();
+                            let summary: Vec<String> = ingress_list.items.iter().map(|ingress| {
+                                let spec = ingress.spec.as_ref();
+                                let hosts: Vec<String> = spec
+                                    .and_then(|s| s.rules.as_ref())
+                                    .map(|rules| rules.iter().filter_map(|r| r.host.clone()).collect())
+                                    .unwrap_or_default();
+                                format!("{}\t{}\t{}\t{}\t{}",
+                                    ingress.metadata.namespace.as_deref().unwrap_or("<unknown>"),
+                                    ingress.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    spec.and_then(|s| s.ingress_class_name.as_deref()).unwrap_or("<none>"),
+                                    if hosts.is_empty() { "*".to_string() } else { hosts.join(",") },
+                                    if spec.and_then(|s| s.tls.as_ref()).is_some_and(|tls| !tls.is_empty()) { "80, 443" } else { "80" }
+                                )
+                            }).collect();
+                            Ok(format!("NAMESPACE\tNAME\tCLASS\tHOSTS\tPORTS\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list ingresses: {}", e)),
+                    }
+                }
+            }
+            "ingressclasses" | "ingressclass" => {
+                let api: Api<IngressClass> = Api::all(self.client.clone());
+                
+                if let Some(name) = &args.name {
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(class) => {
+                            resource_version = class.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&class)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get ingressclass '{}': {}", name, e)),
+                    }
+                } else {
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(class_list) => {
+                            resource_version = class_list.metadata.resource_version.clone();
+                            let summary: Vec<String> = class_list.items.iter().map(|class| {
+                                format!("{}\t{}",
+                                    class.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    class.spec.as_ref()
+                                        .and_then(|s| s.controller.as_deref())
+                                        .unwrap_or("<none>")
+                                )
+                            }).collect();
+                            Ok(format!("NAME\tCONTROLLER\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list ingressclasses: {}", e)),
+                    }
+                }
+            }
+            "clusterroles" | "clusterrole" => {
+                let api: Api<ClusterRole> = Api::all(self.client.clone());
+                
+                if let Some(name) = &args.name {
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(role) => {
+                            resource_version = role.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&role)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get clusterrole '{}': {}", name, e)),
+                    }
+                } else {
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(role_list) => {
+                            resource_version = role_list.metadata.resource_version.clone();
+                            let summary: Vec<String> = role_list.items.iter().map(|role| {
+                                format!("{}\t{}",
+                                    role.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    role.rules.as_ref().map_or(0, |r| r.len())
+                                )
+                            }).collect();
+                            Ok(format!("NAME\tRULES\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list clusterroles: {}", e)),
+                    }
+                }
+            }
+            "roles" | "role" => {
+                let namespace = args.namespace.as_deref().unwrap_or("default");
+                
+                if let Some(name) = &args.name {
+                    let api: Api<Role> = Api::namespaced(self.client.clone(), namespace);
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(role) => {
+                            resource_version = role.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&role)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get role '{}' in namespace '{}': {}", name, namespace, e)),
+                    }
+                } else {
+                    let api: Api<Role> = match args.namespace.as_deref() {
+                        Some("all") => Api::all(self.client.clone()),
+                        Some(ns) => Api::namespaced(self.client.clone(), ns),
+                        None => Api::namespaced(self.client.clone(), "default"),
+                    };
+                    
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(role_list) => {
+                            resource_version = role_list.metadata.resource_version.clone();
+                            let summary: Vec<String> = role_list.items.iter().map(|role| {
+                                format!("{}\t{}\t{}",
+                                    role.metadata.namespace.as_deref().unwrap_or("<unknown>"),
+                                    role.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    role.rules.as_ref().map_or(0, |r| r.len())
+                                )
+                            }).collect();
+                            Ok(format!("NAMESPACE\tNAME\tRULES\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list roles: {}", e)),
+                    }
+                }
+            }
+            "clusterrolebindings" | "clusterrolebinding" => {
+                let api: Api<ClusterRoleBinding> = Api::all(self.client.clone());
+                
+                if let Some(name) = &args.name {
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(binding) => {
+                            resource_version = binding.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&binding)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get clusterrolebinding '{}': {}", name, e)),
+                    }
+                } else {
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(binding_list) => {
+                            resource_version = binding_list.metadata.resource_version.clone();
+                            let summary: Vec<String> = binding_list.items.iter().map(|binding| {
+                                format!("{}\t{}/{}\t{}",
+                                    binding.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    binding.role_ref.kind,
+                                    binding.role_ref.name,
+                                    format_subjects(binding.subjects.as_deref().unwrap_or_default())
+                                )
+                            }).collect();
+                            Ok(format!("NAME\tROLE\tSUBJECTS\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list clusterrolebindings: {}", e)),
+                    }
+                }
+            }
+            "rolebindings" | "rolebinding" => {
+                let namespace = args.namespace.as_deref().unwrap_or("default");
+                
+                if let Some(name) = &args.name {
+                    let api: Api<RoleBinding> = Api::namespaced(self.client.clone(), namespace);
+                    match api.get_with(name, &self.build_get_params(args)).await {
+                        Ok(binding) => {
+                            resource_version = binding.metadata.resource_version.clone();
+                            Ok(serde_json::to_string_pretty(&binding)?)
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to get rolebinding '{}' in namespace '{}': {}", name, namespace, e)),
+                    }
+                } else {
+                    let api: Api<RoleBinding> = match args.namespace.as_deref() {
+                        Some("all") => Api::all(self.client.clone()),
+                        Some(ns) => Api::namespaced(self.client.clone(), ns),
+                        None => Api::namespaced(self.client.clone(), "default"),
+                    };
+                    
+                    let lp = self.build_list_params(args);
+                    match api.list(&lp).await {
+                        Ok(binding_list) => {
+                            resource_version = binding_list.metadata.resource_version.clone();
+                            let summary: Vec<String> = binding_list.items.iter().map(|binding| {
+                                format!("{}\t{}\t{}/{}\t{}",
+                                    binding.metadata.namespace.as_deref().unwrap_or("<unknown>"),
+                                    binding.metadata.name.as_deref().unwrap_or("<unknown>"),
+                                    binding.role_ref.kind,
+                                    binding.role_ref.name,
+                                    format_subjects(binding.subjects.as_deref().unwrap_or_default())
+                                )
+                            }).collect();
+                            Ok(format!("NAMESPACE\tNAME\tROLE\tSUBJECTS\n{}", summary.join("\n")))
+                        }
+                        Err(e) => Err(anyhow::anyhow!("Failed to list rolebindings: {}", e)),
+                    }
+                }
+            }
             _ => Ok(format!("Resource type '{}' not yet implemented", resource)),
-        }
+        }?;
+        
+        Ok((output, resource_version))
     }
     
-    /// Execute "get all" to return common workload resources
-    async fn execute_get_all(&self, args: &KubectlToolArgs) -> Result<String> {
-        let namespace = args.namespace.as_deref().unwrap_or("default");
+    /// Execute "get all" to return common workload resources, along with the
+    /// `resourceVersion` of the last list
+    async fn execute_get_all(&self, args: &KubectlToolArgs) -> Result<(String, Option<String>)> {
         let mut output = Vec::new();
+        let mut resource_version = None;
         let lp = self.build_list_params(args);
         
         // Get pods
@@ -602,6 +1005,7 @@ impl KubectlTool {
         };
         
         if let Ok(pod_list) = pods_api.list(&lp).await {
+            resource_version = pod_list.metadata.resource_version.clone();
             if !pod_list.items.is_empty() {
                 output.push("=== PODS ===".to_string());
                 let formatted = self.format_resource_list(
@@ -626,6 +1030,7 @@ impl KubectlTool {
         };
         
         if let Ok(svc_list) = svc_api.list(&lp).await {
+            resource_version = svc_list.metadata.resource_version.clone();
             if !svc_list.items.is_empty() {
                 output.push("\n=== SERVICES ===".to_string());
                 let formatted = self.format_resource_list(
@@ -650,6 +1055,7 @@ impl KubectlTool {
         };
         
         if let Ok(deploy_list) = deploy_api.list(&lp).await {
+            resource_version = deploy_list.metadata.resource_version.clone();
             if !deploy_list.items.is_empty() {
                 output.push("\n=== DEPLOYMENTS ===".to_string());
                 let formatted = self.format_resource_list(
@@ -674,6 +1080,7 @@ impl KubectlTool {
         };
         
         if let Ok(sts_list) = sts_api.list(&lp).await {
+            resource_version = sts_list.metadata.resource_version.clone();
             if !sts_list.items.is_empty() {
                 output.push("\n=== STATEFULSETS ===".to_string());
                 let formatted = self.format_resource_list(
@@ -698,6 +1105,7 @@ impl KubectlTool {
         };
         
         if let Ok(ds_list) = ds_api.list(&lp).await {
+            resource_version = ds_list.metadata.resource_version.clone();
             if !ds_list.items.is_empty() {
                 output.push("\n=== DAEMONSETS ===".to_string());
                 let formatted = self.format_resource_list(
@@ -715,9 +1123,9 @@ impl KubectlTool {
         }
         
         if output.is_empty() {
-            Ok("No resources found".to_string())
+            Ok(("No resources found".to_string(), resource_version))
         } else {
-            Ok(output.join("\n"))
+            Ok((output.join("\n"), resource_version))
         }
     }
     
@@ -733,13 +1141,9 @@ impl KubectlTool {
                 let api: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
                 match api.get(resource_name).await {
                     Ok(pod) => {
-                        // For a more detailed "describe" like output, we would typically gather
-                        // related events, logs, and more details.
-                        // For now, we'll return the pretty printed Pod spec, similar to `kubectl get pod <name> -o yaml`
-                        // but a true describe often involves more.
-                        // A full `kubectl describe` output is quite complex to replicate perfectly.
-                        // This will give a structured YAML/JSON view of the pod.
-                        Ok(serde_yaml::to_string(&pod)?)
+                        // The pod as YAML, followed by its events like `kubectl describe`
+                        let described = serde_yaml::to_string(&pod)?;
+                        Ok(self.append_events(described, "Pod", resource_name, namespace).await)
                     }
                     Err(e) => Err(anyhow::anyhow!("Failed to get pod '{}' in namespace '{}': {}", resource_name, namespace, e)),
                 }
@@ -754,10 +1158,20 @@ impl KubectlTool {
                     Err(e) => Err(anyhow::anyhow!("Failed to get namespace '{}': {}", resource_name, e)),
                 }
             }
+            "node" | "nodes" | "no" => {
+                let api: Api<Node> = Api::all(self.client.clone());
+                match api.get(resource_name).await {
+                    Ok(node) => Ok(describe_node(&node)),
+                    Err(e) => Err(anyhow::anyhow!("Failed to get node '{}': {}", resource_name, e)),
+                }
+            }
             "deployment" | "deployments" | "deploy" => {
                 let api: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
                 match api.get(resource_name).await {
-                    Ok(deploy) => Ok(serde_yaml::to_string(&deploy)?),
+                    Ok(deploy) => {
+                        let described = serde_yaml::to_string(&deploy)?;
+                        Ok(self.append_events(described, "Deployment", resource_name, namespace).await)
+                    }
                     Err(e) => Err(anyhow::anyhow!("Failed to get deployment '{}' in namespace '{}': {}", resource_name, namespace, e)),
                 }
             }
@@ -771,7 +1185,10 @@ impl KubectlTool {
             "statefulset" | "statefulsets" | "sts" => {
                 let api: Api<StatefulSet> = Api::namespaced(self.client.clone(), namespace);
                 match api.get(resource_name).await {
-                    Ok(sts) => Ok(serde_yaml::to_string(&sts)?),
+                    Ok(sts) => {
+                        let described = serde_yaml::to_string(&sts)?;
+                        Ok(self.append_events(described, "StatefulSet", resource_name, namespace).await)
+                    }
                     Err(e) => Err(anyhow::anyhow!("Failed to get statefulset '{}' in namespace '{}': {}", resource_name, namespace, e)),
                 }
             }
@@ -810,30 +1227,207 @@ impl KubectlTool {
                     Err(e) => Err(anyhow::anyhow!("Failed to get secret '{}' in namespace '{}': {}", resource_name, namespace, e)),
                 }
             }
+            "ingress" | "ingresses" | "ing" => {
+                let api: Api<Ingress> = Api::namespaced(self.client.clone(), namespace);
+                match api.get(resource_name).await {
+                    Ok(ingress) => {
+                        let described = serde_yaml::to_string(&ingress)?;
+                        let certificates = self.fetch_tls_certificate_expiry(&ingress, namespace).await;
+                        Ok(annotate_tls_expiry(described, &certificates, Utc::now()))
+                    }
+                    Err(e) => Err(anyhow::anyhow!("Failed to get ingress '{}' in namespace '{}': {}", resource_name, namespace, e)),
+                }
+            }
+            "ingressclass" | "ingressclasses" => {
+                let api: Api<IngressClass> = Api::all(self.client.clone());
+                match api.get(resource_name).await {
+                    Ok(class) => Ok(serde_yaml::to_string(&class)?),
+                    Err(e) => Err(anyhow::anyhow!("Failed to get ingressclass '{}': {}", resource_name, e)),
+                }
+            }
+            "clusterrole" | "clusterroles" => {
+                let api: Api<ClusterRole> = Api::all(self.client.clone());
+                match api.get(resource_name).await {
+                    Ok(role) => Ok(describe_rules(
+                        "ClusterRole",
+                        resource_name,
+                        "in all namespaces",
+                        role.rules.as_deref().unwrap_or_default(),
+                    )),
+                    Err(e) => Err(anyhow::anyhow!("Failed to get clusterrole '{}': {}", resource_name, e)),
+                }
+            }
+            "role" | "roles" => {
+                let api: Api<Role> = Api::namespaced(self.client.clone(), namespace);
+                match api.get(resource_name).await {
+                    Ok(role) => Ok(describe_rules(
+                        "Role",
+                        resource_name,
+                        &format!("in namespace {}", namespace),
+                        role.rules.as_deref().unwrap_or_default(),
+                    )),
+                    Err(e) => Err(anyhow::anyhow!("Failed to get role '{}' in namespace '{}': {}", resource_name, namespace, e)),
+                }
+            }
+            "clusterrolebinding" | "clusterrolebindings" => {
+                let api: Api<ClusterRoleBinding> = Api::all(self.client.clone());
+                match api.get(resource_name).await {
+                    Ok(binding) => Ok(describe_binding(
+                        "ClusterRoleBinding",
+                        resource_name,
+                        "in all namespaces",
+                        &binding.role_ref,
+                        binding.subjects.as_deref().unwrap_or_default(),
+                    )),
+                    Err(e) => Err(anyhow::anyhow!("Failed to get clusterrolebinding '{}': {}", resource_name, e)),
+                }
+            }
+            "rolebinding" | "rolebindings" => {
+                let api: Api<RoleBinding> = Api::namespaced(self.client.clone(), namespace);
+                match api.get(resource_name).await {
+                    Ok(binding) => Ok(describe_binding(
+                        "RoleBinding",
+                        resource_name,
+                        &format!("in namespace {}", namespace),
+                        &binding.role_ref,
+                        binding.subjects.as_deref().unwrap_or_default(),
+                    )),
+                    Err(e) => Err(anyhow::anyhow!("Failed to get rolebinding '{}' in namespace '{}': {}", resource_name, namespace, e)),
+                }
+            }
             // TODO: Add other resource types as needed (e.g., services, deployments)
             _ => Err(anyhow::anyhow!("Describing resource type '{}' is not yet implemented.", resource_type)),
         }
     }
     
+    /// Append the events about an object to its describe output. Failing to
+    /// list them is noted in place of the table rather than failing the describe.
+    async fn append_events(&self, described: String, kind: &str, name: &str, namespace: &str) -> String {
+        let api: Api<Event> = Api::namespaced(self.client.clone(), namespace);
+        let lp = ListParams::default()
+            .fields(&format!("involvedObject.kind={},involvedObject.name={}", kind, name));
+        let events = match api.list(&lp).await {
+            Ok(list) => format_describe_events(list.items, Utc::now()),
+            Err(e) => format!("Events: unable to list events: {}\n", e),
+        };
+        format!("{}{}", described, events)
+    }
+    
+    /// Look up the expiry of every TLS certificate referenced by an ingress
+    async fn fetch_tls_certificate_expiry(
+        &self,
+        ingress: &Ingress,
+        namespace: &str,
+    ) -> Vec<(String, std::result::Result<DateTime<Utc>, String>)> {
+        let secret_names: Vec<String> = ingress.spec.as_ref()
+            .and_then(|s| s.tls.as_ref())
+            .map(|tls| tls.iter().filter_map(|t| t.secret_name.clone()).collect())
+            .unwrap_or_default();
+        
+        let api: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+        let mut certificates = Vec::new();
+        for secret_name in secret_names {
+            let expiry = match api.get(&secret_name).await {
+                Ok(secret) => secret.data.as_ref()
+                    .and_then(|data| data.get("tls.crt"))
+                    .ok_or_else(|| "secret has no tls.crt entry".to_string())
+                    .and_then(|crt| certificate_not_after(&crt.0).map_err(|e| e.to_string())),
+                Err(e) => Err(format!("failed to fetch secret: {}", e)),
+            };
+            certificates.push((secret_name, expiry));
+        }
+        certificates
+    }
+    
     async fn execute_logs(&self, args: &KubectlToolArgs) -> Result<String> {
         let pod_name = args.name.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Pod name is required for logs"))?;
         let namespace = args.namespace.as_deref().unwrap_or("default");
 
-        // TODO: Add support for specifying container name if a pod has multiple containers.
-        // For now, it will get logs from the first container (or the only one).
         let pods_api: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
         
+        // Without an explicit container, look at the pod to pick its only one
+        let container = match &args.container {
+            Some(container) => container.clone(),
+            None => {
+                let pod = pods_api.get(pod_name).await
+                    .map_err(|e| anyhow::anyhow!("Failed to get pod '{}' in namespace '{}': {}", pod_name, namespace, e))?;
+                log_container(&pod)?
+            }
+        };
+        
         // Set default tail_lines to 100 if not specified
         let mut lp = kube::api::LogParams::default();
         lp.tail_lines = Some(args.tail_lines.unwrap_or(100));
+        lp.container = Some(container);
+        lp.previous = args.previous.unwrap_or(false);
 
         match pods_api.logs(pod_name, &lp).await {
             Ok(logs) => Ok(logs),
+            // Usually the container never restarted; pass the API's
+            // explanation through as is
+            Err(kube::Error::Api(response)) if lp.previous => Err(anyhow::anyhow!(
+                "No previous logs for pod '{}' in namespace '{}': {}", pod_name, namespace, response.message
+            )),
             Err(e) => Err(anyhow::anyhow!("Failed to get logs for pod '{}' in namespace '{}': {}", pod_name, namespace, e)),
         }
     }
     
+    /// Show CPU and memory usage of pods or nodes like `kubectl top`, read
+    /// from the metrics.k8s.io API served by metrics-server
+    async fn execute_top(&self, args: &KubectlToolArgs) -> Result<String> {
+        let resource = args.resource.as_deref().unwrap_or("pods");
+        match resource {
+            "pods" | "pod" | "po" => {
+                let all_namespaces = args.namespace.as_deref() == Some("all");
+                let api: Api<DynamicObject> = if all_namespaces {
+                    Api::all_with(self.client.clone(), &metrics_resource("PodMetrics", "pods"))
+                } else {
+                    let namespace = args.namespace.as_deref().unwrap_or("default");
+                    Api::namespaced_with(self.client.clone(), namespace, &metrics_resource("PodMetrics", "pods"))
+                };
+                let metrics = self.list_metrics(&api, args).await?;
+                Ok(format_pod_metrics(&metrics, all_namespaces))
+            }
+            "nodes" | "node" | "no" => {
+                let api: Api<DynamicObject> = Api::all_with(self.client.clone(), &metrics_resource("NodeMetrics", "nodes"));
+                let metrics = self.list_metrics(&api, args).await?;
+
+                // Percentages are relative to what each node can allocate;
+                // without it they are left unknown
+                let nodes: Api<Node> = Api::all(self.client.clone());
+                let allocatable: HashMap<String, BTreeMap<String, Quantity>> = match nodes.list(&self.build_list_params(args)).await {
+                    Ok(list) => list.items.into_iter()
+                        .filter_map(|node| Some((node.metadata.name?, node.status?.allocatable?)))
+                        .collect(),
+                    Err(e) => {
+                        error!("Failed to list nodes for their allocatable resources: {}", e);
+                        HashMap::new()
+                    }
+                };
+                Ok(format_node_metrics(&metrics, &allocatable))
+            }
+            _ => Err(anyhow::anyhow!("Resource type '{}' not supported for 'top', use pods or nodes", resource)),
+        }
+    }
+
+    /// The named metrics object, or every one matching the selectors
+    async fn list_metrics(&self, api: &Api<DynamicObject>, args: &KubectlToolArgs) -> Result<Vec<DynamicObject>> {
+        let result = match &args.name {
+            Some(name) => api.get(name).await.map(|metrics| vec![metrics]),
+            None => api.list(&self.build_list_params(args)).await.map(|list| list.items),
+        };
+        result.map_err(|e| match e {
+            kube::Error::Api(ref response) if response.code == 404 && args.name.is_none() => {
+                anyhow::anyhow!("Metrics API not available, is metrics-server installed? ({})", e)
+            }
+            kube::Error::Api(ref response) if response.code == 503 => {
+                anyhow::anyhow!("Metrics API not available, is metrics-server running? ({})", e)
+            }
+            e => anyhow::anyhow!("Failed to get metrics: {}", e),
+        })
+    }
+
     /// Execute events command to show cluster events
     async fn execute_events(&self, args: &KubectlToolArgs) -> Result<String> {
         let namespace = args.namespace.as_deref();
@@ -892,6 +1486,35 @@ impl KubectlTool {
         }
     }
     
+    /// Report the pods and target ports behind a service's ports.
+    /// 
+    /// Returns a JSON map of `{service_port -> ["pod_name:target_port", ...]}`.
+    async fn execute_port_forward_info(&self, args: &KubectlToolArgs) -> Result<String> {
+        let service_name = args.name.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Service name is required for port-forward-info"))?;
+        let namespace = args.namespace.as_deref().unwrap_or("default");
+        
+        let svc_api: Api<Service> = Api::namespaced(self.client.clone(), namespace);
+        let service = svc_api.get(service_name).await
+            .map_err(|e| anyhow::anyhow!("Failed to get service '{}' in namespace '{}': {}", service_name, namespace, e))?;
+        
+        let selector = service.spec.as_ref()
+            .and_then(|spec| spec.selector.as_ref())
+            .filter(|selector| !selector.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Service '{}' has no pod selector", service_name))?;
+        
+        let label_selector = selector.iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(",");
+        
+        let pods_api: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+        let pods = pods_api.list(&ListParams::default().labels(&label_selector)).await
+            .map_err(|e| anyhow::anyhow!("Failed to list pods for service '{}': {}", service_name, e))?;
+        
+        Ok(serde_json::to_string_pretty(&port_forward_map(&service, &pods.items))?)
+    }
+    
     /// Validate if the command is safe to execute
     fn validate(&self, args: &KubectlToolArgs) -> Result<()> {
         // 1. Check if the verb is allowed by the tool's configuration.
@@ -935,7 +1558,8 @@ impl KubectlTool {
         }
 
         // Validate namespace if whitelist is configured
-        if let Some(ref whitelist) = self.namespace_whitelist {
+        let whitelist = self.namespace_whitelist.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(ref whitelist) = *whitelist {
             if let Some(ref ns) = args.namespace {
                 // Allow "all" to bypass the namespace whitelist check
                 if ns.to_lowercase() != "all" && !whitelist.contains(ns) {
@@ -948,6 +1572,461 @@ impl KubectlTool {
     }
 }
 
+/// The namespaces listed, comma-separated, under the ConfigMap's
+/// `allowed_namespaces` key, or `None` when the key isn't set
+fn allowed_namespaces(config_map: &ConfigMap) -> Option<Vec<String>> {
+    let value = config_map.data.as_ref()?.get("allowed_namespaces")?;
+    Some(value.split(',')
+        .map(str::trim)
+        .filter(|namespace| !namespace.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// The `resourceVersion` of a resource, fetching only its metadata
+async fn metadata_resource_version<K>(api: Api<K>, name: &str) -> Result<String>
+where
+    K: Resource + Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let meta = api.get_metadata(name).await
+        .map_err(|e| anyhow::anyhow!("Failed to get metadata of '{}': {}", name, e))?;
+    meta.metadata.resource_version
+        .ok_or_else(|| anyhow::anyhow!("'{}' has no resourceVersion", name))
+}
+
+/// The container to read logs from when none was given: the pod's only
+/// container, or an error listing the choices
+fn log_container(pod: &Pod) -> Result<String> {
+    let pod_name = pod.metadata.name.as_deref().unwrap_or_default();
+    let spec = pod.spec.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Pod '{}' has no spec", pod_name))?;
+    
+    match spec.containers.as_slice() {
+        [container] => Ok(container.name.clone()),
+        [] => Err(anyhow::anyhow!("Pod '{}' has no containers", pod_name)),
+        containers => {
+            let names: Vec<&str> = containers.iter().map(|c| c.name.as_str()).collect();
+            let mut message = format!(
+                "Pod '{}' has {} containers, specify one with 'container': {}",
+                pod_name, names.len(), names.join(", ")
+            );
+            let init_names: Vec<&str> = spec.init_containers.iter()
+                .flatten()
+                .map(|c| c.name.as_str())
+                .collect();
+            if !init_names.is_empty() {
+                message.push_str(&format!(" (init containers: {})", init_names.join(", ")));
+            }
+            Err(anyhow::anyhow!(message))
+        }
+    }
+}
+
+/// A `metrics.k8s.io/v1beta1` resource, which has no typed API in k8s-openapi
+fn metrics_resource(kind: &str, plural: &str) -> ApiResource {
+    ApiResource::from_gvk_with_plural(&GroupVersionKind::gvk("metrics.k8s.io", "v1beta1", kind), plural)
+}
+
+/// Parse a resource quantity such as "250m", "128Mi" or "1e3" into base
+/// units (cores or bytes)
+fn parse_quantity(quantity: &str) -> Option<f64> {
+    let quantity = quantity.trim();
+    let binary = [("Ki", 1u64 << 10), ("Mi", 1 << 20), ("Gi", 1 << 30), ("Ti", 1 << 40), ("Pi", 1 << 50), ("Ei", 1 << 60)];
+    for (suffix, multiplier) in binary {
+        if let Some(number) = quantity.strip_suffix(suffix) {
+            return number.parse::<f64>().ok().map(|n| n * multiplier as f64);
+        }
+    }
+
+    let decimal = [("n", 1e-9), ("u", 1e-6), ("m", 1e-3), ("k", 1e3), ("M", 1e6), ("G", 1e9), ("T", 1e12), ("P", 1e15), ("E", 1e18)];
+    for (suffix, multiplier) in decimal {
+        if let Some(number) = quantity.strip_suffix(suffix) {
+            return number.parse::<f64>().ok().map(|n| n * multiplier);
+        }
+    }
+
+    quantity.parse().ok()
+}
+
+/// CPU and memory usage in base units from a metrics `usage` map
+fn metrics_usage(usage: &serde_json::Value) -> (f64, f64) {
+    let read = |key: &str| usage.get(key).and_then(|q| q.as_str()).and_then(parse_quantity).unwrap_or(0.0);
+    (read("cpu"), read("memory"))
+}
+
+/// Render usage in the units `kubectl top` uses: millicores and mebibytes
+fn format_usage(cpu: f64, memory: f64) -> (String, String) {
+    (format!("{}m", (cpu * 1000.0).round() as i64), format!("{}Mi", (memory / (1u64 << 20) as f64).round() as i64))
+}
+
+/// `kubectl top pods` output, summing the usage of each pod's containers
+fn format_pod_metrics(metrics: &[DynamicObject], all_namespaces: bool) -> String {
+    if metrics.is_empty() {
+        return "No pod metrics found".to_string();
+    }
+
+    let mut lines = vec![if all_namespaces {
+        "NAMESPACE\tNAME\tCPU(cores)\tMEMORY(bytes)".to_string()
+    } else {
+        "NAME\tCPU(cores)\tMEMORY(bytes)".to_string()
+    }];
+    for pod in metrics {
+        let (cpu, memory) = pod.data.get("containers")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .map(|container| metrics_usage(&container["usage"]))
+            .fold((0.0, 0.0), |(cpu, memory), (c, m)| (cpu + c, memory + m));
+        let (cpu, memory) = format_usage(cpu, memory);
+        let name = pod.metadata.name.as_deref().unwrap_or("<unknown>");
+        if all_namespaces {
+            let namespace = pod.metadata.namespace.as_deref().unwrap_or("<unknown>");
+            lines.push(format!("{}\t{}\t{}\t{}", namespace, name, cpu, memory));
+        } else {
+            lines.push(format!("{}\t{}\t{}", name, cpu, memory));
+        }
+    }
+    lines.join("\n")
+}
+
+/// `kubectl top nodes` output, with usage as a percentage of each node's
+/// allocatable resources when known
+fn format_node_metrics(metrics: &[DynamicObject], allocatable: &HashMap<String, BTreeMap<String, Quantity>>) -> String {
+    if metrics.is_empty() {
+        return "No node metrics found".to_string();
+    }
+
+    let mut lines = vec!["NAME\tCPU(cores)\tCPU%\tMEMORY(bytes)\tMEMORY%".to_string()];
+    for node in metrics {
+        let name = node.metadata.name.as_deref().unwrap_or("<unknown>");
+        let (cpu, memory) = metrics_usage(&node.data["usage"]);
+        let percent = |used: f64, resource: &str| {
+            allocatable.get(name)
+                .and_then(|resources| resources.get(resource))
+                .and_then(|quantity| parse_quantity(&quantity.0))
+                .filter(|total| *total > 0.0)
+                .map_or_else(|| "<unknown>".to_string(), |total| format!("{}%", (used / total * 100.0).round() as i64))
+        };
+        let (cpu_percent, memory_percent) = (percent(cpu, "cpu"), percent(memory, "memory"));
+        let (cpu, memory) = format_usage(cpu, memory);
+        lines.push(format!("{}\t{}\t{}\t{}\t{}", name, cpu, cpu_percent, memory, memory_percent));
+    }
+    lines.join("\n")
+}
+
+/// Map each service port to the `pod:target_port` endpoints it forwards to
+fn port_forward_map(service: &Service, pods: &[Pod]) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    
+    let ports = service.spec.as_ref()
+        .and_then(|spec| spec.ports.as_ref())
+        .cloned()
+        .unwrap_or_default();
+    
+    for port in ports {
+        let key = match &port.name {
+            Some(name) => format!("{}/{} ({})", port.port, port.protocol.as_deref().unwrap_or("TCP"), name),
+            None => format!("{}/{}", port.port, port.protocol.as_deref().unwrap_or("TCP")),
+        };
+        
+        let endpoints: Vec<serde_json::Value> = pods.iter()
+            .filter_map(|pod| {
+                let pod_name = pod.metadata.name.as_ref()?;
+                let target_port = resolve_target_port(port.target_port.as_ref(), port.port, pod)?;
+                Some(serde_json::Value::String(format!("{}:{}", pod_name, target_port)))
+            })
+            .collect();
+        
+        map.insert(key, serde_json::Value::Array(endpoints));
+    }
+    
+    serde_json::Value::Object(map)
+}
+
+/// Days before expiry at which a TLS certificate is flagged
+const TLS_EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Parse a PEM encoded certificate and return its `notAfter` date
+fn certificate_not_after(pem: &[u8]) -> Result<DateTime<Utc>> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(pem)
+        .map_err(|e| anyhow::anyhow!("invalid PEM data: {}", e))?;
+    let cert = pem.parse_x509()
+        .map_err(|e| anyhow::anyhow!("invalid certificate: {}", e))?;
+    DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+        .ok_or_else(|| anyhow::anyhow!("certificate expiry out of range"))
+}
+
+/// Append certificate expiry dates to a describe output, prepending a warning
+/// when any certificate expires within `TLS_EXPIRY_WARNING_DAYS`
+fn annotate_tls_expiry(
+    described: String,
+    certificates: &[(String, std::result::Result<DateTime<Utc>, String>)],
+    now: DateTime<Utc>,
+) -> String {
+    if certificates.is_empty() {
+        return described;
+    }
+    
+    let mut expiring = Vec::new();
+    let mut lines = vec!["TLS certificates:".to_string()];
+    for (secret_name, expiry) in certificates {
+        match expiry {
+            Ok(not_after) => {
+                lines.push(format!("  {}: expires {}", secret_name, not_after.to_rfc3339()));
+                if *not_after - now < Duration::days(TLS_EXPIRY_WARNING_DAYS) {
+                    expiring.push(format!("{} ({})", secret_name, not_after.format("%Y-%m-%d")));
+                }
+            }
+            Err(e) => lines.push(format!("  {}: unable to read certificate: {}", secret_name, e)),
+        }
+    }
+    
+    let mut output = String::new();
+    if !expiring.is_empty() {
+        output.push_str(&format!(
+            "[WARNING] TLS certificate expires within {} days: {}\n",
+            TLS_EXPIRY_WARNING_DAYS,
+            expiring.join(", ")
+        ));
+    }
+    output.push_str(&described);
+    output.push_str(&lines.join("\n"));
+    output.push('\n');
+    output
+}
+
+/// The "Events:" table of `kubectl describe`, oldest first, with repeated
+/// events shown as e.g. "2m (x15 over 1h)"
+fn format_describe_events(mut events: Vec<Event>, now: DateTime<Utc>) -> String {
+    if events.is_empty() {
+        return "Events: <none>\n".to_string();
+    }
+    
+    let last_seen = |event: &Event| event.last_timestamp.as_ref().map(|t| t.0)
+        .or_else(|| event.event_time.as_ref().map(|t| t.0))
+        .or_else(|| event.first_timestamp.as_ref().map(|t| t.0));
+    events.sort_by_key(last_seen);
+    
+    let mut lines = vec![
+        "Events:".to_string(),
+        "  Type\tReason\tAge\tFrom\tMessage".to_string(),
+        "  ----\t------\t----\t----\t-------".to_string(),
+    ];
+    for event in &events {
+        let age = match last_seen(event) {
+            Some(last) => {
+                let mut age = format_age(last, now);
+                let first = event.first_timestamp.as_ref().map(|t| t.0);
+                if let (Some(count), Some(first)) = (event.count.filter(|c| *c > 1), first) {
+                    age.push_str(&format!(" (x{} over {})", count, format_age(first, now)));
+                }
+                age
+            }
+            None => "<unknown>".to_string(),
+        };
+        let from = event.source.as_ref().and_then(|s| s.component.clone())
+            .or_else(|| event.reporting_component.clone().filter(|c| !c.is_empty()))
+            .unwrap_or_default();
+        lines.push(format!(
+            "  {}\t{}\t{}\t{}\t{}",
+            event.type_.as_deref().unwrap_or_default(),
+            event.reason.as_deref().unwrap_or_default(),
+            age,
+            from,
+            event.message.as_deref().unwrap_or_default().replace('\n', " "),
+        ));
+    }
+    
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Node readiness as shown by `kubectl get nodes`, e.g. "Ready" or
+/// "NotReady,SchedulingDisabled"
+fn node_status(node: &Node) -> String {
+    let ready = node.status.as_ref()
+        .and_then(|s| s.conditions.as_ref())
+        .and_then(|conditions| conditions.iter().find(|c| c.type_ == "Ready"));
+    let mut status = match ready.map(|c| c.status.as_str()) {
+        Some("True") => "Ready".to_string(),
+        Some("False") => "NotReady".to_string(),
+        _ => "Unknown".to_string(),
+    };
+    if node.spec.as_ref().and_then(|s| s.unschedulable).unwrap_or(false) {
+        status.push_str(",SchedulingDisabled");
+    }
+    status
+}
+
+/// Roles from the `node-role.kubernetes.io/<role>` and `kubernetes.io/role` labels
+fn node_roles(node: &Node) -> String {
+    let mut roles: Vec<&str> = node.metadata.labels.iter().flatten()
+        .filter_map(|(key, value)| {
+            if let Some(role) = key.strip_prefix("node-role.kubernetes.io/") {
+                Some(role)
+            } else if key == "kubernetes.io/role" {
+                Some(value.as_str())
+            } else {
+                None
+            }
+        })
+        .filter(|role| !role.is_empty())
+        .collect();
+    roles.sort_unstable();
+    roles.dedup();
+    
+    if roles.is_empty() {
+        "<none>".to_string()
+    } else {
+        roles.join(",")
+    }
+}
+
+/// Whether a node condition reports a problem. `Ready` is healthy when
+/// `True`; the pressure and availability conditions are healthy when `False`.
+fn node_condition_unhealthy(condition_type: &str, status: &str) -> bool {
+    match condition_type {
+        "Ready" => status != "True",
+        _ => status != "False",
+    }
+}
+
+/// Summarize a node for `describe`, prefixing unhealthy conditions with `[WARNING]`
+fn describe_node(node: &Node) -> String {
+    let status = node.status.clone().unwrap_or_default();
+    let mut lines = vec![
+        format!("Name: {}", node.metadata.name.as_deref().unwrap_or("<unknown>")),
+        format!("Status: {}", node_status(node)),
+        format!("Roles: {}", node_roles(node)),
+        format!("Version: {}", status.node_info.as_ref().map_or("<unknown>", |info| info.kubelet_version.as_str())),
+    ];
+    
+    if let Some(created) = &node.metadata.creation_timestamp {
+        lines.push(format!("Created: {}", created.0.to_rfc3339()));
+    }
+    
+    let taints = node.spec.as_ref().and_then(|s| s.taints.as_deref()).unwrap_or_default();
+    if taints.is_empty() {
+        lines.push("Taints: <none>".to_string());
+    } else {
+        lines.push("Taints:".to_string());
+        lines.extend(taints.iter().map(|taint| match &taint.value {
+            Some(value) => format!("  - {}={}:{}", taint.key, value, taint.effect),
+            None => format!("  - {}:{}", taint.key, taint.effect),
+        }));
+    }
+    
+    for (label, resources) in [("Capacity", &status.capacity), ("Allocatable", &status.allocatable)] {
+        if let Some(resources) = resources {
+            lines.push(format!("{}:", label));
+            lines.extend(resources.iter().map(|(name, quantity)| format!("  {}: {}", name, quantity.0)));
+        }
+    }
+    
+    lines.push("Conditions:".to_string());
+    for condition in status.conditions.iter().flatten() {
+        let prefix = if node_condition_unhealthy(&condition.type_, &condition.status) {
+            "[WARNING] "
+        } else {
+            "  "
+        };
+        let mut line = format!("{}{}: {}", prefix, condition.type_, condition.status);
+        if let Some(reason) = &condition.reason {
+            line.push_str(&format!(" ({})", reason));
+        }
+        if let Some(message) = &condition.message {
+            line.push_str(&format!(" - {}", message));
+        }
+        lines.push(line);
+    }
+    
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Render the time since `created` the way kubectl does, e.g. "45s", "12m", "3h" or "20d"
+fn format_age(created: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now.signed_duration_since(created);
+    if age.num_days() > 0 {
+        format!("{}d", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m", age.num_minutes())
+    } else {
+        format!("{}s", age.num_seconds().max(0))
+    }
+}
+
+/// Summarize the permissions granted by each rule of a Role or ClusterRole,
+/// e.g. "ClusterRole view grants [get, list, watch] on [pods, services] in all namespaces"
+fn describe_rules(kind: &str, name: &str, scope: &str, rules: &[PolicyRule]) -> String {
+    if rules.is_empty() {
+        return format!("{} {} grants no permissions\n", kind, name);
+    }
+    
+    let mut lines = Vec::new();
+    for rule in rules {
+        let verbs = rule.verbs.join(", ");
+        let line = match (&rule.resources, &rule.non_resource_urls) {
+            (Some(resources), _) if !resources.is_empty() => {
+                let mut line = format!("{} {} grants [{}] on [{}] {}", kind, name, verbs, resources.join(", "), scope);
+                if let Some(names) = rule.resource_names.as_ref().filter(|n| !n.is_empty()) {
+                    line.push_str(&format!(" (only names: {})", names.join(", ")));
+                }
+                line
+            }
+            (_, Some(urls)) if !urls.is_empty() => {
+                format!("{} {} grants [{}] on non-resource URLs [{}]", kind, name, verbs, urls.join(", "))
+            }
+            _ => format!("{} {} grants [{}] on no resources", kind, name, verbs),
+        };
+        lines.push(line);
+    }
+    
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Summarize a RoleBinding or ClusterRoleBinding: the bound role and every subject
+fn describe_binding(kind: &str, name: &str, scope: &str, role_ref: &RoleRef, subjects: &[Subject]) -> String {
+    let mut lines = vec![format!("{} {} binds {} {} {}", kind, name, role_ref.kind, role_ref.name, scope)];
+    if subjects.is_empty() {
+        lines.push("Subjects: <none>".to_string());
+    } else {
+        lines.push("Subjects:".to_string());
+        lines.extend(subjects.iter().map(|subject| format!("  - {}", format_subject(subject))));
+    }
+    
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Render a binding subject, qualifying service accounts with their namespace
+fn format_subject(subject: &Subject) -> String {
+    match (subject.kind.as_str(), &subject.namespace) {
+        ("ServiceAccount", Some(ns)) => format!("ServiceAccount {}/{}", ns, subject.name),
+        (kind, _) => format!("{} {}", kind, subject.name),
+    }
+}
+
+fn format_subjects(subjects: &[Subject]) -> String {
+    if subjects.is_empty() {
+        return "<none>".to_string();
+    }
+    subjects.iter().map(format_subject).collect::<Vec<_>>().join(", ")
+}
+
+/// Resolve a service target port against a pod's container ports.
+/// Named target ports only resolve if a container declares a port with that name.
+fn resolve_target_port(target_port: Option<&IntOrString>, service_port: i32, pod: &Pod) -> Option<i32> {
+    match target_port {
+        None => Some(service_port),
+        Some(IntOrString::Int(port)) => Some(*port),
+        Some(IntOrString::String(name)) => pod.spec.as_ref()?
+            .containers.iter()
+            .flat_map(|c| c.ports.iter().flatten())
+            .find(|p| p.name.as_deref() == Some(name.as_str()))
+            .map(|p| p.container_port),
+    }
+}
+
 // Implement Rig's Tool trait
 impl RigTool for KubectlTool {
     const NAME: &'static str = "kubectl";
@@ -960,7 +2039,9 @@ impl RigTool for KubectlTool {
         ToolDefinition {
             name: Self::NAME.to_string(),
             description: "Execute kubectl commands for Kubernetes cluster inspection. \
-                         Supports 'get', 'describe', 'logs', and 'events' verbs. \
+                         Supports 'get', 'describe', 'logs', 'top', 'events', and 'port-forward-info' verbs. \
+                         Use 'top' with resource 'pods' or 'nodes' to see current CPU and memory usage. \
+                         Use 'port-forward-info' with a service name to see which pods and ports the service forwards to. \
                          Use this tool to query Kubernetes resources.".to_string(),
             parameters: serde_json::json!({
                 "type": "object",
@@ -968,11 +2049,11 @@ impl RigTool for KubectlTool {
                     "verb": {
                         "type": "string",
                         "description": "The kubectl verb to execute.",
-                        "enum": ["get", "describe", "logs", "events"]
+                        "enum": ["get", "describe", "logs", "top", "events", "port-forward-info"]
                     },
                     "resource": {
                         "type": "string",
-                        "description": "The type of Kubernetes resource. Supported types: pods, namespaces, services, deployments, statefulsets, daemonsets, jobs, cronjobs, configmaps, secrets, and 'all' (returns pods, services, deployments, statefulsets, and daemonsets). Use singular or plural forms. Optional for some verbs."
+                        "description": "The type of Kubernetes resource. Supported types: pods, namespaces, services, deployments, statefulsets, daemonsets, jobs, cronjobs, configmaps, secrets, ingresses, ingressclasses, clusterroles, roles, clusterrolebindings, rolebindings, and 'all' (returns pods, services, deployments, statefulsets, and daemonsets). Use singular or plural forms. Optional for some verbs."
                     },
                     "name": {
                         "type": "string",
@@ -980,7 +2061,7 @@ impl RigTool for KubectlTool {
                     },
                     "namespace": {
                         "type": "string",
-                        "description": "The Kubernetes namespace to operate in. Defaults to 'default' if not specified. For 'get' operations, use 'all' to list resources across all namespaces. Optional."
+                        "description": "The Kubernetes namespace to operate in. Defaults to 'default' if not specified. For 'get' and 'top' operations, use 'all' to list resources across all namespaces. Optional."
                     },
                     "tail_lines": {
                         "type": "integer",
@@ -993,6 +2074,18 @@ impl RigTool for KubectlTool {
                     "label_selector": {
                         "type": "string",
                         "description": "Label selector for filtering resources (e.g., 'app=nginx', 'environment=production,tier=frontend'). Optional."
+                    },
+                    "container": {
+                        "type": "string",
+                        "description": "Container to get logs from. Only used with 'logs' verb. Required when the pod has more than one container. Optional."
+                    },
+                    "previous": {
+                        "type": "boolean",
+                        "description": "Only used with 'logs' verb. Return the logs of the previous, terminated instance of the container, e.g. to see why a CrashLoopBackOff pod crashed. Optional."
+                    },
+                    "as_of_resource_version": {
+                        "type": "string",
+                        "description": "Only used with 'get' verb. Return data no older than this resourceVersion, e.g. one reported by an earlier call, to avoid stale reads. Optional."
                     }
                 },
                 "required": ["verb"]
@@ -1018,11 +2111,11 @@ impl RigTool for KubectlTool {
         .map_err(|e| ToolError::InternalError(anyhow::anyhow!("Task join error: {}", e)))?;
         
         match result {
-            Ok(output) => Ok(ToolResult {
+            Ok((output, resource_version)) => Ok(ToolResult {
                 success: true,
                 output,
                 error: None,
-                metadata: None,
+                metadata: resource_version.map(|rv| serde_json::json!({ "resource_version": rv })),
             }),
             Err(e) => Ok(ToolResult {
                 success: false,
@@ -1058,6 +2151,9 @@ mod tests {
                     tail_lines: None,
                     field_selector: None,
                     label_selector: None,
+                    container: None,
+                    previous: None,
+                    as_of_resource_version: None,
                 };
                 
                 match tool.call(args).await {
@@ -1102,6 +2198,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&disallowed_verb_args).is_err());
         assert!(tool.validate(&disallowed_verb_args).unwrap_err().to_string().contains("Verb 'delete' is not allowed"));
@@ -1115,6 +2214,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&dangerous_name_args).is_err());
         assert!(tool.validate(&dangerous_name_args).unwrap_err().to_string().contains("contains a potentially dangerous pattern: ';'"));
@@ -1127,6 +2229,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&dangerous_name_args_kubectl).is_err());
         assert!(tool.validate(&dangerous_name_args_kubectl).unwrap_err().to_string().contains("pattern: 'kubectl exec'"));
@@ -1140,6 +2245,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&dangerous_resource_args).is_err());
         assert!(tool.validate(&dangerous_resource_args).unwrap_err().to_string().contains("pattern: '&&'"));
@@ -1154,6 +2262,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&safe_args_get_pods).is_ok());
 
@@ -1165,6 +2276,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&safe_args_describe_pod).is_ok());
 
@@ -1176,6 +2290,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool.validate(&safe_args_logs).is_ok());
 
@@ -1189,6 +2306,9 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool_with_ns_whitelist.validate(&ns_allowed_args).is_ok());
 
@@ -1200,11 +2320,82 @@ mod tests {
             tail_lines: None,
             field_selector: None,
             label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
         };
         assert!(tool_with_ns_whitelist.validate(&ns_disallowed_args).is_err());
         assert!(tool_with_ns_whitelist.validate(&ns_disallowed_args).unwrap_err().to_string().contains("Namespace 'forbidden-ns' is not in whitelist"));
     }
 
+    #[tokio::test]
+    async fn test_watch_whitelist_configmap() {
+        use axum::{extract::Query, routing::get, Router};
+        
+        let config_map = |namespaces: &str| serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": { "name": "kubectl-whitelist", "namespace": "operator", "resourceVersion": "2" },
+            "data": { "allowed_namespaces": namespaces },
+        });
+        // Lists see the initial whitelist; watches then see it extended
+        let app = Router::new().route("/api/v1/namespaces/{namespace}/configmaps", get(
+            move |Query(params): Query<HashMap<String, String>>| async move {
+                if params.contains_key("watch") {
+                    let event = serde_json::json!({ "type": "MODIFIED", "object": config_map("team-a, team-b,") });
+                    return format!("{}\n", event);
+                }
+                serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "ConfigMapList",
+                    "metadata": { "resourceVersion": "1" },
+                    "items": [config_map("team-a")],
+                }).to_string()
+            }
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = Client::try_from(Config::new(url.parse().unwrap())).unwrap();
+        
+        let args = |namespace: &str| KubectlToolArgs {
+            verb: "get".to_string(),
+            resource: Some("pods".to_string()),
+            name: None,
+            namespace: Some(namespace.to_string()),
+            tail_lines: None,
+            field_selector: None,
+            label_selector: None,
+            container: None,
+            previous: None,
+            as_of_resource_version: None,
+        };
+        let tool = KubectlTool::new(client.clone());
+        // Clones handed to agents see the reloaded whitelist too
+        let agent_tool = tool.clone();
+        let handle = tool.watch_whitelist_configmap(client, "operator", "kubectl-whitelist");
+        
+        let mut allowed = false;
+        for _ in 0..100 {
+            // The whitelist starts unset, so wait for it to both restrict and include team-b
+            if agent_tool.validate(&args("kube-system")).is_err() && agent_tool.validate(&args("team-b")).is_ok() {
+                allowed = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        handle.abort();
+        assert!(allowed, "whitelist was not reloaded");
+        assert!(agent_tool.validate(&args("team-a")).is_ok());
+        let err = agent_tool.validate(&args("kube-system")).unwrap_err();
+        assert!(err.to_string().contains("Namespace 'kube-system' is not in whitelist"));
+        
+        let mut unrestricted = ConfigMap::default();
+        assert_eq!(allowed_namespaces(&unrestricted), None);
+        unrestricted.data = Some([("other".to_string(), "x".to_string())].into());
+        assert_eq!(allowed_namespaces(&unrestricted), None);
+    }
+
     #[test]
     fn test_allowed_verbs() {
         // Test that we can create a tool and it has the expected allowed verbs
@@ -1251,4 +2442,461 @@ mod tests {
             }
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_port_forward_map() {
+        let service: Service = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "web", "namespace": "default" },
+            "spec": {
+                "selector": { "app": "web" },
+                "ports": [
+                    { "name": "http", "port": 80, "targetPort": "http", "protocol": "TCP" },
+                    { "port": 9090, "targetPort": 9091 }
+                ]
+            }
+        })).unwrap();
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "web-abc", "namespace": "default", "labels": { "app": "web" } },
+            "spec": {
+                "containers": [{
+                    "name": "web",
+                    "ports": [{ "name": "http", "containerPort": 8080 }]
+                }]
+            }
+        })).unwrap();
+        
+        let map = port_forward_map(&service, &[pod]);
+        assert_eq!(map["80/TCP (http)"], serde_json::json!(["web-abc:8080"]));
+        assert_eq!(map["9090/TCP"], serde_json::json!(["web-abc:9091"]));
+    }
+
+    const TEST_CERT_PEM: &str = "-----BEGIN CERTIFICATE-----
+MIIBgjCCASegAwIBAgIUTG/085/2o+ZEJW1ISi33GDpVopQwCgYIKoZIzj0EAwIw
+FjEUMBIGA1UEAwwLZXhhbXBsZS5jb20wHhcNMjQwMTAxMDAwMDAwWhcNMzQwMTAx
+MDAwMDAwWjAWMRQwEgYDVQQDDAtleGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqG
+SM49AwEHA0IABJWx4vjwndTSAHJbCiGfN1X7pzHj1Q2WGlA301OEoF8LEcwQqs+u
+o0LCfxCIX3+/F23EDxEDXCe+ybxq+6BJ9VWjUzBRMB0GA1UdDgQWBBTqM/dcDref
+6RPWCHHAKaJPbpvXmjAfBgNVHSMEGDAWgBTqM/dcDref6RPWCHHAKaJPbpvXmjAP
+BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0kAMEYCIQCrJrzhT8w5J+tdH90v
+XT8XIbuz2RF8+aE8x33bOqauOAIhAI/18ydyIGerFqv97p702YgnYTvQhvctjXDc
+u76YZ3JI
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn test_tls_certificate_expiry() {
+        let not_after = certificate_not_after(TEST_CERT_PEM.as_bytes()).unwrap();
+        assert_eq!(not_after.to_rfc3339(), "2034-01-01T00:00:00+00:00");
+        assert!(certificate_not_after(b"not a certificate").is_err());
+        
+        let certificates = vec![("web-tls".to_string(), Ok(not_after))];
+        
+        let far = annotate_tls_expiry("kind: Ingress\n".to_string(), &certificates, not_after - Duration::days(365));
+        assert!(far.starts_with("kind: Ingress\n"));
+        assert!(far.contains("web-tls: expires 2034-01-01T00:00:00+00:00"));
+        
+        let near = annotate_tls_expiry("kind: Ingress\n".to_string(), &certificates, not_after - Duration::days(10));
+        assert!(near.starts_with("[WARNING] TLS certificate expires within 30 days: web-tls (2034-01-01)"));
+    }
+    
+    #[test]
+    fn test_describe_rbac() {
+        let rules = vec![
+            PolicyRule {
+                verbs: vec!["get".into(), "list".into(), "watch".into()],
+                resources: Some(vec!["pods".into(), "services".into()]),
+                ..Default::default()
+            },
+            PolicyRule {
+                verbs: vec!["get".into()],
+                resources: Some(vec!["configmaps".into()]),
+                resource_names: Some(vec!["app-config".into()]),
+                ..Default::default()
+            },
+            PolicyRule {
+                verbs: vec!["get".into()],
+                non_resource_urls: Some(vec!["/healthz".into()]),
+                ..Default::default()
+            },
+        ];
+        let described = describe_rules("ClusterRole", "viewer", "in all namespaces", &rules);
+        assert_eq!(described, "ClusterRole viewer grants [get, list, watch] on [pods, services] in all namespaces\n\
+            ClusterRole viewer grants [get] on [configmaps] in all namespaces (only names: app-config)\n\
+            ClusterRole viewer grants [get] on non-resource URLs [/healthz]\n");
+        
+        let role_ref = RoleRef {
+            api_group: "rbac.authorization.k8s.io".into(),
+            kind: "ClusterRole".into(),
+            name: "viewer".into(),
+        };
+        let subjects = vec![
+            Subject { kind: "User".into(), name: "alice".into(), ..Default::default() },
+            Subject { kind: "Group".into(), name: "sre".into(), ..Default::default() },
+            Subject { kind: "ServiceAccount".into(), name: "operator".into(), namespace: Some("monitoring".into()), ..Default::default() },
+        ];
+        let described = describe_binding("RoleBinding", "viewers", "in namespace monitoring", &role_ref, &subjects);
+        assert_eq!(described, "RoleBinding viewers binds ClusterRole viewer in namespace monitoring\n\
+            Subjects:\n  - User alice\n  - Group sre\n  - ServiceAccount monitoring/operator\n");
+        assert_eq!(format_subjects(&[]), "<none>");
+    }
+    
+    #[test]
+    fn test_describe_node() {
+        let node: Node = serde_json::from_value(serde_json::json!({
+            "metadata": {
+                "name": "worker-1",
+                "labels": {
+                    "node-role.kubernetes.io/worker": "",
+                    "node-role.kubernetes.io/ingress": "",
+                    "kubernetes.io/role": "worker"
+                }
+            },
+            "spec": {
+                "unschedulable": true,
+                "taints": [{ "key": "node.kubernetes.io/disk-pressure", "effect": "NoSchedule" }]
+            },
+            "status": {
+                "nodeInfo": {
+                    "kubeletVersion": "v1.29.2",
+                    "architecture": "amd64", "bootID": "", "containerRuntimeVersion": "",
+                    "kernelVersion": "", "kubeProxyVersion": "", "machineID": "",
+                    "operatingSystem": "linux", "osImage": "", "systemUUID": ""
+                },
+                "capacity": { "cpu": "4", "memory": "16Gi" },
+                "conditions": [
+                    { "type": "MemoryPressure", "status": "False", "reason": "KubeletHasSufficientMemory" },
+                    { "type": "DiskPressure", "status": "True", "reason": "KubeletHasDiskPressure", "message": "ephemeral storage low" },
+                    { "type": "Ready", "status": "Unknown", "reason": "NodeStatusUnknown" }
+                ]
+            }
+        })).unwrap();
+        
+        assert_eq!(node_status(&node), "Unknown,SchedulingDisabled");
+        assert_eq!(node_roles(&node), "ingress,worker");
+        assert_eq!(node_roles(&Node::default()), "<none>");
+        
+        let described = describe_node(&node);
+        assert!(described.starts_with("Name: worker-1\nStatus: Unknown,SchedulingDisabled\nRoles: ingress,worker\nVersion: v1.29.2\n"));
+        assert!(described.contains("Taints:\n  - node.kubernetes.io/disk-pressure:NoSchedule\n"));
+        assert!(described.contains("Capacity:\n  cpu: 4\n  memory: 16Gi\n"));
+        assert!(described.contains("\n  MemoryPressure: False (KubeletHasSufficientMemory)\n"));
+        assert!(described.contains("\n[WARNING] DiskPressure: True (KubeletHasDiskPressure) - ephemeral storage low\n"));
+        assert!(described.contains("\n[WARNING] Ready: Unknown (NodeStatusUnknown)\n"));
+        
+        let now = Utc::now();
+        assert_eq!(format_age(now - Duration::seconds(45), now), "45s");
+        assert_eq!(format_age(now - Duration::minutes(90), now), "1h");
+        assert_eq!(format_age(now - Duration::days(20), now), "20d");
+    }
+
+    #[test]
+    fn test_describe_events() {
+        let now = Utc::now();
+        let event = |reason: &str, type_: &str, message: &str, first: Duration, last: Duration, count: i32| -> Event {
+            serde_json::from_value(serde_json::json!({
+                "metadata": { "name": format!("api-0.{}", reason), "namespace": "default" },
+                "involvedObject": { "kind": "Pod", "name": "api-0", "namespace": "default" },
+                "type": type_,
+                "reason": reason,
+                "message": message,
+                "source": { "component": "kubelet" },
+                "firstTimestamp": (now - first).to_rfc3339(),
+                "lastTimestamp": (now - last).to_rfc3339(),
+                "count": count
+            })).unwrap()
+        };
+        
+        let described = format_describe_events(vec![
+            event("BackOff", "Warning", "Back-off restarting failed container api", Duration::hours(1), Duration::minutes(2), 15),
+            event("Scheduled", "Normal", "Successfully assigned default/api-0\nto worker-1", Duration::hours(2), Duration::hours(2), 1),
+        ], now);
+        assert_eq!(described, "Events:\n  Type\tReason\tAge\tFrom\tMessage\n  ----\t------\t----\t----\t-------\n\
+            \x20 Normal\tScheduled\t2h\tkubelet\tSuccessfully assigned default/api-0 to worker-1\n\
+            \x20 Warning\tBackOff\t2m (x15 over 1h)\tkubelet\tBack-off restarting failed container api\n");
+        
+        assert_eq!(format_describe_events(Vec::new(), now), "Events: <none>\n");
+    }
+    
+    /// Client for a local stand-in API server serving `pods` and their logs.
+    /// The log endpoint echoes the container it was asked for, and answers
+    /// `previous` reads like the API server does for pods that never
+    /// restarted. Reads at a `resourceVersion` are answered at that
+    /// version, others at "42".
+    async fn mock_pod_api(pods: Vec<serde_json::Value>) -> Client {
+        use axum::{extract::{Path, Query}, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+        
+        let pods: HashMap<String, serde_json::Value> = pods.into_iter()
+            .map(|pod| (pod["metadata"]["name"].as_str().unwrap().to_string(), pod))
+            .collect();
+        let items: Vec<serde_json::Value> = pods.values().cloned().collect();
+        let log_pods = pods.clone();
+        let app = Router::new()
+            .route("/api/v1/namespaces/{namespace}/pods", get(
+                |Query(params): Query<HashMap<String, String>>| async move {
+                    Json(serde_json::json!({
+                        "apiVersion": "v1",
+                        "kind": "PodList",
+                        "metadata": { "resourceVersion": params.get("resourceVersion").map_or("42", String::as_str) },
+                        "items": items,
+                    }))
+                }
+            ))
+            .route("/api/v1/namespaces/{namespace}/pods/{name}", get(
+                move |Path((_, name)): Path<(String, String)>, Query(params): Query<HashMap<String, String>>| async move {
+                    let mut pod = pods[&name].clone();
+                    pod["metadata"]["resourceVersion"] = params.get("resourceVersion").map_or("42", String::as_str).into();
+                    Json(pod)
+                }
+            ))
+            .route("/api/v1/namespaces/{namespace}/pods/{name}/log", get(
+                move |Path((_, name)): Path<(String, String)>, Query(params): Query<HashMap<String, String>>| async move {
+                    let container = params.get("container").map(String::as_str).unwrap_or("<default>");
+                    if params.get("previous").map(String::as_str) != Some("true") {
+                        return format!("logs of {}/{}", name, container).into_response();
+                    }
+                    let restarted = log_pods[&name]["status"]["containerStatuses"].as_array().into_iter().flatten()
+                        .any(|status| status["name"] == container && status["restartCount"].as_i64() > Some(0));
+                    if restarted {
+                        return format!("previous logs of {}/{}", name, container).into_response();
+                    }
+                    let message = format!("previous terminated container \"{}\" in pod \"{}\" not found", container, name);
+                    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                        "kind": "Status", "apiVersion": "v1", "metadata": {}, "status": "Failure",
+                        "message": message, "reason": "BadRequest", "code": 400
+                    }))).into_response()
+                }
+            ));
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        
+        Client::try_from(Config::new(url.parse().unwrap())).unwrap()
+    }
+
+    fn logs_args(pod: &str, container: Option<&str>) -> KubectlToolArgs {
+        KubectlToolArgs {
+            verb: "logs".to_string(),
+            resource: None,
+            name: Some(pod.to_string()),
+            namespace: Some("default".to_string()),
+            tail_lines: None,
+            field_selector: None,
+            label_selector: None,
+            container: container.map(str::to_string),
+            previous: None,
+            as_of_resource_version: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_container_selection() {
+        let client = mock_pod_api(vec![
+            serde_json::json!({
+                "metadata": { "name": "api-0", "namespace": "default" },
+                "spec": { "containers": [{ "name": "api" }] }
+            }),
+            serde_json::json!({
+                "metadata": { "name": "web-0", "namespace": "default" },
+                "spec": {
+                    "initContainers": [{ "name": "migrate" }],
+                    "containers": [{ "name": "web" }, { "name": "envoy" }]
+                }
+            }),
+        ]).await;
+        let tool = KubectlTool::new(client);
+        
+        // A single container is picked without being named
+        assert_eq!(tool.execute_logs(&logs_args("api-0", None)).await.unwrap(), "logs of api-0/api");
+        
+        // Several containers need one to be chosen
+        let err = tool.execute_logs(&logs_args("web-0", None)).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Pod 'web-0' has 2 containers, specify one with 'container': web, envoy (init containers: migrate)"
+        );
+        assert_eq!(tool.execute_logs(&logs_args("web-0", Some("envoy"))).await.unwrap(), "logs of web-0/envoy");
+        assert_eq!(tool.execute_logs(&logs_args("web-0", Some("migrate"))).await.unwrap(), "logs of web-0/migrate");
+    }
+    
+    #[tokio::test]
+    async fn test_previous_logs() {
+        let client = mock_pod_api(vec![
+            serde_json::json!({
+                "metadata": { "name": "api-0", "namespace": "default" },
+                "spec": { "containers": [{ "name": "api" }] },
+                "status": { "containerStatuses": [{
+                    "name": "api", "restartCount": 15, "ready": false, "image": "api", "imageID": ""
+                }] }
+            }),
+            serde_json::json!({
+                "metadata": { "name": "web-0", "namespace": "default" },
+                "spec": { "containers": [{ "name": "web" }] }
+            }),
+        ]).await;
+        let tool = KubectlTool::new(client);
+        let previous_args = |pod: &str| KubectlToolArgs { previous: Some(true), ..logs_args(pod, None) };
+        
+        assert_eq!(tool.execute_logs(&previous_args("api-0")).await.unwrap(), "previous logs of api-0/api");
+        
+        // A container that never restarted has no previous logs; the API's
+        // reason is passed on unchanged
+        let err = tool.execute_logs(&previous_args("web-0")).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No previous logs for pod 'web-0' in namespace 'default': previous terminated container \"web\" in pod \"web-0\" not found"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_resource_version_tracking() {
+        let client = mock_pod_api(vec![serde_json::json!({
+            "metadata": { "name": "api-0", "namespace": "default" },
+            "spec": { "containers": [{ "name": "api" }] }
+        })]).await;
+        let tool = KubectlTool::new(client);
+        let get_args = |name: Option<&str>, as_of: Option<&str>| KubectlToolArgs {
+            verb: "get".to_string(),
+            resource: Some("pods".to_string()),
+            name: name.map(str::to_string),
+            as_of_resource_version: as_of.map(str::to_string),
+            ..logs_args("api-0", None)
+        };
+        
+        // The observed version is reported for gets and lists alike
+        let result = tool.call(get_args(None, None)).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.contains("api-0"));
+        assert_eq!(result.metadata.unwrap()["resource_version"], "42");
+        let result = tool.call(get_args(Some("api-0"), None)).await.unwrap();
+        assert_eq!(result.metadata.unwrap()["resource_version"], "42");
+        
+        // Reads can be pinned to no older than a known version
+        let result = tool.call(get_args(None, Some("57"))).await.unwrap();
+        assert_eq!(result.metadata.unwrap()["resource_version"], "57");
+        let result = tool.call(get_args(Some("api-0"), Some("57"))).await.unwrap();
+        assert_eq!(result.metadata.unwrap()["resource_version"], "57");
+        
+        // Other verbs don't report one
+        let result = tool.call(logs_args("api-0", None)).await.unwrap();
+        assert!(result.metadata.is_none());
+        
+        assert_eq!(tool.fetch_resource_version("pod", "api-0", None).await.unwrap(), "42");
+        let err = tool.fetch_resource_version("widgets", "api-0", None).await.unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+    }
+    
+    /// Client for a stand-in API server with metrics-server installed. Pod
+    /// metrics honour a single `key=value` label selector.
+    async fn mock_metrics_api() -> Client {
+        use axum::{extract::{Path, Query}, routing::get, Json, Router};
+        
+        let pod_metrics = |namespace: &str, name: &str, app: &str, usage: &[(&str, &str)]| serde_json::json!({
+            "metadata": { "name": name, "namespace": namespace, "labels": { "app": app } },
+            "timestamp": "2024-01-01T00:00:00Z",
+            "window": "30s",
+            "containers": usage.iter().enumerate().map(|(i, (cpu, memory))| serde_json::json!({
+                "name": format!("c{}", i),
+                "usage": { "cpu": cpu, "memory": memory }
+            })).collect::<Vec<_>>()
+        });
+        let pods = vec![
+            pod_metrics("default", "api-0", "api", &[("250m", "128Mi"), ("5000000n", "64Mi")]),
+            pod_metrics("monitoring", "prometheus-0", "prometheus", &[("1", "1Gi")]),
+        ];
+        let list = |items: Vec<serde_json::Value>, params: HashMap<String, String>| {
+            let items: Vec<serde_json::Value> = items.into_iter()
+                .filter(|item| match params.get("labelSelector").and_then(|s| s.split_once('=')) {
+                    Some((key, value)) => item["metadata"]["labels"][key] == value,
+                    None => true,
+                })
+                .collect();
+            Json(serde_json::json!({ "apiVersion": "metrics.k8s.io/v1beta1", "kind": "List", "metadata": {}, "items": items }))
+        };
+        
+        let namespaced = pods.clone();
+        let app = Router::new()
+            .route("/apis/metrics.k8s.io/v1beta1/pods", get(
+                move |Query(params): Query<HashMap<String, String>>| async move { list(pods, params) }
+            ))
+            .route("/apis/metrics.k8s.io/v1beta1/namespaces/{namespace}/pods", get(
+                move |Path(namespace): Path<String>, Query(params): Query<HashMap<String, String>>| async move {
+                    let items = namespaced.into_iter().filter(|pod| pod["metadata"]["namespace"] == namespace.as_str()).collect();
+                    list(items, params)
+                }
+            ))
+            .route("/apis/metrics.k8s.io/v1beta1/nodes", get(
+                move |Query(params): Query<HashMap<String, String>>| async move {
+                    list(vec![
+                        serde_json::json!({ "metadata": { "name": "worker-1" }, "usage": { "cpu": "1500m", "memory": "4Gi" } }),
+                        serde_json::json!({ "metadata": { "name": "worker-2" }, "usage": { "cpu": "100m", "memory": "512Mi" } }),
+                    ], params)
+                }
+            ))
+            .route("/api/v1/nodes", get(|| async {
+                Json(serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": "NodeList",
+                    "metadata": {},
+                    "items": [{ "metadata": { "name": "worker-1" }, "status": { "allocatable": { "cpu": "4", "memory": "16Gi" } } }]
+                }))
+            }));
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        
+        Client::try_from(Config::new(url.parse().unwrap())).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_top() {
+        let tool = KubectlTool::new(mock_metrics_api().await);
+        let top_args = |resource: &str, namespace: Option<&str>, label_selector: Option<&str>| KubectlToolArgs {
+            verb: "top".to_string(),
+            resource: Some(resource.to_string()),
+            name: None,
+            namespace: namespace.map(str::to_string),
+            label_selector: label_selector.map(str::to_string),
+            ..logs_args("", None)
+        };
+        
+        // Container usage is summed per pod
+        let result = tool.call(top_args("pods", None, None)).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "NAME\tCPU(cores)\tMEMORY(bytes)\napi-0\t255m\t192Mi");
+        
+        let output = tool.execute_top(&top_args("pods", Some("all"), None)).await.unwrap();
+        assert_eq!(output, "NAMESPACE\tNAME\tCPU(cores)\tMEMORY(bytes)\n\
+            default\tapi-0\t255m\t192Mi\nmonitoring\tprometheus-0\t1000m\t1024Mi");
+        
+        let output = tool.execute_top(&top_args("pods", Some("all"), Some("app=prometheus"))).await.unwrap();
+        assert_eq!(output, "NAMESPACE\tNAME\tCPU(cores)\tMEMORY(bytes)\nmonitoring\tprometheus-0\t1000m\t1024Mi");
+        assert_eq!(tool.execute_top(&top_args("pods", None, Some("app=missing"))).await.unwrap(), "No pod metrics found");
+        
+        // Node usage is relative to allocatable resources where known
+        let output = tool.execute_top(&top_args("nodes", None, None)).await.unwrap();
+        assert_eq!(output, "NAME\tCPU(cores)\tCPU%\tMEMORY(bytes)\tMEMORY%\n\
+            worker-1\t1500m\t38%\t4096Mi\t25%\nworker-2\t100m\t<unknown>\t512Mi\t<unknown>");
+        
+        let err = tool.execute_top(&top_args("deployments", None, None)).await.unwrap_err();
+        assert!(err.to_string().contains("not supported for 'top'"));
+        
+        // Without metrics-server the tool reports it rather than failing hard
+        let tool = KubectlTool::new(mock_pod_api(Vec::new()).await);
+        let result = tool.call(top_args("pods", None, None)).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("is metrics-server installed?"));
+    }
+    
+    #[test]
+    fn test_parse_quantity() {
+        assert_eq!(parse_quantity("250m"), Some(0.25));
+        assert_eq!(parse_quantity("2"), Some(2.0));
+        assert_eq!(parse_quantity("128Mi"), Some(134217728.0));
+        assert_eq!(parse_quantity("1k"), Some(1000.0));
+        assert_eq!(parse_quantity("1e3"), Some(1000.0));
+        assert_eq!(parse_quantity("12345678n").map(|cores| (cores * 1e6).round()), Some(12346.0));
+        assert_eq!(parse_quantity("lots"), None);
+    }
+}
@@ -7,6 +7,8 @@ pub mod kubectl;
 pub mod promql;
 pub mod curl;
 pub mod script;
+pub mod ingress;
+pub mod recorder;
 
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +26,8 @@ pub use kubectl::KubectlTool;
 pub use promql::PromQLTool;
 pub use curl::CurlTool;
 pub use script::ScriptTool;
+pub use ingress::IngressTool;
+pub use recorder::{Recorded, ToolRecorder};
 
 /// Arguments for tool execution (used by all tools)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,22 +1,131 @@
 //! PromQL Tool for Prometheus Queries
 //! 
 //! Allows agents to query Prometheus metrics for investigation.
+//!
+//! Besides the default `query` verb, `labels` and `metadata` describe a
+//! metric's label names and example values and its type and help text, so
+//! queries can be written correctly on the first attempt.
+//!
+//! The `query_range` verb evaluates a query over a time window (the last
+//! hour at a 1m step unless `start`, `end` and `step` say otherwise) and
+//! summarizes each series so trends fit in the agent's context.
 
 use super::{ToolResult, ToolArgs, ToolError};
 use anyhow::Result;
+use moka::future::Cache;
 use reqwest::Client;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool as RigTool;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
 
+/// Default time-to-live for cached query results
+pub const DEFAULT_CACHE_TTL_SECONDS: u64 = 30;
+
+/// Example values reported per label by the `labels` verb
+const MAX_EXAMPLE_LABEL_VALUES: usize = 10;
+
+/// How far back the `labels` verb looks for series
+const LABEL_DISCOVERY_WINDOW_SECONDS: i64 = 3600;
+
+/// Window of a range query without `start`
+const DEFAULT_RANGE_SECONDS: i64 = 3600;
+
+/// Resolution of a range query without `step`
+const DEFAULT_RANGE_STEP: &str = "1m";
+
+/// Samples listed per series of a range query; the rest are summarized
+const MAX_RANGE_SAMPLES: usize = 12;
+
+/// Arguments for PromQLTool execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromQLToolArgs {
+    /// `query` (default), `query_range`, `labels` or `metadata`
+    #[serde(default)]
+    pub verb: Option<String>,
+    /// PromQL query for the `query` and `query_range` verbs
+    #[serde(default)]
+    pub command: String,
+    /// Metric name or series selector for the `labels` and `metadata` verbs
+    #[serde(default)]
+    pub metric: Option<String>,
+    /// Start of a range query: RFC 3339, Unix seconds or e.g. `now-2h`
+    #[serde(default)]
+    pub start: Option<String>,
+    /// End of a range query, in the same formats; defaults to now
+    #[serde(default)]
+    pub end: Option<String>,
+    /// Resolution of a range query, e.g. `30s` or `5m`
+    #[serde(default)]
+    pub step: Option<String>,
+}
+
+impl From<ToolArgs> for PromQLToolArgs {
+    fn from(args: ToolArgs) -> Self {
+        Self { command: args.command, ..Default::default() }
+    }
+}
+
+/// Cache of successful PromQL results keyed by query string
+#[derive(Clone)]
+pub struct PromQLCache {
+    inner: Cache<String, ToolResult>,
+}
+
+impl PromQLCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Cache::builder()
+                .max_capacity(1_000)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+    
+    /// Get a cached result, marked with `metadata.cached: true`
+    pub async fn get(&self, query: &str) -> Option<ToolResult> {
+        let mut result = self.inner.get(query).await?;
+        set_cached_flag(&mut result, true);
+        Some(result)
+    }
+    
+    pub async fn insert(&self, query: String, result: ToolResult) {
+        self.inner.insert(query, result).await;
+    }
+}
+
+impl Default for PromQLCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_CACHE_TTL_SECONDS))
+    }
+}
+
+/// Credentials sent with every Prometheus request. `Debug` redacts them so
+/// they can't leak into logs.
+#[derive(Clone)]
+enum PrometheusAuth {
+    Bearer(String),
+    Basic { username: String, password: String },
+}
+
+impl std::fmt::Debug for PrometheusAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(<redacted>)"),
+            Self::Basic { username, .. } => write!(f, "Basic({}:<redacted>)", username),
+        }
+    }
+}
+
 /// PromQL tool for querying Prometheus
 #[derive(Clone)]
 pub struct PromQLTool {
     prometheus_url: String,
     client: Client,
-    auth_token: Option<String>,
+    auth: Option<PrometheusAuth>,
     timeout: Duration,
+    cache: PromQLCache,
 }
 
 impl PromQLTool {
@@ -24,14 +133,34 @@ impl PromQLTool {
         Self {
             prometheus_url,
             client: Client::new(),
-            auth_token: None,
+            auth: None,
             timeout: Duration::from_secs(30),
+            cache: PromQLCache::default(),
         }
     }
     
-    /// Set authentication token
-    pub fn with_auth_token(mut self, token: String) -> Self {
-        self.auth_token = Some(token);
+    /// Set how long query results are cached
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = PromQLCache::new(ttl);
+        self
+    }
+    
+    /// Share a cache with other PromQL tools, e.g. across the steps of an
+    /// investigation
+    pub fn with_cache(mut self, cache: PromQLCache) -> Self {
+        self.cache = cache;
+        self
+    }
+    
+    /// Authenticate to Prometheus with a bearer token
+    pub fn with_bearer_token(mut self, token: String) -> Self {
+        self.auth = Some(PrometheusAuth::Bearer(token));
+        self
+    }
+    
+    /// Authenticate to Prometheus with HTTP basic auth
+    pub fn with_basic_auth(mut self, username: String, password: String) -> Self {
+        self.auth = Some(PrometheusAuth::Basic { username, password });
         self
     }
     
@@ -45,15 +174,10 @@ impl PromQLTool {
     async fn query(&self, query: &str) -> Result<PrometheusResponse> {
         let url = format!("{}/api/v1/query", self.prometheus_url);
         
-        let mut request = self.client
+        let request = self.authorize(self.client
             .get(&url)
             .query(&[("query", query)])
-            .timeout(self.timeout);
-        
-        // Add auth header if token is provided
-        if let Some(token) = &self.auth_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+            .timeout(self.timeout));
         
         let response = request.send().await?;
         
@@ -70,7 +194,7 @@ impl PromQLTool {
     async fn query_range(&self, query: &str, start: &str, end: &str, step: &str) -> Result<PrometheusResponse> {
         let url = format!("{}/api/v1/query_range", self.prometheus_url);
         
-        let mut request = self.client
+        let request = self.authorize(self.client
             .get(&url)
             .query(&[
                 ("query", query),
@@ -78,11 +202,7 @@ impl PromQLTool {
                 ("end", end),
                 ("step", step),
             ])
-            .timeout(self.timeout);
-        
-        if let Some(token) = &self.auth_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+            .timeout(self.timeout));
         
         let response = request.send().await?;
         
@@ -95,41 +215,108 @@ impl PromQLTool {
         Ok(result)
     }
     
-    /// Execute a PromQL range query
-    async fn range_query(&self, query: &str, start: i64, end: i64, step: &str) -> Result<PrometheusResponse> {
-        let url = format!("{}/api/v1/query_range", self.prometheus_url);
+    /// Attach the configured credentials to a request
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth {
+            Some(PrometheusAuth::Bearer(token)) => request.bearer_auth(token),
+            Some(PrometheusAuth::Basic { username, password }) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+    
+    /// GET a Prometheus API endpoint and return its `data`
+    async fn api_get(&self, path: &str, params: &[(&str, String)]) -> Result<serde_json::Value> {
+        let url = format!("{}{}", self.prometheus_url, path);
         
-        let mut request = self.client
+        let request = self.authorize(self.client
             .get(&url)
-            .query(&[
-                ("query", query),
-                ("start", &start.to_string()),
-                ("end", &end.to_string()),
-                ("step", step),
-            ])
-            .timeout(self.timeout);
-        
-        // Add auth header if token is provided
-        if let Some(token) = &self.auth_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+            .query(params)
+            .timeout(self.timeout));
         
         let response = request.send().await?;
         
         if !response.status().is_success() {
             let error_text = response.text().await?;
-            return Err(anyhow::anyhow!("Prometheus query failed: {}", error_text));
+            return Err(anyhow::anyhow!("Prometheus request to {} failed: {}", path, error_text));
         }
         
-        let result: PrometheusResponse = response.json().await?;
-        Ok(result)
+        let mut body: serde_json::Value = response.json().await?;
+        Ok(body["data"].take())
     }
     
-    /// Parse command to determine query type
-    fn parse_command(&self, input: &str) -> Result<PromQLCommand> {
-        // For now, we only support instant queries
-        // TODO: Add support for range queries with time parameters
-        Ok(PromQLCommand::InstantQuery(input.to_string()))
+    /// Label names of `metric` with example values, from the series seen in
+    /// the last hour. Without a metric, every label name Prometheus knows.
+    async fn labels(&self, metric: Option<&str>) -> Result<(String, serde_json::Value)> {
+        let Some(metric) = metric else {
+            let names: Vec<String> = serde_json::from_value(self.api_get("/api/v1/labels", &[]).await?)?;
+            let output = format!("Label names: {}", names.join(", "));
+            return Ok((output, serde_json::json!({ "labels": names })));
+        };
+        
+        let end = chrono::Utc::now().timestamp();
+        let series: Vec<BTreeMap<String, String>> = serde_json::from_value(self.api_get("/api/v1/series", &[
+            ("match[]", metric.to_string()),
+            ("start", (end - LABEL_DISCOVERY_WINDOW_SECONDS).to_string()),
+            ("end", end.to_string()),
+        ]).await?)?;
+        
+        let mut labels: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for labelset in &series {
+            for (name, value) in labelset {
+                if name != "__name__" {
+                    labels.entry(name.clone()).or_default().insert(value.clone());
+                }
+            }
+        }
+        Ok(format_labels(metric, series.len(), &labels))
+    }
+    
+    /// Type, help text and unit of `metric`
+    async fn metadata(&self, metric: &str) -> Result<(String, serde_json::Value)> {
+        let data = self.api_get("/api/v1/metadata", &[("metric", metric.to_string())]).await?;
+        let metadata: BTreeMap<String, Vec<MetricMetadata>> = serde_json::from_value(data.clone())?;
+        
+        if metadata.is_empty() {
+            return Ok((format!("No metadata found for metric {}", metric), serde_json::json!({ "metadata": data })));
+        }
+        let mut output = String::new();
+        for (name, entries) in &metadata {
+            for entry in entries {
+                output.push_str(&format!("{} ({}", name, entry.metric_type));
+                if !entry.unit.is_empty() {
+                    output.push_str(&format!(", unit: {}", entry.unit));
+                }
+                output.push_str(&format!("): {}\n", entry.help));
+            }
+        }
+        Ok((output, serde_json::json!({ "metadata": data })))
+    }
+    
+    /// Parse the arguments into an instant query, or a range query when the
+    /// verb is `query_range` or a time range is given
+    fn parse_command(&self, args: &PromQLToolArgs) -> Result<PromQLCommand> {
+        let ranged = args.verb.as_deref() == Some("query_range")
+            || args.start.is_some() || args.end.is_some() || args.step.is_some();
+        if !ranged {
+            return Ok(PromQLCommand::InstantQuery(args.command.clone()));
+        }
+        
+        let now = chrono::Utc::now().timestamp();
+        let end = match args.end.as_deref() {
+            Some(end) => parse_time(end, now)?,
+            None => now,
+        };
+        let start = match args.start.as_deref() {
+            Some(start) => parse_time(start, now)?,
+            None => end - DEFAULT_RANGE_SECONDS,
+        };
+        if start >= end {
+            return Err(anyhow::anyhow!("Range query start must be before its end"));
+        }
+        let step = args.step.clone().unwrap_or_else(|| DEFAULT_RANGE_STEP.to_string());
+        parse_duration(&step)?;
+        
+        Ok(PromQLCommand::RangeQuery { query: args.command.clone(), start, end, step })
     }
     
     /// Validate if the query is safe to execute
@@ -152,7 +339,7 @@ impl RigTool for PromQLTool {
     const NAME: &'static str = "promql";
     
     type Error = ToolError;
-    type Args = ToolArgs;
+    type Args = PromQLToolArgs;
     type Output = ToolResult;
     
     async fn definition(&self, _prompt: String) -> ToolDefinition {
@@ -160,45 +347,146 @@ impl RigTool for PromQLTool {
             name: Self::NAME.to_string(),
             description: "Query Prometheus metrics using PromQL. Supports instant queries like \
                          'up{job=\"kubernetes-pods\"}' or 'rate(http_requests_total[5m])'. \
-                         Returns metric values and labels.".to_string(),
+                         Returns metric values and labels. Use verb 'query_range' to see how a \
+                         metric changed over time (the last hour by default). Before querying an unfamiliar metric, \
+                         use verb 'labels' to list its label names with example values and verb \
+                         'metadata' to learn its type and help text.".to_string(),
             parameters: serde_json::json!({
                 "type": "object",
                 "properties": {
+                    "verb": {
+                        "type": "string",
+                        "enum": ["query", "query_range", "labels", "metadata"],
+                        "description": "What to do; defaults to 'query'"
+                    },
                     "command": {
                         "type": "string",
-                        "description": "The PromQL query to execute (e.g., 'rate(http_requests_total[5m])')"
+                        "description": "The PromQL query to execute (e.g., 'rate(http_requests_total[5m])'); required for 'query' and 'query_range'"
+                    },
+                    "start": {
+                        "type": "string",
+                        "description": "Start of a 'query_range' window as RFC 3339, Unix seconds or relative like 'now-6h'; defaults to one hour before 'end'"
+                    },
+                    "end": {
+                        "type": "string",
+                        "description": "End of a 'query_range' window in the same formats; defaults to now"
+                    },
+                    "step": {
+                        "type": "string",
+                        "description": "Resolution of a 'query_range', e.g. '30s' or '5m'; defaults to '1m'"
+                    },
+                    "metric": {
+                        "type": "string",
+                        "description": "Metric name for 'labels' and 'metadata' (e.g., 'http_requests_total'); 'labels' also accepts a series selector and lists all label names when omitted"
                     }
-                },
-                "required": ["command"]
+                }
             }),
         }
     }
     
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        match args.verb.as_deref().unwrap_or("query") {
+            "query" | "query_range" => {}
+            verb @ ("labels" | "metadata") => return self.discover(verb, args.metric.as_deref()).await,
+            verb => return Err(ToolError::ValidationError(format!(
+                "Unsupported verb: {} (expected query, query_range, labels or metadata)", verb
+            ))),
+        }
+        
         // Validate the query
         self.validate(&args.command)
             .map_err(|e| ToolError::ValidationError(e.to_string()))?;
         
+        // Serve repeated queries from the cache; it may be shared by tools
+        // querying different Prometheus servers
+        let command = self.parse_command(&args);
+        let cache_key = match &command {
+            Ok(PromQLCommand::RangeQuery { .. }) => format!(
+                "{} range {} {} {} {}",
+                self.prometheus_url,
+                args.command,
+                args.start.as_deref().unwrap_or_default(),
+                args.end.as_deref().unwrap_or_default(),
+                args.step.as_deref().unwrap_or(DEFAULT_RANGE_STEP),
+            ),
+            _ => format!("{} {}", self.prometheus_url, args.command),
+        };
+        if let Some(cached) = self.cache.get(&cache_key).await {
+            return Ok(cached);
+        }
+        
         // Execute the query
-        match self.parse_command(&args.command) {
-            Ok(PromQLCommand::InstantQuery(query)) => {
-                match self.query(&query).await {
-                    Ok(response) => {
-                        let output = format_prometheus_response(&response);
-                        Ok(ToolResult {
-                            success: true,
-                            output,
-                            error: None,
-                            metadata: Some(serde_json::to_value(&response).unwrap()),
-                        })
-                    }
-                    Err(e) => Ok(ToolResult {
-                        success: false,
-                        output: String::new(),
-                        error: Some(e.to_string()),
-                        metadata: None,
-                    }),
-                }
+        let response = match command {
+            Ok(PromQLCommand::InstantQuery(query)) => self.query(&query).await,
+            Ok(PromQLCommand::RangeQuery { query, start, end, step }) => {
+                self.query_range(&query, &start.to_string(), &end.to_string(), &step).await
+            }
+            Err(e) => Err(e),
+        };
+        match response {
+            Ok(response) => {
+                let output = format_prometheus_response(&response);
+                // A matrix is summarized in the output; repeating every
+                // sample in the metadata would defeat that
+                let metadata = if response.data.result_type == "matrix" {
+                    serde_json::json!({
+                        "status": response.status,
+                        "resultType": response.data.result_type,
+                        "series": response.data.result.len(),
+                    })
+                } else {
+                    serde_json::to_value(&response).unwrap()
+                };
+                let mut result = ToolResult {
+                    success: true,
+                    output,
+                    error: None,
+                    metadata: Some(metadata),
+                };
+                set_cached_flag(&mut result, false);
+                self.cache.insert(cache_key, result.clone()).await;
+                Ok(result)
+            }
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e.to_string()),
+                metadata: None,
+            }),
+        }
+    }
+}
+
+impl PromQLTool {
+    /// Run the `labels` or `metadata` verb, caching results like queries
+    async fn discover(&self, verb: &str, metric: Option<&str>) -> Result<ToolResult, ToolError> {
+        let metric = metric.map(str::trim).filter(|m| !m.is_empty());
+        if let Some(metric) = metric {
+            self.validate(metric)
+                .map_err(|e| ToolError::ValidationError(e.to_string()))?;
+        }
+        
+        let cache_key = format!("{} {} {}", self.prometheus_url, verb, metric.unwrap_or_default());
+        if let Some(cached) = self.cache.get(&cache_key).await {
+            return Ok(cached);
+        }
+        
+        let discovered = match (verb, metric) {
+            ("metadata", Some(metric)) => self.metadata(metric).await,
+            ("metadata", None) => return Err(ToolError::ValidationError("The metadata verb requires a metric".to_string())),
+            _ => self.labels(metric).await,
+        };
+        match discovered {
+            Ok((output, metadata)) => {
+                let mut result = ToolResult {
+                    success: true,
+                    output,
+                    error: None,
+                    metadata: Some(metadata),
+                };
+                set_cached_flag(&mut result, false);
+                self.cache.insert(cache_key, result.clone()).await;
+                Ok(result)
             }
             Err(e) => Ok(ToolResult {
                 success: false,
@@ -213,7 +501,8 @@ impl RigTool for PromQLTool {
 #[derive(Debug)]
 enum PromQLCommand {
     InstantQuery(String),
-    // Could add RangeQuery(query, start, end, step) in the future
+    /// Query evaluated every `step` between the `start` and `end` Unix times
+    RangeQuery { query: String, start: i64, end: i64, step: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -238,6 +527,52 @@ struct PrometheusResult {
     values: Option<Vec<(f64, String)>>,
 }
 
+/// Entry of the `/api/v1/metadata` response
+#[derive(Debug, Deserialize)]
+struct MetricMetadata {
+    #[serde(rename = "type")]
+    metric_type: String,
+    #[serde(default)]
+    help: String,
+    #[serde(default)]
+    unit: String,
+}
+
+/// Summarize the labels of `series_count` series matching `metric`, with
+/// up to `MAX_EXAMPLE_LABEL_VALUES` example values per label
+fn format_labels(metric: &str, series_count: usize, labels: &BTreeMap<String, BTreeSet<String>>) -> (String, serde_json::Value) {
+    if series_count == 0 {
+        return (
+            format!("No series found for {} in the last hour", metric),
+            serde_json::json!({ "series": 0, "labels": {} }),
+        );
+    }
+    
+    let mut output = format!("{} series of {} in the last hour\n", series_count, metric);
+    let mut summary = serde_json::Map::new();
+    for (name, values) in labels {
+        let examples: Vec<&String> = values.iter().take(MAX_EXAMPLE_LABEL_VALUES).collect();
+        let quoted: Vec<String> = examples.iter().map(|v| format!("\"{}\"", v)).collect();
+        output.push_str(&format!("{}: {}", name, quoted.join(", ")));
+        if values.len() > examples.len() {
+            output.push_str(&format!(" (+{} more)", values.len() - examples.len()));
+        }
+        output.push('\n');
+        summary.insert(name.clone(), serde_json::json!({
+            "values": examples,
+            "distinct_values": values.len(),
+        }));
+    }
+    (output, serde_json::json!({ "series": series_count, "labels": summary }))
+}
+
+/// Record in the result metadata whether it was served from the cache
+fn set_cached_flag(result: &mut ToolResult, cached: bool) {
+    if let Some(serde_json::Value::Object(metadata)) = result.metadata.as_mut() {
+        metadata.insert("cached".to_string(), serde_json::Value::Bool(cached));
+    }
+}
+
 /// Format Prometheus response for human-readable output
 fn format_prometheus_response(response: &PrometheusResponse) -> String {
     let mut output = String::new();
@@ -265,14 +600,328 @@ fn format_prometheus_response(response: &PrometheusResponse) -> String {
         }
         
         if let Some(values) = &result.values {
-            output.push_str("Values:\n");
-            for (timestamp, value) in values {
-                output.push_str(&format!("  {} @ {}\n", value, timestamp));
-            }
+            output.push_str(&format_range_values(values));
         }
         
         output.push('\n');
     }
     
     output
-} 
\ No newline at end of file
+}
+
+/// Summarize the samples of a range query series: its span, min, max and
+/// change, then up to `MAX_RANGE_SAMPLES` evenly spaced samples
+fn format_range_values(values: &[(f64, String)]) -> String {
+    let (Some(first), Some(last)) = (values.first(), values.last()) else {
+        return "Values: none\n".to_string();
+    };
+    
+    let numbers: Vec<f64> = values.iter().filter_map(|(_, v)| v.parse().ok()).collect();
+    let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mut output = format!("Samples: {} from {} to {}\n", values.len(), first.0, last.0);
+    if !numbers.is_empty() {
+        output.push_str(&format!("Min: {}, Max: {}, First: {}, Last: {}\n", min, max, first.1, last.1));
+    }
+    
+    let shown: Vec<&(f64, String)> = if values.len() <= MAX_RANGE_SAMPLES {
+        values.iter().collect()
+    } else {
+        // Always keep the last sample so the latest value is visible
+        (0..MAX_RANGE_SAMPLES)
+            .map(|i| &values[i * (values.len() - 1) / (MAX_RANGE_SAMPLES - 1)])
+            .collect()
+    };
+    output.push_str("Values:\n");
+    for (timestamp, value) in shown {
+        output.push_str(&format!("  {} @ {}\n", value, timestamp));
+    }
+    output
+}
+
+/// Parse a range query time: RFC 3339, Unix seconds, `now` or `now-<duration>`
+fn parse_time(input: &str, now: i64) -> Result<i64> {
+    let input = input.trim();
+    if input == "now" {
+        return Ok(now);
+    }
+    if let Some(ago) = input.strip_prefix("now-") {
+        return Ok(now - parse_duration(ago)?);
+    }
+    if let Ok(timestamp) = input.parse::<f64>() {
+        return Ok(timestamp as i64);
+    }
+    chrono::DateTime::parse_from_rfc3339(input)
+        .map(|time| time.timestamp())
+        .map_err(|_| anyhow::anyhow!("Invalid time '{}', expected RFC 3339, Unix seconds or now-<duration>", input))
+}
+
+/// Parse a Prometheus duration such as `90s`, `5m`, `2h`, `1d` or `1w` into seconds
+fn parse_duration(input: &str) -> Result<i64> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: i64 = number.parse()
+        .map_err(|_| anyhow::anyhow!("Invalid duration '{}'", input))?;
+    let seconds = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 604800,
+        _ => return Err(anyhow::anyhow!("Invalid duration '{}', expected a unit of s, m, h, d or w", input)),
+    };
+    if number == 0 {
+        return Err(anyhow::anyhow!("Duration '{}' must be positive", input));
+    }
+    Ok(number * seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    
+    /// Start a fake Prometheus server that counts the queries it receives
+    async fn start_mock_prometheus() -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        
+        let app = axum::Router::new().route(
+            "/api/v1/query",
+            axum::routing::get(move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    axum::Json(serde_json::json!({
+                        "status": "success",
+                        "data": {
+                            "resultType": "vector",
+                            "result": [{
+                                "metric": { "job": "apiserver" },
+                                "value": [1700000000.0, "1"]
+                            }]
+                        }
+                    }))
+                }
+            }),
+)
+        .route("/api/v1/query_range", axum::routing::get(
+            |axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>| async move {
+                // One sample per step, counting up from zero
+                let start: i64 = params["start"].parse().unwrap();
+                let end: i64 = params["end"].parse().unwrap();
+                let step = parse_duration(&params["step"]).unwrap();
+                let values: Vec<_> = (start..=end).step_by(step as usize).enumerate()
+                    .map(|(i, t)| serde_json::json!([t, i.to_string()]))
+                    .collect();
+                axum::Json(serde_json::json!({
+                    "status": "success",
+                    "data": {
+                        "resultType": "matrix",
+                        "result": [{ "metric": { "pod": "api-0" }, "values": values }]
+                    }
+                }))
+            },
+        ))
+        .route("/api/v1/labels", axum::routing::get(|| async {
+            axum::Json(serde_json::json!({ "status": "success", "data": ["__name__", "job", "method"] }))
+        }))
+        .route("/api/v1/series", axum::routing::get(
+            |axum::extract::Query(params): axum::extract::Query<Vec<(String, String)>>| async move {
+                assert!(params.iter().any(|(k, v)| k == "match[]" && v == "http_requests_total"));
+                let series: Vec<_> = (0..12).map(|i| serde_json::json!({
+                    "__name__": "http_requests_total",
+                    "job": "api",
+                    "code": format!("{}", 200 + i),
+                })).collect();
+                axum::Json(serde_json::json!({ "status": "success", "data": series }))
+            },
+        ))
+        .route("/api/v1/metadata", axum::routing::get(|| async {
+            axum::Json(serde_json::json!({
+                "status": "success",
+                "data": {
+                    "http_requests_total": [{ "type": "counter", "help": "Total HTTP requests.", "unit": "" }]
+                }
+            }))
+        }));
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        
+        (format!("http://{}", addr), hits)
+    }
+    
+    #[tokio::test]
+    async fn test_cache_hit_skips_http_call() {
+        let (url, hits) = start_mock_prometheus().await;
+        let tool = PromQLTool::new(url);
+        let args = ToolArgs { command: "up{job=\"apiserver\"}".to_string() };
+        
+        let first = tool.call(args.clone().into()).await.unwrap();
+        let second = tool.call(args.into()).await.unwrap();
+        
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert!(first.success && second.success);
+        assert_eq!(first.output, second.output);
+        assert_eq!(first.metadata.as_ref().unwrap()["cached"], false);
+        assert_eq!(second.metadata.as_ref().unwrap()["cached"], true);
+        assert_eq!(first.metadata.unwrap()["data"], second.metadata.unwrap()["data"]);
+    }
+    
+    #[tokio::test]
+    async fn test_cache_expires_after_ttl() {
+        let (url, hits) = start_mock_prometheus().await;
+        let tool = PromQLTool::new(url).with_cache_ttl(Duration::from_millis(50));
+        let args = ToolArgs { command: "up".to_string() };
+        
+        tool.call(args.clone().into()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let result = tool.call(args.into()).await.unwrap();
+        
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(result.metadata.unwrap()["cached"], false);
+    }
+    
+    #[tokio::test]
+    async fn test_shared_cache() {
+        let (url, hits) = start_mock_prometheus().await;
+        let (other_url, other_hits) = start_mock_prometheus().await;
+        let cache = PromQLCache::default();
+        let args = ToolArgs { command: "up".to_string() };
+        
+        // Tools built for separate steps reuse each other's results
+        PromQLTool::new(url.clone()).with_cache(cache.clone()).call(args.clone().into()).await.unwrap();
+        let result = PromQLTool::new(url).with_cache(cache.clone()).call(args.clone().into()).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(result.metadata.unwrap()["cached"], true);
+        
+        // but not results from another Prometheus
+        let result = PromQLTool::new(other_url).with_cache(cache).call(args.into()).await.unwrap();
+        assert_eq!(other_hits.load(Ordering::SeqCst), 1);
+        assert_eq!(result.metadata.unwrap()["cached"], false);
+    }
+    
+    #[tokio::test]
+    async fn test_label_discovery() {
+        let (url, _) = start_mock_prometheus().await;
+        let tool = PromQLTool::new(url);
+        let args = |verb: &str, metric: Option<&str>| PromQLToolArgs {
+            verb: Some(verb.to_string()),
+            metric: metric.map(str::to_string),
+            ..Default::default()
+        };
+        
+        let result = tool.call(args("labels", Some("http_requests_total"))).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("12 series of http_requests_total"));
+        assert!(result.output.contains("job: \"api\"\n"));
+        assert!(result.output.contains("(+2 more)"));
+        assert!(!result.output.contains("__name__"));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["labels"]["code"]["distinct_values"], 12);
+        assert_eq!(metadata["labels"]["code"]["values"].as_array().unwrap().len(), MAX_EXAMPLE_LABEL_VALUES);
+        
+        let result = tool.call(args("labels", None)).await.unwrap();
+        assert_eq!(result.output, "Label names: __name__, job, method");
+        
+        let result = tool.call(args("metadata", Some("http_requests_total"))).await.unwrap();
+        assert_eq!(result.output, "http_requests_total (counter): Total HTTP requests.\n");
+        assert_eq!(result.metadata.unwrap()["metadata"]["http_requests_total"][0]["type"], "counter");
+        
+        assert!(matches!(tool.call(args("metadata", None)).await, Err(ToolError::ValidationError(_))));
+        assert!(matches!(tool.call(args("delete", None)).await, Err(ToolError::ValidationError(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_range_query() {
+        let (url, _) = start_mock_prometheus().await;
+        let tool = PromQLTool::new(url);
+        let args = |start: Option<&str>, end: Option<&str>, step: Option<&str>| PromQLToolArgs {
+            verb: Some("query_range".to_string()),
+            command: "container_memory_working_set_bytes{pod=\"api-0\"}".to_string(),
+            start: start.map(str::to_string),
+            end: end.map(str::to_string),
+            step: step.map(str::to_string),
+            ..Default::default()
+        };
+        
+        // The last hour at a 1m step, summarized rather than listed in full
+        let result = tool.call(args(None, None, None)).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.starts_with("Metric: {pod=\"api-0\"}\nSamples: 61 from "));
+        assert!(result.output.contains("Min: 0, Max: 60, First: 0, Last: 60\n"));
+        assert_eq!(result.output.matches(" @ ").count(), MAX_RANGE_SAMPLES);
+        assert!(result.output.contains("\n  60 @ "));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["resultType"], "matrix");
+        assert_eq!(metadata["series"], 1);
+        
+        let result = tool.call(args(Some("now-10m"), Some("now"), Some("5m"))).await.unwrap();
+        assert!(result.output.contains("Samples: 3 from "));
+        assert_eq!(result.output.matches(" @ ").count(), 3);
+        
+        // A time range alone makes a range query too
+        let result = tool.call(PromQLToolArgs { verb: None, ..args(Some("now-2m"), None, None) }).await.unwrap();
+        assert!(result.output.contains("Samples: 3 from "));
+        
+        let result = tool.call(args(None, None, Some("fast"))).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Invalid duration 'fast'"));
+        let result = tool.call(args(Some("now"), Some("now-1h"), None)).await.unwrap();
+        assert!(result.error.unwrap().contains("start must be before its end"));
+    }
+    
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("now", 1_700_000_000).unwrap(), 1_700_000_000);
+        assert_eq!(parse_time("now-2h", 1_700_000_000).unwrap(), 1_699_992_800);
+        assert_eq!(parse_time("1699990000.5", 0).unwrap(), 1_699_990_000);
+        assert_eq!(parse_time("2023-11-14T22:13:20Z", 0).unwrap(), 1_700_000_000);
+        assert!(parse_time("yesterday", 0).is_err());
+        assert_eq!(parse_duration("90s").unwrap(), 90);
+        assert_eq!(parse_duration("1w").unwrap(), 604_800);
+        assert!(parse_duration("0m").is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_authentication() {
+        use axum::http::{HeaderMap, StatusCode};
+        
+        // Accepts only the bearer token "s3cr3t" or basic auth prom:hunter2
+        let app = axum::Router::new().route("/api/v1/query", axum::routing::get(|headers: HeaderMap| async move {
+            match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+                Some("Bearer s3cr3t") | Some("Basic cHJvbTpodW50ZXIy") => (StatusCode::OK, axum::Json(serde_json::json!({
+                    "status": "success",
+                    "data": { "resultType": "vector", "result": [] }
+                }))),
+                _ => (StatusCode::UNAUTHORIZED, axum::Json(serde_json::json!({ "status": "error", "error": "unauthorized" }))),
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let args = || PromQLToolArgs { command: "up".to_string(), ..Default::default() };
+        
+        let result = PromQLTool::new(url.clone()).call(args()).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("unauthorized"));
+        
+        let result = PromQLTool::new(url.clone()).with_bearer_token("s3cr3t".to_string()).call(args()).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let result = PromQLTool::new(url.clone())
+            .with_basic_auth("prom".to_string(), "hunter2".to_string())
+            .call(args()).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        
+        // Credentials don't show up in results or debug output
+        let result = PromQLTool::new(url).with_bearer_token("wrong".to_string()).call(args()).await.unwrap();
+        assert!(!result.error.unwrap().contains("wrong"));
+        assert_eq!(format!("{:?}", PrometheusAuth::Bearer("s3cr3t".to_string())), "Bearer(<redacted>)");
+    }
+}
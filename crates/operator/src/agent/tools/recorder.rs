@@ -0,0 +1,166 @@
+//! Tool Invocation Recorder
+//!
+//! Wraps agent tools so that every call an investigation makes is saved to
+//! the store as a `ToolInvocation`, with its arguments, whether it
+//! succeeded and the start of its output. Failing to save a call is logged
+//! and never fails the call itself.
+
+use super::ToolResult;
+use crate::store::{Store, ToolInvocation, TOOL_INVOCATION_OUTPUT_LIMIT};
+use chrono::Utc;
+use rig::completion::ToolDefinition;
+use rig::tool::Tool as RigTool;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+/// Saves the tool calls of one workflow's agent step
+#[derive(Clone)]
+pub struct ToolRecorder {
+    store: Arc<dyn Store>,
+    workflow_id: Uuid,
+    step_name: Option<String>,
+}
+
+impl ToolRecorder {
+    pub fn new(store: Arc<dyn Store>, workflow_id: Uuid) -> Self {
+        Self { store, workflow_id, step_name: None }
+    }
+
+    /// Attribute the calls to this workflow step
+    pub fn with_step_name(mut self, step_name: impl Into<String>) -> Self {
+        self.step_name = Some(step_name.into());
+        self
+    }
+
+    async fn record(&self, tool_name: &str, arguments: serde_json::Value, success: bool, output: &str) {
+        let invocation = ToolInvocation {
+            id: Uuid::new_v4(),
+            workflow_id: self.workflow_id,
+            step_name: self.step_name.clone(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            success,
+            output: truncate_output(output),
+            created_at: Utc::now(),
+        };
+
+        // Store futures aren't `Sync`, which rig requires of tool calls
+        let store = self.store.clone();
+        let saved = tokio::spawn(async move { store.save_tool_invocation(invocation).await }).await;
+        match saved {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Failed to record {} call for workflow {}: {}", tool_name, self.workflow_id, e),
+            Err(e) => error!("Failed to record {} call for workflow {}: {}", tool_name, self.workflow_id, e),
+        }
+    }
+}
+
+/// A tool whose calls are saved by a `ToolRecorder`, if it has one
+#[derive(Clone)]
+pub struct Recorded<T> {
+    tool: T,
+    recorder: Option<ToolRecorder>,
+}
+
+impl<T> Recorded<T> {
+    pub fn new(tool: T, recorder: Option<ToolRecorder>) -> Self {
+        Self { tool, recorder }
+    }
+}
+
+impl<T> RigTool for Recorded<T>
+where
+    T: RigTool<Output = ToolResult>,
+    T::Args: Serialize,
+{
+    const NAME: &'static str = T::NAME;
+
+    type Error = T::Error;
+    type Args = T::Args;
+    type Output = ToolResult;
+
+    async fn definition(&self, prompt: String) -> ToolDefinition {
+        self.tool.definition(prompt).await
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let Some(recorder) = &self.recorder else {
+            return self.tool.call(args).await;
+        };
+
+        let arguments = serde_json::to_value(&args).unwrap_or_default();
+        let result = self.tool.call(args).await;
+        match &result {
+            Ok(output) if output.success => recorder.record(T::NAME, arguments, true, &output.output).await,
+            Ok(output) => {
+                let error = output.error.as_deref().unwrap_or(&output.output);
+                recorder.record(T::NAME, arguments, false, error).await
+            }
+            Err(e) => recorder.record(T::NAME, arguments, false, &e.to_string()).await,
+        }
+        result
+    }
+}
+
+/// The first `TOOL_INVOCATION_OUTPUT_LIMIT` bytes of `output`, cut on a
+/// character boundary
+fn truncate_output(output: &str) -> String {
+    if output.len() <= TOOL_INVOCATION_OUTPUT_LIMIT {
+        return output.to_string();
+    }
+    let mut end = TOOL_INVOCATION_OUTPUT_LIMIT;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... (truncated, {} total bytes)", &output[..end], output.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::script::{ScriptLanguage, ScriptTool, ScriptToolArgs};
+    use crate::store::SqliteStore;
+
+    fn args(script: &str) -> ScriptToolArgs {
+        ScriptToolArgs { script: script.to_string(), language: ScriptLanguage::Sh, output_format: None }
+    }
+
+    #[tokio::test]
+    async fn test_records_calls() {
+        let store: Arc<dyn Store> = Arc::new(SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let workflow_id = Uuid::new_v4();
+        let recorder = ToolRecorder::new(store.clone(), workflow_id).with_step_name("investigate");
+        let tool = Recorded::new(ScriptTool::new(), Some(recorder));
+
+        let result = tool.call(args("echo hello")).await.unwrap();
+        assert!(result.success);
+        assert!(tool.call(args("echo oops >&2; exit 3")).await.unwrap().error.is_some());
+        assert!(tool.call(args("rm -rf /")).await.is_err());
+
+        let invocations = store.list_tool_invocations_by_workflow(workflow_id).await.unwrap();
+        assert_eq!(invocations.len(), 3);
+        assert!(invocations.iter().all(|i| i.tool_name == "script" && i.step_name.as_deref() == Some("investigate")));
+        assert!(invocations[0].success);
+        assert_eq!(invocations[0].arguments["script"], "echo hello");
+        assert_eq!(invocations[0].output.trim(), "hello");
+        assert!(!invocations[1].success);
+        assert!(invocations[1].output.contains("oops"));
+        assert!(!invocations[2].success);
+        assert!(invocations[2].output.contains("Validation failed"));
+
+        assert!(store.list_tool_invocations_by_workflow(Uuid::new_v4()).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(truncate_output("short"), "short");
+
+        let long = "é".repeat(TOOL_INVOCATION_OUTPUT_LIMIT);
+        let truncated = truncate_output(&long);
+        assert!(truncated.len() < long.len());
+        assert!(truncated.ends_with(&format!("(truncated, {} total bytes)", long.len())));
+    }
+}
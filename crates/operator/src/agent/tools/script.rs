@@ -1,72 +1,602 @@
 //! Script Tool for Custom Scripts
-//! 
-//! Allows agents to execute pre-defined custom scripts.
+//!
+//! Allows agents to execute diagnostic scripts. Each language has its own
+//! security profile:
+//!
+//! - **sh** / **bash**: rejected if they match the dangerous command patterns
+//!   from the default `SafetyConfig`
+//! - **python3**: rejected if they import `subprocess`, `os` or `socket`, or
+//!   call `__import__`
+//!
+//! Scripts that print JSON, CSV or `KEY=value` lines can ask for their output
+//! to be parsed with `output_format`; the parsed value is returned in
+//! `metadata.parsed_output` alongside the raw text.
+//!
+//! Scripts run with a fixed interpreter per language, an environment
+//! holding only `PATH`, and in their own process group. The whole group is
+//! killed once the script exits or its timeout expires, so background
+//! processes can't outlive it, and only the first `max_output_bytes` of
+//! stdout and stderr are kept.
 
-use super::{ToolResult, ToolArgs, ToolError};
+use super::{ToolResult, ToolError};
+use crate::agent::safety::SafetyConfig;
 use anyhow::Result;
+use regex::Regex;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool as RigTool;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+/// Language a script is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptLanguage {
+    #[default]
+    Sh,
+    Bash,
+    Python3,
+}
+
+impl ScriptLanguage {
+    /// The interpreter binary scripts of this language run with
+    fn interpreter(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Sh => "sh",
+            ScriptLanguage::Bash => "bash",
+            ScriptLanguage::Python3 => "python3",
+        }
+    }
+}
+
+impl fmt::Display for ScriptLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptLanguage::Sh => write!(f, "sh"),
+            ScriptLanguage::Bash => write!(f, "bash"),
+            ScriptLanguage::Python3 => write!(f, "python3"),
+        }
+    }
+}
+
+/// How to parse a script's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    /// Rows of fields; every row must have the same number of fields
+    Csv,
+    /// `KEY=value` lines; blank lines and `#` comments are skipped
+    Kv,
+    #[default]
+    Text,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Kv => write!(f, "kv"),
+            OutputFormat::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// Arguments for ScriptTool execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptToolArgs {
+    pub script: String,
+    #[serde(default)]
+    pub language: ScriptLanguage,
+    #[serde(default)]
+    pub output_format: Option<OutputFormat>,
+}
+
+/// Configuration for ScriptTool
+#[derive(Debug, Clone)]
+pub struct ScriptToolConfig {
+    /// Languages scripts may be written in ("sh", "bash", "python3")
+    pub allowed_languages: Vec<String>,
+    /// Wall-clock time a script may run before it is killed
+    pub timeout: Duration,
+    /// Bytes of stdout and of stderr kept from a script
+    pub max_output_bytes: usize,
+}
+
+impl Default for ScriptToolConfig {
+    fn default() -> Self {
+        Self {
+            allowed_languages: vec![
+                ScriptLanguage::Sh.to_string(),
+                ScriptLanguage::Bash.to_string(),
+                ScriptLanguage::Python3.to_string(),
+            ],
+            timeout: Duration::from_secs(30),
+            max_output_bytes: 16 * 1024,
+        }
+    }
+}
 
 /// Script tool for custom script execution
 #[derive(Clone)]
 pub struct ScriptTool {
     available_scripts: HashMap<String, String>,
+    config: ScriptToolConfig,
+    shell_patterns: Vec<Regex>,
+    python_patterns: Vec<Regex>,
 }
 
 impl ScriptTool {
     pub fn new() -> Self {
         Self {
             available_scripts: HashMap::new(),
+            config: ScriptToolConfig::default(),
+            shell_patterns: SafetyConfig::default().dangerous_patterns,
+            python_patterns: vec![
+                Regex::new(r"(?m)^\s*import\s+[\w \t,.]*\b(subprocess|os|socket)\b").unwrap(),
+                Regex::new(r"(?m)^\s*from\s+(subprocess|os|socket)\b").unwrap(),
+                Regex::new(r"__import__").unwrap(),
+            ],
         }
     }
-    
+
     pub fn with_script(mut self, name: String, path: String) -> Self {
         self.available_scripts.insert(name, path);
         self
     }
-    
-    fn validate(&self, input: &str) -> Result<()> {
-        // TODO: Validate script name exists
+
+    pub fn with_config(mut self, config: ScriptToolConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    fn validate(&self, args: &ScriptToolArgs) -> Result<()> {
+        let language = args.language.to_string();
+        if !self.config.allowed_languages.contains(&language) {
+            return Err(anyhow::anyhow!(
+                "Language '{}' is not allowed. Allowed languages are: {:?}",
+                language,
+                self.config.allowed_languages
+            ));
+        }
+
+        let patterns = match args.language {
+            ScriptLanguage::Sh | ScriptLanguage::Bash => &self.shell_patterns,
+            ScriptLanguage::Python3 => &self.python_patterns,
+        };
+
+        for pattern in patterns {
+            if let Some(m) = pattern.find(&args.script) {
+                return Err(anyhow::anyhow!(
+                    "{} script contains a forbidden pattern: '{}'",
+                    language,
+                    m.as_str().trim()
+                ));
+            }
+        }
+
         Ok(())
     }
+
+    /// Run a validated script, killing its process group when it exits or
+    /// times out
+    async fn execute(&self, args: &ScriptToolArgs) -> Result<ToolResult> {
+        let mut child = Command::new(args.language.interpreter())
+            .arg("-c")
+            .arg(&args.script)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start {}: {}", args.language.interpreter(), e))?;
+        let pid = child.id();
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let limit = self.config.max_output_bytes;
+        let run = async {
+            tokio::join!(read_capped(stdout, limit), read_capped(stderr, limit), child.wait())
+        };
+        let finished = tokio::time::timeout(self.config.timeout, run).await;
+
+        // Also takes down anything the script left running in the background
+        if let Some(pid) = pid {
+            kill_process_group(pid);
+        }
+
+        let (stdout, stderr, status) = match finished {
+            Ok(finished) => finished,
+            Err(_) => {
+                let _ = child.wait().await;
+                let error = format!("Script timed out after {}s and was killed", self.config.timeout.as_secs_f64());
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(error),
+                    metadata: Some(serde_json::json!({ "timed_out": true })),
+                });
+            }
+        };
+        let status = status?;
+        let (stdout, stdout_total) = stdout?;
+        let (stderr, stderr_total) = stderr?;
+
+        let output = capped_text(&stdout, stdout_total);
+        let stderr = capped_text(&stderr, stderr_total);
+        Ok(ToolResult {
+            success: status.success(),
+            output,
+            error: (!status.success()).then(|| match status.code() {
+                Some(code) => format!("Script exited with status {}: {}", code, stderr.trim()),
+                None => format!("Script was terminated by a signal: {}", stderr.trim()),
+            }),
+            metadata: Some(serde_json::json!({
+                "exit_code": status.code(),
+                "stderr": stderr,
+            })),
+        })
+    }
+}
+
+/// Read a stream to its end, keeping only the first `limit` bytes.
+/// Returns the kept bytes and the total read.
+async fn read_capped(mut reader: impl AsyncRead + Unpin, limit: usize) -> std::io::Result<(Vec<u8>, usize)> {
+    let mut kept = Vec::new();
+    let mut total = 0;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            return Ok((kept, total));
+        }
+        let keep = n.min(limit.saturating_sub(kept.len()));
+        kept.extend_from_slice(&buf[..keep]);
+        total += n;
+    }
+}
+
+/// Captured output as text, noting how much was cut off
+fn capped_text(kept: &[u8], total: usize) -> String {
+    let mut text = String::from_utf8_lossy(kept).into_owned();
+    if total > kept.len() {
+        text.push_str(&format!("\n... (truncated, {} total bytes)", total));
+    }
+    text
+}
+
+/// SIGKILL every process in the group led by `pid`. The group may already
+/// be gone.
+fn kill_process_group(pid: u32) {
+    // SAFETY: kill(2) has no memory safety requirements
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
 }
 
 impl RigTool for ScriptTool {
     const NAME: &'static str = "script";
-    
+
     type Error = ToolError;
-    type Args = ToolArgs;
+    type Args = ScriptToolArgs;
     type Output = ToolResult;
-    
+
     async fn definition(&self, _prompt: String) -> ToolDefinition {
         ToolDefinition {
             name: Self::NAME.to_string(),
-            description: "Execute pre-defined diagnostic scripts. \
-                         Available scripts: debug-pod, check-network, analyze-logs".to_string(),
+            description: format!(
+                "Execute diagnostic scripts. Allowed languages: {}",
+                self.config.allowed_languages.join(", ")
+            ),
             parameters: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "command": {
+                    "script": {
                         "type": "string",
-                        "description": "The script name to execute (e.g., 'debug-pod')"
+                        "description": "The script source to execute"
+                    },
+                    "language": {
+                        "type": "string",
+                        "enum": self.config.allowed_languages,
+                        "description": "Language of the script (defaults to 'sh')"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["json", "csv", "kv", "text"],
+                        "description": "Parse the script's output as JSON, CSV rows or KEY=value lines (defaults to 'text')"
                     }
                 },
-                "required": ["command"]
+                "required": ["script"]
             }),
         }
     }
-    
+
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
-        self.validate(&args.command)
+        self.validate(&args)
             .map_err(|e| ToolError::ValidationError(e.to_string()))?;
-        
-        // TODO: Implement actual script execution
-        Ok(ToolResult {
-            success: true,
-            output: format!("Script tool called with: {}", args.command),
-            error: None,
-            metadata: None,
-        })
+
+        let result = self.execute(&args).await
+            .map_err(ToolError::InternalError)?;
+        if !result.success {
+            return Ok(result);
+        }
+        Ok(with_parsed_output(result, args.output_format.unwrap_or_default()))
     }
-} 
\ No newline at end of file
+}
+
+/// Add the output parsed as `format` to the result metadata. The raw output
+/// is always kept; if it doesn't parse, a `parse_warning` is added instead.
+fn with_parsed_output(mut result: ToolResult, format: OutputFormat) -> ToolResult {
+    if format == OutputFormat::Text {
+        return result;
+    }
+
+    let mut metadata = match result.metadata.take() {
+        Some(serde_json::Value::Object(metadata)) => metadata,
+        _ => serde_json::Map::new(),
+    };
+    metadata.insert("output_format".to_string(), serde_json::json!(format));
+    match parse_output(&result.output, format) {
+        Ok(parsed) => {
+            metadata.insert("parsed_output".to_string(), parsed);
+        }
+        Err(e) => {
+            metadata.insert(
+                "parse_warning".to_string(),
+                serde_json::json!(format!("Could not parse output as {}, returning raw text: {}", format, e)),
+            );
+        }
+    }
+    result.metadata = Some(serde_json::Value::Object(metadata));
+    result
+}
+
+fn parse_output(output: &str, format: OutputFormat) -> std::result::Result<serde_json::Value, String> {
+    match format {
+        OutputFormat::Json => serde_json::from_str(output).map_err(|e| e.to_string()),
+        OutputFormat::Csv => parse_csv(output).map(|rows| serde_json::json!(rows)),
+        OutputFormat::Kv => parse_kv(output).map(|pairs| serde_json::json!(pairs)),
+        OutputFormat::Text => Ok(serde_json::Value::String(output.to_string())),
+    }
+}
+
+/// Parse RFC 4180 CSV: fields may be quoted, with `""` escaping a quote and
+/// quoted fields spanning lines
+fn parse_csv(input: &str) -> std::result::Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    quoted = false;
+                    if !matches!(chars.peek(), None | Some(',') | Some('\n') | Some('\r')) {
+                        return Err(format!("unexpected character after closing quote in row {}", rows.len() + 1));
+                    }
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '"' => return Err(format!("unexpected quote in unquoted field in row {}", rows.len() + 1)),
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    if let Some(width) = rows.first().map(Vec::len) {
+        if let Some((idx, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(format!("row {} has {} fields, expected {}", idx + 1, row.len(), width));
+        }
+    }
+    Ok(rows)
+}
+
+/// Parse `KEY=value` lines, skipping blank lines and `#` comments
+fn parse_kv(input: &str) -> std::result::Result<HashMap<String, String>, String> {
+    let mut pairs = HashMap::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                pairs.insert(key.trim().to_string(), value.trim().to_string());
+            }
+            _ => return Err(format!("line {} is not KEY=value: '{}'", idx + 1, line)),
+        }
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(language: ScriptLanguage, script: &str) -> ScriptToolArgs {
+        ScriptToolArgs { script: script.to_string(), language, output_format: None }
+    }
+
+    #[test]
+    fn test_validate_security_profiles() {
+        let tool = ScriptTool::new();
+
+        assert!(tool.validate(&args(ScriptLanguage::Sh, "df -h; free -m")).is_ok());
+        assert!(tool.validate(&args(ScriptLanguage::Bash, "rm -rf /tmp/cache")).is_err());
+
+        assert!(tool.validate(&args(ScriptLanguage::Python3, "import json, sys\nprint(sys.version)")).is_ok());
+        for script in [
+            "import subprocess",
+            "import sys, os",
+            "  from socket import create_connection",
+            "from os.path import join",
+            "m = __import__('os')",
+        ] {
+            let err = tool.validate(&args(ScriptLanguage::Python3, script)).unwrap_err();
+            assert!(err.to_string().contains("forbidden pattern"), "{}", script);
+        }
+
+        // Shell and Python profiles don't overlap
+        assert!(tool.validate(&args(ScriptLanguage::Sh, "import os")).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_languages() {
+        let tool = ScriptTool::new().with_config(ScriptToolConfig {
+            allowed_languages: vec!["python3".to_string()],
+            ..Default::default()
+        });
+
+        assert!(tool.validate(&args(ScriptLanguage::Python3, "print('ok')")).is_ok());
+        let err = tool.validate(&args(ScriptLanguage::Sh, "uptime")).unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+
+        let parsed: ScriptToolArgs = serde_json::from_value(serde_json::json!({ "script": "uptime" })).unwrap();
+        assert_eq!(parsed.language, ScriptLanguage::Sh);
+        assert_eq!(parsed.output_format, None);
+    }
+
+    fn output(text: &str) -> ToolResult {
+        ToolResult { success: true, output: text.to_string(), error: None, metadata: None }
+    }
+
+    #[test]
+    fn test_parsed_output() {
+        let result = with_parsed_output(output(r#"{"pods": 3, "ready": true}"#), OutputFormat::Json);
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["output_format"], "json");
+        assert_eq!(metadata["parsed_output"], serde_json::json!({ "pods": 3, "ready": true }));
+
+        let result = with_parsed_output(output("name,status\r\napi-0,Running\n\"worker, 1\",\"said \"\"hi\"\"\"\n"), OutputFormat::Csv);
+        assert_eq!(result.metadata.unwrap()["parsed_output"], serde_json::json!([
+            ["name", "status"],
+            ["api-0", "Running"],
+            ["worker, 1", "said \"hi\""]
+        ]));
+
+        let result = with_parsed_output(output("# disk usage\nROOT=42%\n\nDATA = 7%\nURL=http://x?a=b\n"), OutputFormat::Kv);
+        assert_eq!(result.metadata.unwrap()["parsed_output"], serde_json::json!({
+            "ROOT": "42%",
+            "DATA": "7%",
+            "URL": "http://x?a=b"
+        }));
+
+        // Raw text is returned unparsed
+        assert!(with_parsed_output(output("uptime: 3 days"), OutputFormat::Text).metadata.is_none());
+    }
+
+    #[test]
+    fn test_parse_failure_falls_back_to_text() {
+        for (text, format, reason) in [
+            ("not json", OutputFormat::Json, "expected ident"),
+            ("a,b\nc\n", OutputFormat::Csv, "row 2 has 1 fields, expected 2"),
+            ("a,\"b\n", OutputFormat::Csv, "unterminated quoted field"),
+            ("KEY=value\njust text\n", OutputFormat::Kv, "line 2 is not KEY=value"),
+        ] {
+            let result = with_parsed_output(output(text), format);
+            assert_eq!(result.output, text);
+            let metadata = result.metadata.unwrap();
+            assert!(metadata.get("parsed_output").is_none());
+            let warning = metadata["parse_warning"].as_str().unwrap();
+            assert!(warning.contains(&format!("Could not parse output as {}", format)), "{}", warning);
+            assert!(warning.contains(reason), "{}", warning);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let tool = ScriptTool::new();
+
+        let result = tool.call(ScriptToolArgs {
+            output_format: Some(OutputFormat::Kv),
+            ..args(ScriptLanguage::Sh, "echo PODS=3; echo warming up >&2")
+        }).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "PODS=3\n");
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["exit_code"], 0);
+        assert_eq!(metadata["stderr"], "warming up\n");
+        assert_eq!(metadata["parsed_output"], serde_json::json!({ "PODS": "3" }));
+
+        // The operator's environment isn't passed on
+        std::env::set_var("SCRIPT_TOOL_TEST_SECRET", "hunter2");
+        let result = tool.call(args(ScriptLanguage::Bash, "echo \"[$SCRIPT_TOOL_TEST_SECRET]\"")).await.unwrap();
+        assert_eq!(result.output, "[]\n");
+
+        let result = tool.call(args(ScriptLanguage::Sh, "echo broken >&2; exit 3")).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.unwrap(), "Script exited with status 3: broken");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_process_group() {
+        let tool = ScriptTool::new().with_config(ScriptToolConfig {
+            timeout: Duration::from_millis(300),
+            ..Default::default()
+        });
+        let pid_file = std::env::temp_dir().join(format!("script-tool-{}.pid", std::process::id()));
+
+        let started = std::time::Instant::now();
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let result = tool.call(args(ScriptLanguage::Sh, &script)).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!result.success);
+        assert_eq!(result.error.unwrap(), "Script timed out after 0.3s and was killed");
+        assert_eq!(result.metadata.unwrap()["timed_out"], true);
+
+        // The background sleep went down with the script
+        let pid = std::fs::read_to_string(&pid_file).unwrap().trim().to_string();
+        std::fs::remove_file(&pid_file).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let running = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map(|stat| !stat.rsplit(')').next().unwrap_or_default().trim_start().starts_with('Z'))
+            .unwrap_or(false);
+        assert!(!running, "sleep {} is still running", pid);
+    }
+
+    #[tokio::test]
+    async fn test_output_truncation() {
+        let tool = ScriptTool::new().with_config(ScriptToolConfig {
+            max_output_bytes: 1000,
+            ..Default::default()
+        });
+
+        let result = tool.call(args(ScriptLanguage::Sh, "head -c 100000 /dev/zero | tr '\\0' a; head -c 5000 /dev/zero >&2")).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, format!("{}\n... (truncated, 100000 total bytes)", "a".repeat(1000)));
+        let stderr = result.metadata.unwrap()["stderr"].as_str().unwrap().to_string();
+        assert!(stderr.ends_with("\n... (truncated, 5000 total bytes)"));
+    }
+}
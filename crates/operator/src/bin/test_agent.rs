@@ -5,9 +5,12 @@
 use punching_fist_operator::agent::{
     AgentRuntime, LLMConfig, AgentInput, AgentOutput
 };
-use punching_fist_operator::agent::tools::{PromQLTool, CurlTool, ScriptTool, KubectlTool};
+use punching_fist_operator::agent::tools::{PromQLTool, CurlTool, ScriptTool, KubectlTool, IngressTool};
+use punching_fist_operator::store::{ChatSession, SqliteStore, Store};
 use std::collections::HashMap;
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::io::{self, Write};
 use anyhow::Result;
 use clap::{Parser, Subcommand};
@@ -99,6 +102,14 @@ enum Commands {
         /// Model to use (if not mock)
         #[arg(short, long)]
         model: Option<String>,
+        
+        /// Resume an existing chat session
+        #[arg(short, long)]
+        session: Option<String>,
+        
+        /// SQLite database used to persist chat history
+        #[arg(long, default_value = "data/punchingfist.db")]
+        database: PathBuf,
     },
     
     /// Test the new investigator agent
@@ -219,8 +230,8 @@ async fn main() -> Result<()> {
         Commands::Scenario { name, provider } => {
             run_scenario(&name, &provider).await?;
         }
-        Commands::Chatbot { provider, model } => {
-            run_chatbot_mode(&provider, model).await?;
+        Commands::Chatbot { provider, model, session, database } => {
+            run_chatbot_mode(&provider, model, session, database).await?;
         }
         Commands::Investigate { provider, approval } => {
             run_investigator_mode_interactive(&provider, approval).await?;
@@ -307,8 +318,11 @@ async fn test_anthropic_provider(goal: &str, model: &str, api_key: Option<String
             }
         }
         
-        agent_runtime.add_tool("promql".to_string(), PromQLTool::new(prometheus_endpoint));
+        agent_runtime.add_tool("promql".to_string(), promql_tool(prometheus_endpoint));
         agent_runtime.add_tool("curl".to_string(), CurlTool::new());
+        if let Ok(ingress_tool) = IngressTool::infer().await {
+            agent_runtime.add_tool("ingress".to_string(), ingress_tool);
+        }
         agent_runtime.add_tool("script".to_string(), ScriptTool::new());
     }
     
@@ -374,8 +388,11 @@ async fn test_openai_provider(goal: &str, model: &str, api_key: Option<String>,
             }
         }
         
-        agent_runtime.add_tool("promql".to_string(), PromQLTool::new(prometheus_endpoint));
+        agent_runtime.add_tool("promql".to_string(), promql_tool(prometheus_endpoint));
         agent_runtime.add_tool("curl".to_string(), CurlTool::new());
+        if let Ok(ingress_tool) = IngressTool::infer().await {
+            agent_runtime.add_tool("ingress".to_string(), ingress_tool);
+        }
         agent_runtime.add_tool("script".to_string(), ScriptTool::new());
     }
     
@@ -547,7 +564,12 @@ async fn run_scenario(scenario: &str, provider: &str) -> Result<()> {
     Ok(())
 }
 
-async fn run_chatbot_mode(provider: &str, model: Option<String>) -> Result<()> {
+async fn run_chatbot_mode(
+    provider: &str,
+    model: Option<String>,
+    session: Option<String>,
+    database: PathBuf,
+) -> Result<()> {
     println!("=== Interactive Chatbot Mode ===");
     println!("Provider: {}", provider);
     if let Some(ref m) = model {
@@ -615,18 +637,46 @@ async fn run_chatbot_mode(provider: &str, model: Option<String>) -> Result<()> {
         }
     }
     
-    agent_runtime.add_tool("promql".to_string(), PromQLTool::new(prometheus_endpoint));
+    agent_runtime.add_tool("promql".to_string(), promql_tool(prometheus_endpoint));
     agent_runtime.add_tool("curl".to_string(), CurlTool::new());
+    if let Ok(ingress_tool) = IngressTool::infer().await {
+        agent_runtime.add_tool("ingress".to_string(), ingress_tool);
+    }
     agent_runtime.add_tool("script".to_string(), ScriptTool::new());
 
     println!("Tools initialized: {:?}", agent_runtime.list_tools());
     
     let chatbot = agent_runtime.get_chatbot_agent();
-    let mut history: Vec<Message> = Vec::new();
-    let session_id = format!("cli-session-{}", std::time::SystemTime::now()
+    let session_id = session.unwrap_or_else(|| format!("cli-session-{}", std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
-        .as_secs());
+        .as_secs()));
+    
+    // Load any saved history for this session
+    let store = match open_chat_store(database).await {
+        Ok(store) => Some(store),
+        Err(e) => {
+            println!("⚠️  Chat history will not be saved: {}", e);
+            None
+        }
+    };
+    let mut chat_session = match &store {
+        Some(store) => store.get_chat_session(&session_id).await?,
+        None => None,
+    }
+    .unwrap_or_else(|| ChatSession {
+        session_id: session_id.clone(),
+        user_id: "cli-user".to_string(),
+        messages: serde_json::json!([]),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    });
+    let mut history: Vec<Message> = serde_json::from_value(chat_session.messages.clone())?;
+    
+    println!("Session: {}", session_id);
+    if !history.is_empty() {
+        println!("Resumed {} message(s) of history", history.len());
+    }
     
     println!("Chatbot ready! You can ask questions about your Kubernetes cluster.\n");
     
@@ -667,6 +717,14 @@ async fn run_chatbot_mode(provider: &str, model: Option<String>) -> Result<()> {
                 // Add to history
                 history.push(Message::user(input));
                 history.push(Message::assistant(&message));
+                
+                if let Some(store) = &store {
+                    chat_session.messages = serde_json::to_value(&history)?;
+                    chat_session.updated_at = Utc::now();
+                    if let Err(e) = store.save_chat_session(chat_session.clone()).await {
+                        eprintln!("Failed to save chat history: {}", e);
+                    }
+                }
             }
             AgentOutput::Error { message, .. } => {
                 eprintln!("Error: {}", message);
@@ -680,6 +738,16 @@ async fn run_chatbot_mode(provider: &str, model: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Open the SQLite store used for CLI chat history
+async fn open_chat_store(path: PathBuf) -> Result<Arc<dyn Store>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await?;
+    store.init().await?;
+    Ok(Arc::new(store))
+}
+
 async fn run_investigator_mode_interactive(provider: &str, enable_approval: bool) -> Result<()> {
     println!("=== Interactive Investigator Mode ===");
     println!("Provider: {}", provider);
@@ -837,8 +905,11 @@ async fn run_investigator_mode_interactive(provider: &str, enable_approval: bool
         }
     }
     
-    agent_runtime.add_tool("promql".to_string(), PromQLTool::new(prometheus_endpoint));
+    agent_runtime.add_tool("promql".to_string(), promql_tool(prometheus_endpoint));
     agent_runtime.add_tool("curl".to_string(), CurlTool::new());
+    if let Ok(ingress_tool) = IngressTool::infer().await {
+        agent_runtime.add_tool("ingress".to_string(), ingress_tool);
+    }
     agent_runtime.add_tool("script".to_string(), ScriptTool::new());
     
     let investigator = agent_runtime.get_investigator_agent();
@@ -941,6 +1012,19 @@ async fn run_investigator_mode_interactive(provider: &str, enable_approval: bool
     Ok(())
 }
 
+/// PromQL tool for the endpoint, authenticated with `PROMETHEUS_TOKEN` or
+/// `PROMETHEUS_USERNAME`/`PROMETHEUS_PASSWORD` when set
+fn promql_tool(prometheus_endpoint: String) -> PromQLTool {
+    let tool = PromQLTool::new(prometheus_endpoint);
+    if let Ok(token) = env::var("PROMETHEUS_TOKEN") {
+        return tool.with_bearer_token(token);
+    }
+    match (env::var("PROMETHEUS_USERNAME"), env::var("PROMETHEUS_PASSWORD")) {
+        (Ok(username), Ok(password)) => tool.with_basic_auth(username, password),
+        _ => tool,
+    }
+}
+
 fn print_results(result: &punching_fist_operator::agent::result::AgentResult) {
     println!("=== Investigation Results ===");
     println!();
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use tracing::warn;
 
-use crate::store::{DatabaseConfig, DatabaseType};
+use crate::store::{DatabaseConfig, DatabaseType, DEFAULT_DEDUP_WINDOW_MINUTES};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TaskExecutionMode {
@@ -21,16 +23,23 @@ impl Default for TaskExecutionMode {
 pub struct ExecutionConfig {
     #[serde(default)]
     pub mode: TaskExecutionMode,
+    /// Leave the pods of failed CLI steps in the cluster for debugging
+    #[serde(default)]
+    pub keep_failed_pods: bool,
 }
 
 impl Default for ExecutionConfig {
     fn default() -> Self {
         Self {
             mode: TaskExecutionMode::Kubernetes,
+            keep_failed_pods: false,
         }
     }
 }
 
+/// Name of the ConfigMap watched for configuration changes
+pub const CONFIG_MAP_NAME: &str = "punching-fist-config";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub server: ServerConfig,
@@ -39,11 +48,134 @@ pub struct Config {
     pub agent: AgentConfig,
     #[serde(default)]
     pub execution: ExecutionConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub sink_failures: SinkFailureConfig,
+    #[serde(default)]
+    pub custom_resources: CustomResourceConfig,
+    /// LLM prices keyed by provider, then model, used to enforce workflow
+    /// `maxCostUsd` caps
+    #[serde(default)]
+    pub cost_per_token: HashMap<String, HashMap<String, TokenCost>>,
+    #[serde(default)]
+    pub auth: AuthConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenCost {
+    /// USD per prompt token
+    pub input: f64,
+    /// USD per completion token
+    pub output: f64,
+}
+
+impl Config {
+    /// Price of `model` served by `provider`, if configured
+    pub fn token_cost(&self, provider: &str, model: &str) -> Option<TokenCost> {
+        self.cost_per_token.get(provider)?.get(model).copied()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Require a Kubernetes bearer token, validated with a TokenReview, on
+    /// API requests
+    pub enabled: bool,
+    /// Users and groups with full access, including /admin
+    pub admins: RoleSubjects,
+    /// Users and groups that can read everything and create alerts
+    pub editors: RoleSubjects,
+    /// Users and groups with read-only access
+    pub viewers: RoleSubjects,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            admins: RoleSubjects {
+                users: Vec::new(),
+                groups: vec!["system:masters".to_string()],
+            },
+            editors: RoleSubjects::default(),
+            viewers: RoleSubjects {
+                users: Vec::new(),
+                groups: vec!["system:authenticated".to_string()],
+            },
+        }
+    }
+}
+
+/// Kubernetes users and groups a role is granted to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoleSubjects {
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
+
+impl RoleSubjects {
+    /// Whether `username` or one of `groups` is listed
+    pub fn matches(&self, username: &str, groups: &[String]) -> bool {
+        self.users.iter().any(|u| u == username)
+            || self.groups.iter().any(|g| groups.contains(g))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Directory database backups are written to
+    pub dir: PathBuf,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("data/backups"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkFailureConfig {
+    /// Failures of one sink type within the last hour above which operators
+    /// are alerted
+    pub alert_threshold: u64,
+}
+
+impl Default for SinkFailureConfig {
+    fn default() -> Self {
+        Self { alert_threshold: 10 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomResourceConfig {
+    /// Stored resources of one kind above which a Kubernetes Event is emitted
+    pub alert_threshold: u64,
+}
+
+impl Default for CustomResourceConfig {
+    fn default() -> Self {
+        Self { alert_threshold: 1000 }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub addr: String,
+    /// Bearer token required by the /admin endpoints; they are disabled when unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    /// Maximum accepted webhook request body size in bytes
+    #[serde(default = "default_max_payload_bytes")]
+    pub max_payload_bytes: usize,
+}
+
+fn default_max_payload_bytes() -> usize {
+    1024 * 1024
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +190,13 @@ pub struct AgentConfig {
     pub model: String,
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    /// How long agent steps share PromQL query results
+    #[serde(default = "default_promql_cache_ttl_seconds")]
+    pub promql_cache_ttl_seconds: u64,
+}
+
+fn default_promql_cache_ttl_seconds() -> u64 {
+    crate::agent::tools::promql::DEFAULT_CACHE_TTL_SECONDS
 }
 
 impl Config {
@@ -79,6 +218,13 @@ impl Config {
             server: ServerConfig {
                 addr: std::env::var("SERVER_ADDR")
                     .unwrap_or_else(|_| "0.0.0.0:8080".to_string()),
+                admin_token: std::env::var("ADMIN_TOKEN")
+                    .ok()
+                    .filter(|t| !t.is_empty()),
+                max_payload_bytes: std::env::var("MAX_PAYLOAD_BYTES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(default_max_payload_bytes),
             },
             database: DatabaseConfig {
                 db_type: match std::env::var("DATABASE_TYPE")
@@ -94,6 +240,10 @@ impl Config {
                     .ok()
                     .or_else(|| Some(PathBuf::from("data/punching-fist.db"))),
                 connection_string: std::env::var("DATABASE_URL").ok(),
+                dedup_window_minutes: std::env::var("ALERT_DEDUP_WINDOW_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_DEDUP_WINDOW_MINUTES),
             },
             kube: KubeConfig {
                 namespace: std::env::var("KUBE_NAMESPACE")
@@ -115,6 +265,10 @@ impl Config {
                 max_tokens: std::env::var("LLM_MAX_TOKENS")
                     .ok()
                     .and_then(|v| v.parse().ok()),
+                promql_cache_ttl_seconds: std::env::var("PROMQL_CACHE_TTL_SECONDS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(default_promql_cache_ttl_seconds),
             },
             execution: ExecutionConfig {
                 mode: match std::env::var("EXECUTION_MODE")
@@ -125,6 +279,33 @@ impl Config {
                     "kubernetes" => TaskExecutionMode::Kubernetes,
                     _ => TaskExecutionMode::Local,
                 },
+                keep_failed_pods: std::env::var("KEEP_FAILED_PODS")
+                    .map(|v| v == "true")
+                    .unwrap_or(false),
+            },
+            backup: BackupConfig {
+                dir: std::env::var("BACKUP_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| BackupConfig::default().dir),
+            },
+            sink_failures: SinkFailureConfig {
+                alert_threshold: std::env::var("SINK_FAILURE_ALERT_THRESHOLD")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| SinkFailureConfig::default().alert_threshold),
+            },
+            custom_resources: CustomResourceConfig {
+                alert_threshold: std::env::var("CUSTOM_RESOURCE_ALERT_THRESHOLD")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| CustomResourceConfig::default().alert_threshold),
+            },
+            cost_per_token: HashMap::new(),
+            auth: AuthConfig {
+                enabled: std::env::var("AUTH_ENABLED")
+                    .map(|v| v == "true")
+                    .unwrap_or(false),
+                ..Default::default()
             },
         };
 
@@ -133,17 +314,23 @@ impl Config {
             tracing::warn!("No LLM API key found (ANTHROPIC_API_KEY or OPENAI_API_KEY). Using mock provider for testing.");
         }
 
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> crate::Result<()> {
         // Validate database configuration
-        match config.database.db_type {
+        match self.database.db_type {
             DatabaseType::Postgres => {
-                if config.database.connection_string.is_none() {
+                if self.database.connection_string.is_none() {
                     return Err(crate::Error::Config(
                         "DATABASE_URL must be set when using PostgreSQL".to_string(),
                     ));
                 }
             }
             DatabaseType::Sqlite => {
-                if config.database.sqlite_path.is_none() {
+                if self.database.sqlite_path.is_none() {
                     return Err(crate::Error::Config(
                         "SQLITE_PATH must be set when using SQLite".to_string(),
                     ));
@@ -151,7 +338,70 @@ impl Config {
             }
         }
 
-        Ok(config)
+        if self.server.max_payload_bytes == 0 {
+            return Err(crate::Error::Config(
+                "MAX_PAYLOAD_BYTES must be greater than zero".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Overlay TOML documents, such as the values of a ConfigMap's `data`,
+    /// onto this config. Later documents take precedence.
+    pub fn with_toml_overrides<'a>(&self, documents: impl IntoIterator<Item = &'a str>) -> crate::Result<Self> {
+        let mut merged = toml::Value::try_from(self)
+            .map_err(|e| crate::Error::Config(format!("Failed to serialize config: {}", e)))?;
+        for document in documents {
+            let overrides: toml::Table = toml::from_str(document)
+                .map_err(|e| crate::Error::Config(format!("Invalid TOML: {}", e)))?;
+            merge_toml(&mut merged, toml::Value::Table(overrides));
+        }
+        merged.try_into()
+            .map_err(|e| crate::Error::Config(format!("Invalid configuration: {}", e)))
+    }
+
+    /// Replace this config with a reloaded one. Settings that only take
+    /// effect at startup are kept and a warning is logged if they changed.
+    pub fn apply_reload(&mut self, reloaded: Config) {
+        let database_changed = serde_json::to_value(&self.database).ok()
+            != serde_json::to_value(&reloaded.database).ok();
+        if database_changed {
+            warn!("Ignoring change to database configuration; restart the operator to apply it");
+        }
+        if reloaded.server.addr != self.server.addr {
+            warn!("Ignoring change to server.addr; restart the operator to apply it");
+        }
+        if reloaded.server.max_payload_bytes != self.server.max_payload_bytes {
+            warn!("Ignoring change to server.max_payload_bytes; restart the operator to apply it");
+        }
+
+        *self = Config {
+            server: ServerConfig {
+                addr: std::mem::take(&mut self.server.addr),
+                max_payload_bytes: self.server.max_payload_bytes,
+                ..reloaded.server
+            },
+            database: self.database.clone(),
+            ..reloaded
+        };
+    }
+}
+
+/// Recursively merge `overrides` into `base`, replacing non-table values
+fn merge_toml(base: &mut toml::Value, overrides: toml::Value) {
+    match (base, overrides) {
+        (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
     }
 }
 
@@ -160,11 +410,14 @@ impl Default for Config {
         Self {
             server: ServerConfig {
                 addr: "0.0.0.0:8080".to_string(),
+                admin_token: None,
+                max_payload_bytes: default_max_payload_bytes(),
             },
             database: DatabaseConfig {
                 db_type: DatabaseType::Sqlite,
                 sqlite_path: Some(PathBuf::from("data/punching-fist.db")),
                 connection_string: None,
+                dedup_window_minutes: DEFAULT_DEDUP_WINDOW_MINUTES,
             },
             kube: KubeConfig {
                 namespace: "default".to_string(),
@@ -175,8 +428,60 @@ impl Default for Config {
                 model: "claude-3-5-sonnet".to_string(),
                 temperature: Some(0.7),
                 max_tokens: Some(4096),
+                promql_cache_ttl_seconds: default_promql_cache_ttl_seconds(),
             },
             execution: ExecutionConfig::default(),
+            backup: BackupConfig::default(),
+            sink_failures: SinkFailureConfig::default(),
+            custom_resources: CustomResourceConfig::default(),
+            cost_per_token: HashMap::new(),
+            auth: AuthConfig::default(),
         }
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_from_toml() {
+        let base = Config::default();
+        let reloaded = base.with_toml_overrides([
+            "[agent]\nmodel = \"gpt-4\"\ntemperature = 0.2",
+            "[server]\naddr = \"0.0.0.0:9090\"\nadmin_token = \"secret\"",
+        ]).unwrap();
+        assert_eq!(reloaded.agent.model, "gpt-4");
+        assert_eq!(reloaded.agent.provider, base.agent.provider);
+        reloaded.validate().unwrap();
+
+        let mut current = base.clone();
+        current.apply_reload(reloaded);
+        assert_eq!(current.agent.model, "gpt-4");
+        assert_eq!(current.agent.temperature, Some(0.2));
+        assert_eq!(current.server.admin_token.as_deref(), Some("secret"));
+        // The bind address can only change on restart
+        assert_eq!(current.server.addr, base.server.addr);
+
+        let priced = base.with_toml_overrides([
+            "[cost_per_token.anthropic.\"claude-3-5-sonnet\"]\ninput = 0.000003\noutput = 0.000015",
+        ]).unwrap();
+        assert_eq!(
+            priced.token_cost("anthropic", "claude-3-5-sonnet"),
+            Some(TokenCost { input: 0.000003, output: 0.000015 })
+        );
+        assert_eq!(priced.token_cost("openai", "claude-3-5-sonnet"), None);
+
+        let secured = base.with_toml_overrides([
+            "[auth]\nenabled = true\n[auth.editors]\ngroups = [\"sre\"]",
+        ]).unwrap();
+        assert!(secured.auth.enabled);
+        assert!(secured.auth.editors.matches("jane", &["sre".to_string()]));
+        assert!(!secured.auth.editors.matches("sre", &[]));
+        // Unset roles keep their defaults
+        assert!(secured.auth.admins.matches("root", &["system:masters".to_string()]));
+
+        assert!(base.with_toml_overrides(["[agent"]).is_err());
+        let invalid = base.with_toml_overrides(["[server]\nmax_payload_bytes = 0"]).unwrap();
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{
+    api::Api,
+    runtime::watcher::{self, Event},
+    Client,
+};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use crate::{
+    config::{Config, CONFIG_MAP_NAME},
+    Result,
+};
+
+/// Watches the `punching-fist-config` ConfigMap and hot-reloads the shared
+/// configuration whenever it changes.
+pub struct ConfigMapWatcher {
+    client: Client,
+    namespace: String,
+    /// Configuration loaded at startup; ConfigMap values are overlaid on it
+    base: Config,
+    config: Arc<RwLock<Config>>,
+}
+
+impl ConfigMapWatcher {
+    pub fn new(client: Client, namespace: String, config: Arc<RwLock<Config>>, base: Config) -> Self {
+        Self {
+            client,
+            namespace,
+            base,
+            config,
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Watching ConfigMap {}/{} for configuration changes", self.namespace, CONFIG_MAP_NAME);
+
+        let config_maps: Api<ConfigMap> = Api::namespaced(self.client.clone(), &self.namespace);
+        let watcher_config = watcher::Config::default()
+            .fields(&format!("metadata.name={}", CONFIG_MAP_NAME));
+
+        let mut events = watcher::watcher(config_maps, watcher_config).boxed();
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(Event::Applied(config_map)) => self.reload(&config_map).await,
+                Ok(Event::Restarted(config_maps)) => {
+                    for config_map in &config_maps {
+                        self.reload(config_map).await;
+                    }
+                }
+                Ok(Event::Deleted(_)) => {
+                    info!("ConfigMap {} deleted; keeping the current configuration", CONFIG_MAP_NAME);
+                }
+                Err(e) => error!("ConfigMap watch error: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reload(&self, config_map: &ConfigMap) {
+        match self.parse(config_map) {
+            Ok(reloaded) => {
+                self.config.write().await.apply_reload(reloaded);
+                info!("Reloaded configuration from ConfigMap {}", CONFIG_MAP_NAME);
+            }
+            Err(e) => error!("Ignoring invalid ConfigMap {}: {}", CONFIG_MAP_NAME, e),
+        }
+    }
+
+    /// Overlay every `data` entry of the ConfigMap, parsed as TOML, on the base config
+    fn parse(&self, config_map: &ConfigMap) -> Result<Config> {
+        let documents = config_map.data.iter().flat_map(|data| data.values().map(String::as_str));
+        let config = self.base.with_toml_overrides(documents)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
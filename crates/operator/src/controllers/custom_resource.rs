@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use k8s_openapi::api::core::v1::ObjectReference;
+use kube::{
+    runtime::events::{Event, EventType, Recorder, Reporter},
+    Client,
+};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::{config::Config, store::Store, Error, Result};
+
+/// Stored resources fetched per page when tallying namespaces
+const CUSTOM_RESOURCE_PAGE_SIZE: i64 = 500;
+
+/// Namespaces named in the event emitted for a kind over the threshold
+const TOP_NAMESPACES: usize = 5;
+
+const KINDS: [&str; 3] = ["Source", "Workflow", "Sink"];
+
+/// Periodically counts the stored custom resources of each kind and emits a
+/// Warning event on the kind's CRD when there are more than the configured
+/// threshold.
+pub struct CustomResourceMonitor {
+    client: Client,
+    store: Arc<dyn Store>,
+    config: Arc<RwLock<Config>>,
+    interval: Duration,
+}
+
+impl CustomResourceMonitor {
+    pub fn new(client: Client, store: Arc<dyn Store>, config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            client,
+            store,
+            config,
+            interval: Duration::from_secs(300),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Starting custom resource monitor");
+
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.check().await {
+                error!("Custom resource check failed: {}", e);
+            }
+        }
+    }
+
+    /// Count the resources of each kind, emitting an event for every kind
+    /// over the threshold. Returns the counts per kind.
+    pub async fn check(&self) -> Result<HashMap<String, i64>> {
+        let (threshold, namespace) = {
+            let config = self.config.read().await;
+            (config.custom_resources.alert_threshold, config.kube.namespace.clone())
+        };
+
+        let mut counts = HashMap::new();
+        for kind in KINDS {
+            let count = self.store.count_custom_resources(kind, None).await?;
+            counts.insert(kind.to_string(), count);
+            if count as u64 <= threshold {
+                continue;
+            }
+
+            warn!("{} {} resources stored (threshold {})", count, kind, threshold);
+            let namespaces = self.count_by_namespace(kind).await?;
+            self.publish(kind, &namespace, count, threshold, &namespaces).await?;
+        }
+
+        Ok(counts)
+    }
+
+    /// Resources of `kind` per namespace, most first, read a page at a time
+    async fn count_by_namespace(&self, kind: &str) -> Result<Vec<(String, i64)>> {
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        let mut offset = 0;
+        loop {
+            let page = self.store
+                .list_custom_resources(kind, None, true, CUSTOM_RESOURCE_PAGE_SIZE, offset)
+                .await?;
+            for resource in &page {
+                *counts.entry(resource.namespace.clone()).or_default() += 1;
+            }
+            if (page.len() as i64) < CUSTOM_RESOURCE_PAGE_SIZE {
+                break;
+            }
+            offset += CUSTOM_RESOURCE_PAGE_SIZE;
+        }
+
+        let mut counts: Vec<(String, i64)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(counts)
+    }
+
+    async fn publish(
+        &self,
+        kind: &str,
+        namespace: &str,
+        count: i64,
+        threshold: u64,
+        namespaces: &[(String, i64)],
+    ) -> Result<()> {
+        let reporter = Reporter {
+            controller: "punching-fist".to_string(),
+            instance: std::env::var("POD_NAME").ok(),
+        };
+        let reference = ObjectReference {
+            api_version: Some("apiextensions.k8s.io/v1".to_string()),
+            kind: Some("CustomResourceDefinition".to_string()),
+            name: Some(format!("{}s.punchingfist.io", kind.to_lowercase())),
+            namespace: Some(namespace.to_string()),
+            ..Default::default()
+        };
+
+        let top: Vec<String> = namespaces.iter()
+            .take(TOP_NAMESPACES)
+            .map(|(namespace, count)| format!("{} ({})", namespace, count))
+            .collect();
+        let note = format!(
+            "{} {} resources stored, above the threshold of {}. Most are in: {}",
+            count, kind, threshold, top.join(", ")
+        );
+
+        Recorder::new(self.client.clone(), reporter, reference)
+            .publish(Event {
+                type_: EventType::Warning,
+                reason: "TooManyResources".to_string(),
+                note: Some(note),
+                action: "CountResources".to_string(),
+                secondary: None,
+            })
+            .await
+            .map_err(|e| Error::Kubernetes(format!("Failed to publish {} count event: {}", kind, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{CustomResource, SqliteStore};
+    use axum::{extract::Path, routing::post, Json, Router};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_check_emits_event_over_threshold() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = Arc::new(SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap());
+        store.init().await.unwrap();
+
+        let now = Utc::now();
+        for (namespace, name) in [("team-a", "a1"), ("team-a", "a2"), ("team-b", "b1")] {
+            store.save_custom_resource(CustomResource {
+                id: Uuid::new_v4(),
+                api_version: "punchingfist.io/v1alpha1".to_string(),
+                kind: "Workflow".to_string(),
+                name: name.to_string(),
+                namespace: namespace.to_string(),
+                spec: serde_json::json!({}),
+                status: None,
+                created_at: now,
+                updated_at: now,
+            }).await.unwrap();
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let app = Router::new().route("/apis/events.k8s.io/v1/namespaces/{namespace}/events", post(
+            move |Path(namespace): Path<String>, Json(event): Json<serde_json::Value>| async move {
+                tx.send((namespace, event.clone())).unwrap();
+                Json(event)
+            },
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = Client::try_from(kube::Config::new(url.parse().unwrap())).unwrap();
+
+        let mut config = Config::default();
+        config.custom_resources.alert_threshold = 2;
+        let monitor = CustomResourceMonitor::new(client, store, Arc::new(RwLock::new(config)));
+        let counts = monitor.check().await.unwrap();
+        assert_eq!(counts.get("Workflow"), Some(&3));
+        assert_eq!(counts.get("Source"), Some(&0));
+
+        // Only the kind over the threshold gets an event
+        let (namespace, event) = rx.recv().await.unwrap();
+        assert!(rx.try_recv().is_err());
+        assert_eq!(namespace, "default");
+        assert_eq!(event["type"], "Warning");
+        assert_eq!(event["reason"], "TooManyResources");
+        assert_eq!(event["regarding"]["name"], "workflows.punchingfist.io");
+        assert_eq!(
+            event["note"],
+            "3 Workflow resources stored, above the threshold of 2. Most are in: team-a (2), team-b (1)"
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use tokio::sync::RwLock;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::{
+    crd::source::{AlertSeverity as PolicySeverity, EscalationPolicy},
+    store::{Alert, AlertSeverity, AlertStatus, SourceEvent, SourceType, Store},
+    Result,
+};
+
+/// Maximum number of triaging alerts inspected per check
+const ESCALATION_BATCH_SIZE: i64 = 500;
+
+struct SourcePolicy {
+    source_name: String,
+    policy: EscalationPolicy,
+}
+
+/// Periodically escalates alerts that have been triaging longer than their
+/// source's escalation policy allows.
+pub struct EscalationController {
+    store: Arc<dyn Store>,
+    policies: RwLock<HashMap<Uuid, SourcePolicy>>,
+    interval: Duration,
+}
+
+impl EscalationController {
+    pub fn new(store: Arc<dyn Store>) -> Self {
+        Self {
+            store,
+            policies: RwLock::new(HashMap::new()),
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set or clear the escalation policy for a source
+    pub async fn set_policy(&self, source_id: Uuid, source_name: &str, policy: Option<EscalationPolicy>) {
+        let mut policies = self.policies.write().await;
+        match policy {
+            Some(policy) => {
+                policies.insert(source_id, SourcePolicy {
+                    source_name: source_name.to_string(),
+                    policy,
+                });
+            }
+            None => {
+                policies.remove(&source_id);
+            }
+        }
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Starting Escalation controller");
+
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            match self.check_alerts(Utc::now()).await {
+                Ok(0) => {}
+                Ok(count) => info!("Escalated {} alert(s)", count),
+                Err(e) => error!("Escalation check failed: {}", e),
+            }
+        }
+    }
+
+    /// Escalate every triaging alert whose policy threshold has passed at `now`.
+    /// Returns the number of alerts escalated.
+    pub async fn check_alerts(&self, now: DateTime<Utc>) -> Result<usize> {
+        let policies = self.policies.read().await;
+        if policies.is_empty() {
+            return Ok(0);
+        }
+
+        let alerts = self.store.list_alerts_by_status(AlertStatus::Triaging, ESCALATION_BATCH_SIZE).await?;
+        let mut escalated = 0;
+
+        for alert in alerts {
+            let Some(source) = alert.source_id.and_then(|id| policies.get(&id)) else {
+                continue;
+            };
+
+            let target = to_store_severity(source.policy.escalate_to);
+            if severity_rank(alert.severity) >= severity_rank(target) {
+                continue;
+            }
+
+            let triaging_since = alert.triage_started_at.unwrap_or(alert.received_at);
+            let age_minutes = (now - triaging_since).num_minutes();
+            if age_minutes < i64::from(source.policy.after_minutes) {
+                debug!("Alert {} triaging for {}m, below escalation threshold", alert.id, age_minutes);
+                continue;
+            }
+
+            self.escalate(&alert, target, &source.source_name, source.policy.after_minutes, now).await?;
+            escalated += 1;
+        }
+
+        Ok(escalated)
+    }
+
+    async fn escalate(
+        &self,
+        alert: &Alert,
+        target: AlertSeverity,
+        source_name: &str,
+        after_minutes: u32,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        info!(
+            "Escalating alert {} ({}) from {} to {} after {} minutes in triage",
+            alert.id, alert.alert_name, alert.severity, target, after_minutes
+        );
+
+        let note = json!({
+            "from": alert.severity.to_string(),
+            "to": target.to_string(),
+            "after_minutes": after_minutes,
+            "escalated_at": now.to_rfc3339(),
+            "reason": format!("Alert was not resolved within {} minutes of triage", after_minutes),
+        });
+
+        let mut analysis = match alert.ai_analysis.clone() {
+            Some(serde_json::Value::Object(map)) => map,
+            Some(other) => {
+                let mut map = serde_json::Map::new();
+                map.insert("analysis".to_string(), other);
+                map
+            }
+            None => serde_json::Map::new(),
+        };
+        analysis.insert("auto_escalation".to_string(), note.clone());
+
+        self.store.update_alert_severity(alert.id, target).await?;
+        self.store.update_alert_status(alert.id, AlertStatus::Escalated).await?;
+        self.store.update_alert_ai_analysis(
+            alert.id,
+            serde_json::Value::Object(analysis),
+            alert.ai_confidence.unwrap_or(0.0),
+        ).await?;
+
+        self.store.save_source_event(SourceEvent {
+            id: Uuid::new_v4(),
+            source_name: source_name.to_string(),
+            source_type: SourceType::Escalation,
+            event_data: json!({
+                "alert_id": alert.id,
+                "alert_name": alert.alert_name,
+                "escalation": note,
+            }),
+            workflow_triggered: None,
+            received_at: now,
+        }).await?;
+
+        Ok(())
+    }
+}
+
+fn to_store_severity(severity: PolicySeverity) -> AlertSeverity {
+    match severity {
+        PolicySeverity::Critical => AlertSeverity::Critical,
+        PolicySeverity::Warning => AlertSeverity::Warning,
+        PolicySeverity::Info => AlertSeverity::Info,
+    }
+}
+
+fn severity_rank(severity: AlertSeverity) -> u8 {
+    match severity {
+        AlertSeverity::Info => 0,
+        AlertSeverity::Warning => 1,
+        AlertSeverity::Critical => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{create_store, DatabaseConfig, DatabaseType};
+    use std::path::PathBuf;
+
+    async fn test_store() -> Arc<dyn Store> {
+        let store = create_store(&DatabaseConfig {
+            db_type: DatabaseType::Sqlite,
+            sqlite_path: Some(PathBuf::from(":memory:")),
+            connection_string: None,
+            dedup_window_minutes: 0,
+        }).await.unwrap();
+        store.init().await.unwrap();
+        store
+    }
+
+    fn triaging_alert(source_id: Uuid, severity: AlertSeverity, since: DateTime<Utc>) -> Alert {
+        Alert {
+            id: Uuid::new_v4(),
+            external_id: None,
+            fingerprint: Uuid::new_v4().to_string(),
+            status: AlertStatus::Triaging,
+            severity,
+            alert_name: "HighMemoryUsage".to_string(),
+            summary: None,
+            description: None,
+            labels: HashMap::new(),
+            annotations: HashMap::new(),
+            source_id: Some(source_id),
+            workflow_id: None,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at: since,
+            ends_at: None,
+            received_at: since,
+            triage_started_at: Some(since),
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: since,
+            updated_at: since,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_escalates_after_threshold() {
+        let store = test_store().await;
+        let source_id = Uuid::new_v4();
+        let start = Utc::now();
+
+        let alert = triaging_alert(source_id, AlertSeverity::Warning, start);
+        let unrelated = triaging_alert(Uuid::new_v4(), AlertSeverity::Warning, start);
+        store.save_alert(alert.clone()).await.unwrap();
+        store.save_alert(unrelated.clone()).await.unwrap();
+
+        let controller = EscalationController::new(store.clone());
+        controller.set_policy(source_id, "prometheus-alerts", Some(EscalationPolicy {
+            escalate_to: PolicySeverity::Critical,
+            after_minutes: 30,
+        })).await;
+
+        assert_eq!(controller.check_alerts(start + chrono::Duration::minutes(10)).await.unwrap(), 0);
+        assert_eq!(controller.check_alerts(start + chrono::Duration::minutes(31)).await.unwrap(), 1);
+
+        let escalated = store.get_alert(alert.id).await.unwrap().unwrap();
+        assert_eq!(escalated.severity, AlertSeverity::Critical);
+        assert_eq!(escalated.status, AlertStatus::Escalated);
+        assert_eq!(escalated.ai_analysis.unwrap()["auto_escalation"]["to"], "critical");
+
+        let untouched = store.get_alert(unrelated.id).await.unwrap().unwrap();
+        assert_eq!(untouched.severity, AlertSeverity::Warning);
+
+        let events = store.list_source_events("prometheus-alerts", 10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source_type, SourceType::Escalation);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_downgrade_severity() {
+        let store = test_store().await;
+        let source_id = Uuid::new_v4();
+        let start = Utc::now();
+
+        let alert = triaging_alert(source_id, AlertSeverity::Critical, start);
+        store.save_alert(alert.clone()).await.unwrap();
+
+        let controller = EscalationController::new(store.clone());
+        controller.set_policy(source_id, "prometheus-alerts", Some(EscalationPolicy {
+            escalate_to: PolicySeverity::Warning,
+            after_minutes: 5,
+        })).await;
+
+        assert_eq!(controller.check_alerts(start + chrono::Duration::hours(1)).await.unwrap(), 0);
+        let alert = store.get_alert(alert.id).await.unwrap().unwrap();
+        assert_eq!(alert.status, AlertStatus::Triaging);
+    }
+}
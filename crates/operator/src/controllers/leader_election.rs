@@ -0,0 +1,347 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::coordination::v1::{Lease, LeaseSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta};
+use kube::{
+    api::{Api, PostParams},
+    Client,
+};
+use tokio::sync::watch;
+use tracing::{debug, error, info, warn};
+
+use crate::{Error, Result};
+
+/// Name of the Lease replicas compete for
+pub const LEASE_NAME: &str = "punching-fist-operator";
+
+/// How long a lease stays valid without being renewed
+const LEASE_DURATION: Duration = Duration::from_secs(15);
+
+/// How often the leader renews, and followers retry acquiring, the lease
+const RENEW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the leader keeps leading after its last renewal while renewing
+/// fails. Shorter than `LEASE_DURATION`, so it steps down before another
+/// replica can take the lease.
+const RENEW_DEADLINE: Duration = Duration::from_secs(10);
+
+/// Outcome of one attempt to take or renew the lease
+#[derive(Debug, PartialEq)]
+enum Renewal {
+    /// We hold the lease, renewed just now
+    Renewed,
+    /// The lease wasn't renewed; it's held by this replica or another, if known
+    NotRenewed(Option<String>),
+}
+
+/// Elects a single leader among operator replicas using a Kubernetes
+/// `Lease`. Only the leader runs the controllers, scheduled tasks and
+/// workflow engine; every replica keeps serving read-only HTTP.
+pub struct LeaderElection {
+    client: Client,
+    namespace: String,
+    identity: String,
+    is_leader: watch::Sender<bool>,
+    /// Holder of the lease as last seen, if any
+    leader: watch::Sender<Option<String>>,
+}
+
+impl LeaderElection {
+    pub fn new(client: Client, namespace: String, identity: String) -> Self {
+        Self {
+            client,
+            namespace,
+            identity,
+            is_leader: watch::Sender::new(false),
+            leader: watch::Sender::new(None),
+        }
+    }
+
+    /// Identity of this replica: the pod name when running in Kubernetes,
+    /// falling back to the hostname
+    pub fn default_identity() -> String {
+        std::env::var("POD_NAME")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .unwrap_or_else(|_| format!("punching-fist-{}", uuid::Uuid::new_v4()))
+    }
+
+    pub fn is_leader(&self) -> bool {
+        *self.is_leader.borrow()
+    }
+
+    /// Identity of the replica holding the lease, if known
+    pub fn leader(&self) -> Option<String> {
+        self.leader.borrow().clone()
+    }
+
+    /// Wait until this replica holds the lease
+    pub async fn wait_for_leadership(&self) {
+        let mut rx = self.is_leader.subscribe();
+        // The sender lives as long as self, so this can't fail
+        let _ = rx.wait_for(|leader| *leader).await;
+    }
+
+    /// Wait until this replica loses the lease it held
+    pub async fn wait_for_lost_leadership(&self) {
+        let mut rx = self.is_leader.subscribe();
+        let _ = rx.wait_for(|leader| *leader).await;
+        let _ = rx.wait_for(|leader| !*leader).await;
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        info!("Starting leader election for Lease {}/{} as {}", self.namespace, LEASE_NAME, self.identity);
+
+        let mut ticker = tokio::time::interval(RENEW_INTERVAL);
+        let mut last_renewed = None;
+        loop {
+            ticker.tick().await;
+            self.elect(&mut last_renewed, Instant::now()).await;
+        }
+    }
+
+    /// Take or renew the lease at `now`, then update who leads. A leader
+    /// that fails to renew keeps leading until `RENEW_DEADLINE` after its
+    /// last renewal, unless another replica is seen holding the lease.
+    async fn elect(&self, last_renewed: &mut Option<Instant>, now: Instant) {
+        let renewal = match self.try_acquire_or_renew(Utc::now()).await {
+            Ok(renewal) => Some(renewal),
+            Err(e) => {
+                error!("Failed to acquire or renew Lease {}: {}", LEASE_NAME, e);
+                None
+            }
+        };
+        let renewed = renewal == Some(Renewal::Renewed);
+        let still_valid = last_renewed.is_some_and(|renewed| now < renewed + RENEW_DEADLINE);
+        let leader = match renewal {
+            Some(Renewal::Renewed) => {
+                *last_renewed = Some(now);
+                self.leader.send_replace(Some(self.identity.clone()));
+                true
+            }
+            Some(Renewal::NotRenewed(holder)) => {
+                let other_holder = holder.as_deref().is_some_and(|holder| holder != self.identity);
+                self.leader.send_replace(holder);
+                !other_holder && still_valid
+            }
+            None => still_valid,
+        };
+        if !leader {
+            *last_renewed = None;
+        }
+
+        let was_leader = self.is_leader.send_replace(leader);
+        match (was_leader, leader) {
+            (false, true) => info!("Became leader as {}", self.identity),
+            (true, false) => warn!("Lost leadership as {}", self.identity),
+            (true, true) if !renewed => {
+                warn!("Failed to renew Lease {}, leading until the last renewal expires", LEASE_NAME);
+            }
+            _ => {}
+        }
+    }
+
+    /// Take the lease if it's free or expired, or renew it if we hold it
+    async fn try_acquire_or_renew(&self, now: DateTime<Utc>) -> Result<Renewal> {
+        let api: Api<Lease> = Api::namespaced(self.client.clone(), &self.namespace);
+        let existing = api.get_opt(LEASE_NAME).await
+            .map_err(|e| Error::Kubernetes(format!("Failed to get Lease {}: {}", LEASE_NAME, e)))?;
+
+        let result = match existing {
+            None => {
+                let lease = Lease {
+                    metadata: ObjectMeta {
+                        name: Some(LEASE_NAME.to_string()),
+                        namespace: Some(self.namespace.clone()),
+                        ..Default::default()
+                    },
+                    spec: Some(claim(None, &self.identity, now)),
+                };
+                api.create(&PostParams::default(), &lease).await.map(|_| ())
+            }
+            Some(mut lease) => {
+                let spec = lease.spec.take().unwrap_or_default();
+                if !can_acquire(&spec, &self.identity, now) {
+                    debug!("Lease {} is held by {:?}", LEASE_NAME, spec.holder_identity);
+                    return Ok(Renewal::NotRenewed(spec.holder_identity));
+                }
+                lease.spec = Some(claim(Some(spec), &self.identity, now));
+                // The resourceVersion makes the replace fail if another
+                // replica updated the lease since we read it
+                api.replace(LEASE_NAME, &PostParams::default(), &lease).await.map(|_| ())
+            }
+        };
+
+        match result {
+            Ok(()) => Ok(Renewal::Renewed),
+            Err(kube::Error::Api(e)) if e.code == 409 => {
+                // Another replica updated the lease since we read it; see
+                // whether it took the lease or we still hold it
+                debug!("Lost the race for Lease {}", LEASE_NAME);
+                let holder = api.get_opt(LEASE_NAME).await
+                    .map_err(|e| Error::Kubernetes(format!("Failed to get Lease {}: {}", LEASE_NAME, e)))?
+                    .and_then(|lease| lease.spec)
+                    .and_then(|spec| spec.holder_identity);
+                Ok(Renewal::NotRenewed(holder))
+            }
+            Err(e) => Err(Error::Kubernetes(format!("Failed to update Lease {}: {}", LEASE_NAME, e))),
+        }
+    }
+}
+
+/// Whether `identity` may take or renew the lease at `now`
+fn can_acquire(spec: &LeaseSpec, identity: &str, now: DateTime<Utc>) -> bool {
+    match spec.holder_identity.as_deref() {
+        None | Some("") => true,
+        Some(holder) if holder == identity => true,
+        Some(_) => {
+            let duration = spec.lease_duration_seconds.map(i64::from).unwrap_or(LEASE_DURATION.as_secs() as i64);
+            match &spec.renew_time {
+                Some(MicroTime(renewed)) => *renewed + chrono::Duration::seconds(duration) < now,
+                None => true,
+            }
+        }
+    }
+}
+
+/// The lease spec after `identity` takes or renews it at `now`
+fn claim(previous: Option<LeaseSpec>, identity: &str, now: DateTime<Utc>) -> LeaseSpec {
+    let previous = previous.unwrap_or_default();
+    let renewing = previous.holder_identity.as_deref() == Some(identity);
+
+    LeaseSpec {
+        holder_identity: Some(identity.to_string()),
+        lease_duration_seconds: Some(LEASE_DURATION.as_secs() as i32),
+        acquire_time: if renewing { previous.acquire_time } else { Some(MicroTime(now)) },
+        renew_time: Some(MicroTime(now)),
+        lease_transitions: if renewing {
+            previous.lease_transitions
+        } else {
+            Some(previous.lease_transitions.unwrap_or(0) + 1)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicBool, AtomicU16, Ordering},
+        Arc, Mutex,
+    };
+
+    use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+    use serde_json::{json, Value};
+
+    /// Lease API holding one Lease, answering requests with `status` instead
+    /// while it's set, and updates with a conflict while `conflict` is
+    #[derive(Clone, Default)]
+    struct LeaseApi {
+        lease: Arc<Mutex<Option<Value>>>,
+        status: Arc<AtomicU16>,
+        conflict: Arc<AtomicBool>,
+    }
+
+    impl LeaseApi {
+        async fn client(&self) -> Client {
+            let failure = |code: u16| (StatusCode::from_u16(code).unwrap(), Json(json!({
+                "kind": "Status", "apiVersion": "v1", "status": "Failure",
+                "message": "injected failure", "reason": "Injected", "code": code
+            })));
+            let app = Router::new()
+                .route("/apis/coordination.k8s.io/v1/namespaces/{namespace}/leases", axum::routing::post(
+                    move |State(state): State<LeaseApi>, Json(lease): Json<Value>| async move {
+                        *state.lease.lock().unwrap() = Some(lease.clone());
+                        (StatusCode::CREATED, Json(lease))
+                    },
+                ))
+                .route("/apis/coordination.k8s.io/v1/namespaces/{namespace}/leases/{name}", get(
+                    move |State(state): State<LeaseApi>| async move {
+                        match state.status.load(Ordering::SeqCst) {
+                            0 => {}
+                            code => return failure(code),
+                        }
+                        match state.lease.lock().unwrap().clone() {
+                            Some(lease) => (StatusCode::OK, Json(lease)),
+                            None => (StatusCode::NOT_FOUND, Json(json!({
+                                "kind": "Status", "apiVersion": "v1", "status": "Failure",
+                                "message": "not found", "reason": "NotFound", "code": 404
+                            }))),
+                        }
+                    },
+                ).put(move |State(state): State<LeaseApi>, Json(lease): Json<Value>| async move {
+                    match (state.status.load(Ordering::SeqCst), state.conflict.load(Ordering::SeqCst)) {
+                        (0, false) => {}
+                        (0, true) => return failure(409),
+                        (code, _) => return failure(code),
+                    }
+                    *state.lease.lock().unwrap() = Some(lease.clone());
+                    (StatusCode::OK, Json(lease))
+                }))
+                .with_state(self.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leader_survives_failed_renewal() {
+        let api = LeaseApi::default();
+        let election = LeaderElection::new(api.client().await, "default".to_string(), "operator-0".to_string());
+        let start = Instant::now();
+        let mut last_renewed = None;
+
+        election.elect(&mut last_renewed, start).await;
+        assert!(election.is_leader());
+
+        // A failed renewal keeps leadership
+        api.status.store(500, Ordering::SeqCst);
+        election.elect(&mut last_renewed, start + RENEW_INTERVAL).await;
+        assert!(election.is_leader());
+        api.status.store(0, Ordering::SeqCst);
+        election.elect(&mut last_renewed, start + RENEW_INTERVAL * 2).await;
+        assert!(election.is_leader());
+
+        // So does a renewal losing a race while the lease still names us
+        api.conflict.store(true, Ordering::SeqCst);
+        election.elect(&mut last_renewed, start + RENEW_INTERVAL * 3).await;
+        assert!(election.is_leader());
+        api.conflict.store(false, Ordering::SeqCst);
+        election.elect(&mut last_renewed, start + RENEW_INTERVAL * 4).await;
+        assert!(election.is_leader());
+
+        // Leadership ends once renewing has failed for the renew deadline
+        api.status.store(500, Ordering::SeqCst);
+        let renewed = start + RENEW_INTERVAL * 4;
+        election.elect(&mut last_renewed, renewed + RENEW_DEADLINE - Duration::from_secs(1)).await;
+        assert!(election.is_leader());
+        election.elect(&mut last_renewed, renewed + RENEW_DEADLINE).await;
+        assert!(!election.is_leader());
+        assert_eq!(election.leader().as_deref(), Some("operator-0"));
+    }
+
+    #[test]
+    fn test_lease_acquisition() {
+        let now = Utc::now();
+        let held = claim(None, "operator-0", now);
+        assert_eq!(held.holder_identity.as_deref(), Some("operator-0"));
+        assert_eq!(held.lease_duration_seconds, Some(15));
+        assert_eq!(held.lease_transitions, Some(1));
+
+        // The holder renews; others wait for the lease to expire
+        assert!(can_acquire(&held, "operator-0", now + chrono::Duration::seconds(5)));
+        assert!(!can_acquire(&held, "operator-1", now + chrono::Duration::seconds(10)));
+        assert!(can_acquire(&held, "operator-1", now + chrono::Duration::seconds(16)));
+
+        let renewed = claim(Some(held.clone()), "operator-0", now + chrono::Duration::seconds(5));
+        assert_eq!(renewed.acquire_time, held.acquire_time);
+        assert_eq!(renewed.lease_transitions, Some(1));
+
+        let taken = claim(Some(renewed), "operator-1", now + chrono::Duration::seconds(30));
+        assert_eq!(taken.holder_identity.as_deref(), Some("operator-1"));
+        assert_eq!(taken.acquire_time, Some(MicroTime(now + chrono::Duration::seconds(30))));
+        assert_eq!(taken.lease_transitions, Some(2));
+    }
+}
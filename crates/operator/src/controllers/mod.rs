@@ -1,7 +1,15 @@
 pub mod source;
 pub mod workflow;
 pub mod sink;
+pub mod escalation;
+pub mod config_map;
+pub mod leader_election;
+pub mod custom_resource;
 
 pub use source::SourceController;
 pub use workflow::WorkflowController;
-pub use sink::SinkController; 
\ No newline at end of file
+pub use sink::SinkController;
+pub use escalation::EscalationController;
+pub use config_map::ConfigMapWatcher;
+pub use leader_election::LeaderElection;
+pub use custom_resource::CustomResourceMonitor; 
\ No newline at end of file
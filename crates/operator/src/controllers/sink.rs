@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use futures::StreamExt;
+use k8s_openapi::api::core::v1::Secret;
 use kube::{
     api::{Api, Patch, PatchParams, ResourceExt},
     runtime::{controller::{Action, Controller}, watcher::Config},
@@ -9,22 +10,32 @@ use kube::{
 };
 use serde_json::{json, Value};
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
-use crate::crd::sink::{Sink, SinkSpec, SinkStatus, SinkType as CRDSinkType}; // Using authoritative definitions
-use crate::crd::source::Condition;
-use crate::sinks::stdout::StdoutSink;
-use crate::sinks::Sink as SinkTrait; // Import the Sink trait
+use crate::crd::sink::{RetryPolicy, Sink, SinkSpec, SinkStatus, SinkType as CRDSinkType}; // Using authoritative definitions
+use crate::crd::common::set_condition;
+use crate::crd::source::{Condition, ConditionStatus};
+use crate::sinks::{AllSinks, Sink as SinkImpl, SinkCredentials};
+use crate::store::{SinkOutput, SinkStatus as StoreSinkStatus, SinkType as StoreSinkType, Store};
 use crate::{Result, Error};
 
 #[derive(Clone)] // Added Clone
 pub struct SinkController {
     client: Client,
+    /// Where deliveries made for a workflow are recorded
+    store: Option<Arc<dyn Store>>,
     // Potentially a cache for Sink CRs if lookups are frequent
 }
 
 impl SinkController {
     pub fn new(client: Client) -> Self {
-        SinkController { client }
+        SinkController { client, store: None }
+    }
+
+    /// Record each delivery made with `deliver` as a `SinkOutput` in `store`
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = Some(store);
+        self
     }
 
     pub async fn run(self: Arc<Self>) -> Result<()> {
@@ -73,7 +84,7 @@ impl SinkController {
                 debug!("Validated stdout sink configuration for '{}'", name);
             }
             CRDSinkType::Slack => {
-                if sink.spec.config.channel.is_none() || sink.spec.config.bot_token.is_none() {
+                if sink.spec.config.endpoint.is_none() {
                     warn!("Slack sink '{}' missing required configuration", name);
                 }
             }
@@ -82,13 +93,43 @@ impl SinkController {
                     warn!("JIRA sink '{}' missing required configuration", name);
                 }
             }
+            CRDSinkType::Webhook => {
+                if sink.spec.config.url_template.is_none() {
+                    warn!("Webhook sink '{}' missing required configuration", name);
+                }
+            }
+            CRDSinkType::Inhibition => {
+                if sink.spec.config.endpoint.is_none() {
+                    warn!("Inhibition sink '{}' missing required configuration", name);
+                }
+            }
+            CRDSinkType::PagerDuty => {
+                if sink.spec.config.routing_key.is_none() {
+                    warn!("PagerDuty sink '{}' missing required configuration", name);
+                }
+            }
+            CRDSinkType::AlertManager => {
+                if sink.spec.config.endpoint.is_none() {
+                    warn!("AlertManager sink '{}' missing required configuration", name);
+                }
+            }
             _ => {
                 debug!("Sink type {:?} configuration validated for '{}'", sink.spec.sink_type, name);
             }
         }
         
+        let mut conditions = current_status.map(|s| s.conditions.clone()).unwrap_or_default();
+        let conditions_changed = set_condition(
+            &mut conditions,
+            Condition::new("Ready", ConditionStatus::True)
+                .reason("Configured")
+                .message("Sink is configured and ready to receive events")
+                .observed_generation(sink.metadata.generation),
+            chrono::Utc::now(),
+        );
+        
         // Only update status if needed
-        if needs_update {
+        if needs_update || conditions_changed {
             let api = Api::<Sink>::namespaced(ctx.client.clone(), &namespace);
             
             // Preserve existing counters
@@ -100,13 +141,7 @@ impl SinkController {
                 last_sent_time,
                 messages_sent,
                 last_error: None,
-                conditions: vec![Condition {
-                    condition_type: "Ready".to_string(),
-                    status: "True".to_string(),
-                    reason: "Configured".to_string(),
-                    message: format!("Sink is configured and ready to receive events"),
-                    last_transition_time: chrono::Utc::now().to_rfc3339(),
-                }],
+                conditions,
             };
 
             let status_patch = json!({
@@ -140,7 +175,29 @@ impl SinkController {
         sink_name: &str,
         sink_namespace: &str, // Sinks are namespaced
         workflow_output_context: &Value, // The full context to be sent to the sink
-    ) -> Result<()> {
+    ) -> Result<StoreSinkType> {
+        self.dispatch(sink_name, sink_namespace, workflow_output_context, None).await
+    }
+
+    /// Send `context` to the sink like `process_sink_event`, recording the
+    /// delivery as a `SinkOutput` of `workflow_id` that is `Sent` or `Failed`
+    pub async fn deliver(
+        &self,
+        workflow_id: Uuid,
+        sink_name: &str,
+        sink_namespace: &str,
+        context: &Value,
+    ) -> Result<StoreSinkType> {
+        self.dispatch(sink_name, sink_namespace, context, Some(workflow_id)).await
+    }
+
+    async fn dispatch(
+        &self,
+        sink_name: &str,
+        sink_namespace: &str,
+        workflow_output_context: &Value,
+        workflow_id: Option<Uuid>,
+    ) -> Result<StoreSinkType> {
         info!(
             "Processing sink event for sink '{}' in namespace '{}'",
             sink_name,
@@ -163,43 +220,113 @@ impl SinkController {
         };
 
         let sink_spec = sink_cr.spec; // sink_cr has SinkSpec as spec
+        let sink_type = StoreSinkType::from(sink_spec.sink_type.clone());
+
+        let credentials = self.credentials(sink_namespace, &sink_spec).await?;
 
         // Dispatch to the correct sink implementation based on sink_spec.sink_type
-        match sink_spec.sink_type {
-            CRDSinkType::Stdout => {
-                // The name for the SinkOutput instance can be the CRD name
-                let stdout_sink = StdoutSink::new(sink_name.to_string(), &sink_spec)
-                    .map_err(|e| Error::Config(format!("Failed to create stdout sink: {}", e)))?;
-                info!("Dispatching to StdoutSink: {}", stdout_sink.name());
-                stdout_sink.send(workflow_output_context.clone()).await
-                    .map_err(|e| Error::Config(format!("Failed to send to stdout sink: {}", e)))?;
-                
-                // Update sink status with message count
-                self.update_sink_message_count(&sinks_api, sink_name).await?;
-                
-                Ok(())
-            }
-            CRDSinkType::Slack => {
-                // Placeholder for SlackSink implementation
-                info!("Slack sink type not yet implemented. Sink: {}", sink_name);
-                // let slack_sink = SlackSink::new(Some(sink_name.to_string()), &sink_spec)?;
-                // slack_sink.send(workflow_output_context).await?
-                Ok(())
-            }
-            CRDSinkType::AlertManager => {
-                info!("AlertManager sink type not yet implemented. Sink: {}", sink_name);
-                Ok(())
+        let sink = AllSinks::new(sink_name, &sink_spec, &credentials)
+            .map_err(|e| Error::Config(format!("Failed to create {} sink: {}", sink_type, e)))?;
+        let Some(sink) = sink else {
+            error!(
+                "Sink type '{:?}' for sink '{}' is not supported yet.",
+                sink_spec.sink_type,
+                sink_name
+            );
+            return Ok(sink_type);
+        };
+
+        info!("Dispatching to {} sink: {}", sink_type, sink.name());
+        self.deliver_with_retry(sink.as_sink(), sink_type, workflow_output_context, &sink_spec.retry, workflow_id).await?;
+
+        // Update sink status with message count
+        self.update_sink_message_count(&sinks_api, sink_name).await?;
+
+        Ok(sink_type)
+    }
+    
+    /// Deliver `context` to `sink`, retrying failures as `policy` says.
+    /// Deliveries made for a workflow are recorded as a `SinkOutput`, which
+    /// stays `Pending` with the latest error until the context is sent or
+    /// the attempts run out.
+    async fn deliver_with_retry(
+        &self,
+        sink: &dyn SinkImpl,
+        sink_type: StoreSinkType,
+        context: &Value,
+        policy: &RetryPolicy,
+        workflow_id: Option<Uuid>,
+    ) -> Result<()> {
+        let store = self.store.as_ref().filter(|_| workflow_id.is_some());
+        let now = chrono::Utc::now();
+        let mut output = SinkOutput {
+            id: Uuid::new_v4(),
+            workflow_id: workflow_id.unwrap_or_default(),
+            sink_name: sink.name().to_string(),
+            sink_type,
+            payload: Some(context.clone()),
+            status: StoreSinkStatus::Pending,
+            error: None,
+            sent_at: None,
+            created_at: now,
+        };
+        if let Some(store) = store {
+            store.save_sink_output(output.clone()).await?;
+        }
+
+        let max_attempts = policy.max_attempts.max(1);
+        let mut attempt = 1;
+        let sent = loop {
+            match sink.deliver(context.clone()).await {
+                // A bad configuration won't fix itself
+                Err(e) if attempt < max_attempts && !matches!(e, Error::Validation(_)) => {
+                    let delay = policy.delay(attempt);
+                    warn!(
+                        "Attempt {}/{} to send to {} sink '{}' failed, retrying in {:?}: {}",
+                        attempt, max_attempts, sink_type, sink.name(), delay, e
+                    );
+                    if let Some(store) = store {
+                        output.error = Some(e.to_string());
+                        store.save_sink_output(output.clone()).await?;
+                    }
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => break result,
             }
-            // Add other sink types here
-            _ => {
-                error!(
-                    "Sink type '{:?}' for sink '{}' is not supported yet.",
-                    sink_spec.sink_type,
-                    sink_name
-                );
-                Ok(())
+        };
+        let sent = sent.map_err(|e| Error::Config(format!("Failed to send to {} sink: {}", sink_type, e)));
+
+        if let Some(store) = store {
+            // What the destination made of the delivery, e.g. the incident it
+            // opened, is kept under `delivery`
+            if let (Ok(Some(delivery)), Some(Value::Object(map))) = (&sent, output.payload.as_mut()) {
+                map.insert("delivery".to_string(), delivery.clone());
             }
+            output.status = if sent.is_ok() { StoreSinkStatus::Sent } else { StoreSinkStatus::Failed };
+            output.error = sent.as_ref().err().map(ToString::to_string);
+            output.sent_at = sent.is_ok().then(chrono::Utc::now);
+            store.save_sink_output(output).await?;
         }
+        sent.map(|_| ())
+    }
+    
+    /// Data of the Secret named by the sink's `secretRef` or
+    /// `credentialsSecret`, if any
+    async fn credentials(&self, namespace: &str, spec: &SinkSpec) -> Result<SinkCredentials> {
+        let secret_name = spec.config.secret_ref.as_ref()
+            .map(|secret_ref| &secret_ref.name)
+            .or(spec.config.credentials_secret.as_ref());
+        let Some(secret_name) = secret_name else {
+            return Ok(SinkCredentials::new());
+        };
+        let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+        let secret = secrets.get(secret_name).await
+            .map_err(|e| Error::Kubernetes(format!("Failed to get Secret {} in namespace {}: {}", secret_name, namespace, e)))?;
+        Ok(secret.data.unwrap_or_default()
+            .into_iter()
+            .filter_map(|(key, value)| String::from_utf8(value.0).ok().map(|value| (key, value)))
+            .collect())
     }
     
     async fn update_sink_message_count(&self, api: &Api<Sink>, sink_name: &str) -> Result<()> {
@@ -225,73 +352,187 @@ impl SinkController {
     }
 }
 
-// Basic test structure (you'll need a running K8s cluster or mock for Api calls)
 #[cfg(test)]
 mod tests {
     use super::*;
-    use kube::Client;
-    use serde_json::json;
-    use crate::crd::sink::{SinkConfig, SinkStatus};
-
-    // Mocking K8s API calls is complex and out of scope for this basic test.
-    // These tests would typically require environment setup for a test cluster
-    // or a sophisticated mocking framework like `kube-runtime/controller-runtime` provides.
-
-    // A conceptual test for the dispatch logic if we could mock get_sink_crd
-    /*
-    async fn mock_get_sink_crd(name: &str, namespace: &str, client: Client) -> Result<Sink, kube::Error> {
-        // In a real test, this would interact with a mock client
-        // For now, let's construct a Sink directly if it's for stdout
-        if name == "my-stdout-sink" && namespace == "default" {
-            Ok(Sink {
-                metadata: Arc::new(kube::api::ObjectMeta {
-                    name: Some(name.to_string()),
-                    namespace: Some(namespace.to_string()),
-                    ..
-                    Default::default()
-                }),
-                spec: SinkSpec {
-                    sink_type: CRDSinkType::Stdout,
-                    config: SinkConfig { // Assuming this is the detailed one from crd::sink
-                        format: Some("json".to_string()),
-                        pretty: Some(true),
-                        // ... other fields initialized to None or default ...
-                        channel: None, bot_token: None, message_type: None, mention_users: vec![],
-                        endpoint: None, action: None, pushgateway: None, job: None, metrics: std::collections::HashMap::new(),
-                        project: None, issue_type: None, credentials_secret: None, routing_key: None,
-                        workflow_name: None, trigger_condition: None, template: None, context: std::collections::HashMap::new(),
-                    },
-                    condition: None,
-                },
-                status: None, // Or some default status
-            })
-        } else {
-            Err(kube::Error::Api(kube::error::ErrorResponse { 
-                status: "Failure".to_string(), 
-                message: "Not Found".to_string(), 
-                reason: "NotFound".to_string(), 
-                code: 404 
+    use axum::{extract::Path, http::StatusCode, routing::{get, post}, Json, Router};
+    use crate::store::{SqliteStore, Workflow, WorkflowStatus};
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    /// API server holding one Slack sink per channel, `ok` and `missing`,
+    /// whose incoming webhook rejects the `missing` channel, and a
+    /// `pagerduty` sink
+    async fn mock_sinks() -> Client {
+        let slack = serve(Router::new()
+            .route("/hook", post(|Json(message): Json<Value>| async move {
+                match message["channel"].as_str() {
+                    Some("#missing") => (StatusCode::NOT_FOUND, "channel_not_found"),
+                    _ => (StatusCode::OK, "ok"),
+                }
             }))
+            .route("/v2/enqueue", post(|Json(event): Json<Value>| async move {
+                (StatusCode::ACCEPTED, Json(json!({ "status": "success", "dedup_key": event["dedup_key"] })))
+            }))).await;
+        let sink = move |name: String| {
+            let spec = match name.as_str() {
+                "pagerduty" => json!({ "type": "pagerduty", "config": { "endpoint": format!("{}/v2/enqueue", slack), "routingKey": "R0UT1NGK3Y" } }),
+                _ => json!({ "type": "slack", "config": { "endpoint": format!("{}/hook", slack), "channel": format!("#{}", name) } }),
+            };
+            let mut spec = spec;
+            spec["retry"] = json!({ "maxAttempts": 2, "baseDelayMs": 10 });
+            json!({
+                "apiVersion": "punchingfist.io/v1alpha1",
+                "kind": "Sink",
+                "metadata": { "name": name, "namespace": "default" },
+                "spec": spec,
+                "status": { "ready": true, "messagesSent": 0 }
+            })
+        };
+        let status_sink = sink.clone();
+        let api = serve(Router::new()
+            .route("/apis/punchingfist.io/v1alpha1/namespaces/default/sinks/{name}", get(
+                move |Path(name): Path<String>| async move { Json(sink(name)) },
+            ))
+            .route("/apis/punchingfist.io/v1alpha1/namespaces/default/sinks/{name}/status", axum::routing::patch(
+                move |Path(name): Path<String>| async move { Json(status_sink(name)) },
+            ))).await;
+        Client::try_from(kube::Config::new(api.parse().unwrap())).unwrap()
+    }
+
+    async fn store_with_workflow(workflow_id: Uuid) -> Arc<dyn Store> {
+        let store: Arc<dyn Store> = Arc::new(SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let now = chrono::Utc::now();
+        store.save_workflow(Workflow {
+            id: workflow_id,
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 1,
+            total_steps: 1,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: Some(now),
+            created_at: now,
+        }).await.unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn test_deliver_records_sink_outputs() {
+        let workflow_id = Uuid::new_v4();
+        let store = store_with_workflow(workflow_id).await;
+        let controller = SinkController::new(mock_sinks().await).with_store(store.clone());
+        let context = json!({ "summary": "Disk full", "findings": [] });
+
+        let sink_type = controller.deliver(workflow_id, "ok", "default", &context).await.unwrap();
+        assert_eq!(sink_type, StoreSinkType::Slack);
+        let err = controller.deliver(workflow_id, "missing", "default", &context).await.unwrap_err();
+        assert!(err.to_string().contains("channel_not_found"), "{}", err);
+
+        let outputs = store.list_sink_outputs(workflow_id).await.unwrap();
+        assert_eq!(outputs.len(), 2);
+        let sent = outputs.iter().find(|o| o.sink_name == "ok").unwrap();
+        assert_eq!(sent.status, StoreSinkStatus::Sent);
+        assert!(sent.sent_at.is_some());
+        assert_eq!(sent.payload.as_ref(), Some(&context));
+        let failed = outputs.iter().find(|o| o.sink_name == "missing").unwrap();
+        assert_eq!(failed.status, StoreSinkStatus::Failed);
+        assert!(failed.error.as_deref().unwrap().contains("channel_not_found"));
+        assert!(failed.sent_at.is_none());
+
+        // Deliveries that aren't made for a workflow aren't recorded
+        controller.process_sink_event("ok", "default", &context).await.unwrap();
+        assert_eq!(store.list_sink_outputs(workflow_id).await.unwrap().len(), 2);
+
+        // What the destination returns is recorded with the payload
+        let context = json!({ "type": "completion", "workflow": { "name": "triage", "namespace": "default" } });
+        controller.deliver(workflow_id, "pagerduty", "default", &context).await.unwrap();
+        let outputs = store.list_sink_outputs(workflow_id).await.unwrap();
+        let paged = outputs.iter().find(|o| o.sink_name == "pagerduty").unwrap();
+        assert_eq!(paged.sink_type, StoreSinkType::PagerDuty);
+        assert_eq!(paged.payload.as_ref().unwrap()["delivery"], json!({ "event_action": "resolve", "dedup_key": "default/triage" }));
+    }
+
+    /// Sink failing with a 503 until its `succeed_on`th attempt, noting the
+    /// status its `SinkOutput` had at each retry
+    struct FlakySink {
+        store: Arc<dyn Store>,
+        workflow_id: Uuid,
+        succeed_on: u32,
+        attempts: std::sync::atomic::AtomicU32,
+        statuses_seen: std::sync::Mutex<Vec<StoreSinkStatus>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SinkImpl for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn send(&self, _context: Value) -> Result<()> {
+            let attempt = self.attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            if attempt > 1 {
+                let outputs = self.store.list_sink_outputs(self.workflow_id).await.unwrap();
+                self.statuses_seen.lock().unwrap().push(outputs[0].status);
+            }
+            if attempt < self.succeed_on {
+                return Err(Error::Execution(format!("attempt {} got status 503", attempt)));
+            }
+            Ok(())
         }
     }
 
     #[tokio::test]
-    async fn test_process_stdout_sink_event() {
-        // This test requires a K8s client that can be mocked or a test environment.
-        // For now, this is a placeholder demonstrating the intent.
-        // let client = Client::try_default().await.expect("Failed to create K8s client");
-        // let controller = SinkController::new(client.clone());
-        
-        // Here you would mock the Api<Sink>::get call to return a specific Sink CRD
-        // For example, using a library like `mockall` or a custom mock client.
+    async fn test_deliver_with_retry() {
+        let workflow_id = Uuid::new_v4();
+        let store = store_with_workflow(workflow_id).await;
+        let controller = SinkController::new(mock_sinks().await).with_store(store.clone());
+        let policy = RetryPolicy { max_attempts: 3, base_delay_ms: 10 };
+        let flaky = |succeed_on| FlakySink {
+            store: store.clone(),
+            workflow_id,
+            succeed_on,
+            attempts: Default::default(),
+            statuses_seen: Default::default(),
+        };
+        let context = json!({ "summary": "Disk full", "findings": [] });
 
-        let context = json!({ "message": "hello from workflow" });
-        
-        // Simulate a call - this will fail without a real/mocked K8s API
-        // let result = controller.process_sink_event("my-stdout-sink", "default", &context).await;
-        // assert!(result.is_ok()); 
-        // Further assertions would involve capturing stdout or checking mock interactions.
-        println!("Skipping test_process_stdout_sink_event due to K8s client dependency");
+        let sink = flaky(3);
+        controller.deliver_with_retry(&sink, StoreSinkType::Webhook, &context, &policy, Some(workflow_id)).await.unwrap();
+        assert_eq!(sink.attempts.into_inner(), 3);
+        assert_eq!(*sink.statuses_seen.lock().unwrap(), vec![StoreSinkStatus::Pending, StoreSinkStatus::Pending]);
+        let outputs = store.list_sink_outputs(workflow_id).await.unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].status, StoreSinkStatus::Sent);
+        assert!(outputs[0].error.is_none());
+
+        // Once the attempts run out the last error is kept
+        let sink = flaky(4);
+        let err = controller.deliver_with_retry(&sink, StoreSinkType::Webhook, &context, &policy, Some(workflow_id)).await.unwrap_err();
+        assert!(err.to_string().contains("attempt 3 got status 503"), "{}", err);
+        let outputs = store.list_sink_outputs(workflow_id).await.unwrap();
+        assert_eq!(outputs.len(), 2);
+        let failed = outputs.iter().find(|o| o.status == StoreSinkStatus::Failed).unwrap();
+        assert!(failed.error.as_deref().unwrap().contains("attempt 3 got status 503"));
     }
-    */
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy { max_attempts: 5, base_delay_ms: 1000 };
+        for (retry, full) in [(1, 1000), (2, 2000), (3, 4000)] {
+            let delay = policy.delay(retry).as_millis() as u64;
+            assert!(delay >= full / 2 && delay <= full, "retry {}: {}ms", retry, delay);
+        }
+    }
+}
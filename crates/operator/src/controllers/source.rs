@@ -1,25 +1,32 @@
 use std::sync::Arc;
 use std::time::Duration;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use futures::StreamExt;
 use kube::{
-    api::{Api, Patch, PatchParams, ResourceExt},
+    api::{Api, ListParams, Patch, PatchParams, ResourceExt},
     runtime::{controller::{Action, Controller}, watcher::Config},
     Client,
 };
 use serde_json::json;
 use tracing::{debug, error, info, warn};
 
+use uuid::Uuid;
+
 use crate::{
-    crd::source::{Source, SourceStatus, Condition},
-    sources::WebhookHandler,
+    controllers::EscalationController,
+    crd::common::set_condition,
+    crd::source::{Source, SourceStatus, Condition, ConditionStatus},
+    crd::Workflow,
+    sources::{schedule::ScheduleConfig, webhook::WebhookConfig, ScheduleHandler, WebhookHandler},
     Result, Error,
 };
 
 pub struct SourceController {
     client: Client,
     webhook_handler: Arc<WebhookHandler>,
+    escalation_controller: Option<Arc<EscalationController>>,
+    schedule_handler: Option<Arc<ScheduleHandler>>,
 }
 
 impl SourceController {
@@ -27,9 +34,21 @@ impl SourceController {
         Self {
             client,
             webhook_handler,
+            escalation_controller: None,
+            schedule_handler: None,
         }
     }
 
+    pub fn with_escalation_controller(mut self, controller: Arc<EscalationController>) -> Self {
+        self.escalation_controller = Some(controller);
+        self
+    }
+
+    pub fn with_schedule_handler(mut self, handler: Arc<ScheduleHandler>) -> Self {
+        self.schedule_handler = Some(handler);
+        self
+    }
+
     pub async fn run(self: Arc<Self>) -> Result<()> {
         info!("Starting Source controller");
 
@@ -52,6 +71,7 @@ impl SourceController {
     async fn reconcile(source: Arc<Source>, ctx: Arc<Self>) -> Result<Action> {
         let name = source.name_any();
         let namespace = source.namespace().unwrap_or_default();
+        let source_id = source.metadata.uid.as_deref().and_then(|uid| Uuid::parse_str(uid).ok());
 
         // Get current status
         let current_status = source.status.as_ref();
@@ -66,6 +86,25 @@ impl SourceController {
             debug!("Reconciling existing Source: {}/{}", namespace, name);
         }
 
+        // Routes to missing workflows are dropped so those alerts fall back
+        // to the default workflow
+        let mut severity_routing = source.spec.severity_routing.clone().unwrap_or_default();
+        let mut routing_errors = Vec::new();
+        if !severity_routing.is_empty() {
+            let workflows: Api<Workflow> = Api::namespaced(ctx.client.clone(), &namespace);
+            let existing: HashSet<String> = workflows.list(&ListParams::default()).await
+                .map_err(|e| Error::Kubernetes(format!("Failed to list workflows in namespace {}: {}", namespace, e)))?
+                .items.iter()
+                .map(|w| w.name_any())
+                .collect();
+            
+            routing_errors = severity_routing_errors(&severity_routing, &existing);
+            severity_routing.retain(|_, workflow| existing.contains(workflow));
+            for error in &routing_errors {
+                warn!("Source {}/{} has invalid severity routing: {}", namespace, name, error);
+            }
+        }
+
         // Process based on source type
         match &source.spec.source_type {
             crate::crd::source::SourceType::Webhook => {
@@ -76,14 +115,18 @@ impl SourceController {
                         name, webhook_config.path, source.spec.trigger_workflow
                     );
                     
-                    ctx.webhook_handler.register_webhook(
-                        &name,
-                        &webhook_config.path,
-                        webhook_config.filters.clone(),
-                        source.spec.trigger_workflow.clone(),
-                        Some(source.spec.trigger_workflow.clone()),
-                        namespace.clone(),
-                    ).await?;
+                    ctx.webhook_handler.register_webhook(WebhookConfig {
+                        source_name: name.clone(),
+                        source_id,
+                        path: webhook_config.path.clone(),
+                        filters: webhook_config.filters.clone(),
+                        workflow_name: source.spec.trigger_workflow.clone(),
+                        trigger_workflow: Some(source.spec.trigger_workflow.clone()),
+                        severity_routing: severity_routing.clone(),
+                        namespace: namespace.clone(),
+                        renotify_on_update: webhook_config.renotify_on_update,
+                        receiver: webhook_config.receiver,
+                    }).await?;
                     
                     if !webhook_config.filters.is_empty() {
                         info!(
@@ -94,13 +137,52 @@ impl SourceController {
                     }
                 }
             }
+            crate::crd::source::SourceType::Schedule => {
+                if let (crate::crd::source::SourceConfig::Schedule(schedule_config), Some(handler)) =
+                    (&source.spec.config, &ctx.schedule_handler)
+                {
+                    info!(
+                        "Configuring schedule source '{}' with cron '{}' ({}) and workflow '{}'",
+                        name, schedule_config.cron, schedule_config.timezone, source.spec.trigger_workflow
+                    );
+                    
+                    handler.register_schedule(ScheduleConfig {
+                        source_name: name.clone(),
+                        cron: schedule_config.cron.clone(),
+                        timezone: schedule_config.timezone.clone(),
+                        workflow_name: source.spec.trigger_workflow.clone(),
+                        namespace: namespace.clone(),
+                    }).await?;
+                }
+            }
             _ => {
                 warn!("Source type {:?} not yet implemented", source.spec.source_type);
             }
         }
 
+        if let (Some(escalation), Some(source_id)) = (&ctx.escalation_controller, source_id) {
+            escalation.set_policy(source_id, &name, source.spec.escalation_policy.clone()).await;
+        }
+
+        let mut conditions = current_status.map(|s| s.conditions.clone()).unwrap_or_default();
+        let ready = routing_errors.is_empty();
+        let ready_condition = if ready {
+            Condition::new("Ready", ConditionStatus::True)
+                .reason("Configured")
+                .message("Source is configured and ready")
+        } else {
+            Condition::new("Ready", ConditionStatus::False)
+                .reason("InvalidSeverityRouting")
+                .message(routing_errors.join("; "))
+        };
+        let conditions_changed = set_condition(
+            &mut conditions,
+            ready_condition.observed_generation(source.metadata.generation),
+            chrono::Utc::now(),
+        );
+
         // Only update status if needed
-        if needs_update {
+        if needs_update || conditions_changed {
             let api = Api::<Source>::namespaced(ctx.client.clone(), &namespace);
             
             // Preserve existing counters
@@ -108,16 +190,10 @@ impl SourceController {
             let last_event_time = current_status.and_then(|s| s.last_event_time.clone());
             
             let status = SourceStatus {
-                ready: true,
+                ready,
                 last_event_time,
                 events_processed,
-                conditions: vec![Condition {
-                    condition_type: "Ready".to_string(),
-                    status: "True".to_string(),
-                    reason: "Configured".to_string(),
-                    message: "Source is configured and ready".to_string(),
-                    last_transition_time: chrono::Utc::now().to_rfc3339(),
-                }],
+                conditions,
             };
 
             let status_patch = json!({
@@ -130,7 +206,7 @@ impl SourceController {
                 .await
             {
                 Ok(_) => {
-                    info!("Successfully updated Source {}/{} to ready state", namespace, name);
+                    info!("Successfully updated Source {}/{} (ready: {})", namespace, name, ready);
                 }
                 Err(e) => error!("Failed to update status: {}", e),
             }
@@ -147,4 +223,48 @@ impl SourceController {
     pub async fn get_source_by_webhook_path(&self, path: &str) -> Option<Source> {
         None
     }
-} 
\ No newline at end of file
+}
+
+/// Problems with a source's severity routing: unknown severities and
+/// routes to workflows that don't exist
+fn severity_routing_errors(routing: &HashMap<String, String>, workflows: &HashSet<String>) -> Vec<String> {
+    let mut errors: Vec<String> = routing.iter()
+        .flat_map(|(severity, workflow)| {
+            let mut errors = Vec::new();
+            if !["critical", "warning", "info"].contains(&severity.to_lowercase().as_str()) {
+                errors.push(format!("unknown severity '{}'", severity));
+            }
+            if !workflows.contains(workflow) {
+                errors.push(format!("workflow '{}' for severity '{}' not found", workflow, severity));
+            }
+            errors
+        })
+        .collect();
+    errors.sort();
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_routing_errors() {
+        let workflows: HashSet<String> = ["page-oncall".to_string(), "triage".to_string()].into();
+        
+        let valid: HashMap<String, String> = [
+            ("critical".to_string(), "page-oncall".to_string()),
+            ("Warning".to_string(), "triage".to_string()),
+        ].into();
+        assert!(severity_routing_errors(&valid, &workflows).is_empty());
+        
+        let invalid: HashMap<String, String> = [
+            ("info".to_string(), "log-only".to_string()),
+            ("urgent".to_string(), "triage".to_string()),
+        ].into();
+        assert_eq!(severity_routing_errors(&invalid, &workflows), vec![
+            "unknown severity 'urgent'".to_string(),
+            "workflow 'log-only' for severity 'info' not found".to_string(),
+        ]);
+    }
+}
@@ -261,7 +261,9 @@ impl WorkflowController {
             } else {
                 None
             },
-            conditions: vec![],
+            conditions: workflow.status.as_ref()
+                .map(|s| s.conditions.clone())
+                .unwrap_or_default(),
         };
 
         let patch = json!({
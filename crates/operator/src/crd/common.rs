@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 use std::collections::HashMap;
@@ -75,4 +76,145 @@ pub struct SinkConfig {
     pub config: HashMap<String, serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conditions: Option<Vec<String>>,
-} 
\ No newline at end of file
+}
+
+/// Status of a condition, following the Kubernetes API conventions
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub enum ConditionStatus {
+    True,
+    False,
+    Unknown,
+}
+
+/// Resource condition following the `metav1.Condition` model
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct Condition {
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    pub status: ConditionStatus,
+    pub reason: String,
+    pub message: String,
+    #[serde(rename = "lastTransitionTime")]
+    pub last_transition_time: String,
+    /// Generation of the resource the condition was set for
+    #[serde(rename = "observedGeneration", skip_serializing_if = "Option::is_none")]
+    pub observed_generation: Option<i64>,
+}
+
+impl Condition {
+    /// Start building a condition; `lastTransitionTime` is filled in by
+    /// [`set_condition`]
+    pub fn new(condition_type: impl Into<String>, status: ConditionStatus) -> Self {
+        Self {
+            condition_type: condition_type.into(),
+            status,
+            reason: String::new(),
+            message: String::new(),
+            last_transition_time: String::new(),
+            observed_generation: None,
+        }
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = reason.into();
+        self
+    }
+
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    pub fn observed_generation(mut self, generation: Option<i64>) -> Self {
+        self.observed_generation = generation;
+        self
+    }
+}
+
+/// Insert or replace the condition of the same type in `conditions`.
+///
+/// `lastTransitionTime` is set to `now` only when the condition is new or its
+/// status changed; otherwise the existing timestamp is kept. Returns whether
+/// the list changed, so callers can skip status writes that would otherwise
+/// retrigger reconciliation.
+pub fn set_condition(conditions: &mut Vec<Condition>, mut condition: Condition, now: DateTime<Utc>) -> bool {
+    match conditions.iter_mut().find(|c| c.condition_type == condition.condition_type) {
+        Some(existing) => {
+            condition.last_transition_time = if existing.status == condition.status {
+                existing.last_transition_time.clone()
+            } else {
+                now.to_rfc3339()
+            };
+            if *existing == condition {
+                return false;
+            }
+            *existing = condition;
+            true
+        }
+        None => {
+            condition.last_transition_time = now.to_rfc3339();
+            conditions.push(condition);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    /// Clock that only moves when the test advances it
+    struct FakeClock(DateTime<Utc>);
+
+    impl FakeClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0
+        }
+
+        fn advance(&mut self, minutes: i64) {
+            self.0 += Duration::minutes(minutes);
+        }
+    }
+
+    fn ready(status: ConditionStatus, reason: &str, generation: i64) -> Condition {
+        Condition::new("Ready", status)
+            .reason(reason)
+            .message(format!("{} at generation {}", reason, generation))
+            .observed_generation(Some(generation))
+    }
+
+    #[test]
+    fn test_set_condition_transitions() {
+        let mut clock = FakeClock(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        let start = clock.now().to_rfc3339();
+        let mut conditions = Vec::new();
+
+        // New condition gets the current time
+        assert!(set_condition(&mut conditions, ready(ConditionStatus::True, "Configured", 1), clock.now()));
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].last_transition_time, start);
+
+        // Identical condition is a no-op
+        clock.advance(5);
+        assert!(!set_condition(&mut conditions, ready(ConditionStatus::True, "Configured", 1), clock.now()));
+        assert_eq!(conditions[0].last_transition_time, start);
+
+        // Same status with a new generation updates the condition but keeps the transition time
+        clock.advance(5);
+        assert!(set_condition(&mut conditions, ready(ConditionStatus::True, "Configured", 2), clock.now()));
+        assert_eq!(conditions[0].observed_generation, Some(2));
+        assert_eq!(conditions[0].last_transition_time, start);
+
+        // Status change moves the transition time
+        clock.advance(5);
+        assert!(set_condition(&mut conditions, ready(ConditionStatus::False, "Failed", 2), clock.now()));
+        assert_eq!(conditions[0].status, ConditionStatus::False);
+        assert_eq!(conditions[0].last_transition_time, clock.now().to_rfc3339());
+
+        // Other condition types are tracked independently
+        assert!(set_condition(&mut conditions, Condition::new("Degraded", ConditionStatus::Unknown), clock.now()));
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[0].condition_type, "Ready");
+    }
+}
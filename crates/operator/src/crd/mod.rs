@@ -6,7 +6,8 @@ pub mod common;
 pub use source::{Source, SourceSpec, SourceStatus};
 pub use workflow::{
     Workflow, WorkflowSpec, WorkflowStatus, RuntimeConfig, LLMConfig,
-    Step as WorkflowStep, StepType, Tool, DetailedTool, OutputDef, StepStatus,
+    Step as WorkflowStep, StepType, HttpMethod, Tool, DetailedTool, OutputDef, StepStatus,
+    StepResources, ResourceQuantities, StepEnvVar, EnvFromRef,
 };
 pub use sink::{Sink, SinkSpec, SinkStatus};
 
@@ -3,6 +3,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::workflow::{HttpMethod, KeyRef};
+
 #[derive(CustomResource, Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[kube(
     group = "punchingfist.io",
@@ -12,7 +14,7 @@ use std::collections::HashMap;
     status = "SinkStatus"
 )]
 pub struct SinkSpec {
-    /// Type of sink: slack, alertmanager, prometheus, jira, pagerduty, workflow
+    /// Type of sink: slack, alertmanager, prometheus, jira, pagerduty, workflow, stdout, webhook, inhibition
     #[serde(rename = "type")]
     pub sink_type: SinkType,
     
@@ -22,6 +24,57 @@ pub struct SinkSpec {
     /// Condition to evaluate before sending to sink
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+
+    /// Tera template used to render the workflow report sent to the sink.
+    /// Agent result fields (summary, root_cause, findings, recommendations, ...)
+    /// are available as top-level variables.
+    #[serde(rename = "reportTemplate", skip_serializing_if = "Option::is_none")]
+    pub report_template: Option<String>,
+
+    /// Retries of failed deliveries (defaults to 3 attempts, 1s apart at
+    /// first)
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// How failed deliveries are retried: the delay before each retry doubles,
+/// starting at `baseDelayMs`, and is jittered by up to half
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    #[serde(rename = "maxAttempts", default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, in milliseconds
+    #[serde(rename = "baseDelayMs", default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_base_delay_ms() -> u64 {
+    1000
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            base_delay_ms: default_base_delay_ms(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retry number `retry` (starting at 1): the base delay
+    /// doubled for each earlier retry, less up to half of it at random
+    pub fn delay(&self, retry: u32) -> std::time::Duration {
+        let delay = self.base_delay_ms.saturating_mul(1 << retry.saturating_sub(1).min(16));
+        let jitter = rand::random::<f64>() * 0.5;
+        std::time::Duration::from_millis((delay as f64 * (1.0 - jitter)) as u64)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -34,12 +87,14 @@ pub enum SinkType {
     PagerDuty,
     Workflow,
     Stdout,
+    Webhook,
+    Inhibition,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct SinkConfig {
     /// Slack configuration
-    /// Channel to send messages to
+    /// Channel to send messages to, instead of the incoming webhook's default
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel: Option<String>,
     
@@ -55,11 +110,14 @@ pub struct SinkConfig {
     #[serde(rename = "mentionUsers", default)]
     pub mention_users: Vec<String>,
     
-    /// AlertManager/Prometheus endpoint
+    /// AlertManager/Prometheus endpoint, the URL inhibition rules are
+    /// POSTed to (for Inhibition), the incoming webhook URL (for Slack), the
+    /// Events API URL (for PagerDuty, defaults to PagerDuty's), or the site
+    /// URL (for JIRA)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub endpoint: Option<String>,
     
-    /// Action to perform (for AlertManager: resolve, annotate, silence; for PagerDuty: trigger, resolve)
+    /// Action to perform (for AlertManager: resolve, the default; for PagerDuty: trigger or resolve, both by default)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action: Option<String>,
     
@@ -83,11 +141,22 @@ pub struct SinkConfig {
     #[serde(rename = "issueType", skip_serializing_if = "Option::is_none")]
     pub issue_type: Option<String>,
     
-    /// Credentials secret reference (for JIRA)
+    /// Name of the Secret holding the credentials (for JIRA: `email` and
+    /// `apiToken`, or `token`)
     #[serde(rename = "credentialsSecret", skip_serializing_if = "Option::is_none")]
     pub credentials_secret: Option<String>,
     
-    /// Routing key (for PagerDuty)
+    /// Issue summary, rendered as a report template (for JIRA, defaults to
+    /// the workflow name and the investigation summary)
+    #[serde(rename = "summaryTemplate", skip_serializing_if = "Option::is_none")]
+    pub summary_template: Option<String>,
+    
+    /// Issue fields by id, e.g. `customfield_10020`, to values rendered as
+    /// report templates; values that parse as JSON are sent as such (for JIRA)
+    #[serde(rename = "customFields", default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_fields: HashMap<String, String>,
+    
+    /// Events API v2 integration key (for PagerDuty)
     #[serde(rename = "routingKey", skip_serializing_if = "Option::is_none")]
     pub routing_key: Option<String>,
     
@@ -115,6 +184,40 @@ pub struct SinkConfig {
     /// Whether to pretty print JSON output for stdout sink
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pretty: Option<bool>,
+
+    // Webhook specific config
+    /// URL to deliver to, rendered as a template (for Webhook)
+    #[serde(rename = "urlTemplate", skip_serializing_if = "Option::is_none")]
+    pub url_template: Option<String>,
+
+    /// HTTP method, POST or PUT (for Webhook, defaults to POST)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<HttpMethod>,
+
+    /// Request headers; values are rendered as templates (for Webhook and
+    /// Inhibition)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+
+    /// Request body, rendered as a template; the workflow output is sent as
+    /// JSON if unset (for Webhook)
+    #[serde(rename = "bodyTemplate", skip_serializing_if = "Option::is_none")]
+    pub body_template: Option<String>,
+
+    /// Shared secret the body is signed with in the
+    /// `X-Punching-Fist-Signature` header (for Webhook)
+    #[serde(rename = "hmacSecret", skip_serializing_if = "Option::is_none")]
+    pub hmac_secret: Option<String>,
+
+    /// Secret key whose value is sent in the `authHeader` header, so
+    /// credentials stay out of the Sink (for Webhook)
+    #[serde(rename = "secretRef", skip_serializing_if = "Option::is_none")]
+    pub secret_ref: Option<KeyRef>,
+
+    /// Header the `secretRef` value is sent in (for Webhook, defaults to
+    /// Authorization)
+    #[serde(rename = "authHeader", skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
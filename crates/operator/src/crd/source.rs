@@ -3,6 +3,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub use super::common::{Condition, ConditionStatus};
+
 #[derive(CustomResource, Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[kube(
     group = "punchingfist.io",
@@ -23,9 +25,37 @@ pub struct SourceSpec {
     #[serde(rename = "triggerWorkflow")]
     pub trigger_workflow: String,
     
+    /// Workflow to trigger per alert severity (critical, warning, info),
+    /// overriding `triggerWorkflow`
+    #[serde(default, rename = "severityRouting", skip_serializing_if = "Option::is_none")]
+    pub severity_routing: Option<HashMap<String, String>>,
+    
     /// Additional context to pass to the workflow
     #[serde(default)]
     pub context: HashMap<String, String>,
+    
+    /// Automatically escalate alerts that stay in triage too long
+    #[serde(rename = "escalationPolicy", skip_serializing_if = "Option::is_none")]
+    pub escalation_policy: Option<EscalationPolicy>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct EscalationPolicy {
+    /// Severity to escalate the alert to
+    #[serde(rename = "escalateTo")]
+    pub escalate_to: AlertSeverity,
+    
+    /// Minutes an alert may stay in triage before it is escalated
+    #[serde(rename = "afterMinutes")]
+    pub after_minutes: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Critical,
+    Warning,
+    Info,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -57,11 +87,30 @@ pub struct WebhookConfig {
     #[serde(default)]
     pub filters: HashMap<String, Vec<String>>,
     
+    /// Send a finished investigation to its sinks again when a re-fire of
+    /// its alert adds or changes labels or annotations
+    #[serde(default, rename = "renotifyOnUpdate")]
+    pub renotify_on_update: bool,
+    
+    /// Format of the alert payloads posted to the webhook
+    #[serde(default)]
+    pub receiver: WebhookReceiver,
+    
     /// Authentication configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authentication: Option<AuthConfig>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookReceiver {
+    /// Prometheus AlertManager webhook payloads
+    #[default]
+    Prometheus,
+    /// Grafana unified alerting webhook payloads
+    Grafana,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct ChatConfig {
     /// Chat platform (e.g., slack)
@@ -143,17 +192,6 @@ pub struct SourceStatus {
     pub conditions: Vec<Condition>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
-pub struct Condition {
-    #[serde(rename = "type")]
-    pub condition_type: String,
-    pub status: String,
-    pub reason: String,
-    pub message: String,
-    #[serde(rename = "lastTransitionTime")]
-    pub last_transition_time: String,
-}
-
 fn default_timezone() -> String {
     "UTC".to_string()
 } 
\ No newline at end of file
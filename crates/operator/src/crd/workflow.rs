@@ -18,12 +18,39 @@ pub struct WorkflowSpec {
     /// Steps to execute in the workflow
     pub steps: Vec<Step>,
     
+    /// JSON Schema the triggering source event data must satisfy
+    #[serde(default, rename = "inputSchema", skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    
     /// Output definitions
     #[serde(default)]
     pub outputs: Vec<OutputDef>,
     
     /// Sinks to send results to
     pub sinks: Vec<String>,
+    
+    /// Send a preliminary message to the sinks when the workflow starts
+    #[serde(default, rename = "notifyOnStart")]
+    pub notify_on_start: bool,
+    
+    /// Maximum LLM tokens agent steps may consume across one execution
+    #[serde(default, rename = "tokenBudget", skip_serializing_if = "Option::is_none")]
+    pub token_budget: Option<u32>,
+    
+    /// Maximum LLM spend in USD agent steps may incur across one execution,
+    /// priced with the operator's `cost_per_token` table
+    #[serde(default, rename = "maxCostUsd", skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+    
+    /// Variables resolved from ConfigMaps and Secrets before the first step
+    /// runs, available to templates as `{{ vars.NAME }}`
+    #[serde(default, rename = "configVars")]
+    pub config_vars: Vec<ConfigVarSource>,
+    
+    /// Maximum number of agent steps across this workflow's executions that
+    /// may call the LLM at the same time (defaults to 3)
+    #[serde(default, rename = "maxParallelAgents", skip_serializing_if = "Option::is_none")]
+    pub max_parallel_agents: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -38,14 +65,20 @@ pub struct RuntimeConfig {
     /// Environment variables
     #[serde(default)]
     pub environment: HashMap<String, String>,
+    
+    /// ConfigMap in the workflow's namespace holding the agent behavior
+    /// configuration (system_prompt, max_iterations, require_approval_for)
+    #[serde(default, rename = "agentConfigMap", skip_serializing_if = "Option::is_none")]
+    pub agent_config_map: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct LLMConfig {
-    /// LLM provider (local, claude, openai)
+    /// LLM provider (local, claude, openai, vertexai)
     pub provider: String,
     
-    /// Endpoint URL for the LLM (only needed for local/custom providers)
+    /// Endpoint URL for the LLM (only needed for local/custom providers, or
+    /// to override the regional Vertex AI host)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub endpoint: Option<String>,
     
@@ -62,7 +95,7 @@ pub struct Step {
     /// Step name
     pub name: String,
     
-    /// Step type: cli, agent, conditional
+    /// Step type: cli, agent, conditional, http
     #[serde(rename = "type")]
     pub step_type: StepType,
     
@@ -97,6 +130,150 @@ pub struct Step {
     /// Nested agent configuration for conditional steps
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<Box<Step>>,
+    
+    /// Steps that run only when the condition holds (for conditional
+    /// steps). They start after this step; the steps of the branch not
+    /// taken are skipped, and steps depending on a skipped step still run,
+    /// so a later step can join both branches
+    #[serde(rename = "thenSteps", default, skip_serializing_if = "Vec::is_empty")]
+    pub then_steps: Vec<String>,
+    
+    /// Steps that run only when the condition doesn't hold (for
+    /// conditional steps)
+    #[serde(rename = "elseSteps", default, skip_serializing_if = "Vec::is_empty")]
+    pub else_steps: Vec<String>,
+    
+    /// URL to call, rendered as a template (for HTTP steps)
+    #[serde(rename = "urlTemplate", skip_serializing_if = "Option::is_none")]
+    pub url_template: Option<String>,
+    
+    /// HTTP method (for HTTP steps, defaults to GET)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<HttpMethod>,
+    
+    /// Request headers; values are rendered as templates (for HTTP steps)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    
+    /// Request body, rendered as a template (for HTTP steps)
+    #[serde(rename = "bodyTemplate", skip_serializing_if = "Option::is_none")]
+    pub body_template: Option<String>,
+    
+    /// Status code the response must have; any 2xx is accepted if unset
+    /// (for HTTP steps)
+    #[serde(rename = "expectedStatus", skip_serializing_if = "Option::is_none")]
+    pub expected_status: Option<u16>,
+    
+    /// Steps that must succeed before this step starts. Steps without
+    /// dependencies between them run concurrently; when no step of the
+    /// workflow declares any, steps run in order
+    #[serde(rename = "dependsOn", default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    
+    /// Let the steps that depend on this one run, and the workflow succeed,
+    /// even if this step fails
+    #[serde(rename = "continueOnError", default)]
+    pub continue_on_error: bool,
+    
+    /// Times to run the step again after it fails, e.g. when a pod can't be
+    /// scheduled or an API is flaky. Invalid steps aren't retried.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    
+    /// Seconds to wait before each retry (defaults to 5)
+    #[serde(rename = "retryDelaySeconds", skip_serializing_if = "Option::is_none")]
+    pub retry_delay_seconds: Option<u64>,
+    
+    /// Workflow context metadata keys exposed to the pod as environment
+    /// variables named after the upper-cased key (for CLI steps). Keys
+    /// ending in `_secret`, `_token` or `_password` must hold a Secret key
+    /// reference (`{"name": ..., "key": ...}`) and are injected through
+    /// `secretKeyRef`
+    #[serde(rename = "envFromContext", default, skip_serializing_if = "Vec::is_empty")]
+    pub env_from_context: Vec<String>,
+    
+    /// Environment variables of the pod (for CLI steps). Values are
+    /// rendered as templates; sensitive values should come from a Secret
+    /// through `valueFrom`, so they never appear in the command or the
+    /// step's stored output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<StepEnvVar>,
+    
+    /// ConfigMaps and Secrets whose keys all become environment variables
+    /// of the pod (for CLI steps)
+    #[serde(rename = "envFrom", default, skip_serializing_if = "Vec::is_empty")]
+    pub env_from: Vec<EnvFromRef>,
+    
+    /// CPU and memory requests and limits of the pod (for CLI steps).
+    /// Quantities left unset default to requests of 100m CPU and 128Mi
+    /// memory and limits of 500m CPU and 512Mi memory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<StepResources>,
+    
+    /// Service account the pod runs as, so kubectl commands can be limited
+    /// to what its RBAC allows (for CLI steps). Pods use the namespace's
+    /// `default` service account if unset
+    #[serde(rename = "serviceAccountName", skip_serializing_if = "Option::is_none")]
+    pub service_account_name: Option<String>,
+    
+    /// jq expression applied to the step's output once it succeeds; the
+    /// result replaces the output seen by later steps, sinks and workflow
+    /// outputs (e.g. `[.findings[] | select(.severity == "critical")]`).
+    /// The step fails if the expression is invalid or can't be applied.
+    #[serde(rename = "outputTransform", skip_serializing_if = "Option::is_none")]
+    pub output_transform: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct StepEnvVar {
+    /// Variable name
+    pub name: String,
+    
+    /// Value, rendered as a template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    
+    /// Key of a ConfigMap or Secret in the pod's namespace the value is
+    /// read from instead
+    #[serde(rename = "valueFrom", skip_serializing_if = "Option::is_none")]
+    pub value_from: Option<ConfigVarRef>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EnvFromRef {
+    /// ConfigMap in the pod's namespace
+    ConfigMapRef(NameRef),
+    /// Secret in the pod's namespace
+    SecretRef(NameRef),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct NameRef {
+    /// Name of the ConfigMap or Secret
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, JsonSchema)]
+pub struct StepResources {
+    /// Resources the pod is scheduled with
+    #[serde(default)]
+    pub requests: ResourceQuantities,
+    
+    /// Resources the pod may use at most
+    #[serde(default)]
+    pub limits: ResourceQuantities,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, JsonSchema)]
+pub struct ResourceQuantities {
+    /// CPU quantity, e.g. `250m`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<String>,
+    
+    /// Memory quantity, e.g. `256Mi`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -105,6 +282,18 @@ pub enum StepType {
     Cli,
     Agent,
     Conditional,
+    Http,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -131,13 +320,58 @@ pub struct DetailedTool {
     pub endpoint: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct ConfigVarSource {
+    /// Variable name
+    pub name: String,
+    
+    /// Key the value is read from
+    pub source: ConfigVarRef,
+    
+    /// Leave the variable unset instead of failing the workflow when the
+    /// ConfigMap, Secret or key doesn't exist
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigVarRef {
+    /// Key of a ConfigMap in the workflow's namespace
+    ConfigMapKeyRef(KeyRef),
+    /// Key of a Secret in the workflow's namespace
+    SecretKeyRef(KeyRef),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct KeyRef {
+    /// Name of the ConfigMap or Secret
+    pub name: String,
+    
+    /// Key within its data
+    pub key: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct OutputDef {
     /// Output name
     pub name: String,
     
     /// Value expression
-    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    
+    /// Step whose result the output is extracted from
+    #[serde(rename = "fromStep", skip_serializing_if = "Option::is_none")]
+    pub from_step: Option<String>,
+    
+    /// JSON path into the step result, e.g. `$.result.summary` or `$.findings[0]`
+    #[serde(rename = "jsonPath", skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+    
+    /// Fail the workflow if the output cannot be extracted
+    #[serde(default)]
+    pub required: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
@@ -1,24 +1,28 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::RwLock;
 use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use punching_fist_operator::{
     config::{Config, TaskExecutionMode},
-    controllers::{SourceController, WorkflowController, SinkController},
+    controllers::{SourceController, WorkflowController, SinkController, EscalationController, ConfigMapWatcher, LeaderElection, CustomResourceMonitor},
     server::Server,
-    sources::WebhookHandler,
-    store::create_store,
-    workflow::{WorkflowEngine, StepExecutor},
+    sinks::monitor::SinkFailureMonitor,
+    sources::{ScheduleHandler, WebhookHandler},
+    store::{create_store, ArchivalTask, BackupTask, Store},
+    workflow::{WorkflowEngine, StepExecutor, LoggingHook, MetricsHook},
     Result, Error,
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging with more verbose configuration
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"))
-        )
+    // Initialize logging with a reloadable filter so the level can be changed at runtime
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let (filter_layer, log_reload_handle) = tracing_subscriber::reload::Layer::new(env_filter);
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(tracing_subscriber::fmt::layer())
         .init();
 
     info!("Starting punching-fist-operator Phase 1...");
@@ -35,6 +39,10 @@ async fn main() -> Result<()> {
             return Err(e);
         }
     };
+    let shared_config = Arc::new(RwLock::new(config.clone()));
+
+    // Register Prometheus metrics with the registry served at /metrics
+    punching_fist_operator::metrics::register_metrics();
 
     // Initialize store
     info!("Initializing database store...");
@@ -87,11 +95,21 @@ async fn main() -> Result<()> {
     };
 
     // Create workflow engine components
-    let step_executor = Arc::new(StepExecutor::new(
-        kube_client.clone(), 
-        config.kube.namespace.clone()
-    ));
-    let workflow_engine = Arc::new(WorkflowEngine::new(store.clone(), step_executor));
+    let step_executor = Arc::new(
+        StepExecutor::new(kube_client.clone(), config.kube.namespace.clone())
+            .with_promql_cache_ttl(Duration::from_secs(config.agent.promql_cache_ttl_seconds))
+            .with_keep_failed_pods(config.execution.keep_failed_pods)
+            .with_store(store.clone())
+    );
+    let sink_controller = Arc::new(SinkController::new(kube_client.clone()).with_store(store.clone()));
+    let workflow_engine = Arc::new(
+        WorkflowEngine::new(store.clone(), step_executor)
+            .with_hook(MetricsHook)
+            .with_hook(LoggingHook)
+            .with_sink_controller(sink_controller.clone())
+            .with_client(kube_client.clone())
+            .with_shared_config(shared_config.clone())
+    );
     
     // Create webhook handler with workflow engine
     let webhook_handler = Arc::new(
@@ -99,57 +117,76 @@ async fn main() -> Result<()> {
             .with_workflow_engine(workflow_engine.clone())
     );
 
-    // Start workflow engine
-    workflow_engine.clone().start().await;
-
-    // In Kubernetes mode, start controllers
-    match config.execution.mode {
+    // In Kubernetes mode, only the elected leader runs the workflow engine,
+    // controllers and scheduled tasks
+    let leader_election = match config.execution.mode {
         TaskExecutionMode::Kubernetes => {
             info!("Starting in Kubernetes mode");
             
-            // Start source controller
-            let source_controller = Arc::new(SourceController::new(
+            // Hot-reload configuration from the punching-fist-config ConfigMap
+            let watcher = Arc::new(ConfigMapWatcher::new(
                 kube_client.clone(),
-                webhook_handler.clone(),
+                config.kube.namespace.clone(),
+                shared_config.clone(),
+                config.clone(),
             ));
-            let controller = source_controller.clone();
             tokio::spawn(async move {
-                if let Err(e) = controller.run().await {
-                    tracing::error!("Source controller error: {}", e);
+                if let Err(e) = watcher.run().await {
+                    tracing::error!("ConfigMap watcher error: {}", e);
                 }
             });
             
-            // Create sink controller
-            let sink_controller = Arc::new(SinkController::new(kube_client.clone()));
-            
-            // Start sink controller
-            let controller = sink_controller.clone();
+            let leader_election = Arc::new(LeaderElection::new(
+                kube_client.clone(),
+                config.kube.namespace.clone(),
+                LeaderElection::default_identity(),
+            ));
+            let election = leader_election.clone();
             tokio::spawn(async move {
-                if let Err(e) = controller.run().await {
-                    tracing::error!("Sink controller error: {}", e);
+                if let Err(e) = election.run().await {
+                    tracing::error!("Leader election error: {}", e);
                 }
             });
             
-            // Start workflow controller  
-            let workflow_controller = Arc::new(WorkflowController::new(
-                kube_client.clone(),
-                store.clone(),
-                workflow_engine.clone(),
-                sink_controller,
-            ));
-            let controller = workflow_controller.clone();
+            let election = leader_election.clone();
+            let kube_client = kube_client.clone();
+            let store = store.clone();
+            let shared_config = shared_config.clone();
+            let webhook_handler = webhook_handler.clone();
+            let workflow_engine = workflow_engine.clone();
             tokio::spawn(async move {
-                controller.run().await;
+                election.wait_for_leadership().await;
+                info!("Elected leader, starting workflow engine, controllers and scheduled tasks");
+                workflow_engine.clone().start().await;
+                spawn_scheduled_tasks(store.clone(), shared_config.clone(), webhook_handler.clone());
+                spawn_controllers(kube_client, store, shared_config, webhook_handler, workflow_engine, sink_controller);
+                
+                // Controllers can't be stopped cleanly, so restart as a follower
+                election.wait_for_lost_leadership().await;
+                tracing::error!("Lost leadership, exiting so another replica can take over");
+                std::process::exit(1);
             });
+            
+            Some(leader_election)
         }
         _ => {
             info!("Running in local execution mode, skipping Kubernetes controllers");
+            workflow_engine.clone().start().await;
+            spawn_scheduled_tasks(store.clone(), shared_config.clone(), webhook_handler.clone());
+            None
         }
-    }
+    };
 
     // Initialize server
     info!("Initializing HTTP server...");
-    let server = Server::new(&config, store.clone(), webhook_handler.clone());
+    let mut server = Server::new(&config, store.clone(), webhook_handler.clone())
+        .with_shared_config(shared_config)
+        .with_log_reload_handle(log_reload_handle)
+        .with_workflow_engine(workflow_engine.clone())
+        .with_client(kube_client);
+    if let Some(leader_election) = leader_election {
+        server = server.with_leader_election(leader_election);
+    }
     let app = server.build_router();
 
     // Start server
@@ -171,4 +208,108 @@ async fn main() -> Result<()> {
         })?;
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Start the periodic background tasks
+fn spawn_scheduled_tasks(store: Arc<dyn Store>, shared_config: Arc<RwLock<Config>>, webhook_handler: Arc<WebhookHandler>) {
+    // Resolve alerts as they reach their endsAt
+    tokio::spawn(async move {
+        if let Err(e) = webhook_handler.run_alert_expiry().await {
+            tracing::error!("Alert expiry task error: {}", e);
+        }
+    });
+
+    // Start archival task
+    let archival_task = Arc::new(ArchivalTask::new(store.clone()));
+    tokio::spawn(async move {
+        if let Err(e) = archival_task.run().await {
+            tracing::error!("Archival task error: {}", e);
+        }
+    });
+
+    // Start daily database backups
+    let backup_task = Arc::new(BackupTask::new(store.clone(), shared_config.clone()));
+    tokio::spawn(async move {
+        if let Err(e) = backup_task.run().await {
+            tracing::error!("Backup task error: {}", e);
+        }
+    });
+
+    // Export sink failures over the last hour as a gauge
+    let sink_failure_monitor = Arc::new(SinkFailureMonitor::new(store, shared_config));
+    tokio::spawn(async move {
+        if let Err(e) = sink_failure_monitor.run().await {
+            tracing::error!("Sink failure monitor error: {}", e);
+        }
+    });
+}
+
+/// Start the Kubernetes controllers
+fn spawn_controllers(
+    kube_client: kube::Client,
+    store: Arc<dyn Store>,
+    shared_config: Arc<RwLock<Config>>,
+    webhook_handler: Arc<WebhookHandler>,
+    workflow_engine: Arc<WorkflowEngine>,
+    sink_controller: Arc<SinkController>,
+) {
+    // Start escalation controller
+    let escalation_controller = Arc::new(EscalationController::new(store.clone()));
+    let controller = escalation_controller.clone();
+    tokio::spawn(async move {
+        if let Err(e) = controller.run().await {
+            tracing::error!("Escalation controller error: {}", e);
+        }
+    });
+    
+    // Emit events when too many custom resources are stored
+    let custom_resource_monitor = Arc::new(CustomResourceMonitor::new(kube_client.clone(), store.clone(), shared_config));
+    tokio::spawn(async move {
+        if let Err(e) = custom_resource_monitor.run().await {
+            tracing::error!("Custom resource monitor error: {}", e);
+        }
+    });
+    
+    // Start source controller, which also runs scheduled sources
+    let schedule_handler = Arc::new(
+        ScheduleHandler::new(store.clone(), Some(kube_client.clone()))
+            .with_workflow_engine(workflow_engine.clone())
+    );
+    let source_controller = Arc::new(
+        SourceController::new(kube_client.clone(), webhook_handler)
+            .with_escalation_controller(escalation_controller)
+            .with_schedule_handler(schedule_handler)
+    );
+    tokio::spawn(async move {
+        if let Err(e) = source_controller.run().await {
+            tracing::error!("Source controller error: {}", e);
+        }
+    });
+    
+    // Start sink controller
+    let controller = sink_controller.clone();
+    tokio::spawn(async move {
+        if let Err(e) = controller.run().await {
+            tracing::error!("Sink controller error: {}", e);
+        }
+    });
+    
+    // Requeue received alerts the workflow engine dropped
+    let engine = workflow_engine.clone();
+    tokio::spawn(async move {
+        if let Err(e) = engine.run_alert_requeue().await {
+            tracing::error!("Alert requeue task error: {}", e);
+        }
+    });
+    
+    // Start workflow controller  
+    let workflow_controller = Arc::new(WorkflowController::new(
+        kube_client,
+        store,
+        workflow_engine,
+        sink_controller,
+    ));
+    tokio::spawn(async move {
+        workflow_controller.run().await;
+    });
+}
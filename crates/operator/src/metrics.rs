@@ -1,5 +1,13 @@
+use std::sync::Once;
+
+use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
-use prometheus::{register_int_counter, Encoder, IntCounter, Registry, TextEncoder};
+use prometheus::{
+    register_counter, register_histogram, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, register_int_gauge_vec, Counter, Encoder,
+    Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry,
+    TextEncoder,
+};
 
 lazy_static! {
     pub static ref REGISTRY: Registry = Registry::new();
@@ -8,17 +16,164 @@ lazy_static! {
             "punchingfist_processed_alerts_total",
             "Total number of processed alerts."
         ).unwrap();
+    pub static ref SINK_DELIVERY_LATENCY_SECONDS: HistogramVec =
+        register_histogram_vec!(
+            "sink_delivery_latency_seconds",
+            "Time from sink output creation until it was sent.",
+            &["sink_type"],
+            vec![0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0]
+        ).unwrap();
+    pub static ref WORKFLOW_STEP_DURATION_SECONDS: HistogramVec =
+        register_histogram_vec!(
+            "workflow_step_duration_seconds",
+            "Duration of workflow step executions.",
+            &["step_type", "status"],
+            vec![0.5, 1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0]
+        ).unwrap();
+    pub static ref ALERTS_AUTO_EXPIRED_TOTAL: IntCounter =
+        register_int_counter!(
+            "alerts_auto_expired_total",
+            "Total number of alerts resolved because their endsAt was reached."
+        ).unwrap();
+    pub static ref SINK_FAILURES_LAST_HOUR: IntGaugeVec =
+        register_int_gauge_vec!(
+            "sink_failures_last_hour",
+            "Number of failed sink outputs created in the last hour.",
+            &["sink_type"]
+        ).unwrap();
+    pub static ref ALERT_TRIAGE_DURATION_SECONDS: Histogram =
+        register_histogram!(
+            "alert_triage_duration_seconds",
+            "Time from an alert being received until its triage completed.",
+            vec![10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0]
+        ).unwrap();
+    pub static ref ALERT_RESOLUTION_DURATION_SECONDS: Histogram =
+        register_histogram!(
+            "alert_resolution_duration_seconds",
+            "Time from an alert being received until it was resolved.",
+            vec![60.0, 300.0, 900.0, 1800.0, 3600.0, 4.0 * 3600.0, 12.0 * 3600.0, 86400.0]
+        ).unwrap();
+    pub static ref WORKFLOW_STEP_RETRIES_TOTAL: IntCounter =
+        register_int_counter!(
+            "workflow_step_retries_total",
+            "Total number of workflow step attempts that were retried."
+        ).unwrap();
+    pub static ref AGENT_TOOL_RECOVERY_PROMPTS_TOTAL: IntCounter =
+        register_int_counter!(
+            "agent_tool_recovery_prompts_total",
+            "Total number of investigations prompted again after the LLM called a tool in a way it doesn't allow."
+        ).unwrap();
+    pub static ref LLM_API_ERRORS_TOTAL: IntCounterVec =
+        register_int_counter_vec!(
+            "llm_api_errors_total",
+            "Total number of failed LLM API requests.",
+            &["provider", "error_type"]
+        ).unwrap();
+    pub static ref WORKFLOW_PARALLEL_AGENT_SLOTS_IN_USE: IntGaugeVec =
+        register_int_gauge_vec!(
+            "workflow_parallel_agent_slots_in_use",
+            "Number of agent steps currently holding one of a workflow's maxParallelAgents slots.",
+            &["workflow"]
+        ).unwrap();
+    pub static ref ALERT_REQUEUED_TOTAL: IntCounter =
+        register_int_counter!(
+            "alert_requeued_total",
+            "Total number of received alerts requeued because they never got a workflow."
+        ).unwrap();
+    pub static ref WORKFLOW_ENGINE_ALIVE: IntGauge =
+        register_int_gauge!(
+            "workflow_engine_alive",
+            "1 while the workflow engine's execution loop heartbeats, 0 once it has missed its heartbeat."
+        ).unwrap();
+    pub static ref WORKFLOW_COST_USD_TOTAL: Counter =
+        register_counter!(
+            "workflow_cost_usd_total",
+            "Total LLM spend of workflow agent steps in USD, priced with cost_per_token."
+        ).unwrap();
 }
 
+static REGISTER: Once = Once::new();
+
 // Function to register metrics (though lazy_static handles this for PROCESSED_ALERTS_TOTAL)
 // We can add more metrics here later and register them explicitly if needed.
+// Safe to call more than once.
 pub fn register_metrics() {
+    REGISTER.call_once(|| {
+        register_core_metrics();
+        register_custom_metrics();
+    });
+}
+
+fn register_core_metrics() {
     REGISTRY
         .register(Box::new(PROCESSED_ALERTS_TOTAL.clone()))
         .expect("Failed to register PROCESSED_ALERTS_TOTAL");
+    REGISTRY
+        .register(Box::new(SINK_DELIVERY_LATENCY_SECONDS.clone()))
+        .expect("Failed to register SINK_DELIVERY_LATENCY_SECONDS");
+    REGISTRY
+        .register(Box::new(WORKFLOW_STEP_DURATION_SECONDS.clone()))
+        .expect("Failed to register WORKFLOW_STEP_DURATION_SECONDS");
+    REGISTRY
+        .register(Box::new(ALERTS_AUTO_EXPIRED_TOTAL.clone()))
+        .expect("Failed to register ALERTS_AUTO_EXPIRED_TOTAL");
+    REGISTRY
+        .register(Box::new(SINK_FAILURES_LAST_HOUR.clone()))
+        .expect("Failed to register SINK_FAILURES_LAST_HOUR");
     // Add other metric registrations here if they are not using lazy_static register_... macros
 }
 
+// Business-level metrics on alert handling and LLM usage. Called by
+// register_metrics.
+pub fn register_custom_metrics() {
+    REGISTRY
+        .register(Box::new(ALERT_TRIAGE_DURATION_SECONDS.clone()))
+        .expect("Failed to register ALERT_TRIAGE_DURATION_SECONDS");
+    REGISTRY
+        .register(Box::new(ALERT_RESOLUTION_DURATION_SECONDS.clone()))
+        .expect("Failed to register ALERT_RESOLUTION_DURATION_SECONDS");
+    REGISTRY
+        .register(Box::new(WORKFLOW_STEP_RETRIES_TOTAL.clone()))
+        .expect("Failed to register WORKFLOW_STEP_RETRIES_TOTAL");
+    REGISTRY
+        .register(Box::new(AGENT_TOOL_RECOVERY_PROMPTS_TOTAL.clone()))
+        .expect("Failed to register AGENT_TOOL_RECOVERY_PROMPTS_TOTAL");
+    REGISTRY
+        .register(Box::new(LLM_API_ERRORS_TOTAL.clone()))
+        .expect("Failed to register LLM_API_ERRORS_TOTAL");
+    REGISTRY
+        .register(Box::new(WORKFLOW_PARALLEL_AGENT_SLOTS_IN_USE.clone()))
+        .expect("Failed to register WORKFLOW_PARALLEL_AGENT_SLOTS_IN_USE");
+    REGISTRY
+        .register(Box::new(ALERT_REQUEUED_TOTAL.clone()))
+        .expect("Failed to register ALERT_REQUEUED_TOTAL");
+    REGISTRY
+        .register(Box::new(WORKFLOW_ENGINE_ALIVE.clone()))
+        .expect("Failed to register WORKFLOW_ENGINE_ALIVE");
+    REGISTRY
+        .register(Box::new(WORKFLOW_COST_USD_TOTAL.clone()))
+        .expect("Failed to register WORKFLOW_COST_USD_TOTAL");
+}
+
+// Record how long a sink output waited between creation and delivery
+pub fn observe_sink_delivery_latency(sink_type: &str, created_at: DateTime<Utc>, sent_at: DateTime<Utc>) {
+    let latency = (sent_at - created_at).num_milliseconds().max(0) as f64 / 1000.0;
+    SINK_DELIVERY_LATENCY_SECONDS
+        .with_label_values(&[sink_type])
+        .observe(latency);
+}
+
+// Record an alert timing milestone (`triage_completed_at` or `resolved_at`)
+// against the time the alert was received
+pub fn observe_alert_timing(field: &str, received_at: DateTime<Utc>, at: DateTime<Utc>) {
+    let duration = (at - received_at).num_milliseconds().max(0) as f64 / 1000.0;
+    match field {
+        "triage_completed_at" => ALERT_TRIAGE_DURATION_SECONDS.observe(duration),
+        "resolved_at" => ALERT_RESOLUTION_DURATION_SECONDS.observe(duration),
+        _ => {}
+    }
+}
+
 // Function to gather metrics for exposition
 pub fn gather_metrics() -> String {
     let mut buffer = vec![];
@@ -28,4 +183,33 @@ pub fn gather_metrics() -> String {
         .encode(&metric_families, &mut buffer)
         .expect("Failed to encode metrics");
     String::from_utf8(buffer).expect("Failed to convert metrics to string")
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_metrics_are_gathered() {
+        register_metrics();
+        register_metrics();
+
+        let received_at = Utc::now() - chrono::Duration::minutes(5);
+        observe_alert_timing("triage_completed_at", received_at, Utc::now());
+        observe_alert_timing("resolved_at", received_at, Utc::now());
+        WORKFLOW_STEP_RETRIES_TOTAL.inc();
+        AGENT_TOOL_RECOVERY_PROMPTS_TOTAL.inc();
+        LLM_API_ERRORS_TOTAL.with_label_values(&["anthropic", "http"]).inc();
+
+        let output = gather_metrics();
+        for name in [
+            "alert_triage_duration_seconds",
+            "alert_resolution_duration_seconds",
+            "workflow_step_retries_total",
+            "agent_tool_recovery_prompts_total",
+            "llm_api_errors_total",
+        ] {
+            assert!(output.contains(name), "{} missing from:\n{}", name, output);
+        }
+        assert!(output.contains(r#"llm_api_errors_total{error_type="http",provider="anthropic"}"#));
+    }
+}
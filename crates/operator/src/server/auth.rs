@@ -0,0 +1,227 @@
+//! Bearer token authentication against the Kubernetes `TokenReview` API and
+//! role-based authorization of API requests
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use k8s_openapi::api::authentication::v1::{TokenReview, TokenReviewSpec, UserInfo};
+use kube::api::{Api, PostParams};
+use moka::future::Cache;
+use sha2::{Digest, Sha256};
+use tracing::{debug, error};
+
+use crate::config::AuthConfig;
+use crate::store::audit::with_actor;
+
+use super::Server;
+
+/// How long a token accepted by a TokenReview is trusted without asking again
+const TOKEN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Operator roles, each including the permissions of the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Read-only access
+    Viewer,
+    /// Can also create alerts
+    Editor,
+    /// Full access, including /admin
+    Admin,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Editor => write!(f, "editor"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl Role {
+    /// Role needed to make a request
+    pub fn required_for(method: &Method, path: &str) -> Self {
+        if path.starts_with("/admin/") {
+            Role::Admin
+        } else if method == Method::GET || method == Method::HEAD {
+            Role::Viewer
+        } else if method == Method::POST && (path == "/alerts" || path.starts_with("/webhook/")) {
+            Role::Editor
+        } else {
+            Role::Admin
+        }
+    }
+
+    /// Highest role granted to a Kubernetes user
+    pub fn granted(config: &AuthConfig, user: &UserInfo) -> Option<Self> {
+        let username = user.username.as_deref().unwrap_or_default();
+        let groups = user.groups.as_deref().unwrap_or_default();
+        if config.admins.matches(username, groups) {
+            Some(Role::Admin)
+        } else if config.editors.matches(username, groups) {
+            Some(Role::Editor)
+        } else if config.viewers.matches(username, groups) {
+            Some(Role::Viewer)
+        } else {
+            None
+        }
+    }
+}
+
+/// Users of recently reviewed tokens, keyed by the token's SHA-256 so the
+/// tokens themselves aren't kept in memory
+#[derive(Clone)]
+pub struct TokenCache {
+    inner: Cache<String, UserInfo>,
+}
+
+impl TokenCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+
+    /// The user `token` authenticates as, asking the API server unless it
+    /// was accepted within the TTL. `None` if the token is invalid.
+    pub async fn authenticate(&self, client: &kube::Client, token: &str) -> kube::Result<Option<UserInfo>> {
+        let key = hex::encode(Sha256::digest(token.as_bytes()));
+        if let Some(user) = self.inner.get(&key).await {
+            return Ok(Some(user));
+        }
+
+        let review = TokenReview {
+            spec: TokenReviewSpec {
+                token: Some(token.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let review = Api::<TokenReview>::all(client.clone())
+            .create(&PostParams::default(), &review)
+            .await?;
+
+        let Some(status) = review.status.filter(|s| s.authenticated == Some(true)) else {
+            return Ok(None);
+        };
+        let user = status.user.unwrap_or_default();
+        self.inner.insert(key, user.clone()).await;
+        Ok(Some(user))
+    }
+}
+
+impl Default for TokenCache {
+    fn default() -> Self {
+        Self::new(TOKEN_CACHE_TTL)
+    }
+}
+
+/// Paths that stay open for kubelet probes and Prometheus scrapes, plus the
+/// UI's static assets
+fn is_public(path: &str) -> bool {
+    path == "/health" || path == "/metrics" || path == "/ui" || path.starts_with("/ui/")
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|token| !token.is_empty())
+}
+
+/// Reject requests without a valid Kubernetes token (401) or whose user lacks
+/// the role the request needs (403), when `auth.enabled` is set
+pub async fn require_role(
+    State(server): State<Arc<Server>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = server.config.read().await.auth.clone();
+    let path = request.uri().path();
+    if !config.enabled || is_public(path) {
+        return next.run(request).await;
+    }
+
+    let Some(token) = bearer_token(request.headers()) else {
+        return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "error": "Missing bearer token"
+        }))).into_response();
+    };
+    let Some(client) = &server.kube_client else {
+        error!("Authentication is enabled but no Kubernetes client is available to review tokens");
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Token review is not available"
+        }))).into_response();
+    };
+
+    let user = match server.token_cache.authenticate(client, token).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "error": "Invalid bearer token"
+        }))).into_response(),
+        Err(e) => {
+            error!("Failed to review bearer token: {}", e);
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+                "error": "Failed to review bearer token"
+            }))).into_response();
+        }
+    };
+
+    let required = Role::required_for(request.method(), path);
+    let username = user.username.clone().unwrap_or_default();
+    match Role::granted(&config, &user) {
+        Some(role) if role >= required => {
+            debug!("Authorized {} as {} for {} {}", username, role, request.method(), path);
+            with_actor(username, next.run(request)).await
+        }
+        _ => (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": format!("The {} role is required", required),
+            "user": username
+        }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RoleSubjects;
+
+    fn user(username: &str, groups: &[&str]) -> UserInfo {
+        UserInfo {
+            username: Some(username.to_string()),
+            groups: Some(groups.iter().map(|g| g.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roles() {
+        assert_eq!(Role::required_for(&Method::GET, "/workflows"), Role::Viewer);
+        assert_eq!(Role::required_for(&Method::POST, "/alerts"), Role::Editor);
+        assert_eq!(Role::required_for(&Method::POST, "/webhook/alertmanager"), Role::Editor);
+        assert_eq!(Role::required_for(&Method::POST, "/alerts/1/reprocess"), Role::Admin);
+        assert_eq!(Role::required_for(&Method::GET, "/admin/log-level"), Role::Admin);
+
+        let config = AuthConfig {
+            editors: RoleSubjects { users: vec!["ci".to_string()], groups: vec!["sre".to_string()] },
+            ..Default::default()
+        };
+        assert_eq!(Role::granted(&config, &user("jane", &["system:authenticated"])), Some(Role::Viewer));
+        assert_eq!(Role::granted(&config, &user("jane", &["system:authenticated", "sre"])), Some(Role::Editor));
+        assert_eq!(Role::granted(&config, &user("ci", &[])), Some(Role::Editor));
+        assert_eq!(Role::granted(&config, &user("root", &["sre", "system:masters"])), Some(Role::Admin));
+        assert_eq!(Role::granted(&config, &user("anonymous", &[])), None);
+        assert!(Role::Admin > Role::Editor && Role::Editor > Role::Viewer);
+    }
+}
@@ -1,39 +1,99 @@
+mod auth;
+mod openapi;
 mod routes;
 
 use axum::{
-    extract::State,
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, post, put},
     Router,
 };
 use std::sync::Arc;
+use tokio::sync::RwLock;
 use tower_http::{
     trace::TraceLayer,
     services::fs::ServeDir,
 };
 use tracing::info;
+use tracing_subscriber::{reload, EnvFilter, Registry};
 
 use crate::{
     config::Config,
+    controllers::LeaderElection,
     sources::WebhookHandler,
     store::Store,
+    workflow::WorkflowEngine,
     // Removed old imports: AlertRecord, TaskRecord, TaskStatus
 };
 
+/// Handle used to swap the active log filter at runtime
+pub type LogReloadHandle = reload::Handle<EnvFilter, Registry>;
+
 pub struct Server {
     store: Arc<dyn Store>,
     pub webhook_handler: Arc<WebhookHandler>,
+    config: Arc<RwLock<Config>>,
+    max_payload_bytes: usize,
+    log_reload_handle: Option<LogReloadHandle>,
+    workflow_engine: Option<Arc<WorkflowEngine>>,
+    leader_election: Option<Arc<LeaderElection>>,
+    kube_client: Option<kube::Client>,
+    token_cache: auth::TokenCache,
 }
 
 impl Server {
     pub fn new(
-        _config: &Config, 
+        config: &Config, 
         store: Arc<dyn Store>,
         webhook_handler: Arc<WebhookHandler>,
     ) -> Self {
-        Self { store, webhook_handler }
+        Self {
+            store,
+            webhook_handler,
+            config: Arc::new(RwLock::new(config.clone())),
+            max_payload_bytes: config.server.max_payload_bytes,
+            log_reload_handle: None,
+            workflow_engine: None,
+            leader_election: None,
+            kube_client: None,
+            token_cache: auth::TokenCache::default(),
+        }
+    }
+
+    /// Share a configuration that can be hot-reloaded while the server runs
+    pub fn with_shared_config(mut self, config: Arc<RwLock<Config>>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Enable reprocessing alerts through /alerts/{id}/reprocess
+    pub fn with_workflow_engine(mut self, engine: Arc<WorkflowEngine>) -> Self {
+        self.workflow_engine = Some(engine);
+        self
+    }
+
+    /// Enable reading Workflow definitions for /workflows/{id}/graph and
+    /// reviewing bearer tokens when `auth.enabled` is set
+    pub fn with_client(mut self, client: kube::Client) -> Self {
+        self.kube_client = Some(client);
+        self
+    }
+
+    /// Only accept alerts and workflow changes while this replica is the
+    /// leader; followers answer them with 503 and the leader's identity
+    pub fn with_leader_election(mut self, leader_election: Arc<LeaderElection>) -> Self {
+        self.leader_election = Some(leader_election);
+        self
+    }
+
+    /// Enable runtime log level changes through /admin/log-level
+    pub fn with_log_reload_handle(mut self, handle: LogReloadHandle) -> Self {
+        self.log_reload_handle = Some(handle);
+        self
     }
 
     pub fn build_router(self) -> Router {
+        let max_payload_bytes = self.max_payload_bytes;
         let state = Arc::new(self);
 
         // Get static file path from environment variable or use defaults
@@ -52,25 +112,61 @@ impl Server {
 
         info!("Serving static files from: {}", static_path);
 
+        // Routes that change alerts or queue and cancel workflows. Admin
+        // endpoints act on the replica serving them and stay open.
+        let leader_only = middleware::from_fn_with_state(state.clone(), routes::require_leader);
+        let authorized = middleware::from_fn_with_state(state.clone(), auth::require_role);
+
         Router::new()
             .route("/", get(routes::root))
             .route("/health", get(routes::health))
+            .route("/health/db/details", get(routes::db_health_details))
             // Alert endpoints
-            .route("/alerts", post(routes::create_alert))
+            .route("/alerts", post(routes::create_alert).route_layer(leader_only.clone()))
             .route("/alerts", get(routes::list_alerts))
             .route("/alerts/{id}", get(routes::get_alert))
+            .route("/alerts/{id}/reprocess", post(routes::reprocess_alert).route_layer(leader_only.clone()))
+            .route("/alerts/{id}/annotate", post(routes::annotate_alert).route_layer(leader_only.clone()))
+            .route("/alerts/{id}/workflow-diff", get(routes::get_alert_workflow_diff))
             // Workflow endpoints
             .route("/workflows", get(routes::list_workflows))
+            .route("/workflows/bulk-cancel", post(routes::bulk_cancel_workflows).route_layer(leader_only.clone()))
             .route("/workflows/{id}", get(routes::get_workflow))
             .route("/workflows/{id}/steps", get(routes::list_workflow_steps))
+            .route("/workflows/{id}/tool-invocations", get(routes::list_workflow_tool_invocations))
+            .route("/workflows/{id}/graph", get(routes::get_workflow_graph))
+            .route("/workflows/{id}/diff", get(routes::get_workflow_diff))
+            .route("/workflows/{id}/report", get(routes::get_workflow_report))
             .route("/workflows/{id}/outputs", get(routes::list_workflow_outputs))
+            // Completions are only broadcast by the leader's workflow engine
+            .route("/ws/workflows", get(routes::workflow_completions_ws).route_layer(leader_only.clone()))
+            // Statistics endpoints
+            .route("/statistics/workflows/timeseries", get(routes::workflow_timeseries))
+            .route("/statistics/sinks", get(routes::sink_statistics))
+            // Sink output endpoints
+            .route("/sink-outputs/failures", get(routes::list_sink_failures))
             // Source event endpoints
             .route("/source-events", get(routes::list_source_events))
             // Webhook and metrics
-            .route("/webhook/{*path}", post(routes::webhook_alerts))
+            .route(
+                "/webhook/{*path}",
+                post(routes::webhook_alerts)
+                    .layer(DefaultBodyLimit::max(max_payload_bytes))
+                    .route_layer(leader_only),
+            )
             .route("/metrics", get(routes::metrics))
+            // Admin endpoints
+            .route("/admin/log-level", get(routes::get_log_level))
+            .route("/admin/log-level", put(routes::set_log_level))
+            .route("/admin/backup", post(routes::backup_database))
+            .route("/admin/audit-log", get(routes::list_audit_log))
+            // API description
+            .route("/openapi.json", get(routes::openapi_json))
+            .route("/openapi.yaml", get(routes::openapi_yaml))
+            .route("/api-docs", get(routes::api_docs))
             // Serve UI at /ui and /ui/* 
             .nest_service("/ui", ServeDir::new(static_path))
+            .layer(authorized)
             .layer(TraceLayer::new_for_http())
             .with_state(state)
     }
@@ -0,0 +1,267 @@
+//! OpenAPI description of the HTTP API
+//!
+//! Operation summaries come from the endpoint list served at `/`; request,
+//! query and response schemas are generated from the route types with
+//! `schemars`. Served at `/openapi.json` and `/openapi.yaml`, and browsable
+//! through Swagger UI at `/api-docs`. The Swagger UI assets are served with
+//! the rest of the UI from `static/swagger-ui` (see `just swagger-ui`), so
+//! the page works without internet access.
+
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{Schema, SchemaObject},
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+
+use super::routes::{self, EndpointInfo};
+use crate::{
+    sources::webhook::AlertManagerWebhook,
+    store::{Alert, AlertNote, AuditEntry, BulkCancelResult, SinkOutput, SinkTypeStats, SourceEventWithWorkflow, StoreHealth, ToolInvocation, Workflow, WorkflowBucket, WorkflowWithAllData},
+    workflow::{PatchOperation, WorkflowCompletionEvent, WorkflowDiff},
+};
+
+/// Swagger UI page rendering `/openapi.json`
+pub const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Punching Fist Operator API</title>
+  <link rel="stylesheet" href="/ui/swagger-ui/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="/ui/swagger-ui/swagger-ui-bundle.js"></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// The typed parts of one operation; its summary comes from the endpoint list
+struct Operation {
+    method: &'static str,
+    path: &'static str,
+    status: u16,
+    /// Object schema whose properties are the query parameters
+    query: Option<SchemaObject>,
+    request: Option<Schema>,
+    response: Response,
+    admin: bool,
+}
+
+enum Response {
+    Json(Schema),
+    Text(&'static str),
+}
+
+impl Operation {
+    fn new(method: &'static str, path: &'static str) -> Self {
+        Self { method, path, status: 200, query: None, request: None, response: Response::Json(Schema::Bool(true)), admin: false }
+    }
+
+    fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    fn query<T: JsonSchema>(mut self, gen: &mut SchemaGenerator) -> Self {
+        self.query = Some(T::json_schema(gen).into_object());
+        self
+    }
+
+    fn body<T: JsonSchema>(mut self, gen: &mut SchemaGenerator) -> Self {
+        self.request = Some(gen.subschema_for::<T>());
+        self
+    }
+
+    fn returns<T: JsonSchema>(mut self, gen: &mut SchemaGenerator) -> Self {
+        self.response = Response::Json(gen.subschema_for::<T>());
+        self
+    }
+
+    fn returns_text(mut self, content_type: &'static str) -> Self {
+        self.response = Response::Text(content_type);
+        self
+    }
+
+    fn admin(mut self) -> Self {
+        self.admin = true;
+        self
+    }
+}
+
+fn operations(gen: &mut SchemaGenerator) -> Vec<Operation> {
+    vec![
+        Operation::new("GET", "/health").returns::<routes::HealthResponse>(gen),
+        Operation::new("GET", "/health/db/details").returns::<StoreHealth>(gen),
+        // Filtered by namespace and deployment, the page holds that
+        // deployment's latest alerts and the offset is ignored
+        Operation::new("GET", "/alerts").query::<routes::AlertListQuery>(gen).returns::<routes::Page<Alert>>(gen),
+        Operation::new("POST", "/alerts").status(201)
+            .body::<routes::CreateAlertPayload>(gen)
+            .returns::<routes::CreateAlertResponse>(gen),
+        Operation::new("GET", "/alerts/{id}").returns::<routes::DetailedAlert>(gen),
+        Operation::new("POST", "/alerts/{id}/reprocess").status(202),
+        Operation::new("POST", "/alerts/{id}/annotate").status(201)
+            .body::<routes::AnnotateAlertPayload>(gen)
+            .returns::<AlertNote>(gen),
+        Operation::new("GET", "/alerts/{id}/workflow-diff")
+            .query::<routes::AlertWorkflowDiffQuery>(gen)
+            .returns::<Vec<PatchOperation>>(gen),
+        Operation::new("GET", "/workflows").query::<routes::WorkflowListQuery>(gen).returns::<routes::Page<Workflow>>(gen),
+        Operation::new("POST", "/workflows/bulk-cancel")
+            .body::<routes::BulkCancelPayload>(gen)
+            .returns::<BulkCancelResult>(gen),
+        Operation::new("GET", "/workflows/{id}").returns::<Workflow>(gen),
+        Operation::new("GET", "/workflows/{id}/steps").returns::<Vec<routes::TimedWorkflowStep>>(gen),
+        Operation::new("GET", "/workflows/{id}/tool-invocations").returns::<Vec<ToolInvocation>>(gen),
+        Operation::new("GET", "/workflows/{id}/graph").returns::<routes::WorkflowGraph>(gen),
+        Operation::new("GET", "/workflows/{id}/diff").query::<routes::WorkflowDiffQuery>(gen).returns::<WorkflowDiff>(gen),
+        Operation::new("GET", "/workflows/{id}/report").returns::<WorkflowWithAllData>(gen),
+        Operation::new("GET", "/workflows/{id}/outputs").returns::<Vec<SinkOutput>>(gen),
+        // Switches protocols; each WebSocket message is one completion event
+        Operation::new("GET", "/ws/workflows").status(101).returns::<WorkflowCompletionEvent>(gen),
+        Operation::new("GET", "/statistics/workflows/timeseries")
+            .query::<routes::TimeseriesQuery>(gen)
+            .returns::<Vec<WorkflowBucket>>(gen),
+        Operation::new("GET", "/statistics/sinks").returns::<Vec<SinkTypeStats>>(gen),
+        Operation::new("GET", "/sink-outputs/failures").query::<routes::SinkFailureQuery>(gen).returns::<Vec<SinkOutput>>(gen),
+        Operation::new("GET", "/source-events").query::<routes::SourceEventQuery>(gen).returns::<Vec<SourceEventWithWorkflow>>(gen),
+        // Grafana payloads are accepted instead on sources with `receiver: grafana`
+        Operation::new("POST", "/webhook/{path}").body::<AlertManagerWebhook>(gen).returns_text("text/plain"),
+        Operation::new("GET", "/metrics").returns_text("text/plain"),
+        Operation::new("GET", "/admin/log-level").admin().returns::<routes::LogLevelPayload>(gen),
+        Operation::new("PUT", "/admin/log-level").admin()
+            .body::<routes::LogLevelPayload>(gen)
+            .returns::<routes::LogLevelPayload>(gen),
+        Operation::new("POST", "/admin/backup").admin(),
+        Operation::new("GET", "/admin/audit-log").admin()
+            .query::<routes::AuditLogQuery>(gen)
+            .returns::<Vec<AuditEntry>>(gen),
+        Operation::new("GET", "/openapi.json"),
+        Operation::new("GET", "/openapi.yaml").returns_text("application/yaml"),
+        Operation::new("GET", "/api-docs").returns_text("text/html"),
+    ]
+}
+
+/// Build the OpenAPI 3.1 document for every endpoint except the static UI
+pub fn spec() -> Value {
+    let mut gen = SchemaSettings::draft07()
+        .with(|settings| {
+            settings.definitions_path = "#/components/schemas/".to_string();
+            settings.meta_schema = None;
+        })
+        .into_generator();
+    let operations = operations(&mut gen);
+    let endpoints = routes::endpoints();
+
+    let mut paths = Map::new();
+    for operation in &operations {
+        let summary = endpoints.iter()
+            .find(|e| e.method == operation.method && e.path == operation.path)
+            .map(|e: &EndpointInfo| e.description.clone())
+            .unwrap_or_default();
+        let item = paths.entry(operation.path)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("path items are objects");
+        item.insert(operation.method.to_lowercase(), operation_object(operation, summary));
+    }
+
+    let mut schemas = Map::new();
+    for (name, schema) in gen.take_definitions() {
+        schemas.insert(name, serde_json::to_value(schema).unwrap_or_default());
+    }
+    schemas.insert("ErrorResponse".to_string(), json!({
+        "type": "object",
+        "required": ["error"],
+        "properties": { "error": { "type": "string" } }
+    }));
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Punching Fist Operator API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Alerts, workflow executions and sink outputs tracked by the punching-fist operator"
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "adminToken": { "type": "http", "scheme": "bearer" }
+            }
+        }
+    })
+}
+
+fn operation_object(operation: &Operation, summary: String) -> Value {
+    let mut parameters: Vec<Value> = path_parameters(operation.path)
+        .map(|name| json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": if name == "id" { json!({ "type": "string", "format": "uuid" }) } else { json!({ "type": "string" }) }
+        }))
+        .collect();
+    if let Some(query) = &operation.query {
+        if let Some(object) = &query.object {
+            for (name, schema) in &object.properties {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "query",
+                    "required": object.required.contains(name),
+                    "schema": schema,
+                }));
+            }
+        }
+    }
+
+    let content = match &operation.response {
+        Response::Json(schema) => json!({ "application/json": { "schema": schema } }),
+        Response::Text(content_type) => json!({ *content_type: { "schema": { "type": "string" } } }),
+    };
+    let mut responses = Map::new();
+    responses.insert(operation.status.to_string(), json!({ "description": summary, "content": content }));
+    responses.insert("default".to_string(), json!({
+        "description": "Error",
+        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/ErrorResponse" } } }
+    }));
+
+    let mut object = json!({
+        "operationId": operation_id(operation.method, operation.path),
+        "summary": summary,
+        "parameters": parameters,
+        "responses": responses,
+    });
+    if let Some(request) = &operation.request {
+        object["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": request } }
+        });
+    }
+    if operation.admin {
+        object["security"] = json!([{ "adminToken": [] }]);
+    }
+    object
+}
+
+/// Names of the `{param}` segments of a path template
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+}
+
+/// e.g. `GET /workflows/{id}/steps` becomes `get_workflows_id_steps`
+fn operation_id(method: &str, path: &str) -> String {
+    let mut id = method.to_lowercase();
+    for part in path.split(|c: char| !c.is_ascii_alphanumeric()).filter(|p| !p.is_empty()) {
+        id.push('_');
+        id.push_str(part);
+    }
+    id
+}
@@ -1,30 +1,40 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{rejection::JsonRejection, Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::sync::Arc;
 use std::collections::HashMap;
 use uuid::Uuid;
 use tracing::{info, error};
 use chrono::Utc;
+use tracing_subscriber::EnvFilter;
 
 use crate::{
+    crd::{StepType as CRDStepType, Workflow as CRDWorkflow, WorkflowStep as CRDWorkflowStep},
     server::Server,
-    sources::webhook::AlertManagerWebhook,
+    workflow::{diff_json, StepGraph, WorkflowContext},
+    sinks::websocket::{self, WebSocketSink},
+    crd::source::WebhookReceiver,
+    sources::{GrafanaReceiver, PrometheusReceiver},
     metrics::{gather_metrics, PROCESSED_ALERTS_TOTAL},
-    store::models::{Alert, AlertStatus, AlertSeverity},
+    store::{
+        models::{Alert, AlertNote, AlertStatus, AlertSeverity, StepStatus, Workflow, WorkflowStatus, WorkflowStep},
+        backup_to_dir, DeduplicationResult,
+    },
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct HealthResponse {
     status: String,
     version: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct RootResponse {
     service: String,
     version: String,
@@ -32,11 +42,11 @@ pub struct RootResponse {
     endpoints: Vec<EndpointInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct EndpointInfo {
-    path: String,
-    method: String,
-    description: String,
+    pub(super) path: String,
+    pub(super) method: String,
+    pub(super) description: String,
 }
 
 pub async fn root() -> impl IntoResponse {
@@ -44,85 +54,244 @@ pub async fn root() -> impl IntoResponse {
         service: "punching-fist-operator".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         ui_url: "/ui/".to_string(),
-        endpoints: vec![
-            EndpointInfo {
-                path: "/health".to_string(),
-                method: "GET".to_string(),
-                description: "Health check endpoint".to_string(),
-            },
-            EndpointInfo {
-                path: "/alerts".to_string(),
-                method: "GET".to_string(),
-                description: "List alerts with pagination".to_string(),
-            },
-            EndpointInfo {
-                path: "/alerts".to_string(),
-                method: "POST".to_string(),
-                description: "Create a new alert".to_string(),
-            },
-            EndpointInfo {
-                path: "/alerts/{id}".to_string(),
-                method: "GET".to_string(),
-                description: "Get a specific alert by ID".to_string(),
-            },
-            EndpointInfo {
-                path: "/workflows".to_string(),
-                method: "GET".to_string(),
-                description: "List workflows with pagination".to_string(),
-            },
-            EndpointInfo {
-                path: "/workflows/{id}".to_string(),
-                method: "GET".to_string(),
-                description: "Get a specific workflow by ID".to_string(),
-            },
-            EndpointInfo {
-                path: "/workflows/{id}/steps".to_string(),
-                method: "GET".to_string(),
-                description: "List steps for a workflow".to_string(),
-            },
-            EndpointInfo {
-                path: "/workflows/{id}/outputs".to_string(),
-                method: "GET".to_string(),
-                description: "List sink outputs for a workflow".to_string(),
-            },
-            EndpointInfo {
-                path: "/source-events".to_string(),
-                method: "GET".to_string(),
-                description: "List source events (requires source_name query param)".to_string(),
-            },
-            EndpointInfo {
-                path: "/webhook/{path}".to_string(),
-                method: "POST".to_string(),
-                description: "Webhook endpoint for AlertManager".to_string(),
-            },
-            EndpointInfo {
-                path: "/metrics".to_string(),
-                method: "GET".to_string(),
-                description: "Prometheus metrics endpoint".to_string(),
-            },
-            EndpointInfo {
-                path: "/ui".to_string(),
-                method: "GET".to_string(),
-                description: "Web UI Dashboard - view operator state".to_string(),
-            },
-        ],
+        endpoints: endpoints(),
     })
 }
 
-pub async fn health() -> impl IntoResponse {
-    Json(HealthResponse {
-        status: "healthy".to_string(),
+/// Every endpoint served by the router, as listed by `/` and documented in
+/// the OpenAPI spec
+pub(super) fn endpoints() -> Vec<EndpointInfo> {
+    vec![
+        EndpointInfo {
+            path: "/health".to_string(),
+            method: "GET".to_string(),
+            description: "Health check; 503 when the workflow engine stops heartbeating".to_string(),
+        },
+        EndpointInfo {
+            path: "/health/db/details".to_string(),
+            method: "GET".to_string(),
+            description: "Database connectivity with per-table row counts and sizes".to_string(),
+        },
+        EndpointInfo {
+            path: "/alerts".to_string(),
+            method: "GET".to_string(),
+            description: "List alerts with pagination (since and until params bound created_at; filter by deployment with namespace and deployment params)".to_string(),
+        },
+        EndpointInfo {
+            path: "/alerts".to_string(),
+            method: "POST".to_string(),
+            description: "Create a new alert".to_string(),
+        },
+        EndpointInfo {
+            path: "/alerts/{id}".to_string(),
+            method: "GET".to_string(),
+            description: "Get a specific alert by ID, with its related workflows".to_string(),
+        },
+        EndpointInfo {
+            path: "/alerts/{id}/reprocess".to_string(),
+            method: "POST".to_string(),
+            description: "Re-trigger the workflow for an alert stuck in Received or Triaging".to_string(),
+        },
+        EndpointInfo {
+            path: "/alerts/{id}/annotate".to_string(),
+            method: "POST".to_string(),
+            description: "Add an investigation note to an alert".to_string(),
+        },
+        EndpointInfo {
+            path: "/alerts/{id}/workflow-diff".to_string(),
+            method: "GET".to_string(),
+            description: "JSON Patch between the step outputs of two workflow runs for an alert".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows".to_string(),
+            method: "GET".to_string(),
+            description: "List workflows with pagination (optional status, since and until query params)".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/bulk-cancel".to_string(),
+            method: "POST".to_string(),
+            description: "Cancel up to 100 pending or running workflows at once".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}".to_string(),
+            method: "GET".to_string(),
+            description: "Get a specific workflow by ID".to_string(),
+        },
+        EndpointInfo {
+            path: "/ws/workflows".to_string(),
+            method: "GET".to_string(),
+            description: "WebSocket streaming a JSON event for every workflow that completes".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}/steps".to_string(),
+            method: "GET".to_string(),
+            description: "List steps for a workflow".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}/tool-invocations".to_string(),
+            method: "GET".to_string(),
+            description: "List the tool calls agent steps made for a workflow".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}/graph".to_string(),
+            method: "GET".to_string(),
+            description: "Get the step graph for a workflow (vis.js compatible)".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}/diff".to_string(),
+            method: "GET".to_string(),
+            description: "Diff a workflow's outputs against another run (requires other_id query param)".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}/report".to_string(),
+            method: "GET".to_string(),
+            description: "Get a workflow with its steps and sink outputs".to_string(),
+        },
+        EndpointInfo {
+            path: "/workflows/{id}/outputs".to_string(),
+            method: "GET".to_string(),
+            description: "List sink outputs for a workflow".to_string(),
+        },
+        EndpointInfo {
+            path: "/statistics/workflows/timeseries".to_string(),
+            method: "GET".to_string(),
+            description: "Workflow completions per time bucket (from, to, bucket query params)".to_string(),
+        },
+        EndpointInfo {
+            path: "/statistics/sinks".to_string(),
+            method: "GET".to_string(),
+            description: "Sink delivery counts and average latency per sink type".to_string(),
+        },
+        EndpointInfo {
+            path: "/sink-outputs/failures".to_string(),
+            method: "GET".to_string(),
+            description: "Recently failed sink outputs (optional since and limit query params)".to_string(),
+        },
+        EndpointInfo {
+            path: "/source-events".to_string(),
+            method: "GET".to_string(),
+            description: "List source events with the status of the workflow each queued (requires source_name query param)".to_string(),
+        },
+        EndpointInfo {
+            path: "/webhook/{path}".to_string(),
+            method: "POST".to_string(),
+            description: "Webhook endpoint for AlertManager".to_string(),
+        },
+        EndpointInfo {
+            path: "/metrics".to_string(),
+            method: "GET".to_string(),
+            description: "Prometheus metrics endpoint".to_string(),
+        },
+        EndpointInfo {
+            path: "/admin/log-level".to_string(),
+            method: "GET".to_string(),
+            description: "Get the active log filter (requires admin token)".to_string(),
+        },
+        EndpointInfo {
+            path: "/admin/log-level".to_string(),
+            method: "PUT".to_string(),
+            description: "Change the active log filter at runtime (requires admin token)".to_string(),
+        },
+        EndpointInfo {
+            path: "/admin/backup".to_string(),
+            method: "POST".to_string(),
+            description: "Back up the database to the backup directory (requires admin token)".to_string(),
+        },
+        EndpointInfo {
+            path: "/admin/audit-log".to_string(),
+            method: "GET".to_string(),
+            description: "List recorded changes, optionally to one entity (requires admin token)".to_string(),
+        },
+        EndpointInfo {
+            path: "/openapi.json".to_string(),
+            method: "GET".to_string(),
+            description: "OpenAPI specification of this API in JSON".to_string(),
+        },
+        EndpointInfo {
+            path: "/openapi.yaml".to_string(),
+            method: "GET".to_string(),
+            description: "OpenAPI specification of this API in YAML".to_string(),
+        },
+        EndpointInfo {
+            path: "/api-docs".to_string(),
+            method: "GET".to_string(),
+            description: "Swagger UI for browsing the API".to_string(),
+        },
+        EndpointInfo {
+            path: "/ui".to_string(),
+            method: "GET".to_string(),
+            description: "Web UI Dashboard - view operator state".to_string(),
+        },
+    ]
+}
+
+pub async fn health(
+    State(server): State<Arc<Server>>,
+) -> impl IntoResponse {
+    // A workflow engine that stopped heartbeating is deadlocked or gone
+    let engine_alive = server.workflow_engine.as_ref().is_none_or(|engine| engine.is_alive());
+    let (status_code, status) = if engine_alive {
+        (StatusCode::OK, "healthy")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    };
+    (status_code, Json(HealthResponse {
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-    })
+    }))
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ListQuery {
+pub async fn db_health_details(
+    State(server): State<Arc<Server>>,
+) -> impl IntoResponse {
+    match server.store.health_details().await {
+        Ok(health) if health.connected => (StatusCode::OK, Json(health)).into_response(),
+        Ok(health) => (StatusCode::SERVICE_UNAVAILABLE, Json(health)).into_response(),
+        Err(e) => {
+            error!("Failed to get database health details: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get database health details: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WorkflowListQuery {
     limit: Option<i64>,
     offset: Option<i64>,
+    status: Option<WorkflowStatus>,
+    /// Only workflows created at or after this time
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only workflows created before this time
+    until: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+/// One page of a list, with the number of items across all pages
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Page<T> {
+    items: Vec<T>,
+    total: i64,
+    limit: i64,
+    offset: i64,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AlertListQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    /// With `deployment`, only the latest alerts for that deployment, in a
+    /// single page whose total is the number returned
+    namespace: Option<String>,
+    /// Deployment to list alerts for; requires `namespace`
+    deployment: Option<String>,
+    /// Only alerts created at or after this time
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only alerts created before this time
+    until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CreateAlertPayload {
     external_id: Option<String>,
     alert_name: String,
@@ -135,7 +304,7 @@ pub struct CreateAlertPayload {
     ends_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct CreateAlertResponse {
     id: Uuid,
     message: String,
@@ -196,8 +365,8 @@ pub async fn create_alert(
         updated_at: now,
     };
 
-    match server.store.save_alert(new_alert).await {
-        Ok(_) => {
+    match server.store.get_or_create_alert_by_fingerprint(new_alert).await {
+        Ok(DeduplicationResult::New(_)) => {
             info!("Successfully created alert with id: {}", alert_id);
             (
                 StatusCode::CREATED,
@@ -207,6 +376,26 @@ pub async fn create_alert(
                 }),
             ).into_response()
         }
+        Ok(DeduplicationResult::Duplicate(existing)) => {
+            info!("Alert matches existing unresolved alert {}", existing.id);
+            (
+                StatusCode::OK,
+                Json(CreateAlertResponse {
+                    id: existing.id,
+                    message: "Alert already exists".to_string(),
+                }),
+            ).into_response()
+        }
+        Ok(DeduplicationResult::Updated(existing)) => {
+            info!("Alert updated the labels or annotations of existing alert {}", existing.id);
+            (
+                StatusCode::OK,
+                Json(CreateAlertResponse {
+                    id: existing.id,
+                    message: "Alert already exists; its labels and annotations were updated".to_string(),
+                }),
+            ).into_response()
+        }
         Err(e) => {
             error!("Failed to create alert: {}", e);
             (
@@ -220,6 +409,36 @@ pub async fn create_alert(
     }
 }
 
+/// An alert together with the workflows run for it and its investigation notes
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DetailedAlert {
+    #[serde(flatten)]
+    alert: Alert,
+    workflows: Vec<WorkflowSummary>,
+    notes: Vec<AlertNote>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WorkflowSummary {
+    id: Uuid,
+    status: WorkflowStatus,
+    started_at: chrono::DateTime<chrono::Utc>,
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    steps_completed: i32,
+}
+
+impl From<Workflow> for WorkflowSummary {
+    fn from(workflow: Workflow) -> Self {
+        Self {
+            id: workflow.id,
+            status: workflow.status,
+            started_at: workflow.started_at,
+            completed_at: workflow.completed_at,
+            steps_completed: workflow.steps_completed,
+        }
+    }
+}
+
 pub async fn get_alert(
     State(server): State<Arc<Server>>,
     Path(id): Path<Uuid>,
@@ -229,7 +448,23 @@ pub async fn get_alert(
     match server.store.get_alert(id).await {
         Ok(Some(alert)) => {
             info!("Found alert: {:?}", alert.id);
-            (StatusCode::OK, Json(alert)).into_response()
+            match server.store.list_workflows_for_alert(id).await {
+                Ok(workflows) => {
+                    let detailed = DetailedAlert {
+                        notes: alert.notes(),
+                        alert,
+                        workflows: workflows.into_iter().map(WorkflowSummary::from).collect(),
+                    };
+                    (StatusCode::OK, Json(detailed)).into_response()
+                }
+                Err(e) => {
+                    error!("Failed to list workflows for alert {}: {}", id, e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                        "error": format!("Failed to list workflows for alert: {}", e),
+                        "id": id
+                    }))).into_response()
+                }
+            }
         }
         Ok(None) => {
             info!("Alert with id {} not found", id);
@@ -248,24 +483,148 @@ pub async fn get_alert(
     }
 }
 
+/// Reject writes on follower replicas, whose workflow engine isn't running,
+/// pointing the caller at the leader instead
+pub async fn require_leader(
+    State(server): State<Arc<Server>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match &server.leader_election {
+        Some(election) if !election.is_leader() => (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "This replica is not the leader",
+            "leader": election.leader()
+        }))).into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+pub async fn reprocess_alert(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Received request to reprocess alert with id: {}", id);
+
+    let Some(engine) = &server.workflow_engine else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Workflow engine not available",
+            "id": id
+        }))).into_response();
+    };
+
+    match engine.reprocess_alert(id).await {
+        Ok(workflow_id) => (StatusCode::ACCEPTED, Json(serde_json::json!({
+            "id": id,
+            "workflow_id": workflow_id
+        }))).into_response(),
+        Err(crate::Error::NotFound(message)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": message,
+            "id": id
+        }))).into_response(),
+        Err(crate::Error::Validation(message)) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": message,
+            "id": id
+        }))).into_response(),
+        Err(e) => {
+            error!("Failed to reprocess alert: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to reprocess alert: {}", e),
+                "id": id
+            }))).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnnotateAlertPayload {
+    author: String,
+    text: String,
+}
+
+pub async fn annotate_alert(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AnnotateAlertPayload>,
+) -> impl IntoResponse {
+    info!("Received note from {} for alert {}", payload.author, id);
+
+    if payload.author.trim().is_empty() || payload.text.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Both author and text are required",
+            "id": id
+        }))).into_response();
+    }
+
+    let note = AlertNote {
+        author: payload.author,
+        timestamp: Utc::now(),
+        text: payload.text,
+    };
+
+    match server.store.append_alert_annotation(id, note.clone()).await {
+        Ok(()) => (StatusCode::CREATED, Json(note)).into_response(),
+        Err(crate::Error::NotFound(_)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Alert not found",
+            "id": id
+        }))).into_response(),
+        Err(e) => {
+            error!("Failed to annotate alert {}: {}", id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to annotate alert: {}", e),
+                "id": id
+            }))).into_response()
+        }
+    }
+}
+
 pub async fn list_alerts(
     State(server): State<Arc<Server>>,
-    Query(query): Query<ListQuery>,
+    Query(query): Query<AlertListQuery>,
 ) -> impl IntoResponse {
     let limit = query.limit.unwrap_or(20).min(100); // Cap at 100
     let offset = query.offset.unwrap_or(0);
     
-    info!("Received request to list alerts with limit: {}, offset: {}", limit, offset);
+    let failed = |e: crate::Error| {
+        error!("Failed to list alerts: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Failed to list alerts: {}", e)
+        }))).into_response()
+    };
 
-    match server.store.list_alerts(limit, offset).await {
-        Ok(alerts) => {
-            info!("Returning {} alerts", alerts.len());
-            (StatusCode::OK, Json(alerts)).into_response()
+    match (query.namespace.as_deref(), query.deployment.as_deref()) {
+        // The deployment's latest alerts, as a single page
+        (Some(namespace), Some(deployment)) => {
+            info!("Received request to list alerts for deployment {}/{} with limit: {}", namespace, deployment, limit);
+            match server.store.get_recent_alerts_for_deployment(namespace, deployment, limit).await {
+                Ok(items) => {
+                    info!("Returning {} alerts", items.len());
+                    let total = items.len() as i64;
+                    (StatusCode::OK, Json(Page { items, total, limit, offset: 0 })).into_response()
+                }
+                Err(e) => failed(e),
+            }
         }
-        Err(e) => {
-            error!("Failed to list alerts: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-                "error": format!("Failed to list alerts: {}", e)
+        (None, None) => {
+            info!(
+                "Received request to list alerts with limit: {}, offset: {}, since: {:?}, until: {:?}",
+                limit, offset, query.since, query.until
+            );
+            let page = match server.store.list_alerts(limit, offset, query.since, query.until).await {
+                Ok(items) => server.store.count_alerts(query.since, query.until).await
+                    .map(|total| Page { items, total, limit, offset }),
+                Err(e) => Err(e),
+            };
+            match page {
+                Ok(page) => {
+                    info!("Returning {} of {} alerts", page.items.len(), page.total);
+                    (StatusCode::OK, Json(page)).into_response()
+                }
+                Err(e) => failed(e),
+            }
+        }
+        _ => {
+            (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": "Both namespace and deployment are required to filter alerts by deployment"
             }))).into_response()
         }
     }
@@ -274,32 +633,57 @@ pub async fn list_alerts(
 pub async fn webhook_alerts(
     State(server): State<Arc<Server>>,
     Path(path): Path<String>,
-    Json(payload): Json<AlertManagerWebhook>,
+    payload: Result<Json<serde_json::Value>, JsonRejection>,
 ) -> impl IntoResponse {
-    info!("Received AlertManager webhook on path: /{}", path);
-    PROCESSED_ALERTS_TOTAL.inc();
+    info!("Received webhook on path: /{}", path);
+
+    let payload = match payload {
+        Ok(Json(payload)) => payload,
+        Err(rejection) => {
+            error!("Rejected webhook payload on path /{}: {}", path, rejection.body_text());
+            return (rejection.status(), Json(serde_json::json!({
+                "error": format!("Invalid webhook payload: {}", rejection.body_text())
+            }))).into_response();
+        }
+    };
 
     // Reconstruct the full path that was used during registration
     let full_path = format!("/webhook/{}", path);
     
-    // Get webhook configuration for this path
-    let webhook_config = match server.webhook_handler.get_webhook_config(&full_path).await {
-        Some(config) => config,
-        None => {
-            error!("No webhook configured for path: {}", full_path);
-            return (StatusCode::NOT_FOUND, "Webhook path not configured");
+    // Get webhook configuration for this path; payloads posted to unknown
+    // paths are still validated as AlertManager payloads first
+    let webhook_config = server.webhook_handler.get_webhook_config(&full_path).await;
+    let receiver = webhook_config.as_ref().map(|config| config.receiver).unwrap_or_default();
+    let payload = match receiver {
+        WebhookReceiver::Prometheus => PrometheusReceiver::parse(payload),
+        WebhookReceiver::Grafana => GrafanaReceiver::parse(payload),
+    };
+    let payload = match payload {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Webhook payload failed validation on path /{}: {}", path, e);
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+                "error": format!("Invalid webhook payload: {}", e)
+            }))).into_response();
         }
     };
 
+    PROCESSED_ALERTS_TOTAL.inc();
+
+    let Some(webhook_config) = webhook_config else {
+        error!("No webhook configured for path: {}", full_path);
+        return (StatusCode::NOT_FOUND, "Webhook path not configured").into_response();
+    };
+
     // Process the webhook
     match server.webhook_handler.handle_alertmanager_webhook(&webhook_config, payload).await {
         Ok(alert_ids) => {
             info!("Successfully processed {} alerts", alert_ids.len());
-            (StatusCode::OK, "Alerts processed successfully")
+            (StatusCode::OK, "Alerts processed successfully").into_response()
         }
         Err(e) => {
             error!("Failed to process webhook: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to process alerts")
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to process alerts").into_response()
         }
     }
 }
@@ -308,20 +692,53 @@ pub async fn metrics() -> impl IntoResponse {
     gather_metrics()
 }
 
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(super::openapi::spec())
+}
+
+pub async fn openapi_yaml() -> impl IntoResponse {
+    match serde_yaml::to_string(&super::openapi::spec()) {
+        Ok(yaml) => ([(header::CONTENT_TYPE, "application/yaml")], yaml).into_response(),
+        Err(e) => {
+            error!("Failed to render OpenAPI spec as YAML: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to render OpenAPI spec: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+pub async fn api_docs() -> impl IntoResponse {
+    Html(super::openapi::SWAGGER_UI_HTML)
+}
+
 // Workflow endpoints
 pub async fn list_workflows(
     State(server): State<Arc<Server>>,
-    Query(query): Query<ListQuery>,
+    Query(query): Query<WorkflowListQuery>,
 ) -> impl IntoResponse {
     let limit = query.limit.unwrap_or(20).min(100);
     let offset = query.offset.unwrap_or(0);
     
-    info!("Listing workflows with limit: {}, offset: {}", limit, offset);
+    info!(
+        "Listing workflows with status: {:?}, limit: {}, offset: {}, since: {:?}, until: {:?}",
+        query.status, limit, offset, query.since, query.until
+    );
 
-    match server.store.list_workflows(limit, offset).await {
-        Ok(workflows) => {
-            info!("Returning {} workflows", workflows.len());
-            (StatusCode::OK, Json(workflows)).into_response()
+    let workflows = match query.status {
+        Some(status) => server.store.list_workflows_by_status(status, limit, offset, query.since, query.until).await,
+        None => server.store.list_workflows(limit, offset, query.since, query.until).await,
+    };
+    let page = match workflows {
+        Ok(items) => server.store.count_workflows(query.status, query.since, query.until).await
+            .map(|total| Page { items, total, limit, offset }),
+        Err(e) => Err(e),
+    };
+
+    match page {
+        Ok(page) => {
+            info!("Returning {} of {} workflows", page.items.len(), page.total);
+            (StatusCode::OK, Json(page)).into_response()
         }
         Err(e) => {
             error!("Failed to list workflows: {}", e);
@@ -332,6 +749,47 @@ pub async fn list_workflows(
     }
 }
 
+/// Most workflows a single bulk cancel request may name
+const MAX_BULK_CANCEL_WORKFLOWS: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BulkCancelPayload {
+    workflow_ids: Vec<Uuid>,
+    reason: Option<String>,
+}
+
+pub async fn bulk_cancel_workflows(
+    State(server): State<Arc<Server>>,
+    Json(payload): Json<BulkCancelPayload>,
+) -> impl IntoResponse {
+    let mut ids = payload.workflow_ids;
+    if ids.len() > MAX_BULK_CANCEL_WORKFLOWS {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("At most {} workflows can be cancelled at once, got {}", MAX_BULK_CANCEL_WORKFLOWS, ids.len())
+        }))).into_response();
+    }
+    let mut seen = std::collections::HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+
+    let reason = payload.reason.unwrap_or_else(|| "no reason given".to_string());
+    info!("Received request to cancel {} workflow(s): {}", ids.len(), reason);
+
+    let result = match server.store.bulk_cancel_workflows(&ids, &format!("Cancelled: {}", reason)).await {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Failed to cancel workflows: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to cancel workflows: {}", e)
+            }))).into_response();
+        }
+    };
+    if let Some(engine) = &server.workflow_engine {
+        engine.cancel_executions(&ids).await;
+    }
+
+    (StatusCode::OK, Json(result)).into_response()
+}
+
 pub async fn get_workflow(
     State(server): State<Arc<Server>>,
     Path(id): Path<Uuid>,
@@ -360,6 +818,28 @@ pub async fn get_workflow(
     }
 }
 
+/// A workflow step with how long it ran
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TimedWorkflowStep {
+    #[serde(flatten)]
+    step: WorkflowStep,
+    /// Seconds from the step starting until it completed, unset while it
+    /// hasn't finished
+    duration_seconds: Option<f64>,
+}
+
+impl From<WorkflowStep> for TimedWorkflowStep {
+    fn from(step: WorkflowStep) -> Self {
+        let duration_seconds = match (step.started_at, step.completed_at) {
+            (Some(started_at), Some(completed_at)) => {
+                Some((completed_at - started_at).num_milliseconds().max(0) as f64 / 1000.0)
+            }
+            _ => None,
+        };
+        Self { step, duration_seconds }
+    }
+}
+
 pub async fn list_workflow_steps(
     State(server): State<Arc<Server>>,
     Path(workflow_id): Path<Uuid>,
@@ -369,6 +849,7 @@ pub async fn list_workflow_steps(
     match server.store.list_workflow_steps(workflow_id).await {
         Ok(steps) => {
             info!("Returning {} steps for workflow {}", steps.len(), workflow_id);
+            let steps: Vec<TimedWorkflowStep> = steps.into_iter().map(TimedWorkflowStep::from).collect();
             (StatusCode::OK, Json(steps)).into_response()
         }
         Err(e) => {
@@ -381,6 +862,333 @@ pub async fn list_workflow_steps(
     }
 }
 
+pub async fn list_workflow_tool_invocations(
+    State(server): State<Arc<Server>>,
+    Path(workflow_id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Listing tool invocations for workflow: {}", workflow_id);
+
+    match server.store.list_tool_invocations_by_workflow(workflow_id).await {
+        Ok(invocations) => {
+            info!("Returning {} tool invocations for workflow {}", invocations.len(), workflow_id);
+            (StatusCode::OK, Json(invocations)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list tool invocations: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to list tool invocations: {}", e),
+                "workflow_id": workflow_id
+            }))).into_response()
+        }
+    }
+}
+
+/// Step graph for a workflow, shaped for a vis.js network
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WorkflowGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+/// A step of the workflow definition, with its status in this run. Steps
+/// that haven't started are pending and have no `step_id`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GraphNode {
+    id: String,
+    label: String,
+    name: String,
+    step_type: CRDStepType,
+    step_id: Option<Uuid>,
+    status: StepStatus,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// `from` must finish before `to` runs
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GraphEdge {
+    from: String,
+    to: String,
+}
+
+impl WorkflowGraph {
+    /// Build the graph of the defined steps, with the same dependencies the
+    /// engine runs them by, and overlay the status of the stored steps
+    fn build(definition: &[CRDWorkflowStep], steps: Vec<WorkflowStep>) -> crate::Result<Self> {
+        let graph = StepGraph::new(definition)?;
+        let mut stored: HashMap<String, WorkflowStep> = steps.into_iter()
+            .map(|step| (step.name.clone(), step))
+            .collect();
+
+        let edges = (0..definition.len())
+            .flat_map(|idx| graph.dependencies(idx).iter().map(move |&dependency| GraphEdge {
+                from: definition[dependency].name.clone(),
+                to: definition[idx].name.clone(),
+            }))
+            .collect();
+
+        let nodes = definition.iter()
+            .map(|step| {
+                let stored = stored.remove(&step.name);
+                GraphNode {
+                    id: step.name.clone(),
+                    label: step.name.clone(),
+                    name: step.name.clone(),
+                    step_type: step.step_type.clone(),
+                    step_id: stored.as_ref().map(|s| s.id),
+                    status: stored.as_ref().map(|s| s.status).unwrap_or(StepStatus::Pending),
+                    started_at: stored.as_ref().and_then(|s| s.started_at),
+                    completed_at: stored.as_ref().and_then(|s| s.completed_at),
+                }
+            })
+            .collect();
+
+        Ok(Self { nodes, edges })
+    }
+}
+
+pub async fn get_workflow_graph(
+    State(server): State<Arc<Server>>,
+    Path(workflow_id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Building step graph for workflow: {}", workflow_id);
+
+    let workflow = match server.store.get_workflow(workflow_id).await {
+        Ok(Some(workflow)) => workflow,
+        Ok(None) => {
+            info!("Workflow with id {} not found", workflow_id);
+            return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Workflow not found",
+                "id": workflow_id
+            }))).into_response();
+        }
+        Err(e) => {
+            error!("Failed to get workflow: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get workflow: {}", e),
+                "id": workflow_id
+            }))).into_response();
+        }
+    };
+
+    // The graph comes from the step definitions the run was started with;
+    // the stored steps only exist for steps that have started
+    let stored_definition = workflow.input_context.as_ref()
+        .and_then(|context| context.get("steps"))
+        .and_then(|steps| serde_json::from_value::<Vec<CRDWorkflowStep>>(steps.clone()).ok());
+    let definition = match stored_definition {
+        Some(definition) => definition,
+        None => match current_workflow_steps(&server, &workflow).await {
+            Ok(definition) => definition,
+            Err(response) => return response,
+        },
+    };
+
+    let steps = match server.store.list_workflow_steps(workflow_id).await {
+        Ok(steps) => steps,
+        Err(e) => {
+            error!("Failed to list workflow steps: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to list workflow steps: {}", e),
+                "id": workflow_id
+            }))).into_response();
+        }
+    };
+
+    match WorkflowGraph::build(&definition, steps) {
+        Ok(graph) => {
+            info!("Returning graph with {} nodes for workflow {}", graph.nodes.len(), workflow_id);
+            (StatusCode::OK, Json(graph)).into_response()
+        }
+        Err(e) => {
+            error!("Invalid steps in workflow definition {}/{}: {}", workflow.namespace, workflow.name, e);
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+                "error": e.to_string(),
+                "id": workflow_id
+            }))).into_response()
+        }
+    }
+}
+
+/// Steps of the Workflow resource a run was started from, for runs stored
+/// before their step definitions were kept with them
+async fn current_workflow_steps(server: &Server, workflow: &Workflow) -> std::result::Result<Vec<CRDWorkflowStep>, Response> {
+    let Some(client) = &server.kube_client else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Kubernetes client not available",
+            "id": workflow.id
+        }))).into_response());
+    };
+
+    let api: kube::Api<CRDWorkflow> = kube::Api::namespaced(client.clone(), &workflow.namespace);
+    match api.get_opt(&workflow.name).await {
+        Ok(Some(definition)) => Ok(definition.spec.steps),
+        Ok(None) => {
+            info!("Workflow definition {}/{} not found", workflow.namespace, workflow.name);
+            Err((StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Workflow definition not found",
+                "id": workflow.id
+            }))).into_response())
+        }
+        Err(e) => {
+            error!("Failed to get workflow definition {}/{}: {}", workflow.namespace, workflow.name, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get workflow definition: {}", e),
+                "id": workflow.id
+            }))).into_response())
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WorkflowDiffQuery {
+    other_id: Uuid,
+}
+
+pub async fn get_workflow_diff(
+    State(server): State<Arc<Server>>,
+    Path(workflow_id): Path<Uuid>,
+    Query(query): Query<WorkflowDiffQuery>,
+) -> impl IntoResponse {
+    info!("Diffing outputs of workflow {} against {}", workflow_id, query.other_id);
+
+    let mut outputs = Vec::with_capacity(2);
+    for id in [workflow_id, query.other_id] {
+        match server.store.get_workflow(id).await {
+            Ok(Some(workflow)) => outputs.push(workflow.outputs.unwrap_or_else(|| serde_json::json!({}))),
+            Ok(None) => {
+                info!("Workflow with id {} not found", id);
+                return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                    "error": "Workflow not found",
+                    "id": id
+                }))).into_response();
+            }
+            Err(e) => {
+                error!("Failed to get workflow: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                    "error": format!("Failed to get workflow: {}", e),
+                    "id": id
+                }))).into_response();
+            }
+        }
+    }
+
+    let diff = diff_json(&outputs[0], &outputs[1]);
+    (StatusCode::OK, Json(diff)).into_response()
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AlertWorkflowDiffQuery {
+    run1: Uuid,
+    run2: Uuid,
+}
+
+/// JSON Patch from the step outputs of `run1` to those of `run2`, two
+/// workflow runs triggered by the same alert
+pub async fn get_alert_workflow_diff(
+    State(server): State<Arc<Server>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AlertWorkflowDiffQuery>,
+) -> impl IntoResponse {
+    info!("Diffing workflow runs {} and {} of alert {}", query.run1, query.run2, id);
+
+    match server.store.get_alert(id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            info!("Alert with id {} not found", id);
+            return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Alert not found",
+                "id": id
+            }))).into_response();
+        }
+        Err(e) => {
+            error!("Failed to get alert: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get alert: {}", e),
+                "id": id
+            }))).into_response();
+        }
+    }
+
+    let workflows = match server.store.list_workflows_for_alert(id).await {
+        Ok(workflows) => workflows,
+        Err(e) => {
+            error!("Failed to list workflows for alert {}: {}", id, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to list workflows for alert: {}", e),
+                "id": id
+            }))).into_response();
+        }
+    };
+
+    let mut contexts = Vec::with_capacity(2);
+    for run in [query.run1, query.run2] {
+        let Some(workflow) = workflows.iter().find(|w| w.id == run) else {
+            info!("Workflow {} not found for alert {}", run, id);
+            return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Workflow not found for alert",
+                "id": run
+            }))).into_response();
+        };
+        let outputs = workflow.outputs.clone().unwrap_or_else(|| serde_json::json!({}));
+        contexts.push(WorkflowContext::from_workflow_outputs(&outputs));
+    }
+
+    (StatusCode::OK, Json(contexts[0].diff_with(&contexts[1]))).into_response()
+}
+
+pub async fn get_workflow_report(
+    State(server): State<Arc<Server>>,
+    Path(workflow_id): Path<Uuid>,
+) -> impl IntoResponse {
+    info!("Building report for workflow: {}", workflow_id);
+
+    match server.store.get_workflow_with_all_data(workflow_id).await {
+        Ok(Some(report)) => {
+            info!(
+                "Returning report with {} steps and {} outputs for workflow {}",
+                report.steps.len(), report.sink_outputs.len(), workflow_id
+            );
+            (StatusCode::OK, Json(report)).into_response()
+        }
+        Ok(None) => {
+            info!("Workflow with id {} not found", workflow_id);
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Workflow not found",
+                "id": workflow_id
+            }))).into_response()
+        }
+        Err(e) => {
+            error!("Failed to get workflow report: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get workflow report: {}", e),
+                "id": workflow_id
+            }))).into_response()
+        }
+    }
+}
+
+/// Upgrade to a WebSocket that receives a `WorkflowCompletionEvent` JSON
+/// message for every workflow that completes from then on
+pub async fn workflow_completions_ws(
+    State(server): State<Arc<Server>>,
+    request: Request,
+) -> Response {
+    let Some(engine) = &server.workflow_engine else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Workflow engine not available"
+        }))).into_response();
+    };
+
+    let sink = WebSocketSink::new(engine.clone());
+    match websocket::upgrade(request, move |socket| async move { sink.forward(socket).await }) {
+        Ok(response) => response,
+        Err(reason) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": reason
+        }))).into_response(),
+    }
+}
+
 pub async fn list_workflow_outputs(
     State(server): State<Arc<Server>>,
     Path(workflow_id): Path<Uuid>,
@@ -402,7 +1210,106 @@ pub async fn list_workflow_outputs(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TimeseriesQuery {
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+    bucket: Option<String>,
+}
+
+/// Parse a bucket size such as "60m", "2h" or "15" (minutes) into minutes
+fn parse_bucket_minutes(bucket: &str) -> Option<u32> {
+    let bucket = bucket.trim();
+    let (value, multiplier) = if let Some(v) = bucket.strip_suffix('m') {
+        (v, 1)
+    } else if let Some(v) = bucket.strip_suffix('h') {
+        (v, 60)
+    } else {
+        (bucket, 1)
+    };
+
+    value.parse::<u32>().ok()
+        .and_then(|v| v.checked_mul(multiplier))
+        .filter(|v| *v > 0)
+}
+
+pub async fn workflow_timeseries(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<TimeseriesQuery>,
+) -> impl IntoResponse {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or_else(|| to - chrono::Duration::hours(24));
+    let bucket = query.bucket.as_deref().unwrap_or("60m");
+
+    let bucket_minutes = match parse_bucket_minutes(bucket) {
+        Some(minutes) => minutes,
+        None => {
+            error!("Invalid bucket size: {}", bucket);
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": format!("Invalid bucket: {}. Use minutes or hours, e.g. 15m or 1h", bucket)
+            }))).into_response();
+        }
+    };
+
+    info!("Getting workflow timeseries from {} to {} with {}m buckets", from, to, bucket_minutes);
+
+    match server.store.list_workflows_for_time_range(from, to, bucket_minutes).await {
+        Ok(buckets) => {
+            info!("Returning {} workflow buckets", buckets.len());
+            (StatusCode::OK, Json(buckets)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to get workflow timeseries: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get workflow timeseries: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+pub async fn sink_statistics(
+    State(server): State<Arc<Server>>,
+) -> impl IntoResponse {
+    info!("Getting sink statistics");
+
+    match server.store.count_sink_outputs_by_type().await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => {
+            error!("Failed to get sink statistics: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to get sink statistics: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SinkFailureQuery {
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    limit: Option<i64>,
+}
+
+pub async fn list_sink_failures(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<SinkFailureQuery>,
+) -> impl IntoResponse {
+    let since = query.since.unwrap_or_else(|| Utc::now() - chrono::Duration::hours(1));
+    let limit = query.limit.unwrap_or(50).min(100);
+    
+    info!("Listing sink failures since {} with limit: {}", since, limit);
+
+    match server.store.list_recent_sink_failures(since, limit).await {
+        Ok(outputs) => (StatusCode::OK, Json(outputs)).into_response(),
+        Err(e) => {
+            error!("Failed to list sink failures: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to list sink failures: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct SourceEventQuery {
     source_name: String,
     limit: Option<i64>,
@@ -416,7 +1323,7 @@ pub async fn list_source_events(
     
     info!("Listing source events for source: {} with limit: {}", query.source_name, limit);
 
-    match server.store.list_source_events(&query.source_name, limit).await {
+    match server.store.list_source_events_with_workflow_status(&query.source_name, limit).await {
         Ok(events) => {
             info!("Returning {} events for source {}", events.len(), query.source_name);
             (StatusCode::OK, Json(events)).into_response()
@@ -429,4 +1336,149 @@ pub async fn list_source_events(
             }))).into_response()
         }
     }
-}
\ No newline at end of file
+}
+
+// Admin endpoints
+
+/// Check the `Authorization: Bearer <token>` header against the configured
+/// admin token. With `auth.enabled` the token is a Kubernetes one and the
+/// admin role has already been checked by `auth::require_role`.
+async fn authorize_admin(server: &Server, headers: &HeaderMap) -> std::result::Result<(), (StatusCode, &'static str)> {
+    let config = server.config.read().await;
+    if config.auth.enabled {
+        return Ok(());
+    }
+    let Some(expected) = config.server.admin_token.as_deref() else {
+        return Err((StatusCode::FORBIDDEN, "Admin API is disabled: ADMIN_TOKEN is not configured"));
+    };
+
+    let provided = headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    if provided != Some(expected) {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid or missing admin token"));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct LogLevelPayload {
+    level: String,
+}
+
+pub async fn get_log_level(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err((status, message)) = authorize_admin(&server, &headers).await {
+        return (status, Json(serde_json::json!({ "error": message }))).into_response();
+    }
+
+    let Some(handle) = server.log_reload_handle.as_ref() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Log level reloading is not enabled"
+        }))).into_response();
+    };
+
+    match handle.with_current(|filter| filter.to_string()) {
+        Ok(level) => (StatusCode::OK, Json(LogLevelPayload { level })).into_response(),
+        Err(e) => {
+            error!("Failed to read log level: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to read log level: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+pub async fn set_log_level(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+    Json(payload): Json<LogLevelPayload>,
+) -> impl IntoResponse {
+    if let Err((status, message)) = authorize_admin(&server, &headers).await {
+        return (status, Json(serde_json::json!({ "error": message }))).into_response();
+    }
+
+    let Some(handle) = server.log_reload_handle.as_ref() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Log level reloading is not enabled"
+        }))).into_response();
+    };
+
+    let new_filter = match EnvFilter::try_new(&payload.level) {
+        Ok(filter) => filter,
+        Err(e) => {
+            error!("Invalid log level '{}': {}", payload.level, e);
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": format!("Invalid log level '{}': {}", payload.level, e)
+            }))).into_response();
+        }
+    };
+
+    match handle.modify(|filter| *filter = new_filter) {
+        Ok(()) => {
+            info!("Log level changed to: {}", payload.level);
+            (StatusCode::OK, Json(LogLevelPayload { level: payload.level })).into_response()
+        }
+        Err(e) => {
+            error!("Failed to change log level: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to change log level: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+pub async fn backup_database(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Err((status, message)) = authorize_admin(&server, &headers).await {
+        return (status, Json(serde_json::json!({ "error": message }))).into_response();
+    }
+
+    let dir = server.config.read().await.backup.dir.clone();
+    match backup_to_dir(server.store.as_ref(), &dir).await {
+        Ok((path, size_bytes)) => (StatusCode::OK, Json(serde_json::json!({
+            "path": path,
+            "size_bytes": size_bytes,
+        }))).into_response(),
+        Err(e) => {
+            error!("Database backup failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Database backup failed: {}", e)
+            }))).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AuditLogQuery {
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    limit: Option<i64>,
+}
+
+pub async fn list_audit_log(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> impl IntoResponse {
+    if let Err((status, message)) = authorize_admin(&server, &headers).await {
+        return (status, Json(serde_json::json!({ "error": message }))).into_response();
+    }
+
+    let limit = query.limit.unwrap_or(100).min(1000);
+    match server.store.list_audit_entries(query.entity_type.as_deref(), query.entity_id.as_deref(), limit).await {
+        Ok(entries) => (StatusCode::OK, Json(entries)).into_response(),
+        Err(e) => {
+            error!("Failed to list audit log: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Failed to list audit log: {}", e)
+            }))).into_response()
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::{
+    sinks::Sink,
+    store::Alert,
+    Result, Error,
+    crd::sink::SinkSpec,
+};
+
+/// Resolves the alert that triggered a workflow once the workflow succeeds,
+/// by posting it back to AlertManager's `/api/v2/alerts` with an `endsAt` in
+/// the past. The labels are those of the stored alert, so AlertManager
+/// computes the same fingerprint and resolves the original alert.
+pub struct AlertManagerSink {
+    name: String,
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl AlertManagerSink {
+    pub fn new(name: String, spec: &SinkSpec) -> Result<Self> {
+        let config = &spec.config;
+
+        let endpoint = config.endpoint.clone()
+            .ok_or_else(|| Error::Validation("AlertManager sink missing endpoint".to_string()))?;
+        match config.action.as_deref() {
+            None | Some("resolve") => {}
+            Some(action) => {
+                return Err(Error::Validation(format!(
+                    "AlertManager sink action '{}' is not supported, only 'resolve' is",
+                    action
+                )));
+            }
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            name,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for AlertManagerSink {
+    async fn send(&self, context: Value) -> Result<()> {
+        // Nothing to resolve until the investigation is over
+        if context.get("type").and_then(Value::as_str) == Some("start") {
+            return Ok(());
+        }
+        let alert = match context.get("alert").filter(|alert| !alert.is_null()) {
+            Some(alert) => serde_json::from_value::<Alert>(alert.clone())
+                .map_err(|e| Error::Validation(format!("AlertManager sink {} got an invalid alert: {}", self.name, e)))?,
+            None => {
+                info!("[{}] Workflow was not triggered by an alert, nothing to resolve", self.name);
+                return Ok(());
+            }
+        };
+
+        let url = format!("{}/api/v2/alerts", self.endpoint);
+        let response = self.client.post(&url).json(&resolve_payload(&alert)).send().await
+            .map_err(|e| Error::Execution(format!("AlertManager sink {} failed to post to {}: {}", self.name, url, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            error!("AlertManager sink {} got status {}: {}", self.name, status.as_u16(), text);
+            return Err(Error::Execution(format!(
+                "AlertManager sink {} got status {}: {}",
+                self.name, status.as_u16(), text
+            )));
+        }
+
+        info!("[{}] Resolved alert {} ({})", self.name, alert.alert_name, alert.fingerprint);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The `/api/v2/alerts` body resolving `alert`. Annotations starting with
+/// `_` are the operator's own and are left out.
+fn resolve_payload(alert: &Alert) -> Value {
+    let annotations: serde_json::Map<String, Value> = alert.annotations.iter()
+        .filter(|(key, _)| !key.starts_with('_'))
+        .map(|(key, value)| (key.clone(), json!(value)))
+        .collect();
+    json!([{
+        "labels": alert.labels,
+        "annotations": annotations,
+        "startsAt": alert.starts_at,
+        "endsAt": Utc::now() - chrono::Duration::seconds(1),
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{AlertSeverity, AlertStatus};
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use std::collections::HashMap;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    /// AlertManager that records each posted batch and rejects alerts
+    /// without labels
+    async fn mock_alertmanager() -> (String, mpsc::UnboundedReceiver<Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route("/api/v2/alerts", post(move |Json(alerts): Json<Value>| async move {
+            let rejected = alerts[0]["labels"].as_object().is_none_or(|labels| labels.is_empty());
+            tx.send(alerts).unwrap();
+            if rejected {
+                (StatusCode::BAD_REQUEST, "labels: must not be empty")
+            } else {
+                (StatusCode::OK, "")
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/", addr), rx)
+    }
+
+    fn alertmanager_spec(config: Value) -> SinkSpec {
+        serde_json::from_value(json!({ "type": "alertmanager", "config": config })).unwrap()
+    }
+
+    fn alert(labels: HashMap<String, String>) -> Alert {
+        let starts_at = Utc::now() - chrono::Duration::minutes(10);
+        Alert {
+            id: Uuid::new_v4(),
+            external_id: None,
+            fingerprint: Alert::generate_fingerprint("PodCrashLooping", &labels),
+            status: AlertStatus::Triaging,
+            severity: AlertSeverity::Critical,
+            alert_name: "PodCrashLooping".to_string(),
+            summary: None,
+            description: None,
+            labels,
+            annotations: HashMap::from([
+                ("summary".to_string(), "Pod is crash looping".to_string()),
+                (Alert::SOURCE_NAMESPACE_ANNOTATION.to_string(), "monitoring".to_string()),
+            ]),
+            source_id: None,
+            workflow_id: None,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at,
+            ends_at: None,
+            received_at: starts_at,
+            triage_started_at: Some(starts_at),
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: starts_at,
+            updated_at: starts_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alertmanager_sink_resolves_alert() {
+        let (url, mut posted) = mock_alertmanager().await;
+        let sink = AlertManagerSink::new("resolve".to_string(), &alertmanager_spec(json!({ "endpoint": url }))).unwrap();
+
+        let labels = HashMap::from([
+            ("alertname".to_string(), "PodCrashLooping".to_string()),
+            ("pod".to_string(), "api-7f9".to_string()),
+        ]);
+        let alert = alert(labels.clone());
+        sink.send(json!({ "type": "start", "workflow": { "name": "triage" }, "alert": alert })).await.unwrap();
+        sink.send(json!({ "type": "completion", "workflow": { "name": "triage" }, "alert": null })).await.unwrap();
+        sink.send(json!({ "type": "completion", "workflow": { "name": "triage" }, "alert": alert })).await.unwrap();
+
+        // Only the completion with an alert is posted
+        let alerts = posted.recv().await.unwrap();
+        assert!(posted.try_recv().is_err());
+        assert_eq!(alerts[0]["labels"], json!(labels));
+        assert_eq!(alerts[0]["annotations"], json!({ "summary": "Pod is crash looping" }));
+        assert_eq!(alerts[0]["startsAt"], json!(alert.starts_at));
+        let ends_at: chrono::DateTime<Utc> = serde_json::from_value(alerts[0]["endsAt"].clone()).unwrap();
+        assert!(ends_at < Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_alertmanager_sink_errors() {
+        let (url, _posted) = mock_alertmanager().await;
+        let sink = AlertManagerSink::new("resolve".to_string(), &alertmanager_spec(json!({ "endpoint": url }))).unwrap();
+        let err = sink.send(json!({ "alert": alert(HashMap::new()) })).await.unwrap_err();
+        assert!(err.to_string().contains("got status 400: labels: must not be empty"), "{}", err);
+
+        assert!(matches!(
+            AlertManagerSink::new("resolve".to_string(), &alertmanager_spec(json!({}))),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            AlertManagerSink::new("resolve".to_string(), &alertmanager_spec(json!({ "endpoint": url, "action": "silence" }))),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{error, info};
+
+use crate::{
+    agent::result::InhibitionRule,
+    sinks::Sink,
+    Result, Error,
+    crd::sink::SinkSpec,
+};
+
+/// POSTs the inhibition rules found by investigations to an endpoint that
+/// installs them into AlertManager, such as Cortex's alertmanager config API
+/// behind a webhook integration or a custom controller. Each request body is
+/// one rule in AlertManager's `inhibit_rules` format.
+pub struct InhibitionSink {
+    name: String,
+    endpoint: String,
+    headers: HashMap<String, String>,
+    client: reqwest::Client,
+}
+
+impl InhibitionSink {
+    pub fn new(name: String, spec: &SinkSpec) -> Result<Self> {
+        let config = &spec.config;
+
+        let endpoint = config.endpoint.clone()
+            .ok_or_else(|| Error::Validation("Inhibition sink missing endpoint".to_string()))?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            name,
+            endpoint,
+            headers: config.headers.clone(),
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for InhibitionSink {
+    async fn send(&self, context: Value) -> Result<()> {
+        let rules = find_inhibition_rules(&context);
+        if rules.is_empty() {
+            info!("[{}] No inhibition rules in workflow output", self.name);
+            return Ok(());
+        }
+
+        for rule in &rules {
+            let mut request = self.client.post(&self.endpoint).json(&rule.to_alertmanager());
+            for (name, value) in &self.headers {
+                request = request.header(name, crate::template::render_template(value, &context)?);
+            }
+
+            let response = request.send().await
+                .map_err(|e| Error::Execution(format!("Inhibition sink {} failed to call {}: {}", self.name, self.endpoint, e)))?;
+            let status = response.status();
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                error!("Inhibition sink {} got status {} from {}", self.name, status.as_u16(), self.endpoint);
+                return Err(Error::Execution(format!(
+                    "Inhibition sink {} got status {} from {}: {}",
+                    self.name, status.as_u16(), self.endpoint, text
+                )));
+            }
+        }
+
+        info!("[{}] Submitted {} inhibition rule(s) to {}", self.name, rules.len(), self.endpoint);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Every distinct `inhibition_rule` in the workflow output. Step outputs
+/// reach sinks as JSON-encoded strings, so those are searched too.
+pub fn find_inhibition_rules(context: &Value) -> Vec<InhibitionRule> {
+    let mut rules = Vec::new();
+    collect_inhibition_rules(context, &mut rules);
+    rules
+}
+
+fn collect_inhibition_rules(value: &Value, rules: &mut Vec<InhibitionRule>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                if key == "inhibition_rule" {
+                    if let Ok(rule) = serde_json::from_value::<InhibitionRule>(value.clone()) {
+                        if !rules.contains(&rule) {
+                            rules.push(rule);
+                        }
+                        continue;
+                    }
+                }
+                collect_inhibition_rules(value, rules);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_inhibition_rules(value, rules);
+            }
+        }
+        Value::String(s) if s.starts_with('{') || s.starts_with('[') => {
+            if let Ok(value) = serde_json::from_str::<Value>(s) {
+                collect_inhibition_rules(&value, rules);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    fn rule_output(source: &str, target: &str) -> Value {
+        json!({
+            "summary": "done",
+            "inhibition_rule": {
+                "source_labels": { "alertname": source },
+                "target_labels": { "alertname": target },
+                "equal": ["namespace"]
+            }
+        })
+    }
+
+    #[test]
+    fn test_find_inhibition_rules() {
+        // Workflow outputs reach sinks as JSON strings inside the event context
+        let steps = json!({ "triage": rule_output("NodeDown", "PodNotReady"), "check": { "stdout": "ok" } });
+        let context = json!({
+            "workflow": { "outputs": { "steps": steps.to_string(), "outputs": "{}" } },
+            "data": { "again": rule_output("NodeDown", "PodNotReady"), "other": rule_output("DiskFull", "WriteErrors") },
+        });
+
+        let rules = find_inhibition_rules(&context);
+        assert_eq!(rules.len(), 2);
+        assert!(rules.iter().any(|r| r.source_labels["alertname"] == "NodeDown" && r.equal == vec!["namespace"]));
+        assert!(rules.iter().any(|r| r.target_labels["alertname"] == "WriteErrors"));
+
+        assert!(find_inhibition_rules(&json!({ "inhibition_rule": null, "summary": "{not json" })).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_inhibition_sink_posts_rules() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = Router::new().route("/api/v1/inhibitions", post(
+            move |headers: HeaderMap, Json(rule): Json<Value>| async move {
+                tx.send((headers, rule)).unwrap();
+            },
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let spec: SinkSpec = serde_json::from_value(json!({
+            "type": "inhibition",
+            "config": {
+                "endpoint": format!("http://{}/api/v1/inhibitions", addr),
+                "headers": { "X-Scope-OrgID": "{{ tenant }}" }
+            }
+        })).unwrap();
+        let sink = InhibitionSink::new("cortex".to_string(), &spec).unwrap();
+
+        let mut context = rule_output("NodeDown", "PodNotReady");
+        context["tenant"] = json!("team-a");
+        sink.send(context).await.unwrap();
+        let (headers, rule) = rx.recv().await.unwrap();
+        assert_eq!(headers["x-scope-orgid"], "team-a");
+        assert_eq!(rule, json!({
+            "source_matchers": ["alertname=\"NodeDown\""],
+            "target_matchers": ["alertname=\"PodNotReady\""],
+            "equal": ["namespace"]
+        }));
+
+        // Nothing to submit without a causal chain
+        sink.send(json!({ "summary": "done" })).await.unwrap();
+        assert!(rx.try_recv().is_err());
+
+        let spec: SinkSpec = serde_json::from_value(json!({ "type": "inhibition", "config": {} })).unwrap();
+        assert!(matches!(InhibitionSink::new("cortex".to_string(), &spec), Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::{
+    config::Config,
+    metrics::SINK_FAILURES_LAST_HOUR,
+    store::{SinkType, Store},
+    Result,
+};
+
+/// Upper bound on the failures fetched per check; the gauge saturates here
+const MAX_FAILURES_PER_CHECK: i64 = 10_000;
+
+const SINK_TYPES: [SinkType; 9] = [
+    SinkType::Slack,
+    SinkType::AlertManager,
+    SinkType::Prometheus,
+    SinkType::Jira,
+    SinkType::PagerDuty,
+    SinkType::Workflow,
+    SinkType::Stdout,
+    SinkType::Webhook,
+    SinkType::Inhibition,
+];
+
+/// Periodically counts sink failures over the last hour, exports them as
+/// the `sink_failures_last_hour` gauge and warns when a sink type exceeds
+/// the configured threshold.
+pub struct SinkFailureMonitor {
+    store: Arc<dyn Store>,
+    config: Arc<RwLock<Config>>,
+    interval: Duration,
+}
+
+impl SinkFailureMonitor {
+    pub fn new(store: Arc<dyn Store>, config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            store,
+            config,
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Starting sink failure monitor");
+
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.check(Utc::now()).await {
+                error!("Sink failure check failed: {}", e);
+            }
+        }
+    }
+
+    /// Update the gauge with the failures in the hour before `now`, returning
+    /// the counts per sink type
+    pub async fn check(&self, now: DateTime<Utc>) -> Result<HashMap<SinkType, i64>> {
+        let failures = self.store
+            .list_recent_sink_failures(now - chrono::Duration::hours(1), MAX_FAILURES_PER_CHECK)
+            .await?;
+
+        let mut counts: HashMap<SinkType, i64> = HashMap::new();
+        for failure in &failures {
+            *counts.entry(failure.sink_type).or_default() += 1;
+        }
+
+        let threshold = self.config.read().await.sink_failures.alert_threshold;
+        for sink_type in SINK_TYPES {
+            let count = counts.get(&sink_type).copied().unwrap_or(0);
+            SINK_FAILURES_LAST_HOUR
+                .with_label_values(&[&sink_type.to_string()])
+                .set(count);
+            if count as u64 > threshold {
+                warn!(
+                    "{} sink outputs of type {} failed in the last hour (threshold {})",
+                    count, sink_type, threshold
+                );
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{SinkOutput, SinkStatus, SqliteStore, Workflow, WorkflowStatus};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_check_sets_gauge() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = Arc::new(SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap());
+        store.init().await.unwrap();
+
+        let now = Utc::now();
+        let workflow_id = Uuid::new_v4();
+        store.save_workflow(Workflow {
+            id: workflow_id,
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 1,
+            total_steps: 1,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: Some(now),
+            created_at: now,
+        }).await.unwrap();
+        for age_minutes in [1, 10, 90] {
+            store.save_sink_output(SinkOutput {
+                id: Uuid::new_v4(),
+                workflow_id,
+                sink_name: "pagerduty".to_string(),
+                sink_type: SinkType::PagerDuty,
+                payload: None,
+                status: SinkStatus::Failed,
+                error: Some("timeout".to_string()),
+                sent_at: None,
+                created_at: now - chrono::Duration::minutes(age_minutes),
+            }).await.unwrap();
+        }
+
+        let monitor = SinkFailureMonitor::new(store, Arc::new(RwLock::new(Config::default())));
+        let counts = monitor.check(now).await.unwrap();
+        assert_eq!(counts.get(&SinkType::PagerDuty), Some(&2));
+        assert_eq!(SINK_FAILURES_LAST_HOUR.with_label_values(&["pagerduty"]).get(), 2);
+        assert_eq!(SINK_FAILURES_LAST_HOUR.with_label_values(&["jira"]).get(), 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
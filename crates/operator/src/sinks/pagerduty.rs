@@ -0,0 +1,346 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::{
+    sinks::Sink,
+    store::{Alert, AlertSeverity},
+    Result, Error,
+    crd::sink::SinkSpec,
+};
+
+/// PagerDuty Events API v2 endpoint
+const EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Investigations less confident than this are escalated
+const ESCALATION_CONFIDENCE: f64 = 0.5;
+
+/// Longest summary PagerDuty accepts
+const MAX_SUMMARY_CHARS: usize = 1024;
+
+/// Escalates investigations to PagerDuty through the Events API v2.
+///
+/// When a workflow completes, an incident is triggered if the agent was
+/// unsure of its result or recommends something that needs approval, and
+/// resolved otherwise. Incidents are deduplicated on the fingerprint of the
+/// alert that triggered the workflow, so a later successful investigation
+/// of the same alert resolves the incident an earlier one opened. The
+/// `action` config pins the sink to only `trigger` or only `resolve`.
+pub struct PagerDutySink {
+    name: String,
+    routing_key: String,
+    endpoint: String,
+    action: Option<EventAction>,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EventAction {
+    Trigger,
+    Resolve,
+}
+
+impl EventAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            EventAction::Trigger => "trigger",
+            EventAction::Resolve => "resolve",
+        }
+    }
+}
+
+impl PagerDutySink {
+    pub fn new(name: String, spec: &SinkSpec) -> Result<Self> {
+        let config = &spec.config;
+
+        let routing_key = config.routing_key.clone()
+            .ok_or_else(|| Error::Validation("PagerDuty sink missing routingKey".to_string()))?;
+        let action = match config.action.as_deref() {
+            None => None,
+            Some("trigger") => Some(EventAction::Trigger),
+            Some("resolve") => Some(EventAction::Resolve),
+            Some(action) => {
+                return Err(Error::Validation(format!(
+                    "PagerDuty sink action '{}' is not supported, use 'trigger' or 'resolve'",
+                    action
+                )));
+            }
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            name,
+            routing_key,
+            endpoint: config.endpoint.clone().unwrap_or_else(|| EVENTS_URL.to_string()),
+            action,
+            client,
+        })
+    }
+
+    /// The event to send for `context`, if any
+    fn event(&self, context: &Value) -> Option<Value> {
+        // Only finished investigations are escalated or resolved
+        if context.get("type").and_then(Value::as_str) == Some("start") {
+            return None;
+        }
+
+        let alert = context.get("alert")
+            .and_then(|alert| serde_json::from_value::<Alert>(alert.clone()).ok());
+        let result = crate::template::find_agent_result(context);
+        let action = self.action.unwrap_or(match &result {
+            Some(result) if needs_escalation(result) => EventAction::Trigger,
+            _ => EventAction::Resolve,
+        });
+
+        let workflow = &context["workflow"];
+        let workflow_name = workflow["name"].as_str().unwrap_or("workflow");
+        let namespace = workflow["namespace"].as_str().unwrap_or("default");
+        let dedup_key = match &alert {
+            Some(alert) => alert.fingerprint.clone(),
+            None => format!("{}/{}", namespace, workflow_name),
+        };
+
+        let mut event = json!({
+            "routing_key": self.routing_key,
+            "event_action": action.as_str(),
+            "dedup_key": dedup_key,
+        });
+        if action == EventAction::Trigger {
+            let summary = result.as_ref()
+                .and_then(|result| result["summary"].as_str())
+                .map(str::to_string)
+                .or_else(|| alert.as_ref().map(|alert| alert.summary.clone().unwrap_or_else(|| alert.alert_name.clone())))
+                .unwrap_or_else(|| format!("Investigation {} needs attention", workflow_name));
+            event["payload"] = json!({
+                "summary": truncate(&summary, MAX_SUMMARY_CHARS),
+                "source": format!("punching-fist/{}/{}", namespace, workflow_name),
+                "severity": severity(alert.as_ref().map(|alert| alert.severity)),
+                "component": alert.as_ref().map(|alert| alert.alert_name.clone()),
+                "custom_details": {
+                    "summary": result.as_ref().map(|result| result["summary"].clone()),
+                    "root_cause": result.as_ref().map(|result| result["root_cause"].clone()),
+                    "confidence": result.as_ref().map(|result| result["confidence"].clone()),
+                    "recommendations": result.as_ref().map(|result| result["recommendations"].clone()),
+                    "workflow": format!("{}/{}", namespace, workflow_name),
+                },
+            });
+        }
+        Some(event)
+    }
+}
+
+#[async_trait]
+impl Sink for PagerDutySink {
+    async fn send(&self, context: Value) -> Result<()> {
+        self.deliver(context).await.map(|_| ())
+    }
+
+    async fn deliver(&self, context: Value) -> Result<Option<Value>> {
+        let Some(event) = self.event(&context) else {
+            return Ok(None);
+        };
+
+        let response = self.client.post(&self.endpoint).json(&event).send().await
+            .map_err(|e| Error::Execution(format!("PagerDuty sink {} failed to send event: {}", self.name, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            error!("PagerDuty sink {} got status {}: {}", self.name, status.as_u16(), text);
+            return Err(Error::Execution(format!(
+                "PagerDuty sink {} got status {}: {}",
+                self.name, status.as_u16(), text
+            )));
+        }
+
+        // PagerDuty echoes the dedup key, and picks one if none was sent
+        let body: Value = response.json().await.unwrap_or_default();
+        let dedup_key = body["dedup_key"].as_str().map(str::to_string)
+            .unwrap_or_else(|| event["dedup_key"].as_str().unwrap_or_default().to_string());
+        info!("[{}] Sent PagerDuty {} event for {}", self.name, event["event_action"], dedup_key);
+        Ok(Some(json!({
+            "event_action": event["event_action"],
+            "dedup_key": dedup_key,
+        })))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Whether the agent was unsure of `result` or recommends an action that
+/// needs approval
+fn needs_escalation(result: &Value) -> bool {
+    let unsure = result["confidence"].as_f64().is_some_and(|confidence| confidence < ESCALATION_CONFIDENCE);
+    let needs_approval = result["recommendations"].as_array()
+        .is_some_and(|recommendations| recommendations.iter().any(|r| r["requires_approval"] == json!(true)));
+    unsure || needs_approval
+}
+
+/// The PagerDuty severity for an alert's severity
+fn severity(severity: Option<AlertSeverity>) -> &'static str {
+    match severity {
+        Some(AlertSeverity::Critical) => "critical",
+        Some(AlertSeverity::Warning) => "warning",
+        Some(AlertSeverity::Info) => "info",
+        None => "error",
+    }
+}
+
+/// `text` cut to at most `max` characters, ending in an ellipsis if it was
+/// shortened
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::AlertStatus;
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    /// Events API that records each event and rejects unknown routing keys
+    async fn mock_pagerduty() -> (String, mpsc::UnboundedReceiver<Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route("/v2/enqueue", post(move |Json(event): Json<Value>| async move {
+            let accepted = event["routing_key"] == "R0UT1NGK3Y";
+            let dedup_key = event["dedup_key"].clone();
+            tx.send(event).unwrap();
+            if accepted {
+                (StatusCode::ACCEPTED, Json(json!({ "status": "success", "message": "Event processed", "dedup_key": dedup_key })))
+            } else {
+                (StatusCode::BAD_REQUEST, Json(json!({ "status": "invalid event", "message": "Invalid routing key" })))
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/v2/enqueue", addr), rx)
+    }
+
+    fn pagerduty_spec(config: Value) -> SinkSpec {
+        serde_json::from_value(json!({ "type": "pagerduty", "config": config })).unwrap()
+    }
+
+    fn alert() -> Alert {
+        let now = Utc::now();
+        Alert {
+            id: Uuid::new_v4(),
+            external_id: None,
+            fingerprint: "3f2a9c1b".to_string(),
+            status: AlertStatus::Triaging,
+            severity: AlertSeverity::Critical,
+            alert_name: "PodCrashLooping".to_string(),
+            summary: Some("Pod api-7f9 is crash looping".to_string()),
+            description: None,
+            labels: HashMap::new(),
+            annotations: HashMap::new(),
+            source_id: None,
+            workflow_id: None,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at: now,
+            ends_at: None,
+            received_at: now,
+            triage_started_at: Some(now),
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// A completion payload whose agent result has `confidence` and one
+    /// recommendation
+    fn completion(confidence: f64, requires_approval: bool, alert: Option<&Alert>) -> Value {
+        let result = json!({
+            "summary": "Memory limit of 256Mi is too low",
+            "findings": [],
+            "root_cause": "OOMKilled",
+            "confidence": confidence,
+            "recommendations": [
+                { "priority": 1, "action": "Raise the memory limit", "rationale": "", "risk_level": "medium", "requires_approval": requires_approval }
+            ],
+        });
+        json!({
+            "type": "completion",
+            "status": "succeeded",
+            "workflow": { "name": "triage", "namespace": "default", "outputs": { "investigate": result.to_string() } },
+            "alert": alert,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pagerduty_sink_triggers_and_resolves() {
+        let (url, mut events) = mock_pagerduty().await;
+        let sink = PagerDutySink::new(
+            "oncall".to_string(),
+            &pagerduty_spec(json!({ "endpoint": url, "routingKey": "R0UT1NGK3Y" })),
+        ).unwrap();
+        let alert = alert();
+
+        // Nothing is sent when the investigation starts
+        assert_eq!(sink.deliver(json!({ "type": "start", "alert": alert })).await.unwrap(), None);
+
+        let receipt = sink.deliver(completion(0.3, false, Some(&alert))).await.unwrap();
+        assert_eq!(receipt, Some(json!({ "event_action": "trigger", "dedup_key": "3f2a9c1b" })));
+        let event = events.recv().await.unwrap();
+        assert_eq!(event["payload"]["summary"], "Memory limit of 256Mi is too low");
+        assert_eq!(event["payload"]["severity"], "critical");
+        assert_eq!(event["payload"]["source"], "punching-fist/default/triage");
+        assert_eq!(event["payload"]["custom_details"]["recommendations"][0]["action"], "Raise the memory limit");
+
+        // Confident results that need approval are escalated too
+        sink.deliver(completion(0.9, true, Some(&alert))).await.unwrap();
+        assert_eq!(events.recv().await.unwrap()["event_action"], "trigger");
+
+        let receipt = sink.deliver(completion(0.9, false, Some(&alert))).await.unwrap();
+        assert_eq!(receipt, Some(json!({ "event_action": "resolve", "dedup_key": "3f2a9c1b" })));
+        let event = events.recv().await.unwrap();
+        assert!(event.get("payload").is_none());
+
+        // Without an alert the workflow is the dedup key
+        let sink = PagerDutySink::new(
+            "oncall".to_string(),
+            &pagerduty_spec(json!({ "endpoint": url, "routingKey": "R0UT1NGK3Y", "action": "trigger" })),
+        ).unwrap();
+        sink.deliver(completion(0.9, false, None)).await.unwrap();
+        let event = events.recv().await.unwrap();
+        assert_eq!(event["event_action"], "trigger");
+        assert_eq!(event["dedup_key"], "default/triage");
+        assert_eq!(event["payload"]["severity"], "error");
+    }
+
+    #[tokio::test]
+    async fn test_pagerduty_sink_errors() {
+        let (url, _events) = mock_pagerduty().await;
+        let sink = PagerDutySink::new(
+            "oncall".to_string(),
+            &pagerduty_spec(json!({ "endpoint": url, "routingKey": "wrong" })),
+        ).unwrap();
+        let err = sink.deliver(completion(0.3, false, None)).await.unwrap_err();
+        assert!(err.to_string().contains("got status 400"), "{}", err);
+
+        assert!(matches!(PagerDutySink::new("oncall".to_string(), &pagerduty_spec(json!({}))), Err(Error::Validation(_))));
+        assert!(matches!(
+            PagerDutySink::new("oncall".to_string(), &pagerduty_spec(json!({ "routingKey": "R0UT1NGK3Y", "action": "acknowledge" }))),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use crate::{
+    sinks::Sink,
+    Result, Error,
+    crd::sink::SinkSpec,
+};
+
+/// Longest text Slack accepts in a section block
+const MAX_SECTION_CHARS: usize = 3000;
+
+/// Longest text Slack accepts in a header block
+const MAX_HEADER_CHARS: usize = 150;
+
+/// Posts investigation results to a Slack incoming webhook as Block Kit
+/// messages: the summary, the root cause and the recommendations, each in
+/// its own section.
+pub struct SlackSink {
+    name: String,
+    webhook_url: String,
+    channel: Option<String>,
+    mention_users: Vec<String>,
+    report_template: Option<String>,
+    client: reqwest::Client,
+}
+
+impl SlackSink {
+    pub fn new(name: String, spec: &SinkSpec) -> Result<Self> {
+        let config = &spec.config;
+
+        let webhook_url = config.endpoint.clone()
+            .ok_or_else(|| Error::Validation("Slack sink missing endpoint (the incoming webhook URL)".to_string()))?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            name,
+            webhook_url,
+            channel: config.channel.clone(),
+            mention_users: config.mention_users.clone(),
+            report_template: spec.report_template.clone(),
+            client,
+        })
+    }
+
+    /// The webhook payload for `context`
+    fn message(&self, context: &Value) -> Result<Value> {
+        let mut blocks = Vec::new();
+        let text = if context.get("type").and_then(Value::as_str) == Some("start") {
+            let text = start_text(context);
+            blocks.push(section(&text));
+            text
+        } else if let Some(result) = crate::template::find_agent_result(context) {
+            let summary = match &self.report_template {
+                Some(template) => crate::template::render_report(Some(template), context)?,
+                None => result["summary"].as_str().unwrap_or_default().to_string(),
+            };
+            blocks.push(header(&format!("Investigation: {}", workflow_name(context))));
+            blocks.push(section(&summary));
+            if let Some(root_cause) = result["root_cause"].as_str().filter(|s| !s.is_empty()) {
+                blocks.push(section(&format!("*Root cause*\n{}", root_cause)));
+            }
+            let recommendations: Vec<String> = result["recommendations"].as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|r| r["action"].as_str())
+                .map(|action| format!("• {}", action))
+                .collect();
+            if !recommendations.is_empty() {
+                blocks.push(section(&format!("*Recommendations*\n{}", recommendations.join("\n"))));
+            }
+            summary
+        } else {
+            let text = crate::template::render_report(self.report_template.as_deref(), context)?;
+            let text = match self.report_template {
+                Some(_) => text,
+                None => format!("```{}```", text),
+            };
+            blocks.push(section(&text));
+            text
+        };
+
+        if !self.mention_users.is_empty() {
+            let mentions: Vec<String> = self.mention_users.iter().map(|user| format!("<@{}>", user)).collect();
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": mentions.join(" ") }],
+            }));
+        }
+
+        let mut message = json!({
+            "text": truncate(&text, MAX_SECTION_CHARS),
+            "blocks": blocks,
+        });
+        if let Some(channel) = &self.channel {
+            message["channel"] = json!(channel);
+        }
+        Ok(message)
+    }
+}
+
+#[async_trait]
+impl Sink for SlackSink {
+    async fn send(&self, context: Value) -> Result<()> {
+        let message = self.message(&context)?;
+
+        let response = self.client.post(&self.webhook_url).json(&message).send().await
+            .map_err(|e| Error::Execution(format!("Slack sink {} failed to post message: {}", self.name, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            // Slack explains rejected messages in the body, e.g. `channel_not_found`
+            let text = response.text().await.unwrap_or_default();
+            error!("Slack sink {} got status {}: {}", self.name, status.as_u16(), text);
+            return Err(Error::Execution(format!(
+                "Slack sink {} got status {}: {}",
+                self.name, status.as_u16(), text
+            )));
+        }
+
+        info!("[{}] Posted message to Slack", self.name);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The text announcing a workflow's start, from the engine's start payload
+fn start_text(context: &Value) -> String {
+    let mut text = format!(":mag: Started investigating *{}*", workflow_name(context));
+    if let Some(goal) = context["goal"].as_str().filter(|goal| !goal.is_empty()) {
+        text.push_str(&format!(": {}", goal));
+    }
+    if let Some(eta) = context["eta_minutes"].as_i64() {
+        text.push_str(&format!(" (up to {} min)", eta));
+    }
+    text
+}
+
+fn workflow_name(context: &Value) -> &str {
+    context["workflow"]["name"].as_str().unwrap_or("workflow")
+}
+
+fn header(text: &str) -> Value {
+    json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": truncate(text, MAX_HEADER_CHARS) },
+    })
+}
+
+fn section(text: &str) -> Value {
+    json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": truncate(text, MAX_SECTION_CHARS) },
+    })
+}
+
+/// `text` cut to at most `max` characters, ending in an ellipsis if it was
+/// shortened
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use tokio::sync::mpsc;
+
+    /// Incoming webhook that records each message and rejects the
+    /// `#missing` channel like Slack does
+    async fn mock_slack() -> (String, mpsc::UnboundedReceiver<Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route("/services/T000/B000/XXXX", post(move |Json(message): Json<Value>| async move {
+            let rejected = message["channel"] == "#missing";
+            tx.send(message).unwrap();
+            if rejected {
+                (StatusCode::NOT_FOUND, "channel_not_found")
+            } else {
+                (StatusCode::OK, "ok")
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/services/T000/B000/XXXX", addr), rx)
+    }
+
+    fn slack_spec(config: Value) -> SinkSpec {
+        serde_json::from_value(json!({ "type": "slack", "config": config })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_slack_sink_posts_blocks() {
+        let (url, mut messages) = mock_slack().await;
+        let spec = slack_spec(json!({ "endpoint": url, "channel": "#incidents", "mentionUsers": ["U024BE7LH"] }));
+        let sink = SlackSink::new("oncall".to_string(), &spec).unwrap();
+
+        // Step outputs reach sinks as JSON strings
+        let result = json!({
+            "summary": "Pod api-7f9 was OOMKilled",
+            "findings": [],
+            "root_cause": "Memory limit of 256Mi is too low",
+            "recommendations": [
+                { "priority": 1, "action": "Raise the memory limit to 512Mi", "rationale": "", "risk_level": "low", "requires_approval": false },
+                { "priority": 2, "action": "Add a memory alert", "rationale": "", "risk_level": "low", "requires_approval": false }
+            ],
+        });
+        let context = json!({
+            "workflow": { "name": "oom-triage", "outputs": { "investigate": result.to_string() } },
+        });
+        sink.send(context).await.unwrap();
+
+        let message = messages.recv().await.unwrap();
+        assert_eq!(message, json!({
+            "channel": "#incidents",
+            "text": "Pod api-7f9 was OOMKilled",
+            "blocks": [
+                { "type": "header", "text": { "type": "plain_text", "text": "Investigation: oom-triage" } },
+                { "type": "section", "text": { "type": "mrkdwn", "text": "Pod api-7f9 was OOMKilled" } },
+                { "type": "section", "text": { "type": "mrkdwn", "text": "*Root cause*\nMemory limit of 256Mi is too low" } },
+                { "type": "section", "text": { "type": "mrkdwn", "text": "*Recommendations*\n• Raise the memory limit to 512Mi\n• Add a memory alert" } },
+                { "type": "context", "elements": [{ "type": "mrkdwn", "text": "<@U024BE7LH>" }] }
+            ]
+        }));
+
+        // Without a channel override the webhook's default channel is used
+        let sink = SlackSink::new("oncall".to_string(), &slack_spec(json!({ "endpoint": url }))).unwrap();
+        sink.send(json!({
+            "type": "start",
+            "workflow": { "name": "oom-triage" },
+            "goal": "Find out why api-7f9 restarts",
+            "eta_minutes": 10,
+        })).await.unwrap();
+        let message = messages.recv().await.unwrap();
+        assert!(message.get("channel").is_none());
+        assert_eq!(message["blocks"].as_array().unwrap().len(), 1);
+        assert_eq!(message["text"], ":mag: Started investigating *oom-triage*: Find out why api-7f9 restarts (up to 10 min)");
+    }
+
+    #[tokio::test]
+    async fn test_slack_sink_errors() {
+        let (url, _messages) = mock_slack().await;
+        let sink = SlackSink::new("oncall".to_string(), &slack_spec(json!({ "endpoint": url, "channel": "#missing" }))).unwrap();
+        let err = sink.send(json!({ "summary": "done", "findings": [] })).await.unwrap_err();
+        assert!(err.to_string().contains("got status 404: channel_not_found"), "{}", err);
+
+        assert!(matches!(SlackSink::new("oncall".to_string(), &slack_spec(json!({}))), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("ééééé", 3), "éé…");
+    }
+}
@@ -14,10 +14,11 @@ pub struct StdoutSink {
     format: String,
     pretty: bool,
     template: Option<String>, // For text output, from SinkConfig.template
+    report_template: Option<String>, // For text output, from SinkSpec.report_template
 }
 
 impl StdoutSink {
-    pub fn new(name: String, spec: &SinkSpec) -> Result<Box<dyn Sink>> {
+    pub fn new(name: String, spec: &SinkSpec) -> Result<Self> {
         let config = &spec.config;
         
         let format = config.format.as_ref().unwrap_or(&"json".to_string()).clone();
@@ -32,13 +33,15 @@ impl StdoutSink {
         
         // Use the template field from SinkConfig
         let template = config.template.clone();
+        let report_template = spec.report_template.clone();
         
-        Ok(Box::new(Self {
+        Ok(Self {
             name,
             format,
             pretty,
             template,
-        }))
+            report_template,
+        })
     }
 }
 
@@ -60,13 +63,15 @@ impl Sink for StdoutSink {
                     .map_err(|e| Error::Internal(format!("YAML serialization error: {}", e)))?
             }
             "text" => {
-                if let Some(tmpl) = &self.template {
+                if let Some(tmpl) = &self.report_template {
+                    // Report templates get the agent result fields as variables
+                    crate::template::render_report(Some(tmpl), &context)?
+                } else if let Some(tmpl) = &self.template {
                     // Use Tera for template rendering
                     self.render_template(tmpl, &context)?
                 } else {
-                    // If no template, print the context as JSON
-                    serde_json::to_string_pretty(&context)
-                        .map_err(|e| Error::Internal(format!("JSON serialization error: {}", e)))?
+                    // If no template, fall back to the default report (context as JSON)
+                    crate::template::render_report(None, &context)?
                 }
             }
             _ => unreachable!("Format was validated in new()"),
@@ -110,16 +115,27 @@ mod tests {
             project: None,
             issue_type: None,
             credentials_secret: None,
+            summary_template: None,
+            custom_fields: HashMap::new(),
             routing_key: None,
             workflow_name: None,
             trigger_condition: None,
             context: HashMap::new(),
+            url_template: None,
+            method: None,
+            headers: HashMap::new(),
+            body_template: None,
+            hmac_secret: None,
+            secret_ref: None,
+            auth_header: None,
         };
         
         SinkSpec {
             sink_type: SinkType::Stdout,
             config,
             condition: None,
+            report_template: None,
+            retry: Default::default(),
         }
     }
     
@@ -167,4 +183,18 @@ mod tests {
         
         assert!(sink.send(context).await.is_ok());
     }
-} 
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_stdout_sink_text_with_report_template() {
+        let mut sink_spec = create_test_sink_spec(Some("text"), None, None);
+        sink_spec.report_template = Some("{{ summary | truncate_to(length=20) }} ({{ findings | length }} findings)".to_string());
+        let sink = StdoutSink::new("test-sink".to_string(), &sink_spec).unwrap();
+        
+        let context = json!({
+            "summary": "Database connection pool exhausted",
+            "findings": []
+        });
+        
+        assert!(sink.send(context).await.is_ok());
+    }
+}
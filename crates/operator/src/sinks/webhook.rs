@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tracing::{error, info};
+
+use crate::{
+    sinks::{Sink, SinkCredentials},
+    Result, Error,
+    crd::{sink::SinkSpec, workflow::HttpMethod},
+};
+
+/// Header carrying the HMAC-SHA256 signature of the request body
+pub const SIGNATURE_HEADER: &str = "X-Punching-Fist-Signature";
+
+/// Most bytes of a response body kept with a delivery
+const RESPONSE_BODY_LIMIT: usize = 1024;
+
+/// Delivers workflow output to an arbitrary HTTP endpoint
+pub struct WebhookSink {
+    name: String,
+    url_template: String,
+    method: reqwest::Method,
+    headers: HashMap<String, String>,
+    body_template: Option<String>,
+    hmac_secret: Option<String>,
+    /// Header and value from the `secretRef` Secret
+    auth: Option<(String, String)>,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(name: String, spec: &SinkSpec, credentials: &SinkCredentials) -> Result<Self> {
+        let config = &spec.config;
+
+        let url_template = config.url_template.clone()
+            .ok_or_else(|| Error::Validation("Webhook sink missing urlTemplate".to_string()))?;
+        let method = match config.method.unwrap_or(HttpMethod::Post) {
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            other => {
+                return Err(Error::Validation(
+                    format!("Invalid webhook method: {:?}. Must be one of: POST, PUT", other)
+                ));
+            }
+        };
+
+        let auth = match &config.secret_ref {
+            Some(secret_ref) => {
+                let value = credentials.get(&secret_ref.key).ok_or_else(|| Error::Validation(format!(
+                    "Webhook sink key '{}' not found in Secret '{}'",
+                    secret_ref.key, secret_ref.name
+                )))?;
+                let header = config.auth_header.clone().unwrap_or_else(|| "Authorization".to_string());
+                Some((header, value.clone()))
+            }
+            None => None,
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            name,
+            url_template,
+            method,
+            headers: config.headers.clone(),
+            body_template: config.body_template.clone(),
+            hmac_secret: config.hmac_secret.clone(),
+            auth,
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for WebhookSink {
+    async fn send(&self, context: Value) -> Result<()> {
+        self.deliver(context).await.map(|_| ())
+    }
+
+    async fn deliver(&self, context: Value) -> Result<Option<Value>> {
+        let url = crate::template::render_template(&self.url_template, &context)?;
+        let body = match &self.body_template {
+            Some(template) => crate::template::render_template(template, &context)?,
+            None => serde_json::to_string(&context)
+                .map_err(|e| Error::Internal(format!("JSON serialization error: {}", e)))?,
+        };
+
+        let mut request = self.client.request(self.method.clone(), &url);
+        for (name, value) in &self.headers {
+            request = request.header(name, crate::template::render_template(value, &context)?);
+        }
+        let has_content_type = self.headers.keys().any(|name| name.eq_ignore_ascii_case("content-type"));
+        if !has_content_type && serde_json::from_str::<Value>(&body).is_ok() {
+            request = request.header(reqwest::header::CONTENT_TYPE, "application/json");
+        }
+        if let Some(secret) = &self.hmac_secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body.as_bytes()));
+        }
+        if let Some((header, value)) = &self.auth {
+            request = request.header(header, value);
+        }
+
+        let response = request.body(body).send().await
+            .map_err(|e| Error::Execution(format!("Webhook sink {} failed to call {}: {}", self.name, url, e)))?;
+        let status = response.status();
+        let text = truncate_body(&response.text().await.unwrap_or_default());
+        if !status.is_success() {
+            error!("Webhook sink {} got status {} from {}", self.name, status.as_u16(), url);
+            return Err(Error::Execution(format!(
+                "Webhook sink {} got status {} from {}: {}",
+                self.name, status.as_u16(), url, text
+            )));
+        }
+
+        info!("[{}] Delivered to {} ({})", self.name, url, status.as_u16());
+        Ok(Some(json!({ "status": status.as_u16(), "response": text })))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The first `RESPONSE_BODY_LIMIT` bytes of `body`, cut on a character
+/// boundary
+fn truncate_body(body: &str) -> String {
+    if body.len() <= RESPONSE_BODY_LIMIT {
+        return body.to_string();
+    }
+    let mut end = RESPONSE_BODY_LIMIT;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... (truncated, {} total bytes)", &body[..end], body.len())
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body` keyed with `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, extract::Path, http::{HeaderMap, StatusCode}, routing::any, Router};
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    type Delivery = (String, String, HeaderMap, String);
+
+    /// Endpoint that records each request, fails for the `broken` hook and
+    /// answers the `chatty` one at length
+    async fn mock_receiver() -> (String, mpsc::UnboundedReceiver<Delivery>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route("/hooks/{name}", any(
+            move |method: axum::http::Method, Path(name): Path<String>, headers: HeaderMap, body: Bytes| async move {
+                let response = match name.as_str() {
+                    "broken" => (StatusCode::BAD_GATEWAY, String::new()),
+                    "chatty" => (StatusCode::OK, "é".repeat(RESPONSE_BODY_LIMIT)),
+                    _ => (StatusCode::NO_CONTENT, String::new()),
+                };
+                tx.send((method.to_string(), name, headers, String::from_utf8(body.to_vec()).unwrap())).unwrap();
+                response
+            },
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), rx)
+    }
+
+    fn webhook_spec(config: Value) -> SinkSpec {
+        serde_json::from_value(json!({ "type": "webhook", "config": config })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sink_delivers_signed_body() {
+        let (base_url, mut deliveries) = mock_receiver().await;
+        let spec = webhook_spec(json!({
+            "urlTemplate": format!("{}/hooks/{{{{ workflow }}}}", base_url),
+            "method": "PUT",
+            "headers": { "X-Workflow": "{{ workflow }}" },
+            "bodyTemplate": r#"{"text": "{{ workflow }} finished: {{ status }}"}"#,
+            "hmacSecret": "s3cr3t"
+        }));
+        let sink = WebhookSink::new("zapier".to_string(), &spec, &SinkCredentials::new()).unwrap();
+
+        sink.send(json!({ "workflow": "triage", "status": "succeeded" })).await.unwrap();
+        let (method, name, headers, body) = deliveries.recv().await.unwrap();
+        assert_eq!(method, "PUT");
+        assert_eq!(name, "triage");
+        assert_eq!(body, r#"{"text": "triage finished: succeeded"}"#);
+        assert_eq!(headers["x-workflow"], "triage");
+        assert_eq!(headers["content-type"], "application/json");
+        assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), sign("s3cr3t", body.as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sink_defaults() {
+        let (base_url, mut deliveries) = mock_receiver().await;
+        let spec = webhook_spec(json!({ "urlTemplate": format!("{}/hooks/{{{{ name }}}}", base_url) }));
+        let sink = WebhookSink::new("custom".to_string(), &spec, &SinkCredentials::new()).unwrap();
+
+        // Without a body template the output is sent as JSON, unsigned
+        let context = json!({ "name": "plain", "summary": "disk full" });
+        sink.send(context.clone()).await.unwrap();
+        let (method, _, headers, body) = deliveries.recv().await.unwrap();
+        assert_eq!(method, "POST");
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), context);
+        assert!(headers.get(SIGNATURE_HEADER).is_none());
+
+        let err = sink.send(json!({ "name": "broken" })).await.unwrap_err();
+        assert!(err.to_string().contains("got status 502"), "{}", err);
+
+        let spec = webhook_spec(json!({ "urlTemplate": base_url, "method": "GET" }));
+        assert!(matches!(WebhookSink::new("custom".to_string(), &spec, &SinkCredentials::new()), Err(Error::Validation(_))));
+        let spec = webhook_spec(json!({}));
+        assert!(matches!(WebhookSink::new("custom".to_string(), &spec, &SinkCredentials::new()), Err(Error::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sink_auth_and_response() {
+        let (base_url, mut deliveries) = mock_receiver().await;
+        let spec = webhook_spec(json!({
+            "urlTemplate": format!("{}/hooks/{{{{ name }}}}", base_url),
+            "secretRef": { "name": "hook-credentials", "key": "apiKey" },
+            "authHeader": "X-Api-Key"
+        }));
+        let credentials = [("apiKey".to_string(), "k3y".to_string())].into();
+        let sink = WebhookSink::new("custom".to_string(), &spec, &credentials).unwrap();
+
+        let receipt = sink.deliver(json!({ "name": "quiet" })).await.unwrap();
+        assert_eq!(receipt, Some(json!({ "status": 204, "response": "" })));
+        let (_, _, headers, _) = deliveries.recv().await.unwrap();
+        assert_eq!(headers["x-api-key"], "k3y");
+
+        let receipt = sink.deliver(json!({ "name": "chatty" })).await.unwrap().unwrap();
+        deliveries.recv().await.unwrap();
+        assert_eq!(receipt["status"], 200);
+        let response = receipt["response"].as_str().unwrap();
+        assert!(response.ends_with(&format!("(truncated, {} total bytes)", 2 * RESPONSE_BODY_LIMIT)), "{}", response);
+
+        // The header defaults to Authorization, and the key must be there
+        let spec = webhook_spec(json!({
+            "urlTemplate": format!("{}/hooks/quiet", base_url),
+            "secretRef": { "name": "hook-credentials", "key": "token" }
+        }));
+        assert!(matches!(WebhookSink::new("custom".to_string(), &spec, &credentials), Err(Error::Validation(_))));
+        let credentials = [("token".to_string(), "Bearer t0k3n".to_string())].into();
+        WebhookSink::new("custom".to_string(), &spec, &credentials).unwrap().send(json!({})).await.unwrap();
+        let (_, _, headers, _) = deliveries.recv().await.unwrap();
+        assert_eq!(headers["authorization"], "Bearer t0k3n");
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Forwards workflow completions to WebSocket clients.
+//!
+//! Only the server side of RFC 6455 that this needs is implemented: the
+//! upgrade handshake, unfragmented text frames to the client, and ping and
+//! close frames from it.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use axum::{body::Body, extract::Request, http::{header, HeaderMap, StatusCode}, response::Response};
+use base64::Engine;
+use hyper_util::rt::TokioIo;
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tracing::{debug, warn};
+
+use crate::workflow::WorkflowEngine;
+
+/// GUID every server appends to the client's key (RFC 6455 section 1.3)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest frame accepted from a client; clients only send control frames
+const MAX_CLIENT_FRAME_BYTES: u64 = 64 * 1024;
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// Sends every workflow completion to the connected WebSocket clients as a
+/// JSON `WorkflowCompletionEvent` text message
+pub struct WebSocketSink {
+    engine: Arc<WorkflowEngine>,
+}
+
+impl WebSocketSink {
+    pub fn new(engine: Arc<WorkflowEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Forward completions to `socket` until the client disconnects
+    pub async fn forward<S>(&self, socket: S)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let mut completions = self.engine.subscribe_to_completions().await;
+        let (mut reader, mut writer) = tokio::io::split(socket);
+
+        // Frames are read in their own task so a completion never interrupts
+        // a partially read frame
+        let (control_tx, mut control_rx) = mpsc::channel(8);
+        let reading = tokio::spawn(async move {
+            while let Ok(frame) = read_frame(&mut reader).await {
+                let close = frame.0 == OPCODE_CLOSE;
+                if control_tx.send(frame).await.is_err() || close {
+                    break;
+                }
+            }
+        });
+
+        loop {
+            tokio::select! {
+                event = completions.recv() => match event {
+                    Ok(event) => {
+                        let text = match serde_json::to_string(&event) {
+                            Ok(text) => text,
+                            Err(e) => {
+                                warn!("Failed to serialize completion of workflow {}: {}", event.workflow_id, e);
+                                continue;
+                            }
+                        };
+                        if write_frame(&mut writer, OPCODE_TEXT, text.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("WebSocket client fell behind and missed {} workflow completions", missed);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                frame = control_rx.recv() => match frame {
+                    Some((OPCODE_PING, payload)) => {
+                        if write_frame(&mut writer, OPCODE_PONG, &payload).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some((OPCODE_CLOSE, _)) | None => {
+                        let _ = write_frame(&mut writer, OPCODE_CLOSE, &[]).await;
+                        break;
+                    }
+                    Some(_) => {}
+                },
+            }
+        }
+
+        reading.abort();
+        debug!("WebSocket client disconnected");
+    }
+}
+
+/// Answer a WebSocket upgrade request, running `on_upgrade` with the
+/// connection once the client switches protocols. Returns why the request
+/// isn't a valid upgrade otherwise.
+pub fn upgrade<F, Fut>(request: Request, on_upgrade: F) -> Result<Response, &'static str>
+where
+    F: FnOnce(TokioIo<hyper::upgrade::Upgraded>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    let headers = request.headers();
+    if !header_has_token(headers, header::CONNECTION, "upgrade")
+        || !header_has_token(headers, header::UPGRADE, "websocket")
+    {
+        return Err("Expected a WebSocket upgrade request");
+    }
+    if headers.get(header::SEC_WEBSOCKET_VERSION).map(|v| v.as_bytes()) != Some(b"13") {
+        return Err("Unsupported WebSocket version, expected 13");
+    }
+    let key = headers.get(header::SEC_WEBSOCKET_KEY).ok_or("Missing Sec-WebSocket-Key header")?;
+    let accept = accept_key(key.as_bytes());
+
+    let upgrading = hyper::upgrade::on(request);
+    tokio::spawn(async move {
+        match upgrading.await {
+            Ok(upgraded) => on_upgrade(TokioIo::new(upgraded)).await,
+            Err(e) => warn!("WebSocket upgrade failed: {}", e),
+        }
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept)
+        .body(Body::empty())
+        .expect("static upgrade response is valid"))
+}
+
+fn header_has_token(headers: &HeaderMap, name: header::HeaderName, token: &str) -> bool {
+    headers.get_all(name).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(token))
+}
+
+/// `Sec-WebSocket-Accept` for the client's `Sec-WebSocket-Key`
+fn accept_key(key: &[u8]) -> String {
+    let mut sha1 = Sha1::new();
+    sha1.update(key);
+    sha1.update(WEBSOCKET_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(sha1.finalize())
+}
+
+/// Write one unmasked, unfragmented frame, as servers send them
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await?;
+    writer.flush().await
+}
+
+/// Read one frame from the client, returning its opcode and unmasked payload
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let opcode = head[0] & 0x0F;
+    let masked = head[1] & 0x80 != 0;
+    let len = match head[1] & 0x7F {
+        126 => reader.read_u16().await? as u64,
+        127 => reader.read_u64().await?,
+        len => len as u64,
+    };
+    if len > MAX_CLIENT_FRAME_BYTES {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "WebSocket frame too large"));
+    }
+
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok((opcode, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{store::{SqliteStore, Store}, workflow::StepExecutor};
+    use axum::{routing::get, Router};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_forwards_completions() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store: Arc<dyn Store> = Arc::new(SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap());
+        store.init().await.unwrap();
+        let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:9".parse().unwrap())).unwrap();
+        let engine = Arc::new(WorkflowEngine::new(store, Arc::new(StepExecutor::new(client, "default".to_string()))));
+        engine.clone().start().await;
+
+        let sink_engine = engine.clone();
+        let app = Router::new().route("/ws", get(move |request: Request| async move {
+            let sink = WebSocketSink::new(sink_engine);
+            upgrade(request, move |socket| async move { sink.forward(socket).await }).unwrap()
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+        socket.write_all(format!(
+            "GET /ws HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+             Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+            addr
+        ).as_bytes()).await.unwrap();
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            response.push(socket.read_u8().await.unwrap());
+        }
+        let response = String::from_utf8(response).unwrap().to_lowercase();
+        assert!(response.starts_with("http/1.1 101"), "{}", response);
+        assert!(response.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="));
+
+        // Give the server a moment to subscribe before the workflow completes
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let workflow = serde_json::from_value(json!({
+            "apiVersion": "punchingfist.io/v1alpha1",
+            "kind": "Workflow",
+            "metadata": { "name": "triage", "namespace": "default" },
+            "spec": {
+                "runtime": { "image": "busybox:latest", "llmConfig": { "provider": "mock", "model": "mock" } },
+                "steps": [],
+                "sinks": []
+            }
+        })).unwrap();
+        let workflow_id = engine.queue_workflow(workflow).await.unwrap();
+
+        let (opcode, payload) = tokio::time::timeout(std::time::Duration::from_secs(5), read_frame(&mut socket))
+            .await.unwrap().unwrap();
+        assert_eq!(opcode, OPCODE_TEXT);
+        let event: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(event["workflow_id"], workflow_id.to_string());
+        assert_eq!(event["status"], "succeeded");
+
+        // The server answers a close with a close
+        socket.write_all(&[0x80 | OPCODE_CLOSE, 0x80, 0, 0, 0, 0]).await.unwrap();
+        assert_eq!(read_frame(&mut socket).await.unwrap().0, OPCODE_CLOSE);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // Example handshake from RFC 6455 section 1.3
+        assert_eq!(accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[tokio::test]
+    async fn test_frames() {
+        let (mut client, mut server) = tokio::io::duplex(1 << 20);
+
+        let long = "x".repeat(300);
+        write_frame(&mut server, OPCODE_TEXT, b"hi").await.unwrap();
+        write_frame(&mut server, OPCODE_TEXT, long.as_bytes()).await.unwrap();
+        assert_eq!(read_frame(&mut client).await.unwrap(), (OPCODE_TEXT, b"hi".to_vec()));
+        assert_eq!(read_frame(&mut client).await.unwrap(), (OPCODE_TEXT, long.into_bytes()));
+
+        // Masked ping, as a client sends it
+        let mask = [1u8, 2, 3, 4];
+        let mut frame = vec![0x80 | OPCODE_PING, 0x80 | 3];
+        frame.extend_from_slice(&mask);
+        frame.extend(b"abc".iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        client.write_all(&frame).await.unwrap();
+        assert_eq!(read_frame(&mut server).await.unwrap(), (OPCODE_PING, b"abc".to_vec()));
+
+        client.write_all(&[0x80 | OPCODE_TEXT, 127]).await.unwrap();
+        client.write_all(&(1u64 << 40).to_be_bytes()).await.unwrap();
+        assert!(read_frame(&mut server).await.is_err());
+    }
+}
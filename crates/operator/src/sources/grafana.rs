@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    sources::{
+        webhook::{AlertManagerAlert, AlertManagerWebhook},
+        PrometheusReceiver,
+    },
+    Error, Result,
+};
+
+/// Receiver for Grafana unified alerting webhook payloads. Alerts are
+/// converted to the AlertManager shape the webhook handler processes, with
+/// Grafana's evaluated values and links kept as annotations.
+pub struct GrafanaReceiver;
+
+impl GrafanaReceiver {
+    /// Parse a Grafana payload and check it the same way AlertManager
+    /// payloads are checked.
+    pub fn parse(payload: Value) -> Result<AlertManagerWebhook> {
+        let payload: GrafanaWebhook = serde_json::from_value(payload)
+            .map_err(|e| Error::Validation(format!("Not a Grafana alerting payload: {}", e)))?;
+        let payload = AlertManagerWebhook::from(payload);
+        PrometheusReceiver::validate(&payload)?;
+        Ok(payload)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GrafanaWebhook {
+    #[serde(default)]
+    receiver: String,
+    status: String,
+    alerts: Vec<GrafanaAlert>,
+    #[serde(default)]
+    group_labels: HashMap<String, String>,
+    #[serde(default)]
+    common_labels: HashMap<String, String>,
+    #[serde(default)]
+    common_annotations: HashMap<String, String>,
+    #[serde(default, rename = "externalURL")]
+    external_url: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    group_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GrafanaAlert {
+    status: String,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+    starts_at: DateTime<Utc>,
+    #[serde(default)]
+    ends_at: Option<DateTime<Utc>>,
+    #[serde(default, rename = "generatorURL")]
+    generator_url: String,
+    #[serde(default)]
+    fingerprint: String,
+    /// Query results the alert rule was evaluated with
+    #[serde(default)]
+    value_string: String,
+    #[serde(default, rename = "dashboardURL")]
+    dashboard_url: String,
+    #[serde(default, rename = "panelURL")]
+    panel_url: String,
+    #[serde(default, rename = "silenceURL")]
+    silence_url: String,
+}
+
+impl From<GrafanaWebhook> for AlertManagerWebhook {
+    fn from(payload: GrafanaWebhook) -> Self {
+        Self {
+            receiver: payload.receiver,
+            status: payload.status,
+            alerts: payload.alerts.into_iter().map(AlertManagerAlert::from).collect(),
+            group_labels: payload.group_labels,
+            common_labels: payload.common_labels,
+            common_annotations: payload.common_annotations,
+            external_url: payload.external_url,
+            version: payload.version,
+            group_key: payload.group_key,
+        }
+    }
+}
+
+impl From<GrafanaAlert> for AlertManagerAlert {
+    fn from(alert: GrafanaAlert) -> Self {
+        let mut annotations = alert.annotations;
+        for (key, value) in [
+            ("grafana_value_string", alert.value_string),
+            ("grafana_dashboard_url", alert.dashboard_url),
+            ("grafana_panel_url", alert.panel_url),
+            ("grafana_silence_url", alert.silence_url),
+        ] {
+            if !value.is_empty() {
+                annotations.entry(key.to_string()).or_insert(value);
+            }
+        }
+
+        Self {
+            status: alert.status,
+            labels: alert.labels,
+            annotations,
+            starts_at: alert.starts_at,
+            // Firing alerts end at Go's zero time, which would expire them at once
+            ends_at: alert.ends_at.filter(|ends_at| *ends_at > alert.starts_at),
+            generator_url: alert.generator_url,
+            fingerprint: alert.fingerprint,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../../tests/fixtures/grafana_webhook.json")).unwrap()
+    }
+
+    #[test]
+    fn test_parse_grafana_payload() {
+        let payload = GrafanaReceiver::parse(fixture()).unwrap();
+        assert_eq!(payload.status, "firing");
+        assert_eq!(payload.external_url, "https://grafana.example.com/");
+        assert_eq!(payload.alerts.len(), 2);
+
+        let firing = &payload.alerts[0];
+        assert_eq!(firing.status, "firing");
+        assert_eq!(firing.labels["alertname"], "HighPodRestarts");
+        assert_eq!(firing.labels["pod"], "web-app-7d9f8b6c5-x2k4p");
+        assert_eq!(firing.annotations["summary"], "Pod is crash looping");
+        assert_eq!(firing.fingerprint, "5b6e3c1f8a9d2e47");
+        assert_eq!(firing.starts_at.to_rfc3339(), "2024-05-14T09:31:00+00:00");
+        assert_eq!(firing.ends_at, None);
+        assert!(firing.annotations["grafana_value_string"].contains("var='B'"));
+        assert_eq!(firing.annotations["grafana_dashboard_url"], "https://grafana.example.com/d/k8s-pods?orgId=1");
+        assert_eq!(firing.annotations["grafana_panel_url"], "https://grafana.example.com/d/k8s-pods?orgId=1&viewPanel=4");
+
+        // Empty values and links aren't kept
+        let resolved = &payload.alerts[1];
+        assert_eq!(resolved.status, "resolved");
+        assert_eq!(resolved.ends_at.unwrap().to_rfc3339(), "2024-05-14T09:30:00+00:00");
+        assert!(!resolved.annotations.contains_key("grafana_value_string"));
+        assert!(!resolved.annotations.contains_key("grafana_dashboard_url"));
+        assert!(resolved.annotations.contains_key("grafana_silence_url"));
+    }
+
+    #[test]
+    fn test_parse_invalid_grafana_payload() {
+        let mut payload = fixture();
+        payload["alerts"] = json!([]);
+        let err = GrafanaReceiver::parse(payload).unwrap_err();
+        assert!(err.to_string().contains("no alerts"), "{}", err);
+
+        let mut payload = fixture();
+        payload["alerts"][0].as_object_mut().unwrap().remove("startsAt");
+        let err = GrafanaReceiver::parse(payload).unwrap_err();
+        assert!(err.to_string().contains("startsAt"), "{}", err);
+    }
+}
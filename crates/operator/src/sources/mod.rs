@@ -1,3 +1,9 @@
 pub mod webhook;
+pub mod prometheus;
+pub mod grafana;
+pub mod schedule;
 
-pub use webhook::WebhookHandler; 
\ No newline at end of file
+pub use webhook::WebhookHandler;
+pub use prometheus::PrometheusReceiver;
+pub use grafana::GrafanaReceiver;
+pub use schedule::ScheduleHandler;
@@ -0,0 +1,77 @@
+use serde_json::Value;
+
+use crate::{sources::webhook::AlertManagerWebhook, Error, Result};
+
+/// Receiver for Prometheus AlertManager webhook payloads
+pub struct PrometheusReceiver;
+
+impl PrometheusReceiver {
+    /// Parse an AlertManager payload and validate it
+    pub fn parse(payload: Value) -> Result<AlertManagerWebhook> {
+        let payload: AlertManagerWebhook = serde_json::from_value(payload)
+            .map_err(|e| Error::Validation(format!("Not an AlertManager payload: {}", e)))?;
+        Self::validate(&payload)?;
+        Ok(payload)
+    }
+
+    /// Check that a payload carries at least one alert and that every alert
+    /// has a status and at least one label.
+    pub fn validate(payload: &AlertManagerWebhook) -> Result<()> {
+        if payload.alerts.is_empty() {
+            return Err(Error::Validation("Payload contains no alerts".to_string()));
+        }
+
+        for (idx, alert) in payload.alerts.iter().enumerate() {
+            if alert.status.trim().is_empty() {
+                return Err(Error::Validation(format!("Alert {} is missing a status", idx)));
+            }
+            if alert.labels.is_empty() {
+                return Err(Error::Validation(format!("Alert {} has no labels", idx)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(alerts: serde_json::Value) -> AlertManagerWebhook {
+        serde_json::from_value(json!({
+            "receiver": "punching-fist",
+            "status": "firing",
+            "alerts": alerts,
+            "groupLabels": {},
+            "commonLabels": {},
+            "commonAnnotations": {},
+            "externalURL": "http://alertmanager:9093",
+            "version": "4",
+            "groupKey": "{}:{}"
+        })).unwrap()
+    }
+
+    fn alert(status: &str, labels: serde_json::Value) -> serde_json::Value {
+        json!({
+            "status": status,
+            "labels": labels,
+            "annotations": {},
+            "startsAt": "2024-01-01T00:00:00Z",
+            "endsAt": null,
+            "generatorURL": "http://prometheus:9090",
+            "fingerprint": "abc123"
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = payload(json!([alert("firing", json!({ "alertname": "HighCPU" }))]));
+        assert!(PrometheusReceiver::validate(&valid).is_ok());
+
+        assert!(PrometheusReceiver::validate(&payload(json!([]))).is_err());
+        assert!(PrometheusReceiver::validate(&payload(json!([alert("", json!({ "alertname": "HighCPU" }))]))).is_err());
+        assert!(PrometheusReceiver::validate(&payload(json!([alert("firing", json!({}))]))).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use kube::Client;
+use serde_json::json;
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    crd::{source::Source, Workflow},
+    store::{Store, SourceEvent, SourceType},
+    workflow::WorkflowEngine,
+    Error, Result,
+};
+
+/// How far ahead to look for the next run before deciding a schedule never
+/// runs; long enough to reach a 29 February
+const MAX_SEARCH_DAYS: u32 = 366 * 8;
+
+const MONTH_NAMES: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const DAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A five-field cron expression (minute, hour, day of month, month, day of
+/// week), each field holding a bit per value it matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// A day runs when it matches both day fields if either is `*`, and
+    /// when it matches either otherwise, as in cron
+    any_day: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression. Fields take `*`, values, `a-b` ranges,
+    /// `/n` steps and comma-separated lists of those; months and days of
+    /// the week also take three-letter names, and Sunday is 0 or 7. The
+    /// `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` shorthands
+    /// are accepted too.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let invalid = |reason: String| Error::Validation(format!("Invalid cron expression '{}': {}", expression, reason));
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid(format!("expected 5 fields, found {}", fields.len())));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, DAY_NAMES).map_err(invalid)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, &[]).map_err(invalid)?,
+            hours: parse_field(fields[1], 0, 23, &[]).map_err(invalid)?,
+            days_of_month: parse_field(fields[2], 1, 31, &[]).map_err(invalid)?,
+            months: parse_field(fields[3], 1, 12, MONTH_NAMES).map_err(invalid)?,
+            days_of_week,
+            any_day: fields[2].starts_with('*') || fields[4].starts_with('*'),
+        })
+    }
+
+    /// The first run strictly after `after`, with the fields read as local
+    /// time in `timezone`. Local times skipped by a daylight saving change
+    /// don't run, and those repeated by one run once. `None` if the
+    /// schedule never runs, e.g. on 30 February.
+    pub fn next_after(&self, after: DateTime<Utc>, timezone: Tz) -> Option<DateTime<Utc>> {
+        let mut date = after.with_timezone(&timezone).date_naive();
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.runs_on(date) {
+                for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                    for minute in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                        let Some(local) = date.and_hms_opt(hour, minute, 0) else { continue };
+                        let Some(run) = timezone.from_local_datetime(&local).earliest() else { continue };
+                        let run = run.with_timezone(&Utc);
+                        if run > after {
+                            return Some(run);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        let day_of_month = self.days_of_month & (1 << date.day()) != 0;
+        let day_of_week = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        let day = if self.any_day { day_of_month && day_of_week } else { day_of_month || day_of_week };
+        day && self.months & (1 << date.month()) != 0
+    }
+}
+
+/// Bits for the values a cron field matches, in `min..=max`
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> std::result::Result<u64, String> {
+    let value = |s: &str| -> std::result::Result<u32, String> {
+        let value = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(index) => min + index as u32,
+            None => s.parse().map_err(|_| format!("invalid value '{}'", s))?,
+        };
+        if !(min..=max).contains(&value) {
+            return Err(format!("value {} is outside {}-{}", value, min, max));
+        }
+        Ok(value)
+    };
+
+    let mut bits = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<usize>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("invalid step '{}'", step)),
+            },
+            None => (part, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            // `n/step` runs from n to the end of the field
+            let start = value(range)?;
+            (start, if step.is_some() { max } else { start })
+        };
+        if start > end {
+            return Err(format!("range {}-{} is backwards", start, end));
+        }
+        for v in (start..=end).step_by(step.unwrap_or(1)) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleConfig {
+    pub source_name: String,
+    /// Cron expression the workflow is triggered on
+    pub cron: String,
+    /// IANA timezone the cron expression is read in
+    pub timezone: String,
+    pub workflow_name: String,
+    pub namespace: String,
+}
+
+/// A registered schedule and the task firing it
+type RunningSchedule = (ScheduleConfig, JoinHandle<()>);
+
+pub struct ScheduleHandler {
+    store: Arc<dyn Store>,
+    client: Option<Client>,
+    workflow_engine: Option<Arc<WorkflowEngine>>,
+    /// Running schedules by `namespace/name` of their source
+    schedules: Arc<RwLock<HashMap<String, RunningSchedule>>>,
+}
+
+impl ScheduleHandler {
+    pub fn new(store: Arc<dyn Store>, client: Option<Client>) -> Self {
+        Self {
+            store,
+            client,
+            workflow_engine: None,
+            schedules: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_workflow_engine(mut self, engine: Arc<WorkflowEngine>) -> Self {
+        self.workflow_engine = Some(engine);
+        self
+    }
+
+    /// Start triggering the source's workflow on its schedule, replacing
+    /// the schedule it was registered with before. A schedule registered
+    /// again unchanged keeps running.
+    pub async fn register_schedule(self: &Arc<Self>, config: ScheduleConfig) -> Result<()> {
+        let schedule = CronSchedule::parse(&config.cron)?;
+        let timezone: Tz = config.timezone.parse()
+            .map_err(|_| Error::Validation(format!("Unknown timezone '{}'", config.timezone)))?;
+
+        let key = format!("{}/{}", config.namespace, config.source_name);
+        let mut schedules = self.schedules.write().await;
+        if let Some((existing, task)) = schedules.get(&key) {
+            if *existing == config && !task.is_finished() {
+                return Ok(());
+            }
+        }
+
+        info!("Registering schedule '{}' ({}) for source {}", config.cron, config.timezone, key);
+        let handler = self.clone();
+        let task_config = config.clone();
+        let task = tokio::spawn(async move {
+            handler.run_schedule(task_config, schedule, timezone).await;
+        });
+        if let Some((_, previous)) = schedules.insert(key, (config, task)) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Trigger the workflow at each run until the source is deleted. Runs
+    /// are found from the wall clock, so after a restart the schedule picks
+    /// up at its next run rather than replaying the ones it missed.
+    async fn run_schedule(&self, config: ScheduleConfig, schedule: CronSchedule, timezone: Tz) {
+        let mut after = Utc::now();
+        loop {
+            let Some(next) = schedule.next_after(after, timezone) else {
+                warn!("Schedule '{}' of source {}/{} never runs", config.cron, config.namespace, config.source_name);
+                return;
+            };
+            debug!("Next run of source {}/{} is at {}", config.namespace, config.source_name, next);
+            tokio::time::sleep((next - Utc::now()).to_std().unwrap_or_default()).await;
+
+            if !self.source_exists(&config).await {
+                info!("Source {}/{} was deleted, stopping its schedule", config.namespace, config.source_name);
+                return;
+            }
+
+            match self.fire(&config, next).await {
+                Ok(true) => info!("Triggered workflow {} for the {} run of source {}", config.workflow_name, next, config.source_name),
+                Ok(false) => debug!("Run at {} of source {} was already triggered", next, config.source_name),
+                Err(e) => warn!("Failed to trigger the {} run of source {}: {}", next, config.source_name, e),
+            }
+            after = next;
+        }
+    }
+
+    /// Create the event for the source's run due at `scheduled_at` and
+    /// queue its workflow. Returns false, without queueing, when the run
+    /// already has an event, e.g. because another replica fired it. A run
+    /// whose workflow can't be queued is released again.
+    pub async fn fire(&self, config: &ScheduleConfig, scheduled_at: DateTime<Utc>) -> Result<bool> {
+        let client = self.client.as_ref()
+            .ok_or_else(|| Error::Kubernetes("Kubernetes client not available".to_string()))?;
+
+        let source_event_id = Uuid::new_v4();
+        let source_event = SourceEvent {
+            id: source_event_id,
+            source_name: config.source_name.clone(),
+            source_type: SourceType::Schedule,
+            event_data: json!({
+                "cron": config.cron,
+                "timezone": config.timezone,
+                "scheduledAt": scheduled_at,
+            }),
+            workflow_triggered: Some(config.workflow_name.clone()),
+            received_at: Utc::now(),
+        };
+        if !self.store.save_scheduled_source_event(source_event, &config.namespace, scheduled_at).await? {
+            return Ok(false);
+        }
+
+        let queued = async {
+            let api: kube::Api<Workflow> = kube::Api::namespaced(client.clone(), &config.namespace);
+            let workflow = api.get(&config.workflow_name).await
+                .map_err(|e| Error::Kubernetes(format!("Failed to get workflow {}: {}", config.workflow_name, e)))?;
+
+            match &self.workflow_engine {
+                Some(engine) => engine.queue_workflow(workflow).await.map(Some),
+                None => {
+                    warn!("Workflow engine not available, cannot trigger workflow");
+                    Ok(None)
+                }
+            }
+        }.await;
+        let workflow_id = match queued {
+            Ok(workflow_id) => workflow_id,
+            Err(e) => {
+                if let Err(release) = self.store.delete_source_event(source_event_id).await {
+                    warn!("Failed to release the {} run of source {}/{}: {}", scheduled_at, config.namespace, config.source_name, release);
+                }
+                return Err(e);
+            }
+        };
+
+        if let Some(workflow_id) = workflow_id {
+            self.store.set_source_event_workflow(source_event_id, workflow_id).await?;
+        }
+        Ok(true)
+    }
+
+    /// Whether the source is still in the cluster. Sources that can't be
+    /// looked up are assumed to exist, so an API hiccup doesn't stop them.
+    async fn source_exists(&self, config: &ScheduleConfig) -> bool {
+        let Some(client) = &self.client else { return true };
+        let api: kube::Api<Source> = kube::Api::namespaced(client.clone(), &config.namespace);
+        match api.get_opt(&config.source_name).await {
+            Ok(source) => source.is_some(),
+            Err(e) => {
+                warn!("Failed to look up source {}/{}: {}", config.namespace, config.source_name, e);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_cron() {
+        assert!(CronSchedule::parse("*/15 9-17 * * mon-fri").is_ok());
+        assert_eq!(CronSchedule::parse("@daily").unwrap(), CronSchedule::parse("0 0 * * *").unwrap());
+        assert_eq!(CronSchedule::parse("0 0 * * 7").unwrap(), CronSchedule::parse("0 0 * * SUN").unwrap());
+
+        for invalid in ["* * * *", "60 * * * *", "* * 0 * *", "5-1 * * * *", "*/0 * * * *", "0 0 * foo *"] {
+            assert!(CronSchedule::parse(invalid).is_err(), "{} should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        let every_quarter_hour = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(every_quarter_hour.next_after(at("2024-03-01T10:07:30Z"), Tz::UTC), Some(at("2024-03-01T10:15:00Z")));
+        // Runs are strictly after the given time
+        assert_eq!(every_quarter_hour.next_after(at("2024-03-01T10:15:00Z"), Tz::UTC), Some(at("2024-03-01T10:30:00Z")));
+
+        let weekdays = CronSchedule::parse("30 9 * * mon-fri").unwrap();
+        assert_eq!(weekdays.next_after(at("2024-03-01T10:00:00Z"), Tz::UTC), Some(at("2024-03-04T09:30:00Z")));
+
+        // Restricting both day fields runs on days matching either
+        let first_or_sunday = CronSchedule::parse("0 0 1 * 0").unwrap();
+        assert_eq!(first_or_sunday.next_after(at("2024-03-01T12:00:00Z"), Tz::UTC), Some(at("2024-03-03T00:00:00Z")));
+
+        let leap_day = CronSchedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(leap_day.next_after(at("2024-03-01T00:00:00Z"), Tz::UTC), Some(at("2028-02-29T00:00:00Z")));
+        assert_eq!(CronSchedule::parse("0 0 30 2 *").unwrap().next_after(at("2024-03-01T00:00:00Z"), Tz::UTC), None);
+    }
+
+    #[tokio::test]
+    async fn test_fire_releases_run_without_workflow() {
+        // An API server without the workflow
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, axum::Router::new()).await.unwrap() });
+        let client = Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap();
+
+        let store = Arc::new(crate::store::SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let handler = ScheduleHandler::new(store.clone(), Some(client));
+        let config = ScheduleConfig {
+            source_name: "nightly".to_string(),
+            cron: "0 0 * * *".to_string(),
+            timezone: "UTC".to_string(),
+            workflow_name: "missing".to_string(),
+            namespace: "default".to_string(),
+        };
+
+        let scheduled_at = at("2024-03-01T00:00:00Z");
+        assert!(handler.fire(&config, scheduled_at).await.is_err());
+        assert!(store.list_source_events("nightly", 10).await.unwrap().is_empty());
+        // Released, so firing the run again tries again rather than finding it claimed
+        assert!(handler.fire(&config, scheduled_at).await.is_err());
+    }
+
+    #[test]
+    fn test_next_after_in_timezone() {
+        let nine_am = CronSchedule::parse("0 9 * * *").unwrap();
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        assert_eq!(nine_am.next_after(at("2024-03-01T12:00:00Z"), new_york), Some(at("2024-03-01T14:00:00Z")));
+        // Daylight saving time starts on 10 March
+        assert_eq!(nine_am.next_after(at("2024-03-10T12:00:00Z"), new_york), Some(at("2024-03-10T13:00:00Z")));
+
+        // 02:30 doesn't exist on the day clocks go forward
+        let half_two = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(half_two.next_after(at("2024-03-10T00:00:00Z"), new_york), Some(at("2024-03-11T06:30:00Z")));
+    }
+}
@@ -1,31 +1,71 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
+use schemars::JsonSchema;
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use kube::Client;
 
 use crate::{
     store::{
-        Alert, AlertStatus, AlertSeverity, Store, SourceEvent, SourceType,
+        Alert, AlertStatus, AlertSeverity, DeduplicationResult, Store, SourceEvent, SourceType,
     },
     Result,
-    crd::Workflow,
+    crd::{source::WebhookReceiver, Workflow},
     workflow::WorkflowEngine,
 };
 
 #[derive(Debug, Clone)]
 pub struct WebhookConfig {
     pub source_name: String,
+    pub source_id: Option<Uuid>,
     pub path: String,
     pub filters: HashMap<String, Vec<String>>,
     pub workflow_name: String,
     pub trigger_workflow: Option<String>,
+    /// Severity ("critical", "warning", "info") to the workflow triggered for it
+    pub severity_routing: HashMap<String, String>,
     pub namespace: String,
+    /// Resend the finished workflow of an alert to its sinks when a re-fire
+    /// updates the alert
+    pub renotify_on_update: bool,
+    /// Format of the payloads posted to the webhook
+    pub receiver: WebhookReceiver,
 }
 
+impl WebhookConfig {
+    /// Workflow to trigger for an alert of the given severity, falling back
+    /// to the source's default workflow when no route matches
+    pub fn workflow_for_severity(&self, severity: AlertSeverity) -> &str {
+        let default = self.trigger_workflow.as_deref().unwrap_or(&self.workflow_name);
+        route_by_severity(&self.severity_routing, severity, default)
+    }
+}
+
+/// Workflow routed to for an alert of the given severity, matching routes
+/// case-insensitively, or `default` when no route matches
+pub fn route_by_severity<'a>(routing: &'a HashMap<String, String>, severity: AlertSeverity, default: &'a str) -> &'a str {
+    let severity = match severity {
+        AlertSeverity::Critical => "critical",
+        AlertSeverity::Warning => "warning",
+        AlertSeverity::Info => "info",
+    };
+    
+    routing.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(severity))
+        .map(|(_, workflow)| workflow.as_str())
+        .unwrap_or(default)
+}
+
+/// How often to look for alerts that are about to expire
+const ALERT_EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Alerts whose `ends_at` falls within this many minutes are resolved
+const ALERT_EXPIRY_WINDOW_MINUTES: i64 = 1;
+
 pub struct WebhookHandler {
     store: Arc<dyn Store>,
     client: Option<Client>,
@@ -34,7 +74,7 @@ pub struct WebhookHandler {
 }
 
 // AlertManager webhook payload structures
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct AlertManagerWebhook {
     pub receiver: String,
     pub status: String,
@@ -52,7 +92,7 @@ pub struct AlertManagerWebhook {
     pub group_key: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct AlertManagerAlert {
     pub status: String,
     pub labels: HashMap<String, String>,
@@ -81,28 +121,11 @@ impl WebhookHandler {
         self
     }
 
-    pub async fn register_webhook(
-        &self,
-        source_name: &str,
-        path: &str,
-        filters: HashMap<String, Vec<String>>,
-        workflow_name: String,
-        trigger_workflow: Option<String>,
-        namespace: String,
-    ) -> Result<()> {
+    pub async fn register_webhook(&self, config: WebhookConfig) -> Result<()> {
         let mut webhooks = self.webhook_configs.write().await;
-        
-        let config = WebhookConfig {
-            source_name: source_name.to_string(),
-            path: path.to_string(),
-            filters,
-            workflow_name,
-            trigger_workflow,
-            namespace,
-        };
 
-        info!("Registered webhook for source {} at path {}", source_name, path);
-        webhooks.insert(path.to_string(), config);
+        info!("Registered webhook for source {} at path {}", config.source_name, config.path);
+        webhooks.insert(config.path.clone(), config);
         
         Ok(())
     }
@@ -139,64 +162,57 @@ impl WebhookHandler {
             
             let fingerprint = Alert::generate_fingerprint(&alert_name, &alert.labels);
 
-            // Check for existing alert with same fingerprint
-            let existing_alert = self.store.get_alert_by_fingerprint(&fingerprint).await?;
-
-            let alert_id = if let Some(existing) = existing_alert {
-                info!("Found existing alert with fingerprint {}", fingerprint);
-                
-                // Update existing alert if it was resolved
-                if existing.status == AlertStatus::Resolved {
-                    info!("Reopening resolved alert {}", existing.id);
-                    self.store.update_alert_status(existing.id, AlertStatus::Received).await?;
-                    self.store.update_alert_timing(existing.id, "starts_at", alert.starts_at).await?;
-                    if let Some(ends_at) = alert.ends_at {
-                        self.store.update_alert_timing(existing.id, "ends_at", ends_at).await?;
-                    }
+            let severity = self.determine_severity(&alert.labels);
+            let mut annotations = alert.annotations.clone();
+            annotations.insert(Alert::SOURCE_NAMESPACE_ANNOTATION.to_string(), webhook_config.namespace.clone());
+            let new_alert = Alert {
+                id: Uuid::new_v4(),
+                external_id: Some(alert.fingerprint.clone()),
+                fingerprint,
+                status: AlertStatus::Received,
+                severity,
+                alert_name,
+                summary: alert.annotations.get("summary").cloned(),
+                description: alert.annotations.get("description").cloned(),
+                labels: alert.labels.clone(),
+                annotations,
+                source_id: webhook_config.source_id,
+                workflow_id: None,
+                ai_analysis: None,
+                ai_confidence: None,
+                auto_resolved: false,
+                starts_at: alert.starts_at,
+                ends_at: alert.ends_at,
+                received_at: Utc::now(),
+                triage_started_at: None,
+                triage_completed_at: None,
+                resolved_at: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            };
+
+            // Reuse the unresolved alert with the same fingerprint, if any
+            let (alert_id, updated) = match self.store.get_or_create_alert_by_fingerprint(new_alert).await? {
+                DeduplicationResult::New(created) => {
+                    info!("Created new alert {} with fingerprint {}", created.id, created.fingerprint);
+                    (created.id, false)
+                }
+                DeduplicationResult::Duplicate(existing) => {
+                    info!("Found existing alert {} with fingerprint {}", existing.id, existing.fingerprint);
+                    (existing.id, false)
+                }
+                DeduplicationResult::Updated(existing) => {
+                    info!("Updated existing alert {} with fingerprint {}", existing.id, existing.fingerprint);
+                    (existing.id, true)
                 }
-                
-                existing.id
-            } else {
-                // Create new alert
-                let severity = self.determine_severity(&alert.labels);
-                
-                let new_alert = Alert {
-                    id: Uuid::new_v4(),
-                    external_id: Some(alert.fingerprint.clone()),
-                    fingerprint,
-                    status: AlertStatus::Received,
-                    severity,
-                    alert_name,
-                    summary: alert.annotations.get("summary").cloned(),
-                    description: alert.annotations.get("description").cloned(),
-                    labels: alert.labels.clone(),
-                    annotations: alert.annotations.clone(),
-                    source_id: None, // TODO: link to Source CR
-                    workflow_id: None,
-                    ai_analysis: None,
-                    ai_confidence: None,
-                    auto_resolved: false,
-                    starts_at: alert.starts_at,
-                    ends_at: alert.ends_at,
-                    received_at: Utc::now(),
-                    triage_started_at: None,
-                    triage_completed_at: None,
-                    resolved_at: None,
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                };
-
-                self.store.save_alert(new_alert.clone()).await?;
-                info!("Created new alert {} with fingerprint {}", new_alert.id, new_alert.fingerprint);
-                
-                new_alert.id
             };
 
             processed_alert_ids.push(alert_id);
 
             // Create source event
+            let source_event_id = Uuid::new_v4();
             let source_event = SourceEvent {
-                id: Uuid::new_v4(),
+                id: source_event_id,
                 source_name: webhook_config.source_name.clone(),
                 source_type: SourceType::Webhook,
                 event_data: serde_json::to_value(&alert)?,
@@ -206,28 +222,41 @@ impl WebhookHandler {
 
             self.store.save_source_event(source_event).await?;
             
+            // Fetch the full alert object from store
+            let alert = self.store.get_alert(alert_id).await?
+                .ok_or_else(|| crate::Error::NotFound(format!("Alert {} not found", alert_id)))?;
+            
+            // A re-fire of an alert that already has a workflow doesn't start
+            // another one, but may resend its result if it brought news
+            if alert.workflow_id.is_some() {
+                if updated && webhook_config.renotify_on_update {
+                    if let Some(engine) = &self.workflow_engine {
+                        if let Err(e) = engine.renotify_alert(&alert).await {
+                            warn!("Failed to renotify sinks for updated alert {}: {}", alert_id, e);
+                        }
+                    }
+                }
+                continue;
+            }
+            
             // Trigger workflow execution if configured
-            if webhook_config.trigger_workflow.is_some() || !webhook_config.workflow_name.is_empty() {
-                // Fetch the full alert object from store
-                let alert = self.store.get_alert(alert_id).await?
-                    .ok_or_else(|| crate::Error::NotFound(format!("Alert {} not found", alert_id)))?;
-                
-                // Determine which workflow to trigger
-                let workflow_to_trigger = webhook_config.trigger_workflow
-                    .as_ref()
-                    .unwrap_or(&webhook_config.workflow_name);
-                
+            let workflow_to_trigger = webhook_config.workflow_for_severity(severity);
+            if !workflow_to_trigger.is_empty() {
                 // Trigger the workflow
-                if let Err(e) = self.trigger_workflow(workflow_to_trigger, &webhook_config.namespace, &alert).await {
-                    warn!(
+                match self.trigger_workflow(workflow_to_trigger, &webhook_config.namespace, &alert).await {
+                    Ok(workflow_id) => {
+                        info!(
+                            "Successfully triggered workflow {} for alert {}",
+                            workflow_to_trigger, alert_id
+                        );
+                        if let Some(workflow_id) = workflow_id {
+                            self.store.set_source_event_workflow(source_event_id, workflow_id).await?;
+                        }
+                    }
+                    Err(e) => warn!(
                         "Failed to trigger workflow {} for alert {}: {}",
                         workflow_to_trigger, alert_id, e
-                    );
-                } else {
-                    info!(
-                        "Successfully triggered workflow {} for alert {}",
-                        workflow_to_trigger, alert_id
-                    );
+                    ),
                 }
             }
         }
@@ -235,6 +264,35 @@ impl WebhookHandler {
         Ok(processed_alert_ids)
     }
 
+    /// Periodically resolve alerts that AlertManager has marked as ending
+    pub async fn run_alert_expiry(self: Arc<Self>) -> Result<()> {
+        info!("Starting alert expiry task");
+        
+        let mut interval = tokio::time::interval(ALERT_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.expire_alerts().await {
+                warn!("Failed to expire alerts: {}", e);
+            }
+        }
+    }
+
+    /// Resolve every unresolved alert that ends within the expiry window,
+    /// returning how many were resolved
+    pub async fn expire_alerts(&self) -> Result<usize> {
+        let alerts = self.store.list_alerts_near_expiry(ALERT_EXPIRY_WINDOW_MINUTES).await?;
+        debug!("Found {} alert(s) near expiry", alerts.len());
+        
+        for alert in &alerts {
+            info!("Resolving alert {} ({}) ahead of its endsAt", alert.id, alert.alert_name);
+            self.store.update_alert_status(alert.id, AlertStatus::Resolved).await?;
+            self.store.update_alert_timing(alert.id, "resolved_at", Utc::now()).await?;
+            crate::metrics::ALERTS_AUTO_EXPIRED_TOTAL.inc();
+        }
+        
+        Ok(alerts.len())
+    }
+
     fn should_process_alert(
         &self,
         alert: &AlertManagerAlert,
@@ -272,7 +330,9 @@ impl WebhookHandler {
         }
     }
 
-    async fn trigger_workflow(&self, workflow_name: &str, namespace: &str, alert: &Alert) -> Result<()> {
+    /// Queue `workflow_name` for the alert, returning the execution's id if
+    /// a workflow engine is configured
+    async fn trigger_workflow(&self, workflow_name: &str, namespace: &str, alert: &Alert) -> Result<Option<Uuid>> {
         info!("Triggering workflow {} in namespace {} for alert {}", workflow_name, namespace, alert.id);
         
         // Get workflow from Kubernetes
@@ -285,54 +345,230 @@ impl WebhookHandler {
         
         // Queue workflow for execution if we have an engine
         if let Some(engine) = &self.workflow_engine {
-            // Create a workflow instance with alert context
-            let mut workflow_instance = workflow.clone();
+            let workflow_id = engine.queue_alert_workflow(workflow, alert).await?;
             
-            // Add alert context to workflow metadata (will be passed to context)
-            if workflow_instance.metadata.annotations.is_none() {
-                workflow_instance.metadata.annotations = Some(Default::default());
-            }
-            
-            // Add minimal alert info for backward compatibility
-            workflow_instance.metadata.annotations.as_mut().unwrap().insert(
-                "alert.id".to_string(),
-                alert.id.to_string(),
-            );
-            workflow_instance.metadata.annotations.as_mut().unwrap().insert(
-                "alert.name".to_string(),
-                alert.alert_name.clone(),
-            );
-            workflow_instance.metadata.annotations.as_mut().unwrap().insert(
-                "alert.severity".to_string(),
-                format!("{:?}", alert.severity),
-            );
-            
-            // Add the full alert data structure that templates expect
-            // This creates the structure: source.data.alerts[0]
-            let alert_data = serde_json::json!({
-                "alerts": [{
-                    "labels": alert.labels.clone(),
-                    "annotations": alert.annotations.clone(),
-                    "status": "firing",  // Default to firing for compatibility
-                    "startsAt": alert.starts_at,
-                    "endsAt": alert.ends_at,
-                }]
-            });
-            
-            // Store the alert data as JSON string in annotation
-            workflow_instance.metadata.annotations.as_mut().unwrap().insert(
-                "source.data".to_string(),
-                serde_json::to_string(&alert_data).unwrap_or_default(),
-            );
-            
-            engine.queue_workflow(workflow_instance).await?;
-            
-            // Update alert with workflow ID
-            self.store.update_alert_timing(alert.id, "triage_started_at", chrono::Utc::now()).await?;
+            // Link the alert to its workflow so it isn't requeued as dropped
+            let now = Utc::now();
+            let mut alert = alert.clone();
+            alert.workflow_id = Some(workflow_id);
+            alert.status = AlertStatus::Triaging;
+            alert.triage_started_at = Some(now);
+            alert.updated_at = now;
+            self.store.save_alert(alert).await?;
+            Ok(Some(workflow_id))
         } else {
             warn!("Workflow engine not available, cannot trigger workflow");
+            Ok(None)
         }
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow_for_severity() {
+        let mut config = WebhookConfig {
+            source_name: "alertmanager".to_string(),
+            source_id: None,
+            path: "/webhook/alertmanager".to_string(),
+            filters: HashMap::new(),
+            workflow_name: "triage".to_string(),
+            trigger_workflow: None,
+            severity_routing: [("Critical".to_string(), "page-oncall".to_string())].into(),
+            namespace: "default".to_string(),
+            renotify_on_update: false,
+            receiver: WebhookReceiver::Prometheus,
+        };
         
-        Ok(())
+        assert_eq!(config.workflow_for_severity(AlertSeverity::Critical), "page-oncall");
+        assert_eq!(config.workflow_for_severity(AlertSeverity::Warning), "triage");
+        
+        config.trigger_workflow = Some("investigate".to_string());
+        assert_eq!(config.workflow_for_severity(AlertSeverity::Info), "investigate");
     }
-} 
\ No newline at end of file
+
+    /// Kubernetes API serving a `triage` workflow that reports to a
+    /// `stdout` sink, in every namespace
+    async fn workflow_api() -> Client {
+        use axum::{routing::{get, patch}, Json, Router};
+
+        let sink = || Json(serde_json::json!({
+            "apiVersion": "punchingfist.io/v1alpha1",
+            "kind": "Sink",
+            "metadata": { "name": "stdout", "namespace": "default" },
+            "spec": { "type": "stdout", "config": {} },
+            "status": { "ready": true, "messagesSent": 0 }
+        }));
+        let app = Router::new().route(
+            "/apis/punchingfist.io/v1alpha1/namespaces/{namespace}/workflows/{name}",
+            get(|| async {
+                Json(serde_json::json!({
+                    "apiVersion": "punchingfist.io/v1alpha1",
+                    "kind": "Workflow",
+                    "metadata": { "name": "triage", "namespace": "default" },
+                    "spec": {
+                        "runtime": { "image": "busybox:latest", "llmConfig": { "provider": "mock", "model": "mock" } },
+                        "steps": [],
+                        "sinks": ["stdout"]
+                    }
+                }))
+            }),
+        )
+        .route("/apis/punchingfist.io/v1alpha1/namespaces/{namespace}/sinks/{name}", get(move || async move { sink() }))
+        .route("/apis/punchingfist.io/v1alpha1/namespaces/{namespace}/sinks/{name}/status", patch(move || async move { sink() }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_triggered_alert_is_not_requeued() {
+        use crate::{store::SqliteStore, workflow::StepExecutor};
+
+        let store: Arc<dyn Store> = Arc::new(SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let client = workflow_api().await;
+        let engine = Arc::new(WorkflowEngine::new(
+            store.clone(),
+            Arc::new(StepExecutor::new(client.clone(), "default".to_string())),
+        ));
+        let handler = WebhookHandler::new(store.clone(), Some(client)).with_workflow_engine(engine);
+
+        let config = WebhookConfig {
+            source_name: "alertmanager".to_string(),
+            source_id: None,
+            path: "/webhook/alertmanager".to_string(),
+            filters: HashMap::new(),
+            workflow_name: "triage".to_string(),
+            trigger_workflow: None,
+            severity_routing: HashMap::new(),
+            namespace: "default".to_string(),
+            renotify_on_update: false,
+            receiver: WebhookReceiver::Prometheus,
+        };
+        let payload: AlertManagerWebhook = serde_json::from_value(serde_json::json!({
+            "receiver": "punching-fist",
+            "status": "firing",
+            "alerts": [{
+                "status": "firing",
+                "labels": { "alertname": "PodCrashLooping", "severity": "warning" },
+                "annotations": {},
+                "startsAt": "2024-05-01T12:00:00Z",
+                "generatorURL": "",
+                "fingerprint": "abc123"
+            }],
+            "groupLabels": {},
+            "commonLabels": {},
+            "commonAnnotations": {},
+            "externalURL": "",
+            "version": "4",
+            "groupKey": "{}"
+        })).unwrap();
+
+        let ids = handler.handle_alertmanager_webhook(&config, payload).await.unwrap();
+        let alert = store.get_alert(ids[0]).await.unwrap().unwrap();
+        assert_eq!(alert.status, AlertStatus::Triaging);
+        assert!(alert.workflow_id.is_some());
+        assert!(alert.triage_started_at.is_some());
+        assert_eq!(alert.source_namespace(), Some("default"));
+
+        // However old it gets, the alert isn't picked up as dropped
+        let dropped = store.list_alerts_without_workflow(0).await.unwrap();
+        assert!(dropped.iter().all(|a| a.id != alert.id));
+    }
+
+    #[tokio::test]
+    async fn test_refires_branch_on_deduplication() {
+        use crate::{controllers::SinkController, store::{SqliteStore, Workflow as StoredWorkflow, WorkflowStatus}, workflow::StepExecutor};
+
+        let store: Arc<dyn Store> = Arc::new(SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let client = workflow_api().await;
+        let engine = Arc::new(
+            WorkflowEngine::new(store.clone(), Arc::new(StepExecutor::new(client.clone(), "default".to_string())))
+                .with_client(client.clone())
+                .with_sink_controller(Arc::new(SinkController::new(client.clone()).with_store(store.clone()))),
+        );
+        let handler = WebhookHandler::new(store.clone(), Some(client)).with_workflow_engine(engine);
+
+        let mut config = WebhookConfig {
+            source_name: "alertmanager".to_string(),
+            source_id: None,
+            path: "/webhook/alertmanager".to_string(),
+            filters: HashMap::new(),
+            workflow_name: "triage".to_string(),
+            trigger_workflow: None,
+            severity_routing: HashMap::new(),
+            namespace: "default".to_string(),
+            renotify_on_update: true,
+            receiver: WebhookReceiver::Prometheus,
+        };
+        let payload = |annotations: serde_json::Value| -> AlertManagerWebhook {
+            serde_json::from_value(serde_json::json!({
+                "receiver": "punching-fist",
+                "status": "firing",
+                "alerts": [{
+                    "status": "firing",
+                    "labels": { "alertname": "DiskFull", "severity": "warning" },
+                    "annotations": annotations,
+                    "startsAt": "2024-05-01T12:00:00Z",
+                    "generatorURL": "",
+                    "fingerprint": "def456"
+                }],
+                "groupLabels": {},
+                "commonLabels": {},
+                "commonAnnotations": {},
+                "externalURL": "",
+                "version": "4",
+                "groupKey": "{}"
+            })).unwrap()
+        };
+
+        // The first delivery triggers the workflow, which then succeeds
+        let ids = handler.handle_alertmanager_webhook(&config, payload(serde_json::json!({}))).await.unwrap();
+        let workflow_id = store.get_alert(ids[0]).await.unwrap().unwrap().workflow_id.unwrap();
+        let now = Utc::now();
+        store.save_workflow(StoredWorkflow {
+            id: workflow_id,
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 0,
+            total_steps: 0,
+            current_step: None,
+            input_context: None,
+            outputs: Some(serde_json::json!({ "summary": "Disk full" })),
+            error: None,
+            started_at: now,
+            completed_at: Some(now),
+            created_at: now,
+        }).await.unwrap();
+
+        // A duplicate neither triggers another workflow nor renotifies
+        let refired = handler.handle_alertmanager_webhook(&config, payload(serde_json::json!({}))).await.unwrap();
+        assert_eq!(refired, ids);
+        assert_eq!(store.get_alert(ids[0]).await.unwrap().unwrap().workflow_id, Some(workflow_id));
+        assert!(store.list_sink_outputs(workflow_id).await.unwrap().is_empty());
+
+        // Only sources that opt in resend the result for an update
+        config.renotify_on_update = false;
+        handler.handle_alertmanager_webhook(&config, payload(serde_json::json!({ "summary": "Disk 95% full" }))).await.unwrap();
+        assert!(store.list_sink_outputs(workflow_id).await.unwrap().is_empty());
+
+        config.renotify_on_update = true;
+        let runbook = serde_json::json!({ "runbook_url": "https://runbooks/disk" });
+        handler.handle_alertmanager_webhook(&config, payload(runbook)).await.unwrap();
+        let outputs = store.list_sink_outputs(workflow_id).await.unwrap();
+        assert_eq!(outputs.len(), 1);
+        let sent = outputs[0].payload.as_ref().unwrap();
+        assert_eq!(sent["type"], "completion");
+        assert_eq!(sent["workflow"]["outputs"]["summary"], "Disk full");
+        assert_eq!(sent["alert"]["annotations"]["runbook_url"], "https://runbooks/disk");
+        assert_eq!(sent["alert"]["annotations"]["summary"], "Disk 95% full");
+        assert_eq!(store.get_alert(ids[0]).await.unwrap().unwrap().workflow_id, Some(workflow_id));
+    }
+}
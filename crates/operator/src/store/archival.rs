@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{error, info};
+
+use crate::{store::Store, Result};
+
+/// Chat sessions not updated for this long are expired
+pub const CHAT_SESSION_RETENTION_DAYS: i64 = 7;
+
+/// Periodically removes stored data that has outlived its retention period.
+pub struct ArchivalTask {
+    store: Arc<dyn Store>,
+    interval: Duration,
+}
+
+impl ArchivalTask {
+    pub fn new(store: Arc<dyn Store>) -> Self {
+        Self {
+            store,
+            interval: Duration::from_secs(3600),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Starting archival task");
+
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            match self.expire_chat_sessions(Utc::now()).await {
+                Ok(0) => {}
+                Ok(count) => info!("Expired {} chat session(s)", count),
+                Err(e) => error!("Chat session expiry failed: {}", e),
+            }
+        }
+    }
+
+    /// Delete chat sessions idle for longer than the retention period at `now`.
+    /// Returns the number of sessions deleted.
+    pub async fn expire_chat_sessions(&self, now: DateTime<Utc>) -> Result<u64> {
+        let cutoff = now - chrono::Duration::days(CHAT_SESSION_RETENTION_DAYS);
+        self.store.delete_chat_sessions_before(cutoff).await
+    }
+}
@@ -0,0 +1,604 @@
+//! Audit trail of store mutations
+//!
+//! `AuditingStore` wraps another store and records an `AuditEntry` with the
+//! before and after state of the entity for every write made through it.
+//! The states are read and the entry saved in the write's transaction, so
+//! the entry is saved exactly when the write is.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::FutureExt;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::Result;
+
+use super::*;
+
+tokio::task_local! {
+    static ACTOR: String;
+}
+
+/// Run `f`, attributing the changes it makes to `actor`
+pub async fn with_actor<F: Future>(actor: String, f: F) -> F::Output {
+    ACTOR.scope(actor, f).await
+}
+
+fn current_actor() -> Option<String> {
+    ACTOR.try_with(Clone::clone).ok()
+}
+
+pub struct AuditingStore {
+    inner: Arc<dyn Store>,
+}
+
+impl AuditingStore {
+    pub fn new(inner: Arc<dyn Store>) -> Self {
+        Self { inner }
+    }
+
+    /// Run `work` in one transaction of the wrapped store
+    async fn audited<T: Send, F>(&self, work: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>> + Send,
+    {
+        let mut output = None;
+        self.inner.transaction(async {
+            output = Some(work.await?);
+            Ok(())
+        }.boxed()).await?;
+        output.ok_or_else(|| crate::Error::Internal("Audited write produced no result".to_string()))
+    }
+
+    /// Save an entry for a change to an entity, unless nothing changed
+    async fn record<B: Serialize, A: Serialize>(
+        &self,
+        operation: &str,
+        entity_type: &str,
+        entity_id: impl ToString,
+        before: Option<B>,
+        after: Option<A>,
+    ) -> Result<()> {
+        let before = before.map(|b| serde_json::to_value(b)).transpose()?;
+        let after = after.map(|a| serde_json::to_value(a)).transpose()?;
+        if before == after {
+            return Ok(());
+        }
+
+        let entry = AuditEntry {
+            id: Uuid::new_v4(),
+            operation: operation.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id: entity_id.to_string(),
+            actor: current_actor(),
+            before,
+            after,
+            created_at: Utc::now(),
+        };
+        self.inner.save_audit_entry(entry).await.inspect_err(|e| {
+            error!("Failed to record {} of {} {}: {}", operation, entity_type, entity_id.to_string(), e);
+        })
+    }
+
+    /// Run an alert update and record it
+    async fn audit_alert<F>(&self, operation: &str, id: Uuid, write: F) -> Result<()>
+    where
+        F: Future<Output = Result<()>> + Send,
+    {
+        self.audited(async {
+            let before = self.inner.get_alert(id).await?;
+            write.await?;
+            let after = self.inner.get_alert(id).await?;
+            self.record(operation, "alert", id, before, after).await
+        }).await
+    }
+
+    /// Run a workflow update and record it
+    async fn audit_workflow<F>(&self, operation: &str, id: Uuid, write: F) -> Result<()>
+    where
+        F: Future<Output = Result<()>> + Send,
+    {
+        self.audited(async {
+            let before = self.inner.get_workflow(id).await?;
+            write.await?;
+            let after = self.inner.get_workflow(id).await?;
+            self.record(operation, "workflow", id, before, after).await
+        }).await
+    }
+
+    /// Run a workflow step update and record it
+    async fn audit_workflow_step<F>(&self, operation: &str, id: Uuid, write: F) -> Result<()>
+    where
+        F: Future<Output = Result<()>> + Send,
+    {
+        self.audited(async {
+            let before = self.inner.get_workflow_step(id).await?;
+            write.await?;
+            let after = self.inner.get_workflow_step(id).await?;
+            self.record(operation, "workflow_step", id, before, after).await
+        }).await
+    }
+
+    /// Run a sink output update and record it
+    async fn audit_sink_output<F>(&self, operation: &str, id: Uuid, write: F) -> Result<()>
+    where
+        F: Future<Output = Result<()>> + Send,
+    {
+        self.audited(async {
+            let before = self.inner.get_sink_output(id).await?;
+            write.await?;
+            let after = self.inner.get_sink_output(id).await?;
+            self.record(operation, "sink_output", id, before, after).await
+        }).await
+    }
+
+    /// Run a deduplication and record the alert it created or updated
+    async fn audit_deduplication<F>(&self, operation: &str, fingerprint: &str, write: F) -> Result<DeduplicationResult>
+    where
+        F: Future<Output = Result<DeduplicationResult>> + Send,
+    {
+        self.audited(async {
+            let before = self.inner.get_alert_by_fingerprint(fingerprint).await?;
+            let result = write.await?;
+            let alert = match &result {
+                DeduplicationResult::New(alert)
+                | DeduplicationResult::Duplicate(alert)
+                | DeduplicationResult::Updated(alert) => alert,
+            };
+            let after = self.inner.get_alert(alert.id).await?;
+            // A stale alert outside the dedup window was resolved to make way
+            // for the new one
+            if let Some(stale) = before.as_ref().filter(|b| b.id != alert.id && b.status != AlertStatus::Resolved) {
+                let resolved = self.inner.get_alert(stale.id).await?;
+                self.record(operation, "alert", stale.id, Some(stale), resolved).await?;
+            }
+            let before = before.filter(|b| b.id == alert.id);
+            self.record(operation, "alert", alert.id, before, after).await?;
+            Ok(result)
+        }).await
+    }
+}
+
+#[async_trait]
+impl Store for AuditingStore {
+    async fn init(&self) -> Result<()> {
+        self.inner.init().await
+    }
+
+    async fn migrate_alert_labels_to_jsonb(&self) -> Result<()> {
+        self.inner.migrate_alert_labels_to_jsonb().await
+    }
+
+    async fn health_details(&self) -> Result<StoreHealth> {
+        self.inner.health_details().await
+    }
+
+    async fn backup_to_path(&self, path: &std::path::Path) -> Result<u64> {
+        self.inner.backup_to_path(path).await
+    }
+
+    async fn transaction<'a>(&'a self, work: BoxFuture<'a, Result<()>>) -> Result<()> {
+        self.inner.transaction(work).await
+    }
+
+    async fn save_alert(&self, alert: Alert) -> Result<()> {
+        let id = alert.id;
+        self.audit_alert("save_alert", id, self.inner.save_alert(alert)).await
+    }
+
+    async fn get_alert(&self, id: Uuid) -> Result<Option<Alert>> {
+        self.inner.get_alert(id).await
+    }
+
+    async fn get_alert_by_fingerprint(&self, fingerprint: &str) -> Result<Option<Alert>> {
+        self.inner.get_alert_by_fingerprint(fingerprint).await
+    }
+
+    async fn update_alert_status(&self, id: Uuid, status: AlertStatus) -> Result<()> {
+        self.audit_alert("update_alert_status", id, self.inner.update_alert_status(id, status)).await
+    }
+
+    async fn update_alert_severity(&self, id: Uuid, severity: AlertSeverity) -> Result<()> {
+        self.audit_alert("update_alert_severity", id, self.inner.update_alert_severity(id, severity)).await
+    }
+
+    async fn update_alert_ai_analysis(&self, id: Uuid, analysis: JsonValue, confidence: f32) -> Result<()> {
+        self.audit_alert("update_alert_ai_analysis", id, self.inner.update_alert_ai_analysis(id, analysis, confidence)).await
+    }
+
+    async fn update_alert_timing(&self, id: Uuid, field: &str, timestamp: DateTime<Utc>) -> Result<()> {
+        self.audit_alert("update_alert_timing", id, self.inner.update_alert_timing(id, field, timestamp)).await
+    }
+
+    async fn append_alert_annotation(&self, id: Uuid, note: AlertNote) -> Result<()> {
+        self.audit_alert("append_alert_annotation", id, self.inner.append_alert_annotation(id, note)).await
+    }
+
+    async fn set_alert_annotation(&self, id: Uuid, key: &str, value: &str) -> Result<()> {
+        self.audit_alert("set_alert_annotation", id, self.inner.set_alert_annotation(id, key, value)).await
+    }
+
+    async fn list_alerts(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<Vec<Alert>> {
+        self.inner.list_alerts(limit, offset, since, until).await
+    }
+
+    async fn list_alerts_by_status(&self, status: AlertStatus, limit: i64) -> Result<Vec<Alert>> {
+        self.inner.list_alerts_by_status(status, limit).await
+    }
+
+    async fn count_alerts(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<i64> {
+        self.inner.count_alerts(since, until).await
+    }
+
+    async fn count_alerts_by_status(&self, status: AlertStatus) -> Result<i64> {
+        self.inner.count_alerts_by_status(status).await
+    }
+
+    async fn get_recent_alerts_for_deployment(&self, namespace: &str, deployment: &str, limit: i64) -> Result<Vec<Alert>> {
+        self.inner.get_recent_alerts_for_deployment(namespace, deployment, limit).await
+    }
+
+    async fn list_alerts_by_label(&self, labels: &std::collections::HashMap<String, String>, limit: i64) -> Result<Vec<Alert>> {
+        self.inner.list_alerts_by_label(labels, limit).await
+    }
+
+    async fn list_alerts_near_expiry(&self, within_minutes: i64) -> Result<Vec<Alert>> {
+        self.inner.list_alerts_near_expiry(within_minutes).await
+    }
+
+    async fn list_alerts_without_workflow(&self, older_than_minutes: i64) -> Result<Vec<Alert>> {
+        self.inner.list_alerts_without_workflow(older_than_minutes).await
+    }
+
+    async fn save_workflow(&self, workflow: Workflow) -> Result<()> {
+        let id = workflow.id;
+        self.audit_workflow("save_workflow", id, self.inner.save_workflow(workflow)).await
+    }
+
+    async fn get_workflow(&self, id: Uuid) -> Result<Option<Workflow>> {
+        self.inner.get_workflow(id).await
+    }
+
+    async fn update_workflow_status(&self, id: Uuid, status: WorkflowStatus) -> Result<()> {
+        self.audit_workflow("update_workflow_status", id, self.inner.update_workflow_status(id, status)).await
+    }
+
+    async fn update_workflow_progress(&self, id: Uuid, steps_completed: i32, current_step: Option<String>) -> Result<()> {
+        self.audit_workflow("update_workflow_progress", id, self.inner.update_workflow_progress(id, steps_completed, current_step)).await
+    }
+
+    async fn update_workflow_outputs(&self, id: Uuid, outputs: JsonValue) -> Result<()> {
+        self.audit_workflow("update_workflow_outputs", id, self.inner.update_workflow_outputs(id, outputs)).await
+    }
+
+    async fn complete_workflow(&self, id: Uuid, status: WorkflowStatus, outputs: Option<JsonValue>, error: Option<String>) -> Result<()> {
+        self.audit_workflow("complete_workflow", id, self.inner.complete_workflow(id, status, outputs, error)).await
+    }
+
+    async fn bulk_cancel_workflows(&self, ids: &[Uuid], reason: &str) -> Result<BulkCancelResult> {
+        self.audited(async {
+            let mut before = Vec::with_capacity(ids.len());
+            for id in ids {
+                before.push(self.inner.get_workflow(*id).await?);
+            }
+            let result = self.inner.bulk_cancel_workflows(ids, reason).await?;
+            for (id, before) in ids.iter().zip(before) {
+                let after = self.inner.get_workflow(*id).await?;
+                self.record("bulk_cancel_workflows", "workflow", id, before, after).await?;
+            }
+            Ok(result)
+        }).await
+    }
+
+    async fn list_workflows(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<Vec<Workflow>> {
+        self.inner.list_workflows(limit, offset, since, until).await
+    }
+
+    async fn list_workflows_by_status(
+        &self,
+        status: WorkflowStatus,
+        limit: i64,
+        offset: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Workflow>> {
+        self.inner.list_workflows_by_status(status, limit, offset, since, until).await
+    }
+
+    async fn count_workflows(&self, status: Option<WorkflowStatus>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<i64> {
+        self.inner.count_workflows(status, since, until).await
+    }
+
+    async fn list_workflows_for_alert(&self, alert_id: Uuid) -> Result<Vec<Workflow>> {
+        self.inner.list_workflows_for_alert(alert_id).await
+    }
+
+    async fn get_workflow_with_all_data(&self, id: Uuid) -> Result<Option<WorkflowWithAllData>> {
+        self.inner.get_workflow_with_all_data(id).await
+    }
+
+    async fn list_workflows_for_time_range(&self, from: DateTime<Utc>, to: DateTime<Utc>, bucket_minutes: u32) -> Result<Vec<WorkflowBucket>> {
+        self.inner.list_workflows_for_time_range(from, to, bucket_minutes).await
+    }
+
+    async fn save_source_event(&self, event: SourceEvent) -> Result<()> {
+        self.audited(async {
+            let id = event.id;
+            let before = self.inner.get_source_event(id).await?;
+            self.inner.save_source_event(event).await?;
+            let after = self.inner.get_source_event(id).await?;
+            self.record("save_source_event", "source_event", id, before, after).await
+        }).await
+    }
+
+    async fn save_scheduled_source_event(&self, event: SourceEvent, namespace: &str, scheduled_at: DateTime<Utc>) -> Result<bool> {
+        self.audited(async {
+            let id = event.id;
+            if !self.inner.save_scheduled_source_event(event, namespace, scheduled_at).await? {
+                return Ok(false);
+            }
+            let after = self.inner.get_source_event(id).await?;
+            self.record("save_scheduled_source_event", "source_event", id, None::<SourceEvent>, after).await?;
+            Ok(true)
+        }).await
+    }
+
+    async fn get_source_event(&self, id: Uuid) -> Result<Option<SourceEvent>> {
+        self.inner.get_source_event(id).await
+    }
+
+    async fn delete_source_event(&self, id: Uuid) -> Result<()> {
+        self.audited(async {
+            let before = self.inner.get_source_event(id).await?;
+            self.inner.delete_source_event(id).await?;
+            self.record("delete_source_event", "source_event", id, before, None::<SourceEvent>).await
+        }).await
+    }
+
+    async fn list_source_events(&self, source_name: &str, limit: i64) -> Result<Vec<SourceEvent>> {
+        self.inner.list_source_events(source_name, limit).await
+    }
+
+    async fn set_source_event_workflow(&self, id: Uuid, workflow_id: Uuid) -> Result<()> {
+        self.audited(async {
+            self.inner.set_source_event_workflow(id, workflow_id).await?;
+            // Source events are read back without their workflow link
+            self.record("set_source_event_workflow", "source_event", id, None::<JsonValue>, Some(serde_json::json!({
+                "workflow_id": workflow_id
+            }))).await
+        }).await
+    }
+
+    async fn list_source_events_with_workflow_status(&self, source_name: &str, limit: i64) -> Result<Vec<SourceEventWithWorkflow>> {
+        self.inner.list_source_events_with_workflow_status(source_name, limit).await
+    }
+
+    async fn save_workflow_step(&self, step: WorkflowStep) -> Result<()> {
+        let id = step.id;
+        self.audit_workflow_step("save_workflow_step", id, self.inner.save_workflow_step(step)).await
+    }
+
+    async fn get_workflow_step(&self, id: Uuid) -> Result<Option<WorkflowStep>> {
+        self.inner.get_workflow_step(id).await
+    }
+
+    async fn update_workflow_step_status(&self, id: Uuid, status: StepStatus) -> Result<()> {
+        self.audit_workflow_step("update_workflow_step_status", id, self.inner.update_workflow_step_status(id, status)).await
+    }
+
+    async fn complete_workflow_step(&self, id: Uuid, status: StepStatus, result: Option<JsonValue>, error: Option<String>) -> Result<()> {
+        self.audit_workflow_step("complete_workflow_step", id, self.inner.complete_workflow_step(id, status, result, error)).await
+    }
+
+    async fn update_workflow_step_result(&self, id: Uuid, result: JsonValue) -> Result<()> {
+        // Output saved while a step runs isn't audited; the completed step is
+        self.inner.update_workflow_step_result(id, result).await
+    }
+
+    async fn list_workflow_steps(&self, workflow_id: Uuid) -> Result<Vec<WorkflowStep>> {
+        self.inner.list_workflow_steps(workflow_id).await
+    }
+
+    async fn save_sink_output(&self, output: SinkOutput) -> Result<()> {
+        let id = output.id;
+        self.audit_sink_output("save_sink_output", id, self.inner.save_sink_output(output)).await
+    }
+
+    async fn get_sink_output(&self, id: Uuid) -> Result<Option<SinkOutput>> {
+        self.inner.get_sink_output(id).await
+    }
+
+    async fn update_sink_output_status(&self, id: Uuid, status: SinkStatus, error: Option<String>) -> Result<()> {
+        self.audit_sink_output("update_sink_output_status", id, self.inner.update_sink_output_status(id, status, error)).await
+    }
+
+    async fn list_sink_outputs(&self, workflow_id: Uuid) -> Result<Vec<SinkOutput>> {
+        self.inner.list_sink_outputs(workflow_id).await
+    }
+
+    async fn count_sink_outputs_by_type(&self) -> Result<Vec<SinkTypeStats>> {
+        self.inner.count_sink_outputs_by_type().await
+    }
+
+    async fn list_recent_sink_failures(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<SinkOutput>> {
+        self.inner.list_recent_sink_failures(since, limit).await
+    }
+
+    async fn save_custom_resource(&self, resource: CustomResource) -> Result<()> {
+        self.audited(async {
+            let (kind, namespace, name) = (resource.kind.clone(), resource.namespace.clone(), resource.name.clone());
+            let before = self.inner.get_custom_resource(&kind, &namespace, &name).await?;
+            self.inner.save_custom_resource(resource).await?;
+            let after = self.inner.get_custom_resource(&kind, &namespace, &name).await?;
+            self.record("save_custom_resource", "custom_resource", format!("{}/{}/{}", kind, namespace, name), before, after).await
+        }).await
+    }
+
+    async fn get_custom_resource(&self, kind: &str, namespace: &str, name: &str) -> Result<Option<CustomResource>> {
+        self.inner.get_custom_resource(kind, namespace, name).await
+    }
+
+    async fn update_custom_resource_status(&self, id: Uuid, status: JsonValue) -> Result<()> {
+        self.audited(async {
+            self.inner.update_custom_resource_status(id, status.clone()).await?;
+            // Custom resources can't be read back by id
+            self.record("update_custom_resource_status", "custom_resource", id, None::<JsonValue>, Some(serde_json::json!({
+                "status": status
+            }))).await
+        }).await
+    }
+
+    async fn delete_custom_resource(&self, kind: &str, namespace: &str, name: &str) -> Result<()> {
+        self.audited(async {
+            let before = self.inner.get_custom_resource(kind, namespace, name).await?;
+            self.inner.delete_custom_resource(kind, namespace, name).await?;
+            self.record("delete_custom_resource", "custom_resource", format!("{}/{}/{}", kind, namespace, name), before, None::<JsonValue>).await
+        }).await
+    }
+
+    async fn list_custom_resources(
+        &self,
+        kind: &str,
+        namespace: Option<&str>,
+        all_namespaces: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CustomResource>> {
+        self.inner.list_custom_resources(kind, namespace, all_namespaces, limit, offset).await
+    }
+
+    async fn count_custom_resources(&self, kind: &str, namespace: Option<&str>) -> Result<i64> {
+        self.inner.count_custom_resources(kind, namespace).await
+    }
+
+    async fn save_chat_session(&self, session: ChatSession) -> Result<()> {
+        self.audited(async {
+            let session_id = session.session_id.clone();
+            let before = self.inner.get_chat_session(&session_id).await?;
+            self.inner.save_chat_session(session).await?;
+            let after = self.inner.get_chat_session(&session_id).await?;
+            self.record("save_chat_session", "chat_session", session_id, before, after).await
+        }).await
+    }
+
+    async fn get_chat_session(&self, session_id: &str) -> Result<Option<ChatSession>> {
+        self.inner.get_chat_session(session_id).await
+    }
+
+    async fn list_chat_sessions_by_user(&self, user_id: &str) -> Result<Vec<ChatSession>> {
+        self.inner.list_chat_sessions_by_user(user_id).await
+    }
+
+    async fn delete_chat_sessions_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        self.audited(async {
+            let deleted = self.inner.delete_chat_sessions_before(cutoff).await?;
+            if deleted > 0 {
+                // A retention sweep, recorded as one entry for all sessions
+                self.record("delete_chat_sessions_before", "chat_session", "*", Some(serde_json::json!({
+                    "updated_before": cutoff,
+                    "deleted": deleted
+                })), None::<JsonValue>).await?;
+            }
+            Ok(deleted)
+        }).await
+    }
+
+    async fn deduplicate_alert(&self, fingerprint: &str, alert: Alert) -> Result<DeduplicationResult> {
+        self.audit_deduplication("deduplicate_alert", fingerprint, self.inner.deduplicate_alert(fingerprint, alert)).await
+    }
+
+    async fn get_or_create_alert_by_fingerprint(&self, alert: Alert) -> Result<DeduplicationResult> {
+        let fingerprint = alert.fingerprint.clone();
+        self.audit_deduplication("get_or_create_alert_by_fingerprint", &fingerprint, self.inner.get_or_create_alert_by_fingerprint(alert)).await
+    }
+
+    async fn save_audit_entry(&self, entry: AuditEntry) -> Result<()> {
+        self.inner.save_audit_entry(entry).await
+    }
+
+    async fn list_audit_entries(&self, entity_type: Option<&str>, entity_id: Option<&str>, limit: i64) -> Result<Vec<AuditEntry>> {
+        self.inner.list_audit_entries(entity_type, entity_id, limit).await
+    }
+
+    async fn save_tool_invocation(&self, invocation: ToolInvocation) -> Result<()> {
+        self.inner.save_tool_invocation(invocation).await
+    }
+
+    async fn list_tool_invocations_by_workflow(&self, workflow_id: Uuid) -> Result<Vec<ToolInvocation>> {
+        self.inner.list_tool_invocations_by_workflow(workflow_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn test_alert() -> Alert {
+        let now = Utc::now();
+        Alert {
+            id: Uuid::new_v4(),
+            external_id: None,
+            fingerprint: "fp-audit".to_string(),
+            status: AlertStatus::Received,
+            severity: AlertSeverity::Warning,
+            alert_name: "HighCPU".to_string(),
+            summary: None,
+            description: None,
+            labels: HashMap::new(),
+            annotations: HashMap::new(),
+            source_id: None,
+            workflow_id: None,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at: now,
+            ends_at: None,
+            received_at: now,
+            triage_started_at: None,
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auditing_store() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let inner = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        let store = AuditingStore::new(Arc::new(inner));
+        store.init().await.unwrap();
+
+        let alert = test_alert();
+        store.save_alert(alert.clone()).await.unwrap();
+        with_actor("jane".to_string(), store.update_alert_status(alert.id, AlertStatus::Triaging)).await.unwrap();
+
+        // Failed writes aren't recorded
+        assert!(store.append_alert_annotation(Uuid::new_v4(), AlertNote {
+            author: "jane".to_string(),
+            timestamp: Utc::now(),
+            text: "checked".to_string(),
+        }).await.is_err());
+
+        let entries = store.list_audit_entries(Some("alert"), Some(&alert.id.to_string()), 10).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, "update_alert_status");
+        assert_eq!(entries[0].actor.as_deref(), Some("jane"));
+        assert_eq!(entries[0].before.as_ref().unwrap()["status"], "received");
+        assert_eq!(entries[0].after.as_ref().unwrap()["status"], "triaging");
+        assert_eq!(entries[1].operation, "save_alert");
+        assert_eq!(entries[1].actor, None);
+        assert_eq!(entries[1].before, None);
+
+        assert!(store.list_audit_entries(Some("workflow"), None, 10).await.unwrap().is_empty());
+        assert_eq!(store.list_audit_entries(None, None, 10).await.unwrap().len(), 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
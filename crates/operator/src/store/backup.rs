@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use crate::{config::Config, store::Store, Result};
+
+/// Back up the database into a new timestamped file in `dir`, creating the
+/// directory if needed. Returns the backup's path and size in bytes.
+pub async fn backup_to_dir(store: &dyn Store, dir: &Path) -> Result<(PathBuf, u64)> {
+    tokio::fs::create_dir_all(dir).await?;
+    
+    let path = dir.join(format!("punching-fist-{}.backup", Utc::now().format("%Y%m%dT%H%M%SZ")));
+    let size = store.backup_to_path(&path).await?;
+    
+    info!("Backed up database to {} ({} bytes)", path.display(), size);
+    Ok((path, size))
+}
+
+/// Periodically backs up the database into the configured backup directory.
+pub struct BackupTask {
+    store: Arc<dyn Store>,
+    config: Arc<RwLock<Config>>,
+    interval: Duration,
+}
+
+impl BackupTask {
+    pub fn new(store: Arc<dyn Store>, config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            store,
+            config,
+            interval: Duration::from_secs(24 * 3600),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Starting backup task");
+
+        let mut ticker = tokio::time::interval(self.interval);
+        // The first tick completes immediately; don't back up on every restart
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let dir = self.config.read().await.backup.dir.clone();
+            if let Err(e) = backup_to_dir(self.store.as_ref(), &dir).await {
+                error!("Scheduled database backup failed: {}", e);
+            }
+        }
+    }
+}
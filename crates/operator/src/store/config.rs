@@ -11,6 +11,18 @@ pub struct DatabaseConfig {
     
     #[serde(skip_serializing_if = "Option::is_none")]
     pub connection_string: Option<String>,
+    
+    /// Minutes an unresolved alert keeps absorbing re-fires of its
+    /// fingerprint after it was last updated; 0 keeps it until resolved
+    #[serde(default = "default_dedup_window_minutes")]
+    pub dedup_window_minutes: u64,
+}
+
+/// Dedup window used unless configured otherwise
+pub const DEFAULT_DEDUP_WINDOW_MINUTES: u64 = 240;
+
+fn default_dedup_window_minutes() -> u64 {
+    DEFAULT_DEDUP_WINDOW_MINUTES
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -26,6 +38,7 @@ impl Default for DatabaseConfig {
             db_type: DatabaseType::Sqlite,
             sqlite_path: Some(PathBuf::from("data/punchingfist.db")),
             connection_string: None,
+            dedup_window_minutes: default_dedup_window_minutes(),
         }
     }
 }
@@ -1,21 +1,25 @@
-use crate::store::{DatabaseConfig, DatabaseType, SqliteStore, PostgresStore, Store};
+use crate::store::{AuditingStore, DatabaseConfig, DatabaseType, SqliteStore, PostgresStore, Store};
 use std::sync::Arc;
+use std::time::Duration;
 
+/// Open the configured database, recording every change in its audit log
 pub async fn create_store(config: &DatabaseConfig) -> crate::Result<Arc<dyn Store>> {
-    match config.db_type {
+    let dedup_window = (config.dedup_window_minutes > 0).then(|| Duration::from_secs(config.dedup_window_minutes * 60));
+    let store: Arc<dyn Store> = match config.db_type {
         DatabaseType::Sqlite => {
             let path = config.sqlite_path
                 .as_ref()
                 .ok_or_else(|| crate::Error::Config("SQLite path not configured".into()))?
                 .to_str()
                 .unwrap_or("data/punching-fist.db");
-            Ok(Arc::new(SqliteStore::new(path).await?))
+            Arc::new(SqliteStore::new(path).await?.with_dedup_window(dedup_window))
         },
         DatabaseType::Postgres => {
             let connection_string = config.connection_string
                 .as_ref()
                 .ok_or_else(|| crate::Error::Config("PostgreSQL connection string not configured".into()))?;
-            Ok(Arc::new(PostgresStore::new(connection_string).await?))
+            Arc::new(PostgresStore::new(connection_string).await?.with_dedup_window(dedup_window))
         },
-    }
+    };
+    Ok(Arc::new(AuditingStore::new(store)))
 } 
\ No newline at end of file
@@ -1,10 +1,16 @@
+pub mod archival;
+pub mod audit;
+pub mod backup;
 mod config;
 pub mod models;
 pub mod postgres;
 pub mod sqlite;
 mod factory;
 
-pub use config::{DatabaseConfig, DatabaseType};
+pub use archival::ArchivalTask;
+pub use audit::AuditingStore;
+pub use backup::{backup_to_dir, BackupTask};
+pub use config::{DatabaseConfig, DatabaseType, DEFAULT_DEDUP_WINDOW_MINUTES};
 pub use models::*;
 pub use self::postgres::PostgresStore;
 pub use self::sqlite::SqliteStore;
@@ -12,22 +18,96 @@ pub use factory::create_store;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Tables reported by `Store::health_details`
+pub(crate) const HEALTH_CHECK_TABLES: &[&str] = &[
+    "alerts",
+    "workflows",
+    "workflow_steps",
+    "source_events",
+    "sink_outputs",
+    "custom_resources",
+    "tasks",
+    "chat_sessions",
+];
+
+/// Name of the lock held while running database migrations
+pub(crate) const MIGRATION_LOCK_NAME: &str = "punching-fist-migrations";
+
+/// How long to wait for another replica to finish migrating
+pub(crate) const MIGRATION_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A SQLite migration lock older than this was left behind by a process
+/// that died mid-migration and is taken over
+pub(crate) const MIGRATION_LOCK_STALE_AFTER: Duration = Duration::from_secs(300);
+
+/// Delay between attempts to acquire the migration lock
+pub(crate) const MIGRATION_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) fn migration_lock_timeout() -> crate::Error {
+    crate::Error::Migrate(sqlx::migrate::MigrateError::Source(
+        format!(
+            "Timed out after {}s waiting for the migration lock '{}'. Another operator replica may be \
+             running migrations, or a previous migration is stuck; check the database for stuck \
+             migrations before restarting.",
+            MIGRATION_LOCK_TIMEOUT.as_secs(),
+            MIGRATION_LOCK_NAME,
+        ).into(),
+    ))
+}
+
 #[async_trait]
 pub trait Store: Send + Sync {
     // Initialize database schema
     async fn init(&self) -> crate::Result<()>;
     
+    // Add the generated, GIN-indexed JSONB copy of alert labels used for
+    // containment queries. Idempotent; a no-op where labels can't be indexed.
+    async fn migrate_alert_labels_to_jsonb(&self) -> crate::Result<()>;
+    
+    // Health
+    async fn health_details(&self) -> crate::Result<StoreHealth>;
+    
+    // Backup the database to `path`, returning the number of bytes written
+    async fn backup_to_path(&self, path: &std::path::Path) -> crate::Result<u64>;
+    
+    // Transactions
+    /// Run `work` with the operations it makes on this store inside one
+    /// transaction, committed only if it succeeds. Work run inside another
+    /// transaction joins it.
+    async fn transaction<'a>(&'a self, work: BoxFuture<'a, crate::Result<()>>) -> crate::Result<()>;
+    
     // Alert operations
     async fn save_alert(&self, alert: Alert) -> crate::Result<()>;
     async fn get_alert(&self, id: Uuid) -> crate::Result<Option<Alert>>;
     async fn get_alert_by_fingerprint(&self, fingerprint: &str) -> crate::Result<Option<Alert>>;
     async fn update_alert_status(&self, id: Uuid, status: AlertStatus) -> crate::Result<()>;
+    async fn update_alert_severity(&self, id: Uuid, severity: AlertSeverity) -> crate::Result<()>;
     async fn update_alert_ai_analysis(&self, id: Uuid, analysis: serde_json::Value, confidence: f32) -> crate::Result<()>;
     async fn update_alert_timing(&self, id: Uuid, field: &str, timestamp: DateTime<Utc>) -> crate::Result<()>;
-    async fn list_alerts(&self, limit: i64, offset: i64) -> crate::Result<Vec<Alert>>;
+    /// Append a note to the alert's `_notes` annotation in a single UPDATE
+    async fn append_alert_annotation(&self, id: Uuid, note: AlertNote) -> crate::Result<()>;
+    /// Set one of the alert's annotations in a single UPDATE
+    async fn set_alert_annotation(&self, id: Uuid, key: &str, value: &str) -> crate::Result<()>;
+    /// One page of the alerts created in `[since, until)`, newest first.
+    /// Either bound may be left open
+    async fn list_alerts(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> crate::Result<Vec<Alert>>;
     async fn list_alerts_by_status(&self, status: AlertStatus, limit: i64) -> crate::Result<Vec<Alert>>;
+    /// Number of alerts created in `[since, until)`
+    async fn count_alerts(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> crate::Result<i64>;
+    async fn count_alerts_by_status(&self, status: AlertStatus) -> crate::Result<i64>;
+    async fn get_recent_alerts_for_deployment(&self, namespace: &str, deployment: &str, limit: i64) -> crate::Result<Vec<Alert>>;
+    /// Alerts carrying all of the given labels, newest first
+    async fn list_alerts_by_label(&self, labels: &std::collections::HashMap<String, String>, limit: i64) -> crate::Result<Vec<Alert>>;
+    /// Unresolved alerts whose `ends_at` falls within the next `within_minutes`
+    async fn list_alerts_near_expiry(&self, within_minutes: i64) -> crate::Result<Vec<Alert>>;
+    /// Received alerts that never got a workflow or started triage,
+    /// received more than `older_than_minutes` ago, oldest first
+    async fn list_alerts_without_workflow(&self, older_than_minutes: i64) -> crate::Result<Vec<Alert>>;
     
     // Workflow operations
     async fn save_workflow(&self, workflow: Workflow) -> crate::Result<()>;
@@ -36,18 +116,58 @@ pub trait Store: Send + Sync {
     async fn update_workflow_progress(&self, id: Uuid, steps_completed: i32, current_step: Option<String>) -> crate::Result<()>;
     async fn update_workflow_outputs(&self, id: Uuid, outputs: serde_json::Value) -> crate::Result<()>;
     async fn complete_workflow(&self, id: Uuid, status: WorkflowStatus, outputs: Option<serde_json::Value>, error: Option<String>) -> crate::Result<()>;
-    async fn list_workflows(&self, limit: i64, offset: i64) -> crate::Result<Vec<Workflow>>;
+    /// Cancel the pending or running workflows among `ids` in one
+    /// transaction, counting the ones that were cancelled, don't exist or
+    /// had already finished
+    async fn bulk_cancel_workflows(&self, ids: &[Uuid], reason: &str) -> crate::Result<BulkCancelResult>;
+    /// One page of the workflows created in `[since, until)`, newest first.
+    /// Either bound may be left open
+    async fn list_workflows(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> crate::Result<Vec<Workflow>>;
+    /// One page of the workflows in `status` created in `[since, until)`,
+    /// newest first
+    async fn list_workflows_by_status(
+        &self,
+        status: WorkflowStatus,
+        limit: i64,
+        offset: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> crate::Result<Vec<Workflow>>;
+    /// Number of workflows in `status`, or of all workflows when `None`,
+    /// created in `[since, until)`
+    async fn count_workflows(&self, status: Option<WorkflowStatus>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> crate::Result<i64>;
+    /// Workflows linked to the alert through its `workflow_id` or started
+    /// for it (`input_context.alert_id`), newest first
+    async fn list_workflows_for_alert(&self, alert_id: Uuid) -> crate::Result<Vec<Workflow>>;
+    /// The workflow with its steps, in execution order, and sink outputs,
+    /// read in two queries
+    async fn get_workflow_with_all_data(&self, id: Uuid) -> crate::Result<Option<WorkflowWithAllData>>;
+    async fn list_workflows_for_time_range(&self, from: DateTime<Utc>, to: DateTime<Utc>, bucket_minutes: u32) -> crate::Result<Vec<WorkflowBucket>>;
     
     // Source event operations
     async fn save_source_event(&self, event: SourceEvent) -> crate::Result<()>;
+    /// Save the event for the run due at `scheduled_at` of the scheduled
+    /// source in `namespace`, returning false without saving when that run
+    /// already has an event, e.g. because another replica fired it
+    async fn save_scheduled_source_event(&self, event: SourceEvent, namespace: &str, scheduled_at: DateTime<Utc>) -> crate::Result<bool>;
     async fn get_source_event(&self, id: Uuid) -> crate::Result<Option<SourceEvent>>;
+    /// Delete a source event, releasing the scheduled run it claimed
+    async fn delete_source_event(&self, id: Uuid) -> crate::Result<()>;
     async fn list_source_events(&self, source_name: &str, limit: i64) -> crate::Result<Vec<SourceEvent>>;
+    /// Record the workflow execution a source event queued
+    async fn set_source_event_workflow(&self, id: Uuid, workflow_id: Uuid) -> crate::Result<()>;
+    /// The source's events, newest first, each with the status of the
+    /// workflow it queued (read in one LEFT JOIN)
+    async fn list_source_events_with_workflow_status(&self, source_name: &str, limit: i64) -> crate::Result<Vec<SourceEventWithWorkflow>>;
     
     // Workflow step operations
     async fn save_workflow_step(&self, step: WorkflowStep) -> crate::Result<()>;
     async fn get_workflow_step(&self, id: Uuid) -> crate::Result<Option<WorkflowStep>>;
     async fn update_workflow_step_status(&self, id: Uuid, status: StepStatus) -> crate::Result<()>;
     async fn complete_workflow_step(&self, id: Uuid, status: StepStatus, result: Option<serde_json::Value>, error: Option<String>) -> crate::Result<()>;
+    /// Replace the result of a step that is still running, e.g. with the
+    /// output it has logged so far
+    async fn update_workflow_step_result(&self, id: Uuid, result: serde_json::Value) -> crate::Result<()>;
     async fn list_workflow_steps(&self, workflow_id: Uuid) -> crate::Result<Vec<WorkflowStep>>;
     
     // Sink output operations
@@ -55,21 +175,72 @@ pub trait Store: Send + Sync {
     async fn get_sink_output(&self, id: Uuid) -> crate::Result<Option<SinkOutput>>;
     async fn update_sink_output_status(&self, id: Uuid, status: SinkStatus, error: Option<String>) -> crate::Result<()>;
     async fn list_sink_outputs(&self, workflow_id: Uuid) -> crate::Result<Vec<SinkOutput>>;
+    async fn count_sink_outputs_by_type(&self) -> crate::Result<Vec<SinkTypeStats>>;
+    /// Failed sink outputs created at or after `since`, newest first
+    async fn list_recent_sink_failures(&self, since: DateTime<Utc>, limit: i64) -> crate::Result<Vec<SinkOutput>>;
     
     // Custom resource operations
     async fn save_custom_resource(&self, resource: CustomResource) -> crate::Result<()>;
     async fn get_custom_resource(&self, kind: &str, namespace: &str, name: &str) -> crate::Result<Option<CustomResource>>;
     async fn update_custom_resource_status(&self, id: Uuid, status: serde_json::Value) -> crate::Result<()>;
     async fn delete_custom_resource(&self, kind: &str, namespace: &str, name: &str) -> crate::Result<()>;
-    async fn list_custom_resources(&self, kind: &str, namespace: Option<&str>) -> crate::Result<Vec<CustomResource>>;
+    /// One page of the custom resources of `kind`, newest first. Lists every
+    /// namespace when `all_namespaces` is set; otherwise `namespace` is required
+    async fn list_custom_resources(
+        &self,
+        kind: &str,
+        namespace: Option<&str>,
+        all_namespaces: bool,
+        limit: i64,
+        offset: i64,
+    ) -> crate::Result<Vec<CustomResource>>;
+    /// Number of custom resources of `kind` in `namespace`, or in all
+    /// namespaces when `None`
+    async fn count_custom_resources(&self, kind: &str, namespace: Option<&str>) -> crate::Result<i64>;
+    
+    // Chat session operations
+    async fn save_chat_session(&self, session: ChatSession) -> crate::Result<()>;
+    async fn get_chat_session(&self, session_id: &str) -> crate::Result<Option<ChatSession>>;
+    async fn list_chat_sessions_by_user(&self, user_id: &str) -> crate::Result<Vec<ChatSession>>;
+    async fn delete_chat_sessions_before(&self, cutoff: DateTime<Utc>) -> crate::Result<u64>;
     
     // Alert deduplication
     async fn deduplicate_alert(&self, fingerprint: &str, alert: Alert) -> crate::Result<DeduplicationResult>;
+    /// Atomically return the unresolved alert with `alert.fingerprint`, or
+    /// insert `alert` if there is none.
+    ///
+    /// An unresolved alert that hasn't been updated within the store's
+    /// dedup window is resolved and `alert` inserted in its place. One that
+    /// has takes on `alert`'s labels and annotations, and is `Updated` if
+    /// that changed any of them.
+    async fn get_or_create_alert_by_fingerprint(&self, alert: Alert) -> crate::Result<DeduplicationResult>;
+    
+    // Audit log operations
+    /// Append an entry to the audit log; entries are never changed
+    async fn save_audit_entry(&self, entry: AuditEntry) -> crate::Result<()>;
+    /// Most recent audit entries first, optionally only those for one
+    /// entity type and/or entity id
+    async fn list_audit_entries(&self, entity_type: Option<&str>, entity_id: Option<&str>, limit: i64) -> crate::Result<Vec<AuditEntry>>;
+    
+    // Tool invocation operations
+    async fn save_tool_invocation(&self, invocation: ToolInvocation) -> crate::Result<()>;
+    /// The workflow's tool calls, in the order they were made
+    async fn list_tool_invocations_by_workflow(&self, workflow_id: Uuid) -> crate::Result<Vec<ToolInvocation>>;
 }
 
 #[derive(Debug)]
 pub enum DeduplicationResult {
+    /// No unresolved alert within the dedup window had the fingerprint
     New(Alert),
+    /// A re-fire of an unresolved alert that changed nothing
     Duplicate(Alert),
+    /// A re-fire of an unresolved alert with new labels or annotations
     Updated(Alert),
+}
+
+/// `existing` labels or annotations with those of a re-fire laid over them
+pub(crate) fn merge_refire(existing: &HashMap<String, String>, refire: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut merged = existing.clone();
+    merged.extend(refire.iter().map(|(k, v)| (k.clone(), v.clone())));
+    merged
 } 
\ No newline at end of file
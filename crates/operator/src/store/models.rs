@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use uuid::Uuid;
 
 // Alert lifecycle tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Alert {
     pub id: Uuid,
     pub external_id: Option<String>,
@@ -37,7 +38,7 @@ pub struct Alert {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AlertStatus {
     Received,
@@ -46,7 +47,7 @@ pub enum AlertStatus {
     Escalated,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AlertSeverity {
     Critical,
@@ -54,8 +55,16 @@ pub enum AlertSeverity {
     Info,
 }
 
+// Investigation note attached to an alert, kept in its `_notes` annotation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AlertNote {
+    pub author: String,
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+}
+
 // Workflow execution tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Workflow {
     pub id: Uuid,
     pub name: String,
@@ -79,17 +88,26 @@ pub struct Workflow {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum WorkflowStatus {
     Pending,
     Running,
     Succeeded,
     Failed,
+    Cancelled,
+}
+
+/// What happened to each workflow named in a bulk cancel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BulkCancelResult {
+    pub cancelled: u64,
+    pub not_found: u64,
+    pub already_completed: u64,
 }
 
 // Source event tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SourceEvent {
     pub id: Uuid,
     pub source_name: String,
@@ -99,7 +117,17 @@ pub struct SourceEvent {
     pub received_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+// A source event with the outcome of the workflow it queued, if any
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SourceEventWithWorkflow {
+    #[serde(flatten)]
+    pub event: SourceEvent,
+    pub workflow_id: Option<Uuid>,
+    pub workflow_status: Option<WorkflowStatus>,
+    pub workflow_completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SourceType {
     Webhook,
@@ -107,10 +135,11 @@ pub enum SourceType {
     Schedule,
     Api,
     Kubernetes,
+    Escalation,
 }
 
 // Workflow step tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WorkflowStep {
     pub id: Uuid,
     pub workflow_id: Uuid,
@@ -130,15 +159,16 @@ pub struct WorkflowStep {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum StepType {
     Cli,
     Agent,
     Conditional,
+    Http,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum StepStatus {
     Pending,
@@ -149,7 +179,7 @@ pub enum StepStatus {
 }
 
 // Sink output tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SinkOutput {
     pub id: Uuid,
     pub workflow_id: Uuid,
@@ -165,7 +195,7 @@ pub struct SinkOutput {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SinkType {
     Slack,
@@ -174,9 +204,29 @@ pub enum SinkType {
     Jira,
     PagerDuty,
     Workflow,
+    Stdout,
+    Webhook,
+    Inhibition,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+impl From<crate::crd::sink::SinkType> for SinkType {
+    fn from(sink_type: crate::crd::sink::SinkType) -> Self {
+        use crate::crd::sink::SinkType as CRDSinkType;
+        match sink_type {
+            CRDSinkType::Slack => SinkType::Slack,
+            CRDSinkType::AlertManager => SinkType::AlertManager,
+            CRDSinkType::Prometheus => SinkType::Prometheus,
+            CRDSinkType::Jira => SinkType::Jira,
+            CRDSinkType::PagerDuty => SinkType::PagerDuty,
+            CRDSinkType::Workflow => SinkType::Workflow,
+            CRDSinkType::Stdout => SinkType::Stdout,
+            CRDSinkType::Webhook => SinkType::Webhook,
+            CRDSinkType::Inhibition => SinkType::Inhibition,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SinkStatus {
     Pending,
@@ -198,8 +248,129 @@ pub struct CustomResource {
     pub updated_at: DateTime<Utc>,
 }
 
+// Workflow completions aggregated into a time bucket
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub succeeded: i64,
+    pub failed: i64,
+}
+
+// A workflow with everything recorded for it, for reports
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowWithAllData {
+    pub workflow: Workflow,
+    pub steps: Vec<WorkflowStep>,
+    pub sink_outputs: Vec<SinkOutput>,
+}
+
+// Delivery counts and latency for one sink type
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SinkTypeStats {
+    pub sink_type: SinkType,
+    pub sent: i64,
+    pub failed: i64,
+    pub pending: i64,
+    pub avg_latency_ms: Option<f64>,
+}
+
+// Database connectivity and per-table statistics
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StoreHealth {
+    pub connected: bool,
+    /// Total size of the database on disk
+    pub size_bytes: Option<i64>,
+    pub tables: HashMap<String, TableStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TableStats {
+    pub row_count: i64,
+    /// Size of the table including its indexes
+    pub size_bytes: Option<i64>,
+}
+
+// One change made through the store, recorded by `AuditingStore`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    /// Store method that made the change, e.g. `update_alert_status`
+    pub operation: String,
+    /// `alert`, `workflow`, `workflow_step`, `source_event`, `sink_output`,
+    /// `custom_resource` or `chat_session`
+    pub entity_type: String,
+    pub entity_id: String,
+    /// User whose API request made the change; unset for changes the
+    /// operator makes on its own
+    pub actor: Option<String>,
+    /// The entity before the change; unset if it didn't exist
+    pub before: Option<JsonValue>,
+    /// The entity after the change; unset if it was deleted
+    pub after: Option<JsonValue>,
+    pub created_at: DateTime<Utc>,
+}
+
+// One tool call made by an agent during a workflow
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ToolInvocation {
+    pub id: Uuid,
+    pub workflow_id: Uuid,
+    /// Agent step that made the call
+    pub step_name: Option<String>,
+    /// `kubectl`, `promql`, `curl`, `script` or `ingress`
+    pub tool_name: String,
+    pub arguments: JsonValue,
+    pub success: bool,
+    /// The tool's output, or its error if the call failed, truncated to
+    /// `TOOL_INVOCATION_OUTPUT_LIMIT` bytes
+    pub output: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Longest tool output kept with a `ToolInvocation`
+pub const TOOL_INVOCATION_OUTPUT_LIMIT: usize = 4096;
+
+// Chatbot conversation history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSession {
+    pub session_id: String,
+    pub user_id: String,
+    pub messages: JsonValue,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 // Helper functions for alert fingerprinting
 impl Alert {
+    /// Annotation holding the alert's investigation notes as a JSON array
+    pub const NOTES_ANNOTATION: &'static str = "_notes";
+    
+    /// Investigation notes in the order they were added
+    pub fn notes(&self) -> Vec<AlertNote> {
+        self.annotations.get(Self::NOTES_ANNOTATION)
+            .and_then(|notes| serde_json::from_str(notes).ok())
+            .unwrap_or_default()
+    }
+    
+    /// Annotation counting how often the alert was requeued after being
+    /// dropped without a workflow
+    pub const REQUEUE_COUNT_ANNOTATION: &'static str = "_requeue_count";
+    
+    /// How often the alert was requeued
+    pub fn requeue_count(&self) -> u32 {
+        self.annotations.get(Self::REQUEUE_COUNT_ANNOTATION)
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(0)
+    }
+    
+    /// Annotation holding the namespace of the Source that received the alert
+    pub const SOURCE_NAMESPACE_ANNOTATION: &'static str = "_source_namespace";
+    
+    /// Namespace of the Source that received the alert
+    pub fn source_namespace(&self) -> Option<&str> {
+        self.annotations.get(Self::SOURCE_NAMESPACE_ANNOTATION).map(String::as_str)
+    }
+    
     pub fn generate_fingerprint(alert_name: &str, labels: &HashMap<String, String>) -> String {
         use std::collections::BTreeMap;
         
@@ -1,22 +1,63 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
-use sqlx::{postgres::PgPool, Pool, Postgres};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::future::BoxFuture;
+use sqlx::{pool::PoolConnection, postgres::{PgArguments, PgConnection, PgPool, PgRow}, query::Query, Connection, Pool, Postgres, Row, Transaction};
+use tokio::sync::{Mutex, OwnedMutexGuard};
 use tracing::{error, info};
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
 use serde_json::Value as JsonValue;
 
 use crate::{
     store::{
-        Alert, AlertStatus, CustomResource, DeduplicationResult, 
-        SinkOutput, SinkStatus, SourceEvent, StepStatus, 
-        Store, Workflow, WorkflowStatus, WorkflowStep,
+        Alert, AlertNote, AlertSeverity, AlertStatus, AuditEntry, BulkCancelResult, ChatSession, CustomResource, DeduplicationResult, 
+        SinkOutput, SinkStatus, SinkTypeStats, SourceEvent, SourceEventWithWorkflow, StepStatus, 
+        Store, StoreHealth, TableStats, ToolInvocation, Workflow, WorkflowBucket, WorkflowStatus, WorkflowStep, WorkflowWithAllData,
+        merge_refire, migration_lock_timeout, HEALTH_CHECK_TABLES, MIGRATION_LOCK_NAME, MIGRATION_LOCK_RETRY_INTERVAL,
+        MIGRATION_LOCK_TIMEOUT,
     },
     Error, Result,
 };
 
+tokio::task_local! {
+    /// The `Store::transaction` the current task runs in
+    static TRANSACTION: Arc<Mutex<Transaction<'static, Postgres>>>;
+}
+
+/// Connection a query runs on
+enum Conn {
+    Pooled(Box<PoolConnection<Postgres>>),
+    /// The current task's transaction, held until dropped
+    Transaction(OwnedMutexGuard<Transaction<'static, Postgres>>),
+}
+
+impl Deref for Conn {
+    type Target = PgConnection;
+    
+    fn deref(&self) -> &PgConnection {
+        match self {
+            Conn::Pooled(conn) => conn,
+            Conn::Transaction(tx) => tx,
+        }
+    }
+}
+
+impl DerefMut for Conn {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        match self {
+            Conn::Pooled(conn) => conn,
+            Conn::Transaction(tx) => tx,
+        }
+    }
+}
+
 pub struct PostgresStore {
     pool: Pool<Postgres>,
+    connection_string: String,
+    dedup_window: Option<chrono::Duration>,
 }
 
 impl PostgresStore {
@@ -30,7 +71,22 @@ impl PostgresStore {
                 Error::Sqlx(e)
             })?;
         
-        Ok(Self { pool })
+        Ok(Self { pool, connection_string: connection_string.to_string(), dedup_window: None })
+    }
+    
+    /// Stop deduplicating against alerts not updated within `window`;
+    /// `None` deduplicates until they're resolved
+    pub fn with_dedup_window(mut self, window: Option<std::time::Duration>) -> Self {
+        self.dedup_window = window.and_then(|w| chrono::Duration::from_std(w).ok());
+        self
+    }
+    
+    /// The current task's transaction, or else a connection from the pool
+    async fn conn(&self) -> Result<Conn> {
+        match TRANSACTION.try_with(Arc::clone) {
+            Ok(tx) => Ok(Conn::Transaction(tx.lock_owned().await)),
+            Err(_) => Ok(Conn::Pooled(Box::new(self.pool.acquire().await?))),
+        }
     }
 }
 
@@ -39,149 +95,1726 @@ impl Store for PostgresStore {
     async fn init(&self) -> Result<()> {
         info!("Running database migrations");
         
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to run migrations: {}", e);
-                Error::Migrate(e)
-            })?;
+        // Advisory locks are held per session, so lock, migrate and unlock on one connection
+        let mut conn = self.pool.acquire().await?;
+        let deadline = Instant::now() + MIGRATION_LOCK_TIMEOUT;
+        loop {
+            let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock(HASHTEXT($1))")
+                .bind(MIGRATION_LOCK_NAME)
+                .fetch_one(&mut *conn)
+                .await?;
+            if locked {
+                break;
+            }
+            if Instant::now() >= deadline {
+                error!("Timed out waiting for migration lock");
+                return Err(migration_lock_timeout());
+            }
+            info!("Waiting for another replica to finish migrations");
+            tokio::time::sleep(MIGRATION_LOCK_RETRY_INTERVAL).await;
+        }
+        
+        let result = sqlx::migrate!("./migrations").run_direct(&mut *conn).await;
+        
+        let unlocked = sqlx::query("SELECT pg_advisory_unlock(HASHTEXT($1))")
+            .bind(MIGRATION_LOCK_NAME)
+            .execute(&mut *conn)
+            .await;
+        if let Err(e) = &unlocked {
+            // The lock goes with the session, so don't hand the connection back
+            error!("Failed to release migration lock: {}", e);
+            conn.detach();
+        }
+        
+        // A failed migration matters more than a failed unlock
+        result.map_err(|e| {
+            error!("Failed to run migrations: {}", e);
+            Error::Migrate(e)
+        })?;
+        unlocked?;
+        
+        self.migrate_alert_labels_to_jsonb().await
+    }
+    
+    async fn migrate_alert_labels_to_jsonb(&self) -> Result<()> {
+        // The migrations directory is shared with SQLite, which has no JSONB
+        // or GIN indexes, so this runs outside of it
+        sqlx::query(
+            "ALTER TABLE alerts ADD COLUMN IF NOT EXISTS labels_jsonb JSONB GENERATED ALWAYS AS (labels::jsonb) STORED",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_alerts_labels_jsonb ON alerts USING gin(labels_jsonb)",
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn health_details(&self) -> Result<StoreHealth> {
+        if let Err(e) = sqlx::query("SELECT 1").execute(&self.pool).await {
+            error!("Database health check failed: {}", e);
+            return Ok(StoreHealth { connected: false, size_bytes: None, tables: HashMap::new() });
+        }
+        
+        let size_bytes: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
+            .fetch_one(&self.pool)
+            .await?;
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT relname, n_live_tup, pg_total_relation_size(relid) AS size_bytes
+            FROM pg_catalog.pg_stat_user_tables
+            WHERE relname = ANY($1)
+            "#,
+        )
+        .bind(HEALTH_CHECK_TABLES)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let tables = rows.iter()
+            .map(|r| (
+                r.get::<String, _>("relname"),
+                TableStats {
+                    row_count: r.get("n_live_tup"),
+                    size_bytes: r.get("size_bytes"),
+                },
+            ))
+            .collect();
+        
+        Ok(StoreHealth { connected: true, size_bytes: Some(size_bytes), tables })
+    }
+    
+    async fn backup_to_path(&self, path: &std::path::Path) -> Result<u64> {
+        let pg_dump = find_executable("pg_dump")
+            .ok_or_else(|| Error::Config("pg_dump not found in PATH".to_string()))?;
+        info!("Backing up database to {} with {}", path.display(), pg_dump.display());
+        
+        let output = tokio::process::Command::new(&pg_dump)
+            .arg("--format=custom")
+            .arg("--file")
+            .arg(path)
+            .arg("--dbname")
+            .arg(&self.connection_string)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(Error::Execution(format!(
+                "pg_dump exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        
+        Ok(tokio::fs::metadata(path).await?.len())
+    }
+    
+    async fn transaction<'a>(&'a self, work: BoxFuture<'a, Result<()>>) -> Result<()> {
+        if TRANSACTION.try_with(|_| ()).is_ok() {
+            return work.await;
+        }
+        
+        let tx = Arc::new(Mutex::new(self.pool.begin().await?));
+        let result = TRANSACTION.scope(tx.clone(), work).await;
+        let tx = Arc::try_unwrap(tx)
+            .map_err(|_| Error::Internal("Transaction still in use after its work finished".to_string()))?
+            .into_inner();
+        match result {
+            Ok(()) => Ok(tx.commit().await?),
+            Err(e) => {
+                tx.rollback().await?;
+                Err(e)
+            }
+        }
+    }
+    
+    async fn save_alert(&self, alert: Alert) -> Result<()> {
+        bind_alert(sqlx::query(
+            r#"
+            INSERT INTO alerts (
+                id, external_id, fingerprint, status, severity, alert_name, name,
+                summary, description, labels, annotations, source_id, workflow_id,
+                ai_analysis, ai_confidence, auto_resolved,
+                starts_at, ends_at, received_at, triage_started_at,
+                triage_completed_at, resolved_at, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                ai_analysis = EXCLUDED.ai_analysis,
+                ai_confidence = EXCLUDED.ai_confidence,
+                auto_resolved = EXCLUDED.auto_resolved,
+                workflow_id = EXCLUDED.workflow_id,
+                triage_started_at = EXCLUDED.triage_started_at,
+                triage_completed_at = EXCLUDED.triage_completed_at,
+                resolved_at = EXCLUDED.resolved_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        ), &alert)?
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn get_alert(&self, id: Uuid) -> Result<Option<Alert>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(alert_from_row).transpose()
+    }
+    
+    async fn get_alert_by_fingerprint(&self, fingerprint: &str) -> Result<Option<Alert>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE fingerprint = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(fingerprint)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(alert_from_row).transpose()
+    }
+    
+    async fn update_alert_status(&self, id: Uuid, status: AlertStatus) -> Result<()> {
+        sqlx::query(
+            "UPDATE alerts SET status = $1, updated_at = $2 WHERE id = $3",
+        )
+        .bind(status.to_string())
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn update_alert_severity(&self, id: Uuid, severity: AlertSeverity) -> Result<()> {
+        sqlx::query(
+            "UPDATE alerts SET severity = $1, updated_at = $2 WHERE id = $3",
+        )
+        .bind(severity.to_string())
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn update_alert_ai_analysis(&self, id: Uuid, analysis: JsonValue, confidence: f32) -> Result<()> {
+        sqlx::query(
+            "UPDATE alerts SET ai_analysis = $1, ai_confidence = $2, updated_at = $3 WHERE id = $4",
+        )
+        .bind(serde_json::to_string(&analysis)?)
+        .bind(confidence)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn append_alert_annotation(&self, id: Uuid, note: AlertNote) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE alerts
+            SET annotations = jsonb_set(
+                    annotations::jsonb,
+                    '{_notes}',
+                    to_jsonb((COALESCE((annotations::jsonb ->> '_notes')::jsonb, '[]'::jsonb) || jsonb_build_array($1::jsonb))::text)
+                )::text,
+                updated_at = $2
+            WHERE id = $3
+            "#,
+        )
+        .bind(serde_json::to_string(&note)?)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(format!("Alert {} not found", id)));
+        }
+        Ok(())
+    }
+    
+    async fn set_alert_annotation(&self, id: Uuid, key: &str, value: &str) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE alerts SET annotations = jsonb_set(annotations::jsonb, ARRAY[$1], to_jsonb($2::text))::text, updated_at = $3 WHERE id = $4",
+        )
+        .bind(key)
+        .bind(value)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(format!("Alert {} not found", id)));
+        }
+        Ok(())
+    }
+    
+    async fn update_alert_timing(&self, id: Uuid, field: &str, timestamp: DateTime<Utc>) -> Result<()> {
+        // Column names can't be bound, so only known ones make it into the SQL
+        let query = match field {
+            "triage_started_at" => "UPDATE alerts SET triage_started_at = $1, updated_at = $2 WHERE id = $3 RETURNING received_at",
+            "triage_completed_at" => "UPDATE alerts SET triage_completed_at = $1, updated_at = $2 WHERE id = $3 RETURNING received_at",
+            "resolved_at" => "UPDATE alerts SET resolved_at = $1, updated_at = $2 WHERE id = $3 RETURNING received_at",
+            _ => return Err(Error::Config(format!("Invalid timing field: {}", field))),
+        };
+        
+        let row = sqlx::query(query)
+            .bind(timestamp.naive_utc())
+            .bind(Utc::now().naive_utc())
+            .bind(id)
+            .fetch_optional(&mut *self.conn().await?)
+            .await?;
+        if let Some(row) = row {
+            crate::metrics::observe_alert_timing(field, row.get::<NaiveDateTime, _>("received_at").and_utc(), timestamp);
+        }
+        Ok(())
+    }
+    
+    async fn list_alerts(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<Vec<Alert>> {
+        // A NULL bound leaves that end of the range open
+        let rows = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE ($3::TIMESTAMP IS NULL OR created_at >= $3)
+              AND ($4::TIMESTAMP IS NULL OR created_at < $4)
+            ORDER BY created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .bind(since.map(|t| t.naive_utc()))
+        .bind(until.map(|t| t.naive_utc()))
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(alert_from_row).collect()
+    }
+    
+    async fn list_alerts_by_status(&self, status: AlertStatus, limit: i64) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE status = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(status.to_string())
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(alert_from_row).collect()
+    }
+    
+    async fn count_alerts(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM alerts WHERE ($1::TIMESTAMP IS NULL OR created_at >= $1) AND ($2::TIMESTAMP IS NULL OR created_at < $2)",
+        )
+        .bind(since.map(|t| t.naive_utc()))
+        .bind(until.map(|t| t.naive_utc()))
+        .fetch_one(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(count)
+    }
+    
+    async fn count_alerts_by_status(&self, status: AlertStatus) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alerts WHERE status = $1")
+            .bind(status.to_string())
+            .fetch_one(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(count)
+    }
+    
+    async fn deduplicate_alert(&self, fingerprint: &str, mut alert: Alert) -> Result<DeduplicationResult> {
+        alert.fingerprint = fingerprint.to_string();
+        self.get_or_create_alert_by_fingerprint(alert).await
+    }
+    
+    async fn get_or_create_alert_by_fingerprint(&self, alert: Alert) -> Result<DeduplicationResult> {
+        let now = Utc::now();
+        let mut conn = self.conn().await?;
+        let mut tx = conn.begin().await?;
+        
+        // A stale alert is resolved to make way for the new one
+        if let Some(window) = self.dedup_window {
+            sqlx::query(
+                r#"
+                UPDATE alerts SET status = 'resolved', resolved_at = $1, updated_at = $1
+                WHERE fingerprint = $2 AND status <> 'resolved' AND updated_at < $3
+                "#,
+            )
+            .bind(now.naive_utc())
+            .bind(&alert.fingerprint)
+            .bind((now - window).naive_utc())
+            .execute(&mut *tx)
+            .await?;
+        }
+        
+        // A concurrent delivery of the same alert makes the insert wait on
+        // the unique index, then do nothing once that delivery commits
+        let inserted = bind_alert(sqlx::query(
+            r#"
+            INSERT INTO alerts (
+                id, external_id, fingerprint, status, severity, alert_name, name,
+                summary, description, labels, annotations, source_id, workflow_id,
+                ai_analysis, ai_confidence, auto_resolved,
+                starts_at, ends_at, received_at, triage_started_at,
+                triage_completed_at, resolved_at, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+            ON CONFLICT (fingerprint) WHERE status <> 'resolved' DO NOTHING
+            RETURNING id, external_id, fingerprint, status, severity, alert_name,
+                      summary, description, labels, annotations, source_id, workflow_id,
+                      ai_analysis, ai_confidence, auto_resolved,
+                      starts_at, ends_at, received_at, triage_started_at,
+                      triage_completed_at, resolved_at, created_at, updated_at
+            "#,
+        ), &alert)?
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(row) = inserted {
+            tx.commit().await?;
+            return Ok(DeduplicationResult::New(alert_from_row(&row)?));
+        }
+        
+        let (labels, annotations): (String, String) = sqlx::query_as(
+            "SELECT labels, annotations FROM alerts WHERE fingerprint = $1 AND status <> 'resolved' FOR UPDATE",
+        )
+        .bind(&alert.fingerprint)
+        .fetch_one(&mut *tx)
+        .await?;
+        let labels: HashMap<String, String> = serde_json::from_str(&labels)?;
+        let annotations: HashMap<String, String> = serde_json::from_str(&annotations)?;
+        let merged_labels = merge_refire(&labels, &alert.labels);
+        let merged_annotations = merge_refire(&annotations, &alert.annotations);
+        let changed = merged_labels != labels || merged_annotations != annotations;
+        
+        let row = sqlx::query(
+            r#"
+            UPDATE alerts SET labels = $1, annotations = $2, updated_at = $3
+            WHERE fingerprint = $4 AND status <> 'resolved'
+            RETURNING id, external_id, fingerprint, status, severity, alert_name,
+                      summary, description, labels, annotations, source_id, workflow_id,
+                      ai_analysis, ai_confidence, auto_resolved,
+                      starts_at, ends_at, received_at, triage_started_at,
+                      triage_completed_at, resolved_at, created_at, updated_at
+            "#,
+        )
+        .bind(serde_json::to_string(&merged_labels)?)
+        .bind(serde_json::to_string(&merged_annotations)?)
+        .bind(now.naive_utc())
+        .bind(&alert.fingerprint)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        let existing = alert_from_row(&row)?;
+        if changed {
+            Ok(DeduplicationResult::Updated(existing))
+        } else {
+            Ok(DeduplicationResult::Duplicate(existing))
+        }
+    }
+    
+    async fn get_recent_alerts_for_deployment(&self, namespace: &str, deployment: &str, limit: i64) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE labels::jsonb ->> 'namespace' = $1
+              AND COALESCE(labels::jsonb ->> 'deployment', labels::jsonb ->> 'app') = $2
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(namespace)
+        .bind(deployment)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(alert_from_row).collect()
+    }
+    
+    async fn list_alerts_by_label(&self, labels: &HashMap<String, String>, limit: i64) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE labels_jsonb @> $1::jsonb
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(serde_json::to_string(labels)?)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(alert_from_row).collect()
+    }
+    
+    async fn list_alerts_near_expiry(&self, within_minutes: i64) -> Result<Vec<Alert>> {
+        let now = Utc::now();
+        let rows = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE status <> 'resolved'
+              AND ends_at >= $1
+              AND ends_at <= $2
+            ORDER BY ends_at ASC
+            "#,
+        )
+        .bind(now.naive_utc())
+        .bind((now + chrono::Duration::minutes(within_minutes)).naive_utc())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(alert_from_row).collect()
+    }
+    
+    async fn list_alerts_without_workflow(&self, older_than_minutes: i64) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, external_id, fingerprint, status, severity, alert_name,
+                   summary, description, labels, annotations, source_id, workflow_id,
+                   ai_analysis, ai_confidence, auto_resolved,
+                   starts_at, ends_at, received_at, triage_started_at,
+                   triage_completed_at, resolved_at, created_at, updated_at
+            FROM alerts
+            WHERE workflow_id IS NULL
+              AND triage_started_at IS NULL
+              AND status = 'received'
+              AND received_at < $1
+            ORDER BY received_at ASC
+            "#,
+        )
+        .bind((Utc::now() - chrono::Duration::minutes(older_than_minutes)).naive_utc())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(alert_from_row).collect()
+    }
+    
+    async fn save_workflow(&self, workflow: Workflow) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflows (
+                id, name, namespace, trigger_source, status,
+                steps_completed, total_steps, current_step,
+                input_context, outputs, error,
+                started_at, completed_at, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                steps_completed = EXCLUDED.steps_completed,
+                current_step = EXCLUDED.current_step,
+                outputs = EXCLUDED.outputs,
+                error = EXCLUDED.error,
+                completed_at = EXCLUDED.completed_at
+            "#,
+        )
+        .bind(workflow.id)
+        .bind(&workflow.name)
+        .bind(&workflow.namespace)
+        .bind(&workflow.trigger_source)
+        .bind(workflow.status.to_string())
+        .bind(workflow.steps_completed)
+        .bind(workflow.total_steps)
+        .bind(&workflow.current_step)
+        .bind(workflow.input_context.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(workflow.outputs.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(&workflow.error)
+        .bind(workflow.started_at.naive_utc())
+        .bind(workflow.completed_at.map(|t| t.naive_utc()))
+        .bind(workflow.created_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn get_workflow(&self, id: Uuid) -> Result<Option<Workflow>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, namespace, trigger_source, status,
+                   steps_completed, total_steps, current_step,
+                   input_context, outputs, error,
+                   started_at, completed_at, created_at
+            FROM workflows
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(workflow_from_row).transpose()
+    }
+    
+    async fn update_workflow_status(&self, id: Uuid, status: WorkflowStatus) -> Result<()> {
+        sqlx::query("UPDATE workflows SET status = $1 WHERE id = $2")
+            .bind(status.to_string())
+            .bind(id)
+            .execute(&mut *self.conn().await?)
+            .await?;
         
         Ok(())
     }
     
-    // TODO: Implement all the Phase 1 store methods for PostgreSQL
-    // For now, using placeholder implementations
+    async fn update_workflow_progress(&self, id: Uuid, steps_completed: i32, current_step: Option<String>) -> Result<()> {
+        sqlx::query("UPDATE workflows SET steps_completed = $1, current_step = $2 WHERE id = $3")
+            .bind(steps_completed)
+            .bind(current_step)
+            .bind(id)
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
+    }
     
-    async fn save_alert(&self, _alert: Alert) -> Result<()> {
-        todo!("Implement save_alert for PostgreSQL")
+    async fn update_workflow_outputs(&self, id: Uuid, outputs: JsonValue) -> Result<()> {
+        sqlx::query("UPDATE workflows SET outputs = $1 WHERE id = $2")
+            .bind(serde_json::to_string(&outputs)?)
+            .bind(id)
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
     }
     
-    async fn get_alert(&self, _id: Uuid) -> Result<Option<Alert>> {
-        todo!("Implement get_alert for PostgreSQL")
+    async fn bulk_cancel_workflows(&self, ids: &[Uuid], reason: &str) -> Result<BulkCancelResult> {
+        let mut conn = self.conn().await?;
+        let mut tx = conn.begin().await?;
+        
+        // Lock the rows so none finishes between counting and cancelling
+        let found = sqlx::query("SELECT id FROM workflows WHERE id = ANY($1) FOR UPDATE")
+            .bind(ids)
+            .fetch_all(&mut *tx)
+            .await?
+            .len() as u64;
+        
+        let result = sqlx::query(
+            r#"
+            UPDATE workflows
+            SET status = $1, error = $2, completed_at = $3
+            WHERE status IN ($4, $5)
+              AND id = ANY($6)
+            "#,
+        )
+        .bind(WorkflowStatus::Cancelled.to_string())
+        .bind(reason)
+        .bind(Utc::now().naive_utc())
+        .bind(WorkflowStatus::Pending.to_string())
+        .bind(WorkflowStatus::Running.to_string())
+        .bind(ids)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        let cancelled = result.rows_affected();
+        Ok(BulkCancelResult {
+            cancelled,
+            not_found: ids.len() as u64 - found,
+            already_completed: found - cancelled,
+        })
     }
     
-    async fn get_alert_by_fingerprint(&self, _fingerprint: &str) -> Result<Option<Alert>> {
-        todo!("Implement get_alert_by_fingerprint for PostgreSQL")
+    async fn complete_workflow(&self, id: Uuid, status: WorkflowStatus, outputs: Option<JsonValue>, error: Option<String>) -> Result<()> {
+        sqlx::query(
+            "UPDATE workflows SET status = $1, outputs = $2, error = $3, completed_at = $4 WHERE id = $5",
+        )
+        .bind(status.to_string())
+        .bind(outputs.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(error)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn update_alert_status(&self, _id: Uuid, _status: AlertStatus) -> Result<()> {
-        todo!("Implement update_alert_status for PostgreSQL")
+    async fn list_workflows(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<Vec<Workflow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, namespace, trigger_source, status,
+                   steps_completed, total_steps, current_step,
+                   input_context, outputs, error,
+                   started_at, completed_at, created_at
+            FROM workflows
+            WHERE ($3::TIMESTAMP IS NULL OR created_at >= $3)
+              AND ($4::TIMESTAMP IS NULL OR created_at < $4)
+            ORDER BY created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .bind(since.map(|t| t.naive_utc()))
+        .bind(until.map(|t| t.naive_utc()))
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(workflow_from_row).collect()
     }
     
-    async fn update_alert_ai_analysis(&self, _id: Uuid, _analysis: JsonValue, _confidence: f32) -> Result<()> {
-        todo!("Implement update_alert_ai_analysis for PostgreSQL")
+    async fn list_workflows_by_status(
+        &self,
+        status: WorkflowStatus,
+        limit: i64,
+        offset: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Workflow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, namespace, trigger_source, status,
+                   steps_completed, total_steps, current_step,
+                   input_context, outputs, error,
+                   started_at, completed_at, created_at
+            FROM workflows
+            WHERE status = $1
+              AND ($4::TIMESTAMP IS NULL OR created_at >= $4)
+              AND ($5::TIMESTAMP IS NULL OR created_at < $5)
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(status.to_string())
+        .bind(limit)
+        .bind(offset)
+        .bind(since.map(|t| t.naive_utc()))
+        .bind(until.map(|t| t.naive_utc()))
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(workflow_from_row).collect()
     }
     
-    async fn update_alert_timing(&self, _id: Uuid, _field: &str, _timestamp: DateTime<Utc>) -> Result<()> {
-        todo!("Implement update_alert_timing for PostgreSQL")
+    async fn count_workflows(&self, status: Option<WorkflowStatus>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM workflows
+            WHERE ($1::TEXT IS NULL OR status = $1)
+              AND ($2::TIMESTAMP IS NULL OR created_at >= $2)
+              AND ($3::TIMESTAMP IS NULL OR created_at < $3)
+            "#,
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(since.map(|t| t.naive_utc()))
+        .bind(until.map(|t| t.naive_utc()))
+        .fetch_one(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(count)
     }
     
-    async fn list_alerts(&self, _limit: i64, _offset: i64) -> Result<Vec<Alert>> {
-        todo!("Implement list_alerts for PostgreSQL")
+    async fn list_workflows_for_alert(&self, alert_id: Uuid) -> Result<Vec<Workflow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, namespace, trigger_source, status,
+                   steps_completed, total_steps, current_step,
+                   input_context, outputs, error,
+                   started_at, completed_at, created_at
+            FROM workflows
+            WHERE id = (SELECT workflow_id FROM alerts WHERE id = $1)
+               OR input_context::jsonb ->> 'alert_id' = $1::text
+            ORDER BY started_at DESC
+            "#,
+        )
+        .bind(alert_id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(workflow_from_row).collect()
     }
     
-    async fn list_alerts_by_status(&self, _status: AlertStatus, _limit: i64) -> Result<Vec<Alert>> {
-        todo!("Implement list_alerts_by_status for PostgreSQL")
+    async fn get_workflow_with_all_data(&self, id: Uuid) -> Result<Option<WorkflowWithAllData>> {
+        // The workflow and its steps, one row per step
+        let rows = sqlx::query(
+            r#"
+            SELECT w.id, w.name, w.namespace, w.trigger_source, w.status,
+                   w.steps_completed, w.total_steps, w.current_step,
+                   w.input_context, w.outputs, w.error,
+                   w.started_at, w.completed_at, w.created_at,
+                   s.id AS step_id, s.name AS step_name, s.step_type AS step_type,
+                   s.status AS step_status, s.config AS step_config,
+                   s.started_at AS step_started_at, s.completed_at AS step_completed_at,
+                   s.result AS step_result, s.error AS step_error, s.created_at AS step_created_at
+            FROM workflows w
+            LEFT JOIN workflow_steps s ON s.workflow_id = w.id
+            WHERE w.id = $1
+            ORDER BY s.created_at
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        let Some(first) = rows.first() else {
+            return Ok(None);
+        };
+        let workflow = workflow_from_row(first)?;
+        
+        let mut steps = Vec::new();
+        for r in &rows {
+            // A workflow without steps still comes back as one row
+            let Some(step_id) = r.get::<Option<Uuid>, _>("step_id") else {
+                continue;
+            };
+            let json = |column: &str| r.get::<Option<String>, _>(column)
+                .map(|s| serde_json::from_str(&s))
+                .transpose();
+            steps.push(WorkflowStep {
+                id: step_id,
+                workflow_id: workflow.id,
+                name: r.get("step_name"),
+                step_type: r.get::<String, _>("step_type").parse()?,
+                status: r.get::<String, _>("step_status").parse()?,
+                config: json("step_config")?,
+                started_at: r.get::<Option<NaiveDateTime>, _>("step_started_at").map(|t| t.and_utc()),
+                completed_at: r.get::<Option<NaiveDateTime>, _>("step_completed_at").map(|t| t.and_utc()),
+                result: json("step_result")?,
+                error: r.get("step_error"),
+                created_at: r.get::<NaiveDateTime, _>("step_created_at").and_utc(),
+            });
+        }
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, sink_name, sink_type,
+                   payload, status, error, sent_at, created_at
+            FROM sink_outputs
+            WHERE workflow_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        let sink_outputs = rows.iter().map(sink_output_from_row).collect::<Result<Vec<_>>>()?;
+        
+        Ok(Some(WorkflowWithAllData { workflow, steps, sink_outputs }))
     }
     
-    async fn deduplicate_alert(&self, _fingerprint: &str, _alert: Alert) -> Result<DeduplicationResult> {
-        todo!("Implement deduplicate_alert for PostgreSQL")
+    async fn list_workflows_for_time_range(&self, from: DateTime<Utc>, to: DateTime<Utc>, bucket_minutes: u32) -> Result<Vec<WorkflowBucket>> {
+        if bucket_minutes == 0 {
+            return Err(Error::Validation("Bucket size must be greater than zero".to_string()));
+        }
+        let bucket_seconds = f64::from(bucket_minutes) * 60.0;
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT to_timestamp(floor(extract(epoch FROM date_trunc('minute', completed_at))::float8 / $1) * $1) AS bucket_start,
+                   COUNT(*) FILTER (WHERE status = $4) AS succeeded,
+                   COUNT(*) FILTER (WHERE status = $5) AS failed
+            FROM workflows
+            WHERE completed_at IS NOT NULL
+              AND completed_at >= $2
+              AND completed_at < $3
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(bucket_seconds)
+        .bind(from.naive_utc())
+        .bind(to.naive_utc())
+        .bind(WorkflowStatus::Succeeded.to_string())
+        .bind(WorkflowStatus::Failed.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(rows.into_iter()
+            .map(|r| WorkflowBucket {
+                bucket_start: r.get("bucket_start"),
+                succeeded: r.get("succeeded"),
+                failed: r.get("failed"),
+            })
+            .collect())
     }
     
-    async fn save_workflow(&self, _workflow: Workflow) -> Result<()> {
-        todo!("Implement save_workflow for PostgreSQL")
+    async fn save_source_event(&self, event: SourceEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO source_events (
+                id, source_name, source_type, event_data, workflow_triggered, received_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(event.id)
+        .bind(&event.source_name)
+        .bind(event.source_type.to_string())
+        .bind(serde_json::to_string(&event.event_data)?)
+        .bind(&event.workflow_triggered)
+        .bind(event.received_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn get_workflow(&self, _id: Uuid) -> Result<Option<Workflow>> {
-        todo!("Implement get_workflow for PostgreSQL")
+    async fn save_scheduled_source_event(&self, event: SourceEvent, namespace: &str, scheduled_at: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO source_events (
+                id, source_name, source_type, event_data, workflow_triggered, received_at, scheduled_at,
+                source_namespace
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (source_namespace, source_name, scheduled_at) DO NOTHING
+            "#,
+        )
+        .bind(event.id)
+        .bind(&event.source_name)
+        .bind(event.source_type.to_string())
+        .bind(serde_json::to_string(&event.event_data)?)
+        .bind(&event.workflow_triggered)
+        .bind(event.received_at.naive_utc())
+        .bind(scheduled_at.naive_utc())
+        .bind(namespace)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
     }
     
-    async fn update_workflow_status(&self, _id: Uuid, _status: WorkflowStatus) -> Result<()> {
-        todo!("Implement update_workflow_status for PostgreSQL")
+    async fn get_source_event(&self, id: Uuid) -> Result<Option<SourceEvent>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, source_name, source_type, event_data, workflow_triggered, received_at
+            FROM source_events
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(source_event_from_row).transpose()
     }
     
-    async fn update_workflow_progress(&self, _id: Uuid, _steps_completed: i32, _current_step: Option<String>) -> Result<()> {
-        todo!("Implement update_workflow_progress for PostgreSQL")
+    async fn delete_source_event(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM source_events WHERE id = $1")
+            .bind(id)
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
     }
     
-    async fn update_workflow_outputs(&self, _id: Uuid, _outputs: JsonValue) -> Result<()> {
-        todo!("Implement update_workflow_outputs for PostgreSQL")
+    async fn list_source_events(&self, source_name: &str, limit: i64) -> Result<Vec<SourceEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, source_name, source_type, event_data, workflow_triggered, received_at
+            FROM source_events
+            WHERE source_name = $1
+            ORDER BY received_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(source_name)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(source_event_from_row).collect()
     }
     
-    async fn complete_workflow(&self, _id: Uuid, _status: WorkflowStatus, _outputs: Option<JsonValue>, _error: Option<String>) -> Result<()> {
-        todo!("Implement complete_workflow for PostgreSQL")
+    async fn set_source_event_workflow(&self, id: Uuid, workflow_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE source_events SET workflow_id = $1 WHERE id = $2")
+            .bind(workflow_id)
+            .bind(id)
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
     }
     
-    async fn list_workflows(&self, _limit: i64, _offset: i64) -> Result<Vec<Workflow>> {
-        todo!("Implement list_workflows for PostgreSQL")
+    async fn list_source_events_with_workflow_status(&self, source_name: &str, limit: i64) -> Result<Vec<SourceEventWithWorkflow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT e.id, e.source_name, e.source_type, e.event_data, e.workflow_triggered, e.received_at,
+                   w.id AS workflow_id, w.status AS workflow_status, w.completed_at AS workflow_completed_at
+            FROM source_events e
+            LEFT JOIN workflows w ON w.id = e.workflow_id
+            WHERE e.source_name = $1
+            ORDER BY e.received_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(source_name)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(|r| {
+            Ok(SourceEventWithWorkflow {
+                event: source_event_from_row(r)?,
+                workflow_id: r.get("workflow_id"),
+                workflow_status: r.get::<Option<String>, _>("workflow_status").map(|s| s.parse()).transpose()?,
+                workflow_completed_at: r.get::<Option<NaiveDateTime>, _>("workflow_completed_at").map(|t| t.and_utc()),
+            })
+        }).collect()
     }
     
-    async fn save_source_event(&self, _event: SourceEvent) -> Result<()> {
-        todo!("Implement save_source_event for PostgreSQL")
+    async fn save_workflow_step(&self, step: WorkflowStep) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_steps (
+                id, workflow_id, name, step_type, status,
+                config, started_at, completed_at, result, error, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                started_at = EXCLUDED.started_at,
+                completed_at = EXCLUDED.completed_at,
+                result = EXCLUDED.result,
+                error = EXCLUDED.error
+            "#,
+        )
+        .bind(step.id)
+        .bind(step.workflow_id)
+        .bind(&step.name)
+        .bind(step.step_type.to_string())
+        .bind(step.status.to_string())
+        .bind(step.config.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(step.started_at.map(|t| t.naive_utc()))
+        .bind(step.completed_at.map(|t| t.naive_utc()))
+        .bind(step.result.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(&step.error)
+        .bind(step.created_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn get_source_event(&self, _id: Uuid) -> Result<Option<SourceEvent>> {
-        todo!("Implement get_source_event for PostgreSQL")
+    async fn get_workflow_step(&self, id: Uuid) -> Result<Option<WorkflowStep>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, workflow_id, name, step_type, status,
+                   config, started_at, completed_at, result, error, created_at
+            FROM workflow_steps
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(workflow_step_from_row).transpose()
     }
     
-    async fn list_source_events(&self, _source_name: &str, _limit: i64) -> Result<Vec<SourceEvent>> {
-        todo!("Implement list_source_events for PostgreSQL")
+    async fn update_workflow_step_status(&self, id: Uuid, status: StepStatus) -> Result<()> {
+        // Only a step that starts running gets a start time
+        sqlx::query(
+            r#"
+            UPDATE workflow_steps
+            SET status = $1,
+                started_at = CASE WHEN $1 = $2 THEN $3 ELSE started_at END
+            WHERE id = $4
+            "#,
+        )
+        .bind(status.to_string())
+        .bind(StepStatus::Running.to_string())
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn save_workflow_step(&self, _step: WorkflowStep) -> Result<()> {
-        todo!("Implement save_workflow_step for PostgreSQL")
+    async fn complete_workflow_step(&self, id: Uuid, status: StepStatus, result: Option<JsonValue>, error: Option<String>) -> Result<()> {
+        sqlx::query(
+            "UPDATE workflow_steps SET status = $1, result = $2, error = $3, completed_at = $4 WHERE id = $5",
+        )
+        .bind(status.to_string())
+        .bind(result.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(error)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn get_workflow_step(&self, _id: Uuid) -> Result<Option<WorkflowStep>> {
-        todo!("Implement get_workflow_step for PostgreSQL")
+    async fn update_workflow_step_result(&self, id: Uuid, result: JsonValue) -> Result<()> {
+        sqlx::query("UPDATE workflow_steps SET result = $1 WHERE id = $2")
+            .bind(serde_json::to_string(&result)?)
+            .bind(id)
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
     }
     
-    async fn update_workflow_step_status(&self, _id: Uuid, _status: StepStatus) -> Result<()> {
-        todo!("Implement update_workflow_step_status for PostgreSQL")
+    async fn list_workflow_steps(&self, workflow_id: Uuid) -> Result<Vec<WorkflowStep>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, name, step_type, status,
+                   config, started_at, completed_at, result, error, created_at
+            FROM workflow_steps
+            WHERE workflow_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(workflow_id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(workflow_step_from_row).collect()
     }
     
-    async fn complete_workflow_step(&self, _id: Uuid, _status: StepStatus, _result: Option<JsonValue>, _error: Option<String>) -> Result<()> {
-        todo!("Implement complete_workflow_step for PostgreSQL")
+    async fn save_sink_output(&self, output: SinkOutput) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sink_outputs (
+                id, workflow_id, sink_name, sink_type,
+                payload, status, error, sent_at, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (id) DO UPDATE SET
+                payload = EXCLUDED.payload,
+                status = EXCLUDED.status,
+                error = EXCLUDED.error,
+                sent_at = EXCLUDED.sent_at
+            "#,
+        )
+        .bind(output.id)
+        .bind(output.workflow_id)
+        .bind(&output.sink_name)
+        .bind(output.sink_type.to_string())
+        .bind(output.payload.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(output.status.to_string())
+        .bind(&output.error)
+        .bind(output.sent_at.map(|t| t.naive_utc()))
+        .bind(output.created_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn list_workflow_steps(&self, _workflow_id: Uuid) -> Result<Vec<WorkflowStep>> {
-        todo!("Implement list_workflow_steps for PostgreSQL")
+    async fn get_sink_output(&self, id: Uuid) -> Result<Option<SinkOutput>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, workflow_id, sink_name, sink_type,
+                   payload, status, error, sent_at, created_at
+            FROM sink_outputs
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(sink_output_from_row).transpose()
     }
     
-    async fn save_sink_output(&self, _output: SinkOutput) -> Result<()> {
-        todo!("Implement save_sink_output for PostgreSQL")
+    async fn update_sink_output_status(&self, id: Uuid, status: SinkStatus, error: Option<String>) -> Result<()> {
+        let sent_at = matches!(status, SinkStatus::Sent).then(Utc::now);
+        
+        let row = sqlx::query(
+            "UPDATE sink_outputs SET status = $1, error = $2, sent_at = $3 WHERE id = $4 RETURNING sink_type, created_at",
+        )
+        .bind(status.to_string())
+        .bind(error)
+        .bind(sent_at.map(|t| t.naive_utc()))
+        .bind(id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        if let (Some(row), Some(sent_at)) = (row, sent_at) {
+            let created_at = row.get::<NaiveDateTime, _>("created_at").and_utc();
+            crate::metrics::observe_sink_delivery_latency(&row.get::<String, _>("sink_type"), created_at, sent_at);
+        }
+        
+        Ok(())
     }
     
-    async fn get_sink_output(&self, _id: Uuid) -> Result<Option<SinkOutput>> {
-        todo!("Implement get_sink_output for PostgreSQL")
+    async fn list_sink_outputs(&self, workflow_id: Uuid) -> Result<Vec<SinkOutput>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, sink_name, sink_type,
+                   payload, status, error, sent_at, created_at
+            FROM sink_outputs
+            WHERE workflow_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(workflow_id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(sink_output_from_row).collect()
     }
     
-    async fn update_sink_output_status(&self, _id: Uuid, _status: SinkStatus, _error: Option<String>) -> Result<()> {
-        todo!("Implement update_sink_output_status for PostgreSQL")
+    async fn count_sink_outputs_by_type(&self) -> Result<Vec<SinkTypeStats>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sink_type,
+                   COUNT(*) FILTER (WHERE status = $1) AS sent,
+                   COUNT(*) FILTER (WHERE status = $2) AS failed,
+                   COUNT(*) FILTER (WHERE status = $3) AS pending,
+                   (AVG(EXTRACT(EPOCH FROM (sent_at - created_at))) * 1000.0)::float8 AS avg_latency_ms
+            FROM sink_outputs
+            GROUP BY sink_type
+            ORDER BY sink_type
+            "#,
+        )
+        .bind(SinkStatus::Sent.to_string())
+        .bind(SinkStatus::Failed.to_string())
+        .bind(SinkStatus::Pending.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.into_iter()
+            .map(|r| Ok(SinkTypeStats {
+                sink_type: r.get::<String, _>("sink_type").parse()?,
+                sent: r.get("sent"),
+                failed: r.get("failed"),
+                pending: r.get("pending"),
+                avg_latency_ms: r.get("avg_latency_ms"),
+            }))
+            .collect()
     }
     
-    async fn list_sink_outputs(&self, _workflow_id: Uuid) -> Result<Vec<SinkOutput>> {
-        todo!("Implement list_sink_outputs for PostgreSQL")
+    async fn list_recent_sink_failures(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<SinkOutput>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, sink_name, sink_type,
+                   payload, status, error, sent_at, created_at
+            FROM sink_outputs
+            WHERE status = $1
+              AND created_at >= $2
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(SinkStatus::Failed.to_string())
+        .bind(since.naive_utc())
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(sink_output_from_row).collect()
     }
     
-    async fn save_custom_resource(&self, _resource: CustomResource) -> Result<()> {
-        todo!("Implement save_custom_resource for PostgreSQL")
+    async fn save_custom_resource(&self, resource: CustomResource) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO custom_resources (
+                id, api_version, kind, name, namespace,
+                spec, status, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (kind, namespace, name) DO UPDATE SET
+                api_version = EXCLUDED.api_version,
+                spec = EXCLUDED.spec,
+                status = EXCLUDED.status,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(resource.id)
+        .bind(&resource.api_version)
+        .bind(&resource.kind)
+        .bind(&resource.name)
+        .bind(&resource.namespace)
+        .bind(serde_json::to_string(&resource.spec)?)
+        .bind(resource.status.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(resource.created_at.naive_utc())
+        .bind(resource.updated_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn get_custom_resource(&self, _kind: &str, _namespace: &str, _name: &str) -> Result<Option<CustomResource>> {
-        todo!("Implement get_custom_resource for PostgreSQL")
+    async fn get_custom_resource(&self, kind: &str, namespace: &str, name: &str) -> Result<Option<CustomResource>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, api_version, kind, name, namespace,
+                   spec, status, created_at, updated_at
+            FROM custom_resources
+            WHERE kind = $1 AND namespace = $2 AND name = $3
+            "#,
+        )
+        .bind(kind)
+        .bind(namespace)
+        .bind(name)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        row.as_ref().map(custom_resource_from_row).transpose()
     }
     
-    async fn update_custom_resource_status(&self, _id: Uuid, _status: JsonValue) -> Result<()> {
-        todo!("Implement update_custom_resource_status for PostgreSQL")
+    async fn update_custom_resource_status(&self, id: Uuid, status: JsonValue) -> Result<()> {
+        sqlx::query(
+            "UPDATE custom_resources SET status = $1, updated_at = $2 WHERE id = $3",
+        )
+        .bind(serde_json::to_string(&status)?)
+        .bind(Utc::now().naive_utc())
+        .bind(id)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn delete_custom_resource(&self, _kind: &str, _namespace: &str, _name: &str) -> Result<()> {
-        todo!("Implement delete_custom_resource for PostgreSQL")
+    async fn delete_custom_resource(&self, kind: &str, namespace: &str, name: &str) -> Result<()> {
+        sqlx::query(
+            "DELETE FROM custom_resources WHERE kind = $1 AND namespace = $2 AND name = $3",
+        )
+        .bind(kind)
+        .bind(namespace)
+        .bind(name)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn list_custom_resources(
+        &self,
+        kind: &str,
+        namespace: Option<&str>,
+        all_namespaces: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CustomResource>> {
+        let namespace = match (all_namespaces, namespace) {
+            (true, _) => None,
+            (false, Some(ns)) => Some(ns),
+            (false, None) => {
+                return Err(Error::Validation(
+                    "A namespace is required to list custom resources unless all_namespaces is set".to_string(),
+                ));
+            }
+        };
+        
+        // A NULL namespace parameter matches every namespace
+        let rows = sqlx::query(
+            r#"
+            SELECT id, api_version, kind, name, namespace,
+                   spec, status, created_at, updated_at
+            FROM custom_resources
+            WHERE kind = $1 AND ($2::TEXT IS NULL OR namespace = $2)
+            ORDER BY created_at DESC, name
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(kind)
+        .bind(namespace)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(custom_resource_from_row).collect()
+    }
+    
+    async fn count_custom_resources(&self, kind: &str, namespace: Option<&str>) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM custom_resources WHERE kind = $1 AND ($2::TEXT IS NULL OR namespace = $2)",
+        )
+        .bind(kind)
+        .bind(namespace)
+        .fetch_one(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(count)
+    }
+    
+    async fn save_chat_session(&self, session: ChatSession) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO chat_sessions (
+                session_id, user_id, messages, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (session_id) DO UPDATE SET
+                messages = EXCLUDED.messages,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(&session.session_id)
+        .bind(&session.user_id)
+        .bind(serde_json::to_string(&session.messages)?)
+        .bind(session.created_at.naive_utc())
+        .bind(session.updated_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn get_chat_session(&self, session_id: &str) -> Result<Option<ChatSession>> {
+        let row = sqlx::query(
+            r#"
+            SELECT session_id, user_id, messages, created_at, updated_at
+            FROM chat_sessions
+            WHERE session_id = $1
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        match row {
+            Some(r) => Ok(Some(ChatSession {
+                session_id: r.get("session_id"),
+                user_id: r.get("user_id"),
+                messages: serde_json::from_str(r.get("messages"))?,
+                created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+                updated_at: r.get::<NaiveDateTime, _>("updated_at").and_utc(),
+            })),
+            None => Ok(None),
+        }
+    }
+    
+    async fn list_chat_sessions_by_user(&self, user_id: &str) -> Result<Vec<ChatSession>> {
+        let rows = sqlx::query(
+            "SELECT session_id FROM chat_sessions WHERE user_id = $1 ORDER BY updated_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        let mut sessions = Vec::new();
+        for row in rows {
+            if let Some(session) = self.get_chat_session(row.get("session_id")).await? {
+                sessions.push(session);
+            }
+        }
+        
+        Ok(sessions)
+    }
+    
+    async fn delete_chat_sessions_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM chat_sessions WHERE updated_at < $1")
+            .bind(cutoff.naive_utc())
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(result.rows_affected())
+    }
+    
+    async fn save_audit_entry(&self, entry: AuditEntry) -> Result<()> {
+        let before_json = entry.before.as_ref().map(serde_json::to_string).transpose()?;
+        let after_json = entry.after.as_ref().map(serde_json::to_string).transpose()?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (
+                id, operation, entity_type, entity_id, actor, "before", "after", created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(entry.id)
+        .bind(&entry.operation)
+        .bind(&entry.entity_type)
+        .bind(&entry.entity_id)
+        .bind(&entry.actor)
+        .bind(before_json)
+        .bind(after_json)
+        .bind(entry.created_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
     }
     
-    async fn list_custom_resources(&self, _kind: &str, _namespace: Option<&str>) -> Result<Vec<CustomResource>> {
-        todo!("Implement list_custom_resources for PostgreSQL")
+    async fn list_audit_entries(&self, entity_type: Option<&str>, entity_id: Option<&str>, limit: i64) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, operation, entity_type, entity_id, actor, "before", "after", created_at
+            FROM audit_log
+            WHERE ($1::TEXT IS NULL OR entity_type = $1) AND ($2::TEXT IS NULL OR entity_id = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(|r| {
+            let json = |column: &str| r.get::<Option<String>, _>(column)
+                .map(|s| serde_json::from_str(&s))
+                .transpose();
+            Ok(AuditEntry {
+                id: r.get("id"),
+                operation: r.get("operation"),
+                entity_type: r.get("entity_type"),
+                entity_id: r.get("entity_id"),
+                actor: r.get("actor"),
+                before: json("before")?,
+                after: json("after")?,
+                created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+            })
+        }).collect()
+    }
+    
+    async fn save_tool_invocation(&self, invocation: ToolInvocation) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO tool_invocations (
+                id, workflow_id, step_name, tool_name, arguments, success, output, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(invocation.id)
+        .bind(invocation.workflow_id)
+        .bind(&invocation.step_name)
+        .bind(&invocation.tool_name)
+        .bind(serde_json::to_string(&invocation.arguments)?)
+        .bind(invocation.success)
+        .bind(&invocation.output)
+        .bind(invocation.created_at.naive_utc())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn list_tool_invocations_by_workflow(&self, workflow_id: Uuid) -> Result<Vec<ToolInvocation>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, step_name, tool_name, arguments, success, output, created_at
+            FROM tool_invocations
+            WHERE workflow_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(workflow_id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(|r| Ok(ToolInvocation {
+            id: r.get("id"),
+            workflow_id: r.get("workflow_id"),
+            step_name: r.get("step_name"),
+            tool_name: r.get("tool_name"),
+            arguments: serde_json::from_str(&r.get::<String, _>("arguments"))?,
+            success: r.get("success"),
+            output: r.get("output"),
+            created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+        })).collect()
     }
-} 
\ No newline at end of file
+}
+
+/// Bind every column of an alert, in `alerts` table order, to `query`
+fn bind_alert<'q>(
+    query: Query<'q, Postgres, PgArguments>,
+    alert: &Alert,
+) -> Result<Query<'q, Postgres, PgArguments>> {
+    Ok(query
+        .bind(alert.id)
+        .bind(alert.external_id.clone())
+        .bind(alert.fingerprint.clone())
+        .bind(alert.status.to_string())
+        .bind(alert.severity.to_string())
+        .bind(alert.alert_name.clone())
+        .bind(alert.alert_name.clone())
+        .bind(alert.summary.clone())
+        .bind(alert.description.clone())
+        .bind(serde_json::to_string(&alert.labels)?)
+        .bind(serde_json::to_string(&alert.annotations)?)
+        .bind(alert.source_id)
+        .bind(alert.workflow_id)
+        .bind(alert.ai_analysis.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(alert.ai_confidence)
+        .bind(alert.auto_resolved)
+        .bind(alert.starts_at.naive_utc())
+        .bind(alert.ends_at.map(|t| t.naive_utc()))
+        .bind(alert.received_at.naive_utc())
+        .bind(alert.triage_started_at.map(|t| t.naive_utc()))
+        .bind(alert.triage_completed_at.map(|t| t.naive_utc()))
+        .bind(alert.resolved_at.map(|t| t.naive_utc()))
+        .bind(alert.created_at.naive_utc())
+        .bind(alert.updated_at.naive_utc()))
+}
+
+fn alert_from_row(r: &PgRow) -> Result<Alert> {
+    let utc = |column: &str| r.get::<Option<NaiveDateTime>, _>(column).map(|t| t.and_utc());
+    
+    Ok(Alert {
+        id: r.get("id"),
+        external_id: r.get("external_id"),
+        fingerprint: r.get("fingerprint"),
+        status: r.get::<String, _>("status").parse()?,
+        severity: r.get::<String, _>("severity").parse()?,
+        alert_name: r.get("alert_name"),
+        summary: r.get("summary"),
+        description: r.get("description"),
+        labels: serde_json::from_str(r.get("labels"))?,
+        annotations: serde_json::from_str(r.get("annotations"))?,
+        source_id: r.get("source_id"),
+        workflow_id: r.get("workflow_id"),
+        ai_analysis: r.get::<Option<String>, _>("ai_analysis")
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?,
+        ai_confidence: r.get("ai_confidence"),
+        auto_resolved: r.get::<Option<bool>, _>("auto_resolved").unwrap_or(false),
+        starts_at: r.get::<NaiveDateTime, _>("starts_at").and_utc(),
+        ends_at: utc("ends_at"),
+        received_at: r.get::<NaiveDateTime, _>("received_at").and_utc(),
+        triage_started_at: utc("triage_started_at"),
+        triage_completed_at: utc("triage_completed_at"),
+        resolved_at: utc("resolved_at"),
+        created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+        updated_at: r.get::<NaiveDateTime, _>("updated_at").and_utc(),
+    })
+}
+
+fn workflow_from_row(r: &PgRow) -> Result<Workflow> {
+    let json = |column: &str| r.get::<Option<String>, _>(column)
+        .map(|s| serde_json::from_str(&s))
+        .transpose();
+    
+    Ok(Workflow {
+        id: r.get("id"),
+        name: r.get("name"),
+        namespace: r.get("namespace"),
+        trigger_source: r.get("trigger_source"),
+        status: r.get::<String, _>("status").parse()?,
+        steps_completed: r.get::<Option<i32>, _>("steps_completed").unwrap_or(0),
+        total_steps: r.get("total_steps"),
+        current_step: r.get("current_step"),
+        input_context: json("input_context")?,
+        outputs: json("outputs")?,
+        error: r.get("error"),
+        started_at: r.get::<NaiveDateTime, _>("started_at").and_utc(),
+        completed_at: r.get::<Option<NaiveDateTime>, _>("completed_at").map(|t| t.and_utc()),
+        created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+    })
+}
+
+fn source_event_from_row(r: &PgRow) -> Result<SourceEvent> {
+    Ok(SourceEvent {
+        id: r.get("id"),
+        source_name: r.get("source_name"),
+        source_type: r.get::<String, _>("source_type").parse()?,
+        event_data: serde_json::from_str(r.get("event_data"))?,
+        workflow_triggered: r.get("workflow_triggered"),
+        received_at: r.get::<NaiveDateTime, _>("received_at").and_utc(),
+    })
+}
+
+fn workflow_step_from_row(r: &PgRow) -> Result<WorkflowStep> {
+    let json = |column: &str| r.get::<Option<String>, _>(column)
+        .map(|s| serde_json::from_str(&s))
+        .transpose();
+    
+    Ok(WorkflowStep {
+        id: r.get("id"),
+        workflow_id: r.get("workflow_id"),
+        name: r.get("name"),
+        step_type: r.get::<String, _>("step_type").parse()?,
+        status: r.get::<String, _>("status").parse()?,
+        config: json("config")?,
+        started_at: r.get::<Option<NaiveDateTime>, _>("started_at").map(|t| t.and_utc()),
+        completed_at: r.get::<Option<NaiveDateTime>, _>("completed_at").map(|t| t.and_utc()),
+        result: json("result")?,
+        error: r.get("error"),
+        created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+    })
+}
+
+fn sink_output_from_row(r: &PgRow) -> Result<SinkOutput> {
+    Ok(SinkOutput {
+        id: r.get("id"),
+        workflow_id: r.get("workflow_id"),
+        sink_name: r.get("sink_name"),
+        sink_type: r.get::<String, _>("sink_type").parse()?,
+        payload: r.get::<Option<String>, _>("payload")
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?,
+        status: r.get::<String, _>("status").parse()?,
+        error: r.get("error"),
+        sent_at: r.get::<Option<NaiveDateTime>, _>("sent_at").map(|t| t.and_utc()),
+        created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+    })
+}
+
+fn custom_resource_from_row(r: &PgRow) -> Result<CustomResource> {
+    Ok(CustomResource {
+        id: r.get("id"),
+        api_version: r.get("api_version"),
+        kind: r.get("kind"),
+        name: r.get("name"),
+        namespace: r.get("namespace"),
+        spec: serde_json::from_str(r.get("spec"))?,
+        status: r.get::<Option<String>, _>("status")
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?,
+        created_at: r.get::<NaiveDateTime, _>("created_at").and_utc(),
+        updated_at: r.get::<NaiveDateTime, _>("updated_at").and_utc(),
+    })
+}
+
+/// Locate an executable in `PATH`, like `which`
+fn find_executable(name: &str) -> Option<std::path::PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
@@ -1,22 +1,62 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqlitePool, Pool, Sqlite, Row};
-use tracing::{debug, error, info};
+use futures::future::BoxFuture;
+use sqlx::{pool::PoolConnection, query::Query, sqlite::{SqliteArguments, SqliteConnection, SqlitePool}, Pool, Sqlite, Row};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
 use serde_json::Value as JsonValue;
 
 use crate::{
     store::{
-        Alert, AlertStatus, AlertSeverity, CustomResource, DeduplicationResult,
-        SinkOutput, SinkStatus, SinkType, SourceEvent, SourceType, StepStatus, StepType,
-        Store, Workflow, WorkflowStatus, WorkflowStep,
+        Alert, AlertNote, AlertStatus, AlertSeverity, AuditEntry, BulkCancelResult, ChatSession, CustomResource, DeduplicationResult,
+        SinkOutput, SinkStatus, SinkType, SinkTypeStats, SourceEvent, SourceEventWithWorkflow, SourceType, StepStatus, StepType,
+        Store, StoreHealth, TableStats, ToolInvocation, Workflow, WorkflowBucket, WorkflowStatus, WorkflowStep, WorkflowWithAllData,
+        merge_refire, migration_lock_timeout, HEALTH_CHECK_TABLES, MIGRATION_LOCK_NAME, MIGRATION_LOCK_RETRY_INTERVAL,
+        MIGRATION_LOCK_STALE_AFTER, MIGRATION_LOCK_TIMEOUT,
     },
     Error, Result,
 };
 
+tokio::task_local! {
+    /// Connection of the `Store::transaction` the current task runs in
+    static TRANSACTION: Arc<Mutex<PoolConnection<Sqlite>>>;
+}
+
+/// Connection a query runs on
+enum Conn {
+    Pooled(PoolConnection<Sqlite>),
+    /// The current task's transaction, held until dropped
+    Transaction(OwnedMutexGuard<PoolConnection<Sqlite>>),
+}
+
+impl Deref for Conn {
+    type Target = SqliteConnection;
+    
+    fn deref(&self) -> &SqliteConnection {
+        match self {
+            Conn::Pooled(conn) => conn,
+            Conn::Transaction(conn) => conn,
+        }
+    }
+}
+
+impl DerefMut for Conn {
+    fn deref_mut(&mut self) -> &mut SqliteConnection {
+        match self {
+            Conn::Pooled(conn) => conn,
+            Conn::Transaction(conn) => conn,
+        }
+    }
+}
+
 pub struct SqliteStore {
     pool: Pool<Sqlite>,
+    dedup_window: Option<chrono::Duration>,
 }
 
 impl SqliteStore {
@@ -30,7 +70,94 @@ impl SqliteStore {
                 Error::Sqlx(e)
             })?;
         
-        Ok(Self { pool })
+        Ok(Self { pool, dedup_window: None })
+    }
+    
+    /// Stop deduplicating against alerts not updated within `window`;
+    /// `None` deduplicates until they're resolved
+    pub fn with_dedup_window(mut self, window: Option<std::time::Duration>) -> Self {
+        self.dedup_window = window.and_then(|w| chrono::Duration::from_std(w).ok());
+        self
+    }
+    
+    /// The current task's transaction, or else a connection from the pool
+    async fn conn(&self) -> Result<Conn> {
+        match TRANSACTION.try_with(Arc::clone) {
+            Ok(conn) => Ok(Conn::Transaction(conn.lock_owned().await)),
+            Err(_) => Ok(Conn::Pooled(self.pool.acquire().await?)),
+        }
+    }
+    
+    /// Claim the migration lock row inside an exclusive transaction, retrying
+    /// until `MIGRATION_LOCK_TIMEOUT` elapses. Locks older than
+    /// `MIGRATION_LOCK_STALE_AFTER` are cleared first.
+    async fn acquire_migration_lock(&self) -> Result<()> {
+        let deadline = Instant::now() + MIGRATION_LOCK_TIMEOUT;
+        loop {
+            match self.try_acquire_migration_lock().await {
+                Ok(true) => return Ok(()),
+                Ok(false) => debug!("Migration lock is held by another process"),
+                Err(e) => debug!("Failed to acquire migration lock: {}", e),
+            }
+            if Instant::now() >= deadline {
+                error!("Timed out waiting for migration lock");
+                return Err(migration_lock_timeout());
+            }
+            info!("Waiting for another process to finish migrations");
+            tokio::time::sleep(MIGRATION_LOCK_RETRY_INTERVAL).await;
+        }
+    }
+    
+    async fn try_acquire_migration_lock(&self) -> Result<bool> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN EXCLUSIVE TRANSACTION").execute(&mut *conn).await?;
+        
+        let claimed = async {
+            sqlx::query(
+                r#"
+                CREATE TABLE IF NOT EXISTS _migration_lock (
+                    name TEXT PRIMARY KEY,
+                    locked_at TIMESTAMP NOT NULL
+                )
+                "#,
+            )
+            .execute(&mut *conn)
+            .await?;
+            
+            let stale = sqlx::query("DELETE FROM _migration_lock WHERE name = ?1 AND locked_at < ?2")
+                .bind(MIGRATION_LOCK_NAME)
+                .bind(Utc::now() - chrono::Duration::from_std(MIGRATION_LOCK_STALE_AFTER).unwrap_or_default())
+                .execute(&mut *conn)
+                .await?;
+            if stale.rows_affected() > 0 {
+                warn!("Took over migration lock left behind by a process that died mid-migration");
+            }
+            
+            sqlx::query("INSERT OR IGNORE INTO _migration_lock (name, locked_at) VALUES (?1, ?2)")
+                .bind(MIGRATION_LOCK_NAME)
+                .bind(Utc::now())
+                .execute(&mut *conn)
+                .await
+        }.await;
+        
+        match claimed {
+            Ok(result) => {
+                sqlx::query("COMMIT").execute(&mut *conn).await?;
+                Ok(result.rows_affected() == 1)
+            }
+            Err(e) => {
+                sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+                Err(Error::Sqlx(e))
+            }
+        }
+    }
+    
+    async fn release_migration_lock(&self) -> Result<()> {
+        sqlx::query("DELETE FROM _migration_lock WHERE name = ?1")
+            .bind(MIGRATION_LOCK_NAME)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 }
 
@@ -39,28 +166,105 @@ impl Store for SqliteStore {
     async fn init(&self) -> Result<()> {
         info!("Running database migrations");
         
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to run migrations: {}", e);
-                Error::Migrate(e)
-            })?;
+        self.acquire_migration_lock().await?;
+        let result = sqlx::migrate!("./migrations").run(&self.pool).await;
+        let released = self.release_migration_lock().await;
         
+        // A failed migration matters more than a lock left behind
+        result.map_err(|e| {
+            error!("Failed to run migrations: {}", e);
+            Error::Migrate(e)
+        })?;
+        released
+    }
+    
+    async fn migrate_alert_labels_to_jsonb(&self) -> Result<()> {
+        debug!("SQLite queries labels with json_extract; nothing to migrate");
         Ok(())
     }
     
     // Alert operations
+    async fn health_details(&self) -> Result<StoreHealth> {
+        debug!("Collecting database health details");
+        
+        if let Err(e) = sqlx::query("SELECT 1").execute(&self.pool).await {
+            error!("Database health check failed: {}", e);
+            return Ok(StoreHealth { connected: false, size_bytes: None, tables: HashMap::new() });
+        }
+        
+        let size_bytes: i64 = sqlx::query_scalar(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        let mut tables = HashMap::new();
+        for table in HEALTH_CHECK_TABLES {
+            let row_count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(&self.pool)
+                .await?;
+            let table_size: Option<i64> = sqlx::query_scalar(
+                "SELECT SUM(pgsize) FROM dbstat WHERE name IN (SELECT name FROM sqlite_master WHERE tbl_name = ?1)",
+            )
+            .bind(table)
+            .fetch_one(&self.pool)
+            .await
+            .ok()
+            .flatten();
+            tables.insert(table.to_string(), TableStats { row_count, size_bytes: table_size });
+        }
+        
+        Ok(StoreHealth { connected: true, size_bytes: Some(size_bytes), tables })
+    }
+    
+    async fn backup_to_path(&self, path: &std::path::Path) -> Result<u64> {
+        debug!("Backing up database to {}", path.display());
+        
+        // VACUUM INTO from an in-memory database writes another in-memory database
+        let file: String = sqlx::query_scalar("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_one(&self.pool)
+            .await?;
+        if file.is_empty() {
+            return Err(Error::Validation("In-memory databases cannot be backed up".to_string()));
+        }
+        
+        sqlx::query("VACUUM INTO ?1")
+            .bind(path.to_string_lossy().into_owned())
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(tokio::fs::metadata(path).await?.len())
+    }
+    
+    async fn transaction<'a>(&'a self, work: BoxFuture<'a, Result<()>>) -> Result<()> {
+        if TRANSACTION.try_with(|_| ()).is_ok() {
+            return work.await;
+        }
+        
+        // Taking the write lock up front keeps a read followed by a write
+        // from failing on a write another connection made in between
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE TRANSACTION").execute(&mut *conn).await?;
+        
+        let conn = Arc::new(Mutex::new(conn));
+        let result = TRANSACTION.scope(conn.clone(), work).await;
+        let mut conn = conn.lock().await;
+        match result {
+            Ok(()) => {
+                sqlx::query("COMMIT").execute(&mut **conn).await?;
+                Ok(())
+            }
+            Err(e) => {
+                sqlx::query("ROLLBACK").execute(&mut **conn).await?;
+                Err(e)
+            }
+        }
+    }
+    
     async fn save_alert(&self, alert: Alert) -> Result<()> {
         debug!("Saving alert: {}", alert.id);
         
-        let labels_json = serde_json::to_string(&alert.labels)?;
-        let annotations_json = serde_json::to_string(&alert.annotations)?;
-        let ai_analysis_json = alert.ai_analysis.as_ref()
-            .map(|a| serde_json::to_string(a))
-            .transpose()?;
-        
-        sqlx::query(
+        bind_alert(sqlx::query(
             r#"
             INSERT INTO alerts (
                 id, external_id, fingerprint, status, severity, alert_name, name,
@@ -80,32 +284,8 @@ impl Store for SqliteStore {
                 resolved_at = excluded.resolved_at,
                 updated_at = excluded.updated_at
             "#,
-        )
-        .bind(alert.id.to_string())
-        .bind(&alert.external_id)
-        .bind(&alert.fingerprint)
-        .bind(alert.status.to_string())
-        .bind(alert.severity.to_string())
-        .bind(&alert.alert_name)
-        .bind(&alert.alert_name)
-        .bind(&alert.summary)
-        .bind(&alert.description)
-        .bind(labels_json)
-        .bind(annotations_json)
-        .bind(alert.source_id.map(|id| id.to_string()))
-        .bind(alert.workflow_id.map(|id| id.to_string()))
-        .bind(ai_analysis_json)
-        .bind(alert.ai_confidence)
-        .bind(alert.auto_resolved)
-        .bind(alert.starts_at)
-        .bind(alert.ends_at)
-        .bind(alert.received_at)
-        .bind(alert.triage_started_at)
-        .bind(alert.triage_completed_at)
-        .bind(alert.resolved_at)
-        .bind(alert.created_at)
-        .bind(alert.updated_at)
-        .execute(&self.pool)
+        ), &alert)?
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -126,7 +306,7 @@ impl Store for SqliteStore {
             "#,
         )
         .bind(id.to_string())
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match row {
@@ -174,7 +354,7 @@ impl Store for SqliteStore {
             "SELECT id FROM alerts WHERE fingerprint = ?1 ORDER BY created_at DESC LIMIT 1",
         )
         .bind(fingerprint)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match id_row {
@@ -192,7 +372,22 @@ impl Store for SqliteStore {
         .bind(status.to_string())
         .bind(Utc::now())
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn update_alert_severity(&self, id: Uuid, severity: AlertSeverity) -> Result<()> {
+        debug!("Updating alert severity: {} -> {:?}", id, severity);
+        
+        sqlx::query(
+            "UPDATE alerts SET severity = ?1, updated_at = ?2 WHERE id = ?3",
+        )
+        .bind(severity.to_string())
+        .bind(Utc::now())
+        .bind(id.to_string())
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -210,9 +405,56 @@ impl Store for SqliteStore {
         .bind(confidence as f64)
         .bind(Utc::now())
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn append_alert_annotation(&self, id: Uuid, note: AlertNote) -> Result<()> {
+        debug!("Appending note by {} to alert: {}", note.author, id);
+        
+        // `|| ''` keeps the array a string value, as annotations are strings
+        let result = sqlx::query(
+            r#"
+            UPDATE alerts
+            SET annotations = json_set(
+                    annotations,
+                    '$._notes',
+                    json_insert(COALESCE(json_extract(annotations, '$._notes'), '[]'), '$[#]', json(?1)) || ''
+                ),
+                updated_at = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(serde_json::to_string(&note)?)
+        .bind(Utc::now())
+        .bind(id.to_string())
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(format!("Alert {} not found", id)));
+        }
+        Ok(())
+    }
+    
+    async fn set_alert_annotation(&self, id: Uuid, key: &str, value: &str) -> Result<()> {
+        debug!("Setting annotation {} on alert: {}", key, id);
+        
+        let result = sqlx::query(
+            "UPDATE alerts SET annotations = json_set(annotations, ?1, ?2), updated_at = ?3 WHERE id = ?4",
+        )
+        .bind(format!("$.\"{}\"", key.replace('"', "\\\"")))
+        .bind(value)
+        .bind(Utc::now())
+        .bind(id.to_string())
+        .execute(&mut *self.conn().await?)
         .await?;
         
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(format!("Alert {} not found", id)));
+        }
         Ok(())
     }
     
@@ -222,7 +464,7 @@ impl Store for SqliteStore {
         let query = match field {
             "triage_started_at" => {
                 sqlx::query(
-                    "UPDATE alerts SET triage_started_at = ?1, updated_at = ?2 WHERE id = ?3",
+                    "UPDATE alerts SET triage_started_at = ?1, updated_at = ?2 WHERE id = ?3 RETURNING received_at",
                 )
                 .bind(timestamp)
                 .bind(Utc::now())
@@ -230,7 +472,7 @@ impl Store for SqliteStore {
             }
             "triage_completed_at" => {
                 sqlx::query(
-                    "UPDATE alerts SET triage_completed_at = ?1, updated_at = ?2 WHERE id = ?3",
+                    "UPDATE alerts SET triage_completed_at = ?1, updated_at = ?2 WHERE id = ?3 RETURNING received_at",
                 )
                 .bind(timestamp)
                 .bind(Utc::now())
@@ -238,7 +480,7 @@ impl Store for SqliteStore {
             }
             "resolved_at" => {
                 sqlx::query(
-                    "UPDATE alerts SET resolved_at = ?1, updated_at = ?2 WHERE id = ?3",
+                    "UPDATE alerts SET resolved_at = ?1, updated_at = ?2 WHERE id = ?3 RETURNING received_at",
                 )
                 .bind(timestamp)
                 .bind(Utc::now())
@@ -247,20 +489,32 @@ impl Store for SqliteStore {
             _ => return Err(Error::Config(format!("Invalid timing field: {}", field))),
         };
         
-        query.execute(&self.pool).await?;
+        let row = query.fetch_optional(&mut *self.conn().await?).await?;
+        if let Some(row) = row {
+            crate::metrics::observe_alert_timing(field, row.get("received_at"), timestamp);
+        }
         Ok(())
     }
     
-    async fn list_alerts(&self, limit: i64, offset: i64) -> Result<Vec<Alert>> {
-        debug!("Listing alerts: limit={}, offset={}", limit, offset);
+    async fn list_alerts(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<Vec<Alert>> {
+        debug!("Listing alerts: limit={}, offset={}, since={:?}, until={:?}", limit, offset, since, until);
         
+        // A NULL bound leaves that end of the range open
         let mut alerts = Vec::new();
         let rows = sqlx::query(
-            "SELECT id FROM alerts ORDER BY created_at DESC LIMIT ?1 OFFSET ?2",
+            r#"
+            SELECT id FROM alerts
+            WHERE (?3 IS NULL OR created_at >= ?3)
+              AND (?4 IS NULL OR created_at < ?4)
+            ORDER BY created_at DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
         )
         .bind(limit)
         .bind(offset)
-        .fetch_all(&self.pool)
+        .bind(since)
+        .bind(until)
+        .fetch_all(&mut *self.conn().await?)
         .await?;
         
         for row in rows {
@@ -281,7 +535,144 @@ impl Store for SqliteStore {
         )
         .bind(status.to_string())
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(alert) = self.get_alert(row.get::<String, _>("id").parse()?).await? {
+                alerts.push(alert);
+            }
+        }
+        
+        Ok(alerts)
+    }
+    
+    async fn count_alerts(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<i64> {
+        debug!("Counting alerts: since={:?}, until={:?}", since, until);
+        
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM alerts WHERE (?1 IS NULL OR created_at >= ?1) AND (?2 IS NULL OR created_at < ?2)",
+        )
+        .bind(since)
+        .bind(until)
+        .fetch_one(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(count)
+    }
+    
+    async fn count_alerts_by_status(&self, status: AlertStatus) -> Result<i64> {
+        debug!("Counting alerts by status: {:?}", status);
+        
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alerts WHERE status = ?1")
+            .bind(status.to_string())
+            .fetch_one(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(count)
+    }
+    
+    async fn get_recent_alerts_for_deployment(&self, namespace: &str, deployment: &str, limit: i64) -> Result<Vec<Alert>> {
+        debug!("Listing recent alerts for deployment: {}/{}, limit={}", namespace, deployment, limit);
+        
+        let mut alerts = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM alerts
+            WHERE json_extract(labels, '$.namespace') = ?1
+              AND COALESCE(json_extract(labels, '$.deployment'), json_extract(labels, '$.app')) = ?2
+            ORDER BY created_at DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(namespace)
+        .bind(deployment)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(alert) = self.get_alert(row.get::<String, _>("id").parse()?).await? {
+                alerts.push(alert);
+            }
+        }
+        
+        Ok(alerts)
+    }
+    
+    async fn list_alerts_by_label(&self, labels: &HashMap<String, String>, limit: i64) -> Result<Vec<Alert>> {
+        debug!("Listing alerts with labels {:?}, limit={}", labels, limit);
+        
+        let mut alerts = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM alerts
+            WHERE NOT EXISTS (
+                SELECT 1 FROM json_each(?1) wanted
+                WHERE json_extract(alerts.labels, '$."' || wanted.key || '"') IS NOT wanted.value
+            )
+            ORDER BY created_at DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(serde_json::to_string(labels)?)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(alert) = self.get_alert(row.get::<String, _>("id").parse()?).await? {
+                alerts.push(alert);
+            }
+        }
+        
+        Ok(alerts)
+    }
+    
+    async fn list_alerts_near_expiry(&self, within_minutes: i64) -> Result<Vec<Alert>> {
+        debug!("Listing alerts expiring within {} minutes", within_minutes);
+        
+        let now = Utc::now();
+        let mut alerts = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM alerts
+            WHERE status != 'resolved'
+              AND ends_at >= ?1
+              AND ends_at <= ?2
+            ORDER BY ends_at ASC
+            "#,
+        )
+        .bind(now)
+        .bind(now + chrono::Duration::minutes(within_minutes))
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(alert) = self.get_alert(row.get::<String, _>("id").parse()?).await? {
+                alerts.push(alert);
+            }
+        }
+        
+        Ok(alerts)
+    }
+    
+    async fn list_alerts_without_workflow(&self, older_than_minutes: i64) -> Result<Vec<Alert>> {
+        debug!("Listing received alerts without a workflow older than {} minutes", older_than_minutes);
+        
+        let mut alerts = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM alerts
+            WHERE workflow_id IS NULL
+              AND triage_started_at IS NULL
+              AND status = 'received'
+              AND received_at < ?1
+            ORDER BY received_at ASC
+            "#,
+        )
+        .bind(Utc::now() - chrono::Duration::minutes(older_than_minutes))
+        .fetch_all(&mut *self.conn().await?)
         .await?;
         
         for row in rows {
@@ -296,30 +687,109 @@ impl Store for SqliteStore {
     async fn deduplicate_alert(&self, fingerprint: &str, mut alert: Alert) -> Result<DeduplicationResult> {
         debug!("Deduplicating alert with fingerprint: {}", fingerprint);
         
-        // Check if an alert with this fingerprint already exists
-        if let Some(existing) = self.get_alert_by_fingerprint(fingerprint).await? {
-            // If the existing alert is resolved, create a new one
-            if existing.status == AlertStatus::Resolved {
-                alert.fingerprint = fingerprint.to_string();
-                self.save_alert(alert.clone()).await?;
-                Ok(DeduplicationResult::New(alert))
+        alert.fingerprint = fingerprint.to_string();
+        self.get_or_create_alert_by_fingerprint(alert).await
+    }
+    
+    async fn get_or_create_alert_by_fingerprint(&self, alert: Alert) -> Result<DeduplicationResult> {
+        debug!("Getting or creating alert with fingerprint: {}", alert.fingerprint);
+        
+        // The exclusive transaction keeps concurrent deliveries of the same
+        // alert from both inserting a row; a `Store::transaction` already
+        // holds the write lock
+        let mut conn = self.conn().await?;
+        let own_transaction = matches!(conn, Conn::Pooled(_));
+        if own_transaction {
+            sqlx::query("BEGIN EXCLUSIVE TRANSACTION").execute(&mut *conn).await?;
+        }
+        
+        let upserted = async {
+            let now = Utc::now();
+            let existing = sqlx::query(
+                "SELECT id, labels, annotations, updated_at FROM alerts WHERE fingerprint = ?1 AND status != 'resolved'",
+            )
+            .bind(&alert.fingerprint)
+            .fetch_optional(&mut *conn)
+            .await?;
+            
+            // A stale alert is resolved to make way for the new one
+            let existing = match (existing, self.dedup_window) {
+                (Some(row), Some(window)) if row.get::<DateTime<Utc>, _>("updated_at") < now - window => {
+                    sqlx::query("UPDATE alerts SET status = 'resolved', resolved_at = ?1, updated_at = ?1 WHERE id = ?2")
+                        .bind(now)
+                        .bind(row.get::<String, _>("id"))
+                        .execute(&mut *conn)
+                        .await?;
+                    None
+                }
+                (existing, _) => existing,
+            };
+            
+            let mut changed = false;
+            if let Some(row) = existing {
+                let labels: HashMap<String, String> = serde_json::from_str(row.get("labels"))?;
+                let annotations: HashMap<String, String> = serde_json::from_str(row.get("annotations"))?;
+                let merged_labels = merge_refire(&labels, &alert.labels);
+                let merged_annotations = merge_refire(&annotations, &alert.annotations);
+                changed = merged_labels != labels || merged_annotations != annotations;
+                sqlx::query("UPDATE alerts SET labels = ?1, annotations = ?2, updated_at = ?3 WHERE id = ?4")
+                    .bind(serde_json::to_string(&merged_labels)?)
+                    .bind(serde_json::to_string(&merged_annotations)?)
+                    .bind(now)
+                    .bind(row.get::<String, _>("id"))
+                    .execute(&mut *conn)
+                    .await?;
             } else {
-                // Update the existing alert's timestamp
-                sqlx::query(
-                    "UPDATE alerts SET updated_at = ?1 WHERE id = ?2",
-                )
-                .bind(Utc::now())
-                .bind(existing.id.to_string())
-                .execute(&self.pool)
+                bind_alert(sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO alerts (
+                        id, external_id, fingerprint, status, severity, alert_name, name,
+                        summary, description, labels, annotations, source_id, workflow_id,
+                        ai_analysis, ai_confidence, auto_resolved,
+                        starts_at, ends_at, received_at, triage_started_at,
+                        triage_completed_at, resolved_at, created_at, updated_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)
+                    "#,
+                ), &alert)?
+                .execute(&mut *conn)
                 .await?;
-                
-                Ok(DeduplicationResult::Duplicate(existing))
             }
-        } else {
-            // New alert
-            alert.fingerprint = fingerprint.to_string();
-            self.save_alert(alert.clone()).await?;
+            
+            let id: String = sqlx::query_scalar(
+                "SELECT id FROM alerts WHERE fingerprint = ?1 AND status != 'resolved'",
+            )
+            .bind(&alert.fingerprint)
+            .fetch_one(&mut *conn)
+            .await?;
+            Ok::<_, Error>((id.parse::<Uuid>()?, changed))
+        }.await;
+        
+        let (id, changed) = match upserted {
+            Ok(upserted) => {
+                if own_transaction {
+                    sqlx::query("COMMIT").execute(&mut *conn).await?;
+                }
+                upserted
+            }
+            Err(e) => {
+                if own_transaction {
+                    sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+                }
+                return Err(e);
+            }
+        };
+        drop(conn);
+        
+        if id == alert.id {
             Ok(DeduplicationResult::New(alert))
+        } else {
+            let existing = self.get_alert(id).await?
+                .ok_or_else(|| Error::NotFound(format!("Alert {} not found", id)))?;
+            if changed {
+                Ok(DeduplicationResult::Updated(existing))
+            } else {
+                Ok(DeduplicationResult::Duplicate(existing))
+            }
         }
     }
     
@@ -365,7 +835,7 @@ impl Store for SqliteStore {
         .bind(workflow.started_at)
         .bind(workflow.completed_at)
         .bind(workflow.created_at)
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -385,7 +855,7 @@ impl Store for SqliteStore {
             "#,
         )
         .bind(id.to_string())
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match row {
@@ -426,7 +896,7 @@ impl Store for SqliteStore {
         )
         .bind(status.to_string())
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -441,7 +911,7 @@ impl Store for SqliteStore {
         .bind(steps_completed)
         .bind(current_step)
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -457,7 +927,7 @@ impl Store for SqliteStore {
         )
         .bind(outputs_json)
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -478,22 +948,129 @@ impl Store for SqliteStore {
         .bind(error)
         .bind(Utc::now())
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
     }
     
-    async fn list_workflows(&self, limit: i64, offset: i64) -> Result<Vec<Workflow>> {
-        debug!("Listing workflows: limit={}, offset={}", limit, offset);
+    async fn bulk_cancel_workflows(&self, ids: &[Uuid], reason: &str) -> Result<BulkCancelResult> {
+        debug!("Cancelling {} workflow(s): {}", ids.len(), reason);
+        
+        let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let ids_json = serde_json::to_string(&ids)?;
+        let mut conn = self.conn().await?;
+        let own_transaction = matches!(conn, Conn::Pooled(_));
+        if own_transaction {
+            sqlx::query("BEGIN IMMEDIATE TRANSACTION").execute(&mut *conn).await?;
+        }
+        
+        let cancelled = async {
+            let found: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM workflows WHERE id IN (SELECT value FROM json_each(?1))",
+            )
+            .bind(&ids_json)
+            .fetch_one(&mut *conn)
+            .await?;
+            
+            let result = sqlx::query(
+                r#"
+                UPDATE workflows
+                SET status = ?1, error = ?2, completed_at = ?3
+                WHERE status IN (?4, ?5)
+                  AND id IN (SELECT value FROM json_each(?6))
+                "#,
+            )
+            .bind(WorkflowStatus::Cancelled.to_string())
+            .bind(reason)
+            .bind(Utc::now())
+            .bind(WorkflowStatus::Pending.to_string())
+            .bind(WorkflowStatus::Running.to_string())
+            .bind(&ids_json)
+            .execute(&mut *conn)
+            .await?;
+            Ok::<_, sqlx::Error>((found, result))
+        }.await;
+        
+        let (found, result) = match cancelled {
+            Ok(cancelled) => {
+                if own_transaction {
+                    sqlx::query("COMMIT").execute(&mut *conn).await?;
+                }
+                cancelled
+            }
+            Err(e) => {
+                if own_transaction {
+                    sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+                }
+                return Err(Error::Sqlx(e));
+            }
+        };
+        
+        let cancelled = result.rows_affected();
+        Ok(BulkCancelResult {
+            cancelled,
+            not_found: ids.len() as u64 - found as u64,
+            already_completed: found as u64 - cancelled,
+        })
+    }
+    
+    async fn list_workflows(&self, limit: i64, offset: i64, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<Vec<Workflow>> {
+        debug!("Listing workflows: limit={}, offset={}, since={:?}, until={:?}", limit, offset, since, until);
+        
+        let mut workflows = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM workflows
+            WHERE (?3 IS NULL OR created_at >= ?3)
+              AND (?4 IS NULL OR created_at < ?4)
+            ORDER BY created_at DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .bind(since)
+        .bind(until)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(workflow) = self.get_workflow(row.get::<String, _>("id").parse()?).await? {
+                workflows.push(workflow);
+            }
+        }
+        
+        Ok(workflows)
+    }
+    
+    async fn list_workflows_by_status(
+        &self,
+        status: WorkflowStatus,
+        limit: i64,
+        offset: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Workflow>> {
+        debug!("Listing workflows by status: {:?}, limit={}, offset={}, since={:?}, until={:?}", status, limit, offset, since, until);
         
         let mut workflows = Vec::new();
         let rows = sqlx::query(
-            "SELECT id FROM workflows ORDER BY created_at DESC LIMIT ?1 OFFSET ?2",
+            r#"
+            SELECT id FROM workflows
+            WHERE status = ?1
+              AND (?4 IS NULL OR created_at >= ?4)
+              AND (?5 IS NULL OR created_at < ?5)
+            ORDER BY created_at DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
         )
+        .bind(status.to_string())
         .bind(limit)
         .bind(offset)
-        .fetch_all(&self.pool)
+        .bind(since)
+        .bind(until)
+        .fetch_all(&mut *self.conn().await?)
         .await?;
         
         for row in rows {
@@ -505,6 +1082,199 @@ impl Store for SqliteStore {
         Ok(workflows)
     }
     
+    async fn count_workflows(&self, status: Option<WorkflowStatus>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Result<i64> {
+        debug!("Counting workflows: status={:?}, since={:?}, until={:?}", status, since, until);
+        
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM workflows
+            WHERE (?1 IS NULL OR status = ?1)
+              AND (?2 IS NULL OR created_at >= ?2)
+              AND (?3 IS NULL OR created_at < ?3)
+            "#,
+        )
+        .bind(status.map(|s| s.to_string()))
+        .bind(since)
+        .bind(until)
+        .fetch_one(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(count)
+    }
+    
+    async fn list_workflows_for_alert(&self, alert_id: Uuid) -> Result<Vec<Workflow>> {
+        debug!("Listing workflows for alert: {}", alert_id);
+        
+        let mut workflows = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM workflows
+            WHERE id = (SELECT workflow_id FROM alerts WHERE id = ?1)
+               OR json_extract(input_context, '$.alert_id') = ?1
+            ORDER BY started_at DESC
+            "#,
+        )
+        .bind(alert_id.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(workflow) = self.get_workflow(row.get::<String, _>("id").parse()?).await? {
+                workflows.push(workflow);
+            }
+        }
+        
+        Ok(workflows)
+    }
+    
+    async fn get_workflow_with_all_data(&self, id: Uuid) -> Result<Option<WorkflowWithAllData>> {
+        debug!("Getting workflow with all data: {}", id);
+        
+        // The workflow and its steps, one row per step
+        let rows = sqlx::query(
+            r#"
+            SELECT w.id, w.name, w.namespace, w.trigger_source, w.status,
+                   w.steps_completed, w.total_steps, w.current_step,
+                   w.input_context, w.outputs, w.error,
+                   w.started_at, w.completed_at, w.created_at,
+                   s.id AS step_id, s.name AS step_name, s.step_type AS step_type,
+                   s.status AS step_status, s.config AS step_config,
+                   s.started_at AS step_started_at, s.completed_at AS step_completed_at,
+                   s.result AS step_result, s.error AS step_error, s.created_at AS step_created_at
+            FROM workflows w
+            LEFT JOIN workflow_steps s ON s.workflow_id = w.id
+            WHERE w.id = ?1
+            ORDER BY s.created_at
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        let Some(r) = rows.first() else {
+            return Ok(None);
+        };
+        let workflow = Workflow {
+            id: r.get::<String, _>("id").parse()?,
+            name: r.get("name"),
+            namespace: r.get("namespace"),
+            trigger_source: r.get("trigger_source"),
+            status: r.get::<String, _>("status").parse()?,
+            steps_completed: r.get("steps_completed"),
+            total_steps: r.get("total_steps"),
+            current_step: r.get("current_step"),
+            input_context: r.get::<Option<String>, _>("input_context")
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?,
+            outputs: r.get::<Option<String>, _>("outputs")
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?,
+            error: r.get("error"),
+            started_at: r.get("started_at"),
+            completed_at: r.get("completed_at"),
+            created_at: r.get("created_at"),
+        };
+        
+        let mut steps = Vec::new();
+        for r in &rows {
+            // A workflow without steps still comes back as one row
+            let Some(step_id) = r.get::<Option<String>, _>("step_id") else {
+                continue;
+            };
+            steps.push(WorkflowStep {
+                id: step_id.parse()?,
+                workflow_id: workflow.id,
+                name: r.get("step_name"),
+                step_type: r.get::<String, _>("step_type").parse()?,
+                status: r.get::<String, _>("step_status").parse()?,
+                config: r.get::<Option<String>, _>("step_config")
+                    .map(|s| serde_json::from_str(&s))
+                    .transpose()?,
+                started_at: r.get("step_started_at"),
+                completed_at: r.get("step_completed_at"),
+                result: r.get::<Option<String>, _>("step_result")
+                    .map(|s| serde_json::from_str(&s))
+                    .transpose()?,
+                error: r.get("step_error"),
+                created_at: r.get("step_created_at"),
+            });
+        }
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, sink_name, sink_type,
+                   payload, status, error, sent_at, created_at
+            FROM sink_outputs
+            WHERE workflow_id = ?1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        let sink_outputs = rows.into_iter()
+            .map(|r| Ok(SinkOutput {
+                id: r.get::<String, _>("id").parse()?,
+                workflow_id: r.get::<String, _>("workflow_id").parse()?,
+                sink_name: r.get("sink_name"),
+                sink_type: r.get::<String, _>("sink_type").parse()?,
+                payload: r.get::<Option<String>, _>("payload")
+                    .map(|s| serde_json::from_str(&s))
+                    .transpose()?,
+                status: r.get::<String, _>("status").parse()?,
+                error: r.get("error"),
+                sent_at: r.get("sent_at"),
+                created_at: r.get("created_at"),
+            }))
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(Some(WorkflowWithAllData { workflow, steps, sink_outputs }))
+    }
+    
+    async fn list_workflows_for_time_range(&self, from: DateTime<Utc>, to: DateTime<Utc>, bucket_minutes: u32) -> Result<Vec<WorkflowBucket>> {
+        debug!("Listing workflow buckets: from={}, to={}, bucket={}m", from, to, bucket_minutes);
+        
+        if bucket_minutes == 0 {
+            return Err(Error::Validation("Bucket size must be greater than zero".to_string()));
+        }
+        let bucket_seconds = i64::from(bucket_minutes) * 60;
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT (CAST(strftime('%s', completed_at) AS INTEGER) / ?1) * ?1 AS bucket,
+                   SUM(CASE WHEN status = ?4 THEN 1 ELSE 0 END) AS succeeded,
+                   SUM(CASE WHEN status = ?5 THEN 1 ELSE 0 END) AS failed
+            FROM workflows
+            WHERE completed_at IS NOT NULL
+              AND (julianday(completed_at) - 2440587.5) * 86400.0 >= ?2
+              AND (julianday(completed_at) - 2440587.5) * 86400.0 < ?3
+            GROUP BY bucket
+            ORDER BY bucket
+            "#,
+        )
+        .bind(bucket_seconds)
+        .bind(from.timestamp_millis() as f64 / 1000.0)
+        .bind(to.timestamp_millis() as f64 / 1000.0)
+        .bind(WorkflowStatus::Succeeded.to_string())
+        .bind(WorkflowStatus::Failed.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.into_iter()
+            .map(|r| {
+                let bucket: i64 = r.get("bucket");
+                let bucket_start = DateTime::from_timestamp(bucket, 0)
+                    .ok_or_else(|| Error::Internal(format!("Invalid bucket timestamp: {}", bucket)))?;
+                Ok(WorkflowBucket {
+                    bucket_start,
+                    succeeded: r.get("succeeded"),
+                    failed: r.get("failed"),
+                })
+            })
+            .collect()
+    }
+    
     async fn save_source_event(&self, event: SourceEvent) -> Result<()> {
         debug!("Saving source event: {}", event.id);
         
@@ -523,12 +1293,40 @@ impl Store for SqliteStore {
         .bind(event_data_json)
         .bind(&event.workflow_triggered)
         .bind(event.received_at)
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
     }
     
+    async fn save_scheduled_source_event(&self, event: SourceEvent, namespace: &str, scheduled_at: DateTime<Utc>) -> Result<bool> {
+        debug!("Saving source event {} for the run of {}/{} at {}", event.id, namespace, event.source_name, scheduled_at);
+        
+        let event_data_json = serde_json::to_string(&event.event_data)?;
+        
+        let result = sqlx::query(
+            r#"
+            INSERT INTO source_events (
+                id, source_name, source_type, event_data, workflow_triggered, received_at, scheduled_at,
+                source_namespace
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (source_namespace, source_name, scheduled_at) DO NOTHING
+            "#,
+        )
+        .bind(event.id.to_string())
+        .bind(&event.source_name)
+        .bind(event.source_type.to_string())
+        .bind(event_data_json)
+        .bind(&event.workflow_triggered)
+        .bind(event.received_at)
+        .bind(scheduled_at)
+        .bind(namespace)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
     async fn get_source_event(&self, id: Uuid) -> Result<Option<SourceEvent>> {
         debug!("Getting source event: {}", id);
         
@@ -540,7 +1338,7 @@ impl Store for SqliteStore {
             "#,
         )
         .bind(id.to_string())
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match row {
@@ -560,6 +1358,17 @@ impl Store for SqliteStore {
         }
     }
     
+    async fn delete_source_event(&self, id: Uuid) -> Result<()> {
+        debug!("Deleting source event: {}", id);
+        
+        sqlx::query("DELETE FROM source_events WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
+    }
+    
     async fn list_source_events(&self, source_name: &str, limit: i64) -> Result<Vec<SourceEvent>> {
         debug!("Listing source events for source: {}, limit={}", source_name, limit);
         
@@ -569,7 +1378,7 @@ impl Store for SqliteStore {
         )
         .bind(source_name)
         .bind(limit)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *self.conn().await?)
         .await?;
         
         for row in rows {
@@ -581,6 +1390,54 @@ impl Store for SqliteStore {
         Ok(events)
     }
     
+    async fn set_source_event_workflow(&self, id: Uuid, workflow_id: Uuid) -> Result<()> {
+        debug!("Linking source event {} to workflow {}", id, workflow_id);
+        
+        sqlx::query("UPDATE source_events SET workflow_id = ?1 WHERE id = ?2")
+            .bind(workflow_id.to_string())
+            .bind(id.to_string())
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
+    }
+    
+    async fn list_source_events_with_workflow_status(&self, source_name: &str, limit: i64) -> Result<Vec<SourceEventWithWorkflow>> {
+        debug!("Listing source events with workflow status for source: {}, limit={}", source_name, limit);
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT e.id, e.source_name, e.source_type, e.event_data, e.workflow_triggered, e.received_at,
+                   w.id AS workflow_id, w.status AS workflow_status, w.completed_at AS workflow_completed_at
+            FROM source_events e
+            LEFT JOIN workflows w ON w.id = e.workflow_id
+            WHERE e.source_name = ?1
+            ORDER BY e.received_at DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(source_name)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(|r| {
+            Ok(SourceEventWithWorkflow {
+                event: SourceEvent {
+                    id: r.get::<String, _>("id").parse()?,
+                    source_name: r.get("source_name"),
+                    source_type: r.get::<String, _>("source_type").parse()?,
+                    event_data: serde_json::from_str(r.get("event_data"))?,
+                    workflow_triggered: r.get("workflow_triggered"),
+                    received_at: r.get("received_at"),
+                },
+                workflow_id: r.get::<Option<String>, _>("workflow_id").map(|id| id.parse()).transpose()?,
+                workflow_status: r.get::<Option<String>, _>("workflow_status").map(|s| s.parse()).transpose()?,
+                workflow_completed_at: r.get("workflow_completed_at"),
+            })
+        }).collect()
+    }
+    
     async fn save_workflow_step(&self, step: WorkflowStep) -> Result<()> {
         debug!("Saving workflow step: {}", step.id);
         
@@ -616,7 +1473,7 @@ impl Store for SqliteStore {
         .bind(result_json)
         .bind(&step.error)
         .bind(step.created_at)
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -634,7 +1491,7 @@ impl Store for SqliteStore {
             "#,
         )
         .bind(id.to_string())
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match row {
@@ -680,7 +1537,7 @@ impl Store for SqliteStore {
             .bind(status.to_string())
             .bind(timestamp)
             .bind(id.to_string())
-            .execute(&self.pool)
+            .execute(&mut *self.conn().await?)
             .await?;
         } else {
             sqlx::query(
@@ -688,7 +1545,7 @@ impl Store for SqliteStore {
             )
             .bind(status.to_string())
             .bind(id.to_string())
-            .execute(&self.pool)
+            .execute(&mut *self.conn().await?)
             .await?;
         }
         
@@ -710,12 +1567,24 @@ impl Store for SqliteStore {
         .bind(error)
         .bind(Utc::now())
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
     }
     
+    async fn update_workflow_step_result(&self, id: Uuid, result: JsonValue) -> Result<()> {
+        debug!("Updating workflow step result: {}", id);
+        
+        sqlx::query("UPDATE workflow_steps SET result = ?1 WHERE id = ?2")
+            .bind(serde_json::to_string(&result)?)
+            .bind(id.to_string())
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(())
+    }
+    
     async fn list_workflow_steps(&self, workflow_id: Uuid) -> Result<Vec<WorkflowStep>> {
         debug!("Listing workflow steps for workflow: {}", workflow_id);
         
@@ -724,7 +1593,7 @@ impl Store for SqliteStore {
             "SELECT id FROM workflow_steps WHERE workflow_id = ?1 ORDER BY created_at",
         )
         .bind(workflow_id.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *self.conn().await?)
         .await?;
         
         for row in rows {
@@ -750,6 +1619,7 @@ impl Store for SqliteStore {
                 payload, status, error, sent_at, created_at
             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
             ON CONFLICT(id) DO UPDATE SET
+                payload = excluded.payload,
                 status = excluded.status,
                 error = excluded.error,
                 sent_at = excluded.sent_at
@@ -764,7 +1634,7 @@ impl Store for SqliteStore {
         .bind(&output.error)
         .bind(output.sent_at)
         .bind(output.created_at)
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -782,7 +1652,7 @@ impl Store for SqliteStore {
             "#,
         )
         .bind(id.to_string())
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match row {
@@ -816,16 +1686,21 @@ impl Store for SqliteStore {
             None
         };
         
-        sqlx::query(
-            "UPDATE sink_outputs SET status = ?1, error = ?2, sent_at = ?3 WHERE id = ?4",
+        let row = sqlx::query(
+            "UPDATE sink_outputs SET status = ?1, error = ?2, sent_at = ?3 WHERE id = ?4 RETURNING sink_type, created_at",
         )
         .bind(status.to_string())
         .bind(error)
         .bind(sent_at)
         .bind(id.to_string())
-        .execute(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
+        if let (Some(row), Some(sent_at)) = (row, sent_at) {
+            let created_at: DateTime<Utc> = row.get("created_at");
+            crate::metrics::observe_sink_delivery_latency(&row.get::<String, _>("sink_type"), created_at, sent_at);
+        }
+        
         Ok(())
     }
     
@@ -837,7 +1712,67 @@ impl Store for SqliteStore {
             "SELECT id FROM sink_outputs WHERE workflow_id = ?1 ORDER BY created_at",
         )
         .bind(workflow_id.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        for row in rows {
+            if let Some(output) = self.get_sink_output(row.get::<String, _>("id").parse()?).await? {
+                outputs.push(output);
+            }
+        }
+        
+        Ok(outputs)
+    }
+    
+    async fn count_sink_outputs_by_type(&self) -> Result<Vec<SinkTypeStats>> {
+        debug!("Counting sink outputs by type");
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT sink_type,
+                   SUM(CASE WHEN status = ?1 THEN 1 ELSE 0 END) AS sent,
+                   SUM(CASE WHEN status = ?2 THEN 1 ELSE 0 END) AS failed,
+                   SUM(CASE WHEN status = ?3 THEN 1 ELSE 0 END) AS pending,
+                   AVG((julianday(sent_at) - julianday(created_at)) * 86400000.0) AS avg_latency_ms
+            FROM sink_outputs
+            GROUP BY sink_type
+            ORDER BY sink_type
+            "#,
+        )
+        .bind(SinkStatus::Sent.to_string())
+        .bind(SinkStatus::Failed.to_string())
+        .bind(SinkStatus::Pending.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.into_iter()
+            .map(|r| Ok(SinkTypeStats {
+                sink_type: r.get::<String, _>("sink_type").parse()?,
+                sent: r.get("sent"),
+                failed: r.get("failed"),
+                pending: r.get("pending"),
+                avg_latency_ms: r.get("avg_latency_ms"),
+            }))
+            .collect()
+    }
+    
+    async fn list_recent_sink_failures(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<SinkOutput>> {
+        debug!("Listing sink failures since {} with limit {}", since, limit);
+        
+        let mut outputs = Vec::new();
+        let rows = sqlx::query(
+            r#"
+            SELECT id FROM sink_outputs
+            WHERE status = ?1
+              AND created_at >= ?2
+            ORDER BY created_at DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(SinkStatus::Failed.to_string())
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
         .await?;
         
         for row in rows {
@@ -879,7 +1814,7 @@ impl Store for SqliteStore {
         .bind(status_json)
         .bind(resource.created_at)
         .bind(resource.updated_at)
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -899,7 +1834,7 @@ impl Store for SqliteStore {
         .bind(kind)
         .bind(namespace)
         .bind(name)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *self.conn().await?)
         .await?;
         
         match row {
@@ -936,7 +1871,7 @@ impl Store for SqliteStore {
         .bind(status_json)
         .bind(Utc::now())
         .bind(id.to_string())
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
@@ -951,48 +1886,327 @@ impl Store for SqliteStore {
         .bind(kind)
         .bind(namespace)
         .bind(name)
-        .execute(&self.pool)
+        .execute(&mut *self.conn().await?)
         .await?;
         
         Ok(())
     }
     
-    async fn list_custom_resources(&self, kind: &str, namespace: Option<&str>) -> Result<Vec<CustomResource>> {
-        debug!("Listing custom resources: kind={}, namespace={:?}", kind, namespace);
+    async fn list_custom_resources(
+        &self,
+        kind: &str,
+        namespace: Option<&str>,
+        all_namespaces: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CustomResource>> {
+        debug!(
+            "Listing custom resources: kind={}, namespace={:?}, all_namespaces={}, limit={}, offset={}",
+            kind, namespace, all_namespaces, limit, offset
+        );
+        
+        let namespace = match (all_namespaces, namespace) {
+            (true, _) => None,
+            (false, Some(ns)) => Some(ns),
+            (false, None) => {
+                return Err(Error::Validation(
+                    "A namespace is required to list custom resources unless all_namespaces is set".to_string(),
+                ));
+            }
+        };
         
-        let mut resources = Vec::new();
+        // A NULL namespace parameter matches every namespace
+        let rows = sqlx::query(
+            r#"
+            SELECT id, api_version, kind, name, namespace,
+                   spec, status, created_at, updated_at
+            FROM custom_resources
+            WHERE kind = ?1 AND (?2 IS NULL OR namespace = ?2)
+            ORDER BY created_at DESC, name
+            LIMIT ?3 OFFSET ?4
+            "#,
+        )
+        .bind(kind)
+        .bind(namespace)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
         
-        let rows = if let Some(ns) = namespace {
-            sqlx::query(
-                "SELECT kind, namespace, name FROM custom_resources WHERE kind = ?1 AND namespace = ?2 ORDER BY created_at DESC",
-            )
-            .bind(kind)
-            .bind(ns)
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            sqlx::query(
-                "SELECT kind, namespace, name FROM custom_resources WHERE kind = ?1 ORDER BY created_at DESC",
-            )
-            .bind(kind)
-            .fetch_all(&self.pool)
-            .await?
-        };
+        let mut resources = Vec::with_capacity(rows.len());
+        for r in rows {
+            let spec: JsonValue = serde_json::from_str(r.get("spec"))?;
+            let status: Option<JsonValue> = r.get::<Option<String>, _>("status")
+                .map(|s| serde_json::from_str(&s))
+                .transpose()?;
+            
+            resources.push(CustomResource {
+                id: r.get::<String, _>("id").parse()?,
+                api_version: r.get("api_version"),
+                kind: r.get("kind"),
+                name: r.get("name"),
+                namespace: r.get("namespace"),
+                spec,
+                status,
+                created_at: r.get("created_at"),
+                updated_at: r.get("updated_at"),
+            });
+        }
+        
+        Ok(resources)
+    }
+    
+    async fn count_custom_resources(&self, kind: &str, namespace: Option<&str>) -> Result<i64> {
+        debug!("Counting custom resources: kind={}, namespace={:?}", kind, namespace);
+        
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM custom_resources WHERE kind = ?1 AND (?2 IS NULL OR namespace = ?2)",
+        )
+        .bind(kind)
+        .bind(namespace)
+        .fetch_one(&mut *self.conn().await?)
+        .await?;
         
+        Ok(count)
+    }
+    
+    async fn save_chat_session(&self, session: ChatSession) -> Result<()> {
+        debug!("Saving chat session: {}", session.session_id);
+        
+        let messages_json = serde_json::to_string(&session.messages)?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO chat_sessions (
+                session_id, user_id, messages, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(session_id) DO UPDATE SET
+                messages = excluded.messages,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&session.session_id)
+        .bind(&session.user_id)
+        .bind(messages_json)
+        .bind(session.created_at)
+        .bind(session.updated_at)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn get_chat_session(&self, session_id: &str) -> Result<Option<ChatSession>> {
+        debug!("Getting chat session: {}", session_id);
+        
+        let row = sqlx::query(
+            r#"
+            SELECT session_id, user_id, messages, created_at, updated_at
+            FROM chat_sessions
+            WHERE session_id = ?1
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&mut *self.conn().await?)
+        .await?;
+        
+        match row {
+            Some(r) => Ok(Some(ChatSession {
+                session_id: r.get("session_id"),
+                user_id: r.get("user_id"),
+                messages: serde_json::from_str(r.get("messages"))?,
+                created_at: r.get("created_at"),
+                updated_at: r.get("updated_at"),
+            })),
+            None => Ok(None),
+        }
+    }
+    
+    async fn list_chat_sessions_by_user(&self, user_id: &str) -> Result<Vec<ChatSession>> {
+        debug!("Listing chat sessions for user: {}", user_id);
+        
+        let rows = sqlx::query(
+            "SELECT session_id FROM chat_sessions WHERE user_id = ?1 ORDER BY updated_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        let mut sessions = Vec::new();
         for row in rows {
-            let kind: String = row.get("kind");
-            let namespace: String = row.get("namespace");
-            let name: String = row.get("name");
-            
-            if let Some(resource) = self.get_custom_resource(&kind, &namespace, &name).await? {
-                resources.push(resource);
+            let session_id: String = row.get("session_id");
+            if let Some(session) = self.get_chat_session(&session_id).await? {
+                sessions.push(session);
             }
         }
         
-        Ok(resources)
+        Ok(sessions)
+    }
+    
+    async fn delete_chat_sessions_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        debug!("Deleting chat sessions last updated before {}", cutoff);
+        
+        let result = sqlx::query("DELETE FROM chat_sessions WHERE updated_at < ?1")
+            .bind(cutoff)
+            .execute(&mut *self.conn().await?)
+            .await?;
+        
+        Ok(result.rows_affected())
+    }
+    
+    async fn save_audit_entry(&self, entry: AuditEntry) -> Result<()> {
+        debug!("Saving audit entry: {} {} {}", entry.operation, entry.entity_type, entry.entity_id);
+        
+        let before_json = entry.before.as_ref().map(serde_json::to_string).transpose()?;
+        let after_json = entry.after.as_ref().map(serde_json::to_string).transpose()?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (
+                id, operation, entity_type, entity_id, actor, "before", "after", created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(entry.id.to_string())
+        .bind(&entry.operation)
+        .bind(&entry.entity_type)
+        .bind(&entry.entity_id)
+        .bind(&entry.actor)
+        .bind(before_json)
+        .bind(after_json)
+        .bind(entry.created_at)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn list_audit_entries(&self, entity_type: Option<&str>, entity_id: Option<&str>, limit: i64) -> Result<Vec<AuditEntry>> {
+        debug!("Listing audit entries: entity_type={:?}, entity_id={:?}, limit={}", entity_type, entity_id, limit);
+        
+        // NULL parameters match every entity
+        let rows = sqlx::query(
+            r#"
+            SELECT id, operation, entity_type, entity_id, actor, "before", "after", created_at
+            FROM audit_log
+            WHERE (?1 IS NULL OR entity_type = ?1) AND (?2 IS NULL OR entity_id = ?2)
+            ORDER BY created_at DESC, rowid DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(limit)
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(|r| {
+            let json = |column: &str| r.get::<Option<String>, _>(column)
+                .map(|s| serde_json::from_str(&s))
+                .transpose();
+            Ok(AuditEntry {
+                id: r.get::<String, _>("id").parse()?,
+                operation: r.get("operation"),
+                entity_type: r.get("entity_type"),
+                entity_id: r.get("entity_id"),
+                actor: r.get("actor"),
+                before: json("before")?,
+                after: json("after")?,
+                created_at: r.get("created_at"),
+            })
+        }).collect()
+    }
+    
+    async fn save_tool_invocation(&self, invocation: ToolInvocation) -> Result<()> {
+        debug!("Saving {} invocation for workflow {}", invocation.tool_name, invocation.workflow_id);
+        
+        sqlx::query(
+            r#"
+            INSERT INTO tool_invocations (
+                id, workflow_id, step_name, tool_name, arguments, success, output, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(invocation.id.to_string())
+        .bind(invocation.workflow_id.to_string())
+        .bind(&invocation.step_name)
+        .bind(&invocation.tool_name)
+        .bind(serde_json::to_string(&invocation.arguments)?)
+        .bind(invocation.success)
+        .bind(&invocation.output)
+        .bind(invocation.created_at)
+        .execute(&mut *self.conn().await?)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn list_tool_invocations_by_workflow(&self, workflow_id: Uuid) -> Result<Vec<ToolInvocation>> {
+        debug!("Listing tool invocations for workflow: {}", workflow_id);
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT id, workflow_id, step_name, tool_name, arguments, success, output, created_at
+            FROM tool_invocations
+            WHERE workflow_id = ?1
+            ORDER BY created_at, rowid
+            "#,
+        )
+        .bind(workflow_id.to_string())
+        .fetch_all(&mut *self.conn().await?)
+        .await?;
+        
+        rows.iter().map(|r| Ok(ToolInvocation {
+            id: r.get::<String, _>("id").parse()?,
+            workflow_id: r.get::<String, _>("workflow_id").parse()?,
+            step_name: r.get("step_name"),
+            tool_name: r.get("tool_name"),
+            arguments: serde_json::from_str(&r.get::<String, _>("arguments"))?,
+            success: r.get("success"),
+            output: r.get("output"),
+            created_at: r.get("created_at"),
+        })).collect()
     }
 }
 
+/// Bind every column of an alert, in `alerts` table order, to `query`
+fn bind_alert<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    alert: &Alert,
+) -> Result<Query<'q, Sqlite, SqliteArguments<'q>>> {
+    let labels_json = serde_json::to_string(&alert.labels)?;
+    let annotations_json = serde_json::to_string(&alert.annotations)?;
+    let ai_analysis_json = alert.ai_analysis.as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    
+    Ok(query
+        .bind(alert.id.to_string())
+        .bind(alert.external_id.clone())
+        .bind(alert.fingerprint.clone())
+        .bind(alert.status.to_string())
+        .bind(alert.severity.to_string())
+        .bind(alert.alert_name.clone())
+        .bind(alert.alert_name.clone())
+        .bind(alert.summary.clone())
+        .bind(alert.description.clone())
+        .bind(labels_json)
+        .bind(annotations_json)
+        .bind(alert.source_id.map(|id| id.to_string()))
+        .bind(alert.workflow_id.map(|id| id.to_string()))
+        .bind(ai_analysis_json)
+        .bind(alert.ai_confidence)
+        .bind(alert.auto_resolved)
+        .bind(alert.starts_at)
+        .bind(alert.ends_at)
+        .bind(alert.received_at)
+        .bind(alert.triage_started_at)
+        .bind(alert.triage_completed_at)
+        .bind(alert.resolved_at)
+        .bind(alert.created_at)
+        .bind(alert.updated_at))
+}
+
 // Helper implementations for parsing string to enums
 impl std::str::FromStr for AlertStatus {
     type Err = Error;
@@ -1052,6 +2266,7 @@ impl std::str::FromStr for SourceType {
             "schedule" => Ok(SourceType::Schedule),
             "api" => Ok(SourceType::Api),
             "kubernetes" => Ok(SourceType::Kubernetes),
+            "escalation" => Ok(SourceType::Escalation),
             _ => Err(Error::Config(format!("Invalid source type: {}", s))),
         }
     }
@@ -1065,6 +2280,7 @@ impl std::fmt::Display for SourceType {
             SourceType::Schedule => write!(f, "schedule"),
             SourceType::Api => write!(f, "api"),
             SourceType::Kubernetes => write!(f, "kubernetes"),
+            SourceType::Escalation => write!(f, "escalation"),
         }
     }
 }
@@ -1078,6 +2294,7 @@ impl std::str::FromStr for WorkflowStatus {
             "running" => Ok(WorkflowStatus::Running),
             "succeeded" => Ok(WorkflowStatus::Succeeded),
             "failed" => Ok(WorkflowStatus::Failed),
+            "cancelled" => Ok(WorkflowStatus::Cancelled),
             _ => Err(Error::Config(format!("Invalid workflow status: {}", s))),
         }
     }
@@ -1090,6 +2307,7 @@ impl std::fmt::Display for WorkflowStatus {
             WorkflowStatus::Running => write!(f, "running"),
             WorkflowStatus::Succeeded => write!(f, "succeeded"),
             WorkflowStatus::Failed => write!(f, "failed"),
+            WorkflowStatus::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -1102,6 +2320,7 @@ impl std::str::FromStr for StepType {
             "cli" => Ok(StepType::Cli),
             "agent" => Ok(StepType::Agent),
             "conditional" => Ok(StepType::Conditional),
+            "http" => Ok(StepType::Http),
             _ => Err(Error::Config(format!("Invalid step type: {}", s))),
         }
     }
@@ -1113,6 +2332,7 @@ impl std::fmt::Display for StepType {
             StepType::Cli => write!(f, "cli"),
             StepType::Agent => write!(f, "agent"),
             StepType::Conditional => write!(f, "conditional"),
+            StepType::Http => write!(f, "http"),
         }
     }
 }
@@ -1155,6 +2375,9 @@ impl std::str::FromStr for SinkType {
             "jira" => Ok(SinkType::Jira),
             "pagerduty" => Ok(SinkType::PagerDuty),
             "workflow" => Ok(SinkType::Workflow),
+            "stdout" => Ok(SinkType::Stdout),
+            "webhook" => Ok(SinkType::Webhook),
+            "inhibition" => Ok(SinkType::Inhibition),
             _ => Err(Error::Config(format!("Invalid sink type: {}", s))),
         }
     }
@@ -1169,6 +2392,9 @@ impl std::fmt::Display for SinkType {
             SinkType::Jira => write!(f, "jira"),
             SinkType::PagerDuty => write!(f, "pagerduty"),
             SinkType::Workflow => write!(f, "workflow"),
+            SinkType::Stdout => write!(f, "stdout"),
+            SinkType::Webhook => write!(f, "webhook"),
+            SinkType::Inhibition => write!(f, "inhibition"),
         }
     }
 }
@@ -1194,4 +2420,628 @@ impl std::fmt::Display for SinkStatus {
             SinkStatus::Failed => write!(f, "failed"),
         }
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_concurrent_init_is_serialized() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+
+        let first = SqliteStore::new(&url).await.unwrap();
+        let second = SqliteStore::new(&url).await.unwrap();
+        let (a, b) = tokio::join!(first.init(), second.init());
+        a.unwrap();
+        b.unwrap();
+
+        // The lock is released once migrations finish
+        first.init().await.unwrap();
+        let held: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _migration_lock")
+            .fetch_one(&first.pool)
+            .await
+            .unwrap();
+        assert_eq!(held, 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_stale_migration_lock_is_taken_over() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+
+        // A process killed mid-migration never deletes its lock row
+        sqlx::query("INSERT INTO _migration_lock (name, locked_at) VALUES (?1, ?2)")
+            .bind(MIGRATION_LOCK_NAME)
+            .bind(Utc::now() - chrono::Duration::minutes(10))
+            .execute(&store.pool)
+            .await
+            .unwrap();
+
+        tokio::time::timeout(MIGRATION_LOCK_RETRY_INTERVAL, store.init())
+            .await
+            .expect("stale lock should not be waited for")
+            .unwrap();
+        let held: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _migration_lock")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(held, 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_chat_sessions() {
+        use crate::store::ArchivalTask;
+        use serde_json::json;
+
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = std::sync::Arc::new(SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap());
+        store.init().await.unwrap();
+
+        let now = Utc::now();
+        let mut session = ChatSession {
+            session_id: "session-1".to_string(),
+            user_id: "alice".to_string(),
+            messages: json!([{ "role": "user", "content": "hi" }]),
+            created_at: now - chrono::Duration::days(10),
+            updated_at: now - chrono::Duration::days(8),
+        };
+        store.save_chat_session(session.clone()).await.unwrap();
+        store.save_chat_session(ChatSession {
+            session_id: "session-2".to_string(),
+            updated_at: now - chrono::Duration::days(1),
+            ..session.clone()
+        }).await.unwrap();
+
+        // Saving again replaces the history
+        session.messages = json!([
+            { "role": "user", "content": "hi" },
+            { "role": "assistant", "content": "hello" }
+        ]);
+        store.save_chat_session(session.clone()).await.unwrap();
+        let loaded = store.get_chat_session("session-1").await.unwrap().unwrap();
+        assert_eq!(loaded.messages, session.messages);
+
+        let sessions = store.list_chat_sessions_by_user("alice").await.unwrap();
+        assert_eq!(sessions.iter().map(|s| s.session_id.as_str()).collect::<Vec<_>>(), vec!["session-2", "session-1"]);
+
+        // Only the session idle for more than a week expires
+        let archival = ArchivalTask::new(store.clone());
+        assert_eq!(archival.expire_chat_sessions(now).await.unwrap(), 1);
+        assert!(store.get_chat_session("session-1").await.unwrap().is_none());
+        assert!(store.get_chat_session("session-2").await.unwrap().is_some());
+
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    fn test_alert(fingerprint: &str) -> Alert {
+        let now = Utc::now();
+        Alert {
+            id: Uuid::new_v4(),
+            external_id: None,
+            fingerprint: fingerprint.to_string(),
+            status: AlertStatus::Received,
+            severity: AlertSeverity::Warning,
+            alert_name: "HighCPU".to_string(),
+            summary: None,
+            description: None,
+            labels: HashMap::new(),
+            annotations: HashMap::new(),
+            source_id: None,
+            workflow_id: None,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at: now,
+            ends_at: None,
+            received_at: now,
+            triage_started_at: None,
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_get_or_create_alert_by_fingerprint() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        // Concurrent deliveries of the same alert create a single row
+        let results = futures::future::join_all(
+            (0..8).map(|_| store.get_or_create_alert_by_fingerprint(test_alert("fp-1")))
+        ).await;
+        let mut created = Vec::new();
+        let mut duplicates = Vec::new();
+        for result in results {
+            match result.unwrap() {
+                DeduplicationResult::New(alert) => created.push(alert.id),
+                DeduplicationResult::Duplicate(alert) | DeduplicationResult::Updated(alert) => duplicates.push(alert.id),
+            }
+        }
+        assert_eq!(created.len(), 1);
+        assert!(duplicates.iter().all(|id| *id == created[0]));
+        
+        // Once resolved, the next delivery opens a new alert
+        store.update_alert_status(created[0], AlertStatus::Resolved).await.unwrap();
+        let reopened = store.deduplicate_alert("fp-1", test_alert("ignored")).await.unwrap();
+        assert!(matches!(reopened, DeduplicationResult::New(alert) if alert.id != created[0] && alert.fingerprint == "fp-1"));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_dedup_window() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap()
+            .with_dedup_window(Some(std::time::Duration::from_secs(3600)));
+        store.init().await.unwrap();
+        
+        // Within the window a re-fire is a duplicate unless it brings
+        // something new, which is merged into the alert
+        let mut fresh = test_alert("fp-fresh");
+        fresh.labels.insert("pod".to_string(), "api-7f9".to_string());
+        store.save_alert(fresh.clone()).await.unwrap();
+        let mut same = test_alert("fp-fresh");
+        same.labels = fresh.labels.clone();
+        let refire = store.deduplicate_alert("fp-fresh", same).await.unwrap();
+        assert!(matches!(refire, DeduplicationResult::Duplicate(alert) if alert.id == fresh.id));
+        let mut annotated = test_alert("fp-fresh");
+        annotated.annotations.insert("runbook".to_string(), "https://runbooks/cpu".to_string());
+        match store.deduplicate_alert("fp-fresh", annotated.clone()).await.unwrap() {
+            DeduplicationResult::Updated(alert) => {
+                assert_eq!(alert.id, fresh.id);
+                assert_eq!(alert.labels, fresh.labels);
+                assert_eq!(alert.annotations, annotated.annotations);
+                assert!(alert.updated_at > fresh.updated_at);
+            }
+            other => panic!("expected an updated alert, got {:?}", other),
+        }
+        annotated.id = Uuid::new_v4();
+        let refire = store.deduplicate_alert("fp-fresh", annotated).await.unwrap();
+        assert!(matches!(refire, DeduplicationResult::Duplicate(alert) if alert.id == fresh.id));
+        
+        // Past the window the stale alert is resolved and a new one opened
+        let mut stale = test_alert("fp-stale");
+        stale.updated_at = Utc::now() - chrono::Duration::hours(2);
+        store.save_alert(stale.clone()).await.unwrap();
+        let refire = test_alert("fp-stale");
+        let result = store.deduplicate_alert("fp-stale", refire.clone()).await.unwrap();
+        assert!(matches!(result, DeduplicationResult::New(alert) if alert.id == refire.id));
+        let resolved = store.get_alert(stale.id).await.unwrap().unwrap();
+        assert_eq!(resolved.status, AlertStatus::Resolved);
+        assert!(resolved.resolved_at.is_some());
+        
+        // A resolved alert never absorbs a re-fire, however recent
+        store.update_alert_status(refire.id, AlertStatus::Resolved).await.unwrap();
+        let result = store.deduplicate_alert("fp-stale", test_alert("fp-stale")).await.unwrap();
+        assert!(matches!(result, DeduplicationResult::New(alert) if alert.id != refire.id));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_alerts_near_expiry() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let now = Utc::now();
+        let mut expiring = test_alert("expiring");
+        expiring.ends_at = Some(now + chrono::Duration::minutes(3));
+        let mut later = test_alert("later");
+        later.ends_at = Some(now + chrono::Duration::hours(1));
+        let mut ended = test_alert("ended");
+        ended.ends_at = Some(now - chrono::Duration::minutes(3));
+        let mut resolved = test_alert("resolved");
+        resolved.ends_at = Some(now + chrono::Duration::minutes(2));
+        resolved.status = AlertStatus::Resolved;
+        let open_ended = test_alert("open-ended");
+        for alert in [expiring.clone(), later, ended, resolved, open_ended] {
+            store.save_alert(alert).await.unwrap();
+        }
+        
+        let alerts = store.list_alerts_near_expiry(5).await.unwrap();
+        assert_eq!(alerts.iter().map(|a| a.id).collect::<Vec<_>>(), vec![expiring.id]);
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_alerts_without_workflow() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let received_ago = |fingerprint: &str, minutes: i64| Alert {
+            received_at: Utc::now() - chrono::Duration::minutes(minutes),
+            ..test_alert(fingerprint)
+        };
+        let dropped = received_ago("dropped", 20);
+        let older = received_ago("older", 60);
+        let fresh = received_ago("fresh", 1);
+        let mut triaging = received_ago("triaging", 30);
+        triaging.status = AlertStatus::Triaging;
+        let mut with_workflow = received_ago("with-workflow", 30);
+        with_workflow.workflow_id = Some(Uuid::new_v4());
+        for alert in [dropped.clone(), older.clone(), fresh, triaging, with_workflow] {
+            store.save_alert(alert).await.unwrap();
+        }
+        
+        let alerts = store.list_alerts_without_workflow(10).await.unwrap();
+        assert_eq!(alerts.iter().map(|a| a.id).collect::<Vec<_>>(), vec![older.id, dropped.id]);
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_alerts_by_label() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        store.migrate_alert_labels_to_jsonb().await.unwrap();
+        
+        let labeled = |fingerprint: &str, labels: &[(&str, &str)]| Alert {
+            labels: labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            ..test_alert(fingerprint)
+        };
+        let api = labeled("api", &[("namespace", "prod"), ("app", "api"), ("team.io/owner", "payments")]);
+        let worker = labeled("worker", &[("namespace", "prod"), ("app", "worker")]);
+        let staging = labeled("staging", &[("namespace", "staging"), ("app", "api")]);
+        for alert in [api.clone(), worker.clone(), staging] {
+            store.save_alert(alert).await.unwrap();
+        }
+        
+        let query = |labels: &[(&str, &str)]| labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        let mut prod: Vec<Uuid> = store.list_alerts_by_label(&query(&[("namespace", "prod")]), 10).await.unwrap()
+            .into_iter().map(|a| a.id).collect();
+        prod.sort();
+        let mut expected = vec![api.id, worker.id];
+        expected.sort();
+        assert_eq!(prod, expected);
+        
+        let owned = store.list_alerts_by_label(&query(&[("namespace", "prod"), ("team.io/owner", "payments")]), 10).await.unwrap();
+        assert_eq!(owned.iter().map(|a| a.id).collect::<Vec<_>>(), vec![api.id]);
+        
+        assert!(store.list_alerts_by_label(&query(&[("app", "db")]), 10).await.unwrap().is_empty());
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_backup_to_path() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        let alert = test_alert("backed-up");
+        store.save_alert(alert.clone()).await.unwrap();
+        
+        let backup_path = std::env::temp_dir().join(format!("punching-fist-{}.backup", Uuid::new_v4()));
+        let size = store.backup_to_path(&backup_path).await.unwrap();
+        assert_eq!(size, std::fs::metadata(&backup_path).unwrap().len());
+        
+        let restored = SqliteStore::new(&format!("sqlite://{}", backup_path.display())).await.unwrap();
+        assert!(restored.get_alert(alert.id).await.unwrap().is_some());
+        
+        // VACUUM INTO refuses to overwrite an existing file
+        assert!(store.backup_to_path(&backup_path).await.is_err());
+        
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&backup_path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_recent_sink_failures() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let now = Utc::now();
+        let workflow_id = Uuid::new_v4();
+        store.save_workflow(Workflow {
+            id: workflow_id,
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 1,
+            total_steps: 1,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: Some(now),
+            created_at: now,
+        }).await.unwrap();
+        
+        let output = |status: SinkStatus, age_minutes: i64| SinkOutput {
+            id: Uuid::new_v4(),
+            workflow_id,
+            sink_name: "slack".to_string(),
+            sink_type: SinkType::Slack,
+            payload: None,
+            status,
+            error: Some("channel_not_found".to_string()),
+            sent_at: None,
+            created_at: now - chrono::Duration::minutes(age_minutes),
+        };
+        let recent = output(SinkStatus::Failed, 5);
+        let older = output(SinkStatus::Failed, 30);
+        for sink_output in [recent.clone(), older.clone(), output(SinkStatus::Failed, 120), output(SinkStatus::Sent, 1)] {
+            store.save_sink_output(sink_output).await.unwrap();
+        }
+        
+        let failures = store.list_recent_sink_failures(now - chrono::Duration::hours(1), 10).await.unwrap();
+        assert_eq!(failures.iter().map(|o| o.id).collect::<Vec<_>>(), vec![recent.id, older.id]);
+        
+        let failures = store.list_recent_sink_failures(now - chrono::Duration::hours(1), 1).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_custom_resources_paginated() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let now = Utc::now();
+        let resource = |kind: &str, namespace: &str, name: &str, age_minutes: i64| CustomResource {
+            id: Uuid::new_v4(),
+            api_version: "punchingfist.io/v1alpha1".to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            spec: serde_json::json!({ "name": name }),
+            status: None,
+            created_at: now - chrono::Duration::minutes(age_minutes),
+            updated_at: now,
+        };
+        for r in [
+            resource("Workflow", "team-a", "newest", 1),
+            resource("Workflow", "team-b", "middle", 2),
+            resource("Workflow", "team-a", "oldest", 3),
+            resource("Sink", "team-a", "slack", 1),
+        ] {
+            store.save_custom_resource(r).await.unwrap();
+        }
+        
+        let names = |resources: Vec<CustomResource>| resources.into_iter().map(|r| r.name).collect::<Vec<_>>();
+        let page = store.list_custom_resources("Workflow", None, true, 2, 0).await.unwrap();
+        assert_eq!(names(page), vec!["newest", "middle"]);
+        let page = store.list_custom_resources("Workflow", None, true, 2, 2).await.unwrap();
+        assert_eq!(page[0].spec, serde_json::json!({ "name": "oldest" }));
+        assert_eq!(names(page), vec!["oldest"]);
+        
+        // all_namespaces takes precedence over a namespace
+        let page = store.list_custom_resources("Workflow", Some("team-b"), true, 10, 0).await.unwrap();
+        assert_eq!(page.len(), 3);
+        let page = store.list_custom_resources("Workflow", Some("team-a"), false, 10, 0).await.unwrap();
+        assert_eq!(names(page), vec!["newest", "oldest"]);
+        let err = store.list_custom_resources("Workflow", None, false, 10, 0).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+        
+        assert_eq!(store.count_custom_resources("Workflow", None).await.unwrap(), 3);
+        assert_eq!(store.count_custom_resources("Workflow", Some("team-a")).await.unwrap(), 2);
+        assert_eq!(store.count_custom_resources("Source", None).await.unwrap(), 0);
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_workflows_for_alert() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let now = Utc::now();
+        let workflow = |started_minutes_ago: i64, input_context: Option<JsonValue>| Workflow {
+            id: Uuid::new_v4(),
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 2,
+            total_steps: 2,
+            current_step: None,
+            input_context,
+            outputs: None,
+            error: None,
+            started_at: now - chrono::Duration::minutes(started_minutes_ago),
+            completed_at: Some(now),
+            created_at: now,
+        };
+        
+        let mut alert = test_alert("fp-workflows");
+        let linked = workflow(5, None);
+        let triggered = workflow(10, Some(serde_json::json!({ "alert_id": alert.id.to_string() })));
+        let unrelated = workflow(1, Some(serde_json::json!({ "alert_id": Uuid::new_v4().to_string() })));
+        for w in [&linked, &triggered, &unrelated] {
+            store.save_workflow(w.clone()).await.unwrap();
+        }
+        alert.workflow_id = Some(linked.id);
+        store.save_alert(alert.clone()).await.unwrap();
+        
+        let ids: Vec<Uuid> = store.list_workflows_for_alert(alert.id).await.unwrap()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(ids, vec![linked.id, triggered.id]);
+        assert!(store.list_workflows_for_alert(Uuid::new_v4()).await.unwrap().is_empty());
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_list_source_events_with_workflow_status() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let now = Utc::now();
+        let workflow = Workflow {
+            id: Uuid::new_v4(),
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 1,
+            total_steps: 1,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: Some(now),
+            created_at: now,
+        };
+        store.save_workflow(workflow.clone()).await.unwrap();
+        
+        let event = |source_name: &str, minutes_ago: i64| SourceEvent {
+            id: Uuid::new_v4(),
+            source_name: source_name.to_string(),
+            source_type: SourceType::Webhook,
+            event_data: serde_json::json!({ "alertname": "HighCPU" }),
+            workflow_triggered: Some("triage".to_string()),
+            received_at: now - chrono::Duration::minutes(minutes_ago),
+        };
+        let queued = event("alertmanager", 10);
+        let dropped = event("alertmanager", 5);
+        for e in [queued.clone(), dropped.clone(), event("other", 1)] {
+            store.save_source_event(e).await.unwrap();
+        }
+        store.set_source_event_workflow(queued.id, workflow.id).await.unwrap();
+        
+        let events = store.list_source_events_with_workflow_status("alertmanager", 10).await.unwrap();
+        assert_eq!(events.iter().map(|e| e.event.id).collect::<Vec<_>>(), vec![dropped.id, queued.id]);
+        assert!(events[0].workflow_id.is_none() && events[0].workflow_status.is_none());
+        assert_eq!(events[1].workflow_id, Some(workflow.id));
+        assert_eq!(events[1].workflow_status, Some(WorkflowStatus::Succeeded));
+        assert!(events[1].workflow_completed_at.is_some());
+        assert_eq!(events[1].event.event_data["alertname"], "HighCPU");
+        
+        // The event's fields are flattened next to the workflow's
+        let json = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(json["source_name"], "alertmanager");
+        assert_eq!(json["workflow_status"], "succeeded");
+        
+        assert_eq!(store.list_source_events_with_workflow_status("alertmanager", 1).await.unwrap().len(), 1);
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_get_workflow_with_all_data() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let now = Utc::now();
+        let workflow = |name: &str| Workflow {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Succeeded,
+            steps_completed: 2,
+            total_steps: 2,
+            current_step: None,
+            input_context: None,
+            outputs: Some(serde_json::json!({ "summary": "done" })),
+            error: None,
+            started_at: now,
+            completed_at: Some(now),
+            created_at: now,
+        };
+        let reported = workflow("triage");
+        let empty = workflow("empty");
+        store.save_workflow(reported.clone()).await.unwrap();
+        store.save_workflow(empty.clone()).await.unwrap();
+        
+        for (i, name) in ["collect", "investigate"].iter().enumerate() {
+            store.save_workflow_step(WorkflowStep {
+                id: Uuid::new_v4(),
+                workflow_id: reported.id,
+                name: name.to_string(),
+                step_type: StepType::Cli,
+                status: StepStatus::Succeeded,
+                config: None,
+                started_at: Some(now),
+                completed_at: Some(now),
+                result: Some(serde_json::json!({ "stdout": name })),
+                error: None,
+                created_at: now + chrono::Duration::seconds(i as i64),
+            }).await.unwrap();
+        }
+        store.save_sink_output(SinkOutput {
+            id: Uuid::new_v4(),
+            workflow_id: reported.id,
+            sink_name: "slack".to_string(),
+            sink_type: SinkType::Slack,
+            payload: None,
+            status: SinkStatus::Sent,
+            error: None,
+            sent_at: Some(now),
+            created_at: now,
+        }).await.unwrap();
+        
+        let data = store.get_workflow_with_all_data(reported.id).await.unwrap().unwrap();
+        assert_eq!(data.workflow.id, reported.id);
+        assert_eq!(data.workflow.outputs, reported.outputs);
+        assert_eq!(data.steps.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["collect", "investigate"]);
+        assert_eq!(data.steps[1].result, Some(serde_json::json!({ "stdout": "investigate" })));
+        assert_eq!(data.sink_outputs.len(), 1);
+        
+        // Workflows without steps or outputs are still found
+        let data = store.get_workflow_with_all_data(empty.id).await.unwrap().unwrap();
+        assert_eq!(data.workflow.name, "empty");
+        assert!(data.steps.is_empty() && data.sink_outputs.is_empty());
+        
+        assert!(store.get_workflow_with_all_data(Uuid::new_v4()).await.unwrap().is_none());
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_append_alert_annotation() {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store = SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap();
+        store.init().await.unwrap();
+        
+        let mut alert = test_alert("fp-notes");
+        alert.annotations.insert("runbook".to_string(), "https://runbooks/high-cpu".to_string());
+        store.save_alert(alert.clone()).await.unwrap();
+        
+        // Concurrent notes are all kept
+        let note = |i: usize| AlertNote {
+            author: format!("sre-{}", i),
+            timestamp: Utc::now(),
+            text: format!("checked \"service {}\"", i),
+        };
+        let results = futures::future::join_all(
+            (0..5).map(|i| store.append_alert_annotation(alert.id, note(i)))
+        ).await;
+        assert!(results.iter().all(|r| r.is_ok()));
+        
+        let saved = store.get_alert(alert.id).await.unwrap().unwrap();
+        assert_eq!(saved.annotations["runbook"], "https://runbooks/high-cpu");
+        let mut authors: Vec<String> = saved.notes().into_iter().map(|n| n.author).collect();
+        authors.sort();
+        assert_eq!(authors, (0..5).map(|i| format!("sre-{}", i)).collect::<Vec<_>>());
+        assert!(saved.notes().iter().any(|n| n.text == "checked \"service 3\""));
+        
+        let missing = store.append_alert_annotation(Uuid::new_v4(), note(9)).await;
+        assert!(matches!(missing, Err(Error::NotFound(_))));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -3,9 +3,15 @@
 //! This module provides helper functions to convert Go template syntax to Tera syntax
 //! and render templates with consistent error handling.
 
+use std::collections::HashMap;
+
 use tera::{Tera, Context};
 use serde_json::Value;
-use crate::{Result, Error};
+use crate::{Result, Error, agent::AgentResult};
+
+/// Built-in report template used when a sink does not provide `reportTemplate`.
+/// Mirrors the stdout sink behaviour of printing the full context as pretty JSON.
+pub const DEFAULT_REPORT_TEMPLATE: &str = "{{ context | json_encode(pretty=true) | safe }}";
 
 /// Convert Go template syntax to Tera syntax
 /// 
@@ -39,6 +45,7 @@ pub fn render_template(template: &str, context: &Value) -> Result<String> {
     
     // Create Tera instance
     let mut tera = Tera::default();
+    register_filters(&mut tera);
     tera.add_raw_template("template", &converted_template)
         .map_err(|e| Error::Internal(format!("Failed to parse template: {}", e)))?;
     
@@ -63,6 +70,98 @@ pub fn render_template(template: &str, context: &Value) -> Result<String> {
         .map_err(|e| Error::Internal(format!("Failed to render template: {}", e)))
 }
 
+/// Render a workflow report for a sink
+///
+/// The fields of the agent result found in the context (summary, root_cause,
+/// findings, recommendations, ...) are exposed as top-level variables, alongside
+/// the top-level context fields and the raw context itself as `context`.
+/// Falls back to `DEFAULT_REPORT_TEMPLATE` when no template is given.
+pub fn render_report(template: Option<&str>, context: &Value) -> Result<String> {
+    let template = template.unwrap_or(DEFAULT_REPORT_TEMPLATE);
+
+    let mut tera = Tera::default();
+    register_filters(&mut tera);
+    tera.add_raw_template("report", template)
+        .map_err(|e| Error::Internal(format!("Failed to parse report template: {}", e)))?;
+
+    let mut tera_context = Context::new();
+
+    // Start from an empty agent result so templates can always reference its fields
+    if let Value::Object(map) = serde_json::to_value(AgentResult::default())? {
+        for (key, value) in map {
+            tera_context.insert(key, &value);
+        }
+    }
+
+    if let Value::Object(map) = context {
+        for (key, value) in map {
+            tera_context.insert(key, value);
+        }
+    }
+
+    if let Some(Value::Object(map)) = find_agent_result(context) {
+        for (key, value) in map {
+            tera_context.insert(key, &value);
+        }
+    }
+
+    tera_context.insert("context", context);
+
+    tera.render("report", &tera_context)
+        .map_err(|e| Error::Internal(format!("Failed to render report template: {}", e)))
+}
+
+/// Locate a serialized `AgentResult` within a sink context.
+///
+/// Workflow outputs are stored as strings, so string values holding JSON are
+/// parsed as well.
+pub(crate) fn find_agent_result(value: &Value) -> Option<Value> {
+    match value {
+        Value::Object(map) => {
+            if map.get("summary").is_some_and(Value::is_string)
+                && map.get("findings").is_some_and(Value::is_array)
+            {
+                return Some(value.clone());
+            }
+            map.values().find_map(find_agent_result)
+        }
+        Value::Array(items) => items.iter().find_map(find_agent_result),
+        Value::String(s) if s.trim_start().starts_with('{') => {
+            serde_json::from_str::<Value>(s).ok().and_then(|v| find_agent_result(&v))
+        }
+        _ => None,
+    }
+}
+
+/// Register the custom filters available to all templates
+fn register_filters(tera: &mut Tera) {
+    tera.register_filter("truncate_to", truncate_to);
+}
+
+/// Truncate a string to `length` characters, appending `end` (default "...")
+/// when the value was shortened.
+///
+/// Usage: `{{ summary | truncate_to(length=80) }}`
+fn truncate_to(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let length = args.get("length")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| tera::Error::msg("Filter `truncate_to` requires a `length` argument"))? as usize;
+    let end = args.get("end").and_then(Value::as_str).unwrap_or("...");
+
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+
+    if text.chars().count() <= length {
+        return Ok(Value::String(text));
+    }
+
+    let truncated: String = text.chars().take(length).collect();
+    Ok(Value::String(format!("{}{}", truncated, end)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +200,27 @@ mod tests {
         let result = render_template(template_with_default, &context).unwrap();
         assert_eq!(result, "Status: unknown");
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_render_report_with_agent_result() {
+        let context = json!({
+            "workflow": {
+                "name": "triage",
+                "outputs": {
+                    "investigate": r#"{"summary":"Pod is crash looping because of a missing config","findings":[{"category":"pod","description":"CrashLoopBackOff","severity":"high","evidence":{}}],"root_cause":"Missing ConfigMap","confidence":0.9,"actions_taken":[],"recommendations":[{"priority":1,"action":"Create the ConfigMap","rationale":"Pod needs it","risk_level":"low","requires_approval":false}],"can_auto_fix":false,"fix_command":null,"escalation_notes":null,"conversation":[]}"#
+                }
+            }
+        });
+        
+        let template = "{{ workflow.name }}: {{ summary | truncate_to(length=12) }} ({{ root_cause }}, {{ findings | length }} finding) -> {{ recommendations[0].action }}";
+        let result = render_report(Some(template), &context).unwrap();
+        assert_eq!(result, "triage: Pod is crash... (Missing ConfigMap, 1 finding) -> Create the ConfigMap");
+    }
+    
+    #[test]
+    fn test_render_report_default_template() {
+        let context = json!({ "status": "success" });
+        let result = render_report(None, &context).unwrap();
+        assert_eq!(result, serde_json::to_string_pretty(&context).unwrap());
+    }
+}
@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 use serde_json::Value;
+use uuid::Uuid;
+
+use super::diff::json_patch;
 
 #[derive(Debug, Clone)]
 pub struct WorkflowContext {
@@ -12,8 +15,19 @@ pub struct WorkflowContext {
     /// Current step being executed
     pub current_step: Option<String>,
     
+    /// Stored record of the current step, which the step may update with
+    /// its output while it runs
+    pub current_step_id: Option<Uuid>,
+    
     /// Additional metadata
     pub metadata: HashMap<String, Value>,
+    
+    /// Values of the workflow's `configVars`. These may come from Secrets,
+    /// so they're left out of `to_json`.
+    pub vars: HashMap<String, String>,
+    
+    /// LLM spend of the agent steps so far, in USD
+    pub cost_usd: f64,
 }
 
 impl WorkflowContext {
@@ -22,7 +36,10 @@ impl WorkflowContext {
             input: Value::Object(serde_json::Map::new()),
             step_outputs: HashMap::new(),
             current_step: None,
+            current_step_id: None,
             metadata: HashMap::new(),
+            vars: HashMap::new(),
+            cost_usd: 0.0,
         }
     }
 
@@ -31,7 +48,10 @@ impl WorkflowContext {
             input,
             step_outputs: HashMap::new(),
             current_step: None,
+            current_step_id: None,
             metadata: HashMap::new(),
+            vars: HashMap::new(),
+            cost_usd: 0.0,
         }
     }
 
@@ -87,6 +107,7 @@ impl WorkflowContext {
             current_step: obj.get("current_step")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
+            current_step_id: None,
             metadata: obj.get("metadata")
                 .and_then(|v| v.as_object())
                 .map(|map| {
@@ -95,7 +116,33 @@ impl WorkflowContext {
                         .collect()
                 })
                 .unwrap_or_default(),
+            vars: HashMap::new(),
+            cost_usd: 0.0,
+        }
+    }
+
+    /// Partial context rebuilt from the outputs stored for a finished
+    /// workflow. Only the step outputs survive: completed runs keep them
+    /// under `steps`, failed runs under `outputs`.
+    pub fn from_workflow_outputs(outputs: &Value) -> Self {
+        let steps = if outputs.get("failed_step").is_some() {
+            outputs.get("outputs")
+        } else {
+            outputs.get("steps")
+        };
+
+        let mut context = Self::new();
+        if let Some(steps) = steps.and_then(|v| v.as_object()) {
+            context.step_outputs = steps.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
         }
+        context
+    }
+
+    /// JSON Patch (RFC 6902) turning this context's step outputs into
+    /// `other`'s, with one top-level path per step
+    pub fn diff_with(&self, other: &WorkflowContext) -> Value {
+        let steps = |context: &WorkflowContext| serde_json::to_value(&context.step_outputs).unwrap_or_default();
+        serde_json::to_value(json_patch(&steps(self), &steps(other))).unwrap_or_default()
     }
 
     /// Get a combined view of all available data for templating
@@ -104,6 +151,7 @@ impl WorkflowContext {
             "input": self.input,
             "outputs": self.step_outputs,
             "metadata": self.metadata,
+            "vars": self.vars,
         })
     }
 } 
\ No newline at end of file
@@ -0,0 +1,217 @@
+//! Structural diff of workflow outputs
+//!
+//! Used to compare the outputs of two workflow runs, e.g. an original run and
+//! its retry. Paths are JSON pointers into the outputs document. The same
+//! comparison is also available as a JSON Patch (RFC 6902).
+
+use serde::Serialize;
+use schemars::JsonSchema;
+use serde_json::{Map, Value};
+
+/// Differences between two JSON documents
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct WorkflowDiff {
+    /// Values only present in the other document, keyed by path
+    pub added: Value,
+    /// Values only present in the base document, keyed by path
+    pub removed: Value,
+    /// Values present in both documents that differ
+    pub changed: Vec<ValueChange>,
+}
+
+#[derive(Debug, Serialize, PartialEq, JsonSchema)]
+pub struct ValueChange {
+    pub path: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+/// One JSON Patch (RFC 6902) operation
+#[derive(Debug, Serialize, PartialEq, JsonSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// Compute the structural diff from `base` to `other`
+pub fn diff_json(base: &Value, other: &Value) -> WorkflowDiff {
+    let mut added = Map::new();
+    let mut removed = Map::new();
+    let mut changed = Vec::new();
+    walk(base, other, String::new(), &mut added, &mut removed, &mut changed);
+
+    WorkflowDiff {
+        added: Value::Object(added),
+        removed: Value::Object(removed),
+        changed,
+    }
+}
+
+fn walk(
+    base: &Value,
+    other: &Value,
+    path: String,
+    added: &mut Map<String, Value>,
+    removed: &mut Map<String, Value>,
+    changed: &mut Vec<ValueChange>,
+) {
+    match (base, other) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                let child = format!("{}/{}", path, escape(key));
+                match b.get(key) {
+                    Some(other_value) => walk(value, other_value, child, added, removed, changed),
+                    None => {
+                        removed.insert(child, value.clone());
+                    }
+                }
+            }
+            for (key, value) in b {
+                if !a.contains_key(key) {
+                    added.insert(format!("{}/{}", path, escape(key)), value.clone());
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (idx, value) in a.iter().enumerate() {
+                let child = format!("{}/{}", path, idx);
+                match b.get(idx) {
+                    Some(other_value) => walk(value, other_value, child, added, removed, changed),
+                    None => {
+                        removed.insert(child, value.clone());
+                    }
+                }
+            }
+            for (idx, value) in b.iter().enumerate().skip(a.len()) {
+                added.insert(format!("{}/{}", path, idx), value.clone());
+            }
+        }
+        _ if base != other => changed.push(ValueChange {
+            path,
+            from: base.clone(),
+            to: other.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// JSON Patch that turns `base` into `other`. Operations are ordered so they
+/// can be applied in sequence: surplus array elements are removed from the
+/// end first, and new ones appended in order.
+pub fn json_patch(base: &Value, other: &Value) -> Vec<PatchOperation> {
+    let mut patch = Vec::new();
+    walk_patch(base, other, String::new(), &mut patch);
+    patch
+}
+
+fn walk_patch(base: &Value, other: &Value, path: String, patch: &mut Vec<PatchOperation>) {
+    match (base, other) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                let child = format!("{}/{}", path, escape(key));
+                match b.get(key) {
+                    Some(other_value) => walk_patch(value, other_value, child, patch),
+                    None => patch.push(PatchOperation::Remove { path: child }),
+                }
+            }
+            for (key, value) in b {
+                if !a.contains_key(key) {
+                    patch.push(PatchOperation::Add { path: format!("{}/{}", path, escape(key)), value: value.clone() });
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (idx, (value, other_value)) in a.iter().zip(b).enumerate() {
+                walk_patch(value, other_value, format!("{}/{}", path, idx), patch);
+            }
+            for idx in (b.len()..a.len()).rev() {
+                patch.push(PatchOperation::Remove { path: format!("{}/{}", path, idx) });
+            }
+            for value in b.iter().skip(a.len()) {
+                patch.push(PatchOperation::Add { path: format!("{}/-", path), value: value.clone() });
+            }
+        }
+        _ if base != other => patch.push(PatchOperation::Replace { path, value: other.clone() }),
+        _ => {}
+    }
+}
+
+/// Escape a key for use in a JSON pointer (RFC 6901)
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_objects_and_arrays() {
+        let base = json!({
+            "summary": "Pod is crash looping",
+            "findings": ["OOMKilled", "High memory"],
+            "confidence": 0.7,
+            "legacy": true
+        });
+        let other = json!({
+            "summary": "Pod is crash looping",
+            "findings": ["OOMKilled", "Memory limit too low", "Leak in cache"],
+            "confidence": 0.9,
+            "root_cause": "Memory limit"
+        });
+
+        let diff = diff_json(&base, &other);
+
+        assert_eq!(diff.added, json!({
+            "/findings/2": "Leak in cache",
+            "/root_cause": "Memory limit"
+        }));
+        assert_eq!(diff.removed, json!({ "/legacy": true }));
+        assert_eq!(diff.changed, vec![
+            ValueChange { path: "/confidence".to_string(), from: json!(0.7), to: json!(0.9) },
+            ValueChange { path: "/findings/1".to_string(), from: json!("High memory"), to: json!("Memory limit too low") },
+        ]);
+    }
+
+    #[test]
+    fn test_diff_identical_and_escaped_keys() {
+        let value = json!({ "a/b": { "c~d": 1 } });
+        let diff = diff_json(&value, &value);
+        assert_eq!(diff.added, json!({}));
+        assert!(diff.changed.is_empty());
+
+        let diff = diff_json(&value, &json!({ "a/b": { "c~d": 2 } }));
+        assert_eq!(diff.changed[0].path, "/a~1b/c~0d");
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let base = json!({
+            "check-pods": { "exit_code": 0, "restarts": [1, 2, 3] },
+            "legacy": { "stdout": "ok" }
+        });
+        let other = json!({
+            "check-pods": { "exit_code": 1, "restarts": [1] },
+            "investigate": { "root_cause": "OOMKilled" }
+        });
+
+        let patch = serde_json::to_value(json_patch(&base, &other)).unwrap();
+        assert_eq!(patch, json!([
+            { "op": "replace", "path": "/check-pods/exit_code", "value": 1 },
+            { "op": "remove", "path": "/check-pods/restarts/2" },
+            { "op": "remove", "path": "/check-pods/restarts/1" },
+            { "op": "remove", "path": "/legacy" },
+            { "op": "add", "path": "/investigate", "value": { "root_cause": "OOMKilled" } }
+        ]));
+
+        let patch = json_patch(&json!({ "list": ["a"] }), &json!({ "list": ["a", "b", "c"] }));
+        assert_eq!(patch, vec![
+            PatchOperation::Add { path: "/list/-".to_string(), value: json!("b") },
+            PatchOperation::Add { path: "/list/-".to_string(), value: json!("c") },
+        ]);
+        assert!(json_patch(&base, &base).is_empty());
+    }
+}
@@ -1,22 +1,94 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tracing::{error, info};
+use std::time::{Duration, Instant};
+use futures::stream::{FuturesUnordered, StreamExt};
+use kube::{api::ListParams, Api, Client};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc, watch, OwnedSemaphorePermit, RwLock, Semaphore};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    crd::Workflow,
-    store::Store,
-    workflow::{StepExecutor, WorkflowContext, WorkflowState},
-    Result,
+    config::Config,
+    controllers::SinkController,
+    crd::{Source, StepType, Workflow, WorkflowStep},
+    sources::webhook::route_by_severity,
+    store::{Alert, AlertStatus, StepStatus, Store},
+    workflow::{
+        apply_transform, extract_outputs, hooks, resolve_config_vars, validate_input, StepExecutor, StepGraph, StepOutcome,
+        StepResult, WorkflowContext, WorkflowHook, WorkflowHooks, WorkflowState,
+    },
+    Error, Result,
 };
 
 pub struct WorkflowEngine {
     store: Arc<dyn Store>,
     executor: Arc<StepExecutor>,
     executions: Arc<RwLock<HashMap<String, WorkflowExecution>>>,
-    queue_tx: mpsc::Sender<Workflow>,
-    queue_rx: Arc<RwLock<mpsc::Receiver<Workflow>>>,
+    queue_tx: mpsc::Sender<(Uuid, Workflow)>,
+    queue_rx: Arc<RwLock<mpsc::Receiver<(Uuid, Workflow)>>>,
+    hooks: WorkflowHooks,
+    sink_controller: Option<Arc<SinkController>>,
+    client: Option<Client>,
+    /// Operator configuration, for the `cost_per_token` prices
+    config: Option<Arc<RwLock<Config>>>,
+    /// Agent step slots per workflow ("namespace/name"), sized by
+    /// `maxParallelAgents`
+    agent_slots: RwLock<HashMap<String, (u32, Arc<Semaphore>)>>,
+    /// Last time the execution loop was seen alive, `None` until it starts
+    heartbeat: watch::Sender<Option<Instant>>,
+    completions: broadcast::Sender<WorkflowCompletionEvent>,
+}
+
+/// Outcome of a finished workflow, broadcast to completion subscribers
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowCompletionEvent {
+    pub workflow_id: Uuid,
+    /// The alert that triggered the workflow, if any
+    pub alert_id: Option<Uuid>,
+    pub status: crate::store::WorkflowStatus,
+    /// Summary of the last agent step, or the error of a failed workflow
+    pub summary: Option<String>,
+    pub can_auto_fix: bool,
+    pub fix_command: Option<String>,
+}
+
+/// Completion events buffered per subscriber; slower subscribers miss the
+/// oldest ones
+const COMPLETION_CHANNEL_CAPACITY: usize = 100;
+
+/// How often the execution loop reports that it's alive
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The engine is considered dead when the execution loop hasn't reported
+/// for this long
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Agent steps a workflow may run at once when `maxParallelAgents` is unset
+const DEFAULT_MAX_PARALLEL_AGENTS: u32 = 3;
+
+/// How often to look for received alerts that never got a workflow
+const ALERT_REQUEUE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Received alerts without a workflow are requeued once they are this old
+const ALERT_REQUEUE_AFTER_MINUTES: i64 = 5;
+
+/// Times an alert is requeued before it is left alone
+const MAX_ALERT_REQUEUES: u32 = 3;
+
+/// A held agent step slot, released when dropped
+struct AgentSlot {
+    _permit: OwnedSemaphorePermit,
+    workflow: String,
+}
+
+impl Drop for AgentSlot {
+    fn drop(&mut self) {
+        crate::metrics::WORKFLOW_PARALLEL_AGENT_SLOTS_IN_USE
+            .with_label_values(&[&self.workflow])
+            .dec();
+    }
 }
 
 struct WorkflowExecution {
@@ -24,6 +96,8 @@ struct WorkflowExecution {
     state: WorkflowState,
     context: WorkflowContext,
     outputs: serde_json::Value,
+    /// LLM tokens consumed by the agent steps so far
+    tokens_used: u32,
 }
 
 impl WorkflowEngine {
@@ -36,9 +110,42 @@ impl WorkflowEngine {
             executions: Arc::new(RwLock::new(HashMap::new())),
             queue_tx,
             queue_rx: Arc::new(RwLock::new(queue_rx)),
+            hooks: Vec::new(),
+            sink_controller: None,
+            client: None,
+            config: None,
+            agent_slots: RwLock::new(HashMap::new()),
+            heartbeat: watch::channel(None).0,
+            completions: broadcast::channel(COMPLETION_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// Register a hook notified of step and workflow lifecycle events
+    pub fn with_hook(mut self, hook: impl WorkflowHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Set the sink controller used to deliver workflows to their sinks
+    pub fn with_sink_controller(mut self, sink_controller: Arc<SinkController>) -> Self {
+        self.sink_controller = Some(sink_controller);
+        self
+    }
+
+    /// Set the Kubernetes client used to look up Sources and Workflows when
+    /// reprocessing alerts
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set the operator configuration used to price agent steps against
+    /// `maxCostUsd`
+    pub fn with_shared_config(mut self, config: Arc<RwLock<Config>>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
     pub async fn start(self: Arc<Self>) {
         info!("Starting workflow engine");
         
@@ -47,21 +154,90 @@ impl WorkflowEngine {
         tokio::spawn(async move {
             engine.execution_loop().await;
         });
+        
+        // Keep the liveness gauge current even when nothing polls /health
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+            loop {
+                ticker.tick().await;
+                if !engine.is_alive() {
+                    error!("Workflow engine missed its heartbeat for over {:?}", HEARTBEAT_TIMEOUT);
+                }
+            }
+        });
+    }
+
+    /// Whether the execution loop has reported within `HEARTBEAT_TIMEOUT`.
+    /// An engine that hasn't been started yet, as on a follower replica,
+    /// counts as alive. Updates the `workflow_engine_alive` gauge.
+    pub fn is_alive(&self) -> bool {
+        let alive = match *self.heartbeat.borrow() {
+            Some(last) => last.elapsed() < HEARTBEAT_TIMEOUT,
+            None => true,
+        };
+        crate::metrics::WORKFLOW_ENGINE_ALIVE.set(alive as i64);
+        alive
+    }
+
+    /// Receive an event for every workflow that completes from now on,
+    /// for integrations that shouldn't poll the store
+    pub async fn subscribe_to_completions(&self) -> broadcast::Receiver<WorkflowCompletionEvent> {
+        self.completions.subscribe()
+    }
+
+    /// Record the workflow's outcome in the store and broadcast it to
+    /// completion subscribers
+    async fn complete_workflow(
+        &self,
+        workflow_id: Uuid,
+        workflow: &Workflow,
+        status: crate::store::WorkflowStatus,
+        outputs: serde_json::Value,
+        error: Option<String>,
+    ) -> Result<()> {
+        let event = completion_event(workflow_id, workflow, status, &outputs, error.as_deref());
+        self.store.complete_workflow(workflow_id, status, Some(outputs), error).await?;
+        
+        // Nobody listening is fine
+        let _ = self.completions.send(event);
+        Ok(())
     }
 
     async fn execution_loop(self: Arc<Self>) {
         let mut rx = self.queue_rx.write().await;
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
         
-        while let Some(workflow) = rx.recv().await {
+        loop {
+            // Heartbeats come from this loop so a stuck loop stops sending them
+            let (execution_id, workflow) = tokio::select! {
+                _ = heartbeat.tick() => {
+                    self.heartbeat.send_replace(Some(Instant::now()));
+                    crate::metrics::WORKFLOW_ENGINE_ALIVE.set(1);
+                    continue;
+                }
+                queued = rx.recv() => match queued {
+                    Some(queued) => queued,
+                    None => break,
+                },
+            };
             let engine = self.clone();
-            let execution_id = Uuid::new_v4().to_string();
+            let execution_id = execution_id.to_string();
             
             // Create execution record with properly populated context
             let mut context = WorkflowContext::new();
             
             // Add runtime configuration to context metadata
+            context.add_metadata("workflow_id", serde_json::Value::String(execution_id.clone()));
             context.add_metadata("runtime_image", serde_json::Value::String(workflow.spec.runtime.image.clone()));
             context.add_metadata("llm_config", serde_json::to_value(&workflow.spec.runtime.llm_config).unwrap_or_default());
+            if let Some(config_map) = &workflow.spec.runtime.agent_config_map {
+                context.add_metadata("agent_config_map", serde_json::Value::String(config_map.clone()));
+                context.add_metadata(
+                    "workflow_namespace",
+                    serde_json::Value::String(workflow.metadata.namespace.clone().unwrap_or_else(|| "default".to_string())),
+                );
+            }
             
             // Add environment variables to context
             for (key, value) in &workflow.spec.runtime.environment {
@@ -96,6 +272,7 @@ impl WorkflowEngine {
                 state: WorkflowState::Pending,
                 context,
                 outputs: serde_json::json!({}),
+                tokens_used: 0,
             };
             
             {
@@ -115,6 +292,19 @@ impl WorkflowEngine {
     async fn execute_workflow(&self, execution_id: &str) -> Result<()> {
         info!("Executing workflow: {}", execution_id);
         
+        // Workflows cancelled while queued are never started
+        let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
+        if let Some(existing) = self.store.get_workflow(workflow_id).await? {
+            if existing.status == crate::store::WorkflowStatus::Cancelled {
+                info!("Workflow {} was cancelled before it started", execution_id);
+                let mut executions = self.executions.write().await;
+                if let Some(exec) = executions.get_mut(execution_id) {
+                    exec.state = WorkflowState::Cancelled;
+                }
+                return Ok(());
+            }
+        }
+        
         // Update state to Running
         {
             let mut executions = self.executions.write().await;
@@ -131,7 +321,7 @@ impl WorkflowEngine {
                     steps_completed: 0,
                     total_steps: exec.workflow.spec.steps.len() as i32,
                     current_step: None,
-                    input_context: Some(exec.context.to_json()),
+                    input_context: Some(stored_input_context(&exec.workflow, &exec.context)),
                     outputs: None,
                     error: None,
                     started_at: chrono::Utc::now(),
@@ -149,28 +339,105 @@ impl WorkflowEngine {
         };
 
         if let Some(workflow) = workflow {
+            let graph = match StepGraph::new(&workflow.spec.steps) {
+                Ok(graph) => graph,
+                Err(e) => return self.fail_step_graph(execution_id, &workflow, e).await,
+            };
+            
+            // Reject source events that don't match the declared input schema
+            if let Some(schema) = &workflow.spec.input_schema {
+                let input = {
+                    let executions = self.executions.read().await;
+                    executions.get(execution_id)
+                        .and_then(|e| e.context.input.pointer("/source/data").cloned())
+                        .unwrap_or(serde_json::Value::Null)
+                };
+                if let Err(errors) = validate_input(schema, &input) {
+                    return self.fail_input_validation(execution_id, &workflow, schema, errors).await;
+                }
+            }
+            
+            if !workflow.spec.config_vars.is_empty() {
+                let namespace = workflow.metadata.namespace.as_deref().unwrap_or("default");
+                let resolved = match &self.client {
+                    Some(client) => resolve_config_vars(client, namespace, &workflow.spec.config_vars).await,
+                    None => Err(Error::Kubernetes("Kubernetes client not available to resolve config vars".to_string())),
+                };
+                match resolved {
+                    Ok(vars) => {
+                        let mut executions = self.executions.write().await;
+                        if let Some(exec) = executions.get_mut(execution_id) {
+                            exec.context.vars = vars;
+                        }
+                    }
+                    Err(e) => return self.fail_config_vars(execution_id, &workflow, e).await,
+                }
+            }
+            
+            if workflow.spec.notify_on_start {
+                self.notify_start(execution_id, &workflow).await?;
+            }
+            
+            let steps = &workflow.spec.steps;
+            let step_ids = self.record_pending_steps(execution_id, steps).await?;
+            let mut statuses = vec![StepStatus::Pending; steps.len()];
             let mut step_outputs = HashMap::new();
+            let mut skipped = Vec::new();
+            let mut failure = None;
+            let mut completed = 0;
+            let mut running = FuturesUnordered::new();
+            let mut cancelled = false;
             
-            for (idx, step) in workflow.spec.steps.iter().enumerate() {
-                info!("Executing step {}/{}: {}", idx + 1, workflow.spec.steps.len(), step.name);
+            loop {
+                // Cancelled workflows let their running steps finish but start no more
+                cancelled = cancelled || self.executions.read().await.get(execution_id)
+                    .is_some_and(|exec| exec.state == WorkflowState::Cancelled);
+                // Neither do workflows that spent their cost budget
+                let over_budget = self.cost_budget_spent(execution_id, &workflow).await.is_some();
                 
-                // Update current step
-                {
-                    let mut executions = self.executions.write().await;
-                    if let Some(exec) = executions.get_mut(execution_id) {
-                        exec.context.set_current_step(&step.name);
+                // Start every step whose dependencies have all finished
+                for &idx in graph.order() {
+                    let ready = !cancelled
+                        && !over_budget
+                        && statuses[idx] == StepStatus::Pending
+                        && graph.dependencies(idx).iter().all(|&dep| match statuses[dep] {
+                            // Steps skipped by a failure skip their dependents
+                            // too, so these are branches not taken
+                            StepStatus::Succeeded | StepStatus::Skipped => true,
+                            StepStatus::Failed => steps[dep].continue_on_error,
+                            _ => false,
+                        });
+                    if ready {
+                        info!("Executing step {}/{}: {}", idx + 1, steps.len(), steps[idx].name);
+                        statuses[idx] = StepStatus::Running;
+                        self.store.update_workflow_step_status(step_ids[idx], StepStatus::Running).await?;
+                        let (workflow, step, step_id) = (&workflow, &steps[idx], step_ids[idx]);
+                        running.push(async move { (idx, self.execute_step(execution_id, workflow, step, step_id).await) });
                     }
                 }
-
-                // Execute step
-                let context = {
-                    let executions = self.executions.read().await;
-                    executions.get(execution_id).map(|e| e.context.clone())
-                }.unwrap_or_else(WorkflowContext::new);
-
-                match self.executor.execute_step(step, &context).await {
-                    Ok(result) => {
+                
+                let Some((idx, step_result)) = running.next().await else {
+                    break;
+                };
+                let step = &steps[idx];
+                
+                match step_result {
+                    Ok(result) if result.success => {
                         info!("Step {} completed successfully", step.name);
+                        statuses[idx] = StepStatus::Succeeded;
+                        self.store.complete_workflow_step(step_ids[idx], StepStatus::Succeeded, Some(result.output.clone()), None).await?;
+                        
+                        if matches!(step.step_type, StepType::Conditional) {
+                            let condition_met = result.output.get("condition_met").and_then(|v| v.as_bool()).unwrap_or(false);
+                            for &branch_step in graph.branch_not_taken(idx, condition_met) {
+                                if statuses[branch_step] == StepStatus::Pending {
+                                    info!("Skipping step {}, it's on the branch of {} not taken", steps[branch_step].name, step.name);
+                                    statuses[branch_step] = StepStatus::Skipped;
+                                    skipped.push(steps[branch_step].name.clone());
+                                    self.store.update_workflow_step_status(step_ids[branch_step], StepStatus::Skipped).await?;
+                                }
+                            }
+                        }
                         
                         // Store step output
                         step_outputs.insert(step.name.clone(), result.output.clone());
@@ -181,48 +448,149 @@ impl WorkflowEngine {
                             exec.context.add_step_output(&step.name, result.output);
                         }
                     }
-                    Err(e) => {
-                        error!("Step {} failed: {}", step.name, e);
+                    // Steps that ran but reported failure fail like steps that errored
+                    failed => {
+                        let (output, e) = match failed {
+                            Ok(result) => {
+                                let e = unsuccessful_step_error(step, &result);
+                                (result.output, e)
+                            }
+                            Err(e) => (serde_json::json!({ "error": e.to_string() }), e),
+                        };
                         
-                        // Update state to Failed
-                        let mut executions = self.executions.write().await;
-                        if let Some(exec) = executions.get_mut(execution_id) {
-                            exec.state = WorkflowState::Failed;
-                            exec.outputs = serde_json::json!({
-                                "error": e.to_string(),
-                                "failed_step": step.name,
-                                "outputs": step_outputs,
-                            });
+                        // The cost budget is a hard cap, even for steps allowed to fail
+                        let over_budget = self.cost_budget_spent(execution_id, &workflow).await.is_some();
+                        self.store.complete_workflow_step(step_ids[idx], StepStatus::Failed, Some(output.clone()), Some(e.to_string())).await?;
+                        if step.continue_on_error && !over_budget {
+                            warn!("Step {} failed, continuing: {}", step.name, e);
+                            statuses[idx] = StepStatus::Failed;
+                            step_outputs.insert(step.name.clone(), output.clone());
+                            
+                            let mut executions = self.executions.write().await;
+                            if let Some(exec) = executions.get_mut(execution_id) {
+                                exec.context.add_step_output(&step.name, output);
+                            }
+                        } else {
+                            error!("Step {} failed: {}", step.name, e);
+                            statuses[idx] = StepStatus::Failed;
+                            
+                            // Nothing that needs this step can run anymore
+                            for dependent in graph.transitive_dependents(idx) {
+                                if statuses[dependent] == StepStatus::Pending {
+                                    info!("Skipping step {}, it depends on failed step {}", steps[dependent].name, step.name);
+                                    statuses[dependent] = StepStatus::Skipped;
+                                    skipped.push(steps[dependent].name.clone());
+                                    self.store.update_workflow_step_status(step_ids[dependent], StepStatus::Skipped).await?;
+                                }
+                            }
+                            
+                            failure.get_or_insert((step.name.clone(), e));
+                            continue;
                         }
-                        
-                        // Update database
-                        let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
-                        self.store.complete_workflow(
-                            workflow_id,
-                            crate::store::WorkflowStatus::Failed,
-                            Some(serde_json::json!({
-                                "error": e.to_string(),
-                                "failed_step": step.name,
-                                "outputs": step_outputs,
-                            })),
-                            Some(e.to_string()),
-                        ).await?;
-                        
-                        return Err(e);
                     }
                 }
                 
                 // Update database progress
+                completed += 1;
                 let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
                 self.store.update_workflow_progress(
                     workflow_id,
-                    idx as i32 + 1,
+                    completed,
                     Some(step.name.clone()),
                 ).await?;
             }
             
-            // All steps completed successfully
-            let outputs = serde_json::json!({ "steps": step_outputs });
+            if cancelled {
+                info!("Workflow {} was cancelled", execution_id);
+                let outputs = serde_json::json!({
+                    "cancelled": true,
+                    "outputs": step_outputs,
+                });
+                {
+                    let mut executions = self.executions.write().await;
+                    if let Some(exec) = executions.get_mut(execution_id) {
+                        exec.outputs = outputs.clone();
+                    }
+                }
+                hooks::notify_workflow_complete(&self.hooks, &workflow, WorkflowState::Cancelled, &outputs).await;
+                
+                // The store already records the workflow as cancelled
+                return Ok(());
+            }
+            
+            if let Some((failed_step, e)) = failure {
+                // Steps never started because the cost budget ran out
+                for (idx, status) in statuses.iter().enumerate() {
+                    if *status == StepStatus::Pending {
+                        skipped.push(steps[idx].name.clone());
+                        self.store.update_workflow_step_status(step_ids[idx], StepStatus::Skipped).await?;
+                    }
+                }
+                let message = match self.cost_budget_spent(execution_id, &workflow).await {
+                    Some(spent) => cost_budget_message(spent),
+                    None => e.to_string(),
+                };
+                let outputs = serde_json::json!({
+                    "error": message,
+                    "failed_step": failed_step,
+                    "outputs": step_outputs,
+                    "skipped": skipped,
+                });
+                
+                // Update state to Failed
+                {
+                    let mut executions = self.executions.write().await;
+                    if let Some(exec) = executions.get_mut(execution_id) {
+                        exec.state = WorkflowState::Failed;
+                        exec.outputs = outputs.clone();
+                    }
+                }
+                hooks::notify_workflow_complete(&self.hooks, &workflow, WorkflowState::Failed, &outputs).await;
+                
+                // Update database
+                let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
+                self.complete_workflow(
+                    workflow_id,
+                    &workflow,
+                    crate::store::WorkflowStatus::Failed,
+                    outputs,
+                    Some(message),
+                ).await?;
+                
+                return Err(e);
+            }
+            
+            // All steps completed successfully, resolve declared outputs
+            let extracted = extract_outputs(&workflow.spec.outputs, &step_outputs);
+            let outputs = serde_json::json!({
+                "steps": step_outputs,
+                "outputs": extracted.values,
+            });
+            
+            if !extracted.missing_required.is_empty() {
+                let message = format!("Missing required outputs: {}", extracted.missing_required.join(", "));
+                error!("Workflow {} failed: {}", execution_id, message);
+                
+                {
+                    let mut executions = self.executions.write().await;
+                    if let Some(exec) = executions.get_mut(execution_id) {
+                        exec.state = WorkflowState::Failed;
+                        exec.outputs = outputs.clone();
+                    }
+                }
+                hooks::notify_workflow_complete(&self.hooks, &workflow, WorkflowState::Failed, &outputs).await;
+                
+                let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
+                self.complete_workflow(
+                    workflow_id,
+                    &workflow,
+                    crate::store::WorkflowStatus::Failed,
+                    outputs,
+                    Some(message.clone()),
+                ).await?;
+                
+                return Err(Error::Validation(message));
+            }
             
             {
                 let mut executions = self.executions.write().await;
@@ -231,26 +599,588 @@ impl WorkflowEngine {
                     exec.outputs = outputs.clone();
                 }
             }
+            hooks::notify_workflow_complete(&self.hooks, &workflow, WorkflowState::Succeeded, &outputs).await;
             
             // Update database
             let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
-            self.store.complete_workflow(
+            self.complete_workflow(
                 workflow_id,
+                &workflow,
                 crate::store::WorkflowStatus::Succeeded,
-                Some(outputs),
+                outputs.clone(),
                 None,
             ).await?;
+            self.deliver_completion(workflow_id, &workflow, &outputs).await;
         }
 
         Ok(())
     }
 
-    pub async fn queue_workflow(&self, workflow: Workflow) -> Result<()> {
-        self.queue_tx.send(workflow).await
-            .map_err(|e| crate::Error::Internal(format!("Failed to queue workflow: {}", e)))?;
+    /// Run one step of the workflow against a snapshot of its context,
+    /// counting the tokens it used and their cost against the workflow's
+    /// budgets. `step_id` is the step's stored record.
+    async fn execute_step(&self, execution_id: &str, workflow: &Workflow, step: &WorkflowStep, step_id: Uuid) -> Result<StepResult> {
+        // Update current step
+        let mut context = {
+            let mut executions = self.executions.write().await;
+            executions.get_mut(execution_id).map(|exec| {
+                exec.context.set_current_step(&step.name);
+                exec.context.clone()
+            })
+        }.unwrap_or_else(WorkflowContext::new);
+        context.current_step_id = Some(step_id);
+
+        // Agent steps wait for one of the workflow's LLM slots
+        let slot = match step.step_type {
+            StepType::Agent => Some(self.acquire_agent_slot(workflow).await?),
+            StepType::Cli | StepType::Conditional | StepType::Http => None,
+        };
+
+        hooks::notify_step_start(&self.hooks, step, &context).await;
+        let started = Instant::now();
+        let mut step_result = self.executor.execute_step(step, &context).await;
+        drop(slot);
+        if let Ok(result) = &step_result {
+            let recorded = match self.record_token_usage(execution_id, workflow.spec.token_budget, &result.output).await {
+                Ok(()) => self.record_cost(execution_id, workflow, &result.output).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = recorded {
+                step_result = Err(e);
+            }
+        }
+        // Token usage is read from the untransformed output
+        if let (Ok(result), Some(expression)) = (&mut step_result, &step.output_transform) {
+            if result.success {
+                match apply_transform(expression, &result.output) {
+                    Ok(output) => result.output = output,
+                    Err(e) => step_result = Err(e),
+                }
+            }
+        }
+        let outcome = StepOutcome {
+            duration: started.elapsed(),
+            error: match &step_result {
+                Ok(result) if !result.success => Some(unsuccessful_step_error(step, result).to_string()),
+                Ok(_) => None,
+                Err(e) => Some(e.to_string()),
+            },
+        };
+        hooks::notify_step_end(&self.hooks, step, &outcome, &context).await;
+
+        step_result
+    }
+
+    /// Wait for a free agent slot of the workflow. Executions of the same
+    /// workflow share its `maxParallelAgents` slots.
+    async fn acquire_agent_slot(&self, workflow: &Workflow) -> Result<AgentSlot> {
+        let key = format!(
+            "{}/{}",
+            workflow.metadata.namespace.as_deref().unwrap_or("default"),
+            workflow.metadata.name.as_deref().unwrap_or("unnamed-workflow"),
+        );
+        let limit = workflow.spec.max_parallel_agents.unwrap_or(DEFAULT_MAX_PARALLEL_AGENTS).max(1);
+
+        let semaphore = {
+            let mut slots = self.agent_slots.write().await;
+            match slots.get(&key) {
+                Some((size, semaphore)) if *size == limit => semaphore.clone(),
+                // First agent step of the workflow, or its limit changed
+                _ => {
+                    let semaphore = Arc::new(Semaphore::new(limit as usize));
+                    slots.insert(key.clone(), (limit, semaphore.clone()));
+                    semaphore
+                }
+            }
+        };
+
+        if semaphore.available_permits() == 0 {
+            debug!("Waiting for an agent slot of workflow {} ({} in use)", key, limit);
+        }
+        let permit = semaphore.acquire_owned().await
+            .map_err(|e| Error::Internal(format!("Agent slots of workflow {} closed: {}", key, e)))?;
+        crate::metrics::WORKFLOW_PARALLEL_AGENT_SLOTS_IN_USE
+            .with_label_values(&[&key])
+            .inc();
+
+        Ok(AgentSlot { _permit: permit, workflow: key })
+    }
+
+    /// Add the tokens reported in an agent step's output to the execution's
+    /// counter, failing once the workflow's token budget is exceeded
+    async fn record_token_usage(
+        &self,
+        execution_id: &str,
+        token_budget: Option<u32>,
+        output: &serde_json::Value,
+    ) -> Result<()> {
+        let Some(tokens) = output.get("tokens_used").and_then(|v| v.as_u64()) else {
+            return Ok(());
+        };
+        
+        let mut executions = self.executions.write().await;
+        let Some(exec) = executions.get_mut(execution_id) else {
+            return Ok(());
+        };
+        exec.tokens_used = exec.tokens_used.saturating_add(u32::try_from(tokens).unwrap_or(u32::MAX));
+        debug!("Workflow {} has used {} tokens", execution_id, exec.tokens_used);
+        
+        check_token_budget(exec.tokens_used, token_budget)
+    }
+
+    /// Price the tokens reported in an agent step's output with the
+    /// configured `cost_per_token` for the workflow's model and add them to
+    /// the execution's spend, failing once `maxCostUsd` is reached
+    async fn record_cost(&self, execution_id: &str, workflow: &Workflow, output: &serde_json::Value) -> Result<()> {
+        let tokens = |key: &str| output.get(key).and_then(|v| v.as_u64());
+        let Some(total) = tokens("tokens_used") else {
+            return Ok(());
+        };
+        
+        let llm_config = &workflow.spec.runtime.llm_config;
+        let price = match &self.config {
+            Some(config) => config.read().await.token_cost(&llm_config.provider, &llm_config.model),
+            None => None,
+        };
+        let Some(price) = price else {
+            if workflow.spec.max_cost_usd.is_some() {
+                warn!(
+                    "Workflow {} has maxCostUsd but no cost_per_token is configured for {}/{}",
+                    execution_id, llm_config.provider, llm_config.model
+                );
+            }
+            return Ok(());
+        };
+        
+        // Usage without an input/output split is priced as output
+        let cost = match (tokens("input_tokens"), tokens("output_tokens")) {
+            (Some(input), Some(output)) => input as f64 * price.input + output as f64 * price.output,
+            _ => total as f64 * price.output,
+        };
+        crate::metrics::WORKFLOW_COST_USD_TOTAL.inc_by(cost);
+        
+        let mut executions = self.executions.write().await;
+        let Some(exec) = executions.get_mut(execution_id) else {
+            return Ok(());
+        };
+        exec.context.cost_usd += cost;
+        debug!("Workflow {} has spent ${:.4}", execution_id, exec.context.cost_usd);
+        
+        match workflow.spec.max_cost_usd {
+            Some(cap) if exec.context.cost_usd >= cap => {
+                Err(Error::Execution(cost_budget_message(exec.context.cost_usd)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The execution's spend if it has reached the workflow's `maxCostUsd`
+    async fn cost_budget_spent(&self, execution_id: &str, workflow: &Workflow) -> Option<f64> {
+        let cap = workflow.spec.max_cost_usd?;
+        let executions = self.executions.read().await;
+        let spent = executions.get(execution_id)?.context.cost_usd;
+        (spent >= cap).then_some(spent)
+    }
+
+    /// Send the preliminary "started" message to every sink of the workflow,
+    /// which records a `SinkOutput` for each
+    async fn notify_start(&self, execution_id: &str, workflow: &Workflow) -> Result<()> {
+        let Some(sink_controller) = &self.sink_controller else {
+            warn!("Workflow {} requested notifyOnStart but no sink controller is configured", execution_id);
+            return Ok(());
+        };
+        
+        let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
+        let namespace = workflow.metadata.namespace.as_deref().unwrap_or("default");
+        let payload = start_payload(workflow);
+        
+        for sink_name in &workflow.spec.sinks {
+            if let Err(e) = sink_controller.deliver(workflow_id, sink_name, namespace, &payload).await {
+                warn!(
+                    "Failed to send start notification to sink '{}' for workflow {}: {}",
+                    sink_name, execution_id, e
+                );
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Send a succeeded workflow's outputs to every sink of the workflow,
+    /// along with the alert that triggered it
+    async fn deliver_completion(&self, workflow_id: Uuid, workflow: &Workflow, outputs: &serde_json::Value) {
+        let Some(sink_controller) = &self.sink_controller else {
+            return;
+        };
+        
+        let alert_id = workflow.metadata.annotations.as_ref()
+            .and_then(|a| a.get("alert.id"))
+            .and_then(|id| id.parse().ok());
+        let alert = match alert_id {
+            Some(alert_id) => self.store.get_alert(alert_id).await.unwrap_or_else(|e| {
+                warn!("Failed to load alert {} for the sinks of workflow {}: {}", alert_id, workflow_id, e);
+                None
+            }),
+            None => None,
+        };
+        let namespace = workflow.metadata.namespace.as_deref().unwrap_or("default");
+        let payload = completion_payload(workflow_id, workflow, outputs, alert.as_ref());
+        
+        for sink_name in &workflow.spec.sinks {
+            if let Err(e) = sink_controller.deliver(workflow_id, sink_name, namespace, &payload).await {
+                warn!("Failed to deliver workflow {} to sink '{}': {}", workflow_id, sink_name, e);
+            }
+        }
+    }
+
+    /// Fail a workflow whose input did not match its schema, recording the
+    /// validation errors as a step so they show up in the timeline
+    async fn fail_input_validation(
+        &self,
+        execution_id: &str,
+        workflow: &Workflow,
+        schema: &serde_json::Value,
+        errors: Vec<String>,
+    ) -> Result<()> {
+        let message = format!("Input validation failed: {}", errors.join("; "));
+        self.fail_setup_step(
+            execution_id,
+            workflow,
+            "validate-input",
+            serde_json::json!({ "inputSchema": schema }),
+            serde_json::json!({ "valid": false, "errors": errors }),
+            &message,
+        ).await?;
+        
+        Err(Error::Validation(message))
+    }
+
+    /// Fail a workflow whose config vars couldn't be resolved
+    async fn fail_config_vars(&self, execution_id: &str, workflow: &Workflow, error: Error) -> Result<()> {
+        let names: Vec<&str> = workflow.spec.config_vars.iter().map(|v| v.name.as_str()).collect();
+        self.fail_setup_step(
+            execution_id,
+            workflow,
+            "resolve-config-vars",
+            serde_json::json!({ "configVars": names }),
+            serde_json::json!({ "resolved": false }),
+            &error.to_string(),
+        ).await?;
+        
+        Err(error)
+    }
+
+    /// Fail a workflow whose steps' `dependsOn` can't be resolved
+    async fn fail_step_graph(&self, execution_id: &str, workflow: &Workflow, error: Error) -> Result<()> {
+        let depends_on: HashMap<&str, &[String]> = workflow.spec.steps.iter()
+            .map(|step| (step.name.as_str(), step.depends_on.as_slice()))
+            .collect();
+        self.fail_setup_step(
+            execution_id,
+            workflow,
+            "resolve-step-dependencies",
+            serde_json::json!({ "dependsOn": depends_on }),
+            serde_json::json!({ "resolved": false }),
+            &error.to_string(),
+        ).await?;
+        
+        Err(error)
+    }
+
+    /// Record the workflow's steps as pending, returning their ids in the
+    /// order of the steps
+    async fn record_pending_steps(&self, execution_id: &str, steps: &[WorkflowStep]) -> Result<Vec<Uuid>> {
+        let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
+        let mut ids = Vec::with_capacity(steps.len());
+        for step in steps {
+            let id = Uuid::new_v4();
+            self.store.save_workflow_step(crate::store::WorkflowStep {
+                id,
+                workflow_id,
+                name: step.name.clone(),
+                step_type: match step.step_type {
+                    StepType::Cli => crate::store::StepType::Cli,
+                    StepType::Agent => crate::store::StepType::Agent,
+                    StepType::Conditional => crate::store::StepType::Conditional,
+                    StepType::Http => crate::store::StepType::Http,
+                },
+                status: StepStatus::Pending,
+                config: Some(serde_json::to_value(step)?),
+                started_at: None,
+                completed_at: None,
+                result: None,
+                error: None,
+                created_at: chrono::Utc::now(),
+            }).await?;
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /// Record a failed step that ran before the workflow's own steps and mark
+    /// the workflow as failed
+    async fn fail_setup_step(
+        &self,
+        execution_id: &str,
+        workflow: &Workflow,
+        step_name: &str,
+        config: serde_json::Value,
+        result: serde_json::Value,
+        message: &str,
+    ) -> Result<()> {
+        error!("Workflow {} failed: {}", execution_id, message);
+        
+        let workflow_id = Uuid::parse_str(execution_id).unwrap_or_else(|_| Uuid::new_v4());
+        let now = chrono::Utc::now();
+        self.store.save_workflow_step(crate::store::WorkflowStep {
+            id: Uuid::new_v4(),
+            workflow_id,
+            name: step_name.to_string(),
+            step_type: crate::store::StepType::Conditional,
+            status: crate::store::StepStatus::Failed,
+            config: Some(config),
+            started_at: Some(now),
+            completed_at: Some(now),
+            result: Some(result),
+            error: Some(message.to_string()),
+            created_at: now,
+        }).await?;
+        
+        let outputs = serde_json::json!({
+            "error": message,
+            "failed_step": step_name,
+        });
+        {
+            let mut executions = self.executions.write().await;
+            if let Some(exec) = executions.get_mut(execution_id) {
+                exec.state = WorkflowState::Failed;
+                exec.outputs = outputs.clone();
+            }
+        }
+        hooks::notify_workflow_complete(&self.hooks, workflow, WorkflowState::Failed, &outputs).await;
+        
+        self.complete_workflow(
+            workflow_id,
+            workflow,
+            crate::store::WorkflowStatus::Failed,
+            outputs,
+            Some(message.to_string()),
+        ).await?;
+        
         Ok(())
     }
 
+    /// Queue a workflow for execution, returning the id of its execution
+    pub async fn queue_workflow(&self, workflow: Workflow) -> Result<Uuid> {
+        let execution_id = Uuid::new_v4();
+        self.queue_tx.send((execution_id, workflow)).await
+            .map_err(|e| crate::Error::Internal(format!("Failed to queue workflow: {}", e)))?;
+        Ok(execution_id)
+    }
+
+    /// Queue a workflow triggered by `alert`, passing the alert to the
+    /// workflow context through annotations
+    pub async fn queue_alert_workflow(&self, mut workflow: Workflow, alert: &Alert) -> Result<Uuid> {
+        annotate_with_alert(&mut workflow, alert);
+        self.queue_workflow(workflow).await
+    }
+
+    /// Re-trigger the workflow for an alert left in `Received` or `Triaging`
+    /// without an active workflow, e.g. after an operator restart. Returns the
+    /// id of the new workflow, which replaces the alert's `workflow_id`.
+    pub async fn reprocess_alert(&self, alert_id: Uuid) -> Result<Uuid> {
+        let mut alert = self.store.get_alert(alert_id).await?
+            .ok_or_else(|| Error::NotFound(format!("Alert {} not found", alert_id)))?;
+        
+        if !matches!(alert.status, AlertStatus::Received | AlertStatus::Triaging) {
+            return Err(Error::Validation(format!(
+                "Alert {} is {:?}; only Received or Triaging alerts can be reprocessed",
+                alert_id, alert.status
+            )));
+        }
+        
+        if let Some(workflow_id) = alert.workflow_id {
+            if let Some(existing) = self.store.get_workflow(workflow_id).await? {
+                use crate::store::WorkflowStatus;
+                if matches!(existing.status, WorkflowStatus::Pending | WorkflowStatus::Running) {
+                    return Err(Error::Validation(format!(
+                        "Alert {} already has {} workflow {}",
+                        alert_id, existing.status, workflow_id
+                    )));
+                }
+            }
+        }
+        
+        let source_id = alert.source_id
+            .ok_or_else(|| Error::Validation(format!("Alert {} has no source", alert_id)))?;
+        let namespace = alert.source_namespace()
+            .ok_or_else(|| Error::Validation(format!("Alert {} has no recorded source namespace", alert_id)))?
+            .to_string();
+        let client = self.client.clone()
+            .ok_or_else(|| Error::Kubernetes("Kubernetes client not available".to_string()))?;
+        
+        let sources: Api<Source> = Api::namespaced(client.clone(), &namespace);
+        let source = sources.list(&ListParams::default()).await
+            .map_err(|e| Error::Kubernetes(format!("Failed to list sources in namespace {}: {}", namespace, e)))?
+            .items
+            .into_iter()
+            .find(|s| s.metadata.uid.as_deref() == Some(source_id.to_string().as_str()))
+            .ok_or_else(|| Error::NotFound(format!("Source {} not found in namespace {}", source_id, namespace)))?;
+        
+        // Pick the workflow the way the webhook did, falling back to the
+        // default when the routed workflow is gone
+        let routing = source.spec.severity_routing.clone().unwrap_or_default();
+        let default_workflow = &source.spec.trigger_workflow;
+        let workflows: Api<Workflow> = Api::namespaced(client, &namespace);
+        let mut workflow_name = route_by_severity(&routing, alert.severity, default_workflow);
+        let workflow = match workflows.get(workflow_name).await {
+            Err(e) if workflow_name != default_workflow => {
+                warn!("Routed workflow {} unavailable ({}), using {}", workflow_name, e, default_workflow);
+                workflow_name = default_workflow;
+                workflows.get(workflow_name).await
+            }
+            result => result,
+        }.map_err(|e| Error::Kubernetes(format!("Failed to get workflow {}: {}", workflow_name, e)))?;
+        let workflow_name = workflow_name.to_string();
+        let namespace = namespace.as_str();
+        
+        info!("Reprocessing alert {} with workflow {}/{}", alert_id, namespace, workflow_name);
+        
+        // Record the workflow before it is picked up so the alert never
+        // points at a missing row
+        let workflow_id = Uuid::new_v4();
+        let now = chrono::Utc::now();
+        self.store.save_workflow(crate::store::Workflow {
+            id: workflow_id,
+            name: workflow_name.clone(),
+            namespace: namespace.to_string(),
+            trigger_source: source.metadata.name.clone(),
+            status: crate::store::WorkflowStatus::Pending,
+            steps_completed: 0,
+            total_steps: workflow.spec.steps.len() as i32,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: None,
+            created_at: now,
+        }).await?;
+        
+        alert.workflow_id = Some(workflow_id);
+        alert.status = AlertStatus::Triaging;
+        alert.triage_started_at = Some(now);
+        alert.updated_at = now;
+        self.store.save_alert(alert.clone()).await?;
+        
+        let mut workflow = workflow;
+        annotate_with_alert(&mut workflow, &alert);
+        self.queue_tx.send((workflow_id, workflow)).await
+            .map_err(|e| Error::Internal(format!("Failed to queue workflow: {}", e)))?;
+        
+        Ok(workflow_id)
+    }
+
+    /// Send the outputs of the succeeded workflow of `alert` to its sinks
+    /// again, carrying the alert as now stored, e.g. after a re-fire added
+    /// annotations to it. Returns whether anything was sent; a workflow that
+    /// hasn't finished picks up the alert when it completes.
+    pub async fn renotify_alert(&self, alert: &Alert) -> Result<bool> {
+        let Some(workflow_id) = alert.workflow_id else {
+            return Ok(false);
+        };
+        let stored = self.store.get_workflow(workflow_id).await?
+            .ok_or_else(|| Error::NotFound(format!("Workflow {} not found", workflow_id)))?;
+        if stored.status != crate::store::WorkflowStatus::Succeeded {
+            debug!("Not renotifying alert {}: workflow {} is {}", alert.id, workflow_id, stored.status);
+            return Ok(false);
+        }
+        
+        let client = self.client.clone()
+            .ok_or_else(|| Error::Kubernetes("Kubernetes client not available".to_string()))?;
+        let workflows: Api<Workflow> = Api::namespaced(client, &stored.namespace);
+        let mut workflow = workflows.get(&stored.name).await
+            .map_err(|e| Error::Kubernetes(format!("Failed to get workflow {}: {}", stored.name, e)))?;
+        annotate_with_alert(&mut workflow, alert);
+        
+        info!("Renotifying the sinks of workflow {} for updated alert {}", workflow_id, alert.id);
+        let outputs = stored.outputs.unwrap_or_else(|| serde_json::json!({}));
+        self.deliver_completion(workflow_id, &workflow, &outputs).await;
+        Ok(true)
+    }
+
+    /// Periodically requeue received alerts whose workflow was never started
+    pub async fn run_alert_requeue(self: Arc<Self>) -> Result<()> {
+        info!("Starting alert requeue task");
+        
+        let mut interval = tokio::time::interval(ALERT_REQUEUE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.requeue_dropped_alerts().await {
+                Ok(0) => {}
+                Ok(count) => info!("Requeued {} alert(s) without a workflow", count),
+                Err(e) => warn!("Failed to requeue alerts: {}", e),
+            }
+        }
+    }
+
+    /// Reprocess every received alert that has gone without a workflow for
+    /// `ALERT_REQUEUE_AFTER_MINUTES`, up to `MAX_ALERT_REQUEUES` times per
+    /// alert. Returns how many alerts were requeued.
+    pub async fn requeue_dropped_alerts(&self) -> Result<usize> {
+        let alerts = self.store.list_alerts_without_workflow(ALERT_REQUEUE_AFTER_MINUTES).await?;
+        debug!("Found {} received alert(s) without a workflow", alerts.len());
+        
+        let mut requeued = 0;
+        for alert in alerts {
+            let attempts = alert.requeue_count();
+            if attempts >= MAX_ALERT_REQUEUES {
+                debug!("Alert {} was already requeued {} times, leaving it", alert.id, attempts);
+                continue;
+            }
+            
+            // Count the attempt first so alerts that fail to reprocess are
+            // still capped
+            self.store.set_alert_annotation(
+                alert.id,
+                Alert::REQUEUE_COUNT_ANNOTATION,
+                &(attempts + 1).to_string(),
+            ).await?;
+            
+            match self.reprocess_alert(alert.id).await {
+                Ok(workflow_id) => {
+                    info!(
+                        "Requeued alert {} ({}) as workflow {}, attempt {}/{}",
+                        alert.id, alert.alert_name, workflow_id, attempts + 1, MAX_ALERT_REQUEUES
+                    );
+                    crate::metrics::ALERT_REQUEUED_TOTAL.inc();
+                    requeued += 1;
+                }
+                Err(e) => warn!("Failed to requeue alert {}: {}", alert.id, e),
+            }
+        }
+        
+        Ok(requeued)
+    }
+
+    /// Stop the pending or running executions among `ids` from starting any
+    /// more steps, returning how many were cancelled. The caller records the
+    /// cancellation in the store.
+    pub async fn cancel_executions(&self, ids: &[Uuid]) -> usize {
+        let mut executions = self.executions.write().await;
+        let mut cancelled = 0;
+        for id in ids {
+            if let Some(exec) = executions.get_mut(&id.to_string()) {
+                if matches!(exec.state, WorkflowState::Pending | WorkflowState::Running) {
+                    info!("Cancelling workflow execution {}", id);
+                    exec.state = WorkflowState::Cancelled;
+                    cancelled += 1;
+                }
+            }
+        }
+        cancelled
+    }
+
     pub async fn get_execution_status(&self, execution_id: &str) -> Result<Option<String>> {
         let executions = self.executions.read().await;
         Ok(executions.get(execution_id).map(|e| e.state.to_string()))
@@ -262,6 +1192,8 @@ impl WorkflowEngine {
             Ok(serde_json::json!({
                 "current_step": exec.context.current_step(),
                 "state": exec.state.to_string(),
+                "tokens_used": exec.tokens_used,
+                "cost_usd": exec.context.cost_usd,
             }))
         } else {
             Ok(serde_json::json!({}))
@@ -272,4 +1204,956 @@ impl WorkflowEngine {
         let executions = self.executions.read().await;
         Ok(executions.get(execution_id).map(|e| e.outputs.clone()))
     }
-} 
\ No newline at end of file
+} 
+fn check_token_budget(tokens_used: u32, token_budget: Option<u32>) -> Result<()> {
+    match token_budget {
+        Some(budget) if tokens_used > budget => Err(Error::Validation(format!(
+            "Token budget exceeded: used {} of {} tokens",
+            tokens_used, budget
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn cost_budget_message(spent: f64) -> String {
+    format!("cost budget exceeded (${:.2} spent)", spent)
+}
+
+/// The error of a step that ran but reported failure, e.g. a CLI command
+/// exiting non-zero or an agent timing out
+fn unsuccessful_step_error(step: &WorkflowStep, result: &StepResult) -> Error {
+    let reason = result.output.get("error").and_then(|e| e.as_str()).unwrap_or("step reported failure");
+    Error::Execution(format!("Step {} failed: {}", step.name, reason))
+}
+
+/// Add the alert to the workflow annotations, where the execution loop reads
+/// it into the workflow context
+fn annotate_with_alert(workflow: &mut Workflow, alert: &Alert) {
+    let annotations = workflow.metadata.annotations.get_or_insert_with(Default::default);
+    
+    // Add minimal alert info for backward compatibility
+    annotations.insert("alert.id".to_string(), alert.id.to_string());
+    annotations.insert("alert.name".to_string(), alert.alert_name.clone());
+    annotations.insert("alert.severity".to_string(), format!("{:?}", alert.severity));
+    
+    // Add the full alert data structure that templates expect
+    // This creates the structure: source.data.alerts[0]
+    let alert_data = serde_json::json!({
+        "alerts": [{
+            "labels": alert.labels.clone(),
+            "annotations": alert.annotations.clone(),
+            "status": "firing",  // Default to firing for compatibility
+            "startsAt": alert.starts_at,
+            "endsAt": alert.ends_at,
+        }]
+    });
+    
+    // Store the alert data as JSON string in annotation
+    annotations.insert(
+        "source.data".to_string(),
+        serde_json::to_string(&alert_data).unwrap_or_default(),
+    );
+}
+
+/// The workflow context as stored with the workflow, tagged with the id of
+/// the alert that triggered it so the alert's workflows can be looked up.
+/// The step definitions are kept too, so the run's graph still matches what
+/// ran after the Workflow resource is edited or deleted.
+fn stored_input_context(workflow: &Workflow, context: &WorkflowContext) -> serde_json::Value {
+    let mut input_context = context.to_json();
+    input_context["steps"] = serde_json::to_value(&workflow.spec.steps).unwrap_or_default();
+    if let Some(alert_id) = workflow.metadata.annotations.as_ref().and_then(|a| a.get("alert.id")) {
+        input_context["alert_id"] = serde_json::Value::String(alert_id.clone());
+    }
+    input_context
+}
+
+/// The completion event for a workflow that finished with `outputs`. The
+/// summary and fix come from the last agent step that produced them.
+fn completion_event(
+    workflow_id: Uuid,
+    workflow: &Workflow,
+    status: crate::store::WorkflowStatus,
+    outputs: &serde_json::Value,
+    error: Option<&str>,
+) -> WorkflowCompletionEvent {
+    let step_outputs = WorkflowContext::from_workflow_outputs(outputs).step_outputs;
+    let agent_output = workflow.spec.steps.iter().rev()
+        .filter_map(|step| step_outputs.get(&step.name))
+        .find(|output| output.get("summary").is_some_and(|s| s.is_string()));
+    let field = |name: &str| agent_output.and_then(|output| output.get(name));
+    
+    WorkflowCompletionEvent {
+        workflow_id,
+        alert_id: workflow.metadata.annotations.as_ref()
+            .and_then(|a| a.get("alert.id"))
+            .and_then(|id| id.parse().ok()),
+        status,
+        summary: field("summary").and_then(|s| s.as_str()).or(error).map(str::to_string),
+        can_auto_fix: field("can_auto_fix").and_then(|v| v.as_bool()).unwrap_or(false),
+        fix_command: field("fix_command").and_then(|v| v.as_str()).map(str::to_string),
+    }
+}
+
+/// Build the preliminary message sent to sinks when a workflow starts
+fn start_payload(workflow: &Workflow) -> serde_json::Value {
+    let goal = workflow.spec.steps.iter()
+        .find_map(|step| step.goal.clone())
+        .unwrap_or_default();
+    
+    serde_json::json!({
+        "type": "start",
+        "status": "started",
+        "workflow": {
+            "name": workflow.metadata.name,
+            "namespace": workflow.metadata.namespace,
+        },
+        "goal": goal,
+        "eta_minutes": eta_minutes(workflow),
+    })
+}
+
+/// Build the message sent to sinks when a workflow succeeds. `alert` is
+/// the alert that triggered the workflow, as stored.
+fn completion_payload(
+    workflow_id: Uuid,
+    workflow: &Workflow,
+    outputs: &serde_json::Value,
+    alert: Option<&Alert>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "type": "completion",
+        "status": "succeeded",
+        "workflow": {
+            "id": workflow_id,
+            "name": workflow.metadata.name,
+            "namespace": workflow.metadata.namespace,
+            "outputs": outputs,
+        },
+        "alert": alert,
+    })
+}
+
+/// Upper bound on how long a workflow takes, using the same per-step
+/// timeout defaults as the executor
+fn eta_minutes(workflow: &Workflow) -> i64 {
+    workflow.spec.steps.iter()
+        .map(|step| match (step.timeout_minutes, &step.step_type) {
+            (Some(minutes), _) => minutes as i64,
+            (None, StepType::Cli) => 5,
+            (None, StepType::Agent) => 10,
+            (None, StepType::Conditional) => 0,
+            (None, StepType::Http) => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{SqliteStore, StepType, WorkflowStatus};
+    use serde_json::json;
+    use std::{path::PathBuf, time::Duration};
+
+    fn workflow(name: &str, source_data: serde_json::Value) -> Workflow {
+        let mut workflow: Workflow = serde_json::from_value(json!({
+            "apiVersion": "punchingfist.io/v1alpha1",
+            "kind": "Workflow",
+            "metadata": { "name": name, "namespace": "default" },
+            "spec": {
+                "runtime": {
+                    "image": "busybox:latest",
+                    "llmConfig": { "provider": "mock", "model": "mock" }
+                },
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["alerts"],
+                    "properties": { "alerts": { "type": "array", "minItems": 1 } }
+                },
+                "steps": [],
+                "sinks": []
+            }
+        })).unwrap();
+        workflow.metadata.annotations = Some([
+            ("source.data".to_string(), source_data.to_string()),
+        ].into());
+        workflow
+    }
+
+    /// Engine over a fresh SQLite store in a temp file, with Kubernetes
+    /// calls going to `api_url`. Remove the returned file when done.
+    async fn test_engine_with_api(api_url: &str) -> (WorkflowEngine, Arc<dyn Store>, PathBuf) {
+        let path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+        let store: Arc<dyn Store> = Arc::new(SqliteStore::new(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap());
+        store.init().await.unwrap();
+
+        let client = kube::Client::try_from(kube::Config::new(api_url.parse().unwrap())).unwrap();
+        let engine = WorkflowEngine::new(
+            store.clone(),
+            Arc::new(StepExecutor::new(client, "default".to_string())),
+        );
+        (engine, store, path)
+    }
+
+    /// Engine whose Kubernetes calls all fail to connect
+    async fn test_engine() -> (WorkflowEngine, Arc<dyn Store>, PathBuf) {
+        test_engine_with_api("http://127.0.0.1:9").await
+    }
+
+    #[tokio::test]
+    async fn test_input_schema_validation() {
+        let (engine, store, path) = test_engine().await;
+        let engine = Arc::new(engine);
+        engine.clone().start().await;
+
+        engine.queue_workflow(workflow("valid", json!({ "alerts": [{ "labels": {} }] }))).await.unwrap();
+        engine.queue_workflow(workflow("invalid", json!({ "alerts": [] }))).await.unwrap();
+
+        let mut workflows = Vec::new();
+        for _ in 0..50 {
+            workflows = store.list_workflows(10, 0, None, None).await.unwrap();
+            if workflows.len() == 2 && workflows.iter().all(|w| w.completed_at.is_some()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let valid = workflows.iter().find(|w| w.name == "valid").unwrap();
+        assert_eq!(valid.status, WorkflowStatus::Succeeded);
+        assert!(store.list_workflow_steps(valid.id).await.unwrap().is_empty());
+
+        let invalid = workflows.iter().find(|w| w.name == "invalid").unwrap();
+        assert_eq!(invalid.status, WorkflowStatus::Failed);
+        assert!(invalid.error.as_deref().unwrap().starts_with("Input validation failed: /alerts:"));
+        let steps = store.list_workflow_steps(invalid.id).await.unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].step_type, StepType::Conditional);
+        assert_eq!(steps[0].result.as_ref().unwrap()["valid"], json!(false));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_to_completions() {
+        let (engine, _store, path) = test_engine().await;
+        let engine = Arc::new(engine);
+        let mut completions = engine.subscribe_to_completions().await;
+        engine.clone().start().await;
+
+        let valid = engine.queue_workflow(workflow("valid", json!({ "alerts": [{ "labels": {} }] }))).await.unwrap();
+        let invalid = engine.queue_workflow(workflow("invalid", json!({ "alerts": [] }))).await.unwrap();
+
+        let mut events = HashMap::new();
+        while events.len() < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), completions.recv()).await.unwrap().unwrap();
+            events.insert(event.workflow_id, event);
+        }
+        assert_eq!(events[&valid].status, WorkflowStatus::Succeeded);
+        assert_eq!(events[&valid].summary, None);
+        assert_eq!(events[&invalid].status, WorkflowStatus::Failed);
+        assert!(events[&invalid].summary.as_deref().unwrap().starts_with("Input validation failed"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_completion_event() {
+        let alert_id = Uuid::new_v4();
+        let mut triage: Workflow = serde_json::from_value(json!({
+            "apiVersion": "punchingfist.io/v1alpha1",
+            "kind": "Workflow",
+            "metadata": { "name": "triage", "annotations": { "alert.id": alert_id.to_string() } },
+            "spec": {
+                "runtime": { "image": "busybox:latest", "llmConfig": { "provider": "mock", "model": "mock" } },
+                "steps": [
+                    { "name": "investigate", "type": "agent", "goal": "find the cause" },
+                    { "name": "fix", "type": "agent", "goal": "fix it" },
+                    { "name": "report", "type": "cli", "command": "echo done" }
+                ],
+                "sinks": []
+            }
+        })).unwrap();
+        let outputs = json!({
+            "steps": {
+                "investigate": { "summary": "OOMKilled", "can_auto_fix": false },
+                "fix": { "summary": "Memory limit too low", "can_auto_fix": true, "fix_command": "kubectl set resources" },
+                "report": { "stdout": "done" }
+            },
+            "outputs": {}
+        });
+
+        // The last agent step's result wins
+        let event = completion_event(Uuid::nil(), &triage, WorkflowStatus::Succeeded, &outputs, None);
+        assert_eq!(event.alert_id, Some(alert_id));
+        assert_eq!(event.summary.as_deref(), Some("Memory limit too low"));
+        assert!(event.can_auto_fix);
+        assert_eq!(event.fix_command.as_deref(), Some("kubectl set resources"));
+
+        triage.metadata.annotations = None;
+        let failed = json!({ "error": "boom", "failed_step": "investigate", "outputs": {} });
+        let event = completion_event(Uuid::nil(), &triage, WorkflowStatus::Failed, &failed, Some("boom"));
+        assert_eq!(event.alert_id, None);
+        assert_eq!(event.summary.as_deref(), Some("boom"));
+        assert!(!event.can_auto_fix);
+    }
+
+    #[tokio::test]
+    async fn test_is_alive() {
+        let (engine, _store, path) = test_engine().await;
+        let engine = Arc::new(engine);
+        assert!(engine.is_alive(), "an engine that hasn't started counts as alive");
+
+        engine.clone().start().await;
+        for _ in 0..50 {
+            if engine.heartbeat.borrow().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(engine.is_alive());
+        assert_eq!(crate::metrics::WORKFLOW_ENGINE_ALIVE.get(), 1);
+
+        // The next heartbeat is 10s away, so the last one goes stale
+        engine.heartbeat.send_replace(Some(Instant::now() - HEARTBEAT_TIMEOUT));
+        assert!(!engine.is_alive());
+        assert_eq!(crate::metrics::WORKFLOW_ENGINE_ALIVE.get(), 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_agent_slots() {
+        let (engine, _store, path) = test_engine().await;
+
+        let mut limited = workflow("limited-agents", json!({}));
+        limited.spec.max_parallel_agents = Some(2);
+        let in_use = || crate::metrics::WORKFLOW_PARALLEL_AGENT_SLOTS_IN_USE
+            .with_label_values(&["default/limited-agents"])
+            .get();
+
+        let first = engine.acquire_agent_slot(&limited).await.unwrap();
+        let _second = engine.acquire_agent_slot(&limited).await.unwrap();
+        assert_eq!(in_use(), 2);
+        assert!(tokio::time::timeout(Duration::from_millis(50), engine.acquire_agent_slot(&limited)).await.is_err());
+
+        // Other workflows have their own slots, 3 by default
+        let other = workflow("default-agents", json!({}));
+        let _others: Vec<_> = futures::future::try_join_all((0..3).map(|_| engine.acquire_agent_slot(&other))).await.unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(50), engine.acquire_agent_slot(&other)).await.is_err());
+
+        drop(first);
+        assert_eq!(in_use(), 1);
+        let _third = tokio::time::timeout(Duration::from_millis(50), engine.acquire_agent_slot(&limited)).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(in_use(), 2);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_config_vars_fail_workflow() {
+        let (engine, store, path) = test_engine().await;
+        let engine = Arc::new(engine);
+        engine.clone().start().await;
+
+        let mut workflow = workflow("needs-vars", json!({ "alerts": [{ "labels": {} }] }));
+        workflow.spec.config_vars = serde_json::from_value(json!([
+            { "name": "CLUSTER", "source": { "configMapKeyRef": { "name": "cluster-info", "key": "name" } } }
+        ])).unwrap();
+        engine.queue_workflow(workflow).await.unwrap();
+
+        let mut workflows = Vec::new();
+        for _ in 0..50 {
+            workflows = store.list_workflows(10, 0, None, None).await.unwrap();
+            if workflows.iter().any(|w| w.completed_at.is_some()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(workflows[0].status, WorkflowStatus::Failed);
+        assert!(workflows[0].error.as_deref().unwrap().contains("config vars"));
+        let steps = store.list_workflow_steps(workflows[0].id).await.unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].name, "resolve-config-vars");
+        assert_eq!(steps[0].config.as_ref().unwrap()["configVars"], json!(["CLUSTER"]));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_start_payload() {
+        let workflow: Workflow = serde_json::from_value(json!({
+            "apiVersion": "punchingfist.io/v1alpha1",
+            "kind": "Workflow",
+            "metadata": { "name": "triage", "namespace": "default" },
+            "spec": {
+                "runtime": {
+                    "image": "busybox:latest",
+                    "llmConfig": { "provider": "mock", "model": "mock" }
+                },
+                "notifyOnStart": true,
+                "steps": [
+                    { "name": "collect", "type": "cli", "command": "echo hi" },
+                    { "name": "investigate", "type": "agent", "goal": "Find the root cause", "timeoutMinutes": 15 }
+                ],
+                "sinks": ["stdout"]
+            }
+        })).unwrap();
+
+        let payload = start_payload(&workflow);
+        assert_eq!(payload["type"], json!("start"));
+        assert_eq!(payload["status"], json!("started"));
+        assert_eq!(payload["goal"], json!("Find the root cause"));
+        assert_eq!(payload["eta_minutes"], json!(20));
+    }
+
+    #[test]
+    fn test_completion_payload() {
+        let workflow = workflow("triage", json!({ "alerts": [] }));
+        let outputs = json!({ "summary": "Disk full" });
+
+        let workflow_id = Uuid::new_v4();
+        let alert = alert(AlertStatus::Triaging, None);
+        let payload = completion_payload(workflow_id, &workflow, &outputs, Some(&alert));
+        assert_eq!(payload["type"], json!("completion"));
+        assert_eq!(payload["workflow"]["id"], json!(workflow_id));
+        assert_eq!(payload["workflow"]["outputs"], outputs);
+        assert_eq!(payload["alert"]["fingerprint"], json!(alert.fingerprint));
+
+        assert!(completion_payload(workflow_id, &workflow, &outputs, None)["alert"].is_null());
+    }
+
+    fn alert(status: AlertStatus, workflow_id: Option<Uuid>) -> Alert {
+        let now = chrono::Utc::now();
+        Alert {
+            id: Uuid::new_v4(),
+            external_id: None,
+            fingerprint: Uuid::new_v4().to_string(),
+            status,
+            severity: crate::store::AlertSeverity::Warning,
+            alert_name: "PodCrashLooping".to_string(),
+            summary: None,
+            description: None,
+            labels: Default::default(),
+            annotations: Default::default(),
+            source_id: None,
+            workflow_id,
+            ai_analysis: None,
+            ai_confidence: None,
+            auto_resolved: false,
+            starts_at: now,
+            ends_at: None,
+            received_at: now,
+            triage_started_at: None,
+            triage_completed_at: None,
+            resolved_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_budget() {
+        let (engine, _store, path) = test_engine().await;
+        engine.executions.write().await.insert("exec".to_string(), WorkflowExecution {
+            workflow: workflow("budgeted", json!({})),
+            state: WorkflowState::Running,
+            context: WorkflowContext::new(),
+            outputs: json!({}),
+            tokens_used: 0,
+        });
+
+        // Steps that don't report usage don't count against the budget
+        engine.record_token_usage("exec", Some(1000), &json!({ "stdout": "ok" })).await.unwrap();
+        engine.record_token_usage("exec", Some(1000), &json!({ "tokens_used": null })).await.unwrap();
+
+        engine.record_token_usage("exec", Some(1000), &json!({ "tokens_used": 600 })).await.unwrap();
+        engine.record_token_usage("exec", Some(1000), &json!({ "tokens_used": 400 })).await.unwrap();
+        let err = engine.record_token_usage("exec", Some(1000), &json!({ "tokens_used": 1 })).await.unwrap_err();
+        assert!(err.to_string().contains("Token budget exceeded: used 1001 of 1000"));
+
+        let progress = engine.get_execution_progress("exec").await.unwrap();
+        assert_eq!(progress["tokens_used"], 1001);
+
+        // Without a budget usage is only tracked
+        engine.record_token_usage("exec", None, &json!({ "tokens_used": 5000 })).await.unwrap();
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_cost_budget() {
+        let (engine, _store, path) = test_engine().await;
+        let mut config = Config::default();
+        config.cost_per_token.insert(
+            "mock".to_string(),
+            [("mock".to_string(), crate::config::TokenCost { input: 0.001, output: 0.002 })].into(),
+        );
+        let engine = engine.with_shared_config(Arc::new(RwLock::new(config)));
+
+        let mut capped = workflow("capped", json!({}));
+        capped.spec.max_cost_usd = Some(5.0);
+        engine.executions.write().await.insert("exec".to_string(), WorkflowExecution {
+            workflow: capped.clone(),
+            state: WorkflowState::Running,
+            context: WorkflowContext::new(),
+            outputs: json!({}),
+            tokens_used: 0,
+        });
+
+        // Steps that don't report usage cost nothing
+        engine.record_cost("exec", &capped, &json!({ "stdout": "ok" })).await.unwrap();
+        assert!(engine.cost_budget_spent("exec", &capped).await.is_none());
+
+        // 1000 input + 1000 output tokens
+        engine.record_cost("exec", &capped, &json!({
+            "tokens_used": 2000, "input_tokens": 1000, "output_tokens": 1000,
+        })).await.unwrap();
+        // Usage without a split is priced as output
+        let err = engine.record_cost("exec", &capped, &json!({ "tokens_used": 1000 })).await.unwrap_err();
+        assert!(err.to_string().contains("cost budget exceeded ($5.00 spent)"), "{}", err);
+        assert_eq!(engine.cost_budget_spent("exec", &capped).await, Some(5.0));
+
+        let progress = engine.get_execution_progress("exec").await.unwrap();
+        assert_eq!(progress["cost_usd"], 5.0);
+
+        // Models without a price are not counted
+        let mut unpriced = capped.clone();
+        unpriced.spec.runtime.llm_config.model = "other".to_string();
+        engine.record_cost("exec", &unpriced, &json!({ "tokens_used": 1000 })).await.unwrap();
+        assert_eq!(engine.cost_budget_spent("exec", &capped).await, Some(5.0));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_reprocess_alert_guards() {
+        let (engine, store, path) = test_engine().await;
+
+        let missing = engine.reprocess_alert(Uuid::new_v4()).await;
+        assert!(matches!(missing, Err(Error::NotFound(_))));
+
+        let resolved = alert(AlertStatus::Resolved, None);
+        store.save_alert(resolved.clone()).await.unwrap();
+        let err = engine.reprocess_alert(resolved.id).await.unwrap_err();
+        assert!(err.to_string().contains("only Received or Triaging"));
+
+        let running_id = Uuid::new_v4();
+        let now = chrono::Utc::now();
+        store.save_workflow(crate::store::Workflow {
+            id: running_id,
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Running,
+            steps_completed: 0,
+            total_steps: 1,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: None,
+            created_at: now,
+        }).await.unwrap();
+        let stuck = alert(AlertStatus::Triaging, Some(running_id));
+        store.save_alert(stuck.clone()).await.unwrap();
+        let err = engine.reprocess_alert(stuck.id).await.unwrap_err();
+        assert!(err.to_string().contains("already has running workflow"));
+
+        // A workflow queued but not yet started is just as active
+        store.update_workflow_status(running_id, WorkflowStatus::Pending).await.unwrap();
+        let err = engine.reprocess_alert(stuck.id).await.unwrap_err();
+        assert!(err.to_string().contains("already has pending workflow"));
+
+        // Past the guards, the alert's source is needed to find the workflow
+        store.update_workflow_status(running_id, WorkflowStatus::Failed).await.unwrap();
+        let err = engine.reprocess_alert(stuck.id).await.unwrap_err();
+        assert!(err.to_string().contains("has no source"));
+
+        let mut unplaced = alert(AlertStatus::Received, None);
+        unplaced.source_id = Some(Uuid::new_v4());
+        store.save_alert(unplaced.clone()).await.unwrap();
+        let err = engine.reprocess_alert(unplaced.id).await.unwrap_err();
+        assert!(err.to_string().contains("no recorded source namespace"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Kubernetes API with one Source in `team-a`, routing critical alerts
+    /// to `page-oncall` and warnings to a workflow that doesn't exist
+    async fn routing_api(source_uid: Uuid) -> (String, Client) {
+        use axum::{extract::Path, http::StatusCode, routing::get, Json, Router};
+
+        let app = Router::new()
+            .route("/apis/punchingfist.io/v1alpha1/namespaces/{namespace}/sources", get(move |Path(namespace): Path<String>| async move {
+                let items = match namespace.as_str() {
+                    "team-a" => vec![json!({
+                        "apiVersion": "punchingfist.io/v1alpha1",
+                        "kind": "Source",
+                        "metadata": { "name": "alertmanager", "namespace": "team-a", "uid": source_uid.to_string() },
+                        "spec": {
+                            "type": "webhook",
+                            "config": { "path": "/webhook/alertmanager" },
+                            "triggerWorkflow": "triage",
+                            "severityRouting": { "critical": "page-oncall", "warning": "retired" }
+                        }
+                    })],
+                    _ => vec![],
+                };
+                Json(json!({ "apiVersion": "punchingfist.io/v1alpha1", "kind": "SourceList", "metadata": {}, "items": items }))
+            }))
+            .route("/apis/punchingfist.io/v1alpha1/namespaces/{namespace}/workflows/{name}", get(|Path((namespace, name)): Path<(String, String)>| async move {
+                if name == "retired" {
+                    let status = json!({ "kind": "Status", "apiVersion": "v1", "status": "Failure", "reason": "NotFound", "code": 404, "message": "not found" });
+                    return (StatusCode::NOT_FOUND, Json(status));
+                }
+                let mut workflow = serde_json::to_value(workflow(&name, json!({}))).unwrap();
+                workflow["metadata"]["namespace"] = json!(namespace);
+                (StatusCode::OK, Json(workflow))
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let url = format!("http://{}", addr);
+        let client = Client::try_from(kube::Config::new(url.parse().unwrap())).unwrap();
+        (url, client)
+    }
+
+    #[tokio::test]
+    async fn test_reprocess_alert_routes_by_severity() {
+        let source_uid = Uuid::new_v4();
+        let (api_url, client) = routing_api(source_uid).await;
+        let (engine, store, path) = test_engine_with_api(&api_url).await;
+        let engine = engine.with_client(client);
+
+        let reprocess = |severity| {
+            let mut alert = alert(AlertStatus::Received, None);
+            alert.severity = severity;
+            alert.source_id = Some(source_uid);
+            alert.annotations.insert(Alert::SOURCE_NAMESPACE_ANNOTATION.to_string(), "team-a".to_string());
+            let (store, engine) = (store.clone(), &engine);
+            async move {
+                store.save_alert(alert.clone()).await.unwrap();
+                let id = engine.reprocess_alert(alert.id).await.unwrap();
+                store.get_workflow(id).await.unwrap().unwrap()
+            }
+        };
+
+        let paged = reprocess(crate::store::AlertSeverity::Critical).await;
+        assert_eq!(paged.name, "page-oncall");
+        assert_eq!(paged.namespace, "team-a");
+        assert_eq!(paged.trigger_source.as_deref(), Some("alertmanager"));
+
+        // Routes to missing workflows fall back to the default, as on the webhook
+        let triaged = reprocess(crate::store::AlertSeverity::Warning).await;
+        assert_eq!(triaged.name, "triage");
+        assert_eq!(reprocess(crate::store::AlertSeverity::Info).await.name, "triage");
+
+        // The source is only looked for in the namespace it was recorded in
+        let mut elsewhere = alert(AlertStatus::Received, None);
+        elsewhere.source_id = Some(source_uid);
+        elsewhere.annotations.insert(Alert::SOURCE_NAMESPACE_ANNOTATION.to_string(), "team-b".to_string());
+        store.save_alert(elsewhere.clone()).await.unwrap();
+        let err = engine.reprocess_alert(elsewhere.id).await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)), "{}", err);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Server for HTTP steps that records when each step starts and ends.
+    /// `/fail/{step}` answers with a 500.
+    async fn step_server() -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use axum::{extract::{Path, State}, http::StatusCode, routing::get, Router};
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/ok/{step}", get(|State(events): State<Arc<std::sync::Mutex<Vec<String>>>>, Path(step): Path<String>| async move {
+                events.lock().unwrap().push(format!("start {}", step));
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                events.lock().unwrap().push(format!("end {}", step));
+                StatusCode::OK
+            }))
+            .route("/fail/{step}", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .with_state(events.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), events)
+    }
+
+    /// Kubernetes API where every CLI step pod fails, logging "exit code 1"
+    async fn failing_pods_api() -> String {
+        use axum::{routing::get, Json, Router};
+
+        let app = Router::new()
+            .route("/api/v1/namespaces/{namespace}/pods", get(|| async {
+                let event = json!({
+                    "type": "MODIFIED",
+                    "object": { "apiVersion": "v1", "kind": "Pod", "metadata": {}, "status": { "phase": "Failed" } }
+                });
+                format!("{}\n", event)
+            }).post(|Json(pod): Json<serde_json::Value>| async move { (axum::http::StatusCode::CREATED, Json(pod)) }))
+            .route("/api/v1/namespaces/{namespace}/pods/{name}/log", get(|| async { "exit code 1" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    /// Run a workflow of HTTP steps, given as (name, path, dependsOn,
+    /// continueOnError), to completion. Steps with the path `false` are CLI
+    /// steps running `false` instead, in a pod that fails.
+    async fn run_dag(steps: &[(&str, &str, &[&str], bool)]) -> (crate::store::Workflow, Vec<String>) {
+        let (base_url, events) = step_server().await;
+        let (engine, store, path) = test_engine_with_api(&failing_pods_api().await).await;
+        let engine = Arc::new(engine);
+        engine.clone().start().await;
+
+        let mut dag = workflow("dag", json!({ "alerts": [{ "labels": {} }] }));
+        dag.spec.steps = steps.iter()
+            .map(|(name, path, depends_on, continue_on_error)| serde_json::from_value(match *path {
+                "false" => json!({
+                    "name": name,
+                    "type": "cli",
+                    "command": "false",
+                    "dependsOn": depends_on,
+                    "continueOnError": continue_on_error,
+                }),
+                _ => json!({
+                    "name": name,
+                    "type": "http",
+                    "urlTemplate": format!("{}/{}/{}", base_url, path, name),
+                    "dependsOn": depends_on,
+                    "continueOnError": continue_on_error,
+                }),
+            }).unwrap())
+            .collect();
+        let id = engine.queue_workflow(dag).await.unwrap();
+
+        for _ in 0..100 {
+            let stored = store.get_workflow(id).await.unwrap();
+            if let Some(stored) = stored.filter(|w| w.completed_at.is_some()) {
+                let events = events.lock().unwrap().clone();
+                let _ = std::fs::remove_file(&path);
+                return (stored, events);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("workflow did not complete");
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_and_diamond_dependencies() {
+        let (stored, events) = run_dag(&[
+            ("report", "ok", &["pods", "logs", "events"], false),
+            ("pods", "ok", &["collect"], false),
+            ("logs", "ok", &["collect"], false),
+            ("events", "ok", &["collect"], false),
+            ("collect", "ok", &[], false),
+        ]).await;
+        assert_eq!(stored.status, WorkflowStatus::Succeeded);
+        assert_eq!(stored.steps_completed, 5);
+
+        let position = |event: &str| events.iter().position(|e| e == event).unwrap();
+        // The fanned-out steps all start once collect is done, and run concurrently
+        for step in ["pods", "logs", "events"] {
+            assert!(position("end collect") < position(&format!("start {}", step)));
+            for other in ["pods", "logs", "events"] {
+                assert!(position(&format!("start {}", step)) < position(&format!("end {}", other)), "{:?}", events);
+            }
+            assert!(position(&format!("end {}", step)) < position("start report"));
+        }
+        assert_eq!(events.last().unwrap(), "end report");
+    }
+
+    #[tokio::test]
+    async fn test_failed_dependencies_skip_steps() {
+        let (stored, events) = run_dag(&[
+            ("collect", "ok", &[], false),
+            ("restart", "fail", &["collect"], false),
+            ("verify", "ok", &["restart"], false),
+            ("scale", "fail", &["collect"], true),
+            ("verify-scale", "ok", &["scale"], false),
+            ("notes", "ok", &["collect"], false),
+        ]).await;
+        assert_eq!(stored.status, WorkflowStatus::Failed);
+        assert!(stored.error.as_deref().unwrap().contains("got status 500"));
+
+        let outputs = stored.outputs.unwrap();
+        assert_eq!(outputs["failed_step"], "restart");
+        assert_eq!(outputs["skipped"], json!(["verify"]));
+        assert!(outputs["outputs"]["scale"]["error"].as_str().unwrap().contains("got status 500"));
+        assert_eq!(outputs["outputs"]["verify-scale"]["status"], 200);
+        assert_eq!(outputs["outputs"]["notes"]["status"], 200);
+        assert!(!events.contains(&"start verify".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_failed_cli_step_skips_dependents() {
+        let (stored, events) = run_dag(&[
+            ("check", "false", &[], false),
+            ("restart", "ok", &["check"], false),
+        ]).await;
+        assert_eq!(stored.status, WorkflowStatus::Failed);
+        assert!(stored.error.as_deref().unwrap().contains("exit code 1"), "{:?}", stored.error);
+        let outputs = stored.outputs.unwrap();
+        assert_eq!(outputs["failed_step"], "check");
+        assert_eq!(outputs["skipped"], json!(["restart"]));
+        assert!(events.is_empty());
+
+        // With continueOnError the failed command's output is kept and the dependent runs
+        let (stored, events) = run_dag(&[
+            ("check", "false", &[], true),
+            ("restart", "ok", &["check"], false),
+        ]).await;
+        assert_eq!(stored.status, WorkflowStatus::Succeeded);
+        let outputs = stored.outputs.unwrap();
+        assert_eq!(outputs["steps"]["check"]["command"], "false");
+        assert!(outputs["steps"]["check"]["error"].as_str().unwrap().contains("exit code 1"));
+        assert_eq!(events, vec!["start restart", "end restart"]);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_branches() {
+        let (base_url, events) = step_server().await;
+        let (engine, store, path) = test_engine().await;
+        let engine = Arc::new(engine);
+        engine.clone().start().await;
+
+        let run = |condition: &str| {
+            let mut triage = workflow("triage", json!({ "alerts": [{ "labels": {} }] }));
+            let http_step = |name: &str, depends_on: &[&str]| json!({
+                "name": name,
+                "type": "http",
+                "urlTemplate": format!("{}/ok/{}", base_url, name),
+                "dependsOn": depends_on,
+            });
+            triage.spec.steps = serde_json::from_value(json!([
+                http_step("collect", &[]),
+                {
+                    "name": "check",
+                    "type": "conditional",
+                    "condition": condition,
+                    "dependsOn": ["collect"],
+                    "thenSteps": ["restart"],
+                    "elseSteps": ["page"],
+                },
+                http_step("restart", &[]),
+                http_step("page", &[]),
+                http_step("report", &["restart", "page"]),
+            ])).unwrap();
+            let (engine, store, events) = (engine.clone(), store.clone(), events.clone());
+            async move {
+                events.lock().unwrap().clear();
+                let id = engine.queue_workflow(triage).await.unwrap();
+                for _ in 0..100 {
+                    if let Some(stored) = store.get_workflow(id).await.unwrap().filter(|w| w.completed_at.is_some()) {
+                        let statuses: HashMap<String, StepStatus> = store.list_workflow_steps(id).await.unwrap()
+                            .into_iter()
+                            .map(|step| (step.name, step.status))
+                            .collect();
+                        let events = events.lock().unwrap().clone();
+                        return (stored, statuses, events);
+                    }
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                panic!("workflow did not complete");
+            }
+        };
+
+        let (stored, statuses, events) = run("outputs.collect.status == 200").await;
+        assert_eq!(stored.status, WorkflowStatus::Succeeded);
+        assert_eq!(stored.outputs.unwrap()["steps"]["check"]["branch"], "then");
+        assert_eq!(statuses["restart"], StepStatus::Succeeded);
+        assert_eq!(statuses["page"], StepStatus::Skipped);
+        assert_eq!(statuses["report"], StepStatus::Succeeded);
+        assert_eq!(events, ["start collect", "end collect", "start restart", "end restart", "start report", "end report"]);
+
+        let (stored, statuses, events) = run("outputs.collect.status >= 400").await;
+        assert_eq!(stored.status, WorkflowStatus::Succeeded);
+        assert_eq!(statuses["restart"], StepStatus::Skipped);
+        assert_eq!(statuses["page"], StepStatus::Succeeded);
+        assert_eq!(events, ["start collect", "end collect", "start page", "end page", "start report", "end report"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_output_transform() {
+        let (base_url, _) = step_server().await;
+        let (engine, store, path) = test_engine().await;
+        let engine = Arc::new(engine);
+        engine.clone().start().await;
+
+        let run = |transform: &str| {
+            let mut triage = workflow("triage", json!({ "alerts": [{ "labels": {} }] }));
+            triage.spec.steps = vec![serde_json::from_value(json!({
+                "name": "check",
+                "type": "http",
+                "urlTemplate": format!("{}/ok/check", base_url),
+                "outputTransform": transform,
+            })).unwrap()];
+            let engine = engine.clone();
+            let store = store.clone();
+            async move {
+                let id = engine.queue_workflow(triage).await.unwrap();
+                for _ in 0..100 {
+                    if let Some(stored) = store.get_workflow(id).await.unwrap().filter(|w| w.completed_at.is_some()) {
+                        return stored;
+                    }
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                panic!("workflow did not complete");
+            }
+        };
+
+        let stored = run("{code: .status, ok: (.status == 200)}").await;
+        assert_eq!(stored.status, WorkflowStatus::Succeeded);
+        assert_eq!(stored.outputs.unwrap()["steps"]["check"], json!({ "code": 200, "ok": true }));
+
+        let stored = run(".status[").await;
+        assert_eq!(stored.status, WorkflowStatus::Failed);
+        assert!(stored.error.as_deref().unwrap().contains("Invalid output transform"), "{:?}", stored.error);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_cyclic_dependencies_fail_workflow() {
+        let (stored, events) = run_dag(&[
+            ("collect", "ok", &[], false),
+            ("analyze", "ok", &["collect", "remediate"], false),
+            ("remediate", "ok", &["analyze"], false),
+        ]).await;
+        assert_eq!(stored.status, WorkflowStatus::Failed);
+        assert!(stored.error.as_deref().unwrap().contains("cycle between: analyze, remediate"));
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_requeue_dropped_alerts() {
+        let (engine, store, path) = test_engine().await;
+
+        let mut dropped = alert(AlertStatus::Received, None);
+        dropped.received_at -= chrono::Duration::minutes(30);
+        let fresh = alert(AlertStatus::Received, None);
+        store.save_alert(dropped.clone()).await.unwrap();
+        store.save_alert(fresh.clone()).await.unwrap();
+
+        // Without a source the alert can't be reprocessed, but each attempt
+        // counts towards the cap
+        for attempt in 1..=MAX_ALERT_REQUEUES + 1 {
+            assert_eq!(engine.requeue_dropped_alerts().await.unwrap(), 0);
+            let stored = store.get_alert(dropped.id).await.unwrap().unwrap();
+            assert_eq!(stored.requeue_count(), attempt.min(MAX_ALERT_REQUEUES));
+        }
+
+        let fresh = store.get_alert(fresh.id).await.unwrap().unwrap();
+        assert_eq!(fresh.requeue_count(), 0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
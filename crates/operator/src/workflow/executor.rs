@@ -1,25 +1,49 @@
 use std::sync::Arc;
 use std::time::Duration;
 use async_trait::async_trait;
-use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::api::core::v1::{
+    ConfigMapEnvSource, ConfigMapKeySelector, EnvFromSource, EnvVar, EnvVarSource, Pod, SecretEnvSource,
+    SecretKeySelector,
+};
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use kube::{
-    api::{Api, PostParams, WatchEvent, WatchParams},
+    api::{Api, DeleteParams, LogParams, PostParams, WatchEvent, WatchParams},
     Client,
 };
 use serde_json::Value;
 use tokio::time::timeout;
-use tracing::{error, info, warn};
-use futures::{StreamExt, TryStreamExt};
+use uuid::Uuid;
+use tracing::{debug, error, info, warn};
+use futures::{AsyncBufReadExt, StreamExt, TryStreamExt};
 use tera::{Tera, Context as TeraContext};
 use regex;
 
 use crate::{
-    crd::{WorkflowStep, StepType},
+    crd::{WorkflowStep, StepType, HttpMethod, ResourceQuantities, EnvFromRef, workflow::{ConfigVarRef, KeyRef}},
     workflow::WorkflowContext,
-    agent::{AgentRuntime, LLMConfig, tools::{kubectl::KubectlTool, promql::PromQLTool, curl::CurlTool, script::ScriptTool}, provider::map_anthropic_model},
+    agent::{AgentBehaviorConfig, AgentRuntime, LLMConfig, tools::{kubectl::KubectlTool, promql::{PromQLCache, PromQLTool}, curl::CurlTool, script::ScriptTool, ingress::IngressTool, ToolRecorder}, provider::map_anthropic_model},
+    store::Store,
     Result, Error,
 };
 
+/// How often logs followed from a CLI step's pod are saved to the step
+const LOG_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long to wait before following the logs of a pod whose container
+/// hasn't started
+const LOG_FOLLOW_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Seconds between attempts of a step with `retries` and no
+/// `retryDelaySeconds`
+const DEFAULT_RETRY_DELAY_SECONDS: u64 = 5;
+
+/// Requests and limits of CLI step pods whose `resources` leave them unset,
+/// so a runaway command can't take over its node
+const DEFAULT_CPU_REQUEST: &str = "100m";
+const DEFAULT_MEMORY_REQUEST: &str = "128Mi";
+const DEFAULT_CPU_LIMIT: &str = "500m";
+const DEFAULT_MEMORY_LIMIT: &str = "512Mi";
+
 #[derive(Debug, Clone)]
 pub struct StepResult {
     pub output: Value,
@@ -29,17 +53,84 @@ pub struct StepResult {
 pub struct StepExecutor {
     client: Client,
     namespace: String,
+    /// PromQL results shared by the agent steps of all workflows
+    promql_cache: PromQLCache,
+    /// Where agent steps save their tool calls
+    store: Option<Arc<dyn Store>>,
+    /// Leave the pods of failed CLI steps in the cluster
+    keep_failed_pods: bool,
 }
 
 impl StepExecutor {
     pub fn new(client: Client, namespace: String) -> Self {
-        Self { client, namespace }
+        Self { client, namespace, promql_cache: PromQLCache::default(), store: None, keep_failed_pods: false }
+    }
+
+    /// Save the tool calls of agent steps to `store`
+    pub fn with_store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = Some(store);
+        self
     }
 
+    /// Set how long agent steps share PromQL query results
+    pub fn with_promql_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.promql_cache = PromQLCache::new(ttl);
+        self
+    }
+
+    /// Leave the pods of CLI steps that fail or time out in the cluster
+    /// for debugging instead of deleting them
+    pub fn with_keep_failed_pods(mut self, keep: bool) -> Self {
+        self.keep_failed_pods = keep;
+        self
+    }
+
+    /// Run the step, running it again up to `retries` times while it fails.
+    /// A step that succeeds after failing lists its failed attempts in
+    /// `failed_attempts` of its output.
     pub async fn execute_step(
         &self,
         step: &WorkflowStep,
         context: &WorkflowContext,
+    ) -> Result<StepResult> {
+        let attempts = step.retries.unwrap_or(0) + 1;
+        let delay = Duration::from_secs(step.retry_delay_seconds.unwrap_or(DEFAULT_RETRY_DELAY_SECONDS));
+        let mut failed_attempts = Vec::new();
+        
+        for attempt in 1..attempts {
+            let result = self.execute_step_once(step, context).await;
+            let error = match &result {
+                Ok(result) if result.success => None,
+                Ok(result) => Some(result.output.get("error")
+                    .and_then(|e| e.as_str())
+                    .unwrap_or("step reported failure")
+                    .to_string()),
+                // Retrying an invalid step fails the same way again
+                Err(Error::Validation(_)) => return result,
+                Err(e) => Some(e.to_string()),
+            };
+            let Some(error) = error else {
+                return result.map(|result| with_failed_attempts(result, failed_attempts));
+            };
+            warn!(
+                "Step {} failed on attempt {}/{}, retrying in {:?}: {}",
+                step.name, attempt, attempts, delay, error
+            );
+            crate::metrics::WORKFLOW_STEP_RETRIES_TOTAL.inc();
+            failed_attempts.push(serde_json::json!({ "attempt": attempt, "error": error }));
+            tokio::time::sleep(delay).await;
+        }
+        
+        match self.execute_step_once(step, context).await {
+            Ok(result) => Ok(with_failed_attempts(result, failed_attempts)),
+            Err(e) => Err(error_with_failed_attempts(e, &failed_attempts)),
+        }
+    }
+
+    async fn execute_step_once(
+        &self,
+        step: &WorkflowStep,
+        context: &WorkflowContext,
     ) -> Result<StepResult> {
         info!("Executing step: {} (type: {:?})", step.name, step.step_type);
 
@@ -53,6 +144,9 @@ impl StepExecutor {
             StepType::Conditional => {
                 self.execute_conditional_step(step, context).await
             }
+            StepType::Http => {
+                self.execute_http_step(step, context).await
+            }
         }
     }
 
@@ -77,7 +171,7 @@ impl StepExecutor {
         
         // Create a pod to execute the command
         let pod_name = format!("workflow-cli-{}-{}", step.name.to_lowercase().replace(" ", "-"), uuid::Uuid::new_v4());
-        let pod = self.create_cli_pod(&pod_name, &image, &rendered_command, &Default::default())?;
+        let pod = self.create_cli_pod(&pod_name, &image, &rendered_command, step, context)?;
 
         let pods: Api<Pod> = Api::namespaced(self.client.clone(), &self.namespace);
         
@@ -85,9 +179,30 @@ impl StepExecutor {
         pods.create(&PostParams::default(), &pod).await
             .map_err(|e| Error::Kubernetes(e.to_string()))?;
 
-        // Wait for pod completion with timeout
+        // Whatever happens while waiting, the pod is cleaned up afterwards
+        let result = self.run_cli_pod(step, context, &pod_name, &rendered_command).await;
+        let failed = !matches!(&result, Ok(result) if result.success);
+        if failed && self.keep_failed_pods {
+            info!("Keeping pod {} of failed CLI step {}", pod_name, step.name);
+        } else if let Err(e) = pods.delete(&pod_name, &DeleteParams::default()).await {
+            warn!("Failed to delete pod {} of CLI step {}: {}", pod_name, step.name, e);
+        }
+        result
+    }
+
+    /// Wait for the CLI step's pod to finish within the step's timeout,
+    /// saving its logs to the step as it runs
+    async fn run_cli_pod(
+        &self,
+        step: &WorkflowStep,
+        context: &WorkflowContext,
+        pod_name: &str,
+        rendered_command: &str,
+    ) -> Result<StepResult> {
         let timeout_duration = Duration::from_secs(step.timeout_minutes.unwrap_or(5) as u64 * 60);
-        match timeout(timeout_duration, self.wait_for_pod_completion(&pod_name)).await {
+        let streamed_logs = Arc::new(std::sync::Mutex::new(String::new()));
+        let completion = self.wait_for_pod_completion(pod_name, rendered_command, context.current_step_id, streamed_logs.clone());
+        match timeout(timeout_duration, completion).await {
             Ok(Ok(output)) => {
                 info!("CLI step {} completed successfully", step.name);
                 Ok(StepResult {
@@ -110,9 +225,16 @@ impl StepExecutor {
             }
             Err(_) => {
                 error!("CLI step {} timed out", step.name);
+                
+                // Keep what the command logged before its pod goes away
+                let logs = match self.get_pod_logs(pod_name).await {
+                    Ok(logs) => logs,
+                    Err(_) => streamed_logs.lock().unwrap().clone(),
+                };
                 Ok(StepResult {
                     output: serde_json::json!({
                         "error": "Command timed out",
+                        "stdout": logs,
                         "command": rendered_command,
                     }),
                     success: false,
@@ -151,6 +273,25 @@ impl StepExecutor {
         // Create agent runtime
         let mut agent_runtime = AgentRuntime::new(llm_config)
             .map_err(|e| Error::Internal(format!("Failed to create agent runtime: {}", e)))?;
+        
+        if let Some(config_map) = context.get_metadata("agent_config_map").and_then(|v| v.as_str()) {
+            let namespace = context.get_metadata("workflow_namespace")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&self.namespace);
+            let behavior_config = AgentBehaviorConfig::from_config_map(&self.client, namespace, config_map).await
+                .map_err(|e| Error::Config(e.to_string()))?;
+            info!("Using agent configuration from ConfigMap {}/{}", namespace, config_map);
+            agent_runtime = agent_runtime.with_behavior_config(behavior_config);
+        }
+
+        let workflow_id = context.get_metadata("workflow_id")
+            .and_then(|v| v.as_str())
+            .and_then(|id| id.parse().ok());
+        if let (Some(store), Some(workflow_id)) = (&self.store, workflow_id) {
+            agent_runtime = agent_runtime.with_tool_recorder(
+                ToolRecorder::new(store.clone(), workflow_id).with_step_name(&step.name),
+            );
+        }
 
         // Add tools based on step configuration
         if !step.tools.is_empty() {
@@ -171,7 +312,8 @@ impl StepExecutor {
                             .and_then(|v| v.as_str())
                             .unwrap_or("http://prometheus:9090")
                             .to_string();
-                        let promql_tool = PromQLTool::new(prometheus_url);
+                        let promql_tool = with_prometheus_auth(PromQLTool::new(prometheus_url), context)
+                            .with_cache(self.promql_cache.clone());
                         agent_runtime.add_tool("promql".to_string(), promql_tool);
                     }
                     "curl" => {
@@ -182,6 +324,10 @@ impl StepExecutor {
                         let script_tool = ScriptTool::new();
                         agent_runtime.add_tool("script".to_string(), script_tool);
                     }
+                    "ingress" => {
+                        let ingress_tool = IngressTool::new(self.client.clone());
+                        agent_runtime.add_tool("ingress".to_string(), ingress_tool);
+                    }
                     _ => {
                         warn!("Unknown tool requested: {}", tool_name);
                     }
@@ -230,6 +376,10 @@ impl StepExecutor {
                         "can_auto_fix": agent_result.can_auto_fix,
                         "fix_command": agent_result.fix_command,
                         "escalation_notes": agent_result.escalation_notes,
+                        "tokens_used": agent_result.tokens_used,
+                        "input_tokens": agent_result.input_tokens,
+                        "output_tokens": agent_result.output_tokens,
+                        "inhibition_rule": agent_result.inhibition_rule,
                         "report": agent_result.format_report(),
                     }),
                     success: true,
@@ -291,22 +441,93 @@ impl StepExecutor {
         })
     }
 
+    async fn execute_http_step(
+        &self,
+        step: &WorkflowStep,
+        context: &WorkflowContext,
+    ) -> Result<StepResult> {
+        info!("Executing HTTP step: {}", step.name);
+
+        let url_template = step.url_template.as_ref()
+            .ok_or_else(|| Error::Validation("HTTP step missing urlTemplate".to_string()))?;
+        let url = self.render_template(url_template, context)?;
+        let method = step.method.unwrap_or_default();
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(step.timeout_minutes.unwrap_or(1) as u64 * 60))
+            .build()
+            .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+        let mut request = client.request(
+            match method {
+                HttpMethod::Get => reqwest::Method::GET,
+                HttpMethod::Post => reqwest::Method::POST,
+                HttpMethod::Put => reqwest::Method::PUT,
+                HttpMethod::Patch => reqwest::Method::PATCH,
+                HttpMethod::Delete => reqwest::Method::DELETE,
+            },
+            &url,
+        );
+        for (name, value) in &step.headers {
+            request = request.header(name, self.render_template(value, context)?);
+        }
+        if let Some(body_template) = &step.body_template {
+            let body = self.render_template(body_template, context)?;
+            let has_content_type = step.headers.keys().any(|name| name.eq_ignore_ascii_case("content-type"));
+            if !has_content_type && serde_json::from_str::<Value>(&body).is_ok() {
+                request = request.header(reqwest::header::CONTENT_TYPE, "application/json");
+            }
+            request = request.body(body);
+        }
+
+        let response = request.send().await
+            .map_err(|e| Error::Execution(format!("HTTP step {} failed to call {}: {}", step.name, url, e)))?;
+        let status = response.status();
+        let text = response.text().await
+            .map_err(|e| Error::Execution(format!("HTTP step {} failed to read response: {}", step.name, e)))?;
+
+        let status_ok = match step.expected_status {
+            Some(expected) => status.as_u16() == expected,
+            None => status.is_success(),
+        };
+        if !status_ok {
+            let expected = step.expected_status
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "2xx".to_string());
+            error!("HTTP step {} got status {} from {}, expected {}", step.name, status.as_u16(), url, expected);
+            return Err(Error::Execution(format!(
+                "HTTP step {} got status {} from {}, expected {}: {}",
+                step.name, status.as_u16(), url, expected, text
+            )));
+        }
+
+        info!("HTTP step {} completed with status {}", step.name, status.as_u16());
+        // JSON responses are stored parsed so later steps can template into them
+        let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+        Ok(StepResult {
+            output: serde_json::json!({
+                "status": status.as_u16(),
+                "body": body,
+                "url": url,
+                "method": method,
+            }),
+            success: true,
+        })
+    }
+
     fn create_cli_pod(
         &self,
         name: &str,
         image: &str,
         command: &str,
-        env: &std::collections::HashMap<String, String>,
+        step: &WorkflowStep,
+        context: &WorkflowContext,
     ) -> Result<Pod> {
-        use k8s_openapi::api::core::v1::{Container, EnvVar, PodSpec};
+        use k8s_openapi::api::core::v1::{Container, PodSpec, ResourceRequirements};
         
-        let env_vars: Vec<EnvVar> = env.iter()
-            .map(|(k, v)| EnvVar {
-                name: k.clone(),
-                value: Some(v.clone()),
-                ..Default::default()
-            })
-            .collect();
+        // Step variables come last so they take precedence over context ones
+        let mut env_vars = context_env_vars(&step.env_from_context, context);
+        env_vars.extend(self.step_env_vars(step, context)?);
+        let resources = step.resources.clone().unwrap_or_default();
 
         let pod = Pod {
             metadata: k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta {
@@ -324,8 +545,15 @@ impl StepExecutor {
                     command: Some(vec!["/bin/sh".to_string()]),
                     args: Some(vec!["-c".to_string(), command.to_string()]),
                     env: Some(env_vars),
+                    env_from: Some(step.env_from.iter().map(env_from_source).collect()),
+                    resources: Some(ResourceRequirements {
+                        requests: Some(resource_list(&resources.requests, DEFAULT_CPU_REQUEST, DEFAULT_MEMORY_REQUEST)),
+                        limits: Some(resource_list(&resources.limits, DEFAULT_CPU_LIMIT, DEFAULT_MEMORY_LIMIT)),
+                        ..Default::default()
+                    }),
                     ..Default::default()
                 }],
+                service_account_name: step.service_account_name.clone(),
                 restart_policy: Some("Never".to_string()),
                 ..Default::default()
             }),
@@ -335,45 +563,150 @@ impl StepExecutor {
         Ok(pod)
     }
 
-    async fn wait_for_pod_completion(&self, pod_name: &str) -> Result<String> {
+    /// Environment variables declared by the step. Values read from
+    /// ConfigMaps and Secrets are left for the kubelet to resolve, so the
+    /// operator never sees them.
+    fn step_env_vars(&self, step: &WorkflowStep, context: &WorkflowContext) -> Result<Vec<EnvVar>> {
+        step.env.iter().map(|var| {
+            let (value, value_from) = match (&var.value, &var.value_from) {
+                (Some(value), None) => (Some(self.render_template(value, context)?), None),
+                (None, Some(ConfigVarRef::ConfigMapKeyRef(key_ref))) => (None, Some(EnvVarSource {
+                    config_map_key_ref: Some(ConfigMapKeySelector {
+                        name: Some(key_ref.name.clone()),
+                        key: key_ref.key.clone(),
+                        optional: None,
+                    }),
+                    ..Default::default()
+                })),
+                (None, Some(ConfigVarRef::SecretKeyRef(key_ref))) => (None, Some(EnvVarSource {
+                    secret_key_ref: Some(SecretKeySelector {
+                        name: Some(key_ref.name.clone()),
+                        key: key_ref.key.clone(),
+                        optional: None,
+                    }),
+                    ..Default::default()
+                })),
+                _ => return Err(Error::Validation(format!(
+                    "Environment variable '{}' of step '{}' must set exactly one of value and valueFrom",
+                    var.name, step.name
+                ))),
+            };
+            Ok(EnvVar { name: var.name.clone(), value, value_from })
+        }).collect()
+    }
+
+    /// Wait for the pod to finish and return its logs. While it runs, its
+    /// logs are followed into `streamed_logs` and saved as the result of
+    /// the stored step `step_id`.
+    async fn wait_for_pod_completion(
+        &self,
+        pod_name: &str,
+        command: &str,
+        step_id: Option<Uuid>,
+        streamed_logs: Arc<std::sync::Mutex<String>>,
+    ) -> Result<String> {
+        // Following ends with the container, but the pod's outcome comes from the watch
+        let follow = async {
+            self.follow_pod_logs(pod_name, command, step_id, streamed_logs).await;
+            std::future::pending().await
+        };
+        tokio::select! {
+            result = self.watch_pod_completion(pod_name) => result,
+            never = follow => never,
+        }
+    }
+
+    /// Watch the pod until it finishes and return its logs. Watches the API
+    /// server closes are reopened, so only the step's timeout bounds this.
+    async fn watch_pod_completion(&self, pod_name: &str) -> Result<String> {
         let pods: Api<Pod> = Api::namespaced(self.client.clone(), &self.namespace);
         
-        // Watch for pod status changes
+        // Watch for pod status changes; the API server caps watches below 295s
         let wp = WatchParams::default()
             .fields(&format!("metadata.name={}", pod_name))
-            .timeout(300);
-
-        let mut stream = pods.watch(&wp, "0").await
-            .map_err(|e| Error::Kubernetes(e.to_string()))?
-            .boxed();
-
-        while let Some(event) = stream.try_next().await
-            .map_err(|e| Error::Kubernetes(e.to_string()))? {
-            
-            match event {
-                WatchEvent::Modified(pod) => {
-                    if let Some(status) = &pod.status {
-                        if let Some(phase) = &status.phase {
-                            match phase.as_str() {
-                                "Succeeded" => {
-                                    // Get logs
-                                    let logs = self.get_pod_logs(pod_name).await?;
-                                    return Ok(logs);
-                                }
-                                "Failed" => {
-                                    let logs = self.get_pod_logs(pod_name).await?;
-                                    return Err(Error::Execution(format!("Pod failed: {}", logs)));
-                                }
-                                _ => continue,
-                            }
-                        }
+            .timeout(290);
+
+        loop {
+            // Version "0" starts with the pod as it is, in case it already finished
+            let mut stream = pods.watch(&wp, "0").await
+                .map_err(|e| Error::Kubernetes(e.to_string()))?
+                .boxed();
+
+            while let Some(event) = stream.try_next().await
+                .map_err(|e| Error::Kubernetes(e.to_string()))? {
+                
+                let (WatchEvent::Added(pod) | WatchEvent::Modified(pod)) = event else {
+                    continue;
+                };
+                match pod.status.as_ref().and_then(|status| status.phase.as_deref()) {
+                    Some("Succeeded") => return self.get_pod_logs(pod_name).await,
+                    Some("Failed") => {
+                        let logs = self.get_pod_logs(pod_name).await?;
+                        return Err(Error::Execution(format!("Pod failed: {}", logs)));
                     }
+                    _ => continue,
                 }
-                _ => continue,
             }
+            debug!("Watch of pod {} ended, watching again", pod_name);
         }
+    }
 
-        Err(Error::Execution("Pod watch ended without completion".to_string()))
+    /// Follow the pod's logs into `logs` until its container exits, saving
+    /// them to the stored step at most every `LOG_SAVE_INTERVAL`. Logs can't
+    /// be followed until the container starts, so this retries until then.
+    async fn follow_pod_logs(
+        &self,
+        pod_name: &str,
+        command: &str,
+        step_id: Option<Uuid>,
+        logs: Arc<std::sync::Mutex<String>>,
+    ) {
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), &self.namespace);
+        let params = LogParams { follow: true, ..Default::default() };
+        
+        let stream = loop {
+            match pods.log_stream(pod_name, &params).await {
+                Ok(stream) => break stream,
+                Err(e) => {
+                    debug!("Logs of pod {} not available yet: {}", pod_name, e);
+                    tokio::time::sleep(LOG_FOLLOW_RETRY_INTERVAL).await;
+                }
+            }
+        };
+        
+        let save = |logs: String| async move {
+            let (Some(store), Some(step_id)) = (&self.store, step_id) else { return };
+            let result = serde_json::json!({ "stdout": logs, "command": command });
+            if let Err(e) = store.update_workflow_step_result(step_id, result).await {
+                warn!("Failed to save logs of pod {} to step {}: {}", pod_name, step_id, e);
+            }
+        };
+        
+        let mut lines = stream.lines();
+        let mut last_save: Option<std::time::Instant> = None;
+        loop {
+            match lines.try_next().await {
+                Ok(Some(line)) => {
+                    let snapshot = {
+                        let mut logs = logs.lock().unwrap();
+                        logs.push_str(&line);
+                        logs.push('\n');
+                        logs.clone()
+                    };
+                    if last_save.is_none_or(|saved| saved.elapsed() >= LOG_SAVE_INTERVAL) {
+                        save(snapshot).await;
+                        last_save = Some(std::time::Instant::now());
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Stopped following logs of pod {}: {}", pod_name, e);
+                    break;
+                }
+            }
+        }
+        let snapshot = logs.lock().unwrap().clone();
+        save(snapshot).await;
     }
 
     async fn get_pod_logs(&self, pod_name: &str) -> Result<String> {
@@ -389,27 +722,715 @@ impl StepExecutor {
     }
 
     fn evaluate_condition(&self, condition: &str, context: &WorkflowContext) -> Result<bool> {
-        // Simple condition evaluation
-        // Format: "path.to.value == expected" or "path.to.value != expected"
-        
-        let parts: Vec<&str> = condition.split_whitespace().collect();
-        if parts.len() != 3 {
-            return Err(Error::Validation(format!("Invalid condition format: {}", condition)));
-        }
-
-        let path = parts[0];
-        let operator = parts[1];
-        let expected = parts[2].trim_matches('"').trim_matches('\'');
+        let parsed = ConditionExpr::parse(condition)?;
 
         // Use Tera to evaluate the path
-        let path_template = format!("{{{{ {} }}}}", path);
+        let path_template = format!("{{{{ {} }}}}", parsed.path);
         let actual_value = self.render_template(&path_template, context)
             .unwrap_or_else(|_| String::new());
 
-        match operator {
-            "==" => Ok(actual_value == expected),
-            "!=" => Ok(actual_value != expected),
-            _ => Err(Error::Validation(format!("Unknown operator: {}", operator))),
+        parsed.evaluate(&actual_value)
+    }
+} 
+/// Add the step's failed attempts, if any, to its output
+fn with_failed_attempts(mut result: StepResult, failed_attempts: Vec<Value>) -> StepResult {
+    if let (false, Value::Object(output)) = (failed_attempts.is_empty(), &mut result.output) {
+        output.insert("failed_attempts".to_string(), Value::Array(failed_attempts));
+    }
+    result
+}
+
+/// The final attempt's error, followed by those of the attempts before it
+fn error_with_failed_attempts(error: Error, failed_attempts: &[Value]) -> Error {
+    if failed_attempts.is_empty() {
+        return error;
+    }
+    let earlier: Vec<String> = failed_attempts.iter()
+        .map(|attempt| format!("attempt {}: {}", attempt["attempt"], attempt["error"].as_str().unwrap_or_default()))
+        .collect();
+    Error::Execution(format!("{} (earlier failed attempts: {})", error, earlier.join("; ")))
+}
+
+/// Comparison operators of conditional steps
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ComparisonOperator {
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+/// A conditional step's `path op value` expression. The value may be
+/// quoted with `"` or `'` to hold spaces.
+#[derive(Debug, Clone, PartialEq)]
+struct ConditionExpr {
+    path: String,
+    operator: ComparisonOperator,
+    expected: String,
+}
+
+impl ConditionExpr {
+    fn parse(condition: &str) -> Result<Self> {
+        let tokens = condition_tokens(condition)?;
+        let mut tokens = tokens.into_iter();
+
+        let path = tokens.next()
+            .ok_or_else(|| Error::Validation("Condition is empty".to_string()))?;
+        let operator = tokens.next()
+            .ok_or_else(|| Error::Validation(format!("Condition '{}' is missing an operator after '{}'", condition, path)))?;
+        let operator = match operator.as_str() {
+            "==" => ComparisonOperator::Eq,
+            "!=" => ComparisonOperator::Ne,
+            ">" => ComparisonOperator::Gt,
+            "<" => ComparisonOperator::Lt,
+            ">=" => ComparisonOperator::Ge,
+            "<=" => ComparisonOperator::Le,
+            _ => return Err(Error::Validation(format!("Unknown operator '{}' in condition '{}'", operator, condition))),
+        };
+        let expected = tokens.next()
+            .ok_or_else(|| Error::Validation(format!("Condition '{}' is missing a value to compare '{}' with", condition, path)))?;
+        if let Some(extra) = tokens.next() {
+            return Err(Error::Validation(format!(
+                "Unexpected token '{}' in condition '{}'; quote values that contain spaces",
+                extra, condition
+            )));
         }
+
+        Ok(Self { path, operator, expected })
     }
-} 
\ No newline at end of file
+
+    /// Compare `actual` with the expected value. `==` and `!=` compare
+    /// numerically when both sides are numbers and as strings otherwise;
+    /// the ordering operators need numbers on both sides.
+    fn evaluate(&self, actual: &str) -> Result<bool> {
+        let numbers = (actual.trim().parse::<f64>(), self.expected.parse::<f64>());
+        match self.operator {
+            ComparisonOperator::Eq => Ok(match numbers {
+                (Ok(actual), Ok(expected)) => actual == expected,
+                _ => actual == self.expected,
+            }),
+            ComparisonOperator::Ne => Ok(match numbers {
+                (Ok(actual), Ok(expected)) => actual != expected,
+                _ => actual != self.expected,
+            }),
+            ordering => {
+                let (actual, expected) = match numbers {
+                    (Ok(actual), Ok(expected)) => (actual, expected),
+                    (Err(_), _) => return Err(Error::Validation(format!(
+                        "'{}' is '{}', which can't be compared as a number", self.path, actual
+                    ))),
+                    (_, Err(_)) => return Err(Error::Validation(format!(
+                        "'{}' is not a number, so '{}' can't be compared with it", self.expected, self.path
+                    ))),
+                };
+                Ok(match ordering {
+                    ComparisonOperator::Gt => actual > expected,
+                    ComparisonOperator::Lt => actual < expected,
+                    ComparisonOperator::Ge => actual >= expected,
+                    _ => actual <= expected,
+                })
+            }
+        }
+    }
+}
+
+/// Split a condition on whitespace, keeping quoted strings together
+/// without their quotes
+fn condition_tokens(condition: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = condition.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, ch)) if ch == c => break,
+                    Some((_, ch)) => token.push(ch),
+                    None => return Err(Error::Validation(format!(
+                        "Unterminated quote at '{}' in condition '{}'", &condition[start..], condition
+                    ))),
+                }
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&(_, ch)) = chars.peek() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                token.push(ch);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    Ok(tokens)
+}
+
+/// Environment variables for the given context metadata keys. Keys that
+/// aren't in the context are skipped; secret keys are only injected when
+/// they hold a Secret key reference, so their values never end up in the
+/// pod spec.
+fn context_env_vars(keys: &[String], context: &WorkflowContext) -> Vec<EnvVar> {
+    let mut env_vars = Vec::new();
+    
+    for key in keys {
+        let Some(value) = context.get_metadata(key) else {
+            warn!("Context metadata key '{}' not found, not setting it in the CLI pod", key);
+            continue;
+        };
+        let name = key.to_uppercase();
+        
+        if is_secret_key(key) {
+            match serde_json::from_value::<KeyRef>(value.clone()) {
+                Ok(key_ref) => env_vars.push(EnvVar {
+                    name,
+                    value_from: Some(EnvVarSource {
+                        secret_key_ref: Some(SecretKeySelector {
+                            name: Some(key_ref.name),
+                            key: key_ref.key,
+                            optional: None,
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                Err(_) => warn!("Context metadata key '{}' is not a Secret key reference, not setting it in the CLI pod", key),
+            }
+            continue;
+        }
+        
+        let value = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        env_vars.push(EnvVar {
+            name,
+            value: Some(value),
+            ..Default::default()
+        });
+    }
+    
+    env_vars
+}
+
+/// The pod `envFrom` source for a step's ConfigMap or Secret reference
+fn env_from_source(env_from: &EnvFromRef) -> EnvFromSource {
+    match env_from {
+        EnvFromRef::ConfigMapRef(config_map) => EnvFromSource {
+            config_map_ref: Some(ConfigMapEnvSource { name: Some(config_map.name.clone()), optional: None }),
+            ..Default::default()
+        },
+        EnvFromRef::SecretRef(secret) => EnvFromSource {
+            secret_ref: Some(SecretEnvSource { name: Some(secret.name.clone()), optional: None }),
+            ..Default::default()
+        },
+    }
+}
+
+/// CPU and memory quantities of a pod's requests or limits, filling unset
+/// ones with the defaults
+fn resource_list(
+    quantities: &ResourceQuantities,
+    default_cpu: &str,
+    default_memory: &str,
+) -> std::collections::BTreeMap<String, Quantity> {
+    let cpu = quantities.cpu.as_deref().unwrap_or(default_cpu);
+    let memory = quantities.memory.as_deref().unwrap_or(default_memory);
+    [
+        ("cpu".to_string(), Quantity(cpu.to_string())),
+        ("memory".to_string(), Quantity(memory.to_string())),
+    ].into_iter().collect()
+}
+
+/// Authenticate the PromQL tool with `PROMETHEUS_TOKEN`, or else
+/// `PROMETHEUS_USERNAME` and `PROMETHEUS_PASSWORD`, taken from the
+/// workflow's `configVars` or its runtime environment
+fn with_prometheus_auth(tool: PromQLTool, context: &WorkflowContext) -> PromQLTool {
+    let setting = |name: &str| context.vars.get(name).cloned().or_else(|| {
+        context.get_metadata(&format!("env_{}", name))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    });
+    
+    if let Some(token) = setting("PROMETHEUS_TOKEN") {
+        return tool.with_bearer_token(token);
+    }
+    match (setting("PROMETHEUS_USERNAME"), setting("PROMETHEUS_PASSWORD")) {
+        (Some(username), Some(password)) => tool.with_basic_auth(username, password),
+        _ => tool,
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["_secret", "_token", "_password"].iter().any(|suffix| key.ends_with(suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, http::{HeaderMap, StatusCode}, routing::post, Json, Router};
+
+    /// Pipeline trigger endpoint that echoes the request back
+    async fn mock_ci_server() -> String {
+        let app = Router::new().route("/pipelines/{name}/trigger", post(
+            |Path(name): Path<String>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer s3cr3t") {
+                    return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "unauthorized" })));
+                }
+                (StatusCode::CREATED, Json(serde_json::json!({ "pipeline": name, "ref": body["ref"], "id": 42 })))
+            },
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn http_step(base_url: &str, expected_status: Option<u16>) -> WorkflowStep {
+        serde_json::from_value(serde_json::json!({
+            "name": "trigger-rollback",
+            "type": "http",
+            "urlTemplate": format!("{}/pipelines/{{{{ input.service }}}}/trigger", base_url),
+            "method": "POST",
+            "headers": { "Authorization": "Bearer {{ vars.CI_TOKEN }}" },
+            "bodyTemplate": r#"{"ref": "{{ input.ref }}"}"#,
+            "expectedStatus": expected_status
+        })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_step() {
+        let base_url = mock_ci_server().await;
+        let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:9".parse().unwrap())).unwrap();
+        let executor = StepExecutor::new(client, "default".to_string());
+
+        let mut context = WorkflowContext::with_input(serde_json::json!({ "service": "checkout", "ref": "v1.2.3" }));
+        context.vars.insert("CI_TOKEN".to_string(), "s3cr3t".to_string());
+
+        let result = executor.execute_step(&http_step(&base_url, Some(201)), &context).await.unwrap();
+        assert_eq!(result.output["status"], 201);
+        assert_eq!(result.output["method"], "POST");
+        assert_eq!(result.output["body"], serde_json::json!({ "pipeline": "checkout", "ref": "v1.2.3", "id": 42 }));
+
+        // Any 2xx is accepted without expectedStatus, but a specific code must match
+        assert!(executor.execute_step(&http_step(&base_url, None), &context).await.is_ok());
+        let err = executor.execute_step(&http_step(&base_url, Some(200)), &context).await.unwrap_err();
+        assert!(err.to_string().contains("got status 201"), "{}", err);
+
+        context.vars.insert("CI_TOKEN".to_string(), "wrong".to_string());
+        let err = executor.execute_step(&http_step(&base_url, None), &context).await.unwrap_err();
+        assert!(err.to_string().contains("got status 401"), "{}", err);
+        assert!(err.to_string().contains("expected 2xx"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_step_retries() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use axum::routing::get;
+
+        // Fails the first two requests of each step, then succeeds
+        let requests = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/flaky", get(|axum::extract::State(requests): axum::extract::State<Arc<AtomicUsize>>| async move {
+                match requests.fetch_add(1, Ordering::SeqCst) % 3 {
+                    0 | 1 => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::OK,
+                }
+            }))
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:9".parse().unwrap())).unwrap();
+        let executor = StepExecutor::new(client, "default".to_string());
+        let step = |retries: u32| -> WorkflowStep {
+            serde_json::from_value(serde_json::json!({
+                "name": "flaky",
+                "type": "http",
+                "urlTemplate": format!("http://{}/flaky", addr),
+                "retries": retries,
+                "retryDelaySeconds": 0,
+            })).unwrap()
+        };
+
+        let result = executor.execute_step(&step(2), &WorkflowContext::new()).await.unwrap();
+        assert_eq!(result.output["status"], 200);
+        let failed_attempts = result.output["failed_attempts"].as_array().unwrap();
+        assert_eq!(failed_attempts.len(), 2);
+        assert_eq!(failed_attempts[1]["attempt"], 2);
+        assert!(failed_attempts[0]["error"].as_str().unwrap().contains("got status 503"));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // One retry isn't enough
+        let err = executor.execute_step(&step(1), &WorkflowContext::new()).await.unwrap_err();
+        assert!(err.to_string().contains("got status 503"), "{}", err);
+        assert!(err.to_string().contains("earlier failed attempts: attempt 1:"), "{}", err);
+        assert_eq!(requests.load(Ordering::SeqCst), 5);
+
+        // Invalid steps fail without retrying
+        let mut invalid = step(3);
+        invalid.url_template = None;
+        assert!(matches!(executor.execute_step(&invalid, &WorkflowContext::new()).await, Err(Error::Validation(_))));
+    }
+
+    /// Kubernetes API for one CLI step pod that runs for a second, logging
+    /// a line straight away and another as it finishes
+    async fn slow_pod_api() -> kube::Client {
+        use axum::{body::Body, extract::Query, routing::get};
+        use futures::stream;
+
+        let delayed = |delay: u64, chunk: String| stream::once(async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok::<_, std::convert::Infallible>(chunk)
+        });
+        let app = Router::new()
+            .route("/api/v1/namespaces/{namespace}/pods", get(move || async move {
+                let event = |phase: &str| format!("{}\n", serde_json::json!({
+                    "type": "MODIFIED",
+                    "object": { "apiVersion": "v1", "kind": "Pod", "metadata": {}, "status": { "phase": phase } }
+                }));
+                Body::from_stream(delayed(0, event("Running")).chain(delayed(1000, event("Succeeded"))))
+            }).post(|Json(pod): Json<Value>| async move { (StatusCode::CREATED, Json(pod)) }))
+            .route("/api/v1/namespaces/{namespace}/pods/{name}/log", get(move |Query(query): Query<std::collections::HashMap<String, String>>| async move {
+                if query.get("follow").map(String::as_str) == Some("true") {
+                    Body::from_stream(delayed(0, "checking\n".to_string()).chain(delayed(900, "done\n".to_string())))
+                } else {
+                    Body::from("checking\ndone\n")
+                }
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        kube::Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap()
+    }
+
+    /// Save a running workflow with a running CLI step named `check` and
+    /// return the step's id
+    async fn running_cli_step(store: &Arc<dyn Store>) -> Uuid {
+        use crate::store::{StepStatus, WorkflowStatus};
+
+        let now = chrono::Utc::now();
+        let workflow_id = Uuid::new_v4();
+        store.save_workflow(crate::store::Workflow {
+            id: workflow_id,
+            name: "triage".to_string(),
+            namespace: "default".to_string(),
+            trigger_source: None,
+            status: WorkflowStatus::Running,
+            steps_completed: 0,
+            total_steps: 1,
+            current_step: None,
+            input_context: None,
+            outputs: None,
+            error: None,
+            started_at: now,
+            completed_at: None,
+            created_at: now,
+        }).await.unwrap();
+        let step_id = Uuid::new_v4();
+        store.save_workflow_step(crate::store::WorkflowStep {
+            id: step_id,
+            workflow_id,
+            name: "check".to_string(),
+            step_type: crate::store::StepType::Cli,
+            status: StepStatus::Running,
+            config: None,
+            started_at: Some(now),
+            completed_at: None,
+            result: None,
+            error: None,
+            created_at: now,
+        }).await.unwrap();
+        step_id
+    }
+
+    #[tokio::test]
+    async fn test_cli_step_streams_logs() {
+        let store: Arc<dyn Store> = Arc::new(crate::store::SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let step_id = running_cli_step(&store).await;
+
+        let executor = Arc::new(StepExecutor::new(slow_pod_api().await, "default".to_string()).with_store(store.clone()));
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "name": "check",
+            "type": "cli",
+            "command": "./check.sh",
+        })).unwrap();
+        let mut context = WorkflowContext::new();
+        context.current_step_id = Some(step_id);
+        let running = tokio::spawn({
+            let executor = executor.clone();
+            async move { executor.execute_step(&step, &context).await }
+        });
+
+        // The first line is saved to the step while the pod still runs
+        let mut saved = None;
+        for _ in 0..40 {
+            saved = store.get_workflow_step(step_id).await.unwrap().and_then(|step| step.result);
+            if saved.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!running.is_finished());
+        assert_eq!(saved.unwrap(), serde_json::json!({ "stdout": "checking\n", "command": "./check.sh" }));
+
+        let result = running.await.unwrap().unwrap();
+        assert!(result.success);
+        assert_eq!(result.output["stdout"], "checking\ndone\n");
+        let saved = store.get_workflow_step(step_id).await.unwrap().unwrap().result.unwrap();
+        assert_eq!(saved["stdout"], "checking\ndone\n");
+    }
+
+    /// Pods created and names of pods deleted through `finished_pod_api`
+    #[derive(Default)]
+    struct PodRequests {
+        created: Vec<Value>,
+        deleted: Vec<String>,
+    }
+
+    /// Kubernetes API for CLI step pods that finish in `phase` straight
+    /// away, recording the pods created and deleted
+    async fn finished_pod_api(phase: &'static str) -> (kube::Client, Arc<std::sync::Mutex<PodRequests>>) {
+        use axum::routing::get;
+
+        let requests = Arc::new(std::sync::Mutex::new(PodRequests::default()));
+        let app = Router::new()
+            .route("/api/v1/namespaces/{namespace}/pods", get(move || async move {
+                format!("{}\n", serde_json::json!({
+                    "type": "MODIFIED",
+                    "object": { "apiVersion": "v1", "kind": "Pod", "metadata": {}, "status": { "phase": phase } }
+                }))
+            }).post({
+                let requests = requests.clone();
+                move |Json(pod): Json<Value>| async move {
+                    requests.lock().unwrap().created.push(pod.clone());
+                    (StatusCode::CREATED, Json(pod))
+                }
+            }))
+            .route("/api/v1/namespaces/{namespace}/pods/{name}", axum::routing::delete({
+                let requests = requests.clone();
+                move |Path((_, name)): Path<(String, String)>| async move {
+                    requests.lock().unwrap().deleted.push(name.clone());
+                    Json(serde_json::json!({ "apiVersion": "v1", "kind": "Pod", "metadata": { "name": name } }))
+                }
+            }))
+            .route("/api/v1/namespaces/{namespace}/pods/{name}/log", get(|| async { "output\n" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = kube::Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap();
+        (client, requests)
+    }
+
+    #[tokio::test]
+    async fn test_cli_pod_cleanup() {
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "name": "check",
+            "type": "cli",
+            "command": "./check.sh",
+        })).unwrap();
+
+        // Pods of succeeded steps are deleted
+        let (client, requests) = finished_pod_api("Succeeded").await;
+        let executor = StepExecutor::new(client, "default".to_string()).with_keep_failed_pods(true);
+        let result = executor.execute_step(&step, &WorkflowContext::new()).await.unwrap();
+        assert!(result.success);
+        let deleted = requests.lock().unwrap().deleted.clone();
+        assert_eq!(deleted.len(), 1);
+        assert!(deleted[0].starts_with("workflow-cli-check-"), "{}", deleted[0]);
+
+        // Pods of failed steps are deleted unless they're kept
+        let (client, requests) = finished_pod_api("Failed").await;
+        let executor = StepExecutor::new(client, "default".to_string());
+        assert!(!executor.execute_step(&step, &WorkflowContext::new()).await.unwrap().success);
+        assert_eq!(requests.lock().unwrap().deleted.len(), 1);
+
+        let (client, requests) = finished_pod_api("Failed").await;
+        let executor = StepExecutor::new(client, "default".to_string()).with_keep_failed_pods(true);
+        assert!(!executor.execute_step(&step, &WorkflowContext::new()).await.unwrap().success);
+        assert!(requests.lock().unwrap().deleted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cli_step_secret_env() {
+        let store: Arc<dyn Store> = Arc::new(crate::store::SqliteStore::new("sqlite::memory:").await.unwrap());
+        store.init().await.unwrap();
+        let step_id = running_cli_step(&store).await;
+
+        let (client, requests) = finished_pod_api("Succeeded").await;
+        let executor = StepExecutor::new(client, "default".to_string()).with_store(store.clone());
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "name": "check",
+            "type": "cli",
+            "command": "./check.sh --cluster {{ input.cluster }}",
+            "env": [
+                { "name": "REGION", "value": "{{ input.region }}" },
+                { "name": "DB_PASSWORD", "valueFrom": { "secretKeyRef": { "name": "db", "key": "password" } } },
+            ],
+            "envFrom": [{ "secretRef": { "name": "api-tokens" } }],
+        })).unwrap();
+        let mut context = WorkflowContext::with_input(serde_json::json!({ "cluster": "prod", "region": "eu-west-1" }));
+        context.current_step_id = Some(step_id);
+        let result = executor.execute_step(&step, &context).await.unwrap();
+        assert!(result.success);
+
+        // The pod reads the secret itself
+        let pod: Pod = serde_json::from_value(requests.lock().unwrap().created[0].clone()).unwrap();
+        let container = &pod.spec.unwrap().containers[0];
+        let env = container.env.clone().unwrap();
+        assert_eq!(env[0].name, "REGION");
+        assert_eq!(env[0].value.as_deref(), Some("eu-west-1"));
+        assert_eq!(env[1].name, "DB_PASSWORD");
+        assert_eq!(env[1].value, None);
+        let secret_ref = env[1].value_from.as_ref().unwrap().secret_key_ref.as_ref().unwrap();
+        assert_eq!((secret_ref.name.as_deref(), secret_ref.key.as_str()), (Some("db"), "password"));
+        let env_from = container.env_from.clone().unwrap();
+        assert_eq!(env_from[0].secret_ref.as_ref().unwrap().name.as_deref(), Some("api-tokens"));
+
+        // Neither the returned nor the stored output mention it
+        let stored = store.get_workflow_step(step_id).await.unwrap().unwrap().result.unwrap();
+        for output in [&result.output, &stored] {
+            assert_eq!(output["command"], "./check.sh --cluster prod");
+            let output = output.to_string();
+            assert!(!output.contains("DB_PASSWORD") && !output.contains("password"), "{}", output);
+        }
+
+        // A variable must have exactly one source
+        let mut invalid = step.clone();
+        invalid.env[0].value_from = invalid.env[1].value_from.clone();
+        assert!(matches!(executor.execute_step(&invalid, &context).await, Err(Error::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cli_pod_env_from_context() {
+        let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:9".parse().unwrap())).unwrap();
+        let executor = StepExecutor::new(client, "default".to_string());
+
+        let mut context = WorkflowContext::new();
+        context.add_metadata("cluster_name", serde_json::json!("prod-eu"));
+        context.add_metadata("replicas", serde_json::json!(3));
+        context.add_metadata("grafana_token", serde_json::json!({ "name": "grafana", "key": "token" }));
+        context.add_metadata("db_password", serde_json::json!("hunter2"));
+
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "name": "env",
+            "type": "cli",
+            "command": "env",
+            "envFromContext": ["cluster_name", "replicas", "grafana_token", "db_password", "missing"],
+        })).unwrap();
+        let pod = executor.create_cli_pod("cli", "busybox:latest", "env", &step, &context).unwrap();
+        let env = pod.spec.unwrap().containers[0].env.clone().unwrap();
+
+        let names: Vec<&str> = env.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["CLUSTER_NAME", "REPLICAS", "GRAFANA_TOKEN"]);
+        assert_eq!(env[0].value.as_deref(), Some("prod-eu"));
+        assert_eq!(env[1].value.as_deref(), Some("3"));
+
+        // Secrets are referenced, never inlined; a plain secret value is skipped
+        assert_eq!(env[2].value, None);
+        let secret_ref = env[2].value_from.as_ref().unwrap().secret_key_ref.as_ref().unwrap();
+        assert_eq!(secret_ref.name.as_deref(), Some("grafana"));
+        assert_eq!(secret_ref.key, "token");
+    }
+
+    #[tokio::test]
+    async fn test_cli_pod_resources() {
+        let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:9".parse().unwrap())).unwrap();
+        let executor = StepExecutor::new(client, "default".to_string());
+        let quantities = |list: Option<std::collections::BTreeMap<String, Quantity>>| {
+            let list = list.unwrap();
+            (list["cpu"].0.clone(), list["memory"].0.clone())
+        };
+
+        // Unset resources get the defaults and no service account is set
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "name": "check",
+            "type": "cli",
+            "command": "kubectl get pods",
+        })).unwrap();
+        let spec = executor.create_cli_pod("cli", "bitnami/kubectl", "kubectl get pods", &step, &WorkflowContext::new())
+            .unwrap().spec.unwrap();
+        let resources = spec.containers[0].resources.clone().unwrap();
+        assert_eq!(quantities(resources.requests), ("100m".to_string(), "128Mi".to_string()));
+        assert_eq!(quantities(resources.limits), ("500m".to_string(), "512Mi".to_string()));
+        assert_eq!(spec.service_account_name, None);
+
+        // Declared quantities override only the defaults they replace
+        let step: WorkflowStep = serde_json::from_value(serde_json::json!({
+            "name": "check",
+            "type": "cli",
+            "command": "kubectl get pods",
+            "serviceAccountName": "workflow-reader",
+            "resources": {
+                "requests": { "memory": "64Mi" },
+                "limits": { "cpu": "1", "memory": "1Gi" },
+            },
+        })).unwrap();
+        let spec = executor.create_cli_pod("cli", "bitnami/kubectl", "kubectl get pods", &step, &WorkflowContext::new())
+            .unwrap().spec.unwrap();
+        let resources = spec.containers[0].resources.clone().unwrap();
+        assert_eq!(quantities(resources.requests), ("100m".to_string(), "64Mi".to_string()));
+        assert_eq!(quantities(resources.limits), ("1".to_string(), "1Gi".to_string()));
+        assert_eq!(spec.service_account_name.as_deref(), Some("workflow-reader"));
+    }
+
+    #[test]
+    fn test_numeric_conditions() {
+        let cases = [
+            ("confidence > 0.8", "0.93", true),
+            ("confidence > 0.8", "0.8", false),
+            ("confidence >= 0.8", "0.8", true),
+            ("restarts < 10", "9", true),
+            ("restarts <= 10", "11", false),
+            ("replicas == 3", "3.0", true),
+            ("replicas != 3", "3", false),
+        ];
+        for (condition, actual, expected) in cases {
+            let parsed = ConditionExpr::parse(condition).unwrap();
+            assert_eq!(parsed.evaluate(actual).unwrap(), expected, "{} with {}", condition, actual);
+        }
+
+        let err = ConditionExpr::parse("confidence > 0.8").unwrap().evaluate("high").unwrap_err();
+        assert!(err.to_string().contains("'confidence' is 'high'"), "{}", err);
+        let err = ConditionExpr::parse("confidence > high").unwrap().evaluate("0.9").unwrap_err();
+        assert!(err.to_string().contains("'high' is not a number"), "{}", err);
+    }
+
+    #[test]
+    fn test_quoted_condition_values() {
+        let parsed = ConditionExpr::parse(r#"input.summary == "disk pressure on node""#).unwrap();
+        assert_eq!(parsed.expected, "disk pressure on node");
+        assert!(parsed.evaluate("disk pressure on node").unwrap());
+        assert!(!parsed.evaluate("disk pressure").unwrap());
+
+        let parsed = ConditionExpr::parse("input.severity != 'not critical'").unwrap();
+        assert_eq!(parsed.operator, ComparisonOperator::Ne);
+        assert!(parsed.evaluate("critical").unwrap());
+    }
+
+    #[test]
+    fn test_malformed_conditions() {
+        let cases = [
+            ("", "Condition is empty"),
+            ("confidence", "missing an operator after 'confidence'"),
+            ("confidence >", "missing a value to compare 'confidence'"),
+            ("confidence ~= 0.8", "Unknown operator '~='"),
+            ("input.summary == disk pressure", "Unexpected token 'pressure'"),
+            ("input.summary == \"disk pressure", "Unterminated quote at '\"disk pressure'"),
+        ];
+        for (condition, message) in cases {
+            let err = ConditionExpr::parse(condition).unwrap_err();
+            assert!(matches!(err, Error::Validation(_)), "{}", condition);
+            assert!(err.to_string().contains(message), "{}: {}", condition, err);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{crd::{StepType, WorkflowStep}, Error, Result};
+
+/// The dependencies between a workflow's steps, by index into its steps.
+///
+/// Steps name the steps they wait for in `dependsOn`. A workflow where no
+/// step declares `dependsOn` runs its steps one after another, as if each
+/// depended on the one before it.
+///
+/// The steps in a conditional step's `thenSteps` and `elseSteps` depend on
+/// it too, whether or not the workflow declares `dependsOn`.
+#[derive(Debug, Clone)]
+pub struct StepGraph {
+    dependencies: Vec<Vec<usize>>,
+    dependents: Vec<Vec<usize>>,
+    order: Vec<usize>,
+    /// The `thenSteps` and `elseSteps` of each step
+    branches: Vec<(Vec<usize>, Vec<usize>)>,
+}
+
+impl StepGraph {
+    /// Build the graph of the steps, failing with `Error::Validation` on
+    /// duplicate step names, unknown dependencies or branch steps, branches
+    /// of steps that aren't conditional, and cycles
+    pub fn new(steps: &[WorkflowStep]) -> Result<Self> {
+        let mut indices = HashMap::new();
+        for (idx, step) in steps.iter().enumerate() {
+            if indices.insert(step.name.as_str(), idx).is_some() {
+                return Err(Error::Validation(format!("Duplicate step name '{}'", step.name)));
+            }
+        }
+
+        let sequential = steps.iter().all(|step| step.depends_on.is_empty());
+        let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); steps.len()];
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); steps.len()];
+        let mut add_dependency = |idx: usize, dependency: usize| {
+            if !dependencies[idx].contains(&dependency) {
+                dependencies[idx].push(dependency);
+                dependents[dependency].push(idx);
+            }
+        };
+        for (idx, step) in steps.iter().enumerate() {
+            if sequential {
+                if idx > 0 {
+                    add_dependency(idx, idx - 1);
+                }
+                continue;
+            }
+            for name in &step.depends_on {
+                let dependency = *indices.get(name.as_str()).ok_or_else(|| Error::Validation(format!(
+                    "Step '{}' depends on unknown step '{}'", step.name, name
+                )))?;
+                add_dependency(idx, dependency);
+            }
+        }
+
+        let mut branches = Vec::with_capacity(steps.len());
+        for (idx, step) in steps.iter().enumerate() {
+            let has_branches = !step.then_steps.is_empty() || !step.else_steps.is_empty();
+            if has_branches && !matches!(step.step_type, StepType::Conditional) {
+                return Err(Error::Validation(format!(
+                    "Step '{}' declares thenSteps or elseSteps but isn't a conditional step", step.name
+                )));
+            }
+            let mut branch = |names: &[String]| -> Result<Vec<usize>> {
+                names.iter()
+                    .map(|name| {
+                        let target = *indices.get(name.as_str()).ok_or_else(|| Error::Validation(format!(
+                            "Step '{}' branches to unknown step '{}'", step.name, name
+                        )))?;
+                        if step.then_steps.contains(name) && step.else_steps.contains(name) {
+                            return Err(Error::Validation(format!(
+                                "Step '{}' is in both thenSteps and elseSteps of step '{}'", name, step.name
+                            )));
+                        }
+                        add_dependency(target, idx);
+                        Ok(target)
+                    })
+                    .collect()
+            };
+            branches.push((branch(&step.then_steps)?, branch(&step.else_steps)?));
+        }
+
+        // Kahn's algorithm, keeping the declared order among ready steps
+        let mut remaining: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+        let mut ready: VecDeque<usize> = (0..steps.len()).filter(|&idx| remaining[idx] == 0).collect();
+        let mut order = Vec::with_capacity(steps.len());
+        while let Some(idx) = ready.pop_front() {
+            order.push(idx);
+            for &dependent in &dependents[idx] {
+                remaining[dependent] -= 1;
+                if remaining[dependent] == 0 {
+                    ready.push_back(dependent);
+                }
+            }
+        }
+
+        if order.len() < steps.len() {
+            let cyclic: Vec<&str> = (0..steps.len())
+                .filter(|&idx| remaining[idx] > 0)
+                .map(|idx| steps[idx].name.as_str())
+                .collect();
+            return Err(Error::Validation(format!(
+                "Step dependencies contain a cycle between: {}",
+                cyclic.join(", ")
+            )));
+        }
+
+        Ok(Self { dependencies, dependents, order, branches })
+    }
+
+    /// The steps the step waits for
+    pub fn dependencies(&self, idx: usize) -> &[usize] {
+        &self.dependencies[idx]
+    }
+
+    /// The branch steps of a conditional step that don't run, given whether
+    /// its condition held
+    pub fn branch_not_taken(&self, idx: usize, condition_met: bool) -> &[usize] {
+        let (then_steps, else_steps) = &self.branches[idx];
+        if condition_met { else_steps } else { then_steps }
+    }
+
+    /// The steps, in an order where every step comes after its dependencies
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// The steps that depend on the step, directly or through other steps
+    pub fn transitive_dependents(&self, idx: usize) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        let mut pending = vec![idx];
+        while let Some(current) = pending.pop() {
+            for &dependent in &self.dependents[current] {
+                if seen.insert(dependent) {
+                    pending.push(dependent);
+                }
+            }
+        }
+        let mut dependents: Vec<usize> = seen.into_iter().collect();
+        dependents.sort_unstable();
+        dependents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn steps(deps: &[(&str, &[&str])]) -> Vec<WorkflowStep> {
+        deps.iter()
+            .map(|(name, depends_on)| serde_json::from_value(json!({
+                "name": name,
+                "type": "cli",
+                "command": "true",
+                "dependsOn": depends_on,
+            })).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_sequential_by_default() {
+        let graph = StepGraph::new(&steps(&[("a", &[]), ("b", &[]), ("c", &[])])).unwrap();
+        assert_eq!(graph.order(), &[0, 1, 2]);
+        assert_eq!(graph.dependencies(0), &[] as &[usize]);
+        assert_eq!(graph.dependencies(2), &[1]);
+        assert_eq!(graph.transitive_dependents(0), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_diamond() {
+        let graph = StepGraph::new(&steps(&[
+            ("merge", &["left", "right"]),
+            ("left", &["root"]),
+            ("right", &["root"]),
+            ("root", &[]),
+        ])).unwrap();
+        assert_eq!(graph.order(), &[3, 1, 2, 0]);
+        assert_eq!(graph.dependencies(0), &[1, 2]);
+        assert_eq!(graph.transitive_dependents(3), vec![0, 1, 2]);
+        assert_eq!(graph.transitive_dependents(1), vec![0]);
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let err = StepGraph::new(&steps(&[("a", &["c"]), ("b", &["a"]), ("c", &["b"]), ("d", &[])])).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+        assert!(err.to_string().contains("cycle between: a, b, c"), "{}", err);
+
+        let err = StepGraph::new(&steps(&[("a", &["a"])])).unwrap_err();
+        assert!(err.to_string().contains("cycle between: a"), "{}", err);
+
+        let err = StepGraph::new(&steps(&[("a", &[]), ("b", &["missing"])])).unwrap_err();
+        assert!(err.to_string().contains("unknown step 'missing'"), "{}", err);
+
+        let err = StepGraph::new(&steps(&[("a", &[]), ("a", &[])])).unwrap_err();
+        assert!(err.to_string().contains("Duplicate step name 'a'"), "{}", err);
+    }
+
+    #[test]
+    fn test_branches() {
+        let mut workflow = steps(&[("collect", &[]), ("restart", &[]), ("page", &[]), ("report", &[])]);
+        workflow.insert(1, serde_json::from_value(json!({
+            "name": "check",
+            "type": "conditional",
+            "condition": "outputs.collect.status == 200",
+            "thenSteps": ["restart"],
+            "elseSteps": ["page"],
+        })).unwrap());
+        let graph = StepGraph::new(&workflow).unwrap();
+        assert_eq!(graph.branch_not_taken(1, true), &[3]);
+        assert_eq!(graph.branch_not_taken(1, false), &[2]);
+        assert_eq!(graph.branch_not_taken(0, true), &[] as &[usize]);
+
+        // Branch steps wait for their conditional step without dependsOn too
+        workflow[4].depends_on = vec!["restart".to_string(), "page".to_string()];
+        let graph = StepGraph::new(&workflow).unwrap();
+        assert_eq!(graph.dependencies(2), &[1]);
+        assert_eq!(graph.dependencies(3), &[1]);
+        assert_eq!(graph.order(), &[0, 1, 2, 3, 4]);
+
+        workflow[1].else_steps = vec!["restart".to_string()];
+        let err = StepGraph::new(&workflow).unwrap_err();
+        assert!(err.to_string().contains("'restart' is in both thenSteps and elseSteps"), "{}", err);
+
+        workflow[1].else_steps = vec!["missing".to_string()];
+        let err = StepGraph::new(&workflow).unwrap_err();
+        assert!(err.to_string().contains("branches to unknown step 'missing'"), "{}", err);
+
+        workflow[0].then_steps = vec!["page".to_string()];
+        let err = StepGraph::new(&workflow).unwrap_err();
+        assert!(err.to_string().contains("Step 'collect' declares thenSteps or elseSteps"), "{}", err);
+    }
+}
@@ -0,0 +1,208 @@
+//! Workflow lifecycle hooks
+//!
+//! Hooks are notified when steps start and end and when a workflow completes.
+//! They run concurrently and a failing hook never aborts the workflow.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{
+    crd::{Workflow, WorkflowStep},
+    metrics::WORKFLOW_STEP_DURATION_SECONDS,
+    workflow::{WorkflowContext, WorkflowState},
+    Result,
+};
+
+/// How a step finished
+#[derive(Debug, Clone)]
+pub struct StepOutcome {
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+impl StepOutcome {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[async_trait]
+pub trait WorkflowHook: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn on_step_start(&self, _step: &WorkflowStep, _context: &WorkflowContext) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_step_end(&self, _step: &WorkflowStep, _outcome: &StepOutcome, _context: &WorkflowContext) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_workflow_complete(&self, _workflow: &Workflow, _state: WorkflowState, _outputs: &Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub type WorkflowHooks = Vec<Box<dyn WorkflowHook + Send + Sync>>;
+
+/// Notify all hooks that a step is starting
+pub async fn notify_step_start(hooks: &WorkflowHooks, step: &WorkflowStep, context: &WorkflowContext) {
+    let results = join_all(hooks.iter().map(|hook| hook.on_step_start(step, context))).await;
+    log_failures(hooks, results, "on_step_start");
+}
+
+/// Notify all hooks that a step has finished
+pub async fn notify_step_end(hooks: &WorkflowHooks, step: &WorkflowStep, outcome: &StepOutcome, context: &WorkflowContext) {
+    let results = join_all(hooks.iter().map(|hook| hook.on_step_end(step, outcome, context))).await;
+    log_failures(hooks, results, "on_step_end");
+}
+
+/// Notify all hooks that a workflow has reached a terminal state
+pub async fn notify_workflow_complete(hooks: &WorkflowHooks, workflow: &Workflow, state: WorkflowState, outputs: &Value) {
+    let results = join_all(hooks.iter().map(|hook| hook.on_workflow_complete(workflow, state, outputs))).await;
+    log_failures(hooks, results, "on_workflow_complete");
+}
+
+fn log_failures(hooks: &WorkflowHooks, results: Vec<Result<()>>, event: &str) {
+    for (hook, result) in hooks.iter().zip(results) {
+        if let Err(e) = result {
+            warn!("Workflow hook '{}' failed during {}: {}", hook.name(), event, e);
+        }
+    }
+}
+
+/// Records step durations in the `workflow_step_duration_seconds` histogram
+pub struct MetricsHook;
+
+#[async_trait]
+impl WorkflowHook for MetricsHook {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    async fn on_step_end(&self, step: &WorkflowStep, outcome: &StepOutcome, _context: &WorkflowContext) -> Result<()> {
+        let step_type = format!("{:?}", step.step_type).to_lowercase();
+        let status = if outcome.succeeded() { "succeeded" } else { "failed" };
+        WORKFLOW_STEP_DURATION_SECONDS
+            .with_label_values(&[&step_type, status])
+            .observe(outcome.duration.as_secs_f64());
+        Ok(())
+    }
+}
+
+/// Emits a structured log line for every lifecycle event
+pub struct LoggingHook;
+
+#[async_trait]
+impl WorkflowHook for LoggingHook {
+    fn name(&self) -> &str {
+        "logging"
+    }
+
+    async fn on_step_start(&self, step: &WorkflowStep, _context: &WorkflowContext) -> Result<()> {
+        info!(event = "step_start", step = %step.name, step_type = ?step.step_type, "Workflow step started");
+        Ok(())
+    }
+
+    async fn on_step_end(&self, step: &WorkflowStep, outcome: &StepOutcome, _context: &WorkflowContext) -> Result<()> {
+        info!(
+            event = "step_end",
+            step = %step.name,
+            step_type = ?step.step_type,
+            succeeded = outcome.succeeded(),
+            duration_ms = outcome.duration.as_millis() as u64,
+            error = outcome.error.as_deref().unwrap_or(""),
+            "Workflow step finished"
+        );
+        Ok(())
+    }
+
+    async fn on_workflow_complete(&self, workflow: &Workflow, state: WorkflowState, _outputs: &Value) -> Result<()> {
+        info!(
+            event = "workflow_complete",
+            workflow = workflow.metadata.name.as_deref().unwrap_or("unnamed-workflow"),
+            namespace = workflow.metadata.namespace.as_deref().unwrap_or("default"),
+            state = %state,
+            "Workflow completed"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountingHook {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl WorkflowHook for CountingHook {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn on_step_start(&self, _step: &WorkflowStep, _context: &WorkflowContext) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    struct FailingHook;
+
+    #[async_trait]
+    impl WorkflowHook for FailingHook {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn on_step_start(&self, _step: &WorkflowStep, _context: &WorkflowContext) -> Result<()> {
+            Err(Error::Internal("hook unavailable".to_string()))
+        }
+    }
+
+    fn test_step() -> WorkflowStep {
+        serde_json::from_value(serde_json::json!({
+            "name": "check-pods",
+            "type": "cli",
+            "command": "kubectl get pods"
+        })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_failing_hook_does_not_block_others() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let hooks: WorkflowHooks = vec![
+            Box::new(FailingHook),
+            Box::new(CountingHook { calls: calls.clone() }),
+            Box::new(LoggingHook),
+        ];
+
+        notify_step_start(&hooks, &test_step(), &WorkflowContext::new()).await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_hook_records_duration() {
+        let hooks: WorkflowHooks = vec![Box::new(MetricsHook)];
+        let outcome = StepOutcome {
+            duration: Duration::from_millis(250),
+            error: Some("exit code 1".to_string()),
+        };
+        let histogram = WORKFLOW_STEP_DURATION_SECONDS.with_label_values(&["cli", "failed"]);
+        let before = histogram.get_sample_count();
+
+        notify_step_end(&hooks, &test_step(), &outcome, &WorkflowContext::new()).await;
+
+        assert_eq!(histogram.get_sample_count(), before + 1);
+    }
+}
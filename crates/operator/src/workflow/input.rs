@@ -0,0 +1,71 @@
+//! Workflow input validation
+//!
+//! Checks the source event data that triggered a workflow against the
+//! `inputSchema` declared on its `WorkflowSpec`.
+
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+/// Validate `input` against a JSON Schema.
+///
+/// Returns one message per violation, prefixed with the JSON pointer of the
+/// offending value. An invalid schema is reported as a single error.
+pub fn validate_input(schema: &Value, input: &Value) -> Result<(), Vec<String>> {
+    let compiled = JSONSchema::compile(schema)
+        .map_err(|e| vec![format!("Invalid input schema: {}", e)])?;
+
+    compiled.validate(input).map_err(|errors| {
+        errors
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                if path.is_empty() {
+                    e.to_string()
+                } else {
+                    format!("{}: {}", path, e)
+                }
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["alerts"],
+            "properties": {
+                "alerts": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "object",
+                        "required": ["labels"],
+                        "properties": {
+                            "labels": {
+                                "type": "object",
+                                "required": ["namespace"]
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_validate_input() {
+        let valid = json!({ "alerts": [{ "labels": { "namespace": "prod" } }] });
+        assert!(validate_input(&schema(), &valid).is_ok());
+
+        let invalid = json!({ "alerts": [{ "labels": {} }] });
+        let errors = validate_input(&schema(), &invalid).unwrap_err();
+        assert_eq!(errors, vec!["/alerts/0/labels: \"namespace\" is a required property".to_string()]);
+
+        let errors = validate_input(&json!({ "type": 12 }), &valid).unwrap_err();
+        assert!(errors[0].starts_with("Invalid input schema"));
+    }
+}
@@ -2,8 +2,22 @@ pub mod engine;
 pub mod executor;
 pub mod context;
 pub mod state;
+pub mod outputs;
+pub mod hooks;
+pub mod diff;
+pub mod graph;
+pub mod input;
+pub mod vars;
+pub mod transform;
 
-pub use engine::WorkflowEngine;
+pub use engine::{WorkflowCompletionEvent, WorkflowEngine};
 pub use executor::{StepExecutor, StepResult};
 pub use context::WorkflowContext;
-pub use state::WorkflowState; 
\ No newline at end of file
+pub use state::WorkflowState;
+pub use outputs::{extract_outputs, ExtractedOutputs};
+pub use diff::{diff_json, json_patch, PatchOperation, ValueChange, WorkflowDiff};
+pub use graph::StepGraph;
+pub use input::validate_input;
+pub use vars::resolve_config_vars;
+pub use transform::apply_transform;
+pub use hooks::{LoggingHook, MetricsHook, StepOutcome, WorkflowHook, WorkflowHooks}; 
\ No newline at end of file
@@ -0,0 +1,156 @@
+//! Workflow output extraction
+//!
+//! Resolves the `outputs` declared on a `WorkflowSpec` against the results of
+//! completed steps.
+
+use std::collections::HashMap;
+use serde_json::{Map, Value};
+
+use crate::crd::OutputDef;
+
+/// Outputs extracted from step results
+#[derive(Debug, Default)]
+pub struct ExtractedOutputs {
+    /// Output values keyed by output name
+    pub values: Map<String, Value>,
+    /// Names of required outputs that could not be extracted
+    pub missing_required: Vec<String>,
+}
+
+/// Extract declared outputs from step results.
+///
+/// Definitions without `fromStep` are skipped. Optional outputs that cannot be
+/// resolved are omitted; required ones are reported in `missing_required`.
+pub fn extract_outputs(defs: &[OutputDef], step_outputs: &HashMap<String, Value>) -> ExtractedOutputs {
+    let mut extracted = ExtractedOutputs::default();
+
+    for def in defs {
+        let Some(step) = def.from_step.as_deref() else {
+            continue;
+        };
+
+        let value = step_outputs.get(step).and_then(|output| {
+            match def.json_path.as_deref() {
+                Some(path) => select_json_path(output, path),
+                None => Some(output.clone()),
+            }
+        });
+
+        match value {
+            Some(v) if !v.is_null() => {
+                extracted.values.insert(def.name.clone(), v);
+            }
+            _ if def.required => extracted.missing_required.push(def.name.clone()),
+            _ => {}
+        }
+    }
+
+    extracted
+}
+
+/// Select a value using a simple JSON path such as `$.result.findings[0].description`.
+///
+/// Supports dotted keys and numeric array indexes; the leading `$` is optional.
+pub fn select_json_path(value: &Value, path: &str) -> Option<Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(pos) => (&segment[..pos], &segment[pos..]),
+            None => (segment, ""),
+        };
+
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+
+        for index in indexes.split('[').filter(|s| !s.is_empty()) {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            current = current.get(index)?;
+        }
+    }
+
+    Some(current.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn output_def(name: &str, from_step: &str, json_path: &str, required: bool) -> OutputDef {
+        OutputDef {
+            name: name.to_string(),
+            value: None,
+            from_step: Some(from_step.to_string()),
+            json_path: Some(json_path.to_string()),
+            required,
+        }
+    }
+
+    fn step_outputs() -> HashMap<String, Value> {
+        let mut outputs = HashMap::new();
+        outputs.insert("investigate".to_string(), json!({
+            "result": {
+                "summary": "Pod OOMKilled",
+                "findings": [{ "description": "memory limit too low" }]
+            }
+        }));
+        outputs
+    }
+
+    #[test]
+    fn test_select_json_path() {
+        let value = json!({ "a": { "b": [1, { "c": "d" }] } });
+        assert_eq!(select_json_path(&value, "$.a.b[1].c"), Some(json!("d")));
+        assert_eq!(select_json_path(&value, "a.b[0]"), Some(json!(1)));
+        assert_eq!(select_json_path(&value, "$"), Some(value.clone()));
+        assert_eq!(select_json_path(&value, "$.a.missing"), None);
+        assert_eq!(select_json_path(&value, "$.a.b[5]"), None);
+    }
+
+    #[test]
+    fn test_extract_outputs_present() {
+        let defs = vec![
+            output_def("summary", "investigate", "$.result.summary", true),
+            output_def("first_finding", "investigate", "$.result.findings[0].description", false),
+        ];
+
+        let extracted = extract_outputs(&defs, &step_outputs());
+        assert!(extracted.missing_required.is_empty());
+        assert_eq!(extracted.values["summary"], json!("Pod OOMKilled"));
+        assert_eq!(extracted.values["first_finding"], json!("memory limit too low"));
+    }
+
+    #[test]
+    fn test_extract_outputs_missing_required() {
+        let defs = vec![
+            output_def("root_cause", "investigate", "$.result.root_cause", true),
+            output_def("fix", "remediate", "$.result.command", true),
+        ];
+
+        let extracted = extract_outputs(&defs, &step_outputs());
+        assert_eq!(extracted.missing_required, vec!["root_cause", "fix"]);
+        assert!(extracted.values.is_empty());
+    }
+
+    #[test]
+    fn test_extract_outputs_missing_optional() {
+        let defs = vec![
+            output_def("root_cause", "investigate", "$.result.root_cause", false),
+            OutputDef {
+                name: "legacy".to_string(),
+                value: Some("{{ .steps.investigate.result.summary }}".to_string()),
+                from_step: None,
+                json_path: None,
+                required: true,
+            },
+        ];
+
+        let extracted = extract_outputs(&defs, &step_outputs());
+        assert!(extracted.missing_required.is_empty());
+        assert!(extracted.values.is_empty());
+    }
+}
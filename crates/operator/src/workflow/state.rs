@@ -6,6 +6,7 @@ pub enum WorkflowState {
     Running,
     Succeeded,
     Failed,
+    Cancelled,
 }
 
 impl fmt::Display for WorkflowState {
@@ -15,6 +16,7 @@ impl fmt::Display for WorkflowState {
             WorkflowState::Running => write!(f, "Running"),
             WorkflowState::Succeeded => write!(f, "Succeeded"),
             WorkflowState::Failed => write!(f, "Failed"),
+            WorkflowState::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -26,6 +28,7 @@ impl From<&str> for WorkflowState {
             "Running" => WorkflowState::Running,
             "Succeeded" => WorkflowState::Succeeded,
             "Failed" => WorkflowState::Failed,
+            "Cancelled" => WorkflowState::Cancelled,
             _ => WorkflowState::Pending,
         }
     }
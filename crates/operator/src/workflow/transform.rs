@@ -0,0 +1,686 @@
+//! Step output transforms
+//!
+//! Evaluates the `outputTransform` of a step against its output. The
+//! expression language is the subset of jq that reshapes JSON:
+//!
+//! - paths: `.`, `.foo`, `."foo bar"`, `.[0]`, `.[-1]`, `.["foo"]`, `.[]`,
+//!   with `?` to ignore errors
+//! - pipes `|`, commas `,`, parentheses and the alternative operator `//`
+//! - comparisons `==`, `!=`, `<`, `<=`, `>`, `>=` and `and`, `or`
+//! - string, number, `true`, `false` and `null` literals
+//! - array `[...]` and object `{key: ..., "key": ..., key}` construction
+//! - `select(f)`, `map(f)`, `length`, `keys`, `not`, `first`, `last`, `empty`
+//!
+//! An expression yields a stream of values. The transformed output is
+//! `null` for an empty stream, the value itself for a single value and an
+//! array otherwise; wrap the expression in `[...]` to always get an array.
+
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+use crate::{Error, Result};
+
+/// Apply `expression` to `input`. Fails with a `Validation` error if the
+/// expression doesn't parse and an `Execution` error if it can't be applied
+/// to this input.
+pub fn apply_transform(expression: &str, input: &Value) -> Result<Value> {
+    let filter = parse_transform(expression)?;
+    let mut outputs = filter.eval(input)
+        .map_err(|e| Error::Execution(format!("Output transform failed: {}", e)))?;
+    Ok(match outputs.len() {
+        0 => Value::Null,
+        1 => outputs.remove(0),
+        _ => Value::Array(outputs),
+    })
+}
+
+/// Parse an output transform expression
+pub fn parse_transform(expression: &str) -> Result<Filter> {
+    let tokens = tokenize(expression)
+        .map_err(|e| Error::Validation(format!("Invalid output transform: {}", e)))?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let filter = parser.pipe()
+        .and_then(|filter| match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(format!("unexpected {}", token)),
+        })
+        .map_err(|e| Error::Validation(format!("Invalid output transform: {}", e)))?;
+    Ok(filter)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Field(String),
+    Ident(String),
+    Str(String),
+    Num(Value),
+    Op(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Dot => write!(f, "'.'"),
+            Token::Field(name) => write!(f, "'.{}'", name),
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Op(op) => write!(f, "'{}'", op),
+        }
+    }
+}
+
+const OPERATORS: [&str; 16] = [
+    "==", "!=", "<=", ">=", "//", "<", ">", "|", ",", ":", "(", ")", "[", "]", "{", "}",
+];
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c == '.' {
+            rest = &rest[1..];
+            if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                let (name, tail) = split_ident(rest);
+                tokens.push(Token::Field(name.to_string()));
+                rest = tail;
+            } else if rest.starts_with('"') {
+                let (s, tail) = split_string(rest)?;
+                tokens.push(Token::Field(s));
+                rest = tail;
+            } else if rest.starts_with('.') {
+                return Err("recursive descent '..' is not supported".to_string());
+            } else {
+                tokens.push(Token::Dot);
+            }
+        } else if c == '"' {
+            let (s, tail) = split_string(rest)?;
+            tokens.push(Token::Str(s));
+            rest = tail;
+        } else if c.is_ascii_digit() || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            let end = rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '+' || c == '-'))
+                .map_or(rest.len(), |i| i + 1);
+            let number: Value = serde_json::from_str(&rest[..end])
+                .map_err(|_| format!("invalid number '{}'", &rest[..end]))?;
+            tokens.push(Token::Num(number));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let (name, tail) = split_ident(rest);
+            tokens.push(Token::Ident(name.to_string()));
+            rest = tail;
+        } else if c == '?' {
+            tokens.push(Token::Op("?"));
+            rest = &rest[1..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+fn split_ident(input: &str) -> (&str, &str) {
+    let end = input.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(input.len());
+    input.split_at(end)
+}
+
+/// Split a leading JSON string literal off `input`
+fn split_string(input: &str) -> std::result::Result<(String, &str), String> {
+    let mut escaped = false;
+    for (i, c) in input.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => {
+                let s = serde_json::from_str(&input[..=i])
+                    .map_err(|e| format!("invalid string {}: {}", &input[..=i], e))?;
+                return Ok((s, &input[i + 1..]));
+            }
+            _ => escaped = false,
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// A parsed output transform
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Identity,
+    Literal(Value),
+    Field(Box<Filter>, String),
+    Index(Box<Filter>, Box<Filter>),
+    Iterate(Box<Filter>),
+    Try(Box<Filter>),
+    Pipe(Box<Filter>, Box<Filter>),
+    Comma(Box<Filter>, Box<Filter>),
+    Alternative(Box<Filter>, Box<Filter>),
+    Compare(Box<Filter>, &'static str, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Array(Option<Box<Filter>>),
+    Object(Vec<(Filter, Filter)>),
+    Call(String, Option<Box<Filter>>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+type ParseResult = std::result::Result<Filter, String>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(found)) if *found == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> std::result::Result<(), String> {
+        match self.next() {
+            Some(Token::Op(found)) if found == op => Ok(()),
+            Some(token) => Err(format!("expected '{}' but found {}", op, token)),
+            None => Err(format!("expected '{}' but the expression ended", op)),
+        }
+    }
+
+    fn pipe(&mut self) -> ParseResult {
+        let mut filter = self.comma()?;
+        while self.eat_op("|") {
+            filter = Filter::Pipe(Box::new(filter), Box::new(self.comma()?));
+        }
+        Ok(filter)
+    }
+
+    fn comma(&mut self) -> ParseResult {
+        let mut filter = self.alternative()?;
+        while self.eat_op(",") {
+            filter = Filter::Comma(Box::new(filter), Box::new(self.alternative()?));
+        }
+        Ok(filter)
+    }
+
+    fn alternative(&mut self) -> ParseResult {
+        let filter = self.or()?;
+        if self.eat_op("//") {
+            return Ok(Filter::Alternative(Box::new(filter), Box::new(self.alternative()?)));
+        }
+        Ok(filter)
+    }
+
+    fn or(&mut self) -> ParseResult {
+        let mut filter = self.and()?;
+        while self.eat_keyword("or") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> ParseResult {
+        let mut filter = self.comparison()?;
+        while self.eat_keyword("and") {
+            filter = Filter::And(Box::new(filter), Box::new(self.comparison()?));
+        }
+        Ok(filter)
+    }
+
+    fn comparison(&mut self) -> ParseResult {
+        let filter = self.postfix()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat_op(op) {
+                return Ok(Filter::Compare(Box::new(filter), op, Box::new(self.postfix()?)));
+            }
+        }
+        Ok(filter)
+    }
+
+    fn postfix(&mut self) -> ParseResult {
+        let mut filter = self.primary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Field(name)) => {
+                    let name = name.clone();
+                    self.pos += 1;
+                    filter = Filter::Field(Box::new(filter), name);
+                }
+                Some(Token::Op("[")) => {
+                    self.pos += 1;
+                    filter = self.bracket_suffix(filter)?;
+                }
+                // `.foo.[0]`
+                Some(Token::Dot) if self.tokens.get(self.pos + 1) == Some(&Token::Op("[")) => {
+                    self.pos += 2;
+                    filter = self.bracket_suffix(filter)?;
+                }
+                Some(Token::Op("?")) => {
+                    self.pos += 1;
+                    filter = Filter::Try(Box::new(filter));
+                }
+                _ => return Ok(filter),
+            }
+        }
+    }
+
+    /// `[]` or `[index]` following `filter`, after the opening bracket
+    fn bracket_suffix(&mut self, filter: Filter) -> ParseResult {
+        if self.eat_op("]") {
+            return Ok(Filter::Iterate(Box::new(filter)));
+        }
+        let index = self.pipe()?;
+        self.expect_op("]")?;
+        Ok(Filter::Index(Box::new(filter), Box::new(index)))
+    }
+
+    fn primary(&mut self) -> ParseResult {
+        match self.next() {
+            Some(Token::Dot) => match self.peek() {
+                Some(Token::Op("[")) => {
+                    self.pos += 1;
+                    self.bracket_suffix(Filter::Identity)
+                }
+                _ => Ok(Filter::Identity),
+            },
+            Some(Token::Field(name)) => Ok(Filter::Field(Box::new(Filter::Identity), name)),
+            Some(Token::Str(s)) => Ok(Filter::Literal(Value::String(s))),
+            Some(Token::Num(n)) => Ok(Filter::Literal(n)),
+            Some(Token::Op("(")) => {
+                let filter = self.pipe()?;
+                self.expect_op(")")?;
+                Ok(filter)
+            }
+            Some(Token::Op("[")) => {
+                if self.eat_op("]") {
+                    return Ok(Filter::Array(None));
+                }
+                let filter = self.pipe()?;
+                self.expect_op("]")?;
+                Ok(Filter::Array(Some(Box::new(filter))))
+            }
+            Some(Token::Op("{")) => self.object(),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Filter::Literal(Value::Bool(true))),
+                "false" => Ok(Filter::Literal(Value::Bool(false))),
+                "null" => Ok(Filter::Literal(Value::Null)),
+                "select" | "map" => {
+                    self.expect_op("(")?;
+                    let argument = self.pipe()?;
+                    self.expect_op(")")?;
+                    Ok(Filter::Call(name, Some(Box::new(argument))))
+                }
+                "length" | "keys" | "not" | "first" | "last" | "empty" => Ok(Filter::Call(name, None)),
+                _ => Err(format!("unknown function '{}'", name)),
+            },
+            Some(token) => Err(format!("unexpected {}", token)),
+            None => Err("the expression ended unexpectedly".to_string()),
+        }
+    }
+
+    /// Object construction, after the opening brace
+    fn object(&mut self) -> ParseResult {
+        let mut entries = Vec::new();
+        if self.eat_op("}") {
+            return Ok(Filter::Object(entries));
+        }
+        loop {
+            let (key, shorthand) = match self.next() {
+                Some(Token::Ident(name)) | Some(Token::Str(name)) => (
+                    Filter::Literal(Value::String(name.clone())),
+                    Filter::Field(Box::new(Filter::Identity), name),
+                ),
+                Some(Token::Op("(")) => {
+                    let key = self.pipe()?;
+                    self.expect_op(")")?;
+                    self.expect_op(":")?;
+                    entries.push((key, self.object_value()?));
+                    if self.eat_op(",") {
+                        continue;
+                    }
+                    self.expect_op("}")?;
+                    return Ok(Filter::Object(entries));
+                }
+                Some(token) => return Err(format!("unexpected {} in object", token)),
+                None => return Err("unterminated object".to_string()),
+            };
+            let value = if self.eat_op(":") { self.object_value()? } else { shorthand };
+            entries.push((key, value));
+            if self.eat_op(",") {
+                continue;
+            }
+            self.expect_op("}")?;
+            return Ok(Filter::Object(entries));
+        }
+    }
+
+    /// Object values bind tighter than `,`, which separates entries
+    fn object_value(&mut self) -> ParseResult {
+        let mut filter = self.alternative()?;
+        while self.eat_op("|") {
+            filter = Filter::Pipe(Box::new(filter), Box::new(self.alternative()?));
+        }
+        Ok(filter)
+    }
+}
+
+type EvalResult = std::result::Result<Vec<Value>, String>;
+
+impl Filter {
+    /// Evaluate this filter against `input`, yielding its output stream
+    pub fn eval(&self, input: &Value) -> EvalResult {
+        match self {
+            Filter::Identity => Ok(vec![input.clone()]),
+            Filter::Literal(value) => Ok(vec![value.clone()]),
+            Filter::Field(target, name) => map_outputs(target, input, |value| match value {
+                Value::Object(map) => Ok(vec![map.get(name).cloned().unwrap_or(Value::Null)]),
+                Value::Null => Ok(vec![Value::Null]),
+                other => Err(format!("cannot index {} with \"{}\"", type_name(&other), name)),
+            }),
+            Filter::Index(target, index) => {
+                let mut outputs = Vec::new();
+                for value in target.eval(input)? {
+                    for key in index.eval(input)? {
+                        outputs.push(index_value(&value, &key)?);
+                    }
+                }
+                Ok(outputs)
+            }
+            Filter::Iterate(target) => map_outputs(target, input, |value| match value {
+                Value::Array(values) => Ok(values),
+                Value::Object(map) => Ok(map.into_iter().map(|(_, v)| v).collect()),
+                other => Err(format!("cannot iterate over {}", type_name(&other))),
+            }),
+            Filter::Try(filter) => Ok(filter.eval(input).unwrap_or_default()),
+            Filter::Pipe(left, right) => {
+                let mut outputs = Vec::new();
+                for value in left.eval(input)? {
+                    outputs.extend(right.eval(&value)?);
+                }
+                Ok(outputs)
+            }
+            Filter::Comma(left, right) => {
+                let mut outputs = left.eval(input)?;
+                outputs.extend(right.eval(input)?);
+                Ok(outputs)
+            }
+            Filter::Alternative(left, right) => {
+                let outputs: Vec<Value> = left.eval(input).unwrap_or_default()
+                    .into_iter()
+                    .filter(truthy)
+                    .collect();
+                if outputs.is_empty() { right.eval(input) } else { Ok(outputs) }
+            }
+            Filter::Compare(left, op, right) => {
+                let mut outputs = Vec::new();
+                for r in right.eval(input)? {
+                    for l in left.eval(input)? {
+                        let ordering = compare(&l, &r);
+                        outputs.push(Value::Bool(match *op {
+                            "==" => ordering == Ordering::Equal,
+                            "!=" => ordering != Ordering::Equal,
+                            "<" => ordering == Ordering::Less,
+                            "<=" => ordering != Ordering::Greater,
+                            ">" => ordering == Ordering::Greater,
+                            _ => ordering != Ordering::Less,
+                        }));
+                    }
+                }
+                Ok(outputs)
+            }
+            Filter::And(left, right) => {
+                let mut outputs = Vec::new();
+                for l in left.eval(input)? {
+                    if !truthy(&l) {
+                        outputs.push(Value::Bool(false));
+                        continue;
+                    }
+                    outputs.extend(right.eval(input)?.iter().map(|r| Value::Bool(truthy(r))));
+                }
+                Ok(outputs)
+            }
+            Filter::Or(left, right) => {
+                let mut outputs = Vec::new();
+                for l in left.eval(input)? {
+                    if truthy(&l) {
+                        outputs.push(Value::Bool(true));
+                        continue;
+                    }
+                    outputs.extend(right.eval(input)?.iter().map(|r| Value::Bool(truthy(r))));
+                }
+                Ok(outputs)
+            }
+            Filter::Array(None) => Ok(vec![Value::Array(Vec::new())]),
+            Filter::Array(Some(filter)) => Ok(vec![Value::Array(filter.eval(input)?)]),
+            Filter::Object(entries) => {
+                let mut objects = vec![Map::new()];
+                for (key, value) in entries {
+                    let mut next = Vec::new();
+                    for key in key.eval(input)? {
+                        let Value::String(key) = key else {
+                            return Err(format!("object keys must be strings, not {}", type_name(&key)));
+                        };
+                        for value in value.eval(input)? {
+                            for object in &objects {
+                                let mut object = object.clone();
+                                object.insert(key.clone(), value.clone());
+                                next.push(object);
+                            }
+                        }
+                    }
+                    objects = next;
+                }
+                Ok(objects.into_iter().map(Value::Object).collect())
+            }
+            Filter::Call(name, argument) => call(name, argument.as_deref(), input),
+        }
+    }
+}
+
+/// Apply `f` to each output of `target`
+fn map_outputs(
+    target: &Filter,
+    input: &Value,
+    f: impl Fn(Value) -> EvalResult,
+) -> EvalResult {
+    let mut outputs = Vec::new();
+    for value in target.eval(input)? {
+        outputs.extend(f(value)?);
+    }
+    Ok(outputs)
+}
+
+fn index_value(value: &Value, key: &Value) -> std::result::Result<Value, String> {
+    match (value, key) {
+        (Value::Null, _) => Ok(Value::Null),
+        (Value::Object(map), Value::String(name)) => Ok(map.get(name).cloned().unwrap_or(Value::Null)),
+        (Value::Array(values), Value::Number(n)) => {
+            let Some(index) = n.as_f64().map(|f| f.floor() as i64) else {
+                return Ok(Value::Null);
+            };
+            let index = if index < 0 { values.len() as i64 + index } else { index };
+            Ok(usize::try_from(index).ok().and_then(|i| values.get(i)).cloned().unwrap_or(Value::Null))
+        }
+        (value, key) => Err(format!("cannot index {} with {}", type_name(value), type_name(key))),
+    }
+}
+
+fn call(name: &str, argument: Option<&Filter>, input: &Value) -> EvalResult {
+    match (name, argument) {
+        ("select", Some(condition)) => Ok(if condition.eval(input)?.iter().any(truthy) {
+            vec![input.clone()]
+        } else {
+            Vec::new()
+        }),
+        ("map", Some(f)) => {
+            let values = match input {
+                Value::Array(values) => values.clone(),
+                Value::Object(map) => map.values().cloned().collect(),
+                other => return Err(format!("cannot iterate over {}", type_name(other))),
+            };
+            let mut mapped = Vec::new();
+            for value in &values {
+                mapped.extend(f.eval(value)?);
+            }
+            Ok(vec![Value::Array(mapped)])
+        }
+        ("length", None) => Ok(vec![match input {
+            Value::Null => Value::from(0),
+            Value::Number(n) => Value::from(n.as_f64().unwrap_or_default().abs()),
+            Value::String(s) => Value::from(s.chars().count()),
+            Value::Array(values) => Value::from(values.len()),
+            Value::Object(map) => Value::from(map.len()),
+            Value::Bool(_) => return Err("boolean has no length".to_string()),
+        }]),
+        ("keys", None) => match input {
+            Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                Ok(vec![Value::from(keys.into_iter().cloned().collect::<Vec<_>>())])
+            }
+            Value::Array(values) => Ok(vec![Value::from((0..values.len()).collect::<Vec<_>>())]),
+            other => Err(format!("{} has no keys", type_name(other))),
+        },
+        ("not", None) => Ok(vec![Value::Bool(!truthy(input))]),
+        ("first", None) => index_value(input, &Value::from(0)).map(|v| vec![v]),
+        ("last", None) => index_value(input, &Value::from(-1)).map(|v| vec![v]),
+        ("empty", None) => Ok(Vec::new()),
+        _ => Err(format!("unknown function '{}'", name)),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// jq's ordering: null < false < true < numbers < strings < arrays < objects
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().unwrap_or_default()
+            .partial_cmp(&b.as_f64().unwrap_or_default())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a.iter().zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            let mut a_keys: Vec<&String> = a.keys().collect();
+            let mut b_keys: Vec<&String> = b.keys().collect();
+            a_keys.sort();
+            b_keys.sort();
+            a_keys.cmp(&b_keys).then_with(|| a_keys.iter()
+                .map(|k| compare(&a[*k], &b[*k]))
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal))
+        }
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn findings() -> Value {
+        json!({
+            "output": {
+                "findings": [
+                    { "severity": "critical", "description": "Disk full", "count": 3 },
+                    { "severity": "info", "description": "Pod restarted", "count": 1 },
+                    { "severity": "critical", "description": "OOMKilled", "count": 7 }
+                ]
+            },
+            "success": true
+        })
+    }
+
+    #[test]
+    fn test_apply_transform() {
+        let input = findings();
+        let transform = |expression: &str| apply_transform(expression, &input).unwrap();
+
+        assert_eq!(
+            transform(r#".output.findings[] | select(.severity == "critical") | .description"#),
+            json!(["Disk full", "OOMKilled"])
+        );
+        // A single result is stored as is, and `[...]` always collects
+        assert_eq!(transform(".output.findings[0].description"), json!("Disk full"));
+        assert_eq!(transform(r#"[.output.findings[] | select(.severity == "warning")]"#), json!([]));
+        assert_eq!(transform(r#".output.findings[] | select(.severity == "warning")"#), Value::Null);
+
+        assert_eq!(transform(".output.findings[-1].count"), json!(7));
+        assert_eq!(transform(r#".output["findings"] | length"#), json!(3));
+        assert_eq!(transform(".output.findings | map(.count)"), json!([3, 1, 7]));
+        assert_eq!(
+            transform(r#"{ok: .success, critical: [.output.findings[] | select(.count > 2 and .severity != "info") | .description]}"#),
+            json!({ "ok": true, "critical": ["Disk full", "OOMKilled"] })
+        );
+        assert_eq!(transform(".missing.field // \"none\""), json!("none"));
+        assert_eq!(transform("keys"), json!(["output", "success"]));
+        assert_eq!(transform(".success | not"), json!(false));
+        assert_eq!(transform(r#".output.findings | first | {description, "n": .count}"#), json!({ "description": "Disk full", "n": 3 }));
+        assert_eq!(transform(".success.field?"), Value::Null);
+    }
+
+    #[test]
+    fn test_invalid_transforms() {
+        for expression in [".output.findings[", "select(.a", ".a |", "nope(.)", ".a ==", "..a", "\"open"] {
+            assert!(
+                matches!(apply_transform(expression, &findings()), Err(Error::Validation(_))),
+                "{} should not parse",
+                expression
+            );
+        }
+        // Valid expressions can still fail on the output they're given
+        assert!(matches!(apply_transform(".success[]", &findings()), Err(Error::Execution(_))));
+        assert!(matches!(apply_transform(".output.findings.severity", &findings()), Err(Error::Execution(_))));
+    }
+}
@@ -0,0 +1,155 @@
+//! Workflow config variables
+//!
+//! Resolves the `configVars` declared on a `WorkflowSpec` from ConfigMaps
+//! and Secrets in the workflow's namespace before its first step runs.
+
+use std::collections::{BTreeMap, HashMap};
+
+use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+use kube::{Api, Client};
+
+use crate::{
+    crd::workflow::{ConfigVarRef, ConfigVarSource},
+    Error, Result,
+};
+
+/// Data of the objects referenced by config vars, keyed by name. `None`
+/// marks an object that doesn't exist.
+#[derive(Debug, Default)]
+struct ReferencedData {
+    config_maps: HashMap<String, Option<BTreeMap<String, String>>>,
+    secrets: HashMap<String, Option<BTreeMap<String, String>>>,
+}
+
+/// Resolve each config var to its value.
+///
+/// Fails with a `Validation` error naming every required variable whose
+/// ConfigMap, Secret or key is missing. Optional variables that can't be
+/// resolved are left out of the result.
+pub async fn resolve_config_vars(
+    client: &Client,
+    namespace: &str,
+    sources: &[ConfigVarSource],
+) -> Result<HashMap<String, String>> {
+    let config_maps: Api<ConfigMap> = Api::namespaced(client.clone(), namespace);
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), namespace);
+
+    let mut data = ReferencedData::default();
+    for source in sources {
+        match &source.source {
+            ConfigVarRef::ConfigMapKeyRef(key_ref) if !data.config_maps.contains_key(&key_ref.name) => {
+                let config_map = config_maps.get_opt(&key_ref.name).await
+                    .map_err(|e| Error::Kubernetes(format!("Failed to get ConfigMap {}: {}", key_ref.name, e)))?;
+                data.config_maps.insert(key_ref.name.clone(), config_map.map(|c| c.data.unwrap_or_default()));
+            }
+            ConfigVarRef::SecretKeyRef(key_ref) if !data.secrets.contains_key(&key_ref.name) => {
+                let secret = secrets.get_opt(&key_ref.name).await
+                    .map_err(|e| Error::Kubernetes(format!("Failed to get Secret {}: {}", key_ref.name, e)))?;
+                let secret_data = secret.map(|s| {
+                    s.data.unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|(key, value)| String::from_utf8(value.0).ok().map(|value| (key, value)))
+                        .collect()
+                });
+                data.secrets.insert(key_ref.name.clone(), secret_data);
+            }
+            _ => {}
+        }
+    }
+
+    collect_config_vars(sources, &data)
+}
+
+fn collect_config_vars(sources: &[ConfigVarSource], data: &ReferencedData) -> Result<HashMap<String, String>> {
+    let mut vars = HashMap::new();
+    let mut missing = Vec::new();
+
+    for source in sources {
+        let (kind, key_ref, objects) = match &source.source {
+            ConfigVarRef::ConfigMapKeyRef(key_ref) => ("ConfigMap", key_ref, &data.config_maps),
+            ConfigVarRef::SecretKeyRef(key_ref) => ("Secret", key_ref, &data.secrets),
+        };
+
+        let problem = match objects.get(&key_ref.name) {
+            Some(Some(object)) => match object.get(&key_ref.key) {
+                Some(value) => {
+                    vars.insert(source.name.clone(), value.clone());
+                    continue;
+                }
+                None => format!("key '{}' not found in {} '{}'", key_ref.key, kind, key_ref.name),
+            },
+            _ => format!("{} '{}' not found", kind, key_ref.name),
+        };
+
+        if !source.optional {
+            missing.push(format!("{} ({})", source.name, problem));
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(Error::Validation(format!("Missing required config vars: {}", missing.join("; "))));
+    }
+
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crd::workflow::KeyRef;
+
+    fn var(name: &str, source: ConfigVarRef, optional: bool) -> ConfigVarSource {
+        ConfigVarSource { name: name.to_string(), source, optional }
+    }
+
+    fn key_ref(name: &str, key: &str) -> KeyRef {
+        KeyRef { name: name.to_string(), key: key.to_string() }
+    }
+
+    #[test]
+    fn test_collect_config_vars() {
+        let data = ReferencedData {
+            config_maps: [
+                ("cluster-info".to_string(), Some([("name".to_string(), "prod-east".to_string())].into())),
+                ("deleted".to_string(), None),
+            ].into(),
+            secrets: [
+                ("grafana".to_string(), Some([("token".to_string(), "s3cr3t".to_string())].into())),
+            ].into(),
+        };
+
+        let vars = collect_config_vars(&[
+            var("CLUSTER", ConfigVarRef::ConfigMapKeyRef(key_ref("cluster-info", "name")), false),
+            var("GRAFANA_TOKEN", ConfigVarRef::SecretKeyRef(key_ref("grafana", "token")), false),
+            var("REGION", ConfigVarRef::ConfigMapKeyRef(key_ref("cluster-info", "region")), true),
+        ], &data).unwrap();
+        assert_eq!(vars.get("CLUSTER").map(String::as_str), Some("prod-east"));
+        assert_eq!(vars.get("GRAFANA_TOKEN").map(String::as_str), Some("s3cr3t"));
+        assert!(!vars.contains_key("REGION"));
+
+        let mut context = crate::workflow::WorkflowContext::new();
+        context.vars = vars;
+        let rendered = crate::template::render_template("kubectl config use-context {{ vars.CLUSTER }}", &context.get_template_context()).unwrap();
+        assert_eq!(rendered, "kubectl config use-context prod-east");
+        assert!(context.to_json().get("vars").is_none());
+
+        let err = collect_config_vars(&[
+            var("REGION", ConfigVarRef::ConfigMapKeyRef(key_ref("cluster-info", "region")), false),
+            var("OLD", ConfigVarRef::ConfigMapKeyRef(key_ref("deleted", "value")), false),
+        ], &data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation error: Missing required config vars: REGION (key 'region' not found in ConfigMap 'cluster-info'); OLD (ConfigMap 'deleted' not found)"
+        );
+    }
+
+    #[test]
+    fn test_config_var_source_format() {
+        let source: ConfigVarSource = serde_json::from_value(serde_json::json!({
+            "name": "API_ENDPOINT",
+            "source": { "secretKeyRef": { "name": "api", "key": "endpoint" } }
+        })).unwrap();
+        assert_eq!(source.source, ConfigVarRef::SecretKeyRef(key_ref("api", "endpoint")));
+        assert!(!source.optional);
+    }
+}
@@ -1,46 +1,75 @@
 use axum::http::StatusCode;
+use axum_test::TestServer;
+use chrono::Utc;
 use punching_fist_operator::{
     config::Config,
     server::Server,
     sources::WebhookHandler,
-    store::{create_store, DatabaseConfig, DatabaseType},
+    store::{create_store, DatabaseConfig, DatabaseType, Store, Workflow, WorkflowStatus},
 };
 use serde_json::json;
 use std::sync::Arc;
 use std::path::PathBuf;
 use tokio;
+use uuid::Uuid;
 
-#[tokio::test]
-async fn test_server_endpoints() {
-    // Create a test configuration with SQLite in memory
-    let database_config = DatabaseConfig {
-        db_type: DatabaseType::Sqlite,
-        sqlite_path: Some(PathBuf::from(":memory:")),
-        connection_string: None,
+/// Serve the API over a fresh in-memory SQLite store, with `configure`
+/// applied to the server's config first
+async fn test_server(configure: impl FnOnce(&mut Config)) -> (Arc<dyn Store>, TestServer) {
+    test_server_with(configure, |server| server).await
+}
+
+/// Like `test_server`, with `build` adding optional parts to the server
+async fn test_server_with(
+    configure: impl FnOnce(&mut Config),
+    build: impl FnOnce(Server) -> Server,
+) -> (Arc<dyn Store>, TestServer) {
+    let mut config = Config {
+        database: DatabaseConfig {
+            db_type: DatabaseType::Sqlite,
+            sqlite_path: Some(PathBuf::from(":memory:")),
+            connection_string: None,
+            dedup_window_minutes: 0,
+        },
+        ..Default::default()
     };
+    configure(&mut config);
 
-    // Create the store and initialize it
-    let store = create_store(&database_config)
+    let store = create_store(&config.database)
         .await
         .expect("Failed to create store");
     store.init().await.expect("Failed to initialize store");
 
-    // Create webhook handler - pass None for the Kubernetes client in tests
-    let webhook_handler = Arc::new(WebhookHandler::new(
-        store.clone(),
-        None,
-    ));
-
-    // Create config for server
-    let mut config = Config::default();
-    config.database = database_config;
+    // No Kubernetes client for webhooks in tests
+    let webhook_handler = Arc::new(WebhookHandler::new(store.clone(), None));
+    let app = build(Server::new(&config, store.clone(), webhook_handler)).build_router();
+    (store, TestServer::new(app).unwrap())
+}
 
-    // Create and start the server
-    let server = Server::new(&config, store, webhook_handler);
-    let app = server.build_router();
+/// A workflow run that started just now and hasn't completed
+fn workflow(name: &str, status: WorkflowStatus) -> Workflow {
+    let now = Utc::now();
+    Workflow {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        namespace: "default".to_string(),
+        trigger_source: None,
+        status,
+        steps_completed: 0,
+        total_steps: 1,
+        current_step: None,
+        input_context: None,
+        outputs: None,
+        error: None,
+        started_at: now,
+        completed_at: None,
+        created_at: now,
+    }
+}
 
-    // Use axum's test client
-    let client = axum_test::TestServer::new(app).unwrap();
+#[tokio::test]
+async fn test_server_endpoints() {
+    let (_, client) = test_server(|_| {}).await;
 
     // Test health endpoint
     let response = client.get("/health").await;
@@ -48,6 +77,14 @@ async fn test_server_endpoints() {
     let body: serde_json::Value = response.json();
     assert_eq!(body["status"], "healthy");
 
+    // Test database health details
+    let response = client.get("/health/db/details").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["connected"], true);
+    assert_eq!(body["tables"]["alerts"]["row_count"], 0);
+    assert!(body["size_bytes"].as_i64().unwrap() > 0);
+
     // Test create alert
     let create_payload = json!({
         "alert_name": "TestAlert",
@@ -81,13 +118,30 @@ async fn test_server_endpoints() {
     let body: serde_json::Value = response.json();
     assert_eq!(body["alert_name"], "TestAlert");
     assert_eq!(body["severity"], "warning");
+    assert_eq!(body["workflows"], json!([]));
 
     // Test list alerts
     let response = client.get("/alerts?limit=10&offset=0").await;
     assert_eq!(response.status_code(), StatusCode::OK);
-    let body: Vec<serde_json::Value> = response.json();
-    assert_eq!(body.len(), 1);
-    assert_eq!(body[0]["id"], alert_id);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total"], 1);
+    assert_eq!((body["limit"].as_i64(), body["offset"].as_i64()), (Some(10), Some(0)));
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["items"][0]["id"], alert_id);
+
+    // The total counts every alert, not just the page
+    let response = client.get("/alerts?limit=10&offset=1").await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["items"], json!([]));
+
+    // The alert was created just now, so only an open-ended range holds it
+    let response = client.get("/alerts?since=2020-01-01T00:00:00Z").await;
+    assert_eq!(response.json::<serde_json::Value>()["total"], 1);
+    let response = client.get("/alerts?since=2020-01-01T00:00:00Z&until=2021-01-01T00:00:00Z").await;
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total"], 0);
+    assert_eq!(body["items"], json!([]));
 
     // Test get non-existent alert
     let fake_id = "00000000-0000-0000-0000-000000000000";
@@ -99,35 +153,7 @@ async fn test_server_endpoints() {
 
 #[tokio::test]
 async fn test_create_alert_validation() {
-    // Create a test configuration with SQLite in memory
-    let database_config = DatabaseConfig {
-        db_type: DatabaseType::Sqlite,
-        sqlite_path: Some(PathBuf::from(":memory:")),
-        connection_string: None,
-    };
-
-    // Create the store and initialize it
-    let store = create_store(&database_config)
-        .await
-        .expect("Failed to create store");
-    store.init().await.expect("Failed to initialize store");
-
-    // Create webhook handler - pass None for the Kubernetes client in tests
-    let webhook_handler = Arc::new(WebhookHandler::new(
-        store.clone(),
-        None,
-    ));
-
-    // Create config for server
-    let mut config = Config::default();
-    config.database = database_config;
-
-    // Create and start the server
-    let server = Server::new(&config, store, webhook_handler);
-    let app = server.build_router();
-
-    // Use axum's test client
-    let client = axum_test::TestServer::new(app).unwrap();
+    let (_, client) = test_server(|_| {}).await;
 
     // Test invalid severity
     let invalid_payload = json!({
@@ -142,4 +168,1006 @@ async fn test_create_alert_validation() {
     assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
     let body: serde_json::Value = response.json();
     assert!(body["message"].as_str().unwrap().contains("Invalid severity"));
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_workflow_graph() {
+    use axum::{extract::Path, routing::get, Json, Router};
+    use punching_fist_operator::store::{StepStatus, StepType, WorkflowStep};
+
+    // Mock API server serving the Workflow definition: one step followed by
+    // two parallel steps
+    let app = Router::new()
+        .route("/apis/punchingfist.io/v1alpha1/namespaces/{namespace}/workflows/{name}", get(|Path((namespace, name)): Path<(String, String)>| async move {
+            if name != "graph-workflow" {
+                let status = json!({ "kind": "Status", "apiVersion": "v1", "status": "Failure", "reason": "NotFound", "code": 404, "message": "not found" });
+                return (StatusCode::NOT_FOUND, Json(status));
+            }
+            (StatusCode::OK, Json(json!({
+                "apiVersion": "punchingfist.io/v1alpha1",
+                "kind": "Workflow",
+                "metadata": { "name": name, "namespace": namespace },
+                "spec": {
+                    "runtime": { "image": "busybox:latest", "llmConfig": { "provider": "mock", "model": "mock" } },
+                    "steps": [
+                        { "name": "collect", "type": "cli", "command": "true" },
+                        { "name": "check-logs", "type": "agent", "goal": "Check logs", "dependsOn": ["collect"] },
+                        { "name": "check-metrics", "type": "agent", "goal": "Check metrics", "dependsOn": ["collect"] }
+                    ],
+                    "sinks": []
+                }
+            })))
+        }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let kube_client = kube::Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap();
+    let (store, client) = test_server_with(|_| {}, |server| server.with_client(kube_client)).await;
+
+    let now = Utc::now();
+    let mut workflow_ids = Vec::new();
+    let stored_steps = json!({
+        "steps": [
+            { "name": "gather", "type": "cli", "command": "true" },
+            { "name": "summarize", "type": "agent", "goal": "Summarize", "dependsOn": ["gather"] }
+        ]
+    });
+    for (name, input_context) in [("graph-workflow", None), ("deleted-workflow", None), ("removed-workflow", Some(stored_steps))] {
+        let workflow = Workflow {
+            input_context,
+            ..workflow(name, WorkflowStatus::Running)
+        };
+        workflow_ids.push(workflow.id);
+        store.save_workflow(workflow).await.expect("Failed to save workflow");
+    }
+
+    // Only the first step has run so far
+    let collect_id = Uuid::new_v4();
+    store.save_workflow_step(WorkflowStep {
+        id: collect_id,
+        workflow_id: workflow_ids[0],
+        name: "collect".to_string(),
+        step_type: StepType::Cli,
+        status: StepStatus::Succeeded,
+        config: None,
+        started_at: Some(now - chrono::Duration::milliseconds(90_500)),
+        completed_at: Some(now),
+        result: None,
+        error: None,
+        created_at: now,
+    }).await.expect("Failed to save workflow step");
+
+    let response = client.get(&format!("/workflows/{}/graph", workflow_ids[0])).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    let nodes = body["nodes"].as_array().unwrap();
+    let edges = body["edges"].as_array().unwrap();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0]["id"], "collect");
+    assert_eq!(nodes[0]["step_id"], collect_id.to_string());
+    assert_eq!(nodes[0]["status"], "succeeded");
+    // Steps that haven't run yet are still part of the graph
+    assert_eq!(nodes[1]["label"], "check-logs");
+    assert_eq!(nodes[1]["step_type"], "agent");
+    assert_eq!(nodes[1]["status"], "pending");
+    assert!(nodes[1]["step_id"].is_null());
+    // collect fans out to both parallel steps
+    assert_eq!(edges.len(), 2);
+    assert!(edges.iter().all(|e| e["from"] == "collect"));
+
+    let response = client.get(&format!("/workflows/{}/graph", workflow_ids[1])).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(response.json::<serde_json::Value>()["error"], "Workflow definition not found");
+
+    let response = client.get(&format!("/workflows/{}/graph", Uuid::new_v4())).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Runs keep the step definitions they were started with, so their graph
+    // survives the Workflow resource being deleted
+    let response = client.get(&format!("/workflows/{}/graph", workflow_ids[2])).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["nodes"].as_array().unwrap().len(), 2);
+    assert_eq!(body["edges"][0]["from"], "gather");
+    assert_eq!(body["edges"][0]["to"], "summarize");
+
+    // Listed steps carry how long they ran
+    let response = client.get(&format!("/workflows/{}/steps", workflow_ids[0])).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let steps: serde_json::Value = response.json();
+    assert_eq!(steps[0]["id"], collect_id.to_string());
+    assert_eq!(steps[0]["duration_seconds"], 90.5);
+
+    // The report has the same workflow with every stored step
+    let response = client.get(&format!("/workflows/{}/report", workflow_ids[0])).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let report: serde_json::Value = response.json();
+    assert_eq!(report["workflow"]["name"], "graph-workflow");
+    assert_eq!(report["steps"][0]["id"], collect_id.to_string());
+    assert_eq!(report["sink_outputs"], json!([]));
+
+    let response = client.get(&format!("/workflows/{}/report", Uuid::new_v4())).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_workflow_timeseries() {
+    use chrono::{Duration, TimeZone};
+
+    let (store, client) = test_server(|_| {}).await;
+
+    for status in [WorkflowStatus::Succeeded, WorkflowStatus::Succeeded, WorkflowStatus::Failed] {
+        let workflow = workflow("timeseries-workflow", WorkflowStatus::Running);
+        let id = workflow.id;
+        store.save_workflow(workflow).await.expect("Failed to save workflow");
+        store.complete_workflow(id, status, None, None).await.expect("Failed to complete workflow");
+    }
+
+    let to = Utc::now() + Duration::minutes(1);
+    let from = to - Duration::hours(2);
+    let buckets = store.list_workflows_for_time_range(from, to, 60 * 24 * 365 * 100)
+        .await
+        .expect("Failed to list buckets");
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0].succeeded, 2);
+    assert_eq!(buckets[0].failed, 1);
+    assert_eq!(buckets[0].bucket_start, Utc.timestamp_opt(0, 0).unwrap());
+
+    let response = client.get(&format!(
+        "/statistics/workflows/timeseries?from={}&to={}&bucket=1h",
+        from.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        to.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    )).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = response.json();
+    let succeeded: i64 = body.iter().map(|b| b["succeeded"].as_i64().unwrap()).sum();
+    let failed: i64 = body.iter().map(|b| b["failed"].as_i64().unwrap()).sum();
+    assert_eq!((succeeded, failed), (2, 1));
+
+    let response = client.get("/statistics/workflows/timeseries?bucket=soon").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_log_level() {
+    use tracing_subscriber::{layer::SubscriberExt, reload, EnvFilter};
+
+    // Install a reloadable filter for this test thread only
+    let (filter_layer, handle) = reload::Layer::new(EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::registry().with(filter_layer);
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (_, client) = test_server_with(
+        |config| config.server.admin_token = Some("secret-token".to_string()),
+        |server| server.with_log_reload_handle(handle),
+    ).await;
+
+    assert!(tracing::debug_span!("before_reload").is_disabled());
+
+    // Missing token is rejected
+    let response = client.put("/admin/log-level")
+        .json(&json!({ "level": "debug" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = client.put("/admin/log-level")
+        .authorization_bearer("secret-token")
+        .json(&json!({ "level": "debug" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = client.get("/admin/log-level")
+        .authorization_bearer("secret-token")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["level"], "debug");
+
+    // New spans pick up the more verbose filter
+    assert!(!tracing::debug_span!("after_reload").is_disabled());
+    assert!(tracing::trace_span!("trace_after_reload").is_disabled());
+
+    let response = client.put("/admin/log-level")
+        .authorization_bearer("secret-token")
+        .json(&json!({ "level": "not a [valid filter" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_list_alerts_for_deployment() {
+    let (_, client) = test_server(|_| {}).await;
+
+    let alerts = [
+        ("PodCrashLooping", json!({ "namespace": "prod", "deployment": "api" })),
+        ("HighLatency", json!({ "namespace": "prod", "app": "api" })),
+        ("PodCrashLooping", json!({ "namespace": "staging", "deployment": "api" })),
+        ("DiskFull", json!({ "namespace": "prod", "deployment": "worker" })),
+    ];
+    for (name, labels) in alerts {
+        let response = client.post("/alerts")
+            .json(&json!({ "alert_name": name, "severity": "warning", "labels": labels }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+
+    let response = client.get("/alerts?namespace=prod&deployment=api").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let page: serde_json::Value = response.json();
+    assert_eq!((page["total"].as_i64(), page["limit"].as_i64(), page["offset"].as_i64()), (Some(2), Some(20), Some(0)));
+    let items = page["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|a| a["labels"]["namespace"] == "prod"));
+
+    let response = client.get("/alerts?namespace=prod").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_sink_statistics() {
+    use chrono::{DateTime, Duration};
+    use punching_fist_operator::store::{SinkOutput, SinkStatus, SinkType};
+
+    let (store, client) = test_server(|_| {}).await;
+
+    let workflow = workflow("sink-workflow", WorkflowStatus::Succeeded);
+    let (workflow_id, now) = (workflow.id, workflow.created_at);
+    store.save_workflow(workflow).await.expect("Failed to save workflow");
+
+    // Fixed timestamps, so sent slack outputs took exactly 2.5s to deliver
+    let created_at = DateTime::from_timestamp((now - Duration::minutes(10)).timestamp(), 0).unwrap();
+    let sent_at = Some(created_at + Duration::milliseconds(2500));
+    let outputs = [
+        (SinkType::Slack, SinkStatus::Sent, sent_at),
+        (SinkType::Slack, SinkStatus::Failed, None),
+        (SinkType::Slack, SinkStatus::Pending, None),
+        (SinkType::Jira, SinkStatus::Sent, sent_at),
+    ];
+    for (sink_type, status, sent_at) in outputs {
+        store.save_sink_output(SinkOutput {
+            id: Uuid::new_v4(),
+            workflow_id,
+            sink_name: format!("{:?}-sink", sink_type).to_lowercase(),
+            sink_type,
+            payload: None,
+            status,
+            error: None,
+            sent_at,
+            created_at,
+        }).await.expect("Failed to save sink output");
+    }
+
+    let response = client.get("/statistics/sinks").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = response.json();
+    assert_eq!(body.len(), 2);
+
+    let slack = body.iter().find(|s| s["sink_type"] == "slack").unwrap();
+    assert_eq!((slack["sent"].as_i64(), slack["failed"].as_i64(), slack["pending"].as_i64()), (Some(1), Some(1), Some(1)));
+    let latency = slack["avg_latency_ms"].as_f64().unwrap();
+    assert!((latency - 2500.0).abs() < 5.0, "unexpected latency {}", latency);
+
+    let jira = body.iter().find(|s| s["sink_type"] == "jira").unwrap();
+    assert_eq!(jira["sent"], 1);
+
+    let response = client.get("/sink-outputs/failures").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = response.json();
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0]["sink_type"], "slack");
+    assert_eq!(body[0]["status"], "failed");
+
+    let since = (now + Duration::minutes(1)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let response = client.get(&format!("/sink-outputs/failures?since={}", since)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = response.json();
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn test_webhook_payload_validation() {
+    use punching_fist_operator::sources::webhook::WebhookConfig;
+
+    let mut webhook_handler = None;
+    let (_, client) = test_server_with(
+        |config| config.server.max_payload_bytes = 4096,
+        |server| {
+            webhook_handler = Some(server.webhook_handler.clone());
+            server
+        },
+    ).await;
+    webhook_handler.unwrap().register_webhook(WebhookConfig {
+        source_name: "alertmanager".to_string(),
+        source_id: None,
+        path: "/webhook/alertmanager".to_string(),
+        filters: Default::default(),
+        workflow_name: String::new(),
+        trigger_workflow: None,
+        severity_routing: Default::default(),
+        namespace: "default".to_string(),
+        renotify_on_update: false,
+        receiver: Default::default(),
+    }).await.expect("Failed to register webhook");
+
+    let payload = |alerts: serde_json::Value| json!({
+        "receiver": "punching-fist",
+        "status": "firing",
+        "alerts": alerts,
+        "groupLabels": {},
+        "commonLabels": {},
+        "commonAnnotations": {},
+        "externalURL": "http://alertmanager:9093",
+        "version": "4",
+        "groupKey": "{}:{}"
+    });
+    let alert = |labels: serde_json::Value| json!({
+        "status": "firing",
+        "labels": labels,
+        "annotations": {},
+        "startsAt": "2024-01-01T00:00:00Z",
+        "endsAt": null,
+        "generatorURL": "http://prometheus:9090",
+        "fingerprint": "abc123"
+    });
+
+    let response = client.post("/webhook/alertmanager")
+        .json(&payload(json!([alert(json!({ "alertname": "HighCPU" }))])))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = client.post("/webhook/alertmanager").json(&payload(json!([]))).await;
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("no alerts"));
+
+    let response = client.post("/webhook/alertmanager")
+        .json(&payload(json!([alert(json!({}))])))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = client.post("/webhook/alertmanager")
+        .json(&payload(json!([alert(json!({ "alertname": "x".repeat(8192) }))])))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_grafana_webhook() {
+    use punching_fist_operator::{crd::source::WebhookReceiver, sources::webhook::WebhookConfig};
+
+    let mut webhook_handler = None;
+    let (store, client) = test_server_with(|_| {}, |server| {
+        webhook_handler = Some(server.webhook_handler.clone());
+        server
+    }).await;
+    webhook_handler.unwrap().register_webhook(WebhookConfig {
+        source_name: "grafana".to_string(),
+        source_id: None,
+        path: "/webhook/grafana".to_string(),
+        filters: Default::default(),
+        workflow_name: String::new(),
+        trigger_workflow: None,
+        severity_routing: Default::default(),
+        namespace: "default".to_string(),
+        renotify_on_update: false,
+        receiver: WebhookReceiver::Grafana,
+    }).await.expect("Failed to register webhook");
+
+    let fixture: serde_json::Value = serde_json::from_str(include_str!("fixtures/grafana_webhook.json")).unwrap();
+    let response = client.post("/webhook/grafana").json(&fixture).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let alerts = store.list_alerts(10, 0, None, None).await.unwrap();
+    assert_eq!(alerts.len(), 2);
+    let firing = alerts.iter().find(|a| a.external_id.as_deref() == Some("5b6e3c1f8a9d2e47")).unwrap();
+    assert_eq!(firing.alert_name, "HighPodRestarts");
+    assert_eq!(firing.summary.as_deref(), Some("Pod is crash looping"));
+    assert_eq!(firing.labels["pod"], "web-app-7d9f8b6c5-x2k4p");
+    assert_eq!(firing.annotations["grafana_dashboard_url"], "https://grafana.example.com/d/k8s-pods?orgId=1");
+    assert_eq!(firing.ends_at, None);
+
+    let mut invalid = fixture.clone();
+    invalid["alerts"][0]["labels"] = json!({});
+    let response = client.post("/webhook/grafana").json(&invalid).await;
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("Alert 0 has no labels"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_workflow_diff() {
+    let (store, client) = test_server(|_| {}).await;
+
+    let runs = [
+        json!({ "outputs": { "summary": "OOMKilled", "confidence": 0.6, "findings": ["restarts"] } }),
+        json!({ "outputs": { "summary": "OOMKilled", "confidence": 0.9, "root_cause": "memory limit" } }),
+    ];
+    let mut ids = Vec::new();
+    for outputs in runs {
+        let workflow = workflow("diff-workflow", WorkflowStatus::Running);
+        let id = workflow.id;
+        store.save_workflow(workflow).await.expect("Failed to save workflow");
+        store.complete_workflow(id, WorkflowStatus::Succeeded, Some(outputs), None)
+            .await
+            .expect("Failed to complete workflow");
+        ids.push(id);
+    }
+
+    let response = client.get(&format!("/workflows/{}/diff?other_id={}", ids[0], ids[1])).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["added"], json!({ "/outputs/root_cause": "memory limit" }));
+    assert_eq!(body["removed"], json!({ "/outputs/findings": ["restarts"] }));
+    assert_eq!(body["changed"], json!([{ "path": "/outputs/confidence", "from": 0.6, "to": 0.9 }]));
+
+    let response = client.get(&format!("/workflows/{}/diff?other_id={}", ids[0], Uuid::new_v4())).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_backup() {
+    let db_path = std::env::temp_dir().join(format!("punching-fist-{}.db", Uuid::new_v4()));
+    let backup_dir = std::env::temp_dir().join(format!("punching-fist-backups-{}", Uuid::new_v4()));
+    let (_, client) = test_server(|config| {
+        config.database.sqlite_path = Some(PathBuf::from(format!("sqlite://{}?mode=rwc", db_path.display())));
+        config.server.admin_token = Some("secret-token".to_string());
+        config.backup.dir = backup_dir.clone();
+    }).await;
+
+    let response = client.post("/admin/backup").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = client.post("/admin/backup")
+        .authorization_bearer("secret-token")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    let path = PathBuf::from(body["path"].as_str().unwrap());
+    assert!(path.starts_with(&backup_dir));
+    assert_eq!(body["size_bytes"], std::fs::metadata(&path).unwrap().len());
+
+    let _ = std::fs::remove_dir_all(&backup_dir);
+    let _ = std::fs::remove_file(&db_path);
+}
+
+/// Check an OpenAPI 3.1 document for the structural errors a validator would
+/// report, returning one message per problem
+fn openapi_errors(spec: &serde_json::Value) -> Vec<String> {
+    fn collect_refs<'a>(value: &'a serde_json::Value, refs: &mut Vec<&'a str>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(r)) = map.get("$ref") {
+                    refs.push(r);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    let mut errors = Vec::new();
+    if !spec["openapi"].as_str().is_some_and(|v| v.starts_with("3.1.")) {
+        errors.push(format!("unsupported openapi version {}", spec["openapi"]));
+    }
+    for field in ["title", "version"] {
+        if !spec["info"][field].is_string() {
+            errors.push(format!("info.{} is required", field));
+        }
+    }
+
+    let mut operation_ids = std::collections::HashSet::new();
+    for (path, item) in spec["paths"].as_object().into_iter().flatten() {
+        if !path.starts_with('/') {
+            errors.push(format!("path {} must start with /", path));
+        }
+        let templated: Vec<&str> = path.split('/')
+            .filter_map(|s| s.strip_prefix('{').and_then(|s| s.strip_suffix('}')))
+            .collect();
+        for (method, operation) in item.as_object().into_iter().flatten() {
+            if !["get", "put", "post", "delete", "options", "head", "patch", "trace"].contains(&method.as_str()) {
+                errors.push(format!("{}: unknown method {}", path, method));
+            }
+            match operation["operationId"].as_str() {
+                Some(id) if !operation_ids.insert(id.to_string()) => errors.push(format!("duplicate operationId {}", id)),
+                None => errors.push(format!("{} {}: operationId is required", method, path)),
+                _ => {}
+            }
+            if operation["responses"].as_object().is_none_or(|r| r.is_empty()) {
+                errors.push(format!("{} {}: responses are required", method, path));
+            }
+            let parameters = operation["parameters"].as_array().cloned().unwrap_or_default();
+            for name in &templated {
+                let declared = parameters.iter()
+                    .any(|p| p["in"] == "path" && p["name"] == *name && p["required"] == true);
+                if !declared {
+                    errors.push(format!("{} {}: path parameter {} is not declared", method, path, name));
+                }
+            }
+            for parameter in &parameters {
+                if !["path", "query", "header", "cookie"].contains(&parameter["in"].as_str().unwrap_or_default()) {
+                    errors.push(format!("{} {}: invalid parameter location {}", method, path, parameter["in"]));
+                }
+                if parameter.get("schema").is_none() {
+                    errors.push(format!("{} {}: parameter {} has no schema", method, path, parameter["name"]));
+                }
+            }
+        }
+    }
+
+    let mut refs = Vec::new();
+    collect_refs(spec, &mut refs);
+    for r in refs {
+        let resolved = r.strip_prefix('#').and_then(|pointer| spec.pointer(pointer));
+        if resolved.is_none() {
+            errors.push(format!("unresolved $ref {}", r));
+        }
+    }
+
+    // Every component must be a valid JSON Schema
+    for name in spec["components"]["schemas"].as_object().into_iter().flatten().map(|(name, _)| name) {
+        if let Err(e) = component_validator(spec, name) {
+            errors.push(format!("invalid schema {}: {}", name, e));
+        }
+    }
+
+    errors
+}
+
+/// Compile a component schema, resolving its `$ref`s against the spec
+fn component_validator(spec: &serde_json::Value, name: &str) -> Result<jsonschema::JSONSchema, String> {
+    let schema = json!({
+        "$ref": format!("#/components/schemas/{}", name),
+        "components": spec["components"],
+    });
+    jsonschema::JSONSchema::compile(&schema).map_err(|e| e.to_string())
+}
+
+/// Check a response body against the component schema documented for it
+fn assert_conforms(spec: &serde_json::Value, name: &str, body: &serde_json::Value) {
+    let validator = component_validator(spec, name).unwrap();
+    let errors: Vec<String> = match validator.validate(body) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.map(|e| format!("{}: {}", e.instance_path, e)).collect(),
+    };
+    assert!(errors.is_empty(), "response does not match {}:\n{}", name, errors.join("\n"));
+}
+
+#[tokio::test]
+async fn test_openapi_spec() {
+    let (_, client) = test_server(|_| {}).await;
+
+    let response = client.get("/openapi.json").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let spec: serde_json::Value = response.json();
+    let errors = openapi_errors(&spec);
+    assert!(errors.is_empty(), "invalid OpenAPI spec:\n{}", errors.join("\n"));
+
+    // The YAML rendering describes the same document
+    let response = client.get("/openapi.yaml").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let yaml: serde_json::Value = serde_yaml::from_str(&response.text()).unwrap();
+    assert_eq!(yaml, spec);
+
+    // Every endpoint listed at / except the static UI is documented
+    let root: serde_json::Value = client.get("/").await.json();
+    for endpoint in root["endpoints"].as_array().unwrap() {
+        let (path, method) = (endpoint["path"].as_str().unwrap(), endpoint["method"].as_str().unwrap());
+        if path == "/ui" {
+            continue;
+        }
+        let operation = &spec["paths"][path][method.to_lowercase()];
+        assert_eq!(operation["summary"], endpoint["description"], "{} {} is not documented", method, path);
+    }
+
+    let get_alert = &spec["paths"]["/alerts/{id}"]["get"];
+    assert_eq!(get_alert["parameters"][0]["schema"]["format"], "uuid");
+    assert_eq!(
+        get_alert["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/DetailedAlert"
+    );
+    let source_events = &spec["paths"]["/source-events"]["get"]["parameters"];
+    assert!(source_events.as_array().unwrap().iter().any(|p| p["name"] == "source_name" && p["required"] == true));
+
+    // Responses match their documented schemas
+    let health = &spec["paths"]["/health"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"];
+    let health = health.as_str().unwrap().rsplit('/').next().unwrap();
+    assert_conforms(&spec, health, &client.get("/health").await.json());
+    let created: serde_json::Value = client.post("/alerts")
+        .json(&json!({ "alert_name": "TestAlert", "severity": "warning", "summary": "Documented" }))
+        .await
+        .json();
+    let alert: serde_json::Value = client.get(&format!("/alerts/{}", created["id"].as_str().unwrap())).await.json();
+    assert_conforms(&spec, "DetailedAlert", &alert);
+
+    // Swagger UI loads its assets from this server
+    let response = client.get("/api-docs").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let page = response.text();
+    assert!(page.contains("SwaggerUIBundle({ url: \"/openapi.json\""));
+    assert!(page.contains("src=\"/ui/swagger-ui/swagger-ui-bundle.js\""));
+    assert!(!page.contains("https://"));
+}
+
+#[tokio::test]
+async fn test_annotate_alert() {
+    let (_, client) = test_server(|_| {}).await;
+
+    let response = client.post("/alerts")
+        .json(&json!({ "alert_name": "DatabaseDown", "severity": "critical" }))
+        .await;
+    let alert_id = response.json::<serde_json::Value>()["id"].as_str().unwrap().to_string();
+
+    for (author, text) in [("alice", "checked DB logs, no errors"), ("bob", "failover triggered")] {
+        let response = client.post(&format!("/alerts/{}/annotate", alert_id))
+            .json(&json!({ "author": author, "text": text }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let note: serde_json::Value = response.json();
+        assert_eq!(note["author"], author);
+        assert!(note["timestamp"].is_string());
+    }
+
+    let body: serde_json::Value = client.get(&format!("/alerts/{}", alert_id)).await.json();
+    let notes = body["notes"].as_array().unwrap();
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[0]["text"], "checked DB logs, no errors");
+    assert_eq!(notes[1]["author"], "bob");
+
+    let response = client.post(&format!("/alerts/{}/annotate", alert_id))
+        .json(&json!({ "author": "alice", "text": "  " }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = client.post("/alerts/00000000-0000-0000-0000-000000000000/annotate")
+        .json(&json!({ "author": "alice", "text": "hello" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_alert_workflow_diff() {
+    let (store, client) = test_server(|_| {}).await;
+
+    let response = client.post("/alerts")
+        .json(&json!({ "alert_name": "PodCrashLooping", "severity": "warning" }))
+        .await;
+    let alert_id = response.json::<serde_json::Value>()["id"].as_str().unwrap().to_string();
+
+    // A completed run followed by a run that failed in its second step
+    let runs = [
+        (Some(&alert_id), WorkflowStatus::Succeeded, json!({
+            "steps": { "check-pods": { "restarts": 3 }, "investigate": { "root_cause": "OOMKilled" } },
+            "outputs": { "root_cause": "OOMKilled" }
+        })),
+        (Some(&alert_id), WorkflowStatus::Failed, json!({
+            "error": "agent timed out",
+            "failed_step": "investigate",
+            "outputs": { "check-pods": { "restarts": 7 } }
+        })),
+        (None, WorkflowStatus::Succeeded, json!({ "steps": {} })),
+    ];
+    let mut ids = Vec::new();
+    for (alert, status, outputs) in runs {
+        let workflow = Workflow {
+            input_context: alert.map(|alert_id| json!({ "alert_id": alert_id })),
+            ..workflow("crashloop-triage", WorkflowStatus::Running)
+        };
+        let id = workflow.id;
+        store.save_workflow(workflow).await.expect("Failed to save workflow");
+        store.complete_workflow(id, status, Some(outputs), None)
+            .await
+            .expect("Failed to complete workflow");
+        ids.push(id);
+    }
+
+    let response = client.get(&format!("/alerts/{}/workflow-diff?run1={}&run2={}", alert_id, ids[0], ids[1])).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let patch: serde_json::Value = response.json();
+    assert_eq!(patch, json!([
+        { "op": "replace", "path": "/check-pods/restarts", "value": 7 },
+        { "op": "remove", "path": "/investigate" }
+    ]));
+
+    // Runs of other alerts can't be compared through this alert
+    let response = client.get(&format!("/alerts/{}/workflow-diff?run1={}&run2={}", alert_id, ids[0], ids[2])).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(response.json::<serde_json::Value>()["error"], "Workflow not found for alert");
+
+    let response = client.get(&format!("/alerts/{}/workflow-diff?run1={}&run2={}", Uuid::new_v4(), ids[0], ids[1])).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(response.json::<serde_json::Value>()["error"], "Alert not found");
+}
+
+#[tokio::test]
+async fn test_bulk_cancel_workflows() {
+    let (store, client) = test_server(|_| {}).await;
+
+    let mut ids = Vec::new();
+    for status in [WorkflowStatus::Running, WorkflowStatus::Pending, WorkflowStatus::Succeeded] {
+        let workflow = workflow("crashloop-triage", status);
+        ids.push(workflow.id);
+        store.save_workflow(workflow).await.expect("Failed to save workflow");
+    }
+    ids.push(Uuid::new_v4());
+
+    // Repeated IDs are counted once
+    let mut payload_ids = ids.clone();
+    payload_ids.push(ids[0]);
+    let response = client.post("/workflows/bulk-cancel")
+        .json(&json!({ "workflow_ids": payload_ids, "reason": "maintenance window" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<serde_json::Value>(), json!({
+        "cancelled": 2,
+        "not_found": 1,
+        "already_completed": 1
+    }));
+
+    let cancelled = store.get_workflow(ids[0]).await.unwrap().unwrap();
+    assert_eq!(cancelled.status, WorkflowStatus::Cancelled);
+    assert!(cancelled.error.unwrap().contains("maintenance window"));
+    assert!(cancelled.completed_at.is_some());
+    let succeeded = store.get_workflow(ids[2]).await.unwrap().unwrap();
+    assert_eq!(succeeded.status, WorkflowStatus::Succeeded);
+
+    let too_many: Vec<Uuid> = (0..101).map(|_| Uuid::new_v4()).collect();
+    let response = client.post("/workflows/bulk-cancel")
+        .json(&json!({ "workflow_ids": too_many }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // The limit applies to the request as sent, before repeats are dropped
+    let response = client.post("/workflows/bulk-cancel")
+        .json(&json!({ "workflow_ids": vec![ids[1]; 1000] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_list_workflows_by_status() {
+    use chrono::{Duration, SecondsFormat};
+
+    let (store, client) = test_server(|_| {}).await;
+
+    let mut failed = Vec::new();
+    let start = Utc::now() - Duration::minutes(10);
+    let statuses = [WorkflowStatus::Failed, WorkflowStatus::Running, WorkflowStatus::Failed, WorkflowStatus::Succeeded, WorkflowStatus::Failed];
+    for (i, status) in statuses.into_iter().enumerate() {
+        let created_at = start + Duration::minutes(i as i64);
+        let workflow = Workflow {
+            started_at: created_at,
+            created_at,
+            ..workflow("crashloop-triage", status)
+        };
+        if status == WorkflowStatus::Failed {
+            failed.push(workflow.id.to_string());
+        }
+        store.save_workflow(workflow).await.expect("Failed to save workflow");
+    }
+    failed.reverse();
+
+    let response = client.get("/workflows?limit=2").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let page: serde_json::Value = response.json();
+    assert_eq!(page["total"], 5);
+    assert_eq!((page["limit"].as_i64(), page["offset"].as_i64()), (Some(2), Some(0)));
+    assert_eq!(page["items"].as_array().unwrap().len(), 2);
+
+    // Failed workflows only, newest first, one page at a time
+    let response = client.get("/workflows?status=failed&limit=2").await;
+    let page: serde_json::Value = response.json();
+    assert_eq!(page["total"], 3);
+    let ids: Vec<&str> = page["items"].as_array().unwrap().iter().map(|w| w["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, failed[..2]);
+    let response = client.get("/workflows?status=failed&limit=2&offset=2").await;
+    let page: serde_json::Value = response.json();
+    assert_eq!(page["total"], 3);
+    assert_eq!(page["items"][0]["id"], failed[2]);
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+
+    let response = client.get("/workflows?status=cancelled").await;
+    assert_eq!(response.json::<serde_json::Value>(), json!({ "items": [], "total": 0, "limit": 20, "offset": 0 }));
+
+    let response = client.get("/workflows?status=exploded").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // The second through fourth workflows, alone and with the status filter
+    let timestamp = |minutes: i64| (start + Duration::minutes(minutes)).to_rfc3339_opts(SecondsFormat::Micros, true);
+    let range = format!("since={}&until={}", timestamp(1), timestamp(4));
+    let response = client.get(&format!("/workflows?{}", range)).await;
+    let page: serde_json::Value = response.json();
+    assert_eq!(page["total"], 3);
+    let statuses: Vec<&str> = page["items"].as_array().unwrap().iter().map(|w| w["status"].as_str().unwrap()).collect();
+    assert_eq!(statuses, ["succeeded", "failed", "running"]);
+    let response = client.get(&format!("/workflows?status=failed&{}", range)).await;
+    let page: serde_json::Value = response.json();
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["id"], failed[1]);
+
+    let response = client.get("/workflows?since=yesterday").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_follower_rejects_writes() {
+    use punching_fist_operator::controllers::LeaderElection;
+
+    // The election never runs, so this replica stays a follower
+    let client = kube::Client::try_from(kube::Config::new("http://127.0.0.1:9".parse().unwrap())).unwrap();
+    let election = Arc::new(LeaderElection::new(client, "default".to_string(), "operator-1".to_string()));
+
+    let (_, client) = test_server_with(|_| {}, |server| server.with_leader_election(election)).await;
+
+    let response = client.post("/alerts")
+        .json(&json!({ "alert_name": "TestAlert", "severity": "warning" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"], "This replica is not the leader");
+    assert!(body["leader"].is_null());
+
+    let response = client.post("/webhook/alerts").json(&json!({ "alerts": [] })).await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Reads are still served
+    let response = client.get("/alerts").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_token_review_auth() {
+    use axum::{routing::post, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Stand-in for the API server's TokenReview endpoint
+    let reviews = Arc::new(AtomicUsize::new(0));
+    let counter = reviews.clone();
+    let api = Router::new().route("/apis/authentication.k8s.io/v1/tokenreviews", post(
+        move |Json(review): Json<serde_json::Value>| async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let user = match review["spec"]["token"].as_str() {
+                Some("viewer-token") => json!({ "username": "jane", "groups": ["system:authenticated"] }),
+                Some("editor-token") => json!({ "username": "alertmanager", "groups": ["system:authenticated", "alert-senders"] }),
+                Some("admin-token") => json!({ "username": "root", "groups": ["system:masters"] }),
+                _ => return Json(json!({ "apiVersion": "authentication.k8s.io/v1", "kind": "TokenReview", "status": { "authenticated": false } })),
+            };
+            Json(json!({
+                "apiVersion": "authentication.k8s.io/v1",
+                "kind": "TokenReview",
+                "status": { "authenticated": true, "user": user }
+            }))
+        },
+    ));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, api).await.unwrap() });
+    let kube_client = kube::Client::try_from(kube::Config::new(format!("http://{}", addr).parse().unwrap())).unwrap();
+
+    let (_, client) = test_server_with(
+        |config| {
+            config.auth.enabled = true;
+            config.auth.editors.groups = vec!["alert-senders".to_string()];
+        },
+        |server| server.with_client(kube_client),
+    ).await;
+
+    // Probes stay open
+    let response = client.get("/health").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = client.get("/alerts").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let response = client.get("/alerts").authorization_bearer("stolen-token").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = client.get("/alerts").authorization_bearer("viewer-token").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let alert = json!({ "alert_name": "TestAlert", "severity": "warning" });
+    let response = client.post("/alerts").authorization_bearer("viewer-token").json(&alert).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["user"], "jane");
+
+    let response = client.post("/alerts").authorization_bearer("editor-token").json(&alert).await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let response = client.get("/admin/log-level").authorization_bearer("editor-token").await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Admins reach /admin without the static admin token
+    let response = client.get("/admin/log-level").authorization_bearer("admin-token").await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Accepted tokens are cached; rejected ones are reviewed every time
+    assert_eq!(reviews.load(Ordering::SeqCst), 4);
+    let response = client.get("/alerts").authorization_bearer("viewer-token").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(reviews.load(Ordering::SeqCst), 4);
+
+    // Changes are attributed to the user that made them
+    let response = client.get("/admin/audit-log?entity_type=alert").authorization_bearer("admin-token").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let entries: Vec<serde_json::Value> = response.json();
+    assert!(!entries.is_empty());
+    assert!(entries.iter().all(|e| e["actor"] == "alertmanager"));
+}
+
+#[tokio::test]
+async fn test_audit_log() {
+    let (_, client) = test_server(|config| config.server.admin_token = Some("secret-token".to_string())).await;
+
+    let response = client.post("/alerts")
+        .json(&json!({ "alert_name": "DatabaseDown", "severity": "critical" }))
+        .await;
+    let alert_id = response.json::<serde_json::Value>()["id"].as_str().unwrap().to_string();
+    let response = client.post(&format!("/alerts/{}/annotate", alert_id))
+        .json(&json!({ "author": "alice", "text": "failover triggered" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let response = client.get("/admin/audit-log").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = client.get(&format!("/admin/audit-log?entity_type=alert&entity_id={}", alert_id))
+        .authorization_bearer("secret-token")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let entries: Vec<serde_json::Value> = response.json();
+    assert!(entries.len() >= 2);
+    assert!(entries.iter().all(|e| e["entity_id"] == alert_id.as_str()));
+
+    // Newest first: the annotation, then eventually the alert's creation
+    assert_eq!(entries[0]["operation"], "append_alert_annotation");
+    assert!(entries[0]["before"]["annotations"]["_notes"].is_null());
+    assert!(entries[0]["after"]["annotations"]["_notes"].as_str().unwrap().contains("failover triggered"));
+    let created = entries.last().unwrap();
+    assert!(created["before"].is_null());
+    assert_eq!(created["after"]["alert_name"], "DatabaseDown");
+    assert!(created["actor"].is_null());
+
+    let response = client.get("/admin/audit-log?entity_type=workflow&limit=1")
+        .authorization_bearer("secret-token")
+        .await;
+    let entries: Vec<serde_json::Value> = response.json();
+    assert!(entries.iter().all(|e| e["entity_type"] == "workflow"));
+}
+
+#[tokio::test]
+async fn test_workflow_tool_invocations() {
+    use chrono::Duration;
+    use punching_fist_operator::store::ToolInvocation;
+
+    let (store, client) = test_server(|_| {}).await;
+
+    let workflow_id = Uuid::new_v4();
+    let now = Utc::now();
+    for (offset, tool_name, arguments, success) in [
+        (0, "kubectl", json!({ "verb": "get", "resource": "pods", "namespace": "prod" }), true),
+        (1, "promql", json!({ "query": "up == 0" }), false),
+    ] {
+        store.save_tool_invocation(ToolInvocation {
+            id: Uuid::new_v4(),
+            workflow_id,
+            step_name: Some("investigate".to_string()),
+            tool_name: tool_name.to_string(),
+            arguments,
+            success,
+            output: format!("{} output", tool_name),
+            created_at: now + Duration::seconds(offset),
+        }).await.unwrap();
+    }
+
+    let response = client.get(&format!("/workflows/{}/tool-invocations", workflow_id)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let invocations: Vec<serde_json::Value> = response.json();
+    assert_eq!(invocations.len(), 2);
+    assert_eq!(invocations[0]["tool_name"], "kubectl");
+    assert_eq!(invocations[0]["arguments"]["namespace"], "prod");
+    assert_eq!(invocations[0]["success"], true);
+    assert_eq!(invocations[1]["tool_name"], "promql");
+    assert_eq!(invocations[1]["success"], false);
+    assert_eq!(invocations[1]["step_name"], "investigate");
+
+    let response = client.get(&format!("/workflows/{}/tool-invocations", Uuid::new_v4())).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.json::<Vec<serde_json::Value>>().is_empty());
+}
@@ -0,0 +1,405 @@
+//! Runs the same assertions against every store backend, so SQLite and
+//! PostgreSQL don't drift apart. SQLite always runs in memory; PostgreSQL
+//! runs only when `DATABASE_URL` points at a database.
+
+use chrono::{DateTime, SubsecRound, Utc};
+use futures::FutureExt;
+use punching_fist_operator::store::{
+    Alert, AlertSeverity, AlertStatus, AuditingStore, CustomResource, DeduplicationResult, PostgresStore, SinkOutput, SinkStatus,
+    SinkType, SourceEvent, SourceType, SqliteStore, StepStatus, StepType, Store, Workflow, WorkflowStatus,
+    WorkflowStep,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const DEDUP_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Both backends keep at least millisecond precision
+fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(3)
+}
+
+fn alert(fingerprint: &str) -> Alert {
+    let now = now();
+    Alert {
+        id: Uuid::new_v4(),
+        external_id: None,
+        fingerprint: fingerprint.to_string(),
+        status: AlertStatus::Received,
+        severity: AlertSeverity::Warning,
+        alert_name: "HighCPU".to_string(),
+        summary: Some("CPU above 90%".to_string()),
+        description: None,
+        labels: HashMap::from([("pod".to_string(), "api-7f9".to_string())]),
+        annotations: HashMap::new(),
+        source_id: None,
+        workflow_id: None,
+        ai_analysis: None,
+        ai_confidence: None,
+        auto_resolved: false,
+        starts_at: now,
+        ends_at: None,
+        received_at: now,
+        triage_started_at: None,
+        triage_completed_at: None,
+        resolved_at: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn workflow() -> Workflow {
+    let now = now();
+    Workflow {
+        id: Uuid::new_v4(),
+        name: "triage".to_string(),
+        namespace: "default".to_string(),
+        trigger_source: Some("alertmanager".to_string()),
+        status: WorkflowStatus::Pending,
+        steps_completed: 0,
+        total_steps: 2,
+        current_step: None,
+        input_context: Some(json!({ "alert_id": "abc" })),
+        outputs: None,
+        error: None,
+        started_at: now,
+        completed_at: None,
+        created_at: now,
+    }
+}
+
+async fn check_alerts(store: &dyn Store) {
+    // Stores may be shared between runs, so every fingerprint is fresh
+    let fingerprint = Uuid::new_v4().to_string();
+    let first = alert(&fingerprint);
+    store.save_alert(first.clone()).await.unwrap();
+
+    let stored = store.get_alert(first.id).await.unwrap().unwrap();
+    assert_eq!(stored.fingerprint, fingerprint);
+    assert_eq!(stored.summary.as_deref(), Some("CPU above 90%"));
+    assert_eq!(stored.labels, first.labels);
+    assert_eq!(stored.received_at, first.received_at);
+    assert_eq!(store.get_alert_by_fingerprint(&fingerprint).await.unwrap().unwrap().id, first.id);
+    assert!(store.get_alert(Uuid::new_v4()).await.unwrap().is_none());
+
+    store.update_alert_status(first.id, AlertStatus::Triaging).await.unwrap();
+    store.update_alert_ai_analysis(first.id, json!({ "cause": "load" }), 0.75).await.unwrap();
+    let triaged = now();
+    store.update_alert_timing(first.id, "triage_started_at", triaged).await.unwrap();
+    assert!(store.update_alert_timing(first.id, "name", triaged).await.is_err());
+    let stored = store.get_alert(first.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, AlertStatus::Triaging);
+    assert_eq!(stored.ai_analysis, Some(json!({ "cause": "load" })));
+    assert_eq!(stored.ai_confidence, Some(0.75));
+    assert_eq!(stored.triage_started_at, Some(triaged));
+
+    // A repeat of an active alert is a duplicate, a new fingerprint isn't
+    match store.deduplicate_alert(&fingerprint, alert("ignored")).await.unwrap() {
+        DeduplicationResult::Duplicate(existing) => assert_eq!(existing.id, first.id),
+        other => panic!("expected a duplicate, got {:?}", other),
+    }
+    let other = Uuid::new_v4().to_string();
+    let second = alert(&other);
+    match store.deduplicate_alert(&other, second.clone()).await.unwrap() {
+        DeduplicationResult::New(created) => assert_eq!(created.id, second.id),
+        other => panic!("expected a new alert, got {:?}", other),
+    }
+
+    // Once resolved, the fingerprint starts a new alert
+    store.update_alert_status(first.id, AlertStatus::Resolved).await.unwrap();
+    match store.get_or_create_alert_by_fingerprint(alert(&fingerprint)).await.unwrap() {
+        DeduplicationResult::New(created) => assert_ne!(created.id, first.id),
+        other => panic!("expected a new alert, got {:?}", other),
+    }
+
+    // A re-fire that adds an annotation is merged into the alert
+    let mut annotated = alert(&other);
+    annotated.annotations.insert("runbook".to_string(), "https://runbooks/cpu".to_string());
+    match store.deduplicate_alert(&other, annotated).await.unwrap() {
+        DeduplicationResult::Updated(updated) => {
+            assert_eq!(updated.id, second.id);
+            assert_eq!(updated.labels, second.labels);
+            assert_eq!(updated.annotations["runbook"], "https://runbooks/cpu");
+        }
+        other => panic!("expected an updated alert, got {:?}", other),
+    }
+
+    // Past the dedup window the stale alert is resolved and replaced
+    let stale_fingerprint = Uuid::new_v4().to_string();
+    let mut stale = alert(&stale_fingerprint);
+    stale.updated_at -= chrono::Duration::hours(2);
+    store.save_alert(stale.clone()).await.unwrap();
+    match store.deduplicate_alert(&stale_fingerprint, alert("ignored")).await.unwrap() {
+        DeduplicationResult::New(created) => assert_ne!(created.id, stale.id),
+        other => panic!("expected a new alert, got {:?}", other),
+    }
+    assert_eq!(store.get_alert(stale.id).await.unwrap().unwrap().status, AlertStatus::Resolved);
+
+    let triaging = store.list_alerts_by_status(AlertStatus::Received, 1000).await.unwrap();
+    assert!(triaging.iter().any(|a| a.id == second.id));
+    assert!(triaging.iter().all(|a| a.status == AlertStatus::Received));
+    assert!(store.count_alerts_by_status(AlertStatus::Received).await.unwrap() >= 1);
+    assert!(store.count_alerts(None, None).await.unwrap() >= 3);
+    let page = store.list_alerts(2, 0, None, None).await.unwrap();
+    assert_eq!(page.len(), 2);
+    assert!(page[0].created_at >= page[1].created_at);
+
+    // Ranges include `since` and exclude `until`
+    let mut old = alert(&Uuid::new_v4().to_string());
+    old.created_at -= chrono::Duration::days(400);
+    store.save_alert(old.clone()).await.unwrap();
+    let (since, until) = (old.created_at, old.created_at + chrono::Duration::seconds(1));
+    let range = store.list_alerts(1000, 0, Some(since), Some(until)).await.unwrap();
+    assert!(range.iter().any(|a| a.id == old.id));
+    assert!(range.iter().all(|a| a.created_at >= since && a.created_at < until));
+    assert!(store.count_alerts(Some(since), Some(until)).await.unwrap() >= 1);
+    assert!(!store.list_alerts(1000, 0, None, Some(since)).await.unwrap().iter().any(|a| a.id == old.id));
+    assert!(!store.list_alerts(1000, 0, Some(until), None).await.unwrap().iter().any(|a| a.id == old.id));
+}
+
+async fn check_workflows(store: &dyn Store) {
+    let workflow = workflow();
+    store.save_workflow(workflow.clone()).await.unwrap();
+    store.update_workflow_status(workflow.id, WorkflowStatus::Running).await.unwrap();
+    store.update_workflow_progress(workflow.id, 1, Some("investigate".to_string())).await.unwrap();
+    store.update_workflow_outputs(workflow.id, json!({ "gather": "ok" })).await.unwrap();
+
+    let stored = store.get_workflow(workflow.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, WorkflowStatus::Running);
+    assert_eq!(stored.steps_completed, 1);
+    assert_eq!(stored.current_step.as_deref(), Some("investigate"));
+    assert_eq!(stored.input_context, workflow.input_context);
+    assert_eq!(stored.outputs, Some(json!({ "gather": "ok" })));
+    assert_eq!(stored.started_at, workflow.started_at);
+
+    store.complete_workflow(workflow.id, WorkflowStatus::Failed, None, Some("timed out".to_string())).await.unwrap();
+    let stored = store.get_workflow(workflow.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, WorkflowStatus::Failed);
+    assert_eq!(stored.error.as_deref(), Some("timed out"));
+    assert!(stored.completed_at.is_some());
+    assert!(store.list_workflows(1000, 0, None, None).await.unwrap().iter().any(|w| w.id == workflow.id));
+    let failed = store.list_workflows_by_status(WorkflowStatus::Failed, 1000, 0, None, None).await.unwrap();
+    assert!(failed.iter().any(|w| w.id == workflow.id));
+    assert!(failed.iter().all(|w| w.status == WorkflowStatus::Failed));
+    assert!(store.count_workflows(Some(WorkflowStatus::Failed), None, None).await.unwrap() >= 1);
+    assert!(store.count_workflows(None, None, None).await.unwrap() >= store.count_workflows(Some(WorkflowStatus::Failed), None, None).await.unwrap());
+    let (since, until) = (workflow.created_at, workflow.created_at + chrono::Duration::seconds(1));
+    let range = store.list_workflows_by_status(WorkflowStatus::Failed, 1000, 0, Some(since), Some(until)).await.unwrap();
+    assert!(range.iter().any(|w| w.id == workflow.id));
+    assert!(range.iter().all(|w| w.created_at >= since && w.created_at < until));
+    assert!(store.count_workflows(Some(WorkflowStatus::Failed), Some(since), Some(until)).await.unwrap() >= 1);
+    assert!(!store.list_workflows(1000, 0, Some(until), None).await.unwrap().iter().any(|w| w.id == workflow.id));
+    assert_eq!(store.count_workflows(None, Some(until), Some(until)).await.unwrap(), 0);
+
+    let now = now();
+    let step = WorkflowStep {
+        id: Uuid::new_v4(),
+        workflow_id: workflow.id,
+        name: "gather".to_string(),
+        step_type: StepType::Cli,
+        status: StepStatus::Pending,
+        config: Some(json!({ "command": "kubectl get pods" })),
+        started_at: None,
+        completed_at: None,
+        result: None,
+        error: None,
+        created_at: now,
+    };
+    store.save_workflow_step(step.clone()).await.unwrap();
+    store.update_workflow_step_status(step.id, StepStatus::Running).await.unwrap();
+    let running = store.get_workflow_step(step.id).await.unwrap().unwrap();
+    assert_eq!(running.status, StepStatus::Running);
+    assert!(running.started_at.is_some());
+    store.update_workflow_step_status(step.id, StepStatus::Pending).await.unwrap();
+    assert_eq!(store.get_workflow_step(step.id).await.unwrap().unwrap().started_at, running.started_at);
+
+    store.complete_workflow_step(step.id, StepStatus::Succeeded, Some(json!({ "pods": 3 })), None).await.unwrap();
+    let steps = store.list_workflow_steps(workflow.id).await.unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].status, StepStatus::Succeeded);
+    assert_eq!(steps[0].config, step.config);
+    assert_eq!(steps[0].result, Some(json!({ "pods": 3 })));
+    assert!(steps[0].completed_at.is_some());
+
+    let output = SinkOutput {
+        id: Uuid::new_v4(),
+        workflow_id: workflow.id,
+        sink_name: "oncall".to_string(),
+        sink_type: SinkType::Slack,
+        payload: Some(json!({ "text": "done" })),
+        status: SinkStatus::Pending,
+        error: None,
+        sent_at: None,
+        created_at: now,
+    };
+    store.save_sink_output(output.clone()).await.unwrap();
+    // Saving again updates the delivery in place
+    store.save_sink_output(SinkOutput { error: Some("retrying".to_string()), ..output.clone() }).await.unwrap();
+    assert_eq!(store.get_sink_output(output.id).await.unwrap().unwrap().error.as_deref(), Some("retrying"));
+    store.update_sink_output_status(output.id, SinkStatus::Sent, None).await.unwrap();
+    let outputs = store.list_sink_outputs(workflow.id).await.unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].status, SinkStatus::Sent);
+    assert_eq!(outputs[0].error, None);
+    assert_eq!(outputs[0].payload, output.payload);
+    assert!(outputs[0].sent_at.is_some());
+}
+
+async fn check_source_events(store: &dyn Store) {
+    let source_name = format!("source-{}", Uuid::new_v4());
+    let older = SourceEvent {
+        id: Uuid::new_v4(),
+        source_name: source_name.clone(),
+        source_type: SourceType::Webhook,
+        event_data: json!({ "alerts": [] }),
+        workflow_triggered: Some("triage".to_string()),
+        received_at: now() - chrono::Duration::minutes(1),
+    };
+    let newer = SourceEvent { id: Uuid::new_v4(), received_at: now(), ..older.clone() };
+    store.save_source_event(older.clone()).await.unwrap();
+    store.save_source_event(newer.clone()).await.unwrap();
+
+    let stored = store.get_source_event(older.id).await.unwrap().unwrap();
+    assert_eq!(stored.source_type, SourceType::Webhook);
+    assert_eq!(stored.event_data, older.event_data);
+    assert_eq!(stored.received_at, older.received_at);
+    let events = store.list_source_events(&source_name, 10).await.unwrap();
+    assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![newer.id, older.id]);
+
+    // Each scheduled run gets one event, however many replicas fire it
+    let scheduled_at = now();
+    let run = SourceEvent {
+        id: Uuid::new_v4(),
+        source_type: SourceType::Schedule,
+        event_data: json!({ "cron": "*/5 * * * *" }),
+        ..older.clone()
+    };
+    assert!(store.save_scheduled_source_event(run.clone(), "default", scheduled_at).await.unwrap());
+    assert!(!store.save_scheduled_source_event(SourceEvent { id: Uuid::new_v4(), ..run.clone() }, "default", scheduled_at).await.unwrap());
+    let next_run = SourceEvent { id: Uuid::new_v4(), ..run.clone() };
+    assert!(store.save_scheduled_source_event(next_run, "default", scheduled_at + chrono::Duration::minutes(5)).await.unwrap());
+    // A same-named source in another namespace has runs of its own
+    let other_namespace = SourceEvent { id: Uuid::new_v4(), ..run.clone() };
+    assert!(store.save_scheduled_source_event(other_namespace.clone(), "monitoring", scheduled_at).await.unwrap());
+
+    // Deleting an event releases its run
+    store.delete_source_event(other_namespace.id).await.unwrap();
+    assert!(store.get_source_event(other_namespace.id).await.unwrap().is_none());
+    assert!(store.save_scheduled_source_event(SourceEvent { id: Uuid::new_v4(), ..run.clone() }, "monitoring", scheduled_at).await.unwrap());
+    let stored = store.get_source_event(run.id).await.unwrap().unwrap();
+    assert_eq!(stored.source_type, SourceType::Schedule);
+    assert_eq!(store.list_source_events(&source_name, 10).await.unwrap().len(), 5);
+}
+
+async fn check_custom_resources(store: &dyn Store) {
+    let namespace = format!("ns-{}", Uuid::new_v4());
+    let now = now();
+    let resource = CustomResource {
+        id: Uuid::new_v4(),
+        api_version: "punchingfist.io/v1alpha1".to_string(),
+        kind: "Sink".to_string(),
+        name: "oncall".to_string(),
+        namespace: namespace.clone(),
+        spec: json!({ "type": "slack" }),
+        status: None,
+        created_at: now,
+        updated_at: now,
+    };
+    store.save_custom_resource(resource.clone()).await.unwrap();
+
+    // Saving the same kind, namespace and name again updates the row
+    store.save_custom_resource(CustomResource {
+        id: Uuid::new_v4(),
+        spec: json!({ "type": "jira" }),
+        ..resource.clone()
+    }).await.unwrap();
+    let stored = store.get_custom_resource("Sink", &namespace, "oncall").await.unwrap().unwrap();
+    assert_eq!(stored.id, resource.id);
+    assert_eq!(stored.spec, json!({ "type": "jira" }));
+    assert_eq!(store.count_custom_resources("Sink", Some(&namespace)).await.unwrap(), 1);
+
+    store.update_custom_resource_status(resource.id, json!({ "ready": true })).await.unwrap();
+    let listed = store.list_custom_resources("Sink", Some(&namespace), false, 10, 0).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].status, Some(json!({ "ready": true })));
+    let everywhere = store.list_custom_resources("Sink", None, true, 1000, 0).await.unwrap();
+    assert!(everywhere.iter().any(|r| r.id == resource.id));
+    assert!(store.list_custom_resources("Sink", None, false, 10, 0).await.is_err());
+    assert!(store.count_custom_resources("Sink", None).await.unwrap() >= 1);
+
+    store.delete_custom_resource("Sink", &namespace, "oncall").await.unwrap();
+    assert!(store.get_custom_resource("Sink", &namespace, "oncall").await.unwrap().is_none());
+    assert_eq!(store.count_custom_resources("Sink", Some(&namespace)).await.unwrap(), 0);
+}
+
+async fn check_transactions(store: &dyn Store) {
+    // Nothing a failed transaction wrote is kept
+    let rolled_back = alert(&format!("fp-{}", Uuid::new_v4()));
+    let failed = store.transaction(async {
+        store.save_alert(rolled_back.clone()).await?;
+        store.save_workflow(workflow()).await?;
+        Err(punching_fist_operator::Error::Validation("rolled back".to_string()))
+    }.boxed()).await;
+    assert!(failed.is_err());
+    assert!(store.get_alert(rolled_back.id).await.unwrap().is_none());
+
+    // Operations with transactions of their own join the enclosing one
+    let fingerprint = format!("fp-{}", Uuid::new_v4());
+    let flow = workflow();
+    store.transaction(async {
+        store.deduplicate_alert(&fingerprint, alert(&fingerprint)).await?;
+        store.save_workflow(flow.clone()).await?;
+        store.bulk_cancel_workflows(&[flow.id], "superseded").await?;
+        Ok(())
+    }.boxed()).await.unwrap();
+    assert!(store.get_alert_by_fingerprint(&fingerprint).await.unwrap().is_some());
+    assert_eq!(store.get_workflow(flow.id).await.unwrap().unwrap().status, WorkflowStatus::Cancelled);
+}
+
+async fn check_audit(store: Arc<dyn Store>) {
+    let store = AuditingStore::new(store);
+    let fingerprint = format!("fp-{}", Uuid::new_v4());
+    let DeduplicationResult::New(created) = store.deduplicate_alert(&fingerprint, alert(&fingerprint)).await.unwrap() else {
+        panic!("expected a new alert");
+    };
+    store.update_alert_status(created.id, AlertStatus::Triaging).await.unwrap();
+
+    let entries = store.list_audit_entries(Some("alert"), Some(&created.id.to_string()), 10).await.unwrap();
+    assert_eq!(entries.iter().map(|e| e.operation.as_str()).collect::<Vec<_>>(), vec!["update_alert_status", "deduplicate_alert"]);
+    assert_eq!(entries[0].before.as_ref().unwrap()["status"], "received");
+    assert_eq!(entries[0].after.as_ref().unwrap()["status"], "triaging");
+
+    // A failed write records nothing
+    assert!(store.update_alert_timing(created.id, "no_such_column", now()).await.is_err());
+    assert_eq!(store.list_audit_entries(Some("alert"), Some(&created.id.to_string()), 10).await.unwrap().len(), 2);
+}
+
+async fn check_store(store: &dyn Store) {
+    store.init().await.unwrap();
+    check_alerts(store).await;
+    check_workflows(store).await;
+    check_source_events(store).await;
+    check_custom_resources(store).await;
+    check_transactions(store).await;
+}
+
+#[tokio::test]
+async fn test_sqlite_store() {
+    let store = SqliteStore::new("sqlite::memory:").await.unwrap().with_dedup_window(Some(DEDUP_WINDOW));
+    check_store(&store).await;
+    check_audit(Arc::new(store)).await;
+}
+
+#[tokio::test]
+async fn test_postgres_store() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping the PostgreSQL store test");
+        return;
+    };
+    let store = PostgresStore::new(&url).await.unwrap().with_dedup_window(Some(DEDUP_WINDOW));
+    check_store(&store).await;
+    check_audit(Arc::new(store)).await;
+}